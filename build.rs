@@ -0,0 +1,99 @@
+//! Bundles the OpenAPI spec under resources/ into the single JSON document served at
+//! GET /api/schema. Every `$ref` is inlined, as the spec is split across files whose component
+//! names clash.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+const SPEC_ROOT: &str = "resources/secure-signer.yaml";
+
+/// Parsed spec files by path, so each is read once however often it is referenced
+struct Files(HashMap<PathBuf, Value>);
+
+impl Files {
+    fn get(&mut self, path: &Path) -> &Value {
+        self.0.entry(path.to_path_buf()).or_insert_with(|| {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let yaml = std::fs::read_to_string(path)
+                .unwrap_or_else(|e| panic!("Couldn't read {}: {e}", path.display()));
+            serde_yaml::from_str(&yaml)
+                .unwrap_or_else(|e| panic!("Bad OpenAPI file {}: {e}", path.display()))
+        })
+    }
+}
+
+/// `dir/relative` with `.` and `..` resolved, so the same file is always the same key
+fn join(dir: &Path, relative: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+    for component in dir.join(relative).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                path.pop();
+            }
+            other => path.push(other),
+        }
+    }
+    path
+}
+
+/// Replaces every `$ref` in `value`, which was read from `file`, with what it points to
+fn inline(files: &mut Files, value: &Value, file: &Path, stack: &mut Vec<String>) -> Value {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                let (target, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+                let target = match target {
+                    "" => file.to_path_buf(),
+                    target => join(file.parent().unwrap(), target),
+                };
+                let key = format!("{}#{pointer}", target.display());
+                if stack.contains(&key) {
+                    panic!("Recursive $ref {key} cannot be inlined");
+                }
+                let mut resolved = files
+                    .get(&target)
+                    .pointer(pointer)
+                    .unwrap_or_else(|| panic!("Dangling $ref {reference} in {}", file.display()))
+                    .clone();
+                // Path files keep the parameters they share under their own `components`, which
+                // is not a field of the path item they are inlined as
+                if pointer.is_empty() {
+                    if let Some(path_item) = resolved.as_object_mut() {
+                        path_item.remove("components");
+                    }
+                }
+                stack.push(key);
+                let inlined = inline(files, &resolved, &target, stack);
+                stack.pop();
+                return inlined;
+            }
+            Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), inline(files, v, file, stack)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| inline(files, item, file, stack))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let mut files = Files(HashMap::new());
+    let root = Path::new(SPEC_ROOT);
+    let spec = files.get(root).clone();
+    let mut spec = inline(&mut files, &spec, root, &mut vec![]);
+    spec["info"]["version"] = Value::String(std::env::var("CARGO_PKG_VERSION").unwrap());
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("openapi.json");
+    std::fs::write(&out, serde_json::to_string(&spec).unwrap())
+        .unwrap_or_else(|e| panic!("Couldn't write {}: {e}", out.display()));
+}
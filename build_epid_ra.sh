@@ -124,6 +124,7 @@ secure_signer_build() {
     ar -x ${INSTALLDIR}/lib/libcurl.a
     ar -x ${INSTALLDIR}/lib/libcrypto.a
     ar -x ${INSTALLDIR}/lib/libssl.a
+    ar -x /opt/occlum/toolchains/dcap_lib/musl/libocclum_dcap.a
     ar -crs ${COMBINED_LIB} *.o
     rm *.o
     cp $THISDIR/build/${COMBINED_LIB} $INSTALLDIR/lib 
@@ -46,6 +46,8 @@ def update_occlum_json(enclave_path):
         c["resource_limits"]["kernel_space_heap_size"] = "512MB"
         c["process"]["default_heap_size"] = "512MB"
         c["env"]["default"] = ["OCCLUM=yes", "RUST_LOG=info"]
+        # IAS credentials are passed from the host so they are not measured into the enclave
+        c["env"]["untrusted"] = ["IAS_URL", "IAS_ACCESS_KEY", "IAS_SPID"]
         c["metadata"]["debuggable"] = False
     with open(f"{enclave_path}/Occlum.json", "w") as occlum_config_file:
         occlum_config_file.write(json.dumps(c))
@@ -57,7 +59,6 @@ def new_instance(enclave_name, enclave_path):
     os.chdir(enclave_path)
     subprocess.run(["occlum", "init", enclave_name])
     subprocess.run(["copy_bom", "-f", f"../conf/{build_type}-rust-config.yaml", "--root", "image", "--include-dir", "/opt/occlum/etc/template"])
-    subprocess.run(["cp", f"../conf/ra_config.json", "./image/etc/"])
     subprocess.run(["cp", "/etc/resolv.conf", "./image/etc"])
     subprocess.run(["cp", "/etc/hosts", "./image/etc"])
     update_occlum_json(enclave_path)
@@ -68,13 +69,13 @@ def run_enclave(binary_name, enclave_path, port, fork_version):
     if using_enclave:
         subprocess.run(["occlum", "run", f"/bin/{binary_name}", str(port), fork_version])
     else:
-        subprocess.run([cargo_bin, "run", "--bin", binary_name, str(port), fork_version])
+        subprocess.run([cargo_bin, "run", "--bin", binary_name, *build_flags, "--", str(port), fork_version])
 
 def unit_tests():
     if using_enclave:
         os.environ["OPENSSL_DIR"] = OPENSSL_DIR
     os.environ["SECURE_SIGNER_PORT"] = str(port)
-    subprocess.run([cargo_bin, "test", "--", "--test-threads", "1"])
+    subprocess.run([cargo_bin, "test", "-F", "mock-attestation", "--", "--test-threads", "1"])
 
 def read_fork_version(path):
     try:
@@ -130,7 +131,7 @@ def process_args():
         build_flags = ["--release", "-F", "sgx"]
     else:
         cargo_bin = "cargo" 
-        build_flags = ""
+        build_flags = ["-F", "mock-attestation"]
 
     # Binary type
     if args.secure_signer:
@@ -3,7 +3,7 @@ use puffersecuresigner::{eth2::eth_types::Version, strip_0x_prefix};
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
+    puffersecuresigner::enclave::shared::request_log::init_logging(Default::default());
 
     let port = std::env::args()
         .nth(1)
@@ -1,30 +1,797 @@
 extern crate puffersecuresigner;
-use puffersecuresigner::{eth2::eth_types::Version, strip_0x_prefix};
+use clap::{CommandFactory, Parser};
+use puffersecuresigner::{
+    config::{Config, SlashingDb},
+    eth2::eth_types::{Root, Version},
+    strip_0x_prefix,
+};
+
+/// Secure-Signer, a remote signer for Ethereum validators that keeps its keys in an SGX enclave
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The port to listen on, same as --port
+    #[arg(value_name = "PORT", conflicts_with = "port")]
+    port_arg: Option<u16>,
+
+    /// The genesis fork version, 0x-prefixed hex. Defaults to that of --network or
+    /// --fork-schedule, else mainnet's.
+    genesis_fork_version: Option<String>,
+
+    /// The genesis validators root, 0x-prefixed hex. Defaults to that of --network, else zero.
+    genesis_validators_root: Option<String>,
+
+    /// What to do with a validator registration whose gas limit differs from the pinned one:
+    /// reject or override
+    #[arg(default_value = "reject")]
+    gas_limit_policy: puffersecuresigner::enclave::shared::handlers::GasLimitPolicy,
+
+    /// A TOML file with the settings below, which flags given here override
+    #[arg(long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// Refuse --config files with keys Secure-Signer does not know, instead of warning
+    #[arg(long, requires = "config")]
+    strict_config: bool,
+
+    /// The port to listen on, 3031 by default
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// The address to listen on, localhost by default. Validator clients on other hosts need
+    /// e.g. 0.0.0.0 or ::
+    #[arg(long)]
+    address: Option<std::net::IpAddr>,
+
+    /// The directory keys and slashing protection histories are saved in, ./etc/ by default
+    #[arg(long, env = "SECURE_SIGNER_DATADIR")]
+    datadir: Option<std::path::PathBuf>,
+
+    /// The network to sign for: mainnet, goerli, sepolia, holesky, or custom with
+    /// --network-config
+    #[arg(long)]
+    network: Option<String>,
+
+    /// The consensus-layer config.yaml of a custom --network
+    #[arg(long)]
+    network_config: Option<String>,
+
+    /// A known network's fork schedule, or the path to a JSON fork schedule
+    #[arg(long)]
+    fork_schedule: Option<String>,
+
+    /// Refuse messages for epochs past the last known fork instead of signing them with its version
+    #[arg(long)]
+    strict_fork_schedule: bool,
+
+    /// Check aggregation slot selection proofs before signing aggregates
+    #[arg(long)]
+    verify_selection_proofs: bool,
+
+    /// How many epochs ahead of the clock a RANDAO reveal may be signed
+    #[arg(long)]
+    max_randao_lookahead: Option<u64>,
+
+    /// The chain's genesis time in seconds, to refuse messages for slots far in the future.
+    /// Defaults to that of --network.
+    #[arg(long)]
+    genesis_time: Option<u64>,
+
+    /// The slot duration in seconds. Defaults to that of --network, else 12.
+    #[arg(long)]
+    seconds_per_slot: Option<u64>,
+
+    /// How many slots ahead of the clock a message may be signed, 64 by default
+    #[arg(long)]
+    max_future_slots: Option<u64>,
+
+    /// Refuse blocks below this slot, for validators migrated without an EIP-3076 file
+    #[arg(long)]
+    min_slot: Option<u64>,
+
+    /// Refuse attestations targeting an epoch below this one
+    #[arg(long)]
+    min_epoch: Option<u64>,
+
+    /// How many epochs of slashing protection history to keep when pruning, 32 by default
+    #[arg(long)]
+    slashing_prune_window: Option<usize>,
+
+    /// Where slashing protection histories are saved: one JSON file per key by default, or one
+    /// SQLite database when built with the sqlite feature
+    #[arg(long, value_enum)]
+    slashing_db: Option<puffersecuresigner::config::SlashingDb>,
+
+    /// How log lines are written: text by default, json or pretty. The level is read from
+    /// RUST_LOG.
+    #[arg(long)]
+    log_format: Option<puffersecuresigner::enclave::shared::request_log::LogFormat>,
+
+    /// Serve Prometheus metrics on their own port instead of next to the API
+    #[arg(long)]
+    metrics_port: Option<u16>,
+
+    /// The address to serve metrics on, defaults to --address
+    #[arg(long)]
+    metrics_address: Option<std::net::IpAddr>,
+
+    /// The largest sign request body in bytes, 1 MiB by default
+    #[arg(long)]
+    max_sign_body_bytes: Option<usize>,
+
+    /// The largest keystore or slashing protection import body in bytes, 10 MiB by default
+    #[arg(long)]
+    max_import_body_bytes: Option<usize>,
+
+    /// How many seconds in-flight requests get to finish on SIGINT or SIGTERM, 10 by default
+    #[arg(long)]
+    shutdown_timeout: Option<u64>,
+
+    /// Serve a Swagger UI page browsing the OpenAPI spec at /api/docs. The page loads Swagger UI
+    /// from a CDN.
+    #[arg(long)]
+    swagger_ui: bool,
+
+    /// A PEM certificate chain, leaf first, to serve HTTPS with
+    #[arg(long)]
+    tls_cert: Option<String>,
+
+    /// The PEM private key of --tls-cert
+    #[arg(long)]
+    tls_key: Option<String>,
+
+    /// A PEM bundle of the CAs whose client certificates are accepted
+    #[arg(long)]
+    tls_client_ca: Option<String>,
+
+    /// Serve the API on a Unix domain socket only its owner may connect to, instead of a port
+    #[arg(long)]
+    unix_socket: Option<std::path::PathBuf>,
+
+    /// Read the API token key management routes require from this file, generating it on the
+    /// first start. SECURE_SIGNER_API_TOKEN takes precedence.
+    #[arg(long)]
+    api_token_file: Option<String>,
+
+    /// Which routes need the API token: admin by default, or all
+    #[arg(long)]
+    api_token_scope: Option<puffersecuresigner::enclave::shared::api_token::ApiTokenScope>,
+
+    /// Sign requests per second allowed for each BLS public key
+    #[arg(long)]
+    sign_rate_limit: Option<u64>,
+
+    /// Sign requests allowed at once for each BLS public key, defaults to --sign-rate-limit
+    #[arg(long)]
+    sign_rate_burst: Option<u64>,
+
+    /// Key management requests per second allowed, shared by every caller
+    #[arg(long)]
+    admin_rate_limit: Option<u64>,
+
+    /// Key management requests allowed at once, defaults to --admin-rate-limit
+    #[arg(long)]
+    admin_rate_burst: Option<u64>,
+
+    /// Comma separated browser origins allowed to call the API, * for any
+    #[arg(long)]
+    cors_origins: Option<String>,
+
+    /// Comma separated methods allowed cross origin, GET and HEAD by default
+    #[arg(long)]
+    cors_methods: Option<String>,
+
+    /// How many seconds browsers may cache the answer to a preflight request, 600 by default
+    #[arg(long)]
+    cors_max_age: Option<u64>,
+
+    /// Allow --cors-methods that change keys or sign
+    #[arg(long)]
+    cors_allow_mutating: bool,
+
+    /// Read the master password keys are encrypted with from stdin, unless
+    /// SECURE_SIGNER_PASSWORD is set
+    #[arg(long)]
+    password_prompt: bool,
+
+    /// The most keys of each kind that may be saved
+    #[arg(long)]
+    max_keys: Option<usize>,
+
+    /// How many signing keys stay in memory after their first use, 0 reads them every time
+    #[arg(long)]
+    max_cached_keys: Option<usize>,
+
+    /// Derive every new BLS key from the sealed master seed
+    #[arg(long)]
+    deterministic_keygen: bool,
+
+    /// The PCCS that DCAP collateral is fetched from
+    #[arg(long)]
+    pccs_url: Option<String>,
+
+    /// A JSON file with the IAS account for EPID attestation, else read from the [ias] table of
+    /// --config or the IAS_* env variables
+    #[arg(long)]
+    ias_config: Option<String>,
+
+    /// The measurement policy enclaves sending keys must satisfy
+    #[arg(long)]
+    measurement_policy: Option<String>,
+
+    /// How many seconds attestation evidence is reused, 0 generates it for every request
+    #[arg(long)]
+    evidence_cache_ttl: Option<u64>,
+
+    /// How many milliseconds a leader waits on each worker for its partial signature
+    #[arg(long)]
+    worker_timeout_ms: Option<u64>,
+
+    /// How many seconds a leader waits between heartbeats to its workers, 0 disables them
+    #[arg(long)]
+    heartbeat_interval_secs: Option<u64>,
+
+    /// After how many missed heartbeats in a row a leader stops sending a worker sign requests
+    #[arg(long)]
+    max_missed_heartbeats: Option<u32>,
+
+    /// How many seconds a leader and its workers trust each other's attestation before
+    /// handshaking again
+    #[arg(long)]
+    peer_attestation_ttl_secs: Option<u64>,
+
+    /// http(s) url to fetch the price of a pair from for /datafeed/v1/price, with a {pair}
+    /// placeholder
+    #[arg(long)]
+    datafeed_url: Option<String>,
+
+    /// The JSON pointer of the price in the datafeed source's answer
+    #[arg(long)]
+    datafeed_price_pointer: Option<String>,
+
+    /// The saved ETH key datafeed prices are signed with
+    #[arg(long)]
+    datafeed_eth_pk: Option<String>,
+}
+
+impl Args {
+    /// Overrides the settings of `config` with those given on the command line
+    fn apply(&self, config: &mut Config) {
+        fn set<T: Clone>(setting: &mut T, arg: &Option<T>) {
+            if let Some(value) = arg {
+                *setting = value.clone();
+            }
+        }
+        fn set_some<T: Clone>(setting: &mut Option<T>, arg: &Option<T>) {
+            if arg.is_some() {
+                *setting = arg.clone();
+            }
+        }
+        let list = |s: &str| -> Vec<String> {
+            s.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        set(&mut config.datadir, &self.datadir);
+        set(&mut config.log_format, &self.log_format);
+
+        // `secure-signer 3031` predates --port and keeps working
+        set(&mut config.server.port, &self.port.or(self.port_arg));
+        set(&mut config.server.address, &self.address);
+        set_some(&mut config.server.unix_socket, &self.unix_socket);
+        set(&mut config.server.shutdown_timeout, &self.shutdown_timeout);
+        set(
+            &mut config.server.max_sign_body_bytes,
+            &self.max_sign_body_bytes,
+        );
+        set(
+            &mut config.server.max_import_body_bytes,
+            &self.max_import_body_bytes,
+        );
+        config.server.swagger_ui |= self.swagger_ui;
+
+        set_some(&mut config.tls.cert, &self.tls_cert);
+        set_some(&mut config.tls.key, &self.tls_key);
+        set_some(&mut config.tls.client_ca, &self.tls_client_ca);
+
+        set_some(&mut config.network.name, &self.network);
+        set_some(&mut config.network.config, &self.network_config);
+        set_some(&mut config.network.fork_schedule, &self.fork_schedule);
+        config.network.strict_fork_schedule |= self.strict_fork_schedule;
+        config.network.verify_selection_proofs |= self.verify_selection_proofs;
+        set_some(
+            &mut config.network.max_randao_lookahead,
+            &self.max_randao_lookahead,
+        );
+        set_some(&mut config.network.genesis_time, &self.genesis_time);
+        set_some(&mut config.network.seconds_per_slot, &self.seconds_per_slot);
+        set(&mut config.network.max_future_slots, &self.max_future_slots);
+
+        set(&mut config.slashing.db, &self.slashing_db);
+        set(&mut config.slashing.min_slot, &self.min_slot);
+        set(&mut config.slashing.min_epoch, &self.min_epoch);
+        set(
+            &mut config.slashing.prune_window,
+            &self.slashing_prune_window,
+        );
+
+        set(&mut config.keys.max_keys, &self.max_keys);
+        set(&mut config.keys.max_cached_keys, &self.max_cached_keys);
+        config.keys.deterministic_keygen |= self.deterministic_keygen;
+
+        set_some(&mut config.api_token.file, &self.api_token_file);
+        set(&mut config.api_token.scope, &self.api_token_scope);
+
+        set_some(&mut config.rate_limits.sign, &self.sign_rate_limit);
+        set_some(&mut config.rate_limits.sign_burst, &self.sign_rate_burst);
+        set_some(&mut config.rate_limits.admin, &self.admin_rate_limit);
+        set_some(&mut config.rate_limits.admin_burst, &self.admin_rate_burst);
+
+        set(
+            &mut config.cors.origins,
+            &self.cors_origins.as_deref().map(list),
+        );
+        set(
+            &mut config.cors.methods,
+            &self.cors_methods.as_deref().map(list),
+        );
+        set(&mut config.cors.max_age, &self.cors_max_age);
+        config.cors.allow_mutating |= self.cors_allow_mutating;
+
+        set_some(&mut config.metrics.port, &self.metrics_port);
+        set_some(&mut config.metrics.address, &self.metrics_address);
+
+        set_some(&mut config.attestation.pccs_url, &self.pccs_url);
+        set_some(
+            &mut config.attestation.measurement_policy,
+            &self.measurement_policy,
+        );
+        set_some(
+            &mut config.attestation.evidence_cache_ttl,
+            &self.evidence_cache_ttl,
+        );
+
+        set(
+            &mut config.leader.worker_timeout_ms,
+            &self.worker_timeout_ms,
+        );
+        set(
+            &mut config.leader.heartbeat_interval_secs,
+            &self.heartbeat_interval_secs,
+        );
+        set(
+            &mut config.leader.max_missed_heartbeats,
+            &self.max_missed_heartbeats,
+        );
+        set(
+            &mut config.leader.peer_attestation_ttl_secs,
+            &self.peer_attestation_ttl_secs,
+        );
+
+        set_some(&mut config.datafeed.url, &self.datafeed_url);
+        set(
+            &mut config.datafeed.price_pointer,
+            &self.datafeed_price_pointer,
+        );
+        set_some(&mut config.datafeed.eth_pk, &self.datafeed_eth_pk);
+    }
+}
+
+/// Refuses settings that only make sense together with another one, wherever they were given
+fn check(config: &Config) {
+    let needs = |arg: &str, set: bool, other: &str, other_set: bool| {
+        if set && !other_set {
+            invalid(arg, format!("needs {other}"))
+        }
+    };
+    needs(
+        "--tls-cert",
+        config.tls.cert.is_some(),
+        "--tls-key",
+        config.tls.key.is_some(),
+    );
+    needs(
+        "--tls-key",
+        config.tls.key.is_some(),
+        "--tls-cert",
+        config.tls.cert.is_some(),
+    );
+    needs(
+        "--tls-client-ca",
+        config.tls.client_ca.is_some(),
+        "--tls-cert",
+        config.tls.cert.is_some(),
+    );
+    if config.server.unix_socket.is_some() && config.tls.cert.is_some() {
+        invalid("--unix-socket", "cannot be used with --tls-cert")
+    }
+    needs(
+        "--network-config",
+        config.network.config.is_some(),
+        "--network",
+        config.network.name.is_some(),
+    );
+    needs(
+        "--sign-rate-burst",
+        config.rate_limits.sign_burst.is_some(),
+        "--sign-rate-limit",
+        config.rate_limits.sign.is_some(),
+    );
+    needs(
+        "--admin-rate-burst",
+        config.rate_limits.admin_burst.is_some(),
+        "--admin-rate-limit",
+        config.rate_limits.admin.is_some(),
+    );
+    let cors = !config.cors.origins.is_empty();
+    needs(
+        "--cors-methods",
+        !config.cors.methods.is_empty(),
+        "--cors-origins",
+        cors,
+    );
+    needs(
+        "--cors-allow-mutating",
+        config.cors.allow_mutating,
+        "--cors-origins",
+        cors,
+    );
+    needs(
+        "--metrics-address",
+        config.metrics.address.is_some(),
+        "--metrics-port",
+        config.metrics.port.is_some(),
+    );
+    needs(
+        "--datafeed-url",
+        config.datafeed.url.is_some(),
+        "--datafeed-eth-pk",
+        config.datafeed.eth_pk.is_some(),
+    );
+    needs(
+        "--datafeed-eth-pk",
+        config.datafeed.eth_pk.is_some(),
+        "--datafeed-url",
+        config.datafeed.url.is_some(),
+    );
+}
+
+/// Exits like clap does for a malformed argument, for values only checked once they are loaded
+fn invalid(arg: &str, e: impl std::fmt::Display) -> ! {
+    Args::command()
+        .error(
+            clap::error::ErrorKind::ValueValidation,
+            format!("invalid value for {arg}: {e:#}"),
+        )
+        .exit()
+}
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
-
-    let port = std::env::args()
-        .nth(1)
-        .unwrap_or("3031".into())
-        .parse::<u16>()
-        .expect("BAD PORT");
-    let genesis_fork_version_str: String =
-        std::env::args().nth(2).unwrap_or("00000000".to_string());
+    let args = Args::parse();
+    // Settings come from the --config file, else their defaults, unless given on the command line
+    let (mut config, unknown_keys) = match &args.config {
+        Some(path) => {
+            Config::load(path, args.strict_config).unwrap_or_else(|e| invalid("--config", e))
+        }
+        None => (Config::default(), vec![]),
+    };
+    args.apply(&mut config);
+    check(&config);
+    puffersecuresigner::enclave::shared::request_log::init_logging(config.log_format);
+    for key in unknown_keys {
+        log::warn!("Ignoring unknown key {key} in --config");
+    }
+    puffersecuresigner::io::data_dir::set_data_dir(&config.datadir)
+        .expect("Failed to set the data directory");
+
+    let addr = std::net::SocketAddr::new(config.server.address, config.server.port);
+    // Prometheus metrics are served next to the API, unless given their own port, e.g. on an
+    // address only the monitoring network reaches
+    let metrics_addr = config.metrics.port.map(|port| {
+        std::net::SocketAddr::new(
+            config.metrics.address.unwrap_or(config.server.address),
+            port,
+        )
+    });
+    let max_sign_body_bytes = config.server.max_sign_body_bytes;
+    let max_import_body_bytes = config.server.max_import_body_bytes;
+    let shutdown_grace = std::time::Duration::from_secs(config.server.shutdown_timeout);
+    let swagger_ui = config.server.swagger_ui;
+    // Terminate TLS in the signer itself when given a PEM certificate chain and key, and only
+    // accept clients with a certificate from --tls-client-ca when it is set
+    let tls_config = match (&config.tls.cert, &config.tls.key) {
+        (Some(cert), Some(key)) => Some(
+            puffersecuresigner::enclave::shared::tls::load_tls_config(
+                cert,
+                key,
+                config.tls.client_ca.as_deref(),
+            )
+            .unwrap_or_else(|e| invalid("--tls-cert", e)),
+        ),
+        _ => None,
+    };
+    let unix_socket = config.server.unix_socket.clone();
+    // Key management routes need a bearer token from SECURE_SIGNER_API_TOKEN or a file, which
+    // is generated on first start
+    let api_token = match (
+        std::env::var("SECURE_SIGNER_API_TOKEN").ok(),
+        &config.api_token.file,
+    ) {
+        (Some(token), _) => Some(
+            puffersecuresigner::enclave::shared::api_token::ApiToken::new(
+                &token,
+                config.api_token.scope,
+            )
+            .unwrap_or_else(|e| invalid("SECURE_SIGNER_API_TOKEN", e)),
+        ),
+        (None, Some(path)) => Some(
+            puffersecuresigner::enclave::shared::api_token::ApiToken::load_or_generate(
+                path,
+                config.api_token.scope,
+            )
+            .unwrap_or_else(|e| invalid("--api-token-file", e)),
+        ),
+        (None, None) => None,
+    };
+    // Both are unlimited unless set, bursts default to the rate
+    let rate_limit = |name: &str, rate: Option<u64>, burst: Option<u64>| {
+        rate.map(|rate| {
+            puffersecuresigner::enclave::shared::rate_limit::RateLimit::new(rate, burst)
+                .unwrap_or_else(|e| invalid(name, e))
+        })
+    };
+    let rate_limits = puffersecuresigner::enclave::shared::rate_limit::RateLimits {
+        sign: rate_limit(
+            "--sign-rate-limit",
+            config.rate_limits.sign,
+            config.rate_limits.sign_burst,
+        )
+        .map(|limit| {
+            puffersecuresigner::enclave::shared::rate_limit::RateLimiter::new("sign", limit)
+        }),
+        admin: rate_limit(
+            "--admin-rate-limit",
+            config.rate_limits.admin,
+            config.rate_limits.admin_burst,
+        )
+        .map(|limit| {
+            puffersecuresigner::enclave::shared::rate_limit::RateLimiter::new("admin", limit)
+        }),
+    };
+    let cors = (!config.cors.origins.is_empty()).then(|| {
+        puffersecuresigner::enclave::shared::cors::CorsConfig::parse(
+            &config.cors.origins.join(","),
+            (!config.cors.methods.is_empty())
+                .then(|| config.cors.methods.join(","))
+                .as_deref(),
+            config.cors.max_age,
+            config.cors.allow_mutating,
+        )
+        .unwrap_or_else(|e| invalid("--cors-origins", e))
+    });
+    // Presets for known networks, or a consensus-layer config.yaml for `custom`
+    let network = config.network.name.as_ref().map(|name| {
+        puffersecuresigner::eth2::eth_types::NetworkPreset::load(
+            name,
+            config.network.config.as_deref(),
+        )
+        .unwrap_or_else(|e| invalid("--network", e))
+    });
+    // Known networks can be named, any other network is read from a JSON file
+    let fork_schedule = config
+        .network
+        .fork_schedule
+        .as_ref()
+        .map(|spec| {
+            puffersecuresigner::eth2::eth_types::ForkSchedule::load(spec)
+                .unwrap_or_else(|e| invalid("--fork-schedule", e))
+        })
+        .or(network.as_ref().map(|n| n.fork_schedule.clone()));
+    let strict_fork_schedule = config.network.strict_fork_schedule;
+    let verify_selection_proofs = config.network.verify_selection_proofs;
+    let max_randao_lookahead = config.network.max_randao_lookahead;
+    let genesis_fork_version_str: String = args.genesis_fork_version.clone().unwrap_or(
+        fork_schedule
+            .as_ref()
+            .map(|s| hex::encode(s.genesis_fork_version()))
+            .unwrap_or("00000000".to_string()),
+    );
     let genesis_fork_version_str: String = strip_0x_prefix!(genesis_fork_version_str);
-    let mut genesis_fork_version = Version::default();
-    genesis_fork_version.copy_from_slice(
-        &hex::decode(&genesis_fork_version_str).expect("Bad genesis_fork_version"),
+    let genesis_fork_version: Version = hex::decode(&genesis_fork_version_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .unwrap_or_else(|| invalid("GENESIS_FORK_VERSION", "expected 4 bytes of hex"));
+    if let Some(schedule) = &fork_schedule {
+        if schedule.genesis_fork_version() != genesis_fork_version {
+            invalid(
+                "GENESIS_FORK_VERSION",
+                "contradicts the fork schedule of --fork-schedule or --network",
+            )
+        }
+    }
+    let genesis_validators_root_str: String =
+        args.genesis_validators_root.clone().unwrap_or(hex::encode(
+            network
+                .as_ref()
+                .map(|n| n.genesis_validators_root)
+                .unwrap_or_default(),
+        ));
+    let genesis_validators_root_str: String = strip_0x_prefix!(genesis_validators_root_str);
+    let genesis_validators_root: Root = hex::decode(&genesis_validators_root_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .unwrap_or_else(|| invalid("GENESIS_VALIDATORS_ROOT", "expected 32 bytes of hex"));
+    let gas_limit_policy = args.gas_limit_policy;
+    // Safety floor for validators migrated without an EIP-3076 interchange file
+    let slashing_floor = puffersecuresigner::eth2::slash_protection::SlashingFloor {
+        min_slot: config.slashing.min_slot,
+        min_epoch: config.slashing.min_epoch,
+    };
+    let slashing_prune_window = config.slashing.prune_window;
+    // Only checked when the chain's genesis time is known, testnets with odd timing can omit it
+    let slot_clock = config
+        .network
+        .genesis_time
+        .or(network.as_ref().map(|n| n.genesis_time))
+        .map(
+            |genesis_time| puffersecuresigner::eth2::slash_protection::SlotClock {
+                genesis_time,
+                seconds_per_slot: config
+                    .network
+                    .seconds_per_slot
+                    .or(network.as_ref().map(|n| n.seconds_per_slot))
+                    .unwrap_or(12),
+                max_future_slots: config.network.max_future_slots,
+            },
+        );
+    // Encrypt keys at rest with a master password instead of the enclave's sealing key
+    let password = std::env::var("SECURE_SIGNER_PASSWORD").ok().or_else(|| {
+        args.password_prompt
+            .then(|| puffersecuresigner::io::key_vault::prompt_password().expect("Bad password"))
+    });
+    if let Some(password) = password {
+        let resealed = puffersecuresigner::io::key_vault::use_password(&password)
+            .expect("Failed to unlock the key vault");
+        println!("Unlocked the key vault, resealed {resealed} keys");
+    }
+    puffersecuresigner::io::key_management::set_max_keys(config.keys.max_keys)
+        .expect("Failed to set the key quota");
+    puffersecuresigner::crypto::bls_keys::set_max_cached_keys(config.keys.max_cached_keys)
+        .expect("Failed to set the key cache size");
+    // Derive every new BLS key from the sealed master seed, so restoring the seed restores them
+    let deterministic_keygen = config.keys.deterministic_keygen;
+    if deterministic_keygen {
+        puffersecuresigner::crypto::bls_keys::init_master_seed()
+            .expect("Failed to initialize the master seed");
+    }
+    if let Some(pccs_url) = &config.attestation.pccs_url {
+        puffersecuresigner::io::remote_attestation::set_pccs_url(pccs_url)
+            .unwrap_or_else(|e| invalid("--pccs-url", e));
+    }
+    // EPID attestation needs an IAS account, read from a JSON file, the [ias] table of --config
+    // or the IAS_* env variables
+    let ias_config = match (&args.ias_config, &config.ias) {
+        (Some(path), _) => Some(
+            puffersecuresigner::io::remote_attestation::IasConfig::from_file(path)
+                .unwrap_or_else(|e| invalid("--ias-config", e)),
+        ),
+        (None, Some(ias_config)) => Some(ias_config.clone()),
+        (None, None) => puffersecuresigner::io::remote_attestation::IasConfig::from_env()
+            .expect("Failed to load the IAS config"),
+    };
+    match ias_config {
+        Some(ias_config) => puffersecuresigner::io::remote_attestation::set_ias_config(ias_config)
+            .expect("Failed to set the IAS config"),
+        None => println!("No IAS config, EPID attestation is disabled"),
+    }
+    // Keystores imported with evidence must come from an enclave build this policy allows
+    if let Some(path) = &config.attestation.measurement_policy {
+        puffersecuresigner::io::remote_attestation::set_measurement_policy_path(path)
+            .unwrap_or_else(|e| invalid("--measurement-policy", e));
+    }
+    if let Some(ttl) = config.attestation.evidence_cache_ttl {
+        puffersecuresigner::io::remote_attestation::set_evidence_cache_ttl(ttl)
+            .expect("Failed to set the evidence cache TTL");
+    }
+    let leader = puffersecuresigner::enclave::secure_signer::leader::LeaderState::new(
+        config.leader.worker_timeout_ms,
+        config.leader.max_missed_heartbeats,
     );
+    let worker = puffersecuresigner::enclave::secure_signer::worker::WorkerState::default();
+    puffersecuresigner::enclave::secure_signer::handshake::set_peer_attestation_ttl_secs(
+        config.leader.peer_attestation_ttl_secs,
+    )
+    .expect("Failed to set the peer attestation TTL");
+    if let (Some(url), Some(eth_pk)) = (&config.datafeed.url, &config.datafeed.eth_pk) {
+        let source = puffersecuresigner::enclave::secure_signer::datafeed::DatafeedSource::new(
+            url,
+            &config.datafeed.price_pointer,
+            eth_pk,
+        )
+        .unwrap_or_else(|e| invalid("--datafeed-url", e));
+        puffersecuresigner::enclave::secure_signer::datafeed::set_datafeed_source(source)
+            .expect("Failed to set the datafeed source");
+    }
+    // Leaders poll their registered workers, so sign requests skip those that stopped answering
+    if config.leader.heartbeat_interval_secs > 0 {
+        tokio::spawn(
+            leader
+                .clone()
+                .run_heartbeats(std::time::Duration::from_secs(
+                    config.leader.heartbeat_interval_secs,
+                )),
+        );
+    }
+    let slashing_backend: std::sync::Arc<
+        dyn puffersecuresigner::eth2::slash_protection::SlashingProtectionBackend,
+    > = match config.slashing.db {
+        SlashingDb::File => {
+            std::sync::Arc::new(puffersecuresigner::eth2::slash_protection::FileBackend)
+        }
+        #[cfg(feature = "sqlite")]
+        SlashingDb::Sqlite => std::sync::Arc::new(
+            puffersecuresigner::eth2::slash_protection_sqlite::SqliteBackend::open(
+                &puffersecuresigner::io::data_dir::slashing_protection_sqlite_path(),
+            )
+            .expect("Failed to open slashing protection db"),
+        ),
+        // Neither clap nor the config file accept sqlite without the feature
+        #[cfg(not(feature = "sqlite"))]
+        SlashingDb::Sqlite => unreachable!("Built without the sqlite feature"),
+    };
+
+    // Every history is read once up front, a bad one is reported by /healthcheck while its key
+    // keeps refusing to sign
+    let slashing_protection_intact =
+        match puffersecuresigner::eth2::slash_protection::check_integrity(slashing_backend.as_ref())
+        {
+            Ok(n) => {
+                println!("Checked {n} slashing protection histories");
+                true
+            }
+            Err(e) => {
+                eprintln!("Slashing protection integrity check failed: {e:#}");
+                false
+            }
+        };
 
     println!(
-        "Starting SGX Secure-Signer: localhost:{}, using genesis_fork_version: {:?}",
-        port, genesis_fork_version
+        "Starting SGX Secure-Signer: {}, using genesis_fork_version: {:?}, saving to {}",
+        match &unix_socket {
+            Some(path) => format!("unix:{}", path.display()),
+            None if tls_config.is_some() => format!("https://{addr}"),
+            None => addr.to_string(),
+        },
+        genesis_fork_version,
+        puffersecuresigner::io::data_dir::data_dir().display()
     );
 
     let app_state = puffersecuresigner::enclave::shared::handlers::AppState {
         genesis_fork_version,
+        genesis_validators_root,
+        network: network.map(|n| n.name),
+        gas_limit_policy,
+        slashing_floor,
+        slashing_prune_window,
+        slot_clock,
+        fork_schedule,
+        strict_fork_schedule,
+        verify_selection_proofs,
+        max_randao_lookahead,
+        sign_locks: Default::default(),
+        signature_counts: Default::default(),
+        randao_reveal_epochs: Default::default(),
+        validator_configs: Default::default(),
+        slashing_backend,
+        slashing_protection_intact,
+        data_dir: puffersecuresigner::io::data_dir::data_dir(),
+        config: std::sync::Arc::new(config),
+        leader,
+        worker: worker.clone(),
     };
 
     let app = axum::Router::new()
@@ -33,6 +800,16 @@ async fn main() {
             "/upcheck",
             axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
         )
+        // Endpoint to serve the OpenAPI spec of every route
+        .route(
+            "/api/schema",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::openapi::handler),
+        )
+        // Endpoint to report the network Secure-Signer signs for
+        .route(
+            "/healthcheck",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::healthcheck::handler),
+        )
         // Endpoint to securely generate and save an ETH sk
         .route(
             "/eth/v1/keygen/secp256k1",
@@ -43,8 +820,167 @@ async fn main() {
         // Endpoint to securely generate and save a BLS sk
         .route(
             "/eth/v1/keygen/bls",
+            if deterministic_keygen {
+                axum::routing::post(
+                    puffersecuresigner::enclave::secure_signer::handlers::deterministic_bls_keygen::handler,
+                )
+            } else {
+                axum::routing::post(
+                    puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+                )
+            },
+        )
+        // Endpoint to derive and save the BLS key at the next index of the master seed
+        .route(
+            "/eth/v1/keygen/bls/deterministic",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::deterministic_bls_keygen::handler,
+            ),
+        )
+        // Endpoint to derive and save the EIP-2334 signing key of a validator index
+        .route(
+            "/eth/v1/keygen/bls/derive",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::derive_bls_key::handler,
+            ),
+        )
+        // Endpoint to generate the mnemonic that BLS keys are derived from
+        .route(
+            "/eth/v1/keygen/bls/mnemonic",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::mnemonic_keygen::handler,
+            ),
+        )
+        // Endpoint to recover derived BLS keys from an encrypted mnemonic
+        .route(
+            "/eth/v1/keygen/bls/recover",
             axum::routing::post(
-                puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+                puffersecuresigner::enclave::secure_signer::handlers::recover_bls_keys::handler,
+            ),
+        )
+        // Endpoint to attest every key held by the enclave with one quote
+        .route(
+            "/eth/v1/remote-attestation/all",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::attest_key_set::handler,
+            ),
+        )
+        // Endpoint to attest a saved BLS or ETH key with an EPID or DCAP quote
+        .route(
+            "/eth/v1/remote-attestation/:kind",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::remote_attestation::handler,
+            ),
+        )
+        // Endpoint to verify attestation evidence from another enclave
+        .route(
+            "/eth/v1/verify-attestation",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::verify_attestation::handler,
+            ),
+        )
+        // Endpoint for a leader to register attested worker enclaves and list them
+        .route(
+            "/leader/v1/workers",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::list_workers::handler,
+            )
+            .post(puffersecuresigner::enclave::secure_signer::handlers::register_worker::handler),
+        )
+        // Endpoint for a leader to poll this worker's uptime and key shares
+        .route(
+            "/worker/v1/status",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_status::handler,
+            ),
+        )
+        // Endpoint for a leader to check this worker's watermarks for a key share
+        .route(
+            "/worker/v1/watermark/:pk_share_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_watermark::handler,
+            ),
+        )
+        // Endpoints for a leader to attest to this worker, and this worker to it
+        .route(
+            "/worker/v1/handshake/challenge",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_handshake_challenge::handler,
+            ),
+        )
+        .route(
+            "/worker/v1/handshake",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_handshake::handler,
+            ),
+        )
+        // Endpoint for a leader to deliver a key share to this worker
+        .route(
+            "/worker/v1/keyshare",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_keyshare::handler,
+            ),
+        )
+        // Endpoints for a leader to re-share a key across this worker
+        .route(
+            "/worker/v1/reshare/subshares",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_reshare_subshares::handler,
+            ),
+        )
+        .route(
+            "/worker/v1/reshare/combine",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_reshare_combine::handler,
+            ),
+        )
+        .route(
+            "/worker/v1/reshare/revoke",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_reshare_revoke::handler,
+            ),
+        )
+        // Endpoint for a leader to list its split keys and whether they are signable
+        .route(
+            "/leader/v1/keys",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::list_threshold_keys::handler,
+            ),
+        )
+        // Endpoint for a leader to show which worker holds each share of a split key
+        .route(
+            "/leader/v1/keys/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::get_threshold_key::handler,
+            ),
+        )
+        // Endpoint for a leader to deliver the shares of a split key to its workers
+        .route(
+            "/leader/v1/keys/:bls_pk_hex/distribute",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::distribute_key_shares::handler,
+            ),
+        )
+        // Endpoint for a leader to move a split key to new workers without reconstructing it
+        .route(
+            "/leader/v1/keys/:bls_pk_hex/reshare",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::reshare_key_shares::handler,
+            ),
+        )
+        // Endpoint for a leader to sign with a key split across its workers
+        .route(
+            "/leader/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::leader_sign::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(max_sign_body_bytes)),
+        )
+        // Endpoint to reread the measurement policy of enclaves allowed to send keys
+        .route(
+            "/eth/v1/measurement-policy/reload",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::reload_measurement_policy::handler,
             ),
         )
         // Endpoint to list the pks of all the generated ETH keys
@@ -54,6 +990,20 @@ async fn main() {
                 puffersecuresigner::enclave::shared::handlers::list_eth_keys::handler,
             ),
         )
+        // Endpoint to look up a generated ETH key by its address
+        .route(
+            "/eth/v1/keygen/eth/:address",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::get_eth_key_by_address::handler,
+            ),
+        )
+        // Endpoint to delete generated ETH keys from the enclave
+        .route(
+            "/eth/v1/keygen/secp256k1",
+            axum::routing::delete(
+                puffersecuresigner::enclave::secure_signer::handlers::delete_eth_keys::handler,
+            ),
+        )
         // Endpoint to list all pks of saved bls keys in the enclave
         .route(
             "/eth/v1/keystores",
@@ -61,6 +1011,90 @@ async fn main() {
                 puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
             ),
         )
+        // Endpoint to import EIP-2335 keystores into the enclave
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::import_bls_keystores::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(max_import_body_bytes)),
+        )
+        // Endpoint to export saved bls keys encrypted to another enclave
+        .route(
+            "/eth/v1/keystores/export",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::export_bls_keystores::handler,
+            ),
+        )
+        // Endpoint to split a saved bls key into threshold shares for worker enclaves
+        .route(
+            "/eth/v1/keystores/split",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::split_bls_key::handler,
+            ),
+        )
+        // Endpoint to relabel a saved bls or eth key
+        .route(
+            "/eth/v1/keystores/:pubkey",
+            axum::routing::patch(
+                puffersecuresigner::enclave::secure_signer::handlers::update_key_label::handler,
+            ),
+        )
+        // Endpoint to delete saved bls keys from the enclave
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::delete(
+                puffersecuresigner::enclave::shared::handlers::delete_bls_keys::handler,
+            ),
+        )
+        // Endpoint to list the keys served by worker signers
+        .route(
+            "/eth/v1/remotekeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::list_remote_keys::handler,
+            ),
+        )
+        // Endpoint to register keys served by worker signers
+        .route(
+            "/eth/v1/remotekeys",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::import_remote_keys::handler,
+            ),
+        )
+        // Endpoint to unregister keys served by worker signers
+        .route(
+            "/eth/v1/remotekeys",
+            axum::routing::delete(
+                puffersecuresigner::enclave::secure_signer::handlers::delete_remote_keys::handler,
+            ),
+        )
+        // Endpoint to manage the fee recipient pinned for a bls key
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/feerecipient",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_fee_recipient::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_fee_recipient::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_fee_recipient::handler),
+        )
+        // Endpoint to manage the gas limit pinned for a bls key
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/gas_limit",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_gas_limit::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_gas_limit::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_gas_limit::handler),
+        )
+        // Endpoint to manage the graffiti pinned for a bls key
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/graffiti",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_graffiti::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_graffiti::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_graffiti::handler),
+        )
         // Endpoint to sign DepositData message for registering validator on beacon chain
         .route(
             "/api/v1/eth2/deposit",
@@ -68,18 +1102,189 @@ async fn main() {
                 puffersecuresigner::enclave::secure_signer::handlers::validator_deposit::handler,
             ),
         )
+        // Endpoint to export the EIP-3076 slashing protection history of saved bls keys
+        .route(
+            "/eth/v1/slashing-protection",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::export_slashing_protection::handler,
+            ),
+        )
+        // Endpoint to seed the slashing protection history from an EIP-3076 interchange file
+        .route(
+            "/eth/v1/slashing-protection",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::import_slashing_protection::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(max_import_body_bytes)),
+        )
+        // Endpoint to report the slashing protection watermarks of every saved bls key
+        .route(
+            "/eth/v1/slashing-protection-status",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_slashing_protection_status::handler,
+            ),
+        )
+        // Endpoint to report the slashing protection watermarks of a bls key
+        .route(
+            "/eth/v1/slashing-protection/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_slashing_protection_status::handler,
+            ),
+        )
+        // Endpoint to drop slashing protection history that is not needed for safety
+        .route(
+            "/admin/prune-slashing-protection",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::prune_slashing_protection::handler,
+            ),
+        )
+        // Endpoint to list all pks of saved bls keys in the Web3Signer format
+        .route(
+            "/api/v1/eth2/publicKeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_public_keys::handler,
+            ),
+        )
         // Endpoint to request a signature using BLS sk
         .route(
             "/api/v1/eth2/sign/:bls_pk_hex",
             axum::routing::post(
                 puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(max_sign_body_bytes)),
+        )
+        // Endpoint to aggregate bls signatures over the same message
+        .route(
+            "/eth/v1/aggregate",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::aggregate_bls_signatures::handler,
+            ),
+        )
+        // Endpoint to check a bls signature over a message
+        .route(
+            "/eth/v1/verify/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::verify_bls_signature::handler,
+            ),
+        )
+        // Endpoint to request a signature over a digest using a generated ETH sk
+        .route(
+            "/eth/v1/sign/secp256k1/:eth_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::sign_secp256k1::handler,
+            ),
+        )
+        // Endpoint to fetch a price signed by the datafeed key
+        .route(
+            "/datafeed/v1/price/:pair",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::datafeed_price::handler,
             ),
         )
+        // Bound every request body before it is buffered and deserialized, sign and import
+        // requests are bounded by their own limits above
+        .layer(axum::extract::DefaultBodyLimit::max(
+            puffersecuresigner::constants::MAX_REQUEST_BODY_BYTES,
+        ))
+        // Only JSON bodies are ever parsed
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::content_type::require_json,
+        ))
+        // Answer every error, including axum's rejections, with a JSON ErrorResponse
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::errors::json_errors,
+        ))
         .with_state(app_state);
+    // Endpoint to browse the OpenAPI spec, which pulls Swagger UI from a CDN
+    let app = if swagger_ui {
+        app.route(
+            "/api/docs",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::openapi::swagger_ui),
+        )
+    } else {
+        app
+    };
+    let metrics = axum::Router::new().route(
+        "/metrics",
+        axum::routing::get(puffersecuresigner::enclave::shared::handlers::metrics::handler),
+    );
+    let app = match metrics_addr {
+        Some(metrics_addr) => {
+            let listener = std::net::TcpListener::bind(metrics_addr).unwrap_or_else(|e| {
+                eprintln!("Failed to listen on {metrics_addr}: {e}");
+                std::process::exit(1)
+            });
+            let server = axum::Server::from_tcp(listener)
+                .expect("Failed to serve metrics")
+                .serve(metrics.into_make_service());
+            tokio::spawn(async move {
+                if let Err(e) = server.await {
+                    eprintln!("Metrics server failed: {e}");
+                }
+            });
+            println!("Serving metrics on {metrics_addr}");
+            app
+        }
+        // Endpoint to report Prometheus metrics
+        None => app.merge(metrics),
+    };
+    // Inside the API token check, so callers without the token cannot use up the admin limit
+    let app = app.layer(axum::middleware::from_fn_with_state(
+        rate_limits,
+        puffersecuresigner::enclave::shared::rate_limit::rate_limit,
+    ));
+    let app = match api_token {
+        Some(api_token) => app.layer(axum::middleware::from_fn_with_state(
+            api_token,
+            puffersecuresigner::enclave::shared::api_token::require_api_token,
+        )),
+        None => {
+            println!("No API token, key management routes are open to every caller");
+            app
+        }
+    };
+    // Outside the API token check, as browsers send preflight requests without the token
+    let app = match cors {
+        Some(cors) => app.layer(axum::middleware::from_fn_with_state(
+            cors,
+            puffersecuresigner::enclave::shared::cors::cors,
+        )),
+        None => app,
+    };
+    // Outermost, so requests refused for their API token are logged too
+    let app = app.layer(axum::middleware::from_fn(
+        puffersecuresigner::enclave::shared::request_log::log_requests,
+    ));
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    worker.start_uptime_clock();
 
-    _ = axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await;
+    let served = match unix_socket {
+        Some(path) => {
+            puffersecuresigner::enclave::shared::unix_socket::serve_unix_until(
+                &path,
+                app,
+                puffersecuresigner::enclave::shared::shutdown::shutdown_signal(),
+                shutdown_grace,
+            )
+            .await
+        }
+        None => {
+            let listener = std::net::TcpListener::bind(addr).unwrap_or_else(|e| {
+                eprintln!("Failed to listen on {addr}: {e}");
+                std::process::exit(1)
+            });
+            puffersecuresigner::enclave::shared::shutdown::serve_until(
+                listener,
+                app,
+                tls_config,
+                puffersecuresigner::enclave::shared::shutdown::shutdown_signal(),
+                shutdown_grace,
+            )
+            .await
+        }
+    };
+    if let Err(e) = served {
+        eprintln!("{e:#}");
+        std::process::exit(1)
+    }
 }
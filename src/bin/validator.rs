@@ -1,22 +1,166 @@
 extern crate puffersecuresigner;
-use puffersecuresigner::{eth2::eth_types::Version, strip_0x_prefix};
+use puffersecuresigner::{
+    eth2::eth_types::{Root, Version},
+    strip_0x_prefix,
+};
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
+    puffersecuresigner::enclave::shared::request_log::init_logging(Default::default());
 
-    let port = std::env::args()
-        .nth(1)
+    // Positional args come first, `--name=value` options may follow
+    let (options, args): (Vec<String>, Vec<String>) =
+        std::env::args().partition(|arg| arg.starts_with("--"));
+    let option_str = |name: &str| -> Option<String> {
+        options
+            .iter()
+            .find_map(|opt| opt.strip_prefix(&format!("--{name}=")).map(String::from))
+    };
+    let option = |name: &str| -> Option<u64> {
+        option_str(name).map(|v| v.parse().unwrap_or_else(|_| panic!("Bad --{name}")))
+    };
+    // Keys and slashing protection histories are saved under --datadir or SECURE_SIGNER_DATADIR,
+    // ./etc relative to the working directory if neither is given
+    if let Some(dir) = option_str("datadir").or_else(|| std::env::var("SECURE_SIGNER_DATADIR").ok())
+    {
+        puffersecuresigner::io::data_dir::set_data_dir(dir)
+            .expect("Failed to set the data directory");
+    }
+
+    let port = args
+        .get(1)
+        .cloned()
         .unwrap_or("3031".into())
         .parse::<u16>()
         .expect("BAD PORT");
-    let genesis_fork_version_str: String =
-        std::env::args().nth(2).unwrap_or("00000000".to_string());
+    // Presets for known networks, or a consensus-layer config.yaml for `custom`
+    let network = option_str("network").map(|name| {
+        puffersecuresigner::eth2::eth_types::NetworkPreset::load(
+            &name,
+            option_str("network-config").as_deref(),
+        )
+        .expect("Bad --network")
+    });
+    // Known networks can be named, any other network is read from a JSON file
+    let fork_schedule = option_str("fork-schedule")
+        .map(|spec| {
+            puffersecuresigner::eth2::eth_types::ForkSchedule::load(&spec)
+                .expect("Bad --fork-schedule")
+        })
+        .or(network.as_ref().map(|n| n.fork_schedule.clone()));
+    let strict_fork_schedule = options.iter().any(|opt| opt == "--strict-fork-schedule");
+    let verify_selection_proofs = options.iter().any(|opt| opt == "--verify-selection-proofs");
+    let max_randao_lookahead = option("max-randao-lookahead");
+    let genesis_fork_version_str: String = args.get(2).cloned().unwrap_or(
+        fork_schedule
+            .as_ref()
+            .map(|s| hex::encode(s.genesis_fork_version()))
+            .unwrap_or("00000000".to_string()),
+    );
     let genesis_fork_version_str: String = strip_0x_prefix!(genesis_fork_version_str);
     let mut genesis_fork_version = Version::default();
     genesis_fork_version.copy_from_slice(
         &hex::decode(&genesis_fork_version_str).expect("Bad genesis_fork_version"),
     );
+    if let Some(schedule) = &fork_schedule {
+        assert_eq!(
+            schedule.genesis_fork_version(),
+            genesis_fork_version,
+            "genesis_fork_version contradicts --fork-schedule"
+        );
+    }
+    let genesis_validators_root_str: String = args.get(3).cloned().unwrap_or(hex::encode(
+        network
+            .as_ref()
+            .map(|n| n.genesis_validators_root)
+            .unwrap_or_default(),
+    ));
+    let genesis_validators_root_str: String = strip_0x_prefix!(genesis_validators_root_str);
+    let mut genesis_validators_root = Root::default();
+    genesis_validators_root.copy_from_slice(
+        &hex::decode(&genesis_validators_root_str).expect("Bad genesis_validators_root"),
+    );
+    let gas_limit_policy: puffersecuresigner::enclave::shared::handlers::GasLimitPolicy = args
+        .get(4)
+        .cloned()
+        .unwrap_or("reject".to_string())
+        .parse()
+        .expect("Bad gas_limit_policy");
+    // Safety floor for validators migrated without an EIP-3076 interchange file
+    let slashing_floor = puffersecuresigner::eth2::slash_protection::SlashingFloor {
+        min_slot: option("min-slot").unwrap_or(0),
+        min_epoch: option("min-epoch").unwrap_or(0),
+    };
+    let slashing_prune_window = option("slashing-prune-window")
+        .map(|w| w as usize)
+        .unwrap_or(puffersecuresigner::constants::DEFAULT_SLASHING_PRUNE_WINDOW);
+    // Only checked when the chain's genesis time is known, testnets with odd timing can omit it
+    let slot_clock = option("genesis-time")
+        .or(network.as_ref().map(|n| n.genesis_time))
+        .map(
+            |genesis_time| puffersecuresigner::eth2::slash_protection::SlotClock {
+                genesis_time,
+                seconds_per_slot: option("seconds-per-slot")
+                    .or(network.as_ref().map(|n| n.seconds_per_slot))
+                    .unwrap_or(12),
+                max_future_slots: option("max-future-slots")
+                    .unwrap_or(puffersecuresigner::constants::DEFAULT_MAX_FUTURE_SLOTS),
+            },
+        );
+    // Encrypt keys at rest with a master password instead of the enclave's sealing key
+    let password = std::env::var("SECURE_SIGNER_PASSWORD").ok().or_else(|| {
+        options
+            .iter()
+            .any(|opt| opt == "--password-prompt")
+            .then(|| puffersecuresigner::io::key_vault::prompt_password().expect("Bad password"))
+    });
+    if let Some(password) = password {
+        let resealed = puffersecuresigner::io::key_vault::use_password(&password)
+            .expect("Failed to unlock the key vault");
+        println!("Unlocked the key vault, resealed {resealed} keys");
+    }
+    // Large operators can raise the per kind cap on saved keys
+    if let Some(max_keys) = option("max-keys") {
+        puffersecuresigner::io::key_management::set_max_keys(max_keys as usize)
+            .expect("Failed to set the key quota");
+    }
+    // Signing keys stay in memory after their first use, 0 reads them from disk every time
+    if let Some(max_cached_keys) = option("max-cached-keys") {
+        puffersecuresigner::crypto::bls_keys::set_max_cached_keys(max_cached_keys as usize)
+            .expect("Failed to set the key cache size");
+    }
+    let slashing_backend: std::sync::Arc<
+        dyn puffersecuresigner::eth2::slash_protection::SlashingProtectionBackend,
+    > = match option_str("slashing-db").as_deref() {
+        None | Some("file") => {
+            std::sync::Arc::new(puffersecuresigner::eth2::slash_protection::FileBackend)
+        }
+        #[cfg(feature = "sqlite")]
+        Some("sqlite") => std::sync::Arc::new(
+            puffersecuresigner::eth2::slash_protection_sqlite::SqliteBackend::open(
+                &puffersecuresigner::io::data_dir::slashing_protection_sqlite_path(),
+            )
+            .expect("Failed to open slashing protection db"),
+        ),
+        #[cfg(not(feature = "sqlite"))]
+        Some("sqlite") => panic!("Bad --slashing-db: built without the sqlite feature"),
+        Some(other) => panic!("Bad --slashing-db: {other}"),
+    };
+
+    // Every history is read once up front, a bad one is reported by /healthcheck while its key
+    // keeps refusing to sign
+    let slashing_protection_intact =
+        match puffersecuresigner::eth2::slash_protection::check_integrity(slashing_backend.as_ref())
+        {
+            Ok(n) => {
+                log::info!("Checked {n} slashing protection histories");
+                true
+            }
+            Err(e) => {
+                log::error!("Slashing protection integrity check failed: {e:#}");
+                false
+            }
+        };
 
     log::info!(
         "Starting SGX Validator: localhost:{}, using genesis_fork_version: {:?}",
@@ -26,6 +170,26 @@ async fn main() {
 
     let app_state = puffersecuresigner::enclave::shared::handlers::AppState {
         genesis_fork_version,
+        genesis_validators_root,
+        network: network.map(|n| n.name),
+        gas_limit_policy,
+        slashing_floor,
+        slashing_prune_window,
+        slot_clock,
+        fork_schedule,
+        strict_fork_schedule,
+        verify_selection_proofs,
+        max_randao_lookahead,
+        sign_locks: Default::default(),
+        signature_counts: Default::default(),
+        randao_reveal_epochs: Default::default(),
+        validator_configs: Default::default(),
+        slashing_backend,
+        slashing_protection_intact,
+        data_dir: puffersecuresigner::io::data_dir::data_dir(),
+        config: Default::default(),
+        leader: Default::default(),
+        worker: Default::default(),
     };
 
     let app = axum::Router::new()
@@ -34,6 +198,11 @@ async fn main() {
             "/upcheck",
             axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
         )
+        // Endpoint to report the network Secure-Signer signs for
+        .route(
+            "/healthcheck",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::healthcheck::handler),
+        )
         // Endpoint to securely generate and save a BLS sk
         .route(
             "/bls/v1/keygen",
@@ -48,6 +217,82 @@ async fn main() {
                 puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
             ),
         )
+        // Endpoint to delete saved bls keys from the enclave
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::delete(
+                puffersecuresigner::enclave::shared::handlers::delete_bls_keys::handler,
+            ),
+        )
+        // Endpoint to manage the fee recipient pinned for a bls key
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/feerecipient",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_fee_recipient::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_fee_recipient::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_fee_recipient::handler),
+        )
+        // Endpoint to manage the gas limit pinned for a bls key
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/gas_limit",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_gas_limit::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_gas_limit::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_gas_limit::handler),
+        )
+        // Endpoint to manage the graffiti pinned for a bls key
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/graffiti",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_graffiti::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_graffiti::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_graffiti::handler),
+        )
+        // Endpoint to export the EIP-3076 slashing protection history of saved bls keys
+        .route(
+            "/eth/v1/slashing-protection",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::export_slashing_protection::handler,
+            ),
+        )
+        // Endpoint to seed the slashing protection history from an EIP-3076 interchange file
+        .route(
+            "/eth/v1/slashing-protection",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::import_slashing_protection::handler,
+            ),
+        )
+        // Endpoint to report the slashing protection watermarks of every saved bls key
+        .route(
+            "/eth/v1/slashing-protection-status",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_slashing_protection_status::handler,
+            ),
+        )
+        // Endpoint to report the slashing protection watermarks of a bls key
+        .route(
+            "/eth/v1/slashing-protection/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_slashing_protection_status::handler,
+            ),
+        )
+        // Endpoint to drop slashing protection history that is not needed for safety
+        .route(
+            "/admin/prune-slashing-protection",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::prune_slashing_protection::handler,
+            ),
+        )
+        // Endpoint to list all pks of saved bls keys in the Web3Signer format
+        .route(
+            "/api/v1/eth2/publicKeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_public_keys::handler,
+            ),
+        )
         // Endpoint to request a signature using BLS sk
         .route(
             "/api/v1/eth2/sign/:bls_pk_hex",
@@ -55,6 +300,10 @@ async fn main() {
                 puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
             ),
         )
+        // Bound every request body before it is buffered and deserialized
+        .layer(axum::extract::DefaultBodyLimit::max(
+            puffersecuresigner::constants::MAX_REQUEST_BODY_BYTES,
+        ))
         .with_state(app_state);
 
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
@@ -26,16 +26,14 @@ fn build_deposit_request(
     withdrawal_credentials: &str,
     fork_version: Version,
 ) -> Result<DepositRequest> {
-    let validator_pk_hex: String = strip_0x_prefix!(validator_pk_hex);
-    let pk_bytes = hex::decode(validator_pk_hex)?;
+    let pk_bytes = puffersecuresigner::parse_hex(validator_pk_hex)?;
     assert_eq!(
         pk_bytes.len(),
         BLS_PUB_KEY_BYTES,
         "Invalid bls public key length"
     );
 
-    let withdrawal_credentials: String = strip_0x_prefix!(withdrawal_credentials);
-    let withdrawal_bytes = hex::decode(withdrawal_credentials)?;
+    let withdrawal_bytes = puffersecuresigner::parse_hex(withdrawal_credentials)?;
     assert_eq!(
         withdrawal_bytes.len(),
         32,
@@ -79,6 +79,7 @@ pub async fn post<T: DeserializeOwned>(
             client
                 .post(&url.to_string())
                 .header("Content-Type", "application/json")
+                .header("Accept", "application/json")
                 .body(json.clone())
                 .send()
                 .await
@@ -82,9 +82,11 @@ mod tests {
     use crate::eth2::eth_types::GENESIS_FORK_VERSION;
 
     use super::*;
+    use crate::io::data_dir::ScopedDataDir;
 
     #[test]
     fn test_keygen_and_decrypt() {
+        let _data_dir = ScopedDataDir::temp("test_keygen_and_decrypt");
         let withdrawal_credentials = [1; 32];
         let threshold = 1;
         let (_g_sk, g_pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
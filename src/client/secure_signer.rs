@@ -58,6 +58,101 @@ impl SecureSignerClient {
             .await?)
     }
 
+    pub async fn generate_deterministic_bls_key(
+        &self,
+    ) -> anyhow::Result<crate::enclave::types::DeriveBlsKeyResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/keygen/bls/deterministic", self.url))
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn derive_bls_key(
+        &self,
+        index: u32,
+    ) -> anyhow::Result<crate::enclave::types::DeriveBlsKeyResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/keygen/bls/derive", self.url))
+            .json(&crate::enclave::types::DeriveBlsKeyRequest { index, label: None })
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn generate_mnemonic(
+        &self,
+    ) -> anyhow::Result<crate::enclave::types::MnemonicKeyGenResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/keygen/bls/mnemonic", self.url))
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn recover_bls_keys(
+        &self,
+        req: &crate::enclave::types::RecoverBlsKeysRequest,
+    ) -> anyhow::Result<crate::enclave::types::ListKeysResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/keygen/bls/recover", self.url))
+            .json(req)
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn export_bls_keystores(
+        &self,
+        req: &crate::enclave::types::ExportKeystoresRequest,
+    ) -> anyhow::Result<crate::enclave::types::ImportKeystoresRequest> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/keystores/export", self.url))
+            .json(req)
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn update_key_label(
+        &self,
+        pubkey: &str,
+        label: Option<String>,
+    ) -> anyhow::Result<crate::io::key_metadata::KeyMetadata> {
+        Ok(self
+            .client
+            .patch(format!("{}/eth/v1/keystores/{}", self.url, pubkey))
+            .json(&crate::enclave::types::UpdateKeyLabelRequest { label })
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn split_bls_key(
+        &self,
+        req: &crate::enclave::types::SplitBlsKeyRequest,
+    ) -> anyhow::Result<crate::enclave::types::SplitBlsKeyResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/keystores/split", self.url))
+            .json(req)
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
     pub async fn secure_sign_bls(
         &self,
         public_key_hex: &str,
@@ -66,10 +161,59 @@ impl SecureSignerClient {
         Ok(self
             .client
             .post(format!("{}/api/v1/eth2/sign/{public_key_hex}", self.url))
+            .header(reqwest::header::ACCEPT, "application/json")
             .json(&signing_data)
             .send()
             .await?
             .json()
             .await?)
     }
+
+    pub async fn sign_secp256k1(
+        &self,
+        public_key_hex: &str,
+        digest: &[u8; 32],
+    ) -> anyhow::Result<crate::enclave::types::Secp256k1SignResponse> {
+        Ok(self
+            .client
+            .post(format!(
+                "{}/eth/v1/sign/secp256k1/{public_key_hex}",
+                self.url
+            ))
+            .json(&crate::enclave::types::Secp256k1SignRequest {
+                msg_hex: format!("0x{}", hex::encode(digest)),
+            })
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn aggregate_bls_signatures(
+        &self,
+        req: &crate::enclave::types::BlsAggregateRequest,
+    ) -> anyhow::Result<crate::enclave::types::BlsAggregateResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/aggregate", self.url))
+            .json(req)
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+
+    pub async fn verify_bls_signature(
+        &self,
+        req: &crate::enclave::types::BlsVerifyRequest,
+    ) -> anyhow::Result<crate::enclave::types::BlsVerifyResponse> {
+        Ok(self
+            .client
+            .post(format!("{}/eth/v1/verify/bls", self.url))
+            .json(req)
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
 }
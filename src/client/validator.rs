@@ -70,6 +70,7 @@ impl ValidatorClientTrait for ValidatorClient {
         Ok(self
             .client
             .post(format!("{}/api/v1/eth2/sign/{}", self.url, bls_pk_hex))
+            .header(reqwest::header::ACCEPT, "application/json")
             .json(&req)
             .send()
             .await?
@@ -0,0 +1,425 @@
+use crate::enclave::shared::api_token::ApiTokenScope;
+use crate::enclave::shared::request_log::LogFormat;
+use crate::io::remote_attestation::IasConfig;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// Where slashing protection histories are saved
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SlashingDb {
+    /// One JSON file per key
+    #[default]
+    File,
+    /// One SQLite database for every key, only with the sqlite feature
+    #[cfg_attr(not(feature = "sqlite"), serde(skip), value(skip))]
+    Sqlite,
+}
+
+/// Settings read from the TOML file passed with `--config`. Every field has a default, so a file
+/// only lists what it changes, and command line flags override what it sets.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Where keys and slashing protection histories are saved
+    pub datadir: PathBuf,
+    pub log_format: LogFormat,
+    pub server: ServerConfig,
+    pub tls: TlsConfig,
+    pub network: NetworkConfig,
+    pub slashing: SlashingConfig,
+    pub keys: KeysConfig,
+    pub api_token: ApiTokenConfig,
+    pub rate_limits: RateLimitsConfig,
+    pub cors: CorsFileConfig,
+    pub metrics: MetricsConfig,
+    pub attestation: AttestationConfig,
+    pub leader: LeaderConfig,
+    pub datafeed: DatafeedConfig,
+    /// The IAS account for EPID attestation, else read from the IAS_* env variables
+    pub ias: Option<IasConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            datadir: PathBuf::from(crate::constants::DEFAULT_DATA_DIR),
+            log_format: LogFormat::default(),
+            server: ServerConfig::default(),
+            tls: TlsConfig::default(),
+            network: NetworkConfig::default(),
+            slashing: SlashingConfig::default(),
+            keys: KeysConfig::default(),
+            api_token: ApiTokenConfig::default(),
+            rate_limits: RateLimitsConfig::default(),
+            cors: CorsFileConfig::default(),
+            metrics: MetricsConfig::default(),
+            attestation: AttestationConfig::default(),
+            leader: LeaderConfig::default(),
+            datafeed: DatafeedConfig::default(),
+            ias: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub address: IpAddr,
+    pub port: u16,
+    /// Serve on this Unix domain socket instead of the port
+    pub unix_socket: Option<PathBuf>,
+    /// Seconds in-flight requests get to finish on SIGINT or SIGTERM
+    pub shutdown_timeout: u64,
+    pub max_sign_body_bytes: usize,
+    pub max_import_body_bytes: usize,
+    /// Serve a Swagger UI page browsing /api/schema at /api/docs
+    pub swagger_ui: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            address: crate::constants::DEFAULT_LISTEN_ADDRESS,
+            port: crate::constants::DEFAULT_PORT,
+            unix_socket: None,
+            shutdown_timeout: crate::constants::DEFAULT_SHUTDOWN_GRACE_SECS,
+            max_sign_body_bytes: crate::constants::DEFAULT_MAX_SIGN_BODY_BYTES,
+            max_import_body_bytes: crate::constants::DEFAULT_MAX_IMPORT_BODY_BYTES,
+            swagger_ui: false,
+        }
+    }
+}
+
+/// HTTPS is served when both `cert` and `key` are set
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub client_ca: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// mainnet, goerli, sepolia, holesky, or custom with `config`
+    pub name: Option<String>,
+    /// The consensus-layer config.yaml of a custom network
+    pub config: Option<String>,
+    pub fork_schedule: Option<String>,
+    pub strict_fork_schedule: bool,
+    pub verify_selection_proofs: bool,
+    pub max_randao_lookahead: Option<u64>,
+    pub genesis_time: Option<u64>,
+    pub seconds_per_slot: Option<u64>,
+    pub max_future_slots: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            name: None,
+            config: None,
+            fork_schedule: None,
+            strict_fork_schedule: false,
+            verify_selection_proofs: false,
+            max_randao_lookahead: None,
+            genesis_time: None,
+            seconds_per_slot: None,
+            max_future_slots: crate::constants::DEFAULT_MAX_FUTURE_SLOTS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SlashingConfig {
+    pub db: SlashingDb,
+    pub min_slot: u64,
+    pub min_epoch: u64,
+    pub prune_window: usize,
+}
+
+impl Default for SlashingConfig {
+    fn default() -> Self {
+        SlashingConfig {
+            db: SlashingDb::default(),
+            min_slot: 0,
+            min_epoch: 0,
+            prune_window: crate::constants::DEFAULT_SLASHING_PRUNE_WINDOW,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    pub max_keys: usize,
+    pub max_cached_keys: usize,
+    /// Derive every new BLS key from the sealed master seed
+    pub deterministic_keygen: bool,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        KeysConfig {
+            max_keys: crate::constants::DEFAULT_MAX_KEYS,
+            max_cached_keys: crate::constants::DEFAULT_MAX_CACHED_KEYS,
+            deterministic_keygen: false,
+        }
+    }
+}
+
+/// SECURE_SIGNER_API_TOKEN takes precedence over `file`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ApiTokenConfig {
+    pub file: Option<String>,
+    pub scope: ApiTokenScope,
+}
+
+/// Requests per second, unlimited unless set. Bursts default to the rate.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RateLimitsConfig {
+    pub sign: Option<u64>,
+    pub sign_burst: Option<u64>,
+    pub admin: Option<u64>,
+    pub admin_burst: Option<u64>,
+}
+
+/// CORS is off unless `origins` lists at least one
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CorsFileConfig {
+    pub origins: Vec<String>,
+    /// GET and HEAD when empty
+    pub methods: Vec<String>,
+    pub max_age: u64,
+    pub allow_mutating: bool,
+}
+
+impl Default for CorsFileConfig {
+    fn default() -> Self {
+        CorsFileConfig {
+            origins: vec![],
+            methods: vec![],
+            max_age: crate::constants::DEFAULT_CORS_MAX_AGE_SECS,
+            allow_mutating: false,
+        }
+    }
+}
+
+/// Metrics are served next to the API unless `port` is set
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub port: Option<u16>,
+    pub address: Option<IpAddr>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AttestationConfig {
+    pub pccs_url: Option<String>,
+    pub measurement_policy: Option<String>,
+    /// Seconds attestation evidence is reused, 0 generates it for every request
+    pub evidence_cache_ttl: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LeaderConfig {
+    /// Milliseconds each worker gets to return its partial signature or answer a heartbeat
+    pub worker_timeout_ms: u64,
+    /// Seconds between heartbeats to the registered workers, 0 disables them
+    pub heartbeat_interval_secs: u64,
+    /// Heartbeats a worker may miss in a row before it is skipped
+    pub max_missed_heartbeats: u32,
+    /// Seconds a leader and its workers trust each other's attestation before handshaking again
+    pub peer_attestation_ttl_secs: u64,
+}
+
+impl Default for LeaderConfig {
+    fn default() -> Self {
+        LeaderConfig {
+            worker_timeout_ms: crate::constants::DEFAULT_WORKER_TIMEOUT_MS,
+            heartbeat_interval_secs: crate::constants::DEFAULT_HEARTBEAT_INTERVAL_SECS,
+            max_missed_heartbeats: crate::constants::DEFAULT_MAX_MISSED_HEARTBEATS,
+            peer_attestation_ttl_secs: crate::constants::DEFAULT_PEER_ATTESTATION_TTL_SECS,
+        }
+    }
+}
+
+/// Prices are served at `/datafeed/v1/price` only if `url` and `eth_pk` are set
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DatafeedConfig {
+    /// http(s) url the price of a pair is fetched from, with a `{pair}` placeholder
+    pub url: Option<String>,
+    /// The JSON pointer of the price in the source's answer
+    pub price_pointer: String,
+    /// The saved ETH key prices are signed with
+    pub eth_pk: Option<String>,
+}
+
+impl Default for DatafeedConfig {
+    fn default() -> Self {
+        DatafeedConfig {
+            url: None,
+            price_pointer: crate::constants::DEFAULT_DATAFEED_PRICE_POINTER.to_string(),
+            eth_pk: None,
+        }
+    }
+}
+
+impl Config {
+    /// Parses a TOML config. Keys it does not know are returned, so they can be warned about, or
+    /// refused when `strict`. Errors point at the line but never quote it, as the file may hold
+    /// IAS credentials.
+    pub fn from_toml(toml_str: &str, strict: bool) -> Result<(Self, Vec<String>)> {
+        let mut unknown_keys = vec![];
+        let deserializer = toml::Deserializer::new(toml_str);
+        let config: Config =
+            serde_ignored::deserialize(deserializer, |path| unknown_keys.push(path.to_string()))
+                .map_err(|e| {
+                    let line = e
+                        .span()
+                        .map(|span| toml_str[..span.start].matches('\n').count() + 1);
+                    match line {
+                        Some(line) => anyhow::anyhow!("{} at line {line}", e.message().trim()),
+                        None => anyhow::anyhow!("{}", e.message().trim()),
+                    }
+                })?;
+        if strict && !unknown_keys.is_empty() {
+            bail!("Unknown keys: {}", unknown_keys.join(", "))
+        }
+        if let Some(ias) = &config.ias {
+            ias.validate()?;
+        }
+        Ok((config, unknown_keys))
+    }
+
+    /// Reads and parses the TOML config at `path`, see `from_toml`
+    pub fn load(path: &str, strict: bool) -> Result<(Self, Vec<String>)> {
+        let toml_str =
+            std::fs::read_to_string(path).with_context(|| format!("Couldn't read {path}"))?;
+        Config::from_toml(&toml_str, strict).with_context(|| format!("Bad config {path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_CONFIG: &str = r#"
+datadir = "/var/lib/secure-signer"
+log_format = "json"
+
+[server]
+address = "0.0.0.0"
+port = 9001
+shutdown_timeout = 30
+
+[tls]
+cert = "/etc/secure-signer/signer.crt"
+key = "/etc/secure-signer/signer.key"
+
+[network]
+name = "holesky"
+max_future_slots = 32
+
+[slashing]
+db = "file"
+min_slot = 6500000
+
+[keys]
+max_keys = 5000
+
+[api_token]
+file = "/etc/secure-signer/api-token"
+scope = "all"
+
+[rate_limits]
+sign = 20
+sign_burst = 40
+
+[cors]
+origins = ["https://dashboard.example.com"]
+
+[metrics]
+port = 9101
+
+[datafeed]
+url = "https://prices.example.com/v1/{pair}"
+price_pointer = "/data/amount"
+eth_pk = "0x02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc"
+
+[ias]
+ias_access_key = "0123456789abcdef0123456789abcdef"
+enclave_spid = "0123456789ABCDEF0123456789ABCDEF"
+"#;
+
+    #[test]
+    fn test_sample_config_round_trips() {
+        let (config, unknown_keys) = Config::from_toml(SAMPLE_CONFIG, true).unwrap();
+        assert!(unknown_keys.is_empty());
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(config.server.port, 9001);
+        assert_eq!(config.network.name.as_deref(), Some("holesky"));
+        assert_eq!(config.slashing.db, SlashingDb::File);
+        assert_eq!(config.api_token.scope, ApiTokenScope::All);
+        assert_eq!(
+            config.ias.as_ref().unwrap().ias_url,
+            crate::constants::DEFAULT_IAS_URL
+        );
+
+        // Unset fields keep their defaults
+        let defaults = Config::default();
+        assert_eq!(
+            config.server.max_sign_body_bytes,
+            defaults.server.max_sign_body_bytes
+        );
+        assert_eq!(config.slashing.prune_window, defaults.slashing.prune_window);
+        assert_eq!(
+            defaults.datafeed.price_pointer,
+            crate::constants::DEFAULT_DATAFEED_PRICE_POINTER
+        );
+        assert_eq!(config.rate_limits.admin, None);
+
+        let written = toml::to_string(&config).unwrap();
+        assert_eq!(Config::from_toml(&written, true).unwrap().0, config);
+        assert_eq!(Config::from_toml("", true).unwrap().0, defaults);
+    }
+
+    #[test]
+    fn test_sqlite_needs_the_sqlite_feature() {
+        let sqlite = Config::from_toml("[slashing]\ndb = \"sqlite\"\n", true);
+        #[cfg(feature = "sqlite")]
+        assert_eq!(sqlite.unwrap().0.slashing.db, SlashingDb::Sqlite);
+        #[cfg(not(feature = "sqlite"))]
+        assert!(sqlite.is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys_are_reported() {
+        let toml_str = "datadir = \"/data\"\nprot = 9001\n\n[server]\nadress = \"0.0.0.0\"\n";
+        let (config, unknown_keys) = Config::from_toml(toml_str, false).unwrap();
+        assert_eq!(config.datadir, PathBuf::from("/data"));
+        assert_eq!(unknown_keys, vec!["prot", "server.adress"]);
+
+        let e = Config::from_toml(toml_str, true).unwrap_err();
+        assert_eq!(e.to_string(), "Unknown keys: prot, server.adress");
+    }
+
+    #[test]
+    fn test_errors_do_not_quote_the_file() {
+        let toml_str = "[ias]\nias_access_key = \"secret-key\"\nenclave_spid = 5\n";
+        let e = Config::from_toml(toml_str, false).unwrap_err();
+        assert!(!format!("{e:#}").contains("secret-key"));
+        assert!(format!("{e:#}").contains("line 3"));
+    }
+}
@@ -1,16 +1,73 @@
-pub const KEYS_DIR: &str = "./etc/keys/";
-pub const BLS_KEYS_DIR: &str = "./etc/keys/bls_keys/";
-pub const ETH_KEYS_DIR: &str = "./etc/keys/eth_keys/";
-pub const SLASHING_PROTECTION_DIR: &str = "./etc/slashing/";
+/// Everything Secure-Signer saves lives under this directory, unless `--datadir` says otherwise
+pub const DEFAULT_DATA_DIR: &str = "./etc/";
 
 pub const BLS_SIG_BYTES: usize = 96;
 pub const BLS_PUB_KEY_BYTES: usize = 48;
 pub const BLS_PRIV_KEY_BYTES: usize = 32;
+pub const BLS_SEED_BYTES: usize = 64;
+/// Ethereum's proof-of-possession ciphersuite, signatures on G2
+pub const BLS_MIN_PK_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// The same ciphersuite with the groups swapped, signatures on G1
+pub const BLS_MIN_SIG_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+pub const BLS_MIN_SIG_SIG_BYTES: usize = 48;
+pub const BLS_MIN_SIG_PUB_KEY_BYTES: usize = 96;
 pub const ETH_COMPRESSED_PK_BYTES: usize = 33;
 pub const ETH_UNCOMPRESSED_PK_BYTES: usize = 65;
 pub const ETH_SIGNATURE_BYTES: usize = 64;
 
 pub const ALLOW_GROWABLE_SLASH_PROTECTION_DB: bool = false;
+pub const DEFAULT_SLASHING_PRUNE_WINDOW: usize = 32;
+pub const DEFAULT_MAX_FUTURE_SLOTS: u64 = 64;
+pub const DEFAULT_MAX_KEYS: usize = 1000;
+pub const DEFAULT_MAX_CACHED_KEYS: usize = 1000;
+pub const MAX_KEY_LABEL_LEN: usize = 64;
+/// The port Secure-Signer serves on unless `--port` says otherwise
+pub const DEFAULT_PORT: u16 = 3031;
+/// Secure-Signer only serves its own host unless `--address` says otherwise
+pub const DEFAULT_LISTEN_ADDRESS: std::net::IpAddr =
+    std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+/// How long in-flight requests may take to finish once shutdown starts
+pub const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 10;
+/// How long browsers may cache the answer to a CORS preflight
+pub const DEFAULT_CORS_MAX_AGE_SECS: u64 = 600;
+/// Fits a full block with a maximal execution payload, so larger bodies never reach serde
+pub const MAX_REQUEST_BODY_BYTES: usize = 8 * 1024 * 1024;
+/// Sign requests are at most a phase0 block, far below this
+pub const DEFAULT_MAX_SIGN_BODY_BYTES: usize = 1024 * 1024;
+/// Room for a batch of keystores with their slashing protection history
+pub const DEFAULT_MAX_IMPORT_BODY_BYTES: usize = 10 * 1024 * 1024;
+/// The longest single hex field of a request, the 256 byte logs_bloom or a full committee's
+/// aggregation_bits, with room to spare. Lists of hex strings are bounded per item by SSZ.
+pub const MAX_HEX_FIELD_BYTES: usize = 1024;
+/// The IAS development endpoint, used when the IAS config omits a url
+pub const DEFAULT_IAS_URL: &str = "https://api.trustedservices.intel.com/sgx/dev/attestation/v4";
+/// The port a PCCS listens on when installed next to the enclave
+pub const DEFAULT_PCCS_URL: &str = "https://localhost:8081";
+/// Fits an ECDSA quote with its embedded PCK certificate chain
+pub const DCAP_QUOTE_MAX_BYTES: usize = 8 * 1024;
+pub const MAX_ATTESTATION_NONCE_BYTES: usize = 64;
+pub const DEFAULT_EVIDENCE_CACHE_TTL_SECS: u64 = 600;
+pub const MAX_CACHED_EVIDENCE: usize = 1000;
+/// How long a leader waits on each worker for its partial signature
+pub const DEFAULT_WORKER_TIMEOUT_MS: u64 = 2000;
+/// How often a leader polls its workers' status
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+/// Heartbeats a worker may miss in a row before the leader stops sending it sign requests
+pub const DEFAULT_MAX_MISSED_HEARTBEATS: u32 = 3;
+/// How long a leader and worker trust each other's attestation before handshaking again
+pub const DEFAULT_PEER_ATTESTATION_TTL_SECS: u64 = 3600;
+/// How long a leader waits on each step of a handshake, which includes generating evidence
+pub const HANDSHAKE_TIMEOUT_SECS: u64 = 30;
+/// How long a worker keeps the challenge of a handshake a leader started
+pub const HANDSHAKE_CHALLENGE_TTL_SECS: u64 = 60;
+/// Handshakes a worker lets leaders have started at once
+pub const MAX_PENDING_HANDSHAKES: usize = 1024;
+/// Where in the datafeed source's JSON answer the price is, as a JSON pointer
+pub const DEFAULT_DATAFEED_PRICE_POINTER: &str = "/price";
+/// How long the datafeed waits on its source for a price
+pub const DATAFEED_TIMEOUT_MS: u64 = 5000;
+/// Signed prices are integers scaled by 10^8, like Chainlink's USD feeds
+pub const DATAFEED_PRICE_DECIMALS: u8 = 8;
 
 pub const FULL_DEPOSIT_AMOUNT: u64 = 32000000000;
 pub const WITHDRAWAL_CREDENTIALS_BYTES: usize = 32;
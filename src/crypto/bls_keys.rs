@@ -1,19 +1,31 @@
-use crate::constants::BLS_PUB_KEY_BYTES;
+use crate::constants::{
+    BLS_PRIV_KEY_BYTES, BLS_PUB_KEY_BYTES, BLS_SEED_BYTES, DEFAULT_MAX_CACHED_KEYS,
+};
+use crate::crypto::bls_variant::{BlsVariant, SelectedVariant};
 use crate::io::key_management::{
-    read_bls_key, read_bls_keystore, write_bls_key, write_bls_keystore,
+    key_fname, read_bls_key, read_bls_keystore, read_bls_seed, write_bls_key, write_bls_keystore,
+    write_bls_seed,
 };
-use crate::strip_0x_prefix;
 
 use blsttc::{
     PublicKeySet, PublicKeyShare, SecretKeySet, SecretKeyShare, Signature, SignatureShare,
 };
 
 use anyhow::{bail, Context, Result};
-use std::collections::BTreeMap;
+use hkdf::Hkdf;
+use num_bigint::BigUint;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Raw BLS secret key bytes, zero-filled when dropped
+pub type BlsSecretBytes = Zeroizing<Vec<u8>>;
 
 /// Sanitizes a BLS public key hex string, and errors out if malformed.
 pub fn sanitize_bls_pk_hex(bls_pk_hex: &String) -> Result<String> {
-    let bls_pk: String = strip_0x_prefix!(bls_pk_hex);
+    let bls_pk = crate::normalize_hex(bls_pk_hex).with_context(|| "Invalid bls_pk_hex")?;
     // The length expected to be double since hex-encoded
     if bls_pk.len() != 2 * BLS_PUB_KEY_BYTES {
         bail!("Invalid bls_pk_hex length")
@@ -33,7 +45,8 @@ pub fn new_bls_key(threshold: usize) -> SecretKeySet {
 pub fn save_bls_key(sk_set: &SecretKeySet) -> Result<()> {
     // Hex-encode pk and sk
     let pk_hex = sk_set.public_keys().public_key().to_hex();
-    let sk_hex = hex::encode(sk_set.to_bytes());
+    let sk_bytes: BlsSecretBytes = Zeroizing::new(sk_set.to_bytes());
+    let sk_hex = Zeroizing::new(hex::encode(&*sk_bytes));
 
     // Save to file
     write_bls_key(&pk_hex, &sk_hex).with_context(|| "aggregate bls sk failed to save")
@@ -54,21 +67,140 @@ pub fn save_bls_keystore(sk_set: &SecretKeySet, password: &String) -> Result<Str
     Ok(uuid)
 }
 
+/// Destroys the saved BLS secret key, zero-filling the file before it is unlinked
+pub fn delete_bls_key(pk_hex: &String) -> Result<()> {
+    let pk_hex = sanitize_bls_pk_hex(pk_hex)?;
+    crate::io::key_management::delete_bls_key(&pk_hex)
+        .with_context(|| format!("Failed to delete bls key {pk_hex}"))
+}
+
 /// Read the BLS secret key from a secure file using the hex encoded pk as filename
 pub fn fetch_bls_sk(pk_hex: &String) -> Result<SecretKeySet> {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let sk_bytes = read_bls_key(pk_hex)?;
-    match SecretKeySet::from_bytes(sk_bytes) {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let sk_bytes: BlsSecretBytes = Zeroizing::new(read_bls_key(pk_hex)?);
+    match SecretKeySet::from_bytes(sk_bytes.to_vec()) {
         Ok(sk) => Ok(sk),
         Err(e) => bail!("Error deserializing bls sk bytes: {:?}", e),
     }
 }
 
+/// A BLS secret key held in the key cache. Only the raw secret scalar is kept, and it is
+/// zero-filled once the last reference to the entry is dropped.
+pub struct CachedBlsKey {
+    pk: blsttc::PublicKey,
+    sk_bytes: [u8; BLS_PRIV_KEY_BYTES],
+}
+
+impl CachedBlsKey {
+    fn new(sk_set: &SecretKeySet) -> Self {
+        let sk = sk_set.secret_key();
+        CachedBlsKey {
+            pk: sk.public_key(),
+            sk_bytes: sk.to_bytes(),
+        }
+    }
+
+    pub fn public_key(&self) -> &blsttc::PublicKey {
+        &self.pk
+    }
+
+    /// The big-endian secret scalar, for signing under another BLS variant
+    pub(crate) fn secret_bytes(&self) -> &[u8; BLS_PRIV_KEY_BYTES] {
+        &self.sk_bytes
+    }
+
+    /// Returns the BLS signature over `msg`
+    pub fn sign(&self, msg: &[u8]) -> Result<Signature> {
+        match blsttc::SecretKey::from_bytes(self.sk_bytes) {
+            Ok(sk) => Ok(sk.sign(msg)),
+            Err(e) => bail!("Error deserializing cached bls sk: {:?}", e),
+        }
+    }
+}
+
+impl Drop for CachedBlsKey {
+    fn drop(&mut self) {
+        self.sk_bytes.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for CachedBlsKey {}
+
+/// Deserialized BLS keys by hex public key, so signing does not read and unseal the key file on
+/// every request
+static BLS_KEY_CACHE: OnceLock<RwLock<HashMap<String, Arc<CachedBlsKey>>>> = OnceLock::new();
+static MAX_CACHED_KEYS: OnceLock<usize> = OnceLock::new();
+
+/// Caps how many BLS keys are kept in memory, 0 disables the cache. Must be called before the
+/// first key is cached.
+pub fn set_max_cached_keys(max_cached_keys: usize) -> Result<()> {
+    if MAX_CACHED_KEYS.set(max_cached_keys).is_err() {
+        bail!("Key cache size already set");
+    }
+    Ok(())
+}
+
+/// The most BLS keys kept in memory
+pub fn max_cached_keys() -> usize {
+    *MAX_CACHED_KEYS.get().unwrap_or(&DEFAULT_MAX_CACHED_KEYS)
+}
+
+fn bls_key_cache() -> &'static RwLock<HashMap<String, Arc<CachedBlsKey>>> {
+    BLS_KEY_CACHE.get_or_init(Default::default)
+}
+
+/// Like `fetch_bls_sk`, but the key is only read from disk the first time. When the cache is
+/// full an arbitrary key is evicted to make room.
+pub fn fetch_cached_bls_sk(pk_hex: &String) -> Result<Arc<CachedBlsKey>> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let cache = bls_key_cache();
+    if let Some(sk) = cache.read().ok().and_then(|c| c.get(pk_hex).cloned()) {
+        return Ok(sk);
+    }
+
+    // Read under the write lock, so a key deleted meanwhile cannot be cached after its eviction
+    let mut cache = cache
+        .write()
+        .map_err(|_| anyhow::anyhow!("bls key cache poisoned"))?;
+    if let Some(sk) = cache.get(pk_hex) {
+        return Ok(sk.clone());
+    }
+    let sk = Arc::new(CachedBlsKey::new(&fetch_bls_sk(&pk_hex.to_string())?));
+    let limit = max_cached_keys();
+    if limit > 0 {
+        if cache.len() >= limit {
+            if let Some(evicted) = cache.keys().next().cloned() {
+                cache.remove(&evicted);
+            }
+        }
+        cache.insert(pk_hex.to_string(), sk.clone());
+    }
+    Ok(sk)
+}
+
+/// Drops the BLS key from memory, whenever its key file is written or deleted
+pub fn evict_cached_bls_key(pk_hex: &str) {
+    let pk_hex: &str = &key_fname(pk_hex);
+    if let Ok(mut cache) = bls_key_cache().write() {
+        cache.remove(pk_hex);
+    }
+}
+
+/// Drops every BLS key from memory, zero-filling each once no request holds it. Returns how many
+/// keys were cached.
+pub fn clear_cached_bls_keys() -> usize {
+    match bls_key_cache().write() {
+        Ok(mut cache) => cache.drain().count(),
+        Err(_) => 0,
+    }
+}
+
 /// Read the BLS secret key from an encrypted keystore file using the hex encoded pk as filename
 pub fn fetch_bls_sk_keystore(pk_hex: &String, password: &String) -> Result<SecretKeySet> {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let sk_bytes = read_bls_keystore(&pk_hex.to_string(), password)?;
-    match SecretKeySet::from_bytes(sk_bytes) {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let sk_bytes: BlsSecretBytes =
+        Zeroizing::new(read_bls_keystore(&pk_hex.to_string(), password)?);
+    match SecretKeySet::from_bytes(sk_bytes.to_vec()) {
         Ok(sk) => Ok(sk),
         Err(e) => bail!("Error deserializing bls sk bytes: {:?}", e),
     }
@@ -80,18 +212,212 @@ pub fn bls_agg_sign(secret_key_set: &SecretKeySet, msg: &[u8]) -> Signature {
 }
 
 /// Performs BLS signature on `msg` using the BLS secret key looked up from memory
-/// with pk_hex as the file name.
-pub fn bls_agg_sign_from_saved_sk(pk_hex: &String, msg: &[u8]) -> Result<Signature> {
-    // Fetch the secret key set from memory using the provided pk_hex
-    let secret_key_set = fetch_bls_sk(pk_hex)?;
+/// with pk_hex as the file name. Signs under `SelectedVariant`, so the signature is
+/// `SelectedVariant::SIG_BYTES` long.
+pub fn bls_agg_sign_from_saved_sk(pk_hex: &String, msg: &[u8]) -> Result<Vec<u8>> {
+    crate::crypto::bls_variant::sign_from_saved_sk::<SelectedVariant>(pk_hex, msg)
+}
+
+/// Verifies the BLS `sig` over `msg` against the hex encoded `SelectedVariant` public key
+pub fn bls_verify(pk_hex: &String, msg: &[u8], sig: &[u8]) -> Result<bool> {
+    let pk = crate::parse_hex(pk_hex).with_context(|| "Invalid BLS public key")?;
+    if pk.len() != SelectedVariant::PUB_KEY_BYTES {
+        bail!(
+            "BLS public key must be {} bytes, got {}",
+            SelectedVariant::PUB_KEY_BYTES,
+            pk.len()
+        )
+    }
+    if sig.len() != SelectedVariant::SIG_BYTES {
+        bail!(
+            "BLS signature must be {} bytes, got {}",
+            SelectedVariant::SIG_BYTES,
+            sig.len()
+        )
+    }
+    Ok(SelectedVariant::verify(&pk, msg, sig))
+}
+
+/// Verifies the BLS `sig` over `msg` like the signer's own signatures are verified, under
+/// `SelectedVariant`, after checking that the key is a valid point other than the identity and
+/// that the signature is in its subgroup. Returns false rather than an error for any key or
+/// signature that fails, including those of the wrong length.
+pub fn bls_verify_subgroup_checked(pk: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+    SelectedVariant::verify(pk, msg, sig)
+}
+
+/// The order r of the BLS12-381 groups, which secret keys are reduced modulo
+fn curve_order() -> BigUint {
+    BigUint::parse_bytes(
+        b"73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+        16,
+    )
+    .expect("valid curve order")
+}
+
+/// I2OSP(sk, 32), the big-endian encoding EIP-2333 and EIP-2335 use for secret keys
+fn sk_to_bytes(sk: &BigUint) -> [u8; BLS_PRIV_KEY_BYTES] {
+    let be = sk.to_bytes_be();
+    let mut out = [0_u8; BLS_PRIV_KEY_BYTES];
+    out[BLS_PRIV_KEY_BYTES - be.len()..].copy_from_slice(&be);
+    out
+}
+
+/// EIP-2333 HKDF_mod_r with an empty key_info
+fn hkdf_mod_r(ikm: &[u8]) -> BigUint {
+    let r = curve_order();
+    let ikm: Vec<u8> = [ikm, &[0]].concat();
+    let mut salt = b"BLS-SIG-KEYGEN-SALT-".to_vec();
+    loop {
+        salt = Sha256::digest(&salt).to_vec();
+        let mut okm = [0_u8; 48];
+        Hkdf::<Sha256>::new(Some(&salt), &ikm)
+            .expand(&48_u16.to_be_bytes(), &mut okm)
+            .expect("48 bytes is a valid HKDF-SHA256 output length");
+        let sk = BigUint::from_bytes_be(&okm) % &r;
+        if sk != BigUint::default() {
+            return sk;
+        }
+    }
+}
+
+/// EIP-2333 IKM_to_lamport_SK, returned as the 255 32-byte chunks laid end to end
+fn ikm_to_lamport_sk(ikm: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut okm = vec![0_u8; 255 * 32];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(&[], &mut okm)
+        .expect("255 * 32 bytes is the maximum HKDF-SHA256 output length");
+    okm
+}
+
+/// EIP-2333 parent_SK_to_lamport_PK, the compressed Lamport public key a child is derived from
+fn parent_sk_to_lamport_pk(parent_sk: &BigUint, index: u32) -> [u8; 32] {
+    let salt = index.to_be_bytes();
+    let ikm = sk_to_bytes(parent_sk);
+    let not_ikm: Vec<u8> = ikm.iter().map(|b| !b).collect();
+    let lamport_0 = ikm_to_lamport_sk(&ikm, &salt);
+    let lamport_1 = ikm_to_lamport_sk(&not_ikm, &salt);
+    let mut lamport_pk = Sha256::new();
+    for chunk in lamport_0.chunks(32).chain(lamport_1.chunks(32)) {
+        lamport_pk.update(Sha256::digest(chunk));
+    }
+    lamport_pk.finalize().into()
+}
+
+/// EIP-2333 derive_master_SK
+pub fn derive_master_sk(seed: &[u8]) -> Result<BigUint> {
+    if seed.len() < 32 {
+        bail!("Seed must be at least 32 bytes, got {}", seed.len())
+    }
+    Ok(hkdf_mod_r(seed))
+}
+
+/// EIP-2333 derive_child_SK
+pub fn derive_child_sk(parent_sk: &BigUint, index: u32) -> BigUint {
+    hkdf_mod_r(&parent_sk_to_lamport_pk(parent_sk, index))
+}
+
+/// Parses an EIP-2334 path such as m/12381/3600/0/0/0 into its indices
+pub fn parse_derivation_path(path: &str) -> Result<Vec<u32>> {
+    let mut nodes = path.split('/');
+    if nodes.next() != Some("m") {
+        bail!("Derivation path must start with m: {path}")
+    }
+    nodes
+        .map(|node| {
+            node.parse::<u32>()
+                .with_context(|| format!("Bad index {node:?} in derivation path {path}"))
+        })
+        .collect()
+}
+
+/// The EIP-2334 signing key path of the validator at `index`
+pub fn signing_key_path(index: u32) -> String {
+    format!("m/12381/3600/{index}/0/0")
+}
+
+/// Derives the BLS secret key at the EIP-2334 `path` from `seed` following EIP-2333
+pub fn derive_bls_child_key(seed: &[u8], path: &str) -> Result<SecretKeySet> {
+    let mut sk = derive_master_sk(seed)?;
+    for index in parse_derivation_path(path)? {
+        sk = derive_child_sk(&sk, index);
+    }
+    match SecretKeySet::from_bytes(sk_to_bytes(&sk).to_vec()) {
+        Ok(sk) => Ok(sk),
+        Err(e) => bail!("Error deserializing derived bls sk bytes: {:?}", e),
+    }
+}
+
+/// Serializes seed creation so concurrent first derivations agree on one seed
+static SEED_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_seed() -> Result<std::sync::MutexGuard<'static, ()>> {
+    match SEED_LOCK.lock() {
+        Ok(guard) => Ok(guard),
+        Err(_) => bail!("bls seed lock poisoned"),
+    }
+}
 
-    // Verify the supplied pk_hex matches the derived
-    if pk_hex != &secret_key_set.public_keys().public_key().to_hex() {
-        bail!("Mismatch with input and derived pk");
+/// Reads the seed that BLS keys are derived from, generating and saving one on first use.
+/// The seed never leaves the enclave.
+pub fn fetch_or_create_bls_seed() -> Result<Vec<u8>> {
+    let _guard = lock_seed()?;
+    if let Some(seed) = read_bls_seed()? {
+        return Ok(seed);
     }
+    let mut seed = vec![0_u8; BLS_SEED_BYTES];
+    rand::thread_rng().fill_bytes(&mut seed);
+    write_bls_seed(&seed).with_context(|| "Failed to save bls seed")?;
+    Ok(seed)
+}
+
+/// Saves `seed` as the seed BLS keys are derived from. Returns false without saving if the
+/// enclave already holds a different seed.
+pub fn set_bls_seed(seed: &[u8]) -> Result<bool> {
+    let _guard = lock_seed()?;
+    if let Some(existing) = read_bls_seed()? {
+        return Ok(existing == seed);
+    }
+    write_bls_seed(seed).with_context(|| "Failed to save bls seed")?;
+    Ok(true)
+}
+
+/// Makes sure the enclave holds a sealed master seed, generating one if needed, so that keys
+/// derived from it can all be restored from the seed alone
+pub fn init_master_seed() -> Result<()> {
+    fetch_or_create_bls_seed().map(|_| ())
+}
+
+/// Derives the EIP-2334 signing key of validator `index` from the master seed following EIP-2333
+pub fn derive_indexed_bls_key(index: u32) -> Result<SecretKeySet> {
+    let seed = fetch_or_create_bls_seed()?;
+    derive_bls_child_key(&seed, &signing_key_path(index))
+}
+
+/// Serializes deterministic keygen so that every index is handed out once
+static SEED_INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Held while reading, deriving at, and advancing the persisted seed index
+pub fn lock_seed_index() -> Result<std::sync::MutexGuard<'static, ()>> {
+    match SEED_INDEX_LOCK.lock() {
+        Ok(guard) => Ok(guard),
+        Err(_) => bail!("bls seed index lock poisoned"),
+    }
+}
 
-    // Sign the message using the fetched secret key set
-    Ok(bls_agg_sign(&secret_key_set, msg))
+/// Generates a fresh 24-word BIP-39 mnemonic
+pub fn new_mnemonic() -> Result<bip39::Mnemonic> {
+    let mut entropy = [0_u8; 32];
+    rand::thread_rng().fill_bytes(&mut entropy);
+    bip39::Mnemonic::from_entropy(&entropy).with_context(|| "Failed to generate mnemonic")
+}
+
+/// The BIP-39 seed of an English `mnemonic`, which EIP-2334 keys are derived from as the
+/// staking-deposit-cli does
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<[u8; 64]> {
+    let mnemonic = bip39::Mnemonic::parse_normalized(mnemonic.trim())
+        .with_context(|| "Invalid BIP-39 mnemonic")?;
+    Ok(mnemonic.to_seed_normalized(passphrase))
 }
 
 /// Distributes `n` key shares from a given BLS `SecretKeySet`.
@@ -117,6 +443,66 @@ pub fn distribute_key_shares(
         .collect()
 }
 
+/// Splits `sk` into `n` Shamir shares over the BLS scalar field, any `t` of which can sign for it.
+/// The sharing polynomial has `sk` as its constant term and `t - 1` random coefficients, and
+/// share `i` is its evaluation at `i + 1`. The returned `PublicKeySet` holds the commitments to
+/// the coefficients, which is the verification vector every share can be checked against.
+pub fn split_bls_key(
+    sk: &blsttc::SecretKey,
+    n: usize,
+    t: usize,
+) -> Result<(PublicKeySet, Vec<SecretKeyShare>)> {
+    if t == 0 || t > n {
+        bail!("Threshold must be between 1 and {n}, got {t}")
+    }
+
+    // Coefficients are serialized constant term first. The buffer never grows, so no copy of
+    // them is left behind unwiped.
+    let mut coeffs: BlsSecretBytes = Zeroizing::new(Vec::with_capacity(t * BLS_PRIV_KEY_BYTES));
+    coeffs.extend_from_slice(&*Zeroizing::new(sk.to_bytes()));
+    for _ in 1..t {
+        coeffs.extend_from_slice(&*Zeroizing::new(new_bls_key(0).secret_key().to_bytes()));
+    }
+    let sk_set = SecretKeySet::from_bytes(coeffs.to_vec())
+        .map_err(|e| anyhow::anyhow!("Failed to build the sharing polynomial: {:?}", e))?;
+    if sk_set.public_keys().public_key() != sk.public_key() {
+        bail!("Sharing polynomial does not commit to the key being split")
+    }
+
+    let shares = (0..n).map(|i| sk_set.secret_key_share(i)).collect();
+    Ok((sk_set.public_keys(), shares))
+}
+
+/// Recombines partial signatures over `msg`, keyed by share index, into a signature valid under
+/// the public key of `pk_set` by Lagrange interpolation. Every partial is verified against its
+/// public key share first, and at least `pk_set.threshold() + 1` are needed.
+pub fn combine_partial_signatures(
+    pk_set: &PublicKeySet,
+    partials: &BTreeMap<usize, SignatureShare>,
+    msg: &[u8],
+) -> Result<Signature> {
+    if partials.len() <= pk_set.threshold() {
+        bail!(
+            "Need {} partial signatures to recombine, got {}",
+            pk_set.threshold() + 1,
+            partials.len()
+        )
+    }
+    for (i, partial) in partials.iter() {
+        if !pk_set.public_key_share(*i).verify(partial, msg) {
+            bail!("Partial signature of share {i} does not verify")
+        }
+    }
+
+    let sig = pk_set
+        .combine_signatures(partials.clone())
+        .with_context(|| "Failed to recombine partial signatures")?;
+    if !pk_set.public_key().verify(&sig, msg) {
+        bail!("Recombined signature does not verify")
+    }
+    Ok(sig)
+}
+
 /// Aggregate BLS signature shares into a single signature.
 ///
 /// This function takes a reference to a PublicKeySet and a vector of SignatureShares.
@@ -150,9 +536,27 @@ pub fn aggregate_signature_shares(
     Ok(aggregated_signature)
 }
 
+/// Aggregates signatures by different keys over the same message into one signature, which
+/// verifies against the aggregate of the keys. Keys and signatures are `SelectedVariant` points.
+/// Callers must verify each signature first, and only aggregate keys whose possession was
+/// proven, as aggregate keys allow rogue key attacks.
+pub fn aggregate_uniform_bls_sigs(pks: &[Vec<u8>], sigs: &[Vec<u8>]) -> Result<(Vec<u8>, Vec<u8>)> {
+    if pks.is_empty() || pks.len() != sigs.len() {
+        bail!(
+            "Need one signature per key to aggregate, got {} keys and {} signatures",
+            pks.len(),
+            sigs.len()
+        )
+    }
+    let agg_pk = SelectedVariant::aggregate_public_keys(pks)?;
+    let agg_sig = SelectedVariant::aggregate(sigs)?;
+    Ok((agg_sig, agg_pk))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::data_dir::ScopedDataDir;
     use crate::io::key_management::{bls_key_exists, delete_bls_key};
     #[test]
     fn test_new_bls_key() {
@@ -167,6 +571,7 @@ mod tests {
 
     #[test]
     fn test_save_and_fetch_bls_key() {
+        let _data_dir = ScopedDataDir::temp("test_save_and_fetch_bls_key");
         let threshold = 3;
         let sk_set = new_bls_key(threshold);
         let pk_hex = sk_set.public_keys().public_key().to_hex();
@@ -190,8 +595,61 @@ mod tests {
         assert!(!bls_key_exists(&pk_hex));
     }
 
+    #[test]
+    fn test_deleted_bls_key_cannot_sign() {
+        let _data_dir = ScopedDataDir::temp("bls_keys_test_deleted_bls_key_cannot_sign");
+        let sk_set = new_bls_key(0);
+        let pk_hex = sk_set.public_keys().public_key().to_hex();
+        save_bls_key(&sk_set).unwrap();
+        assert!(bls_agg_sign_from_saved_sk(&pk_hex, b"msg").is_ok());
+
+        super::delete_bls_key(&format!("0x{pk_hex}")).unwrap();
+        assert!(!bls_key_exists(&pk_hex));
+        assert!(fetch_bls_sk(&pk_hex).is_err());
+        assert!(bls_agg_sign_from_saved_sk(&pk_hex, b"msg").is_err());
+
+        // Malformed keys are refused rather than looked up
+        assert!(super::delete_bls_key(&"0xdeadbeef".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_cached_key_signs_without_reading_disk() {
+        let _data_dir = ScopedDataDir::temp("test_cached_key_signs_without_reading_disk");
+        let sk_set = new_bls_key(0);
+        let pk_hex = sk_set.public_keys().public_key().to_hex();
+        save_bls_key(&sk_set).unwrap();
+        let msg = b"msg";
+        let sig = bls_agg_sign_from_saved_sk(&pk_hex, msg).unwrap();
+
+        // Hide the key file, so signing only succeeds if the key is not read again
+        let key_path: std::path::PathBuf = crate::io::data_dir::bls_keys_dir().join(&pk_hex);
+        let hidden = key_path.with_file_name(format!(".{pk_hex}.hidden"));
+        std::fs::rename(&key_path, &hidden).unwrap();
+        assert_eq!(bls_agg_sign_from_saved_sk(&pk_hex, msg).unwrap(), sig);
+        std::fs::rename(&hidden, &key_path).unwrap();
+
+        // Deleting the key evicts it
+        delete_bls_key(&pk_hex).unwrap();
+        assert!(bls_agg_sign_from_saved_sk(&pk_hex, msg).is_err());
+    }
+
+    #[test]
+    fn test_secret_key_material_is_zeroized_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<BlsSecretBytes>();
+        assert_zeroize_on_drop::<crate::crypto::eth_keys::EthSecretBytes>();
+        assert_zeroize_on_drop::<CachedBlsKey>();
+
+        // The cached key signs exactly like the key set it was built from
+        let sk_set = new_bls_key(0);
+        let cached = CachedBlsKey::new(&sk_set);
+        assert_eq!(cached.public_key(), &sk_set.public_keys().public_key());
+        assert_eq!(cached.sign(b"msg").unwrap(), bls_agg_sign(&sk_set, b"msg"));
+    }
+
     #[test]
     fn test_save_and_fetch_bls_keystore() {
+        let _data_dir = ScopedDataDir::temp("test_save_and_fetch_bls_keystore");
         let threshold = 3;
         let sk_set = new_bls_key(threshold);
         let pk_hex = sk_set.public_keys().public_key().to_hex();
@@ -219,6 +677,7 @@ mod tests {
 
     #[test]
     fn test_bls_agg_sign_from_saved_sk_success() {
+        let _data_dir = ScopedDataDir::temp("test_bls_agg_sign_from_saved_sk_success");
         let threshold = 1;
         let secret_key_set = new_bls_key(threshold);
         let public_key_set = secret_key_set.public_keys();
@@ -230,8 +689,11 @@ mod tests {
         let signature =
             bls_agg_sign_from_saved_sk(&pk_hex, msg).expect("Failed to sign the message");
 
+        let pk = crate::crypto::bls_variant::public_key_from_saved_sk::<SelectedVariant>(&pk_hex)
+            .unwrap();
+        assert_eq!(signature.len(), SelectedVariant::SIG_BYTES);
         assert!(
-            public_key_set.public_key().verify(&signature, msg),
+            SelectedVariant::verify(&pk, msg, &signature),
             "Signature verification failed"
         );
     }
@@ -250,6 +712,109 @@ mod tests {
         bls_agg_sign_from_saved_sk(&pk_hex, msg).expect("Failed to sign the message");
     }
 
+    #[test]
+    fn test_eip2333_known_answers() {
+        // https://eips.ethereum.org/EIPS/eip-2333#test-cases
+        let cases = [
+            (
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                "6083874454709270928345386274498605044986640685124978867557563392430687146096",
+                0,
+                "20397789859736650942317412262472558107875392172444076792671091975210932703118",
+            ),
+            (
+                "3141592653589793238462643383279502884197169399375105820974944592",
+                "29757020647961307431480504535336562678282505419141012933316116377660817309383",
+                3141592653,
+                "25457201688850691947727629385191704516744796114925897962676248250929345014287",
+            ),
+            (
+                "0099FF991111002299DD7744EE3355BBDD8844115566CC55663355668888CC00",
+                "27580842291869792442942448775674722299803720648445448686099262467207037398656",
+                4294967295,
+                "29358610794459428860402234341874281240803786294062035874021252734817515685787",
+            ),
+            (
+                "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+                "19022158461524446591288038168518313374041767046816487870552872741050760015818",
+                42,
+                "31372231650479070279774297061823572166496564838472787488249775572789064611981",
+            ),
+        ];
+        for (seed, master_sk, index, child_sk) in cases {
+            let seed = hex::decode(seed).unwrap();
+            let master = derive_master_sk(&seed).unwrap();
+            assert_eq!(master.to_string(), master_sk);
+            assert_eq!(derive_child_sk(&master, index).to_string(), child_sk);
+        }
+    }
+
+    #[test]
+    fn test_derive_bls_child_key() {
+        let seed = hex::decode("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04").unwrap();
+        let sk = derive_bls_child_key(&seed, "m/0").unwrap();
+        assert_eq!(
+            hex::encode(sk.secret_key().to_bytes()),
+            "2d18bd6c14e6d15bf8b5085c9b74f3daae3b03cc2014770a599d8c1539e50f8e"
+        );
+        let sk = derive_bls_child_key(&seed, &signing_key_path(0)).unwrap();
+        assert_eq!(
+            hex::encode(sk.secret_key().to_bytes()),
+            "032e6c3c7359223e127e9479afc521c4342f8903bc29ae01b671bcbcc98be0f6"
+        );
+
+        assert!(derive_bls_child_key(&seed[..31], "m/0").is_err());
+        assert!(parse_derivation_path("12381/3600/0/0/0").is_err());
+        assert!(parse_derivation_path("m/12381/-1").is_err());
+        assert!(parse_derivation_path("m/4294967296").is_err());
+        assert_eq!(parse_derivation_path("m").unwrap(), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_mnemonic_to_pubkeys() {
+        // EIP-2333 test case 0 is the BIP-39 seed of this mnemonic and passphrase
+        let seed = mnemonic_to_seed(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "TREZOR",
+        )
+        .unwrap();
+        assert_eq!(
+            derive_master_sk(&seed).unwrap().to_string(),
+            "6083874454709270928345386274498605044986640685124978867557563392430687146096"
+        );
+
+        // Validator keys as the staking-deposit-cli derives them, with an empty passphrase
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+        let seed = mnemonic_to_seed(mnemonic, "").unwrap();
+        assert_eq!(
+            hex::encode(seed),
+            "408b285c123836004f4b8842c89324c1f01382450c0d439af345ba7fc49acf705489c6fc77dbd4e3dc1dd8cc6bc9f043db8ada1e243c4a0eafb290d399480840"
+        );
+        let pubkeys = [
+            "b384f767d964e100c8a9b21018d08c25ffebae268b3ab6d610353897541971726dbfc3c7463884c68a531515aab94c87",
+            "b3d89e2f29c712c6a9f8e5a269b97617c4a94dd6f6662ab3b07ce9e5434573f15b5c988cd14bbd5804f77156a8af1cfa",
+        ];
+        for (index, pubkey) in pubkeys.iter().enumerate() {
+            let sk = derive_bls_child_key(&seed, &signing_key_path(index as u32)).unwrap();
+            assert_eq!(&sk.public_keys().public_key().to_hex(), pubkey);
+        }
+
+        // A bad checksum word is refused
+        assert!(mnemonic_to_seed(&mnemonic.replace("art", "zoo"), "").is_err());
+    }
+
+    #[test]
+    fn test_new_mnemonic() {
+        let mnemonic = new_mnemonic().unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+        let phrase = mnemonic.to_string();
+        assert_eq!(
+            mnemonic_to_seed(&phrase, "").unwrap(),
+            mnemonic.to_seed_normalized("")
+        );
+        assert_ne!(new_mnemonic().unwrap().to_string(), phrase);
+    }
+
     #[test]
     fn test_distribute_key_shares() {
         let threshold = 2;
@@ -328,7 +893,105 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    fn test_aggregate_uniform_bls_sigs() {
+        let msg = b"Hello, world!";
+        let sks: Vec<[u8; BLS_PRIV_KEY_BYTES]> = (0..3)
+            .map(|_| new_bls_key(0).secret_key().to_bytes())
+            .collect();
+        let pks: Vec<Vec<u8>> = sks
+            .iter()
+            .map(|sk| SelectedVariant::sk_to_pk(sk).unwrap())
+            .collect();
+        let sigs: Vec<Vec<u8>> = sks
+            .iter()
+            .map(|sk| SelectedVariant::sign(sk, msg).unwrap())
+            .collect();
+
+        let (agg_sig, agg_pk) = aggregate_uniform_bls_sigs(&pks, &sigs).unwrap();
+        assert!(SelectedVariant::verify(&agg_pk, msg, &agg_sig));
+        assert!(!SelectedVariant::verify(
+            &agg_pk,
+            b"Goodbye, world!",
+            &agg_sig
+        ));
+
+        // Dropping a signer breaks the aggregate
+        let (agg_sig, _) = aggregate_uniform_bls_sigs(&pks[..2], &sigs[..2]).unwrap();
+        assert!(!SelectedVariant::verify(&agg_pk, msg, &agg_sig));
+
+        assert!(aggregate_uniform_bls_sigs(&pks, &sigs[..2]).is_err());
+        assert!(aggregate_uniform_bls_sigs(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_split_bls_key_recombines_with_threshold_partials() {
+        let (n, t) = (5, 3);
+        let sk_set = new_bls_key(0);
+        let pk = sk_set.public_keys().public_key();
+        let (pk_set, shares) = split_bls_key(&sk_set.secret_key(), n, t).unwrap();
+        assert_eq!(shares.len(), n);
+        assert_eq!(pk_set.public_key(), pk);
+        assert_eq!(pk_set.threshold(), t - 1);
+
+        // Every share matches the verification vector
+        for (i, share) in shares.iter().enumerate() {
+            assert_eq!(share.public_key_share(), pk_set.public_key_share(i));
+        }
+
+        let msg = b"Hello, world!";
+        let partials: BTreeMap<usize, SignatureShare> = shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| (i, share.sign(msg)))
+            .collect();
+
+        // Any t partials recombine into a signature under the original key
+        let some: BTreeMap<usize, SignatureShare> = partials
+            .iter()
+            .filter(|(i, _)| [0, 2, 4].contains(*i))
+            .map(|(i, s)| (*i, s.clone()))
+            .collect();
+        let sig = combine_partial_signatures(&pk_set, &some, msg).unwrap();
+        assert!(pk.verify(&sig, msg));
+        assert_eq!(sig, bls_agg_sign(&sk_set, msg));
+
+        // t - 1 do not
+        let too_few: BTreeMap<usize, SignatureShare> = some.into_iter().take(t - 1).collect();
+        assert!(combine_partial_signatures(&pk_set, &too_few, msg).is_err());
+
+        // Partials over another message are caught
+        let mut mixed: BTreeMap<usize, SignatureShare> = partials.into_iter().take(t).collect();
+        mixed.insert(1, shares[1].sign(b"Goodbye, world!"));
+        assert!(combine_partial_signatures(&pk_set, &mixed, msg).is_err());
+
+        assert!(split_bls_key(&sk_set.secret_key(), 2, 3).is_err());
+        assert!(split_bls_key(&sk_set.secret_key(), 2, 0).is_err());
+    }
+
+    #[test]
+    fn test_bls_verify_subgroup_checked() {
+        let msg = b"Hello, world!";
+        let sk = new_bls_key(0).secret_key().to_bytes();
+        let pk = SelectedVariant::sk_to_pk(&sk).unwrap();
+        let sig = SelectedVariant::sign(&sk, msg).unwrap();
+        assert!(bls_verify_subgroup_checked(&pk, msg, &sig));
+        assert!(!bls_verify_subgroup_checked(&pk, b"Goodbye, world!", &sig));
+        assert!(bls_verify(&crate::to_0x_hex(&pk), msg, &sig).unwrap());
+        assert!(bls_verify(&crate::to_0x_hex(&pk), msg, &sig[1..]).is_err());
+
+        // The identity key and points off the curve are refused without panicking
+        let mut identity = vec![0_u8; SelectedVariant::PUB_KEY_BYTES];
+        identity[0] = 0xc0;
+        assert!(!bls_verify_subgroup_checked(&identity, msg, &sig));
+        assert!(!bls_verify_subgroup_checked(
+            &pk,
+            msg,
+            &vec![0xff; SelectedVariant::SIG_BYTES]
+        ));
+        assert!(!bls_verify_subgroup_checked(&pk, msg, &sig[1..]));
+    }
+
+    #[test]
     fn test_aggregate_signature_shares_different_messages() {
         let threshold = 3;
         let n = 5;
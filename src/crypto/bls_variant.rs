@@ -0,0 +1,220 @@
+use crate::constants::{BLS_MIN_PK_DST, BLS_PRIV_KEY_BYTES, BLS_PUB_KEY_BYTES, BLS_SIG_BYTES};
+#[cfg(feature = "min-sig")]
+use crate::constants::{BLS_MIN_SIG_DST, BLS_MIN_SIG_PUB_KEY_BYTES, BLS_MIN_SIG_SIG_BYTES};
+
+use anyhow::{anyhow, bail, Result};
+
+/// The BLS operations that differ between the two BLS12-381 group orderings. Keys are saved once
+/// as a secret scalar, so the same saved key can sign under either variant, each with its own
+/// public key.
+pub trait BlsVariant {
+    /// Length of a compressed public key
+    const PUB_KEY_BYTES: usize;
+    /// Length of a compressed signature
+    const SIG_BYTES: usize;
+    /// Hash-to-curve domain separation tag of the ciphersuite
+    const DST: &'static [u8];
+
+    /// Derives the compressed public key of the big-endian secret scalar
+    fn sk_to_pk(sk: &[u8; BLS_PRIV_KEY_BYTES]) -> Result<Vec<u8>>;
+
+    /// Returns the compressed signature over `msg`
+    fn sign(sk: &[u8; BLS_PRIV_KEY_BYTES], msg: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verifies `sig` over `msg`, after checking the key and signature are valid subgroup points.
+    /// Returns false rather than an error for malformed input.
+    fn verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> bool;
+
+    /// Aggregates signatures by different keys into one of the same length
+    fn aggregate(sigs: &[Vec<u8>]) -> Result<Vec<u8>>;
+
+    /// Aggregates public keys into the one their aggregate signature verifies against
+    fn aggregate_public_keys(pks: &[Vec<u8>]) -> Result<Vec<u8>>;
+}
+
+/// Ethereum's ordering: 48 byte public keys on G1, 96 byte signatures on G2. The default.
+pub struct MinPk;
+
+/// 96 byte public keys on G2, 48 byte signatures on G1
+#[cfg(feature = "min-sig")]
+pub struct MinSig;
+
+/// The variant keys sign, verify and aggregate with, `MinSig` when built with the `min-sig`
+/// feature
+#[cfg(not(feature = "min-sig"))]
+pub type SelectedVariant = MinPk;
+#[cfg(feature = "min-sig")]
+pub type SelectedVariant = MinSig;
+
+macro_rules! impl_bls_variant {
+    ($variant:ident, $blst:ident, $pk_bytes:expr, $sig_bytes:expr, $dst:expr) => {
+        impl BlsVariant for $variant {
+            const PUB_KEY_BYTES: usize = $pk_bytes;
+            const SIG_BYTES: usize = $sig_bytes;
+            const DST: &'static [u8] = $dst;
+
+            fn sk_to_pk(sk: &[u8; BLS_PRIV_KEY_BYTES]) -> Result<Vec<u8>> {
+                let sk = blst::$blst::SecretKey::from_bytes(sk)
+                    .map_err(|e| anyhow!("Invalid BLS secret key: {:?}", e))?;
+                Ok(sk.sk_to_pk().to_bytes().to_vec())
+            }
+
+            fn sign(sk: &[u8; BLS_PRIV_KEY_BYTES], msg: &[u8]) -> Result<Vec<u8>> {
+                let sk = blst::$blst::SecretKey::from_bytes(sk)
+                    .map_err(|e| anyhow!("Invalid BLS secret key: {:?}", e))?;
+                Ok(sk.sign(msg, Self::DST, &[]).to_bytes().to_vec())
+            }
+
+            fn verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+                if pk.len() != Self::PUB_KEY_BYTES || sig.len() != Self::SIG_BYTES {
+                    return false;
+                }
+                let (pk, sig) = match (
+                    blst::$blst::PublicKey::key_validate(pk),
+                    blst::$blst::Signature::sig_validate(sig, true),
+                ) {
+                    (Ok(pk), Ok(sig)) => (pk, sig),
+                    _ => return false,
+                };
+                sig.verify(false, msg, Self::DST, &[], &pk, false) == blst::BLST_ERROR::BLST_SUCCESS
+            }
+
+            fn aggregate(sigs: &[Vec<u8>]) -> Result<Vec<u8>> {
+                let sigs = sigs
+                    .iter()
+                    .map(|sig| blst::$blst::Signature::from_bytes(sig))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| anyhow!("Invalid BLS signature: {:?}", e))?;
+                let agg_sig = blst::$blst::AggregateSignature::aggregate(
+                    &sigs.iter().collect::<Vec<_>>(),
+                    true,
+                )
+                .map_err(|e| anyhow!("Failed to aggregate signatures: {:?}", e))?;
+                Ok(agg_sig.to_signature().to_bytes().to_vec())
+            }
+
+            fn aggregate_public_keys(pks: &[Vec<u8>]) -> Result<Vec<u8>> {
+                let pks = pks
+                    .iter()
+                    .map(|pk| blst::$blst::PublicKey::from_bytes(pk))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| anyhow!("Invalid BLS public key: {:?}", e))?;
+                let agg_pk = blst::$blst::AggregatePublicKey::aggregate(
+                    &pks.iter().collect::<Vec<_>>(),
+                    true,
+                )
+                .map_err(|e| anyhow!("Failed to aggregate public keys: {:?}", e))?;
+                Ok(agg_pk.to_public_key().to_bytes().to_vec())
+            }
+        }
+    };
+}
+
+impl_bls_variant!(
+    MinPk,
+    min_pk,
+    BLS_PUB_KEY_BYTES,
+    BLS_SIG_BYTES,
+    BLS_MIN_PK_DST
+);
+
+#[cfg(feature = "min-sig")]
+impl_bls_variant!(
+    MinSig,
+    min_sig,
+    BLS_MIN_SIG_PUB_KEY_BYTES,
+    BLS_MIN_SIG_SIG_BYTES,
+    BLS_MIN_SIG_DST
+);
+
+/// The public key of the saved BLS key under the variant `V`. `pk_hex` is the key's Ethereum
+/// (min_pk) public key, which names the key file.
+pub fn public_key_from_saved_sk<V: BlsVariant>(pk_hex: &String) -> Result<Vec<u8>> {
+    let sk = crate::crypto::bls_keys::fetch_cached_bls_sk(pk_hex)?;
+    V::sk_to_pk(sk.secret_bytes())
+}
+
+/// Signs `msg` with the saved BLS key under the variant `V`, see `public_key_from_saved_sk`
+pub fn sign_from_saved_sk<V: BlsVariant>(pk_hex: &String, msg: &[u8]) -> Result<Vec<u8>> {
+    let sk = crate::crypto::bls_keys::fetch_cached_bls_sk(pk_hex)?;
+
+    // Verify the supplied pk_hex matches the derived
+    if crate::io::key_management::key_fname(pk_hex) != sk.public_key().to_hex() {
+        bail!("Mismatch with input and derived pk");
+    }
+    V::sign(sk.secret_bytes(), msg)
+}
+
+/// The key the saved BLS key's signatures verify against, when it is not the key's Ethereum
+/// public key `pk_hex`, i.e. when built with `min-sig`
+pub fn signing_pk_hex(pk_hex: &String) -> Result<Option<String>> {
+    if SelectedVariant::PUB_KEY_BYTES == BLS_PUB_KEY_BYTES {
+        return Ok(None);
+    }
+    let pk = public_key_from_saved_sk::<SelectedVariant>(pk_hex)?;
+    Ok(Some(crate::to_0x_hex(pk)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::bls_keys::{bls_agg_sign, new_bls_key};
+
+    fn sign_verify_aggregate<V: BlsVariant>() {
+        let msg = b"msg";
+        let sks: Vec<[u8; BLS_PRIV_KEY_BYTES]> = (0..3)
+            .map(|_| new_bls_key(0).secret_key().to_bytes())
+            .collect();
+        let pks: Vec<Vec<u8>> = sks.iter().map(|sk| V::sk_to_pk(sk).unwrap()).collect();
+        let sigs: Vec<Vec<u8>> = sks.iter().map(|sk| V::sign(sk, msg).unwrap()).collect();
+
+        for (pk, sig) in pks.iter().zip(sigs.iter()) {
+            assert_eq!(pk.len(), V::PUB_KEY_BYTES);
+            assert_eq!(sig.len(), V::SIG_BYTES);
+            assert!(V::verify(pk, msg, sig));
+            assert!(!V::verify(pk, b"other msg", sig));
+        }
+        assert!(!V::verify(&pks[0], msg, &sigs[1]));
+
+        let agg_sig = V::aggregate(&sigs).unwrap();
+        assert_eq!(agg_sig.len(), V::SIG_BYTES);
+        assert!(V::aggregate(&[vec![0; 7]]).is_err());
+        let agg_pk = V::aggregate_public_keys(&pks).unwrap();
+        assert_eq!(agg_pk.len(), V::PUB_KEY_BYTES);
+        assert!(V::verify(&agg_pk, msg, &agg_sig));
+        assert!(V::aggregate_public_keys(&[vec![0; 7]]).is_err());
+    }
+
+    #[test]
+    fn test_min_pk_matches_the_default_signer() {
+        sign_verify_aggregate::<MinPk>();
+
+        let sk_set = new_bls_key(0);
+        let sk = sk_set.secret_key().to_bytes();
+        assert_eq!(
+            MinPk::sk_to_pk(&sk).unwrap(),
+            sk_set.public_keys().public_key().to_bytes().to_vec()
+        );
+        assert_eq!(
+            MinPk::sign(&sk, b"msg").unwrap(),
+            bls_agg_sign(&sk_set, b"msg").to_bytes().to_vec()
+        );
+    }
+
+    #[cfg(feature = "min-sig")]
+    #[test]
+    fn test_min_sig_swaps_the_groups() {
+        let _data_dir = crate::io::data_dir::ScopedDataDir::temp("test_min_sig_swaps_the_groups");
+        sign_verify_aggregate::<MinSig>();
+
+        // The same saved key signs under both variants with different public keys
+        let sk_set = new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&sk_set).unwrap();
+        let pk_hex = sk_set.public_keys().public_key().to_hex();
+        let pk = public_key_from_saved_sk::<MinSig>(&pk_hex).unwrap();
+        let sig = sign_from_saved_sk::<MinSig>(&pk_hex, b"msg").unwrap();
+        assert!(MinSig::verify(&pk, b"msg", &sig));
+        assert!(!MinPk::verify(&pk, b"msg", &sig));
+        crate::crypto::bls_keys::delete_bls_key(&pk_hex).unwrap();
+    }
+}
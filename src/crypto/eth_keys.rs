@@ -4,8 +4,12 @@ use crate::strip_0x_prefix;
 
 use anyhow::{bail, Context, Result};
 use ecies::{utils::generate_keypair, PublicKey as EthPublicKey, SecretKey as EthSecretKey};
-use libsecp256k1::{Message, Signature};
+use libsecp256k1::{Message, RecoveryId, Signature};
 use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
+
+/// Raw SECP256K1 secret key bytes, zero-filled when dropped
+pub type EthSecretBytes = Zeroizing<Vec<u8>>;
 
 /// Wrapper around ecies utility function to generate SECP256K1 keypair
 pub fn new_eth_key() -> Result<(EthSecretKey, EthPublicKey)> {
@@ -16,7 +20,15 @@ pub fn new_eth_key() -> Result<(EthSecretKey, EthPublicKey)> {
 /// ETH address derived from the public key as the filename.
 pub fn eth_key_gen() -> Result<EthPublicKey> {
     let (sk, pk) = new_eth_key()?;
-    save_eth_key(sk, pk).with_context(|| "Failed to save generated ETH key")
+    let pk = save_eth_key(sk, pk).with_context(|| "Failed to save generated ETH key")?;
+    crate::io::key_metadata::write_eth_metadata(
+        &eth_pk_to_hex(&pk),
+        &crate::io::key_metadata::KeyMetadata::new(
+            crate::io::key_metadata::KeyOrigin::Generated,
+            None,
+        ),
+    )?;
+    Ok(pk)
 }
 
 /// Hex-encode ETH secret key
@@ -34,10 +46,28 @@ pub fn eth_pk_to_hex_uncompressed(pk: &EthPublicKey) -> String {
     strip_0x_prefix!(hex::encode(pk.serialize()))
 }
 
+/// Derives an ETH public key from either its compressed 33B or uncompressed 65B hex form
+pub fn parse_eth_pk_hex(pk_hex: &String) -> Result<EthPublicKey> {
+    let pk_hex: String = strip_0x_prefix!(pk_hex);
+    if pk_hex.len() == 2 * ETH_UNCOMPRESSED_PK_BYTES {
+        eth_pk_from_hex_uncompressed(&pk_hex)
+    } else {
+        eth_pk_from_hex(&pk_hex)
+    }
+}
+
+/// The EIP-55 checksummed address of an ETH public key, keccak256(uncompressed_pk[1..])[12..]
+pub fn eth_pk_to_address(pk: &EthPublicKey) -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(&pk.serialize()[1..]);
+    let digest = hasher.finalize();
+    ethers::utils::to_checksum(&ethers::types::Address::from_slice(&digest[12..]), None)
+}
+
 /// Derives an ETH public key from a hex-string, expects the hex string to be in compressed 33B form
 pub fn eth_pk_from_hex(pk_hex: &String) -> Result<EthPublicKey> {
     let pk_hex: String = strip_0x_prefix!(pk_hex);
-    let pk_bytes = hex::decode(&pk_hex)?;
+    let pk_bytes = crate::parse_hex(&pk_hex)?;
 
     if pk_bytes.len() != ETH_COMPRESSED_PK_BYTES {
         bail!("ETH pk should be in compressed 33B form")
@@ -59,7 +89,7 @@ pub fn eth_pk_from_hex(pk_hex: &String) -> Result<EthPublicKey> {
 /// Derives an ETH public key from a hex-string, expects the hex string to be in compressed 33B form
 pub fn eth_pk_from_hex_uncompressed(pk_hex: &String) -> Result<EthPublicKey> {
     let pk_hex: String = strip_0x_prefix!(pk_hex);
-    let pk_bytes = hex::decode(&pk_hex)?;
+    let pk_bytes = crate::parse_hex(&pk_hex)?;
 
     if pk_bytes.len() != ETH_UNCOMPRESSED_PK_BYTES {
         bail!("ETH pk should be in compressed 33B form")
@@ -80,6 +110,7 @@ pub fn eth_pk_from_hex_uncompressed(pk_hex: &String) -> Result<EthPublicKey> {
 
 /// Derives an ETH public key from a hex-string, expects the hex string to be in compressed 33B form
 pub fn eth_sk_from_bytes(sk: Vec<u8>) -> Result<EthSecretKey> {
+    let sk: EthSecretBytes = Zeroizing::new(sk);
     EthSecretKey::parse_slice(&sk).with_context(|| "couldn't parse sk bytes to eth sk type")
 }
 
@@ -87,7 +118,7 @@ pub fn eth_sk_from_bytes(sk: Vec<u8>) -> Result<EthSecretKey> {
 pub fn save_eth_key(sk: EthSecretKey, pk: EthPublicKey) -> Result<EthPublicKey> {
     let pk_hex = eth_pk_to_hex(&pk);
 
-    let sk_hex = eth_sk_to_hex(&sk);
+    let sk_hex = Zeroizing::new(eth_sk_to_hex(&sk));
 
     write_eth_key(&pk_hex, &sk_hex).with_context(|| "eth sk failed to save")?;
 
@@ -97,8 +128,15 @@ pub fn save_eth_key(sk: EthSecretKey, pk: EthPublicKey) -> Result<EthPublicKey>
 /// Read the ETH SECP256K1 secret key from a secure file using the hex encoded pk as filename
 pub fn fetch_eth_key(pk_hex: &String) -> Result<EthSecretKey> {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let sk_bytes = read_eth_key(pk_hex)?;
-    eth_sk_from_bytes(sk_bytes)
+    let sk_bytes: EthSecretBytes = Zeroizing::new(read_eth_key(pk_hex)?);
+    EthSecretKey::parse_slice(&sk_bytes).with_context(|| "couldn't parse sk bytes to eth sk type")
+}
+
+/// Destroys the saved ETH SECP256K1 secret key, zero-filling the file before it is unlinked
+pub fn delete_eth_key(pk_hex: &String) -> Result<()> {
+    let pk_hex = eth_pk_to_hex(&parse_eth_pk_hex(pk_hex)?);
+    crate::io::key_management::delete_eth_key(&pk_hex)
+        .with_context(|| format!("Failed to delete eth key {pk_hex}"))
 }
 
 /// Computes digest = keccak256(message), then signs digest using SECP256K1 secret key.
@@ -118,6 +156,17 @@ pub fn sign_message(message: &[u8], secret_key: &EthSecretKey) -> Result<(Signat
     Ok((signature, digest))
 }
 
+/// Signs the 32B `digest` as is with the saved ETH key, returning the signature and its
+/// recovery id
+pub fn sign_secp256k1(pk_hex: &String, digest: &[u8]) -> Result<(Signature, RecoveryId)> {
+    if digest.len() != 32 {
+        bail!("Digest must be 32 bytes, got {}", digest.len())
+    }
+    let digest = Message::parse_slice(digest).with_context(|| "Invalid digest")?;
+    let secret_key = fetch_eth_key(pk_hex)?;
+    Ok(libsecp256k1::sign(&digest, &secret_key))
+}
+
 /// Verify the signature over keccak256(message) using SECP256K1 secret key
 pub fn verify_message(
     message: &[u8],
@@ -177,6 +226,46 @@ pub fn envelope_decrypt_from_saved_sk(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+
+    #[test]
+    fn test_eth_pk_to_address() {
+        let sk = eth_sk_from_bytes(
+            hex::decode("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318")
+                .unwrap(),
+        )
+        .unwrap();
+        let pk = EthPublicKey::from_secret_key(&sk);
+        assert_eq!(
+            eth_pk_to_address(&pk),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+
+        // Compressed and uncompressed keys have the same address
+        let compressed = parse_eth_pk_hex(&eth_pk_to_hex(&pk)).unwrap();
+        let uncompressed = parse_eth_pk_hex(&eth_pk_to_hex_uncompressed(&pk)).unwrap();
+        assert_eq!(eth_pk_to_address(&compressed), eth_pk_to_address(&pk));
+        assert_eq!(eth_pk_to_address(&uncompressed), eth_pk_to_address(&pk));
+        assert!(parse_eth_pk_hex(&"0xdeadbeef".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_sign_secp256k1() {
+        let _data_dir = ScopedDataDir::temp("test_sign_secp256k1");
+        let pk = eth_key_gen().unwrap();
+        let pk_hex = eth_pk_to_hex(&pk);
+        let digest = [7_u8; 32];
+
+        let (sig, recovery_id) = sign_secp256k1(&pk_hex, &digest).unwrap();
+        let msg = Message::parse(&digest);
+        assert!(libsecp256k1::verify(&msg, &sig, &pk));
+        assert_eq!(libsecp256k1::recover(&msg, &sig, &recovery_id).unwrap(), pk);
+
+        // Only 32B digests are signed
+        assert!(sign_secp256k1(&pk_hex, &[7_u8; 31]).is_err());
+        delete_eth_key(&pk_hex).unwrap();
+        assert!(sign_secp256k1(&pk_hex, &digest).is_err());
+    }
 
     #[test]
     fn test_sign_message() {
@@ -267,6 +356,7 @@ mod tests {
 
     #[test]
     fn test_save_fetch_eth_key() {
+        let _data_dir = ScopedDataDir::temp("test_save_fetch_eth_key");
         let (secret_key, public_key) = new_eth_key().unwrap();
         let public_key_hex = eth_pk_to_hex(&public_key);
 
@@ -299,6 +389,7 @@ mod tests {
 
     #[test]
     fn test_envelope_decrypt_from_saved_sk() {
+        let _data_dir = ScopedDataDir::temp("test_envelope_decrypt_from_saved_sk");
         // Generate a new SECP256K1 keypair (ETH keypair) and save the secret key to a file
         let (secret_key, public_key) = new_eth_key().unwrap();
         let eth_pk_hex = eth_pk_to_hex(&public_key);
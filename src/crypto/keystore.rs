@@ -1,9 +1,17 @@
 use crate::strip_0x_prefix;
 
 use super::eth_keys;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use blsttc::SecretKeySet;
 use ecies::SecretKey as EthSecretKey;
 use eth_keystore::decrypt_keystore;
+use openssl::hash::MessageDigest;
+use openssl::symm::Cipher;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// EIP-2335 recommended pbkdf2 iteration count
+const PBKDF2_ITERATIONS: usize = 262144;
 
 pub fn import_keystore(
     keystore: &String,
@@ -11,18 +19,113 @@ pub fn import_keystore(
     envelope_sk: &EthSecretKey,
 ) -> Result<Vec<u8>> {
     // Decrypt the password
-    let ct_password_hex: String = strip_0x_prefix!(ct_password_hex);
-    let ct_password_bytes = hex::decode(ct_password_hex)?;
+    let ct_password_bytes = crate::parse_hex(ct_password_hex)?;
     let password_bytes = eth_keys::envelope_decrypt(envelope_sk, &ct_password_bytes)?;
     let password = String::from_utf8(password_bytes).with_context(|| "non-utf8 password")?;
     decrypt_keystore(keystore, password).with_context(|| "Failed to decrypt keystore")
 }
 
+/// Decrypts an EIP-2335 keystore (scrypt or pbkdf2) and verifies that the public key derived
+/// from the decrypted secret matches the keystore's `pubkey` field.
+pub fn decrypt_bls_keystore(keystore: &String, password: &String) -> Result<SecretKeySet> {
+    let keystore_json: serde_json::Value =
+        serde_json::from_str(keystore).with_context(|| "Keystore is not valid JSON")?;
+    let exp_pk_hex: String = match keystore_json.get("pubkey").and_then(|pk| pk.as_str()) {
+        Some(pk) => strip_0x_prefix!(pk),
+        None => bail!("Keystore is missing the pubkey field"),
+    };
+
+    let sk_bytes = decrypt_keystore(keystore, password.to_string())
+        .with_context(|| "Failed to decrypt keystore")?;
+    let sk_set = match SecretKeySet::from_bytes(sk_bytes) {
+        Ok(sk) => sk,
+        Err(e) => bail!("Error deserializing bls sk bytes: {:?}", e),
+    };
+
+    let got_pk_hex = sk_set.public_keys().public_key().to_hex();
+    if got_pk_hex != exp_pk_hex.to_lowercase() {
+        bail!("Derived pubkey 0x{got_pk_hex} does not match keystore pubkey 0x{exp_pk_hex}")
+    }
+    Ok(sk_set)
+}
+
+/// Encrypts the BLS secret key into a version 4 EIP-2335 keystore using pbkdf2 and aes-128-ctr.
+/// `password` is expected to be ASCII, so EIP-2335 password normalization is a no-op.
+pub fn encrypt_bls_keystore(sk_set: &SecretKeySet, password: &str, path: &str) -> Result<String> {
+    let mut rng = rand::thread_rng();
+    let mut salt = [0_u8; 32];
+    let mut iv = [0_u8; 16];
+    let mut uuid = [0_u8; 16];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut iv);
+    rng.fill_bytes(&mut uuid);
+    // Random (version 4) UUID
+    uuid[6] = (uuid[6] & 0x0f) | 0x40;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+    let uuid = hex::encode(uuid);
+
+    let mut decryption_key = [0_u8; 32];
+    openssl::pkcs5::pbkdf2_hmac(
+        password.as_bytes(),
+        &salt,
+        PBKDF2_ITERATIONS,
+        MessageDigest::sha256(),
+        &mut decryption_key,
+    )?;
+    let cipher_message = openssl::symm::encrypt(
+        Cipher::aes_128_ctr(),
+        &decryption_key[..16],
+        Some(&iv),
+        &sk_set.secret_key().to_bytes(),
+    )?;
+    let checksum = Sha256::digest([&decryption_key[16..], &cipher_message[..]].concat());
+
+    let keystore = serde_json::json!({
+        "crypto": {
+            "kdf": {
+                "function": "pbkdf2",
+                "params": {
+                    "dklen": 32,
+                    "c": PBKDF2_ITERATIONS,
+                    "prf": "hmac-sha256",
+                    "salt": hex::encode(salt)
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": hex::encode(checksum)
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": hex::encode(iv)
+                },
+                "message": hex::encode(cipher_message)
+            }
+        },
+        "description": "",
+        "pubkey": sk_set.public_keys().public_key().to_hex(),
+        "path": path,
+        "uuid": format!(
+            "{}-{}-{}-{}-{}",
+            &uuid[0..8],
+            &uuid[8..12],
+            &uuid[12..16],
+            &uuid[16..20],
+            &uuid[20..32]
+        ),
+        "version": 4
+    });
+    Ok(keystore.to_string())
+}
+
 #[cfg(test)]
 pub mod keystore_tests {
     use crate::crypto::eth_keys;
 
-    use super::import_keystore;
+    use super::{decrypt_bls_keystore, encrypt_bls_keystore, import_keystore};
     use hex::FromHex;
 
     #[test]
@@ -75,6 +178,81 @@ pub mod keystore_tests {
         );
     }
 
+    /// Test vec from: https://eips.ethereum.org/EIPS/eip-2335
+    fn pbkdf2_keystore(pubkey: &str) -> String {
+        format!(
+            r#"
+        {{
+            "crypto": {{
+                "kdf": {{
+                    "function": "pbkdf2",
+                    "params": {{
+                        "dklen": 32,
+                        "c": 262144,
+                        "prf": "hmac-sha256",
+                        "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                    }},
+                    "message": ""
+                }},
+                "checksum": {{
+                    "function": "sha256",
+                    "params": {{}},
+                    "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+                }},
+                "cipher": {{
+                    "function": "aes-128-ctr",
+                    "params": {{
+                        "iv": "264daa3f303d7259501c93d997d84fe6"
+                    }},
+                    "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+                }}
+            }},
+            "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+            "pubkey": "{pubkey}",
+            "path": "m/12381/60/0/0",
+            "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+            "version": 4
+        }}"#
+        )
+    }
+
+    #[test]
+    fn test_decrypt_bls_keystore_pbkdf2() {
+        let keystore = pbkdf2_keystore("9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07");
+        let sk_set = decrypt_bls_keystore(&keystore, &"testpassword\u{1f511}".to_string()).unwrap();
+        assert_eq!(
+            sk_set.secret_key().to_bytes().to_vec(),
+            Vec::from_hex("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decrypt_bls_keystore_wrong_password() {
+        let keystore = pbkdf2_keystore("9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07");
+        assert!(decrypt_bls_keystore(&keystore, &"wrongpassword".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_bls_keystore_pubkey_mismatch() {
+        let keystore = pbkdf2_keystore("a8a1580a80406ccb0a89e1115c92ec1a09994e2ac6341cfddcad5daf75f587244aa6d722b3449a17b0b0b482c1d13215");
+        assert!(decrypt_bls_keystore(&keystore, &"testpassword\u{1f511}".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_bls_keystore_round_trips() {
+        let sk_set = crate::crypto::bls_keys::new_bls_key(0);
+        let keystore =
+            encrypt_bls_keystore(&sk_set, "exportpassword", "m/12381/3600/0/0/0").unwrap();
+        let keystore_json: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+        assert_eq!(keystore_json["version"], 4);
+        assert_eq!(keystore_json["path"], "m/12381/3600/0/0/0");
+
+        let decrypted = decrypt_bls_keystore(&keystore, &"exportpassword".to_string()).unwrap();
+        assert!(decrypted == sk_set);
+        assert!(decrypt_bls_keystore(&keystore, &"wrongpassword".to_string()).is_err());
+    }
+
     #[test]
     fn test_encrypt_decrypt_keystore() {
         std::fs::create_dir_all("./test_keys").unwrap();
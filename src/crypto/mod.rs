@@ -1,3 +1,6 @@
 pub mod bls_keys;
+pub mod bls_variant;
 pub mod eth_keys;
 pub mod keystore;
+pub mod reshare;
+pub mod session;
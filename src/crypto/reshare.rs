@@ -0,0 +1,248 @@
+//! Proactive re-sharing of a threshold key without reconstructing it. Each holder in a qualified
+//! set of the old sharing splits its share into sub-shares for the new workers with
+//! `bls_keys::split_bls_key`, committing to them with a verification vector whose public key is
+//! its old public key share. Each new worker weighs the sub-shares it received by the Lagrange
+//! coefficients of the old set and adds them into its new share, and the same combination of the
+//! sub-share verification vectors is the verification vector of the new sharing, which commits to
+//! the same public key.
+//!
+//! Share `i` of a blsttc key set is its polynomial evaluated at `i + 1`.
+
+use anyhow::{anyhow, bail, Result};
+use blsttc::{PublicKeySet, SecretKeyShare};
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+const G1_COMPRESSED_BYTES: usize = 48;
+
+fn fr_from_u64(x: u64) -> blst::blst_fr {
+    let mut fr = blst::blst_fr::default();
+    let limbs = [x, 0, 0, 0];
+    unsafe { blst::blst_fr_from_uint64(&mut fr, limbs.as_ptr()) };
+    fr
+}
+
+fn fr_add(a: &blst::blst_fr, b: &blst::blst_fr) -> blst::blst_fr {
+    let mut sum = blst::blst_fr::default();
+    unsafe { blst::blst_fr_add(&mut sum, a, b) };
+    sum
+}
+
+fn fr_sub(a: &blst::blst_fr, b: &blst::blst_fr) -> blst::blst_fr {
+    let mut diff = blst::blst_fr::default();
+    unsafe { blst::blst_fr_sub(&mut diff, a, b) };
+    diff
+}
+
+fn fr_mul(a: &blst::blst_fr, b: &blst::blst_fr) -> blst::blst_fr {
+    let mut product = blst::blst_fr::default();
+    unsafe { blst::blst_fr_mul(&mut product, a, b) };
+    product
+}
+
+fn fr_inverse(a: &blst::blst_fr) -> blst::blst_fr {
+    let mut inverse = blst::blst_fr::default();
+    unsafe { blst::blst_fr_inverse(&mut inverse, a) };
+    inverse
+}
+
+fn fr_from_share(share: &SecretKeyShare) -> Result<blst::blst_fr> {
+    let bytes = Zeroizing::new(share.to_bytes());
+    let mut scalar = blst::blst_scalar::default();
+    unsafe { blst::blst_scalar_from_bendian(&mut scalar, bytes.as_ptr()) };
+    if !unsafe { blst::blst_scalar_fr_check(&scalar) } {
+        bail!("Share is not a scalar of the BLS12-381 field")
+    }
+    let mut fr = blst::blst_fr::default();
+    unsafe { blst::blst_fr_from_scalar(&mut fr, &scalar) };
+    Ok(fr)
+}
+
+fn share_from_fr(fr: &blst::blst_fr) -> Result<SecretKeyShare> {
+    let mut scalar = blst::blst_scalar::default();
+    let mut bytes = Zeroizing::new([0_u8; 32]);
+    unsafe {
+        blst::blst_scalar_from_fr(&mut scalar, fr);
+        blst::blst_bendian_from_scalar(bytes.as_mut_ptr(), &scalar);
+    }
+    SecretKeyShare::from_bytes(*bytes).map_err(|e| anyhow!("Bad combined share: {e:?}"))
+}
+
+/// Parses a verification vector coefficient, which must be a point of the G1 subgroup other than
+/// the identity, as uncompressing only checks the point is on the curve
+fn g1_from_bytes(bytes: &[u8]) -> Result<blst::blst_p1> {
+    let mut affine = blst::blst_p1_affine::default();
+    if unsafe { blst::blst_p1_uncompress(&mut affine, bytes.as_ptr()) }
+        != blst::BLST_ERROR::BLST_SUCCESS
+    {
+        bail!("Bad verification vector coefficient")
+    }
+    if unsafe { blst::blst_p1_affine_is_inf(&affine) } {
+        bail!("Verification vector coefficient is the identity")
+    }
+    if !unsafe { blst::blst_p1_affine_in_g1(&affine) } {
+        bail!("Verification vector coefficient is not in G1")
+    }
+    let mut point = blst::blst_p1::default();
+    unsafe { blst::blst_p1_from_affine(&mut point, &affine) };
+    Ok(point)
+}
+
+fn g1_mul(point: &blst::blst_p1, k: &blst::blst_fr) -> blst::blst_p1 {
+    let mut scalar = blst::blst_scalar::default();
+    let mut product = blst::blst_p1::default();
+    unsafe {
+        blst::blst_scalar_from_fr(&mut scalar, k);
+        blst::blst_p1_mult(&mut product, point, scalar.b.as_ptr(), 255);
+    }
+    product
+}
+
+fn g1_add(a: &blst::blst_p1, b: &blst::blst_p1) -> blst::blst_p1 {
+    let mut sum = blst::blst_p1::default();
+    unsafe { blst::blst_p1_add_or_double(&mut sum, a, b) };
+    sum
+}
+
+/// The Lagrange coefficient of share `i` for interpolating the shares `indices` at zero
+fn lagrange_at_zero(i: usize, indices: &[usize]) -> blst::blst_fr {
+    let x_i = fr_from_u64(i as u64 + 1);
+    let mut num = fr_from_u64(1);
+    let mut den = fr_from_u64(1);
+    for &j in indices.iter().filter(|&&j| j != i) {
+        let x_j = fr_from_u64(j as u64 + 1);
+        num = fr_mul(&num, &x_j);
+        den = fr_mul(&den, &fr_sub(&x_j, &x_i));
+    }
+    fr_mul(&num, &fr_inverse(&den))
+}
+
+/// Combines the sub-shares a new worker received, keyed by the index of the old share each was
+/// split from, into its share of the new sharing. Exactly a qualified set of the old sharing must
+/// be given, and the same set as to `combine_commitments`.
+pub fn combine_sub_shares(sub_shares: &BTreeMap<usize, SecretKeyShare>) -> Result<SecretKeyShare> {
+    if sub_shares.is_empty() {
+        bail!("No sub-shares to combine")
+    }
+    let indices: Vec<usize> = sub_shares.keys().copied().collect();
+    let mut share = fr_from_u64(0);
+    for (&i, sub_share) in sub_shares {
+        let weighted = fr_mul(&lagrange_at_zero(i, &indices), &fr_from_share(sub_share)?);
+        share = fr_add(&share, &weighted);
+    }
+    share_from_fr(&share)
+}
+
+/// Combines the verification vectors of the sub-shares, keyed by the index of the old share each
+/// was split from, into the verification vector of the new sharing
+pub fn combine_commitments(commitments: &BTreeMap<usize, PublicKeySet>) -> Result<PublicKeySet> {
+    let Some(first) = commitments.values().next() else {
+        bail!("No verification vectors to combine")
+    };
+    let degree = first.threshold();
+    if commitments.values().any(|c| c.threshold() != degree) {
+        bail!("Sub-shares were split with different thresholds")
+    }
+
+    let indices: Vec<usize> = commitments.keys().copied().collect();
+    let mut coeffs: Vec<Option<blst::blst_p1>> = vec![None; degree + 1];
+    for (&i, commitment) in commitments {
+        let bytes = commitment.to_bytes();
+        if bytes.len() != (degree + 1) * G1_COMPRESSED_BYTES {
+            bail!("Verification vector {i} has the wrong length")
+        }
+        let lambda = lagrange_at_zero(i, &indices);
+        for (coeff, chunk) in coeffs.iter_mut().zip(bytes.chunks(G1_COMPRESSED_BYTES)) {
+            let weighted = g1_mul(&g1_from_bytes(chunk)?, &lambda);
+            *coeff = Some(match coeff {
+                Some(sum) => g1_add(sum, &weighted),
+                None => weighted,
+            });
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(coeffs.len() * G1_COMPRESSED_BYTES);
+    for coeff in coeffs.iter().flatten() {
+        let mut compressed = [0_u8; G1_COMPRESSED_BYTES];
+        unsafe { blst::blst_p1_compress(compressed.as_mut_ptr(), coeff) };
+        bytes.extend_from_slice(&compressed);
+    }
+    PublicKeySet::from_bytes(bytes).map_err(|e| anyhow!("Bad combined verification vector: {e:?}"))
+}
+
+#[cfg(test)]
+mod test_reshare {
+    use super::*;
+    use crate::crypto::bls_keys::{combine_partial_signatures, new_bls_key, split_bls_key};
+
+    #[test]
+    fn test_reshare_keeps_the_public_key() {
+        let sk = new_bls_key(0).secret_key();
+        let (pk_set, shares) = split_bls_key(&sk, 3, 2).unwrap();
+
+        // Shares 0 and 2 re-share 2-of-3 into 3-of-4
+        let mut commitments = BTreeMap::new();
+        let mut sub_shares = BTreeMap::new();
+        for i in [0, 2] {
+            let share_sk = blsttc::SecretKey::from_bytes(shares[i].to_bytes()).unwrap();
+            let (commitment, subs) = split_bls_key(&share_sk, 4, 3).unwrap();
+            assert_eq!(
+                commitment.public_key().to_bytes(),
+                pk_set.public_key_share(i).to_bytes()
+            );
+            commitments.insert(i, commitment);
+            sub_shares.insert(i, subs);
+        }
+        let new_pk_set = combine_commitments(&commitments).unwrap();
+        assert_eq!(new_pk_set.public_key(), pk_set.public_key());
+        assert_eq!(new_pk_set.threshold(), 2);
+
+        let msg = b"reshared";
+        let mut partials = BTreeMap::new();
+        for j in 0..4 {
+            let received = sub_shares
+                .iter()
+                .map(|(&i, subs)| (i, subs[j].clone()))
+                .collect();
+            let new_share = combine_sub_shares(&received).unwrap();
+            assert_eq!(new_share.public_key_share(), new_pk_set.public_key_share(j));
+            partials.insert(j, new_share.sign(msg));
+        }
+        partials.remove(&1);
+        let sig = combine_partial_signatures(&new_pk_set, &partials, msg).unwrap();
+        assert!(sk.public_key().verify(&sig, msg));
+
+        // Without a qualified set the new shares do not match the old key
+        commitments.remove(&2);
+        assert_ne!(
+            combine_commitments(&commitments).unwrap().public_key(),
+            pk_set.public_key()
+        );
+    }
+
+    #[test]
+    fn test_coefficients_must_be_in_g1() {
+        let sk = new_bls_key(0).secret_key();
+        assert!(g1_from_bytes(&sk.public_key().to_bytes()).is_ok());
+
+        let mut identity = [0_u8; G1_COMPRESSED_BYTES];
+        identity[0] = 0xc0;
+        assert!(g1_from_bytes(&identity).is_err());
+
+        // A point on the curve but outside the G1 subgroup, as almost every point is
+        let off_subgroup = (1_u8..=u8::MAX)
+            .map(|x| {
+                let mut bytes = [0_u8; G1_COMPRESSED_BYTES];
+                bytes[0] = 0x80;
+                bytes[G1_COMPRESSED_BYTES - 1] = x;
+                bytes
+            })
+            .find(|bytes| {
+                let mut affine = blst::blst_p1_affine::default();
+                let uncompressed = unsafe { blst::blst_p1_uncompress(&mut affine, bytes.as_ptr()) };
+                uncompressed == blst::BLST_ERROR::BLST_SUCCESS
+            })
+            .unwrap();
+        assert!(g1_from_bytes(&off_subgroup).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Sessions between two enclaves that attested to their ETH keys to each other. The session key
+//! is derived from the ECDH of the two keys, and messages are sealed with AES-256-GCM under it.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ecies::{PublicKey as EthPublicKey, SecretKey as EthSecretKey};
+use hkdf::Hkdf;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+const SESSION_INFO: &[u8] = b"SECURE_SIGNER_WORKER_SESSION";
+const IV_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+
+pub type SessionKey = Zeroizing<[u8; 32]>;
+
+/// Derives the key of the session between `leader_pk` and `worker_pk` from the ECDH of `sk`, held
+/// by one side, and `peer_pk`, the other side's key. Both sides derive the same key.
+pub fn session_key(
+    sk: &EthSecretKey,
+    peer_pk: &EthPublicKey,
+    leader_pk: &EthPublicKey,
+    worker_pk: &EthPublicKey,
+) -> Result<SessionKey> {
+    let mut shared = *peer_pk;
+    shared
+        .tweak_mul_assign(sk)
+        .map_err(|e| anyhow!("ECDH failed: {e:?}"))?;
+    let shared = Zeroizing::new(shared.serialize_compressed());
+
+    let info = [
+        SESSION_INFO,
+        &leader_pk.serialize_compressed(),
+        &worker_pk.serialize_compressed(),
+    ]
+    .concat();
+    let mut key = Zeroizing::new([0_u8; 32]);
+    Hkdf::<Sha256>::new(None, shared.as_slice())
+        .expand(&info, key.as_mut_slice())
+        .map_err(|e| anyhow!("Failed to derive the session key: {e:?}"))?;
+    Ok(key)
+}
+
+/// AES-256-GCM encrypts `plaintext` into `iv | tag | ciphertext`
+pub fn seal(key: &SessionKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut iv = [0_u8; IV_BYTES];
+    openssl::rand::rand_bytes(&mut iv)?;
+    let mut tag = [0_u8; TAG_BYTES];
+    let ct = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        key.as_slice(),
+        Some(&iv),
+        SESSION_INFO,
+        plaintext,
+        &mut tag,
+    )
+    .with_context(|| "Failed to seal message")?;
+    Ok([&iv[..], &tag, &ct].concat())
+}
+
+/// Decrypts what `seal` sealed under the same key
+pub fn open(key: &SessionKey, sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if sealed.len() < IV_BYTES + TAG_BYTES {
+        bail!("Sealed message is too short")
+    }
+    let (iv, rest) = sealed.split_at(IV_BYTES);
+    let (tag, ct) = rest.split_at(TAG_BYTES);
+    decrypt_aead(
+        Cipher::aes_256_gcm(),
+        key.as_slice(),
+        Some(iv),
+        SESSION_INFO,
+        ct,
+        tag,
+    )
+    .map(Zeroizing::new)
+    .with_context(|| "Failed to open message, it was sealed under another session")
+}
+
+#[cfg(test)]
+mod test_session {
+    use super::*;
+
+    #[test]
+    fn test_both_sides_derive_the_session_key() {
+        let (leader_sk, leader_pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
+        let (worker_sk, worker_pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
+        let leader_key = session_key(&leader_sk, &worker_pk, &leader_pk, &worker_pk).unwrap();
+        let worker_key = session_key(&worker_sk, &leader_pk, &leader_pk, &worker_pk).unwrap();
+        assert_eq!(leader_key, worker_key);
+
+        let sealed = seal(&leader_key, b"key share").unwrap();
+        assert_eq!(open(&worker_key, &sealed).unwrap().as_slice(), b"key share");
+
+        // Another pair of keys makes another session
+        let (other_sk, other_pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
+        let other_key = session_key(&other_sk, &worker_pk, &other_pk, &worker_pk).unwrap();
+        assert!(open(&other_key, &sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(open(&worker_key, &tampered).is_err());
+    }
+}
@@ -13,11 +13,11 @@ pub async fn handler(
         }
         Err(e) => {
             error!("eth_key_gen_with_blockhash_service() failed with: {}", e);
-            (
+            crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("eth_key_gen_with_blockhash_service failed: {:?}", e),
             )
-                .into_response()
+            .into_response()
         }
     }
 }
@@ -11,11 +11,11 @@ pub async fn handler(
 
         Err(e) => {
             error!("{:?}", e);
-            (
+            crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("{}", e),
             )
-                .into_response()
+            .into_response()
         }
     }
 }
@@ -23,8 +23,7 @@ pub fn attest_new_eth_key_with_blockhash(
     info!("attest_new_eth_key_with_blockhash()");
     // Generate a fresh SECP256K1 ETH keypair (saving ETH private key)
     let pk = crate::crypto::eth_keys::eth_key_gen()?;
-    let blockhash: String = crate::strip_0x_prefix!(blockhash);
-    let blockhash = hex::decode(blockhash)?;
+    let blockhash = crate::parse_hex(blockhash)?;
 
     if blockhash.len() != 32 {
         bail!("Bad blockhash")
@@ -71,9 +70,14 @@ pub async fn verify_and_sign_custody_received(
     let sk_share = verify_custody(&request.keygen_payload, &guardian_enclave_sk)?;
 
     // save the keyshare
-    crate::io::key_management::write_bls_key(
-        &hex::encode(sk_share.public_key_share().to_bytes()),
-        &hex::encode(sk_share.to_bytes()),
+    let pk_share_hex = hex::encode(sk_share.public_key_share().to_bytes());
+    crate::io::key_management::write_bls_key(&pk_share_hex, &hex::encode(sk_share.to_bytes()))?;
+    crate::io::key_metadata::write_bls_metadata(
+        &pk_share_hex,
+        &crate::io::key_metadata::KeyMetadata::new(
+            crate::io::key_metadata::KeyOrigin::Imported,
+            None,
+        ),
     )?;
 
     // return guardian enclave signature
@@ -97,6 +101,7 @@ pub fn verify_remote_attestation_evidence(
         raw_report: keygen_payload.intel_report.clone(),
         signed_report: keygen_payload.intel_sig.clone(),
         signing_cert: keygen_payload.intel_x509.clone(),
+        ..Default::default()
     };
 
     // Verify the evidence was signed from intel x509s
@@ -110,14 +115,14 @@ pub fn verify_remote_attestation_evidence(
         bail!("Invalid MRSIGNER value");
     }
 
-    let pk_set = PublicKeySet::from_bytes(hex::decode(&keygen_payload.bls_pub_key_set)?)?;
+    let pk_set = PublicKeySet::from_bytes(crate::parse_hex(&keygen_payload.bls_pub_key_set)?)?;
 
     let rec_payload = e.get_report_data()?;
     let mut dd_root: [u8; 32] = [0; 32];
-    dd_root.copy_from_slice(&hex::decode(&keygen_payload.deposit_data_root)?);
+    dd_root.copy_from_slice(&crate::parse_hex(&keygen_payload.deposit_data_root)?);
     let payload = crate::enclave::shared::build_validator_remote_attestation_payload(
         pk_set,
-        &hex::decode(&keygen_payload.signature)?.into(),
+        &crate::parse_hex(&keygen_payload.signature)?.into(),
         &dd_root,
         keygen_payload.bls_enc_priv_key_shares.clone(),
         keygen_payload
@@ -356,6 +361,7 @@ mod tests {
             raw_report: resp.intel_report,
             signed_report: resp.intel_sig,
             signing_cert: resp.intel_x509,
+            ..Default::default()
         };
 
         e.verify_intel_signing_certificate().unwrap();
@@ -0,0 +1,210 @@
+//! Prices signed by this enclave: a price is fetched from the configured HTTP source,
+//! canonicalized into a `PriceFeed` and signed with a designated ETH key over the EIP-191 digest of
+//! the keccak256 of its ABI encoding, so an on-chain consumer can recover the key and match it to
+//! the key's remote attestation evidence. Nothing is cached, so a source that fails is never
+//! answered with a stale price.
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use sha3::Digest;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Where prices are fetched from and the saved ETH key they are signed with
+#[derive(Clone, Debug)]
+pub struct DatafeedSource {
+    /// http(s) url with a `{pair}` placeholder
+    url: String,
+    /// The host of `url`, which signed prices name as their source
+    host: String,
+    /// The JSON pointer of the price in the source's answer
+    price_pointer: String,
+    /// Compressed ETH public key without the 0x prefix
+    eth_pk_hex: String,
+}
+
+impl DatafeedSource {
+    /// Checks the url has a `{pair}` placeholder and the ETH key is saved in this enclave
+    pub fn new(url: &str, price_pointer: &str, eth_pk_hex: &String) -> Result<Self> {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            bail!("Bad datafeed url, expected http(s)://: {url}")
+        }
+        if !url.contains("{pair}") {
+            bail!("Bad datafeed url, expected a {{pair}} placeholder: {url}")
+        }
+        let host = reqwest::Url::parse(&url.replace("{pair}", "ETHUSD"))
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .with_context(|| format!("Bad datafeed url: {url}"))?;
+        if !price_pointer.is_empty() && !price_pointer.starts_with('/') {
+            bail!("Bad datafeed price pointer, expected a JSON pointer: {price_pointer}")
+        }
+        let eth_pk = crate::crypto::eth_keys::parse_eth_pk_hex(eth_pk_hex)?;
+        let eth_pk_hex = crate::crypto::eth_keys::eth_pk_to_hex(&eth_pk);
+        if !crate::io::key_management::eth_key_exists(&eth_pk_hex) {
+            bail!("Datafeed key 0x{eth_pk_hex} is not saved in this enclave")
+        }
+        Ok(DatafeedSource {
+            url: url.to_string(),
+            host,
+            price_pointer: price_pointer.to_string(),
+            eth_pk_hex,
+        })
+    }
+}
+
+static DATAFEED_SOURCE: OnceLock<DatafeedSource> = OnceLock::new();
+
+/// Sets the source `/datafeed/v1/price` fetches from. Must be called before serving requests.
+pub fn set_datafeed_source(source: DatafeedSource) -> Result<()> {
+    if DATAFEED_SOURCE.set(source).is_err() {
+        bail!("Datafeed source already set");
+    }
+    Ok(())
+}
+
+/// The configured source, if any
+pub fn datafeed_source() -> Option<&'static DatafeedSource> {
+    DATAFEED_SOURCE.get()
+}
+
+/// Returned when the source did not answer with a price
+#[derive(Debug)]
+pub struct UpstreamError {
+    pub message: String,
+}
+
+impl std::fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Datafeed source failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+/// Upper-cases a pair like `ethusd`, refusing anything but 2 to 20 letters and digits
+pub fn sanitize_pair(pair: &str) -> Result<String> {
+    if !(2..=20).contains(&pair.len()) || !pair.chars().all(|c| c.is_ascii_alphanumeric()) {
+        bail!("Bad pair, expected 2 to 20 letters and digits like ETHUSD: {pair}")
+    }
+    Ok(pair.to_ascii_uppercase())
+}
+
+/// Parses a non-negative decimal like `3012.5` into an integer scaled by 10^`decimals`. Digits
+/// past `decimals` are truncated, so the same price always canonicalizes the same way.
+pub fn parse_price(price: &str, decimals: u8) -> Result<u128> {
+    let (whole, fraction) = price.split_once('.').unwrap_or((price, ""));
+    if whole.is_empty() || !(whole.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit()) {
+        bail!("Bad price {price}, expected a non-negative decimal")
+    }
+    let fraction: String = fraction
+        .chars()
+        .chain(std::iter::repeat('0'))
+        .take(decimals as usize)
+        .collect();
+    format!("{whole}{fraction}")
+        .parse::<u128>()
+        .with_context(|| format!("Price {price} is too large"))
+}
+
+/// `abi.encode(pair, price, decimals, source, timestamp)`
+pub fn abi_encode(feed: &crate::enclave::types::PriceFeed) -> Result<Vec<u8>> {
+    let price = ethers::types::U256::from_dec_str(&feed.price)
+        .with_context(|| format!("Bad price {}", feed.price))?;
+    Ok(ethers::abi::encode(&[
+        ethers::abi::Token::String(feed.pair.clone()),
+        ethers::abi::Token::Uint(price),
+        ethers::abi::Token::Uint(feed.decimals.into()),
+        ethers::abi::Token::String(feed.source.clone()),
+        ethers::abi::Token::Uint(feed.timestamp.into()),
+    ]))
+}
+
+/// Fetches the price of `pair` from `source` as a decimal string, failing with `UpstreamError`
+async fn fetch_price(source: &DatafeedSource, pair: &str) -> Result<String, UpstreamError> {
+    let upstream = |message: String| UpstreamError { message };
+    let url = source.url.replace("{pair}", pair);
+    let resp = reqwest::Client::new()
+        .get(&url)
+        .timeout(Duration::from_millis(crate::constants::DATAFEED_TIMEOUT_MS))
+        .send()
+        .await
+        .map_err(|e| upstream(format!("{e}")))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        return Err(upstream(format!(
+            "{status}: {}",
+            resp.text().await.unwrap_or_default()
+        )));
+    }
+    let body: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| upstream(format!("Bad JSON: {e}")))?;
+    match body.pointer(&source.price_pointer) {
+        Some(serde_json::Value::String(price)) => Ok(price.clone()),
+        Some(serde_json::Value::Number(price)) => Ok(price.to_string()),
+        _ => Err(upstream(format!("No price at {}", source.price_pointer))),
+    }
+}
+
+/// Fetches the price of `pair` from `source` and signs it with the datafeed key. The price is
+/// fetched anew for every call, and any failure of the source is an `UpstreamError`.
+pub async fn signed_price(
+    source: &DatafeedSource,
+    pair: &str,
+) -> Result<crate::enclave::types::SignedPriceResponse> {
+    let pair = sanitize_pair(pair)?;
+    let raw = fetch_price(source, &pair).await?;
+    let decimals = crate::constants::DATAFEED_PRICE_DECIMALS;
+    let price = parse_price(&raw, decimals).map_err(|e| UpstreamError {
+        message: format!("{e:#}"),
+    })?;
+    let payload = crate::enclave::types::PriceFeed {
+        pair,
+        price: price.to_string(),
+        decimals,
+        source: source.host.clone(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    };
+    let encoded = abi_encode(&payload)?;
+    let digest = ethers::utils::hash_message(sha3::Keccak256::digest(&encoded));
+    let (sig, recovery_id) =
+        crate::crypto::eth_keys::sign_secp256k1(&source.eth_pk_hex, digest.as_bytes())?;
+    let eth_pk = crate::crypto::eth_keys::eth_pk_from_hex(&source.eth_pk_hex)?;
+    info!(
+        "Signed {} at {} from {}",
+        payload.pair, payload.price, payload.source
+    );
+    Ok(crate::enclave::types::SignedPriceResponse {
+        payload,
+        encoded: crate::to_0x_hex(&encoded),
+        digest: crate::to_0x_hex(digest.as_bytes()),
+        eth_pk_hex: format!("0x{}", source.eth_pk_hex),
+        address: crate::crypto::eth_keys::eth_pk_to_address(&eth_pk),
+        signature: crate::enclave::types::Secp256k1SignResponse::new(&sig, &recovery_id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prices_canonicalize_to_scaled_integers() {
+        assert_eq!(parse_price("3012.5", 8).unwrap(), 301_250_000_000);
+        assert_eq!(parse_price("3012", 8).unwrap(), 301_200_000_000);
+        assert_eq!(parse_price("0.123456789", 8).unwrap(), 12_345_678);
+        for bad in ["", "-1", "1e3", ".5", "1.2.3", "NaN", "１"] {
+            assert!(parse_price(bad, 8).is_err(), "{bad}");
+        }
+        assert!(parse_price(&"9".repeat(40), 8).is_err());
+
+        assert_eq!(sanitize_pair("ethUSD").unwrap(), "ETHUSD");
+        for bad in ["E", "ETH/USD", "ETH USD", "../x"] {
+            assert!(sanitize_pair(bad).is_err(), "{bad}");
+        }
+    }
+}
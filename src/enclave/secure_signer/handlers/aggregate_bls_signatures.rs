@@ -0,0 +1,95 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::crypto::bls_variant::{BlsVariant, SelectedVariant};
+use crate::enclave::types::{
+    BlsAggregateErrorDetails, BlsAggregateRequest, BlsAggregateRequestInner, BlsAggregateResponse,
+    ErrorResponse,
+};
+
+/// Parses the key and signature of one entry and checks the signature verifies over `msg`
+fn verify_entry(
+    entry: &BlsAggregateRequestInner,
+    msg: &[u8],
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let pk = crate::parse_hex(&entry.bls_pk_hex)?;
+    if pk.len() != SelectedVariant::PUB_KEY_BYTES {
+        anyhow::bail!(
+            "BLS public key must be {} bytes",
+            SelectedVariant::PUB_KEY_BYTES
+        )
+    }
+    let sig = crate::parse_hex(&entry.signature_hex)?;
+    if sig.len() != SelectedVariant::SIG_BYTES {
+        anyhow::bail!("BLS signature must be {} bytes", SelectedVariant::SIG_BYTES)
+    }
+    if !SelectedVariant::verify(&pk, msg, &sig) {
+        anyhow::bail!(
+            "Signature does not verify against {}",
+            crate::to_0x_hex(&pk)
+        )
+    }
+    Ok((pk, sig))
+}
+
+/// Aggregates BLS signatures by different keys over the same message. Every signature is
+/// verified first, and the index of the first one that fails is returned with a 400.
+pub async fn handler(Json(req): Json<BlsAggregateRequest>) -> axum::response::Response {
+    info!("aggregate_bls_signatures()");
+    let msg = match crate::parse_hex(&req.msg_hex) {
+        Ok(msg) => msg,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad msg_hex: {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    if req.signatures.is_empty() {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::BAD_REQUEST,
+            "No signatures to aggregate",
+        )
+        .into_response();
+    }
+
+    let mut pks = vec![];
+    let mut sigs = vec![];
+    for (index, entry) in req.signatures.iter().enumerate() {
+        match verify_entry(entry, &msg) {
+            Ok((pk, sig)) => {
+                pks.push(pk);
+                sigs.push(sig);
+            }
+            Err(e) => {
+                error!("aggregate_bls_signatures() bad signature {index}: {:?}", e);
+                return ErrorResponse::new(
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    format!("{}", e),
+                )
+                .with_details(BlsAggregateErrorDetails { index })
+                .into_response();
+            }
+        }
+    }
+
+    match crate::crypto::bls_keys::aggregate_uniform_bls_sigs(&pks, &sigs) {
+        Ok((sig, pk)) => (
+            axum::http::status::StatusCode::OK,
+            Json(BlsAggregateResponse {
+                signature: crate::to_0x_hex(&sig),
+                pubkey: crate::to_0x_hex(&pk),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("aggregate_bls_signatures() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Aggregation failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
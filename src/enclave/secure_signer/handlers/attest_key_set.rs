@@ -0,0 +1,65 @@
+use anyhow::{bail, Result};
+use axum::{extract::Query, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, KeySetAttestationQuery, KeySetAttestationResponse};
+use crate::io::remote_attestation::{
+    check_attestation, AttestationEvidence, EvidenceKind, KeySetBinding,
+};
+
+/// Attests to every BLS and ETH key the enclave holds with one quote over the digest of the
+/// sorted key lists. Returns the lists and their count so verifiers can recompute the digest.
+pub async fn handler(Query(query): Query<KeySetAttestationQuery>) -> axum::response::Response {
+    info!("attest_key_set()");
+    crate::enclave::shared::metrics::record_attestation_request(query.kind);
+    if let Err(e) = check_attestation(query.kind) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    let nonce = match parse_nonce(&query.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad nonce: {:#}", e),
+            )
+            .into_response()
+        }
+    };
+
+    match attest_held_keys(query.kind, &nonce).await {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!("attest_key_set() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Remote attestation failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
+
+fn parse_nonce(nonce: &Option<String>) -> Result<Vec<u8>> {
+    let nonce = match nonce {
+        Some(nonce) => crate::parse_hex(nonce)?,
+        None => vec![],
+    };
+    if nonce.len() > crate::constants::MAX_ATTESTATION_NONCE_BYTES {
+        bail!(
+            "nonce exceeds {} bytes",
+            crate::constants::MAX_ATTESTATION_NONCE_BYTES
+        )
+    }
+    Ok(nonce)
+}
+
+async fn attest_held_keys(kind: EvidenceKind, nonce: &[u8]) -> Result<KeySetAttestationResponse> {
+    let key_set = KeySetBinding::held_keys(nonce)?;
+    let report_data = key_set.report_data()?;
+    let evidence = AttestationEvidence::generate(kind, &report_data).await?;
+    Ok(KeySetAttestationResponse {
+        key_set,
+        report_data: crate::to_0x_hex(report_data),
+        evidence,
+    })
+}
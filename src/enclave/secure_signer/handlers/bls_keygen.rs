@@ -1,22 +1,54 @@
+use axum::extract::State;
 use axum::response::IntoResponse;
 use axum::Json;
 use log::{error, info};
 
 /// Generates, saves, and performs remote attestation on a new ETH key. Returns a `KeyGenResponse` on success.
-pub async fn handler() -> axum::response::Response {
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    req: Option<Json<crate::enclave::types::KeyGenRequest>>,
+) -> axum::response::Response {
     info!("eth_bls_gen_service()");
-    match crate::enclave::secure_signer::attest_new_bls_key() {
+    let label = req.and_then(|Json(req)| req.label);
+    if let Err(resp) = crate::enclave::shared::handlers::check_label(label.as_deref()) {
+        return resp;
+    }
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(1) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+    if let Err(e) = crate::io::remote_attestation::check_attestation(
+        crate::io::remote_attestation::EvidenceKind::Epid,
+    ) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    match crate::enclave::secure_signer::attest_new_bls_key(state.slashing_backend.as_ref(), label)
+    {
         Ok((evidence, eth_pk)) => {
-            let resp = crate::enclave::types::KeyGenResponse::from_bls_key(eth_pk, evidence);
+            let mut resp = crate::enclave::types::KeyGenResponse::from_bls_key(eth_pk, evidence);
+            match crate::crypto::bls_variant::signing_pk_hex(&resp.pk_hex) {
+                Ok(signing_pk) => resp.signing_pk = signing_pk,
+                Err(e) => {
+                    error!("bls_key_gen_service() failed with: {}", e);
+                    return crate::enclave::types::ErrorResponse::new(
+                        axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("bls_key_gen_service failed: {:?}", e),
+                    )
+                    .into_response();
+                }
+            }
+            crate::enclave::shared::metrics::record_bls_keys_created(
+                crate::enclave::shared::metrics::KeySource::Keygen,
+                1,
+            );
             (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
         }
         Err(e) => {
             error!("bls_key_gen_service() failed with: {}", e);
-            (
+            crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("bls_key_gen_service failed: {:?}", e),
             )
-                .into_response()
+            .into_response()
         }
     }
 }
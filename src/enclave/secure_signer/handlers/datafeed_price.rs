@@ -0,0 +1,35 @@
+use axum::{extract::Path, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::ErrorResponse;
+
+/// Fetches the price of `pair` from the configured source and returns it signed by the datafeed
+/// key. Answers 502 with the source's error if it fails, as prices are never cached, and 503 if
+/// Secure-Signer was started without a datafeed.
+pub async fn handler(Path(pair): Path<String>) -> axum::response::Response {
+    info!("datafeed_price()");
+    if let Err(e) = crate::enclave::secure_signer::datafeed::sanitize_pair(&pair) {
+        return ErrorResponse::new(axum::http::status::StatusCode::BAD_REQUEST, format!("{e}"))
+            .into_response();
+    }
+    let Some(source) = crate::enclave::secure_signer::datafeed::datafeed_source() else {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+            "No datafeed configured, start Secure-Signer with --datafeed-url and --datafeed-eth-pk",
+        )
+        .into_response();
+    };
+
+    match crate::enclave::secure_signer::datafeed::signed_price(source, &pair).await {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!("datafeed_price() failed with: {:?}", e);
+            let status = if e.is::<crate::enclave::secure_signer::datafeed::UpstreamError>() {
+                axum::http::status::StatusCode::BAD_GATEWAY
+            } else {
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR
+            };
+            ErrorResponse::new(status, format!("{:#}", e)).into_response()
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{
+    DeleteKeyStatus, DeleteKeysRequest, DeleteKeysResponse, DeleteKeysResponseInner,
+};
+use crate::io::key_management;
+
+/// Deletes each requested ETH key, overwriting the secret before unlinking
+pub async fn handler(Json(req): Json<DeleteKeysRequest>) -> axum::response::Response {
+    info!("delete_eth_keys()");
+    let data = req
+        .pubkeys
+        .iter()
+        .map(|pk| {
+            let eth_pk_hex = match crate::crypto::eth_keys::parse_eth_pk_hex(pk) {
+                Ok(pk) => crate::crypto::eth_keys::eth_pk_to_hex(&pk),
+                Err(e) => {
+                    return DeleteKeysResponseInner {
+                        status: DeleteKeyStatus::Error,
+                        message: Some(format!("Bad eth_pk_hex, {:?}", e)),
+                    }
+                }
+            };
+
+            if !key_management::eth_key_exists(&eth_pk_hex) {
+                return DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::NotFound,
+                    message: None,
+                };
+            }
+
+            match crate::crypto::eth_keys::delete_eth_key(&eth_pk_hex) {
+                Ok(()) => DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::Deleted,
+                    message: None,
+                },
+                Err(e) => {
+                    error!("delete_eth_keys() failed for {eth_pk_hex} with: {:?}", e);
+                    DeleteKeysResponseInner {
+                        status: DeleteKeyStatus::Error,
+                        message: Some(format!("Failed to delete key: {:?}", e)),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    (
+        axum::http::status::StatusCode::OK,
+        Json(DeleteKeysResponse { data }),
+    )
+        .into_response()
+}
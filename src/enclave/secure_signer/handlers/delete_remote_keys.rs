@@ -0,0 +1,55 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{
+    DeleteKeyStatus, DeleteKeysRequest, DeleteKeysResponse, DeleteKeysResponseInner,
+};
+use crate::io::remote_keys;
+
+/// Unregisters each requested remote key, reporting `not_found` per key rather than failing
+/// the whole batch
+pub async fn handler(Json(req): Json<DeleteKeysRequest>) -> axum::response::Response {
+    info!("delete_remote_keys()");
+    let data = req
+        .pubkeys
+        .iter()
+        .map(|pk| {
+            let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(pk) {
+                Ok(pk) => pk,
+                Err(e) => {
+                    return DeleteKeysResponseInner {
+                        status: DeleteKeyStatus::Error,
+                        message: Some(format!("Bad bls_pk_hex, {:?}", e)),
+                    }
+                }
+            };
+
+            if !remote_keys::remote_key_exists(&bls_pk_hex) {
+                return DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::NotFound,
+                    message: None,
+                };
+            }
+
+            match remote_keys::delete_remote_key(&bls_pk_hex) {
+                Ok(()) => DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::Deleted,
+                    message: None,
+                },
+                Err(e) => {
+                    error!("delete_remote_keys() failed for {bls_pk_hex} with: {:?}", e);
+                    DeleteKeysResponseInner {
+                        status: DeleteKeyStatus::Error,
+                        message: Some(format!("Failed to delete remote key: {:?}", e)),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    (
+        axum::http::status::StatusCode::OK,
+        Json(DeleteKeysResponse { data }),
+    )
+        .into_response()
+}
@@ -0,0 +1,55 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use log::{error, info};
+
+/// Derives, saves, and performs remote attestation on the EIP-2334 signing key of the requested
+/// validator index. Returns a `DeriveBlsKeyResponse` on success.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<crate::enclave::types::DeriveBlsKeyRequest>,
+) -> axum::response::Response {
+    info!("derive_bls_key()");
+    if let Err(resp) = crate::enclave::shared::handlers::check_label(req.label.as_deref()) {
+        return resp;
+    }
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(1) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+    if let Err(e) = crate::io::remote_attestation::check_attestation(
+        crate::io::remote_attestation::EvidenceKind::Epid,
+    ) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    match crate::enclave::secure_signer::attest_derived_bls_key(
+        state.slashing_backend.as_ref(),
+        req.index,
+        req.label,
+    ) {
+        Ok(Some((evidence, pk, derivation_path))) => {
+            let resp = crate::enclave::types::DeriveBlsKeyResponse {
+                pk_hex: format!("0x{}", pk.to_hex()),
+                derivation_path,
+                evidence,
+            };
+            crate::enclave::shared::metrics::record_bls_keys_created(
+                crate::enclave::shared::metrics::KeySource::Keygen,
+                1,
+            );
+            (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
+        }
+        Ok(None) => crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::CONFLICT,
+            format!("The key at index {} was already derived", req.index),
+        )
+        .into_response(),
+        Err(e) => {
+            error!("derive_bls_key() failed with: {}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("derive_bls_key failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use log::{error, info};
+
+/// Derives, saves, and performs remote attestation on the key at the next index of the enclave's
+/// sealed master seed, so that restoring the seed restores every key. Returns a
+/// `DeriveBlsKeyResponse` on success.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    req: Option<Json<crate::enclave::types::KeyGenRequest>>,
+) -> axum::response::Response {
+    info!("deterministic_bls_keygen()");
+    let label = req.and_then(|Json(req)| req.label);
+    if let Err(resp) = crate::enclave::shared::handlers::check_label(label.as_deref()) {
+        return resp;
+    }
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(1) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+    if let Err(e) = crate::io::remote_attestation::check_attestation(
+        crate::io::remote_attestation::EvidenceKind::Epid,
+    ) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    match crate::enclave::secure_signer::attest_deterministic_bls_key(
+        state.slashing_backend.as_ref(),
+        label,
+    ) {
+        Ok((evidence, pk, derivation_path)) => {
+            let resp = crate::enclave::types::DeriveBlsKeyResponse {
+                pk_hex: format!("0x{}", pk.to_hex()),
+                derivation_path,
+                evidence,
+            };
+            crate::enclave::shared::metrics::record_bls_keys_created(
+                crate::enclave::shared::metrics::KeySource::Keygen,
+                1,
+            );
+            (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
+        }
+        Err(e) => {
+            error!("deterministic_bls_keygen() failed with: {}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("deterministic_bls_keygen failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
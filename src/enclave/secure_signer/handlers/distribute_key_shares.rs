@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{DistributeKeySharesResponse, ErrorResponse};
+
+/// Delivers the shares of a split key to the registered workers they were encrypted to, and
+/// reports how each delivery went. Shares already acknowledged are not sent again, so a failed
+/// delivery is retried by calling this again.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("distribute_key_shares()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    let mut key = match crate::io::threshold_keys::read_threshold_key(&bls_pk_hex) {
+        Ok(key) => key,
+        Err(_) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Public key not found: 0x{bls_pk_hex}"),
+            )
+            .into_response()
+        }
+    };
+
+    match state.leader.distribute_shares(&mut key).await {
+        Ok(data) => (
+            axum::http::status::StatusCode::OK,
+            Json(DistributeKeySharesResponse { data }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("distribute_key_shares() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to distribute 0x{bls_pk_hex}: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
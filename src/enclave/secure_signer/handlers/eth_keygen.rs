@@ -3,20 +3,35 @@ use axum::Json;
 use log::{error, info};
 
 /// Generates, saves, and performs remote attestation on a new ETH key. Returns a `KeyGenResponse` on success.
-pub async fn handler() -> axum::response::Response {
+pub async fn handler(
+    req: Option<Json<crate::enclave::types::KeyGenRequest>>,
+) -> axum::response::Response {
     info!("eth_key_gen_service()");
-    match crate::enclave::secure_signer::attest_new_eth_key() {
+    let label = req.and_then(|Json(req)| req.label);
+    if let Err(resp) = crate::enclave::shared::handlers::check_label(label.as_deref()) {
+        return resp;
+    }
+    if let Err(e) = crate::io::key_management::check_eth_key_quota(1) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+    if let Err(e) = crate::io::remote_attestation::check_attestation(
+        crate::io::remote_attestation::EvidenceKind::Epid,
+    ) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    match crate::enclave::secure_signer::attest_new_eth_key(label) {
         Ok((evidence, eth_pk)) => {
             let resp = crate::enclave::types::KeyGenResponse::from_eth_key(eth_pk, evidence);
+            crate::enclave::shared::metrics::record_eth_key_created();
             (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
         }
         Err(e) => {
             error!("eth_key_gen_service() failed with: {}", e);
-            (
+            crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("eth_key_gen_service failed: {:?}", e),
             )
-                .into_response()
+            .into_response()
         }
     }
 }
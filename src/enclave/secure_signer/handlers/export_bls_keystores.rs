@@ -0,0 +1,148 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ExportKeystoresRequest, ImportKeystoresRequest};
+
+/// Checks that the keys are moving to an ETH key held by an enclave build the measurement policy
+/// allows, refusing others with 403 and naming their measurement. The allowed measurements are
+/// the operator's, never the caller's.
+fn verify_encrypting_pk(
+    req: &ExportKeystoresRequest,
+) -> Result<ecies::PublicKey, axum::response::Response> {
+    let envelope_pk = match crate::crypto::eth_keys::eth_pk_from_hex(&req.encrypting_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("export_bls_keystores() bad encrypting key: {:?}", e);
+            return Err(crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad encrypting_pk_hex: {:?}", e),
+            )
+            .into_response());
+        }
+    };
+    // ETH keygen evidence commits to the compressed public key
+    let res =
+        crate::io::remote_attestation::check_measurement_policy(&req.evidence).and_then(|_| {
+            req.evidence
+                .verify_report_data(&envelope_pk.serialize_compressed())
+        });
+    match res {
+        Ok(()) => Ok(envelope_pk),
+        Err(e) => {
+            error!("export_bls_keystores() bad receiver evidence: {:?}", e);
+            Err(crate::enclave::shared::handlers::bad_evidence_response(&e))
+        }
+    }
+}
+
+/// Exports the requested BLS keys as EIP-2335 keystores whose passwords are ECIES encrypted to
+/// another enclave's ETH key, along with their slashing protection history. The response can be
+/// posted as is to the other enclave's `/eth/v1/keystores`.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<ExportKeystoresRequest>,
+) -> axum::response::Response {
+    info!("export_bls_keystores()");
+    if req.attest {
+        if let Err(e) = crate::io::remote_attestation::check_attestation(
+            crate::io::remote_attestation::EvidenceKind::Epid,
+        ) {
+            return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+        }
+    }
+    let envelope_pk = match verify_encrypting_pk(&req) {
+        Ok(pk) => pk,
+        Err(resp) => return resp,
+    };
+
+    let mut pubkeys = vec![];
+    for pk_hex in req.pubkeys.iter() {
+        let pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(pk_hex) {
+            Ok(pk_hex) => pk_hex,
+            Err(e) => {
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    format!("Bad pubkey {pk_hex}: {:?}", e),
+                )
+                .into_response()
+            }
+        };
+        if !crate::io::key_management::bls_key_exists(&pk_hex) {
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Unknown pubkey 0x{pk_hex}"),
+            )
+            .into_response();
+        }
+        pubkeys.push(pk_hex);
+    }
+
+    let mut keystores = vec![];
+    let mut passwords = vec![];
+    let mut labels = vec![];
+    for pk_hex in pubkeys.iter() {
+        match crate::enclave::secure_signer::export_bls_keystore(pk_hex, &envelope_pk) {
+            Ok((keystore, ct_password)) => {
+                keystores.push(keystore);
+                passwords.push(ct_password);
+                labels.push(
+                    crate::io::key_metadata::read_bls_metadata(pk_hex)
+                        .ok()
+                        .and_then(|m| m.label)
+                        .unwrap_or_default(),
+                );
+            }
+            Err(e) => {
+                error!("export_bls_keystores() failed with: {:?}", e);
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to export 0x{pk_hex}: {:?}", e),
+                )
+                .into_response();
+            }
+        }
+    }
+
+    let slashing_protection = match crate::eth2::slash_protection::SlashingProtectionDB::export(
+        state.slashing_backend.as_ref(),
+        state.genesis_validators_root,
+        Some(pubkeys),
+    )
+    .and_then(|db| Ok(serde_json::to_string(&db)?))
+    {
+        Ok(interchange) => interchange,
+        Err(e) => {
+            error!("export_bls_keystores() failed with: {:?}", e);
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to export slashing protection: {:?}", e),
+            )
+            .into_response();
+        }
+    };
+
+    let mut resp = ImportKeystoresRequest {
+        keystores,
+        passwords,
+        encrypting_pk_hex: Some(req.encrypting_pk_hex),
+        slashing_protection: Some(slashing_protection),
+        // Carry labels over to the other enclave
+        labels: labels.iter().any(|l| !l.is_empty()).then_some(labels),
+        evidence: None,
+    };
+    if req.attest {
+        match crate::io::remote_attestation::AttestationEvidence::new(&resp.transfer_report_data())
+        {
+            Ok(evidence) => resp.evidence = Some(evidence),
+            Err(e) => {
+                error!("export_bls_keystores() failed to attest: {:?}", e);
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to attest the export: {:?}", e),
+                )
+                .into_response();
+            }
+        }
+    }
+    (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+}
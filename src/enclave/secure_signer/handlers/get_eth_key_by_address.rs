@@ -0,0 +1,51 @@
+use axum::{extract::Path, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, ListKeysResponseInner};
+use crate::io::key_management;
+
+/// Resolves an address back to the generated ETH key it was derived from
+pub async fn handler(Path(address): Path<String>) -> axum::response::Response {
+    info!("get_eth_key_by_address()");
+    let address_hex = match crate::normalize_hex(&address) {
+        Ok(address_hex) if address_hex.len() == 40 => address_hex,
+        _ => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad address {address}"),
+            )
+            .into_response();
+        }
+    };
+
+    let pk_hexes = match key_management::list_eth_keys() {
+        Ok(pk_hexes) => pk_hexes,
+        // No ETH keys were generated yet
+        Err(_) => vec![],
+    };
+    for pk_hex in pk_hexes {
+        let pk = match crate::crypto::eth_keys::parse_eth_pk_hex(&pk_hex) {
+            Ok(pk) => pk,
+            Err(e) => {
+                error!("Skipping bad ETH key file {pk_hex}: {:?}", e);
+                continue;
+            }
+        };
+        let pk_address = crate::crypto::eth_keys::eth_pk_to_address(&pk);
+        if pk_address.to_lowercase() == format!("0x{address_hex}") {
+            let resp = ListKeysResponseInner {
+                pubkey: format!("0x{pk_hex}"),
+                derivation_path: None,
+                address: Some(pk_address),
+                metadata: crate::io::key_metadata::read_eth_metadata(&pk_hex).ok(),
+            };
+            return (axum::http::status::StatusCode::OK, Json(resp)).into_response();
+        }
+    }
+
+    ErrorResponse::new(
+        axum::http::status::StatusCode::NOT_FOUND,
+        format!("No ETH key for address {address}"),
+    )
+    .into_response()
+}
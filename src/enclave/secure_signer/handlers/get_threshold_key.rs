@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::info;
+
+use crate::enclave::types::{
+    DistributionState, ErrorResponse, ShareInventory, ThresholdKeyInventory,
+};
+
+/// Shows which worker holds each share of a split key, merging the delivery record saved by
+/// `distribute` with the heartbeats and partial signatures the leader saw from each worker
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("get_threshold_key()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    let key = match crate::io::threshold_keys::read_threshold_key(&bls_pk_hex) {
+        Ok(key) => key,
+        Err(_) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Public key not found: 0x{bls_pk_hex}"),
+            )
+            .into_response()
+        }
+    };
+
+    let shares: Vec<ShareInventory> = key
+        .shares
+        .iter()
+        .map(|share| {
+            let worker = crate::io::workers::worker_id(&share.worker_pk_hex)
+                .and_then(|id| crate::io::workers::read_worker(&id))
+                .ok();
+            let healthy = worker
+                .as_ref()
+                .map_or(false, |w| state.leader.is_healthy(&w.id));
+            ShareInventory {
+                index: share.index,
+                pk_share_hex: share.pk_share_hex.clone(),
+                worker_pk_hex: share.worker_pk_hex.clone(),
+                last_heartbeat: worker.as_ref().map(|w| w.last_seen),
+                worker_id: worker.map(|w| w.id),
+                acknowledged: share.acknowledged,
+                delivered_at: share.delivered_at,
+                last_partial_signature: state.leader.last_partial_at(&share.pk_share_hex),
+                healthy,
+            }
+        })
+        .collect();
+    let acknowledged = shares.iter().filter(|share| share.acknowledged).count();
+    let distribution = if acknowledged == 0 {
+        DistributionState::Undistributed
+    } else if acknowledged < shares.len() {
+        DistributionState::Partial
+    } else {
+        DistributionState::Distributed
+    };
+    let signable = state.leader.available_shares(&key).len() >= key.threshold;
+
+    (
+        axum::http::status::StatusCode::OK,
+        Json(ThresholdKeyInventory {
+            bls_pk_hex: key.bls_pk_hex,
+            n: shares.len(),
+            threshold: key.threshold,
+            verification_vector: key.verification_vector,
+            state: distribution,
+            signable,
+            shares,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,173 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+use zeroize::Zeroizing;
+
+use crate::enclave::types::{
+    ErrorResponse, ImportKeystoreStatus, ImportKeystoresRequest, ImportKeystoresResponse,
+    ImportKeystoresResponseInner,
+};
+
+/// Recovers the plaintext keystore password, decrypting it with the enclave ETH key if requested
+fn decrypt_password(
+    password: &String,
+    encrypting_pk_hex: &Option<String>,
+) -> anyhow::Result<Zeroizing<String>> {
+    match encrypting_pk_hex {
+        None => Ok(Zeroizing::new(password.to_string())),
+        Some(pk_hex) => {
+            let ct_password_bytes = crate::parse_hex(password)?;
+            let password_bytes = crate::crypto::eth_keys::envelope_decrypt_from_saved_sk(
+                pk_hex,
+                &ct_password_bytes,
+            )?;
+            // The plaintext buffer moves into the String, which is wiped once the key is imported
+            Ok(Zeroizing::new(String::from_utf8(password_bytes)?))
+        }
+    }
+}
+
+/// Checks the keystores come from an enclave build the measurement policy allows, refusing
+/// others with 403 and naming their measurement
+fn check_sender(
+    evidence: &crate::io::remote_attestation::AttestationEvidence,
+    req: &ImportKeystoresRequest,
+) -> Result<(), axum::response::Response> {
+    let res = crate::io::remote_attestation::check_measurement_policy(evidence)
+        .and_then(|_| evidence.verify_report_data(&req.transfer_report_data()));
+    match res {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            error!("import_bls_keystores() bad sender evidence: {:?}", e);
+            Err(crate::enclave::shared::handlers::bad_evidence_response(&e))
+        }
+    }
+}
+
+/// Imports a batch of EIP-2335 keystores. A keystore that fails to import is reported with an
+/// `error` status without failing the rest of the batch. The optional `slashing_protection`
+/// interchange file is imported first so the keys never sign without their history. Keystores
+/// exported with `evidence` are only imported from enclaves the measurement policy allows.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<ImportKeystoresRequest>,
+) -> axum::response::Response {
+    info!("import_bls_keystores()");
+    if req.keystores.len() != req.passwords.len() {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!(
+                "Received {} keystores but {} passwords",
+                req.keystores.len(),
+                req.passwords.len()
+            ),
+        )
+        .into_response();
+    }
+    if let Some(labels) = &req.labels {
+        if labels.len() != req.keystores.len() {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!(
+                    "Received {} keystores but {} labels",
+                    req.keystores.len(),
+                    labels.len()
+                ),
+            )
+            .into_response();
+        }
+        // Empty labels stand for unlabeled keys
+        for label in labels.iter().filter(|l| !l.is_empty()) {
+            if let Err(resp) = crate::enclave::shared::handlers::check_label(Some(label)) {
+                return resp;
+            }
+        }
+    }
+
+    if let Some(evidence) = &req.evidence {
+        if let Err(resp) = check_sender(evidence, &req) {
+            return resp;
+        }
+    }
+
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(req.keystores.len()) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+
+    if let Some(interchange) = &req.slashing_protection {
+        let db = match crate::eth2::slash_protection::SlashingProtectionDB::from_interchange_str(
+            interchange,
+        )
+        .and_then(|db| {
+            db.check_genesis_validators_root(state.genesis_validators_root)?;
+            Ok(db)
+        }) {
+            Ok(db) => db,
+            Err(e) => {
+                error!("import_bls_keystores() bad slashing_protection: {:?}", e);
+                return ErrorResponse::new(
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    format!("Bad slashing_protection: {:?}", e),
+                )
+                .into_response();
+            }
+        };
+        if let Err(e) = db.import(
+            state.slashing_backend.as_ref(),
+            crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB,
+        ) {
+            error!("import_bls_keystores() failed with: {:?}", e);
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to import slashing protection: {:?}", e),
+            )
+            .into_response();
+        }
+    }
+
+    let data = req
+        .keystores
+        .iter()
+        .zip(req.passwords.iter())
+        .enumerate()
+        .map(|(i, (keystore, password))| {
+            let label = req
+                .labels
+                .as_ref()
+                .map(|labels| labels[i].clone())
+                .filter(|l| !l.is_empty());
+            let res = decrypt_password(password, &req.encrypting_pk_hex).and_then(|password| {
+                crate::enclave::secure_signer::import_bls_keystore(
+                    state.slashing_backend.as_ref(),
+                    keystore,
+                    &password,
+                    label,
+                )
+            });
+            match res {
+                Ok(status) => ImportKeystoresResponseInner {
+                    status,
+                    message: None,
+                },
+                Err(e) => {
+                    error!("import_bls_keystores() failed with: {:?}", e);
+                    ImportKeystoresResponseInner {
+                        status: ImportKeystoreStatus::Error,
+                        message: Some(format!("{:?}", e)),
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    crate::enclave::shared::metrics::record_bls_keys_created(
+        crate::enclave::shared::metrics::KeySource::Import,
+        data.iter()
+            .filter(|inner| inner.status == ImportKeystoreStatus::Imported)
+            .count(),
+    );
+
+    (
+        axum::http::status::StatusCode::OK,
+        Json(ImportKeystoresResponse { data }),
+    )
+        .into_response()
+}
@@ -0,0 +1,65 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{
+    ImportKeystoreStatus, ImportKeystoresResponseInner, ImportRemoteKeysRequest,
+    ImportRemoteKeysResponse,
+};
+use crate::io::{key_management, remote_keys};
+
+/// Registers each pubkey with the url of the worker signer holding its secret
+pub async fn handler(Json(req): Json<ImportRemoteKeysRequest>) -> axum::response::Response {
+    info!("import_remote_keys()");
+    let data = req
+        .remote_keys
+        .iter()
+        .map(|rk| {
+            let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&rk.pubkey) {
+                Ok(pk) => pk,
+                Err(e) => {
+                    return ImportKeystoresResponseInner {
+                        status: ImportKeystoreStatus::Error,
+                        message: Some(format!("Bad bls_pk_hex, {:?}", e)),
+                    }
+                }
+            };
+
+            if !(rk.url.starts_with("http://") || rk.url.starts_with("https://")) {
+                return ImportKeystoresResponseInner {
+                    status: ImportKeystoreStatus::Error,
+                    message: Some(format!("Bad url, expected http(s)://: {}", rk.url)),
+                };
+            }
+
+            // A key is either held locally or by exactly one worker
+            if remote_keys::remote_key_exists(&bls_pk_hex)
+                || key_management::bls_key_exists(&bls_pk_hex)
+            {
+                return ImportKeystoresResponseInner {
+                    status: ImportKeystoreStatus::Duplicate,
+                    message: None,
+                };
+            }
+
+            match remote_keys::write_remote_key(&bls_pk_hex, &rk.url) {
+                Ok(()) => ImportKeystoresResponseInner {
+                    status: ImportKeystoreStatus::Imported,
+                    message: None,
+                },
+                Err(e) => {
+                    error!("import_remote_keys() failed for {bls_pk_hex} with: {:?}", e);
+                    ImportKeystoresResponseInner {
+                        status: ImportKeystoreStatus::Error,
+                        message: Some(format!("Failed to register remote key: {:?}", e)),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    (
+        axum::http::status::StatusCode::OK,
+        Json(ImportRemoteKeysResponse { data }),
+    )
+        .into_response()
+}
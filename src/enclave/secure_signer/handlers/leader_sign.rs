@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SignatureFormat, SignatureResponse};
+
+/// Signs a Web3Signer sign request with a key split across worker enclaves. The request is
+/// forwarded to the workers holding its shares, which enforce slashing protection on them, and
+/// their partial signatures are recombined into a signature under the key. Answers 503 if fewer
+/// workers than the threshold return a valid partial signature, and 412 if the workers'
+/// slashing protection watermarks diverge from a block or attestation request.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    headers: HeaderMap,
+    Json(mut req): Json<crate::eth2::eth_signing::BLSSignMsg>,
+) -> axum::response::Response {
+    info!("leader_sign()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    let accept = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok());
+    let Some(format) = SignatureFormat::from_accept(accept) else {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_ACCEPTABLE,
+            "Unsupported Accept header, expected application/json or text/plain",
+        )
+        .into_response();
+    };
+    let key = match crate::io::threshold_keys::read_threshold_key(&bls_pk_hex) {
+        Ok(key) => key,
+        Err(_) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Public key not found: 0x{bls_pk_hex}"),
+            )
+            .into_response()
+        }
+    };
+
+    // Workers must sign the same root, so settle the fork_info before forwarding the request
+    if let Some(violation) = crate::enclave::shared::resolve_fork_info(&mut req, &state) {
+        error!("{violation}");
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::PRECONDITION_FAILED,
+            violation,
+        )
+        .into_response();
+    }
+    let signing_root = req.to_signing_root(Some(state.genesis_fork_version));
+    if let Some(provided) = req.provided_signing_root() {
+        if provided != signing_root {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                "Provided signingRoot does not match the computed signing root",
+            )
+            .with_details(crate::enclave::types::SigningRootMismatch::new(
+                provided,
+                signing_root,
+            ))
+            .into_response();
+        }
+    }
+
+    // Held until the workers confirmed the request, so a concurrent request for the same key
+    // is checked against their updated watermarks
+    let _guard = state.sign_locks.lock(&bls_pk_hex).await;
+    let sig = match state.leader.threshold_sign(&key, &req, signing_root).await {
+        Ok(sig) => sig,
+        Err(e) => {
+            error!("leader_sign() failed with: {:#}", e);
+            let status = if e.is::<crate::enclave::secure_signer::leader::NotEnoughPartials>() {
+                axum::http::status::StatusCode::SERVICE_UNAVAILABLE
+            } else if e.is::<crate::enclave::secure_signer::leader::WatermarkDivergence>() {
+                axum::http::status::StatusCode::PRECONDITION_FAILED
+            } else {
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR
+            };
+            return ErrorResponse::new(status, format!("Signing operation failed: {:#}", e))
+                .into_response();
+        }
+    };
+
+    let response = SignatureResponse::new(&sig.to_bytes());
+    match format {
+        SignatureFormat::Json => {
+            (axum::http::status::StatusCode::OK, Json(response)).into_response()
+        }
+        SignatureFormat::Text => (
+            axum::http::status::StatusCode::OK,
+            [(
+                axum::http::header::CONTENT_TYPE,
+                "text/plain; charset=utf-8",
+            )],
+            response.signature,
+        )
+            .into_response(),
+    }
+}
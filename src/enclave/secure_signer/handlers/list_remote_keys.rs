@@ -0,0 +1,34 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ListRemoteKeysResponse, RemoteKey};
+
+/// Lists the keys registered to be served by worker signers
+pub async fn handler() -> axum::response::Response {
+    info!("list_remote_keys()");
+    match crate::io::remote_keys::list_remote_keys() {
+        Ok(keys) => {
+            let data = keys
+                .into_iter()
+                .map(|(pk_hex, url)| RemoteKey {
+                    pubkey: format!("0x{pk_hex}"),
+                    url,
+                    readonly: false,
+                })
+                .collect();
+            (
+                axum::http::status::StatusCode::OK,
+                Json(ListRemoteKeysResponse { data }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("list_remote_keys() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list remote keys: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
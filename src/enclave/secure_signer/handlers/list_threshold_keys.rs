@@ -0,0 +1,42 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, ListThresholdKeysResponse, ThresholdKeyInfo};
+
+/// Lists the keys split across this leader's workers, with the shares their healthy workers hold
+/// and whether that is enough to sign with them now
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("list_threshold_keys()");
+    match crate::io::threshold_keys::list_threshold_keys() {
+        Ok(keys) => {
+            let data = keys
+                .iter()
+                .map(|key| {
+                    let available_shares = state.leader.available_shares(key);
+                    ThresholdKeyInfo {
+                        bls_pk_hex: key.bls_pk_hex.clone(),
+                        n: key.shares.len(),
+                        threshold: key.threshold,
+                        signable: available_shares.len() >= key.threshold,
+                        available_shares,
+                    }
+                })
+                .collect();
+            (
+                axum::http::status::StatusCode::OK,
+                Json(ListThresholdKeysResponse { data }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("list_threshold_keys() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list threshold keys: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ListWorkersResponse, Worker, WorkerStatus};
+use crate::io::remote_attestation::check_loaded_measurement_policy;
+
+/// Lists the registered worker enclaves with their heartbeat health, rechecking each one's
+/// measurements against the loaded measurement policy
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("list_workers()");
+    match crate::io::workers::list_workers() {
+        Ok(workers) => {
+            let data = workers
+                .into_iter()
+                .map(|w| {
+                    let status = match check_loaded_measurement_policy(&w.verdict) {
+                        Ok(()) => WorkerStatus::Verified,
+                        Err(_) => WorkerStatus::Revoked,
+                    };
+                    let health = state.leader.worker_health(&w.id);
+                    Worker {
+                        healthy: health.is_healthy(state.leader.max_missed_heartbeats()),
+                        consecutive_failures: health.consecutive_failures,
+                        latency_ms: health.latency.map(|l| l.as_millis() as u64),
+                        id: w.id,
+                        url: w.url,
+                        ecies_pubkey: w.ecies_pubkey,
+                        status,
+                        mrenclave: w.verdict.mrenclave,
+                        mrsigner: w.verdict.mrsigner,
+                        isv_svn: w.verdict.isv_svn,
+                        registered_at: w.registered_at,
+                        last_seen: w.last_seen,
+                    }
+                })
+                .collect();
+            (
+                axum::http::status::StatusCode::OK,
+                Json(ListWorkersResponse { data }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("list_workers() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list workers: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
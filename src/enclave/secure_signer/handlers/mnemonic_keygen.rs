@@ -0,0 +1,55 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use log::{error, info};
+
+/// Generates a 24-word mnemonic that becomes the enclave's derivation seed, then derives, saves,
+/// and performs remote attestation on the key of validator 0. The mnemonic is only ever returned
+/// in this `MnemonicKeyGenResponse`.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    req: Option<Json<crate::enclave::types::KeyGenRequest>>,
+) -> axum::response::Response {
+    info!("mnemonic_keygen()");
+    let label = req.and_then(|Json(req)| req.label);
+    if let Err(resp) = crate::enclave::shared::handlers::check_label(label.as_deref()) {
+        return resp;
+    }
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(1) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+    if let Err(e) = crate::io::remote_attestation::check_attestation(
+        crate::io::remote_attestation::EvidenceKind::Epid,
+    ) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    match crate::enclave::secure_signer::attest_new_mnemonic(state.slashing_backend.as_ref(), label)
+    {
+        Ok(Some((mnemonic, evidence, pk, derivation_path))) => {
+            let resp = crate::enclave::types::MnemonicKeyGenResponse {
+                pk_hex: format!("0x{}", pk.to_hex()),
+                derivation_path,
+                mnemonic,
+                evidence,
+            };
+            crate::enclave::shared::metrics::record_bls_keys_created(
+                crate::enclave::shared::metrics::KeySource::Keygen,
+                1,
+            );
+            (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
+        }
+        Ok(None) => crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::CONFLICT,
+            "The enclave already holds a derivation seed",
+        )
+        .into_response(),
+        Err(e) => {
+            error!("mnemonic_keygen() failed with: {}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("mnemonic_keygen failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -1,3 +1,39 @@
+pub mod aggregate_bls_signatures;
+pub mod attest_key_set;
 pub mod bls_keygen;
+pub mod datafeed_price;
+pub mod delete_eth_keys;
+pub mod delete_remote_keys;
+pub mod derive_bls_key;
+pub mod deterministic_bls_keygen;
+pub mod distribute_key_shares;
 pub mod eth_keygen;
+pub mod export_bls_keystores;
+pub mod get_eth_key_by_address;
+pub mod get_threshold_key;
+pub mod import_bls_keystores;
+pub mod import_remote_keys;
+pub mod leader_sign;
+pub mod list_remote_keys;
+pub mod list_threshold_keys;
+pub mod list_workers;
+pub mod mnemonic_keygen;
+pub mod recover_bls_keys;
+pub mod register_worker;
+pub mod reload_measurement_policy;
+pub mod remote_attestation;
+pub mod reshare_key_shares;
+pub mod sign_secp256k1;
+pub mod split_bls_key;
+pub mod update_key_label;
 pub mod validator_deposit;
+pub mod verify_attestation;
+pub mod verify_bls_signature;
+pub mod worker_handshake;
+pub mod worker_handshake_challenge;
+pub mod worker_keyshare;
+pub mod worker_reshare_combine;
+pub mod worker_reshare_revoke;
+pub mod worker_reshare_subshares;
+pub mod worker_status;
+pub mod worker_watermark;
@@ -0,0 +1,70 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use log::{error, info};
+
+/// Recovers the plaintext mnemonic with the enclave ETH key and checks that it is well formed
+fn decrypt_mnemonic(req: &crate::enclave::types::RecoverBlsKeysRequest) -> anyhow::Result<String> {
+    let ct_mnemonic_bytes = crate::parse_hex(&req.encrypted_mnemonic)?;
+    let mnemonic_bytes = crate::crypto::eth_keys::envelope_decrypt_from_saved_sk(
+        &req.encrypting_pk_hex,
+        &ct_mnemonic_bytes,
+    )?;
+    let mnemonic = String::from_utf8(mnemonic_bytes)?;
+    crate::crypto::bls_keys::mnemonic_to_seed(&mnemonic, "")?;
+    Ok(mnemonic)
+}
+
+/// Decrypts the mnemonic with the enclave ETH key and re-derives the keys of validators
+/// 0..`num_keys`, saving any that are missing. Returns the recovered keys as a `ListKeysResponse`.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<crate::enclave::types::RecoverBlsKeysRequest>,
+) -> axum::response::Response {
+    info!("recover_bls_keys()");
+    let mnemonic = match decrypt_mnemonic(&req) {
+        Ok(mnemonic) => mnemonic,
+        Err(e) => {
+            error!("recover_bls_keys() bad mnemonic: {:?}", e);
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad encrypted_mnemonic: {:?}", e),
+            )
+            .into_response();
+        }
+    };
+
+    // Keys that are already saved count against the quota too, so a recovery near it may need
+    // the quota raised
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(req.num_keys as usize) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
+
+    match crate::enclave::secure_signer::recover_bls_keys(
+        state.slashing_backend.as_ref(),
+        &mnemonic,
+        req.num_keys,
+    ) {
+        Ok(Some(keys)) => {
+            let (pks, paths): (Vec<String>, Vec<String>) = keys.into_iter().unzip();
+            let mut resp = crate::enclave::types::ListKeysResponse::new(pks);
+            for (key, path) in resp.data.iter_mut().zip(paths) {
+                key.derivation_path = Some(path);
+            }
+            (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+        }
+        Ok(None) => crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::CONFLICT,
+            "The enclave holds a derivation seed from another mnemonic",
+        )
+        .into_response(),
+        Err(e) => {
+            error!("recover_bls_keys() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("recover_bls_keys failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, RegisterWorkerRequest, RegisterWorkerResponse};
+
+/// Registers a worker enclave with this leader. The worker's evidence must come from an enclave
+/// build the measurement policy allows and commit to its ECIES public key, else it is refused
+/// with 403 and nothing is saved. Registering again refreshes the worker's url and last seen time.
+pub async fn handler(Json(req): Json<RegisterWorkerRequest>) -> axum::response::Response {
+    info!("register_worker()");
+    if !(req.url.starts_with("http://") || req.url.starts_with("https://")) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("Bad url, expected http(s)://: {}", req.url),
+        )
+        .into_response();
+    }
+    let ecies_pk = match crate::crypto::eth_keys::parse_eth_pk_hex(&req.ecies_pubkey) {
+        Ok(pk) => crate::to_0x_hex(pk.serialize_compressed()),
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad ecies_pubkey, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let verdict = match crate::enclave::secure_signer::handshake::verify_peer(
+        &ecies_pk,
+        &req.nonce,
+        &req.evidence,
+    ) {
+        Ok(verdict) => verdict,
+        Err(e) => {
+            error!("register_worker() bad worker evidence: {:?}", e);
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::FORBIDDEN,
+                format!("Bad evidence: {:#}", e),
+            )
+            .into_response();
+        }
+    };
+
+    match crate::io::workers::register_worker(&req.url, &ecies_pk, verdict) {
+        Ok(worker) => {
+            info!("Registered worker {} at {}", worker.id, worker.url);
+            (
+                axum::http::status::StatusCode::OK,
+                Json(RegisterWorkerResponse { id: worker.id }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("register_worker() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to register worker: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
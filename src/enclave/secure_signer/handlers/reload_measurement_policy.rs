@@ -0,0 +1,21 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::ErrorResponse;
+
+/// Rereads the measurement policy file Secure-Signer was started with and returns the loaded
+/// policy. If the file fails to load the current policy is kept.
+pub async fn handler() -> axum::response::Response {
+    info!("reload_measurement_policy()");
+    match crate::io::remote_attestation::reload_measurement_policy() {
+        Ok(policy) => (axum::http::status::StatusCode::OK, Json(policy)).into_response(),
+        Err(e) => {
+            error!("reload_measurement_policy() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to reload the measurement policy: {:#}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{bail, Result};
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{
+    ErrorResponse, RemoteAttestationQuery, RemoteAttestationRequest, RemoteAttestationResponse,
+};
+use crate::io::remote_attestation::{
+    cache_evidence, cached_evidence, check_attestation, AttestationEvidence, EvidenceKind,
+    KeyBinding,
+};
+
+/// Attests to saved BLS and ETH keys with an EPID or DCAP quote over
+/// SHA256(eth_pk || bls_pk || nonce). Returns the hashed fields alongside the evidence so
+/// verifiers can recompute the report data. Evidence is reused for the cache TTL unless
+/// `fresh=true` is passed.
+pub async fn handler(
+    Path(kind): Path<EvidenceKind>,
+    Query(query): Query<RemoteAttestationQuery>,
+    Json(req): Json<RemoteAttestationRequest>,
+) -> axum::response::Response {
+    info!("remote_attestation()");
+    crate::enclave::shared::metrics::record_attestation_request(kind);
+    if let Err(e) = check_attestation(kind) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    let (binding, report_data) = match bind_saved_keys(&req) {
+        Ok(binding) => binding,
+        Err(e) => {
+            error!("Bad remote attestation request: {:?}", e);
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("{:#}", e),
+            )
+            .into_response();
+        }
+    };
+
+    let cached = if query.fresh {
+        None
+    } else {
+        cached_evidence(&binding, kind)
+    };
+    let generated = match cached {
+        Some(cached) => Ok((cached.evidence, cached.generated_at)),
+        None => AttestationEvidence::generate(kind, &report_data)
+            .await
+            .map(|evidence| {
+                let generated_at = cache_evidence(binding.clone(), evidence.clone());
+                (evidence, generated_at)
+            }),
+    };
+
+    match generated {
+        Ok((evidence, generated_at)) => {
+            let resp = RemoteAttestationResponse {
+                binding,
+                report_data: crate::to_0x_hex(report_data),
+                evidence,
+                generated_at,
+            };
+            (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => {
+            error!("remote_attestation() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Remote attestation failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
+
+/// Binds the requested keys, after checking the enclave holds them, so the quote only ever
+/// vouches for keys the enclave itself will use
+fn bind_saved_keys(req: &RemoteAttestationRequest) -> Result<(KeyBinding, [u8; 32])> {
+    let eth_pubkey = match &req.eth_pubkey {
+        Some(pk_hex) => {
+            let pk = crate::crypto::eth_keys::parse_eth_pk_hex(pk_hex)?;
+            if !crate::io::key_management::eth_key_exists(&crate::crypto::eth_keys::eth_pk_to_hex(
+                &pk,
+            )) {
+                bail!("ETH key {} is not held by this enclave", pk_hex)
+            }
+            Some(crate::to_0x_hex(pk.serialize_compressed()))
+        }
+        None => None,
+    };
+    let bls_pubkey = match &req.bls_pubkey {
+        Some(pk_hex) => {
+            let bls_pk_hex = crate::crypto::bls_keys::sanitize_bls_pk_hex(pk_hex)?;
+            if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+                bail!("BLS key {} is not held by this enclave", pk_hex)
+            }
+            Some(format!("0x{bls_pk_hex}"))
+        }
+        None => None,
+    };
+    let nonce = match &req.nonce {
+        Some(nonce) => crate::parse_hex(nonce)?,
+        None => vec![],
+    };
+    if nonce.len() > crate::constants::MAX_ATTESTATION_NONCE_BYTES {
+        bail!(
+            "nonce exceeds {} bytes",
+            crate::constants::MAX_ATTESTATION_NONCE_BYTES
+        )
+    }
+
+    let binding = KeyBinding {
+        eth_pubkey,
+        bls_pubkey,
+        nonce: crate::to_0x_hex(nonce),
+    };
+    let report_data = binding.report_data()?;
+    Ok((binding, report_data))
+}
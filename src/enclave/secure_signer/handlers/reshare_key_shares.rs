@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, ReshareKeyRequest};
+
+/// Moves a split key to a new set of workers, or to a new threshold, without reconstructing it.
+/// The workers holding the old shares split them into sub-shares for the new workers, which
+/// combine them into new shares of the same key. Without `workers` the new shares go to
+/// registered workers. Answers 503 if an old holder does not report its watermarks, too few split
+/// their shares or a new worker does not acknowledge its share, in which case the old shares keep
+/// signing, and also if an old holder does not delete its old share once the new shares are in
+/// use.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<ReshareKeyRequest>,
+) -> axum::response::Response {
+    info!("reshare_key_shares()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    let n = match (req.n, req.workers.len()) {
+        (None, 0) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                "n is required when no workers are given",
+            )
+            .into_response()
+        }
+        (Some(n), 0) => n,
+        (None, given) => given,
+        (Some(n), given) if n == given => n,
+        (Some(n), given) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("n is {n} but {given} workers were given"),
+            )
+            .into_response()
+        }
+    };
+    if req.threshold < 2 || req.threshold > n {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("threshold must be between 2 and n {n}"),
+        )
+        .into_response();
+    }
+
+    // Signing with the old shares while they are re-shared could sign below the watermarks the
+    // new shares start from
+    let _guard = state.sign_locks.lock(&bls_pk_hex).await;
+    let key = match crate::io::threshold_keys::read_threshold_key(&bls_pk_hex) {
+        Ok(key) => key,
+        Err(_) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Public key not found: 0x{bls_pk_hex}"),
+            )
+            .into_response()
+        }
+    };
+    let workers = if req.workers.is_empty() {
+        state.leader.pick_workers(n)
+    } else {
+        state.leader.registered_workers(&req.workers)
+    };
+    let workers = match workers {
+        Ok(workers) => workers,
+        Err(e) => {
+            error!("reshare_key_shares() bad workers: {:?}", e);
+            let status = if req.workers.is_empty()
+                && !e.is::<crate::enclave::secure_signer::leader::NotEnoughWorkers>()
+            {
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                axum::http::status::StatusCode::BAD_REQUEST
+            };
+            return ErrorResponse::new(status, format!("{:#}", e)).into_response();
+        }
+    };
+
+    match state.leader.reshare(&key, req.threshold, workers).await {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) if e.is::<crate::enclave::secure_signer::leader::RevocationFailed>() => {
+            error!("reshare_key_shares() failed to revoke old shares: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+                format!("0x{bls_pk_hex}: {:#}", e),
+            )
+            .into_response()
+        }
+        Err(e) => {
+            error!("reshare_key_shares() failed with: {:?}", e);
+            let status = if e.is::<crate::enclave::secure_signer::leader::ReshareFailed>() {
+                axum::http::status::StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR
+            };
+            ErrorResponse::new(
+                status,
+                format!("Failed to re-share 0x{bls_pk_hex}: {:#}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, Secp256k1SignRequest, Secp256k1SignResponse};
+
+/// Signs a 32B digest with a generated ETH key. Returns a recoverable ECDSA signature. The datafeed
+/// key is refused, as a digest signed here could pass for a signed price.
+pub async fn handler(
+    Path(eth_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<Secp256k1SignRequest>,
+) -> axum::response::Response {
+    info!("sign_secp256k1()");
+    let eth_pk_hex = match crate::crypto::eth_keys::parse_eth_pk_hex(&eth_pk_hex) {
+        Ok(pk) => crate::crypto::eth_keys::eth_pk_to_hex(&pk),
+        Err(e) => {
+            error!("Bad eth_pk_hex: {:?}", e);
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad eth_pk_hex: {:?}", e),
+            )
+            .into_response();
+        }
+    };
+
+    let datafeed_pk_hex = state
+        .config
+        .datafeed
+        .eth_pk
+        .as_ref()
+        .and_then(|pk| crate::crypto::eth_keys::parse_eth_pk_hex(pk).ok())
+        .map(|pk| crate::crypto::eth_keys::eth_pk_to_hex(&pk));
+    if datafeed_pk_hex.as_ref() == Some(&eth_pk_hex) {
+        error!("Refused to sign with the datafeed key: {eth_pk_hex}");
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::FORBIDDEN,
+            format!("0x{eth_pk_hex} signs prices only, at /datafeed/v1/price"),
+        )
+        .into_response();
+    }
+
+    let digest = match crate::parse_hex(&req.msg_hex) {
+        Ok(digest) if digest.len() == 32 => digest,
+        _ => {
+            error!("Bad msg_hex: {}", req.msg_hex);
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                "msg_hex must be a hex-encoded 32 byte digest",
+            )
+            .into_response();
+        }
+    };
+
+    if !crate::io::key_management::eth_key_exists(&eth_pk_hex) {
+        error!("Unknown ETH public key: {eth_pk_hex}");
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{eth_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    match crate::crypto::eth_keys::sign_secp256k1(&eth_pk_hex, &digest) {
+        Ok((sig, recovery_id)) => {
+            if let Err(e) = crate::io::key_metadata::record_eth_key_use(&eth_pk_hex) {
+                error!("Failed to record use of {eth_pk_hex}: {:?}", e);
+            }
+            (
+                axum::http::status::StatusCode::OK,
+                Json(Secp256k1SignResponse::new(&sig, &recovery_id)),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("sign_secp256k1() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Signing failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
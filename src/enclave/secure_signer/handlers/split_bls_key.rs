@@ -0,0 +1,94 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SplitBlsKeyRequest};
+
+/// Splits a saved BLS key into `threshold`-of-`n` Shamir shares for worker enclaves, each share
+/// ECIES encrypted to its worker's ETH key. Without `workers` the shares go to registered
+/// workers. The key stays saved, so this enclave can keep acting as the leader that recombines
+/// the workers' partial signatures.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<SplitBlsKeyRequest>,
+) -> axum::response::Response {
+    info!("split_bls_key()");
+    let pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&req.bls_pk_hex) {
+        Ok(pk_hex) => pk_hex,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex: {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    let n = match (req.n, req.workers.len()) {
+        (None, 0) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                "n is required when no workers are given",
+            )
+            .into_response()
+        }
+        (Some(n), 0) => n,
+        (None, given) => given,
+        (Some(n), given) if n == given => n,
+        (Some(n), given) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("n is {n} but {given} workers were given"),
+            )
+            .into_response()
+        }
+    };
+    if req.threshold < 2 || req.threshold > n {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("threshold must be between 2 and n {n}"),
+        )
+        .into_response();
+    }
+    let worker_pks = if req.workers.is_empty() {
+        state.leader.pick_workers(n).and_then(|workers| {
+            workers
+                .iter()
+                .map(|w| crate::crypto::eth_keys::parse_eth_pk_hex(&w.ecies_pubkey))
+                .collect()
+        })
+    } else {
+        crate::enclave::secure_signer::verify_worker_keys(&req.workers, &req.mrenclave)
+    };
+    let worker_pks = match worker_pks {
+        Ok(pks) => pks,
+        Err(e) => {
+            error!("split_bls_key() bad workers: {:?}", e);
+            let status = if req.workers.is_empty()
+                && !e.is::<crate::enclave::secure_signer::leader::NotEnoughWorkers>()
+            {
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                axum::http::status::StatusCode::BAD_REQUEST
+            };
+            return ErrorResponse::new(status, format!("{:?}", e)).into_response();
+        }
+    };
+    if !crate::io::key_management::bls_key_exists(&pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Unknown pubkey 0x{pk_hex}"),
+        )
+        .into_response();
+    }
+
+    match crate::enclave::secure_signer::split_bls_key(&pk_hex, req.threshold, worker_pks) {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!("split_bls_key() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to split 0x{pk_hex}: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
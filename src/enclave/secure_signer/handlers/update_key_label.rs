@@ -0,0 +1,55 @@
+use axum::{extract::Path, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, UpdateKeyLabelRequest};
+use crate::io::{key_management, key_metadata};
+
+/// Replaces the label of a saved BLS or ETH key, returning its updated `KeyMetadata`
+pub async fn handler(
+    Path(pubkey): Path<String>,
+    Json(req): Json<UpdateKeyLabelRequest>,
+) -> axum::response::Response {
+    info!("update_key_label()");
+    if let Err(resp) = crate::enclave::shared::handlers::check_label(req.label.as_deref()) {
+        return resp;
+    }
+
+    let bls_pk_hex = crate::crypto::bls_keys::sanitize_bls_pk_hex(&pubkey).ok();
+    let eth_pk_hex = crate::crypto::eth_keys::parse_eth_pk_hex(&pubkey)
+        .ok()
+        .map(|pk| crate::crypto::eth_keys::eth_pk_to_hex(&pk));
+    let res = match (bls_pk_hex, eth_pk_hex) {
+        (Some(pk_hex), _) if key_management::bls_key_exists(&pk_hex) => {
+            key_metadata::set_bls_label(&pk_hex, req.label)
+        }
+        (_, Some(pk_hex)) if key_management::eth_key_exists(&pk_hex) => {
+            key_metadata::set_eth_label(&pk_hex, req.label)
+        }
+        (None, None) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad pubkey {pubkey}"),
+            )
+            .into_response()
+        }
+        _ => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Unknown pubkey {pubkey}"),
+            )
+            .into_response()
+        }
+    };
+
+    match res {
+        Ok(metadata) => (axum::http::status::StatusCode::OK, Json(metadata)).into_response(),
+        Err(e) => {
+            error!("update_key_label() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update label: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -11,20 +11,20 @@ pub async fn handler(
     let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
         Ok(pk) => pk,
         Err(e) => {
-            return (
+            return crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::BAD_REQUEST,
                 format!("Bad bls_pk_hex, {:?}", e),
             )
-                .into_response();
+            .into_response();
         }
     };
 
     if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
-        return (
+        return crate::enclave::types::ErrorResponse::new(
             axum::http::status::StatusCode::PRECONDITION_FAILED,
             format!("This validator key does not exist"),
         )
-            .into_response();
+        .into_response();
     }
 
     info!("Deposit request for validator pubkey: {bls_pk_hex}");
@@ -37,11 +37,11 @@ pub async fn handler(
     ) {
         Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
         Err(e) => {
-            return (
+            return crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Deposit signing operation failed: {:?}", e),
             )
-                .into_response();
+            .into_response();
         }
     }
 }
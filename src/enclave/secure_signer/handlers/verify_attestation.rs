@@ -0,0 +1,15 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::io::remote_attestation::AttestationEvidence;
+
+/// Verifies attestation evidence from another enclave, such as one exporting keys to this one.
+/// Always returns an `AttestationVerdict`, whose `valid` says whether the evidence can be trusted.
+pub async fn handler(Json(evidence): Json<AttestationEvidence>) -> axum::response::Response {
+    info!("verify_attestation()");
+    let verdict = evidence.verify();
+    if let Some(e) = &verdict.error {
+        error!("Rejected attestation evidence: {}", e);
+    }
+    (axum::http::status::StatusCode::OK, Json(verdict)).into_response()
+}
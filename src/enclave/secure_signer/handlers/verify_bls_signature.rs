@@ -0,0 +1,53 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::crypto::bls_variant::{BlsVariant, SelectedVariant};
+use crate::enclave::types::{BlsVerifyRequest, BlsVerifyResponse, ErrorResponse};
+
+/// Decodes `hex_str` into exactly `len` bytes
+fn decode_fixed(hex_str: &str, len: usize, what: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = crate::parse_hex(hex_str)?;
+    if bytes.len() != len {
+        anyhow::bail!("{what} must be {len} bytes")
+    }
+    Ok(bytes)
+}
+
+fn decode_request(req: &BlsVerifyRequest) -> anyhow::Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let pk = decode_fixed(
+        &req.bls_pk_hex,
+        SelectedVariant::PUB_KEY_BYTES,
+        "BLS public key",
+    )?;
+    let msg = crate::parse_hex(&req.msg_hex)?;
+    let sig = decode_fixed(
+        &req.signature_hex,
+        SelectedVariant::SIG_BYTES,
+        "BLS signature",
+    )?;
+    Ok((pk, msg, sig))
+}
+
+/// Checks a BLS signature the way the signer's own signatures verify. Malformed hex is a 400,
+/// while keys and signatures that decode but are invalid or fail the subgroup checks are
+/// reported as `valid: false`.
+pub async fn handler(Json(req): Json<BlsVerifyRequest>) -> axum::response::Response {
+    info!("verify_bls_signature()");
+    match decode_request(&req) {
+        Ok((pk, msg, sig)) => (
+            axum::http::status::StatusCode::OK,
+            Json(BlsVerifyResponse {
+                valid: crate::crypto::bls_keys::bls_verify_subgroup_checked(&pk, &msg, &sig),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("verify_bls_signature() bad request: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("{}", e),
+            )
+            .into_response()
+        }
+    }
+}
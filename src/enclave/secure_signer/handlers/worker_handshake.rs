@@ -0,0 +1,32 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, HandshakeRequest, HandshakeResponse};
+
+/// Finishes a leader's attestation handshake with this worker. The leader's evidence must answer
+/// a challenge from `/worker/v1/handshake/challenge` and come from an enclave build this worker's
+/// measurement policy allows, else it is refused with 403. Key shares sealed under the session
+/// are then accepted until it expires.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<HandshakeRequest>,
+) -> axum::response::Response {
+    info!("worker_handshake()");
+    match state.worker.finish_handshake(&req) {
+        Ok(ttl) => (
+            axum::http::status::StatusCode::OK,
+            Json(HandshakeResponse {
+                expires_in_secs: ttl.as_secs(),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("worker_handshake() refused leader: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::FORBIDDEN,
+                format!("Bad leader evidence: {:#}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, HandshakeChallengeRequest};
+
+/// Starts a leader's attestation handshake with this worker: returns evidence that the worker
+/// holds the requested ETH key, bound to the leader's nonce, and a challenge the leader binds its
+/// own evidence to
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<HandshakeChallengeRequest>,
+) -> axum::response::Response {
+    info!("worker_handshake_challenge()");
+    if let Err(e) = crate::io::remote_attestation::check_attestation(req.kind) {
+        return crate::enclave::shared::handlers::attestation_unavailable_response(&e);
+    }
+    let worker_pk =
+        match crate::crypto::eth_keys::parse_eth_pk_hex(&req.worker_pubkey).and_then(|pk| {
+            crate::crypto::eth_keys::parse_eth_pk_hex(&req.leader_pubkey)?;
+            crate::parse_hex(&req.nonce)?;
+            Ok(pk)
+        }) {
+            Ok(pk) => crate::crypto::eth_keys::eth_pk_to_hex(&pk),
+            Err(e) => {
+                return ErrorResponse::new(
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    format!("Bad handshake request, {:?}", e),
+                )
+                .into_response()
+            }
+        };
+    if !crate::io::key_management::eth_key_exists(&worker_pk) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("This worker does not hold 0x{worker_pk}"),
+        )
+        .into_response();
+    }
+
+    match state.worker.start_handshake(&req).await {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!("worker_handshake_challenge() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to start handshake: {:#}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SealedKeyShareRequest, WorkerKeyShareResponse};
+
+/// Saves a key share delivered by the leader, after checking it decrypts with this worker's ETH
+/// key to the share the verification vector commits to. The share must be sealed under the
+/// session of a leader that attested to this worker, else it is refused with 403. Delivering the
+/// same share again is harmless, so the leader can retry.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(sealed): Json<SealedKeyShareRequest>,
+) -> axum::response::Response {
+    info!("worker_keyshare()");
+    let req = match state.worker.open_sealed_key_share(&sealed) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("worker_keyshare() refused a share: {:?}", e);
+            let status = if e.is::<crate::enclave::secure_signer::worker::NoSession>() {
+                axum::http::status::StatusCode::FORBIDDEN
+            } else {
+                axum::http::status::StatusCode::BAD_REQUEST
+            };
+            return ErrorResponse::new(status, format!("{:#}", e)).into_response();
+        }
+    };
+    let sk_share = match crate::enclave::secure_signer::worker::open_key_share(&req) {
+        Ok(sk_share) => sk_share,
+        Err(e) => {
+            error!("worker_keyshare() refused share {}: {:?}", req.index, e);
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad key share: {:#}", e),
+            )
+            .into_response();
+        }
+    };
+    match crate::enclave::secure_signer::worker::save_key_share(
+        state.slashing_backend.as_ref(),
+        &sk_share,
+    ) {
+        Ok(pk_share_hex) => (
+            axum::http::status::StatusCode::OK,
+            Json(WorkerKeyShareResponse { pk_share_hex }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("worker_keyshare() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to save key share: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
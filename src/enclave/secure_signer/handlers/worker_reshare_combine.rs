@@ -0,0 +1,74 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SealedReshareRequest, WorkerKeyShareResponse};
+
+/// Combines the sub-shares the old holders of a key split for this worker into its share of the
+/// new sharing and saves it, after checking every sub-share against its old holder's verification
+/// vector and the result against the new one. The share's slashing protection starts from the
+/// highest watermarks of the old shares. The request must be sealed under the session of a leader
+/// that attested to this worker, else it is refused with 403.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(sealed): Json<SealedReshareRequest>,
+) -> axum::response::Response {
+    info!("worker_reshare_combine()");
+    let req = match state.worker.open_sealed_sub_shares(&sealed) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("worker_reshare_combine() refused a request: {:?}", e);
+            let status = if e.is::<crate::enclave::secure_signer::worker::NoSession>() {
+                axum::http::status::StatusCode::FORBIDDEN
+            } else {
+                axum::http::status::StatusCode::BAD_REQUEST
+            };
+            return ErrorResponse::new(status, format!("{:#}", e)).into_response();
+        }
+    };
+    let sk_share = match crate::enclave::secure_signer::worker::combine_sub_shares(&req) {
+        Ok(sk_share) => sk_share,
+        Err(e) => {
+            error!(
+                "worker_reshare_combine() refused share {}: {:?}",
+                req.index, e
+            );
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad sub-shares: {:#}", e),
+            )
+            .into_response();
+        }
+    };
+    // The share must not sign below the old shares' watermarks, so they are saved first
+    let pk_share_hex = hex::encode(sk_share.public_key_share().to_bytes());
+    if let Err(e) = crate::enclave::secure_signer::worker::seed_watermark(
+        state.slashing_backend.as_ref(),
+        &pk_share_hex,
+        &req.watermark,
+    ) {
+        error!("worker_reshare_combine() failed with: {:?}", e);
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to save slashing protection: {:?}", e),
+        )
+        .into_response();
+    }
+    match crate::enclave::secure_signer::worker::save_key_share(
+        state.slashing_backend.as_ref(),
+        &sk_share,
+    ) {
+        Ok(pk_share_hex) => (
+            axum::http::status::StatusCode::OK,
+            Json(WorkerKeyShareResponse { pk_share_hex }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("worker_reshare_combine() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to save key share: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SealedReshareRequest, WorkerKeyShareResponse};
+
+/// Deletes this worker's old share of a key the leader re-shared, once every new worker saved its
+/// new share, like `DELETE /eth/v1/keystores` but for the leader. The slashing protection history
+/// is kept. A share already deleted is acknowledged again, so the leader can retry. The request
+/// must be sealed under the session of a leader that attested to this worker, else it is refused
+/// with 403.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(sealed): Json<SealedReshareRequest>,
+) -> axum::response::Response {
+    info!("worker_reshare_revoke()");
+    let req = match state.worker.open_sealed_revoke_request(&sealed) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("worker_reshare_revoke() refused a request: {:?}", e);
+            let status = if e.is::<crate::enclave::secure_signer::worker::NoSession>() {
+                axum::http::status::StatusCode::FORBIDDEN
+            } else {
+                axum::http::status::StatusCode::BAD_REQUEST
+            };
+            return ErrorResponse::new(status, format!("{:#}", e)).into_response();
+        }
+    };
+    let pk_share_hex = match crate::enclave::secure_signer::worker::revoked_share(&req) {
+        Ok(pk_share_hex) => pk_share_hex,
+        Err(e) => {
+            error!(
+                "worker_reshare_revoke() refused share {}: {:?}",
+                req.index, e
+            );
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Failed to revoke share: {:#}", e),
+            )
+            .into_response();
+        }
+    };
+
+    // Wait for in-flight signatures with the share to finish first
+    let _guard = state.sign_locks.lock(&pk_share_hex).await;
+    if crate::io::key_management::bls_key_exists(&pk_share_hex) {
+        if let Err(e) = crate::io::key_management::delete_bls_key(&pk_share_hex) {
+            error!(
+                "worker_reshare_revoke() failed for {pk_share_hex} with: {:?}",
+                e
+            );
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to delete share: {:?}", e),
+            )
+            .into_response();
+        }
+        state.forget_key(&pk_share_hex);
+        info!("Revoked share {} of {}", req.index, req.bls_pk_hex);
+    }
+    (
+        axum::http::status::StatusCode::OK,
+        Json(WorkerKeyShareResponse {
+            pk_share_hex: format!("0x{pk_share_hex}"),
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,41 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SealedReshareRequest};
+
+/// Splits this worker's share of a key into sub-shares for the new workers of a re-sharing, each
+/// encrypted to its recipient, so the leader only relays them. The request must be sealed under
+/// the session of a leader that attested to this worker, else it is refused with 403. The share
+/// itself is kept, and keeps signing until the leader commits the new sharing.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(sealed): Json<SealedReshareRequest>,
+) -> axum::response::Response {
+    info!("worker_reshare_subshares()");
+    let req = match state.worker.open_sealed_sub_share_request(&sealed) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("worker_reshare_subshares() refused a request: {:?}", e);
+            let status = if e.is::<crate::enclave::secure_signer::worker::NoSession>() {
+                axum::http::status::StatusCode::FORBIDDEN
+            } else {
+                axum::http::status::StatusCode::BAD_REQUEST
+            };
+            return ErrorResponse::new(status, format!("{:#}", e)).into_response();
+        }
+    };
+    match crate::enclave::secure_signer::worker::sub_share(&req) {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!(
+                "worker_reshare_subshares() refused share {}: {:?}",
+                req.index, e
+            );
+            ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Failed to split share: {:#}", e),
+            )
+            .into_response()
+        }
+    }
+}
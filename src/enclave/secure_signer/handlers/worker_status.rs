@@ -0,0 +1,30 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, WorkerStatusResponse};
+
+/// Reports the worker's uptime and the key shares it can sign with, which the leader polls as
+/// a heartbeat
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("worker_status()");
+    match crate::io::key_management::list_bls_keys() {
+        Ok(keys) => (
+            axum::http::status::StatusCode::OK,
+            Json(WorkerStatusResponse {
+                uptime_secs: state.worker.uptime().as_secs(),
+                key_shares: keys.iter().map(|pk_hex| format!("0x{pk_hex}")).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("worker_status() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list key shares: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
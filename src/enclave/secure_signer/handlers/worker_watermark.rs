@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, WorkerWatermarkResponse};
+
+/// Reports the slashing protection watermarks of one of the worker's key shares, so the leader
+/// can check the workers agree a sign request advances them before asking for partials
+pub async fn handler(
+    Path(pk_share_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("worker_watermark()");
+    let pk_share_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&pk_share_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad pk_share_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+    if !crate::io::key_management::bls_key_exists(&pk_share_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{pk_share_hex}"),
+        )
+        .into_response();
+    }
+
+    match crate::enclave::secure_signer::worker::watermark(
+        state.slashing_backend.as_ref(),
+        &pk_share_hex,
+    ) {
+        Ok(watermark) => (axum::http::status::StatusCode::OK, Json(watermark)).into_response(),
+        Err(e) => {
+            error!("worker_watermark() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read slashing protection: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! The mutual attestation handshake between a leader and its workers. Each side proves with
+//! evidence bound to its ETH key and a nonce chosen by the other that it runs in an enclave the
+//! other's measurement policy allows, and the session key is derived from the ECDH of the two
+//! attested keys. Both sides trust the session for the peer attestation TTL, then handshake again.
+
+use anyhow::{bail, Result};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::io::remote_attestation::{
+    check_measurement_policy, AttestationEvidence, AttestationVerdict, EvidenceKind, KeyBinding,
+};
+
+static PEER_ATTESTATION_TTL_SECS: OnceLock<u64> = OnceLock::new();
+
+/// Sets how many seconds a leader and worker trust each other's attestation. Must be called
+/// before the first handshake.
+pub fn set_peer_attestation_ttl_secs(ttl_secs: u64) -> Result<()> {
+    if PEER_ATTESTATION_TTL_SECS.set(ttl_secs).is_err() {
+        bail!("Peer attestation TTL already set");
+    }
+    Ok(())
+}
+
+/// How long a leader and worker trust each other's attestation
+pub fn peer_attestation_ttl() -> Duration {
+    Duration::from_secs(
+        *PEER_ATTESTATION_TTL_SECS
+            .get()
+            .unwrap_or(&crate::constants::DEFAULT_PEER_ATTESTATION_TTL_SECS),
+    )
+}
+
+/// A fresh hex-encoded nonce for the peer to bind its evidence to
+pub fn new_nonce() -> Result<String> {
+    let mut nonce = [0_u8; 32];
+    openssl::rand::rand_bytes(&mut nonce)?;
+    Ok(crate::to_0x_hex(nonce))
+}
+
+/// Evidence of `kind` that this enclave holds the ETH key `eth_pk_hex`, bound to the peer's
+/// `nonce`
+pub async fn attest(
+    eth_pk_hex: &str,
+    nonce: &str,
+    kind: EvidenceKind,
+) -> Result<AttestationEvidence> {
+    let binding = KeyBinding {
+        eth_pubkey: Some(eth_pk_hex.to_string()),
+        bls_pubkey: None,
+        nonce: nonce.to_string(),
+    };
+    AttestationEvidence::generate(kind, &binding.report_data()?).await
+}
+
+/// Checks the peer's `evidence` comes from an enclave build the measurement policy allows and
+/// commits to its ETH key `eth_pk_hex` and our `nonce`
+pub fn verify_peer(
+    eth_pk_hex: &str,
+    nonce: &str,
+    evidence: &AttestationEvidence,
+) -> Result<AttestationVerdict> {
+    let binding = KeyBinding {
+        eth_pubkey: Some(eth_pk_hex.to_string()),
+        bls_pubkey: None,
+        nonce: nonce.to_string(),
+    };
+    let verdict = check_measurement_policy(evidence)?;
+    binding.verify(evidence)?;
+    Ok(verdict)
+}
@@ -0,0 +1,1281 @@
+//! Threshold signing led by this enclave: sign requests for a key split with `split_bls_key` are
+//! forwarded to the registered workers holding its shares, and their partial signatures are
+//! recombined into a signature under the key. The shares are first delivered to the workers, over
+//! sessions set up by a mutual attestation handshake, and only those that acknowledged theirs are
+//! asked to sign. Workers are polled with heartbeats, so those that stopped answering are skipped
+//! instead of waited on. A split key is moved to new workers by re-sharing it through the workers
+//! holding its shares, without reconstructing it.
+
+use anyhow::{bail, Context, Result};
+use blsttc::{PublicKeySet, SignatureShare};
+use log::{error, info, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use crate::io::threshold_keys::{ThresholdKey, ThresholdShare};
+
+/// This leader's in-memory state: its handshake key, its attested sessions with its workers, and
+/// what heartbeats and partial signatures tell about them. Clones share the same state.
+#[derive(Clone)]
+pub struct LeaderState {
+    worker_timeout: Duration,
+    max_missed_heartbeats: u32,
+    /// This leader's ETH key for handshakes with its workers. It is generated in the enclave and
+    /// never leaves it, so a restarted leader handshakes with a new key.
+    key: Arc<(ecies::SecretKey, ecies::PublicKey)>,
+    health: Arc<Mutex<HashMap<String, WorkerHealth>>>,
+    sessions: Arc<Mutex<HashMap<String, WorkerSession>>>,
+    last_partials: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl Default for LeaderState {
+    fn default() -> Self {
+        Self::new(
+            crate::constants::DEFAULT_WORKER_TIMEOUT_MS,
+            crate::constants::DEFAULT_MAX_MISSED_HEARTBEATS,
+        )
+    }
+}
+
+impl LeaderState {
+    /// A leader giving each worker `worker_timeout_ms` to answer, that deems a worker unhealthy
+    /// after `max_missed_heartbeats` heartbeats missed in a row
+    pub fn new(worker_timeout_ms: u64, max_missed_heartbeats: u32) -> Self {
+        Self {
+            worker_timeout: Duration::from_millis(worker_timeout_ms),
+            max_missed_heartbeats,
+            key: Arc::new(ecies::utils::generate_keypair()),
+            health: Default::default(),
+            sessions: Default::default(),
+            last_partials: Default::default(),
+        }
+    }
+
+    /// How long each worker gets to return its partial signature
+    pub fn worker_timeout(&self) -> Duration {
+        self.worker_timeout
+    }
+
+    /// After how many heartbeats missed in a row a worker is unhealthy
+    pub fn max_missed_heartbeats(&self) -> u32 {
+        self.max_missed_heartbeats
+    }
+}
+
+/// What the heartbeats tell about a registered worker. Workers not polled yet are healthy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorkerHealth {
+    pub consecutive_failures: u32,
+    /// How long the last answered heartbeat took
+    pub latency: Option<Duration>,
+}
+
+impl WorkerHealth {
+    /// Whether the worker missed fewer than `max_missed_heartbeats` heartbeats in a row
+    pub fn is_healthy(&self, max_missed_heartbeats: u32) -> bool {
+        self.consecutive_failures < max_missed_heartbeats
+    }
+}
+
+/// Asks the worker at `url` for its status, returning how long it took to answer
+async fn heartbeat(client: reqwest::Client, url: String, timeout: Duration) -> Result<Duration> {
+    let started = Instant::now();
+    let resp = client
+        .get(format!("{}/worker/v1/status", url.trim_end_matches('/')))
+        .timeout(timeout)
+        .send()
+        .await?
+        .error_for_status()?;
+    resp.json::<crate::enclave::types::WorkerStatusResponse>()
+        .await?;
+    Ok(started.elapsed())
+}
+
+impl LeaderState {
+    /// The health of worker `id`
+    pub fn worker_health(&self, id: &str) -> WorkerHealth {
+        self.health
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Whether worker `id` answers its heartbeats
+    pub fn is_healthy(&self, id: &str) -> bool {
+        self.worker_health(id)
+            .is_healthy(self.max_missed_heartbeats)
+    }
+
+    /// Records the outcome of a heartbeat of worker `id`, its latency if it answered
+    fn record_heartbeat(&self, id: &str, latency: Option<Duration>) -> WorkerHealth {
+        let health = {
+            let mut healths = self.health.lock().unwrap_or_else(|e| e.into_inner());
+            let health = healths.entry(id.to_string()).or_default();
+            match latency {
+                Some(latency) => {
+                    health.consecutive_failures = 0;
+                    health.latency = Some(latency);
+                }
+                None => health.consecutive_failures = health.consecutive_failures.saturating_add(1),
+            }
+            health.clone()
+        };
+        crate::enclave::shared::metrics::observe_worker_heartbeat(
+            id,
+            health.is_healthy(self.max_missed_heartbeats),
+            health.consecutive_failures,
+            latency,
+        );
+        health
+    }
+
+    /// Polls every registered worker once, concurrently, recording their health and when they
+    /// were last seen
+    pub async fn poll_workers(&self, client: &reqwest::Client) -> Result<()> {
+        let mut beats = tokio::task::JoinSet::new();
+        for worker in crate::io::workers::list_workers()? {
+            let beat = heartbeat(client.clone(), worker.url.clone(), self.worker_timeout);
+            beats.spawn(async move { (worker, beat.await) });
+        }
+        while let Some(joined) = beats.join_next().await {
+            let Ok((worker, beat)) = joined else {
+                continue;
+            };
+            let was_healthy = self.is_healthy(&worker.id);
+            let health = match beat {
+                Ok(latency) => {
+                    if let Err(e) = crate::io::workers::touch_worker(&worker.id) {
+                        error!("Couldn't record worker {} was seen: {:?}", worker.id, e);
+                    }
+                    self.record_heartbeat(&worker.id, Some(latency))
+                }
+                Err(e) => {
+                    warn!("Worker {} missed a heartbeat: {:#}", worker.id, e);
+                    self.record_heartbeat(&worker.id, None)
+                }
+            };
+            match (was_healthy, health.is_healthy(self.max_missed_heartbeats)) {
+                (true, false) => warn!(
+                    "Worker {} is unhealthy after {} missed heartbeats",
+                    worker.id, health.consecutive_failures
+                ),
+                (false, true) => info!("Worker {} is healthy again", worker.id),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Polls the registered workers every `interval` until the process exits
+    pub async fn run_heartbeats(self, interval: Duration) {
+        let client = reqwest::Client::new();
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            if let Err(e) = self.poll_workers(&client).await {
+                error!("Failed to poll the workers: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Returned when fewer registered workers than needed are verified and healthy
+#[derive(Debug)]
+pub struct NotEnoughWorkers {
+    pub needed: usize,
+    pub available: usize,
+}
+
+impl std::fmt::Display for NotEnoughWorkers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Need {} verified and healthy workers, but {} are registered",
+            self.needed, self.available
+        )
+    }
+}
+
+impl std::error::Error for NotEnoughWorkers {}
+
+impl LeaderState {
+    /// The first `n` registered workers, by id, whose evidence passes the loaded measurement policy
+    /// and that answer their heartbeats
+    pub fn pick_workers(&self, n: usize) -> Result<Vec<crate::io::workers::WorkerRecord>> {
+        let workers: Vec<_> = crate::io::workers::list_workers()?
+            .into_iter()
+            .filter(|w| {
+                crate::io::remote_attestation::check_loaded_measurement_policy(&w.verdict).is_ok()
+                    && self.is_healthy(&w.id)
+            })
+            .collect();
+        if workers.len() < n {
+            return Err(NotEnoughWorkers {
+                needed: n,
+                available: workers.len(),
+            }
+            .into());
+        }
+        Ok(workers.into_iter().take(n).collect())
+    }
+
+    /// The registered workers with the ETH keys `ecies_pks`, in that order, failing unless each
+    /// one's evidence passes the loaded measurement policy and it answers its heartbeats
+    pub fn registered_workers(
+        &self,
+        ecies_pks: &[String],
+    ) -> Result<Vec<crate::io::workers::WorkerRecord>> {
+        ecies_pks
+            .iter()
+            .map(|pk_hex| {
+                let worker = crate::io::workers::worker_id(pk_hex)
+                    .and_then(|id| crate::io::workers::read_worker(&id))
+                    .with_context(|| format!("Worker {pk_hex} is not registered"))?;
+                crate::io::remote_attestation::check_loaded_measurement_policy(&worker.verdict)
+                    .with_context(|| format!("Worker {} is revoked", worker.id))?;
+                if !self.is_healthy(&worker.id) {
+                    bail!("Worker {} is unhealthy", worker.id)
+                }
+                Ok(worker)
+            })
+            .collect()
+    }
+
+    /// The 0x-prefixed compressed ETH key this leader attests to in handshakes
+    pub fn leader_pubkey_hex(&self) -> String {
+        crate::to_0x_hex(self.key.1.serialize_compressed())
+    }
+}
+
+/// A worker that attested to this leader in a handshake, and the key of their session
+struct WorkerSession {
+    key: crate::crypto::session::SessionKey,
+    expires: Instant,
+}
+
+/// Parses a worker's answer, or fails with why it refused
+async fn worker_response<T: serde::de::DeserializeOwned>(
+    worker_id: &str,
+    resp: reqwest::Response,
+) -> Result<T> {
+    if !resp.status().is_success() {
+        bail!(
+            "Worker {worker_id} refused with {}: {}",
+            resp.status(),
+            resp.text().await.unwrap_or_default()
+        )
+    }
+    resp.json()
+        .await
+        .with_context(|| format!("Worker {worker_id} sent a bad response"))
+}
+
+impl LeaderState {
+    /// Runs the mutual attestation handshake with `worker`: it attests to its registered ETH key
+    /// bound to our nonce, then we attest to ours bound to its challenge, and both derive the
+    /// session key from the ECDH of the two keys
+    async fn handshake(
+        &self,
+        client: &reqwest::Client,
+        worker: &crate::io::workers::WorkerRecord,
+    ) -> Result<WorkerSession> {
+        use crate::enclave::secure_signer::handshake::{attest, new_nonce, verify_peer};
+
+        let (leader_sk, leader_pk) = &*self.key;
+        let leader_pubkey = self.leader_pubkey_hex();
+        let base = worker.url.trim_end_matches('/');
+        let kind = worker.verdict.kind;
+        let timeout = Duration::from_secs(crate::constants::HANDSHAKE_TIMEOUT_SECS);
+
+        let nonce = new_nonce()?;
+        let resp = client
+            .post(format!("{base}/worker/v1/handshake/challenge"))
+            .json(&crate::enclave::types::HandshakeChallengeRequest {
+                leader_pubkey: leader_pubkey.clone(),
+                worker_pubkey: worker.ecies_pubkey.clone(),
+                nonce: nonce.clone(),
+                kind,
+            })
+            .timeout(timeout)
+            .send()
+            .await
+            .with_context(|| format!("Worker {} did not answer", worker.id))?;
+        let challenge: crate::enclave::types::HandshakeChallengeResponse =
+            worker_response(&worker.id, resp).await?;
+        verify_peer(&worker.ecies_pubkey, &nonce, &challenge.evidence)
+            .with_context(|| format!("Worker {} failed to attest", worker.id))?;
+
+        let evidence = attest(&leader_pubkey, &challenge.challenge, kind).await?;
+        let resp = client
+            .post(format!("{base}/worker/v1/handshake"))
+            .json(&crate::enclave::types::HandshakeRequest {
+                leader_pubkey,
+                challenge: challenge.challenge,
+                evidence,
+            })
+            .timeout(timeout)
+            .send()
+            .await
+            .with_context(|| format!("Worker {} did not answer", worker.id))?;
+        let accepted: crate::enclave::types::HandshakeResponse =
+            worker_response(&worker.id, resp).await?;
+
+        let worker_pk = crate::crypto::eth_keys::parse_eth_pk_hex(&worker.ecies_pubkey)?;
+        let key =
+            crate::crypto::session::session_key(leader_sk, &worker_pk, leader_pk, &worker_pk)?;
+        let ttl = crate::enclave::secure_signer::handshake::peer_attestation_ttl()
+            .min(Duration::from_secs(accepted.expires_in_secs));
+        info!(
+            "Worker {} attested, session expires in {:?}",
+            worker.id, ttl
+        );
+        Ok(WorkerSession {
+            key,
+            expires: Instant::now() + ttl,
+        })
+    }
+
+    /// The key of the attested session with `worker`, handshaking first if there is none, it
+    /// expired or `fresh` is set
+    async fn worker_session_key(
+        &self,
+        client: &reqwest::Client,
+        worker: &crate::io::workers::WorkerRecord,
+        fresh: bool,
+    ) -> Result<crate::crypto::session::SessionKey> {
+        if !fresh {
+            let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(session) = sessions
+                .get(&worker.id)
+                .filter(|session| session.expires > Instant::now())
+            {
+                return Ok(session.key.clone());
+            }
+        }
+        let session = self.handshake(client, worker).await?;
+        let key = session.key.clone();
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(worker.id.clone(), session);
+        Ok(key)
+    }
+
+    /// Posts `plaintext` to `path` of `worker`, sealed under their attested session and wrapped
+    /// with `wrap`, and parses the answer. A worker that no longer trusts the session, e.g. because
+    /// it restarted, is handshaken with again once.
+    async fn post_sealed<W: serde::Serialize, R: serde::de::DeserializeOwned>(
+        &self,
+        client: &reqwest::Client,
+        worker: &crate::io::workers::WorkerRecord,
+        path: &str,
+        plaintext: &[u8],
+        wrap: impl Fn(String) -> W,
+    ) -> Result<R> {
+        let mut fresh = false;
+        loop {
+            let key = self.worker_session_key(client, worker, fresh).await?;
+            let sealed = wrap(crate::to_0x_hex(crate::crypto::session::seal(
+                &key, plaintext,
+            )?));
+            let resp = client
+                .post(format!("{}{path}", worker.url.trim_end_matches('/')))
+                .json(&sealed)
+                .timeout(self.worker_timeout)
+                .send()
+                .await
+                .with_context(|| format!("Worker {} did not answer", worker.id))?;
+            if resp.status() == reqwest::StatusCode::FORBIDDEN && !fresh {
+                warn!(
+                    "Worker {} refused our session, handshaking again",
+                    worker.id
+                );
+                fresh = true;
+                continue;
+            }
+            return worker_response(&worker.id, resp).await;
+        }
+    }
+
+    /// Delivers `share` of `key` to the registered worker it was encrypted to, sealed under their
+    /// attested session, returning once the worker acknowledged saving it
+    async fn deliver_share(
+        self,
+        client: reqwest::Client,
+        worker: crate::io::workers::WorkerRecord,
+        req: crate::enclave::types::WorkerKeyShareRequest,
+    ) -> Result<()> {
+        let share = Zeroizing::new(serde_json::to_vec(&req)?);
+        let resp: crate::enclave::types::WorkerKeyShareResponse = self
+            .post_sealed(
+                &client,
+                &worker,
+                "/worker/v1/keyshare",
+                &share,
+                |sealed_share| crate::enclave::types::SealedKeyShareRequest {
+                    leader_pubkey: self.leader_pubkey_hex(),
+                    sealed_share,
+                },
+            )
+            .await?;
+        if crate::normalize_hex(&resp.pk_share_hex)? != crate::normalize_hex(&req.pk_share_hex)? {
+            bail!("Worker {} saved {} instead", worker.id, resp.pk_share_hex)
+        }
+        Ok(())
+    }
+
+    /// Delivers every share of `key` not acknowledged yet to its worker at once, and saves which
+    /// workers acknowledged theirs. A worker must be registered and its evidence still pass the
+    /// measurement policy. Shares that failed stay unacknowledged, so calling this again retries
+    /// just those.
+    pub async fn distribute_shares(
+        &self,
+        key: &mut ThresholdKey,
+    ) -> Result<Vec<crate::enclave::types::ShareDelivery>> {
+        use crate::enclave::types::{ShareDelivery, ShareDeliveryStatus};
+
+        let client = reqwest::Client::new();
+        let mut outcomes = BTreeMap::new();
+        let mut deliveries = tokio::task::JoinSet::new();
+        for (i, share) in key.shares.iter().enumerate() {
+            if share.acknowledged {
+                outcomes.insert(i, Ok(ShareDeliveryStatus::AlreadyDelivered));
+                continue;
+            }
+            let worker = crate::io::workers::worker_id(&share.worker_pk_hex)
+                .and_then(|id| crate::io::workers::read_worker(&id))
+                .with_context(|| format!("Worker {} is not registered", share.worker_pk_hex))
+                .and_then(|worker| {
+                    crate::io::remote_attestation::check_loaded_measurement_policy(&worker.verdict)
+                        .with_context(|| format!("Worker {} is revoked", worker.id))?;
+                    Ok(worker)
+                });
+            let worker = match worker {
+                Ok(worker) => worker,
+                Err(e) => {
+                    outcomes.insert(i, Err(e));
+                    continue;
+                }
+            };
+            let req = crate::enclave::types::WorkerKeyShareRequest {
+                worker_pk_hex: share.worker_pk_hex.clone(),
+                bls_pk_hex: key.bls_pk_hex.clone(),
+                index: share.index,
+                verification_vector: key.verification_vector.clone(),
+                pk_share_hex: share.pk_share_hex.clone(),
+                encrypted_sk_share_hex: share.encrypted_sk_share_hex.clone(),
+            };
+            let delivery = self.clone().deliver_share(client.clone(), worker, req);
+            deliveries.spawn(async move { (i, delivery.await) });
+        }
+        while let Some(joined) = deliveries.join_next().await {
+            match joined {
+                Ok((i, delivery)) => {
+                    outcomes.insert(i, delivery.map(|_| ShareDeliveryStatus::Delivered));
+                }
+                Err(e) => error!("Share delivery task failed: {:?}", e),
+            }
+        }
+
+        let mut data = vec![];
+        for (i, share) in key.shares.iter_mut().enumerate() {
+            let (status, message) = match outcomes.remove(&i) {
+                Some(Ok(status)) => (status, None),
+                Some(Err(e)) => {
+                    error!("Failed to deliver share {}: {:#}", share.index, e);
+                    (ShareDeliveryStatus::Failed, Some(format!("{e:#}")))
+                }
+                None => (
+                    ShareDeliveryStatus::Failed,
+                    Some("Delivery was aborted".to_string()),
+                ),
+            };
+            if status == ShareDeliveryStatus::Delivered {
+                share.acknowledged = true;
+                share.delivered_at = Some(crate::io::workers::now());
+            }
+            data.push(ShareDelivery {
+                index: share.index,
+                worker_pk_hex: share.worker_pk_hex.clone(),
+                status,
+                message,
+            });
+        }
+        crate::io::threshold_keys::write_threshold_key(key)?;
+        Ok(data)
+    }
+}
+
+impl LeaderState {
+    /// When the worker holding the share `pk_share_hex` last sent a partial signature that
+    /// verified, in unix seconds, if it did since this leader started
+    pub fn last_partial_at(&self, pk_share_hex: &str) -> Option<u64> {
+        let pk_share_hex = crate::normalize_hex(pk_share_hex).ok()?;
+        self.last_partials
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&pk_share_hex)
+            .copied()
+    }
+
+    fn record_partial(&self, pk_share_hex: &str) {
+        if let Ok(pk_share_hex) = crate::normalize_hex(pk_share_hex) {
+            self.last_partials
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(pk_share_hex, crate::io::workers::now());
+        }
+    }
+}
+
+/// Returned when fewer workers than the threshold answered with a valid partial signature
+#[derive(Debug)]
+pub struct NotEnoughPartials {
+    pub needed: usize,
+    pub got: usize,
+    /// Why each other worker did not count
+    pub failures: Vec<String>,
+}
+
+impl std::fmt::Display for NotEnoughPartials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Got {} of the {} partial signatures needed: {}",
+            self.got,
+            self.needed,
+            self.failures.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for NotEnoughPartials {}
+
+/// Returned when a worker's slashing protection watermarks disagree with a slashable sign
+/// request, before the partials are requested or after they were recombined
+#[derive(Debug)]
+pub struct WatermarkDivergence {
+    /// Each divergent worker and its watermark
+    pub divergent: Vec<String>,
+}
+
+impl std::fmt::Display for WatermarkDivergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Worker watermarks diverge from the request: {}",
+            self.divergent.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for WatermarkDivergence {}
+
+/// The watermark a slashable sign request must advance on every worker asked to sign it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Watermark {
+    Block(crate::eth2::eth_types::Slot),
+    Attestation {
+        source: crate::eth2::eth_types::Epoch,
+        target: crate::eth2::eth_types::Epoch,
+    },
+}
+
+impl Watermark {
+    fn of(req: &crate::eth2::eth_signing::BLSSignMsg) -> Option<Self> {
+        use crate::eth2::eth_signing::BLSSignMsg;
+        match req {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => Some(Watermark::Block(m.block.slot)),
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => {
+                Some(Watermark::Block(m.beacon_block.block_header.slot))
+            }
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => {
+                Some(Watermark::Attestation {
+                    source: m.attestation.source.epoch,
+                    target: m.attestation.target.epoch,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The watermark of `seen` this request is compared against
+    fn last_signed(&self, seen: &crate::enclave::types::WorkerWatermarkResponse) -> String {
+        match self {
+            Watermark::Block(_) => match seen.block_slot {
+                Some(last) => format!("last signed block slot {last}"),
+                None => "signed no block".to_string(),
+            },
+            Watermark::Attestation { .. } => {
+                match (seen.attestation_source_epoch, seen.attestation_target_epoch) {
+                    (Some(src), Some(tgt)) => format!("last signed attestation {src} -> {tgt}"),
+                    _ => "signed no attestation".to_string(),
+                }
+            }
+        }
+    }
+
+    /// Whether a worker at `seen` would sign this request, which must strictly advance its
+    /// watermark, so even a retry of the last request is refused
+    fn advances(&self, seen: &crate::enclave::types::WorkerWatermarkResponse) -> bool {
+        match *self {
+            Watermark::Block(slot) => seen.block_slot.map_or(true, |last| slot > last),
+            Watermark::Attestation { source, target } => {
+                match (seen.attestation_source_epoch, seen.attestation_target_epoch) {
+                    (Some(last_src), Some(last_tgt)) => source >= last_src && target > last_tgt,
+                    _ => true,
+                }
+            }
+        }
+    }
+
+    /// Whether a worker at `seen` persisted this watermark
+    fn persisted_by(&self, seen: &crate::enclave::types::WorkerWatermarkResponse) -> bool {
+        match *self {
+            Watermark::Block(slot) => seen.block_slot == Some(slot),
+            Watermark::Attestation { source, target } => {
+                seen.attestation_source_epoch == Some(source)
+                    && seen.attestation_target_epoch == Some(target)
+            }
+        }
+    }
+}
+
+impl LeaderState {
+    /// Asks `worker` for its watermarks for `share`
+    async fn fetch_watermark(
+        self,
+        client: reqwest::Client,
+        worker: crate::io::workers::WorkerRecord,
+        share: ThresholdShare,
+    ) -> Result<crate::enclave::types::WorkerWatermarkResponse> {
+        let resp = client
+            .get(format!(
+                "{}/worker/v1/watermark/{}",
+                worker.url.trim_end_matches('/'),
+                share.pk_share_hex
+            ))
+            .timeout(self.worker_timeout)
+            .send()
+            .await
+            .with_context(|| format!("Worker {} did not answer", worker.id))?;
+        worker_response(&worker.id, resp).await
+    }
+
+    /// Asks every worker in `holders` for its watermarks for its share at once, in `holders` order
+    async fn fetch_watermarks(
+        &self,
+        client: &reqwest::Client,
+        holders: &[(ThresholdShare, crate::io::workers::WorkerRecord)],
+    ) -> Vec<Result<crate::enclave::types::WorkerWatermarkResponse>> {
+        let mut fetches = tokio::task::JoinSet::new();
+        for (i, (share, worker)) in holders.iter().enumerate() {
+            let fetch = self
+                .clone()
+                .fetch_watermark(client.clone(), worker.clone(), share.clone());
+            fetches.spawn(async move { (i, fetch.await) });
+        }
+        let mut watermarks: Vec<_> = holders
+            .iter()
+            .map(|(_, worker)| Err(anyhow::anyhow!("Worker {} was not asked", worker.id)))
+            .collect();
+        while let Some(joined) = fetches.join_next().await {
+            if let Ok((i, watermark)) = joined {
+                watermarks[i] = watermark;
+            }
+        }
+        watermarks
+    }
+
+    /// The registered worker holding `share`, unless it never acknowledged it or stopped answering
+    /// its heartbeats
+    fn share_worker(&self, share: &ThresholdShare) -> Result<crate::io::workers::WorkerRecord> {
+        if !share.acknowledged {
+            bail!("Share {} was not delivered to its worker", share.index)
+        }
+        let worker_id = crate::io::workers::worker_id(&share.worker_pk_hex)?;
+        let worker = crate::io::workers::read_worker(&worker_id)
+            .with_context(|| format!("Worker {} is not registered", share.worker_pk_hex))?;
+        let health = self.worker_health(&worker_id);
+        if !health.is_healthy(self.max_missed_heartbeats) {
+            bail!(
+                "Worker {worker_id} is unhealthy, it missed {} heartbeats",
+                health.consecutive_failures
+            )
+        }
+        Ok(worker)
+    }
+
+    /// Indices of the shares of `key` that `threshold_sign` would ask a worker for right now
+    pub fn available_shares(&self, key: &ThresholdKey) -> Vec<usize> {
+        key.shares
+            .iter()
+            .filter(|share| self.share_worker(share).is_ok())
+            .map(|share| share.index)
+            .collect()
+    }
+
+    /// Asks `worker` to sign the sign request `body` with `share`, and checks the partial signature
+    /// verifies over `signing_root` under the share's public key
+    async fn request_partial(
+        self,
+        client: reqwest::Client,
+        pk_set: PublicKeySet,
+        worker: crate::io::workers::WorkerRecord,
+        share: ThresholdShare,
+        body: serde_json::Value,
+        signing_root: crate::eth2::eth_types::Root,
+    ) -> Result<SignatureShare> {
+        let worker_id = worker.id;
+        let url = format!(
+            "{}/api/v1/eth2/sign/{}",
+            worker.url.trim_end_matches('/'),
+            share.pk_share_hex
+        );
+        let resp = client
+            .post(&url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .json(&body)
+            .timeout(self.worker_timeout)
+            .send()
+            .await
+            .with_context(|| format!("Worker {worker_id} did not answer"))?;
+        if !resp.status().is_success() {
+            bail!(
+                "Worker {worker_id} refused with {}: {}",
+                resp.status(),
+                resp.text().await.unwrap_or_default()
+            )
+        }
+        let resp: crate::enclave::types::SignatureResponse = resp
+            .json()
+            .await
+            .with_context(|| format!("Worker {worker_id} sent a bad response"))?;
+
+        let sig_bytes: [u8; crate::constants::BLS_SIG_BYTES] =
+            match crate::parse_hex(&resp.signature)?.try_into() {
+                Ok(bytes) => bytes,
+                Err(_) => bail!("Worker {worker_id} sent a partial signature of the wrong length"),
+            };
+        let partial = SignatureShare::from_bytes(sig_bytes).map_err(|e| {
+            anyhow::anyhow!("Worker {worker_id} sent a bad partial signature: {e:?}")
+        })?;
+        if !pk_set
+            .public_key_share(share.index)
+            .verify(&partial, signing_root)
+        {
+            bail!("Partial signature of worker {worker_id} does not verify")
+        }
+        self.record_partial(&share.pk_share_hex);
+        Ok(partial)
+    }
+
+    /// Forwards `req` to every healthy worker that acknowledged a share of `key` at once, and
+    /// recombines exactly the first `key.threshold` partial signatures that verify. Workers that
+    /// fail, time out or send a bad partial are skipped; if too few are left this fails with
+    /// `NotEnoughPartials`, without asking any worker if too few shares are available to begin
+    /// with. For blocks and attestations every worker must first report watermarks the request
+    /// advances, and every worker asked must then report the request's, else this fails with
+    /// `WatermarkDivergence`.
+    pub async fn threshold_sign(
+        &self,
+        key: &ThresholdKey,
+        req: &crate::eth2::eth_signing::BLSSignMsg,
+        signing_root: crate::eth2::eth_types::Root,
+    ) -> Result<blsttc::Signature> {
+        let pk_set = PublicKeySet::from_bytes(crate::parse_hex(&key.verification_vector)?)
+            .map_err(|e| anyhow::anyhow!("Bad verification vector: {e:?}"))?;
+        let body = serde_json::to_value(req)?;
+        let client = reqwest::Client::new();
+
+        let mut failures = vec![];
+        let mut holders = vec![];
+        for share in key.shares.iter().cloned() {
+            match self.share_worker(&share) {
+                Ok(worker) => holders.push((share, worker)),
+                Err(e) => {
+                    error!("No partial signature for share {}: {:#}", share.index, e);
+                    failures.push(format!("share {}: {e:#}", share.index));
+                }
+            }
+        }
+        let not_enough = |got: usize, failures: Vec<String>| -> anyhow::Error {
+            NotEnoughPartials {
+                needed: key.threshold,
+                got,
+                failures,
+            }
+            .into()
+        };
+        if holders.len() < key.threshold {
+            return Err(not_enough(0, failures));
+        }
+
+        // Each worker protects only its own share, so desynced workers could each sign a request
+        // that is slashable for the key. Every worker asked must agree the request advances it.
+        let watermark = Watermark::of(req);
+        if let Some(watermark) = watermark {
+            let seen = self.fetch_watermarks(&client, &holders).await;
+            let mut divergent = vec![];
+            let mut agreeing = vec![];
+            for ((share, worker), seen) in holders.into_iter().zip(seen) {
+                match seen {
+                    Ok(seen) if watermark.advances(&seen) => agreeing.push((share, worker)),
+                    Ok(seen) => divergent.push(format!(
+                        "worker {} (share {}) {}",
+                        worker.id,
+                        share.index,
+                        watermark.last_signed(&seen)
+                    )),
+                    Err(e) => {
+                        error!("No watermark for share {}: {:#}", share.index, e);
+                        failures.push(format!("share {}: {e:#}", share.index));
+                    }
+                }
+            }
+            if !divergent.is_empty() {
+                return Err(WatermarkDivergence { divergent }.into());
+            }
+            if agreeing.len() < key.threshold {
+                return Err(not_enough(0, failures));
+            }
+            holders = agreeing;
+        }
+
+        let mut requests = tokio::task::JoinSet::new();
+        for (share, worker) in holders.iter().cloned() {
+            let index = share.index;
+            let partial = self.clone().request_partial(
+                client.clone(),
+                pk_set.clone(),
+                worker,
+                share,
+                body.clone(),
+                signing_root,
+            );
+            requests.spawn(async move { (index, partial.await) });
+        }
+
+        // Dropping the JoinSet aborts the requests still in flight once enough partials are in.
+        // Blocks and attestations wait for every worker asked instead, so none is left unknown to
+        // have recorded the request, which each request timing out bounds.
+        let mut partials = BTreeMap::new();
+        while let Some(joined) = requests.join_next().await {
+            match joined {
+                Ok((index, Ok(partial))) => {
+                    if partials.len() < key.threshold {
+                        partials.insert(index, partial);
+                    }
+                    if partials.len() == key.threshold && watermark.is_none() {
+                        break;
+                    }
+                }
+                Ok((index, Err(e))) => {
+                    error!("No partial signature for share {index}: {:#}", e);
+                    failures.push(format!("share {index}: {e:#}"));
+                }
+                Err(e) => failures.push(format!("{e}")),
+            }
+        }
+        if partials.len() < key.threshold {
+            return Err(not_enough(partials.len(), failures));
+        }
+
+        // Every worker asked must have recorded the request, whether or not its partial is
+        // recombined
+        if let Some(watermark) = watermark {
+            let seen = self.fetch_watermarks(&client, &holders).await;
+            let divergent: Vec<String> = holders
+                .iter()
+                .zip(seen)
+                .filter_map(|((share, worker), seen)| match seen {
+                    Ok(seen) if watermark.persisted_by(&seen) => None,
+                    Ok(seen) => Some(format!(
+                        "worker {} (share {}) did not persist the request, {}",
+                        worker.id,
+                        share.index,
+                        watermark.last_signed(&seen)
+                    )),
+                    Err(e) => Some(format!(
+                        "worker {} (share {}) did not confirm it: {e:#}",
+                        worker.id, share.index
+                    )),
+                })
+                .collect();
+            if !divergent.is_empty() {
+                return Err(WatermarkDivergence { divergent }.into());
+            }
+        }
+
+        info!(
+            "Recombining {} partial signatures for {}",
+            partials.len(),
+            key.bls_pk_hex
+        );
+        crate::crypto::bls_keys::combine_partial_signatures(&pk_set, &partials, &signing_root)
+    }
+}
+
+/// Returned when a key could not be re-shared, in which case its old shares stay in use
+#[derive(Debug)]
+pub struct ReshareFailed {
+    pub failures: Vec<String>,
+}
+
+impl std::fmt::Display for ReshareFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to re-share, the old shares are kept: {}",
+            self.failures.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for ReshareFailed {}
+
+/// Returned when a key was re-shared, but old holders did not acknowledge deleting their shares.
+/// The new shares are in use, and those holders must delete their old shares themselves.
+#[derive(Debug)]
+pub struct RevocationFailed {
+    pub failures: Vec<String>,
+}
+
+impl std::fmt::Display for RevocationFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Re-shared, but old holders still hold their shares: {}",
+            self.failures.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for RevocationFailed {}
+
+/// The highest of the watermarks `seen`, reported for `pk_share_hex`
+fn highest_watermark(
+    pk_share_hex: String,
+    seen: &[crate::enclave::types::WorkerWatermarkResponse],
+) -> crate::enclave::types::WorkerWatermarkResponse {
+    crate::enclave::types::WorkerWatermarkResponse {
+        pk_share_hex,
+        block_slot: seen.iter().filter_map(|w| w.block_slot).max(),
+        attestation_source_epoch: seen.iter().filter_map(|w| w.attestation_source_epoch).max(),
+        attestation_target_epoch: seen.iter().filter_map(|w| w.attestation_target_epoch).max(),
+    }
+}
+
+impl LeaderState {
+    /// Asks `worker` to split `share` into the sub-shares `req` asks for, sealed under their
+    /// attested session, and checks the verification vector of the sub-shares commits to the share
+    async fn request_sub_shares(
+        self,
+        client: reqwest::Client,
+        pk_set: PublicKeySet,
+        worker: crate::io::workers::WorkerRecord,
+        req: crate::enclave::types::SubShareRequest,
+    ) -> Result<(PublicKeySet, crate::enclave::types::SubShareResponse)> {
+        let plaintext = serde_json::to_vec(&req)?;
+        let resp: crate::enclave::types::SubShareResponse = self
+            .post_sealed(
+                &client,
+                &worker,
+                "/worker/v1/reshare/subshares",
+                &plaintext,
+                |sealed_request| crate::enclave::types::SealedReshareRequest {
+                    leader_pubkey: self.leader_pubkey_hex(),
+                    sealed_request,
+                },
+            )
+            .await?;
+        let commitment =
+            PublicKeySet::from_bytes(crate::parse_hex(&resp.commitment)?).map_err(|e| {
+                anyhow::anyhow!("Worker {} sent a bad verification vector: {e:?}", worker.id)
+            })?;
+        if commitment.public_key().to_bytes() != pk_set.public_key_share(req.index).to_bytes() {
+            bail!(
+                "Worker {} split a share other than {}",
+                worker.id,
+                req.index
+            )
+        }
+        if commitment.threshold() + 1 != req.threshold
+            || resp.encrypted_sub_shares.len() != req.recipients.len()
+        {
+            bail!(
+                "Worker {} split share {} into the wrong sub-shares",
+                worker.id,
+                req.index
+            )
+        }
+        Ok((commitment, resp))
+    }
+
+    /// Relays the sub-shares in `req` to the new worker they were encrypted to, sealed under their
+    /// attested session, returning once the worker acknowledged saving the share they combine into
+    async fn deliver_sub_shares(
+        self,
+        client: reqwest::Client,
+        worker: crate::io::workers::WorkerRecord,
+        req: crate::enclave::types::CombineSubSharesRequest,
+    ) -> Result<()> {
+        let plaintext = serde_json::to_vec(&req)?;
+        let resp: crate::enclave::types::WorkerKeyShareResponse = self
+            .post_sealed(
+                &client,
+                &worker,
+                "/worker/v1/reshare/combine",
+                &plaintext,
+                |sealed_request| crate::enclave::types::SealedReshareRequest {
+                    leader_pubkey: self.leader_pubkey_hex(),
+                    sealed_request,
+                },
+            )
+            .await?;
+        if crate::normalize_hex(&resp.pk_share_hex)?
+            != crate::normalize_hex(&req.watermark.pk_share_hex)?
+        {
+            bail!("Worker {} saved {} instead", worker.id, resp.pk_share_hex)
+        }
+        Ok(())
+    }
+}
+
+impl LeaderState {
+    /// Asks the old holder `worker` to delete the share `req` names, sealed under their attested
+    /// session, returning once the worker acknowledged it no longer holds it
+    async fn revoke_share(
+        self,
+        client: reqwest::Client,
+        worker: crate::io::workers::WorkerRecord,
+        req: crate::enclave::types::RevokeShareRequest,
+    ) -> Result<()> {
+        let plaintext = serde_json::to_vec(&req)?;
+        let resp: crate::enclave::types::WorkerKeyShareResponse = self
+            .post_sealed(
+                &client,
+                &worker,
+                "/worker/v1/reshare/revoke",
+                &plaintext,
+                |sealed_request| crate::enclave::types::SealedReshareRequest {
+                    leader_pubkey: self.leader_pubkey_hex(),
+                    sealed_request,
+                },
+            )
+            .await?;
+        if crate::normalize_hex(&resp.pk_share_hex)? != crate::normalize_hex(&req.pk_share_hex)? {
+            bail!("Worker {} revoked {} instead", worker.id, resp.pk_share_hex)
+        }
+        Ok(())
+    }
+
+    /// Re-shares `key` as `threshold`-of-n across `new_workers`, share `j` going to worker `j`,
+    /// without the key ever being reconstructed. The first `key.threshold` holders by index that
+    /// answer split their shares into sub-shares encrypted to the new workers, which the leader
+    /// relays for each new worker to combine into its share, starting from the highest watermarks
+    /// of all the old holders, so every one of them must report its watermarks. The new sharing
+    /// commits to the same public key and is saved only once every new worker acknowledged its
+    /// share, else this fails with `ReshareFailed` and the old shares stay in use. Every old holder
+    /// is then asked to delete its old share, and unless all of them acknowledge it this fails with
+    /// `RevocationFailed`, though the new shares are in use.
+    pub async fn reshare(
+        &self,
+        key: &ThresholdKey,
+        threshold: usize,
+        new_workers: Vec<crate::io::workers::WorkerRecord>,
+    ) -> Result<crate::enclave::types::ReshareKeyResponse> {
+        use crate::enclave::types::{ShareDelivery, ShareDeliveryStatus};
+
+        let pk_set = PublicKeySet::from_bytes(crate::parse_hex(&key.verification_vector)?)
+            .map_err(|e| anyhow::anyhow!("Bad verification vector: {e:?}"))?;
+        let recipients: Vec<String> = new_workers.iter().map(|w| w.ecies_pubkey.clone()).collect();
+        let client = reqwest::Client::new();
+
+        let mut failures = vec![];
+        let mut holders = vec![];
+        for share in key.shares.iter().cloned() {
+            match self.share_worker(&share) {
+                Ok(worker) => holders.push((share, worker)),
+                Err(e) => failures.push(format!("share {}: {e:#}", share.index)),
+            }
+        }
+
+        // The new shares must not sign below what any old share signed, including the shares not
+        // split, so every old holder must report its watermarks
+        let mut seen = vec![];
+        for ((share, _), watermark) in holders
+            .iter()
+            .zip(self.fetch_watermarks(&client, &holders).await)
+        {
+            match watermark {
+                Ok(watermark) => seen.push(watermark),
+                Err(e) => failures.push(format!("share {}: {e:#}", share.index)),
+            }
+        }
+        if !failures.is_empty() {
+            return Err(ReshareFailed { failures }.into());
+        }
+
+        let mut requests = tokio::task::JoinSet::new();
+        for (share, worker) in holders.iter().cloned() {
+            let req = crate::enclave::types::SubShareRequest {
+                worker_pk_hex: share.worker_pk_hex.clone(),
+                bls_pk_hex: key.bls_pk_hex.clone(),
+                index: share.index,
+                verification_vector: key.verification_vector.clone(),
+                pk_share_hex: share.pk_share_hex.clone(),
+                threshold,
+                recipients: recipients.clone(),
+            };
+            let sub_shares =
+                self.clone()
+                    .request_sub_shares(client.clone(), pk_set.clone(), worker, req);
+            requests.spawn(async move { (share.index, sub_shares.await) });
+        }
+        let mut split = BTreeMap::new();
+        while let Some(joined) = requests.join_next().await {
+            match joined {
+                Ok((index, Ok(sub_shares))) => {
+                    split.insert(index, sub_shares);
+                }
+                Ok((index, Err(e))) => {
+                    error!("No sub-shares of share {index}: {:#}", e);
+                    failures.push(format!("share {index}: {e:#}"));
+                }
+                Err(e) => failures.push(format!("{e}")),
+            }
+        }
+        if split.len() < key.threshold {
+            return Err(ReshareFailed { failures }.into());
+        }
+        let split: BTreeMap<_, _> = split.into_iter().take(key.threshold).collect();
+
+        let commitments: BTreeMap<_, _> = split
+            .iter()
+            .map(|(&index, (commitment, _))| (index, commitment.clone()))
+            .collect();
+        let new_pk_set = crate::crypto::reshare::combine_commitments(&commitments)?;
+        if new_pk_set.public_key() != pk_set.public_key() {
+            bail!(
+                "The re-shared verification vector does not commit to {}",
+                key.bls_pk_hex
+            )
+        }
+        let verification_vector = crate::to_0x_hex(new_pk_set.to_bytes());
+
+        let mut deliveries = tokio::task::JoinSet::new();
+        for (j, worker) in new_workers.iter().cloned().enumerate() {
+            let pk_share_hex = crate::to_0x_hex(new_pk_set.public_key_share(j).to_bytes());
+            let req = crate::enclave::types::CombineSubSharesRequest {
+                worker_pk_hex: worker.ecies_pubkey.clone(),
+                bls_pk_hex: key.bls_pk_hex.clone(),
+                index: j,
+                old_verification_vector: key.verification_vector.clone(),
+                verification_vector: verification_vector.clone(),
+                sub_shares: split
+                    .iter()
+                    .map(|(&from_index, (_, resp))| crate::enclave::types::SubShare {
+                        from_index,
+                        commitment: resp.commitment.clone(),
+                        encrypted_sub_share_hex: resp.encrypted_sub_shares[j].clone(),
+                    })
+                    .collect(),
+                watermark: highest_watermark(pk_share_hex, &seen),
+            };
+            let delivery = self.clone().deliver_sub_shares(client.clone(), worker, req);
+            deliveries.spawn(async move { (j, delivery.await) });
+        }
+        let mut outcomes = BTreeMap::new();
+        while let Some(joined) = deliveries.join_next().await {
+            match joined {
+                Ok((j, delivery)) => {
+                    outcomes.insert(j, delivery);
+                }
+                Err(e) => error!("Sub-share delivery task failed: {:?}", e),
+            }
+        }
+
+        let mut failures = vec![];
+        let mut shares = vec![];
+        let mut data = vec![];
+        for (j, worker) in new_workers.iter().enumerate() {
+            let pk_share_hex = crate::to_0x_hex(new_pk_set.public_key_share(j).to_bytes());
+            match outcomes.remove(&j) {
+                Some(Ok(())) => {}
+                Some(Err(e)) => {
+                    error!("Failed to re-share share {j}: {:#}", e);
+                    failures.push(format!("new share {j}: {e:#}"));
+                }
+                None => failures.push(format!("new share {j}: delivery was aborted")),
+            }
+            shares.push(ThresholdShare {
+                index: j,
+                worker_pk_hex: worker.ecies_pubkey.clone(),
+                pk_share_hex,
+                encrypted_sk_share_hex: String::new(),
+                acknowledged: true,
+                delivered_at: Some(crate::io::workers::now()),
+            });
+            data.push(ShareDelivery {
+                index: j,
+                worker_pk_hex: worker.ecies_pubkey.clone(),
+                status: ShareDeliveryStatus::Delivered,
+                message: None,
+            });
+        }
+        if !failures.is_empty() {
+            return Err(ReshareFailed { failures }.into());
+        }
+
+        crate::io::threshold_keys::write_threshold_key(&ThresholdKey {
+            bls_pk_hex: key.bls_pk_hex.clone(),
+            threshold,
+            verification_vector: verification_vector.clone(),
+            shares,
+        })?;
+        info!(
+            "Re-shared {} as {}-of-{}",
+            key.bls_pk_hex,
+            threshold,
+            new_workers.len()
+        );
+
+        // An old share that is not deleted still signs for the key with those of other old holders
+        let mut revocations = tokio::task::JoinSet::new();
+        for (share, worker) in holders {
+            let req = crate::enclave::types::RevokeShareRequest {
+                worker_pk_hex: share.worker_pk_hex.clone(),
+                bls_pk_hex: key.bls_pk_hex.clone(),
+                index: share.index,
+                verification_vector: key.verification_vector.clone(),
+                pk_share_hex: share.pk_share_hex.clone(),
+            };
+            let revocation = self.clone().revoke_share(client.clone(), worker, req);
+            revocations.spawn(async move { (share.index, revocation.await) });
+        }
+        let mut failures = vec![];
+        while let Some(joined) = revocations.join_next().await {
+            match joined {
+                Ok((_, Ok(()))) => {}
+                Ok((index, Err(e))) => {
+                    error!("Failed to revoke old share {index}: {:#}", e);
+                    failures.push(format!("old share {index}: {e:#}"));
+                }
+                Err(e) => failures.push(format!("{e}")),
+            }
+        }
+        if !failures.is_empty() {
+            return Err(RevocationFailed { failures }.into());
+        }
+        Ok(crate::enclave::types::ReshareKeyResponse {
+            verification_vector,
+            data,
+        })
+    }
+}
@@ -1,12 +1,24 @@
+pub mod datafeed;
 pub mod handlers;
+pub mod handshake;
+pub mod leader;
+pub mod worker;
 use anyhow::{Context, Result};
 
-fn attest_new_eth_key() -> Result<(
+fn attest_new_eth_key(
+    label: Option<String>,
+) -> Result<(
     crate::io::remote_attestation::AttestationEvidence,
     ecies::PublicKey,
 )> {
     // Generate a fresh SECP256K1 ETH keypair (saving ETH private key)
     let pk = crate::crypto::eth_keys::eth_key_gen()?;
+    if label.is_some() {
+        crate::io::key_metadata::set_eth_label(
+            &crate::crypto::eth_keys::eth_pk_to_hex(&pk),
+            label,
+        )?;
+    }
 
     // Commit to the payload
     let proof =
@@ -14,7 +26,10 @@ fn attest_new_eth_key() -> Result<(
     Ok((proof, pk))
 }
 
-fn attest_new_bls_key() -> Result<(
+fn attest_new_bls_key(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    label: Option<String>,
+) -> Result<(
     crate::io::remote_attestation::AttestationEvidence,
     blsttc::PublicKey,
 )> {
@@ -22,11 +37,285 @@ fn attest_new_bls_key() -> Result<(
     let sk = crate::crypto::bls_keys::new_bls_key(0);
     let pk = sk.public_keys().public_key();
     crate::crypto::bls_keys::save_bls_key(&sk).with_context(|| "Failed to save BLS key")?;
+    crate::io::key_metadata::write_bls_metadata(
+        &pk.to_hex(),
+        &crate::io::key_metadata::KeyMetadata::new(
+            crate::io::key_metadata::KeyOrigin::Generated,
+            label,
+        ),
+    )?;
 
     // Create a new slashing protection database
-    crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk.to_hex())?.write()?;
+    backend.write(
+        &crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk.to_hex())?,
+    )?;
 
     // Commit to the payload
     let proof = crate::io::remote_attestation::AttestationEvidence::new(&pk.to_bytes())?;
     Ok((proof, pk))
 }
+
+/// Derives the signing key of validator `index` from `seed` and saves it exactly like a generated
+/// key, recording its derivation path and `label`. Returns the key, its path, and whether it was
+/// newly saved.
+fn save_derived_bls_key(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    seed: &[u8],
+    index: u32,
+    label: Option<String>,
+) -> Result<(blsttc::PublicKey, String, bool)> {
+    let path = crate::crypto::bls_keys::signing_key_path(index);
+    let sk = crate::crypto::bls_keys::derive_bls_child_key(seed, &path)?;
+    let pk = sk.public_keys().public_key();
+
+    if crate::io::key_management::bls_key_exists(&pk.to_hex()) {
+        return Ok((pk, path, false));
+    }
+
+    crate::crypto::bls_keys::save_bls_key(&sk).with_context(|| "Failed to save BLS key")?;
+    crate::io::key_management::write_derivation_path(&pk.to_hex(), &path)?;
+    crate::io::key_metadata::write_bls_metadata(
+        &pk.to_hex(),
+        &crate::io::key_metadata::KeyMetadata::new(
+            crate::io::key_metadata::KeyOrigin::Derived,
+            label,
+        ),
+    )?;
+
+    // Only create a new slashing protection database if there is no history for this key
+    if !backend.exists(&pk.to_hex()) {
+        backend.write(
+            &crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk.to_hex())?,
+        )?;
+    }
+    Ok((pk, path, true))
+}
+
+/// Derives and saves the signing key of validator `index` from the enclave's seed. Returns None
+/// if it was already derived.
+fn attest_derived_bls_key(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    index: u32,
+    label: Option<String>,
+) -> Result<
+    Option<(
+        crate::io::remote_attestation::AttestationEvidence,
+        blsttc::PublicKey,
+        String,
+    )>,
+> {
+    let seed = crate::crypto::bls_keys::fetch_or_create_bls_seed()?;
+    let (pk, path, saved) = save_derived_bls_key(backend, &seed, index, label)?;
+    if !saved {
+        return Ok(None);
+    }
+
+    // Commit to the payload
+    let proof = crate::io::remote_attestation::AttestationEvidence::new(&pk.to_bytes())?;
+    Ok(Some((proof, pk, path)))
+}
+
+/// Derives and saves the key at the enclave's next seed index, skipping indices whose key was
+/// already saved, e.g. through `/eth/v1/keygen/bls/derive`. The index is only advanced once the
+/// key is saved, so a crash can never skip over an unsaved key.
+fn attest_deterministic_bls_key(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    label: Option<String>,
+) -> Result<(
+    crate::io::remote_attestation::AttestationEvidence,
+    blsttc::PublicKey,
+    String,
+)> {
+    let seed = crate::crypto::bls_keys::fetch_or_create_bls_seed()?;
+    let _guard = crate::crypto::bls_keys::lock_seed_index()?;
+    let mut index = crate::io::key_management::read_bls_seed_index()?;
+    let (pk, path) = loop {
+        let (pk, path, saved) = save_derived_bls_key(backend, &seed, index, label.clone())?;
+        index = index
+            .checked_add(1)
+            .with_context(|| "Exhausted the bls seed indices")?;
+        if saved {
+            break (pk, path);
+        }
+    };
+    crate::io::key_management::write_bls_seed_index(index)?;
+
+    // Commit to the payload
+    let proof = crate::io::remote_attestation::AttestationEvidence::new(&pk.to_bytes())?;
+    Ok((proof, pk, path))
+}
+
+/// Generates a mnemonic whose seed becomes the enclave's seed, then derives and saves the key of
+/// validator 0. The mnemonic is only returned, never saved. Returns None if the enclave already
+/// holds a seed.
+fn attest_new_mnemonic(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    label: Option<String>,
+) -> Result<
+    Option<(
+        String,
+        crate::io::remote_attestation::AttestationEvidence,
+        blsttc::PublicKey,
+        String,
+    )>,
+> {
+    let mnemonic = crate::crypto::bls_keys::new_mnemonic()?;
+    let seed = mnemonic.to_seed_normalized("");
+    if !crate::crypto::bls_keys::set_bls_seed(&seed)? {
+        return Ok(None);
+    }
+    let (pk, path, _) = save_derived_bls_key(backend, &seed, 0, label)?;
+
+    // Commit to the payload
+    let proof = crate::io::remote_attestation::AttestationEvidence::new(&pk.to_bytes())?;
+    Ok(Some((mnemonic.to_string(), proof, pk, path)))
+}
+
+/// Restores the enclave's seed from `mnemonic` and re-derives the keys of validators
+/// 0..`num_keys`, saving any that are missing. Returns None if the enclave holds another seed.
+fn recover_bls_keys(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    mnemonic: &str,
+    num_keys: u32,
+) -> Result<Option<Vec<(String, String)>>> {
+    let seed = crate::crypto::bls_keys::mnemonic_to_seed(mnemonic, "")?;
+    if !crate::crypto::bls_keys::set_bls_seed(&seed)? {
+        return Ok(None);
+    }
+    let mut keys = vec![];
+    for index in 0..num_keys {
+        let (pk, path, _) = save_derived_bls_key(backend, &seed, index, None)?;
+        keys.push((pk.to_hex(), path));
+    }
+    Ok(Some(keys))
+}
+
+/// Decrypts the EIP-2335 keystore and saves the BLS key exactly like a generated key, recording
+/// `label` in its metadata. Existing slashing protection history for the key is kept.
+fn import_bls_keystore(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    keystore: &String,
+    password: &String,
+    label: Option<String>,
+) -> Result<crate::enclave::types::ImportKeystoreStatus> {
+    let sk = crate::crypto::keystore::decrypt_bls_keystore(keystore, password)?;
+    let pk_hex = sk.public_keys().public_key().to_hex();
+
+    if crate::io::key_management::bls_key_exists(&pk_hex) {
+        return Ok(crate::enclave::types::ImportKeystoreStatus::Duplicate);
+    }
+
+    crate::crypto::bls_keys::save_bls_key(&sk).with_context(|| "Failed to save BLS key")?;
+    crate::io::key_metadata::write_bls_metadata(
+        &pk_hex,
+        &crate::io::key_metadata::KeyMetadata::new(
+            crate::io::key_metadata::KeyOrigin::Imported,
+            label,
+        ),
+    )?;
+
+    // Only create a new slashing protection database if there is no history for this key
+    if !backend.exists(&pk_hex) {
+        backend
+            .write(&crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk_hex)?)?;
+    }
+
+    Ok(crate::enclave::types::ImportKeystoreStatus::Imported)
+}
+
+/// Encrypts the saved BLS key into an EIP-2335 keystore under a fresh random password that is
+/// ECIES encrypted to `envelope_pk`. Returns the keystore and the hex-encoded password ciphertext,
+/// as `import_bls_keystore` expects them.
+fn export_bls_keystore(
+    pk_hex: &String,
+    envelope_pk: &ecies::PublicKey,
+) -> Result<(String, String)> {
+    let sk = crate::crypto::bls_keys::fetch_bls_sk(pk_hex)?;
+    let mut password = [0_u8; 32];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut password);
+    let password = hex::encode(password);
+
+    let path = crate::io::key_management::read_derivation_path(pk_hex).unwrap_or_default();
+    let keystore = crate::crypto::keystore::encrypt_bls_keystore(&sk, &password, &path)?;
+    let ct_password = crate::crypto::eth_keys::envelope_encrypt(envelope_pk, password.as_bytes())?;
+    Ok((keystore, crate::to_0x_hex(ct_password)))
+}
+
+/// Checks each worker's ETH key, and its evidence if `mrenclave` is set
+fn verify_worker_keys(
+    workers: &[crate::enclave::types::KeyGenResponse],
+    mrenclave: &Option<String>,
+) -> Result<Vec<ecies::PublicKey>> {
+    workers
+        .iter()
+        .map(|worker| {
+            let pk = crate::crypto::eth_keys::parse_eth_pk_hex(&worker.pk_hex)?;
+            if let Some(mrenclave) = mrenclave {
+                let attested = crate::enclave::types::KeyGenResponse {
+                    pk_hex: crate::crypto::eth_keys::eth_pk_to_hex(&pk),
+                    evidence: worker.evidence.clone(),
+                    address: None,
+                    signing_pk: None,
+                };
+                attested
+                    .validate_eth_ra(mrenclave)
+                    .with_context(|| format!("Bad evidence for worker {}", worker.pk_hex))?;
+            }
+            Ok(pk)
+        })
+        .collect()
+}
+
+/// Splits the saved BLS key into Shamir shares, encrypting share `i` to the ETH key of worker `i`
+fn split_bls_key(
+    pk_hex: &String,
+    threshold: usize,
+    worker_pks: Vec<ecies::PublicKey>,
+) -> Result<crate::enclave::types::SplitBlsKeyResponse> {
+    let sk = crate::crypto::bls_keys::fetch_bls_sk(pk_hex)?.secret_key();
+    let (pk_set, sk_shares) =
+        crate::crypto::bls_keys::split_bls_key(&sk, worker_pks.len(), threshold)?;
+
+    let mut shares = vec![];
+    for (index, (worker_pk, sk_share)) in worker_pks.into_iter().zip(sk_shares).enumerate() {
+        let k = crate::enclave::validator::RecipientKeys {
+            guardian_public_key: worker_pk,
+            public_key_share: sk_share.public_key_share(),
+            secret_key_share: sk_share,
+        }
+        .encrypt_to_recipient()?;
+        shares.push(crate::enclave::types::SplitBlsKeyShare {
+            worker_pk_hex: format!("0x{}", crate::crypto::eth_keys::eth_pk_to_hex(&worker_pk)),
+            index,
+            pk_share_hex: format!("0x{}", hex::encode(k.public_key_share.to_bytes())),
+            encrypted_sk_share_hex: format!("0x{}", k.encrypted_secret_key_share_hex),
+        });
+    }
+
+    let resp = crate::enclave::types::SplitBlsKeyResponse {
+        bls_pk_hex: format!("0x{}", pk_set.public_key().to_hex()),
+        threshold,
+        verification_vector: format!("0x{}", hex::encode(pk_set.to_bytes())),
+        shares,
+    };
+
+    // Remember which worker each share is for, so this enclave can deliver them and lead signing
+    crate::io::threshold_keys::write_threshold_key(&crate::io::threshold_keys::ThresholdKey {
+        bls_pk_hex: resp.bls_pk_hex.clone(),
+        threshold,
+        verification_vector: resp.verification_vector.clone(),
+        shares: resp
+            .shares
+            .iter()
+            .map(|share| crate::io::threshold_keys::ThresholdShare {
+                index: share.index,
+                worker_pk_hex: share.worker_pk_hex.clone(),
+                pk_share_hex: share.pk_share_hex.clone(),
+                encrypted_sk_share_hex: share.encrypted_sk_share_hex.clone(),
+                acknowledged: false,
+                delivered_at: None,
+            })
+            .collect(),
+    })?;
+    Ok(resp)
+}
@@ -0,0 +1,515 @@
+//! A worker enclave's side of threshold signing: what it reports to the leader it is registered
+//! with, its side of the attestation handshake, and the key shares the leader delivers to it
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::info;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// This worker's in-memory state: when it started serving, and its handshakes and attested
+/// sessions with leaders. Clones share the same state.
+#[derive(Clone, Default)]
+pub struct WorkerState {
+    started: Arc<OnceLock<Instant>>,
+    pending_handshakes: Arc<Mutex<HashMap<String, PendingHandshake>>>,
+    leader_sessions: Arc<Mutex<HashMap<String, LeaderSession>>>,
+}
+
+impl WorkerState {
+    /// Starts the uptime clock, called once the server is about to serve
+    pub fn start_uptime_clock(&self) {
+        self.started.get_or_init(Instant::now);
+    }
+
+    /// How long this enclave has been serving, zero before `start_uptime_clock`
+    pub fn uptime(&self) -> Duration {
+        self.started.get().map(|s| s.elapsed()).unwrap_or_default()
+    }
+}
+
+/// Decrypts the key share a leader delivered, checking it is share `req.index` of the
+/// verification vector and matches `req.pk_share_hex`
+pub fn open_key_share(
+    req: &crate::enclave::types::WorkerKeyShareRequest,
+) -> Result<blsttc::SecretKeyShare> {
+    let pk_set = verification_vector(&req.verification_vector, &req.bls_pk_hex)?;
+    let sk_share = decrypt_share(&req.worker_pk_hex, &req.encrypted_sk_share_hex)?;
+
+    let pk_share = sk_share.public_key_share();
+    if pk_share != pk_set.public_key_share(req.index) {
+        bail!(
+            "Share is not share {} of the verification vector",
+            req.index
+        )
+    }
+    if hex::encode(pk_share.to_bytes())
+        != crate::crypto::bls_keys::sanitize_bls_pk_hex(&req.pk_share_hex)?
+    {
+        bail!("Share does not match {}", req.pk_share_hex)
+    }
+    Ok(sk_share)
+}
+
+/// Parses a verification vector, checking it commits to `bls_pk_hex`
+fn verification_vector(vvec_hex: &str, bls_pk_hex: &String) -> Result<blsttc::PublicKeySet> {
+    let pk_set = blsttc::PublicKeySet::from_bytes(crate::parse_hex(vvec_hex)?)
+        .map_err(|e| anyhow!("Bad verification vector: {e:?}"))?;
+    let bls_pk_hex = crate::crypto::bls_keys::sanitize_bls_pk_hex(bls_pk_hex)?;
+    if pk_set.public_key().to_hex() != bls_pk_hex {
+        bail!("Verification vector does not commit to 0x{bls_pk_hex}")
+    }
+    Ok(pk_set)
+}
+
+/// Decrypts a BLS secret key share ECIES encrypted to this worker's ETH key `worker_pk_hex`
+fn decrypt_share(worker_pk_hex: &String, ct_hex: &str) -> Result<blsttc::SecretKeyShare> {
+    let ct = crate::parse_hex(ct_hex)?;
+    let sk_bytes = Zeroizing::new(
+        crate::crypto::eth_keys::envelope_decrypt_from_saved_sk(worker_pk_hex, &ct)
+            .with_context(|| format!("Failed to decrypt the share for {worker_pk_hex}"))?,
+    );
+    let sk_bytes: [u8; 32] = match sk_bytes.as_slice().try_into() {
+        Ok(bytes) => bytes,
+        Err(_) => bail!("Decrypted share has the wrong length"),
+    };
+    blsttc::SecretKeyShare::from_bytes(sk_bytes)
+        .map_err(|e| anyhow!("Decrypted share is not a BLS secret key: {e:?}"))
+}
+
+/// Saves a key share opened with `open_key_share` as a BLS key, so the sign route signs with it
+/// under its own slashing protection. Returns the 0x-prefixed public key share.
+pub fn save_key_share(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    sk_share: &blsttc::SecretKeyShare,
+) -> Result<String> {
+    let pk_share_hex = hex::encode(sk_share.public_key_share().to_bytes());
+    let created = !crate::io::key_management::bls_key_exists(&pk_share_hex);
+    crate::io::key_management::write_bls_key(
+        &pk_share_hex,
+        &Zeroizing::new(hex::encode(sk_share.to_bytes())),
+    )?;
+    if !backend.exists(&pk_share_hex) {
+        backend.write(
+            &crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk_share_hex)?,
+        )?;
+    }
+    if created {
+        crate::enclave::shared::metrics::record_bls_keys_created(
+            crate::enclave::shared::metrics::KeySource::Import,
+            1,
+        );
+    }
+    Ok(format!("0x{pk_share_hex}"))
+}
+
+/// The slashing protection watermarks of the key share `pk_share_hex`, null for a share that has
+/// not signed a block or attestation
+pub fn watermark(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    pk_share_hex: &str,
+) -> Result<crate::enclave::types::WorkerWatermarkResponse> {
+    let mut watermark = crate::enclave::types::WorkerWatermarkResponse {
+        pk_share_hex: format!("0x{pk_share_hex}"),
+        block_slot: None,
+        attestation_source_epoch: None,
+        attestation_target_epoch: None,
+    };
+    if !backend.exists(pk_share_hex) {
+        return Ok(watermark);
+    }
+
+    let db = backend.read(pk_share_hex)?;
+    if !db.signed_blocks.is_empty() {
+        watermark.block_slot = Some(db.get_latest_signed_block_slot());
+    }
+    if !db.signed_attestations.is_empty() {
+        let (src, tgt) = db.get_latest_signed_attestation_epochs();
+        watermark.attestation_source_epoch = Some(src);
+        watermark.attestation_target_epoch = Some(tgt);
+    }
+    Ok(watermark)
+}
+
+/// Raises the slashing protection watermarks of the key share `pk_share_hex` to `watermark`,
+/// never lowering them, so a re-shared share cannot sign what the old shares already signed
+pub fn seed_watermark(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    pk_share_hex: &String,
+    watermark: &crate::enclave::types::WorkerWatermarkResponse,
+) -> Result<()> {
+    use crate::eth2::slash_protection::{
+        SignedAttestationEpochs, SignedBlockSlot, SlashingProtectionData,
+    };
+
+    let pk_share_hex = crate::crypto::bls_keys::sanitize_bls_pk_hex(pk_share_hex)?;
+    let mut seen = SlashingProtectionData::from_pk_hex(&pk_share_hex)?;
+    if let Some(slot) = watermark.block_slot {
+        seen.signed_blocks.push(SignedBlockSlot {
+            slot,
+            signing_root: None,
+        });
+    }
+    if let (Some(source_epoch), Some(target_epoch)) = (
+        watermark.attestation_source_epoch,
+        watermark.attestation_target_epoch,
+    ) {
+        seen.signed_attestations.push(SignedAttestationEpochs {
+            source_epoch,
+            target_epoch,
+            signing_root: None,
+        });
+    }
+    if seen.signed_blocks.is_empty() && seen.signed_attestations.is_empty() {
+        return Ok(());
+    }
+
+    // An unreadable history must not be mistaken for an empty one
+    let mut db = if backend.exists(&pk_share_hex) {
+        backend.read(&pk_share_hex)?
+    } else {
+        SlashingProtectionData::from_pk_hex(&pk_share_hex)?
+    };
+    db.merge_watermarks(&seen, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB);
+    backend
+        .write(&db)
+        .with_context(|| format!("Failed to save slashing protection for 0x{pk_share_hex}"))
+}
+
+/// Returned when a leader sends a key share or re-sharing request without an attested session, or
+/// its session lapsed
+#[derive(Debug)]
+pub struct NoSession {
+    pub leader_pubkey: String,
+}
+
+impl std::fmt::Display for NoSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No attested session with leader {}, handshake first",
+            self.leader_pubkey
+        )
+    }
+}
+
+impl std::error::Error for NoSession {}
+
+/// A handshake a leader started, waiting for its evidence bound to the challenge
+struct PendingHandshake {
+    leader_pk: ecies::PublicKey,
+    worker_pk: ecies::PublicKey,
+    expires: Instant,
+}
+
+/// A leader that attested to this worker, and the key of their session
+struct LeaderSession {
+    worker_pk_hex: String,
+    key: crate::crypto::session::SessionKey,
+    expires: Instant,
+}
+
+/// Fails unless `worker_pk_hex` is the ETH key this worker attested to in the session
+fn check_session_key(worker_pk_hex: &str, session_pk_hex: &str) -> Result<()> {
+    if crate::normalize_hex(worker_pk_hex)? != crate::normalize_hex(session_pk_hex)? {
+        bail!("The request is for {worker_pk_hex}, but the session attested {session_pk_hex}")
+    }
+    Ok(())
+}
+
+impl WorkerState {
+    /// Starts a handshake with a leader: attests that this worker holds `req.worker_pubkey`, bound
+    /// to the leader's nonce, and remembers a challenge for the leader to bind its own evidence to
+    pub async fn start_handshake(
+        &self,
+        req: &crate::enclave::types::HandshakeChallengeRequest,
+    ) -> Result<crate::enclave::types::HandshakeChallengeResponse> {
+        let leader_pk = crate::crypto::eth_keys::parse_eth_pk_hex(&req.leader_pubkey)?;
+        let worker_pk = crate::crypto::eth_keys::parse_eth_pk_hex(&req.worker_pubkey)?;
+        let worker_pk_hex = crate::to_0x_hex(worker_pk.serialize_compressed());
+        crate::crypto::eth_keys::fetch_eth_key(&worker_pk_hex)
+            .with_context(|| format!("This worker does not hold {worker_pk_hex}"))?;
+
+        let evidence =
+            crate::enclave::secure_signer::handshake::attest(&worker_pk_hex, &req.nonce, req.kind)
+                .await?;
+        let challenge = crate::enclave::secure_signer::handshake::new_nonce()?;
+
+        let mut pending = self
+            .pending_handshakes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        pending.retain(|_, handshake| handshake.expires > now);
+        if pending.len() >= crate::constants::MAX_PENDING_HANDSHAKES {
+            bail!("Too many handshakes in flight")
+        }
+        pending.insert(
+            challenge.clone(),
+            PendingHandshake {
+                leader_pk,
+                worker_pk,
+                expires: now + Duration::from_secs(crate::constants::HANDSHAKE_CHALLENGE_TTL_SECS),
+            },
+        );
+        Ok(crate::enclave::types::HandshakeChallengeResponse {
+            challenge,
+            evidence,
+        })
+    }
+
+    /// Finishes a handshake: the leader's evidence must answer one of our challenges and pass the
+    /// measurement policy. Returns how long the session is trusted.
+    pub fn finish_handshake(
+        &self,
+        req: &crate::enclave::types::HandshakeRequest,
+    ) -> Result<Duration> {
+        let leader_pk = crate::crypto::eth_keys::parse_eth_pk_hex(&req.leader_pubkey)?;
+        let Some(handshake) = self
+            .pending_handshakes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&req.challenge)
+        else {
+            bail!("Unknown handshake challenge")
+        };
+        if handshake.expires <= Instant::now() {
+            bail!("The handshake challenge expired")
+        }
+        if handshake.leader_pk != leader_pk {
+            bail!("The handshake was started by another leader")
+        }
+
+        let leader_pk_hex = crate::to_0x_hex(leader_pk.serialize_compressed());
+        let verdict = crate::enclave::secure_signer::handshake::verify_peer(
+            &leader_pk_hex,
+            &req.challenge,
+            &req.evidence,
+        )?;
+        let worker_pk_hex = crate::to_0x_hex(handshake.worker_pk.serialize_compressed());
+        let worker_sk = crate::crypto::eth_keys::fetch_eth_key(&worker_pk_hex)?;
+        let key = crate::crypto::session::session_key(
+            &worker_sk,
+            &leader_pk,
+            &leader_pk,
+            &handshake.worker_pk,
+        )?;
+
+        let ttl = crate::enclave::secure_signer::handshake::peer_attestation_ttl();
+        info!(
+            "Leader {leader_pk_hex} attested with MRENCLAVE {}",
+            verdict.mrenclave.unwrap_or_default()
+        );
+        self.leader_sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                leader_pk_hex,
+                LeaderSession {
+                    worker_pk_hex,
+                    key,
+                    expires: Instant::now() + ttl,
+                },
+            );
+        Ok(ttl)
+    }
+
+    /// Opens a request sealed under the session of the leader `leader_pubkey`, failing with
+    /// `NoSession` if that leader has no attested session that is still trusted. Returns the
+    /// request and the ETH key this worker attested to in the session.
+    fn open_sealed<T: serde::de::DeserializeOwned>(
+        &self,
+        leader_pubkey: &str,
+        sealed: &str,
+    ) -> Result<(T, String)> {
+        let no_session = || NoSession {
+            leader_pubkey: leader_pubkey.to_string(),
+        };
+        let leader_pk = crate::crypto::eth_keys::parse_eth_pk_hex(&leader_pubkey.to_string())
+            .map_err(|_| no_session())?;
+        let leader_pk_hex = crate::to_0x_hex(leader_pk.serialize_compressed());
+
+        let mut sessions = self
+            .leader_sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(session) = sessions.get(&leader_pk_hex) else {
+            return Err(no_session().into());
+        };
+        if session.expires <= Instant::now() {
+            sessions.remove(&leader_pk_hex);
+            return Err(no_session().into());
+        }
+
+        let opened = crate::crypto::session::open(&session.key, &crate::parse_hex(sealed)?)?;
+        Ok((
+            serde_json::from_slice(&opened)?,
+            session.worker_pk_hex.clone(),
+        ))
+    }
+
+    /// Opens a key share sealed under the session of the leader that sent it, failing with
+    /// `NoSession` if that leader has no attested session that is still trusted
+    pub fn open_sealed_key_share(
+        &self,
+        req: &crate::enclave::types::SealedKeyShareRequest,
+    ) -> Result<crate::enclave::types::WorkerKeyShareRequest> {
+        let (share, session_pk_hex): (crate::enclave::types::WorkerKeyShareRequest, _) =
+            self.open_sealed(&req.leader_pubkey, &req.sealed_share)?;
+        check_session_key(&share.worker_pk_hex, &session_pk_hex)?;
+        Ok(share)
+    }
+
+    /// Opens a request for sub-shares sealed under the session of the leader that sent it, failing
+    /// with `NoSession` like `open_sealed_key_share`
+    pub fn open_sealed_sub_share_request(
+        &self,
+        req: &crate::enclave::types::SealedReshareRequest,
+    ) -> Result<crate::enclave::types::SubShareRequest> {
+        let (sub_share_req, session_pk_hex): (crate::enclave::types::SubShareRequest, _) =
+            self.open_sealed(&req.leader_pubkey, &req.sealed_request)?;
+        check_session_key(&sub_share_req.worker_pk_hex, &session_pk_hex)?;
+        Ok(sub_share_req)
+    }
+
+    /// Opens a request to revoke a share sealed under the session of the leader that sent it,
+    /// failing with `NoSession` like `open_sealed_key_share`
+    pub fn open_sealed_revoke_request(
+        &self,
+        req: &crate::enclave::types::SealedReshareRequest,
+    ) -> Result<crate::enclave::types::RevokeShareRequest> {
+        let (revoke_req, session_pk_hex): (crate::enclave::types::RevokeShareRequest, _) =
+            self.open_sealed(&req.leader_pubkey, &req.sealed_request)?;
+        check_session_key(&revoke_req.worker_pk_hex, &session_pk_hex)?;
+        Ok(revoke_req)
+    }
+
+    /// Opens sub-shares sealed under the session of the leader that relayed them, failing with
+    /// `NoSession` like `open_sealed_key_share`
+    pub fn open_sealed_sub_shares(
+        &self,
+        req: &crate::enclave::types::SealedReshareRequest,
+    ) -> Result<crate::enclave::types::CombineSubSharesRequest> {
+        let (sub_shares, session_pk_hex): (crate::enclave::types::CombineSubSharesRequest, _) =
+            self.open_sealed(&req.leader_pubkey, &req.sealed_request)?;
+        check_session_key(&sub_shares.worker_pk_hex, &session_pk_hex)?;
+        Ok(sub_shares)
+    }
+}
+
+/// Splits this worker's share `req.index` of `req.bls_pk_hex` into sub-shares for the new
+/// workers `req.recipients`, each ECIES encrypted to its recipient
+pub fn sub_share(
+    req: &crate::enclave::types::SubShareRequest,
+) -> Result<crate::enclave::types::SubShareResponse> {
+    let pk_set = verification_vector(&req.verification_vector, &req.bls_pk_hex)?;
+    let pk_share_hex = crate::crypto::bls_keys::sanitize_bls_pk_hex(&req.pk_share_hex)?;
+    if hex::encode(pk_set.public_key_share(req.index).to_bytes()) != pk_share_hex {
+        bail!(
+            "0x{pk_share_hex} is not share {} of the verification vector",
+            req.index
+        )
+    }
+    let recipients = req
+        .recipients
+        .iter()
+        .map(crate::crypto::eth_keys::parse_eth_pk_hex)
+        .collect::<Result<Vec<_>>>()?;
+
+    let sk = crate::crypto::bls_keys::fetch_bls_sk(&pk_share_hex)
+        .with_context(|| format!("This worker does not hold 0x{pk_share_hex}"))?
+        .secret_key();
+    let (commitment, sub_shares) =
+        crate::crypto::bls_keys::split_bls_key(&sk, recipients.len(), req.threshold)?;
+    let encrypted_sub_shares = recipients
+        .iter()
+        .zip(sub_shares)
+        .map(|(recipient, sub_share)| {
+            let sub_share_bytes = Zeroizing::new(sub_share.to_bytes());
+            crate::crypto::eth_keys::envelope_encrypt(recipient, sub_share_bytes.as_slice())
+                .map(crate::to_0x_hex)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    info!(
+        "Split share {} of 0x{} into {} sub-shares",
+        req.index,
+        pk_set.public_key().to_hex(),
+        encrypted_sub_shares.len()
+    );
+    Ok(crate::enclave::types::SubShareResponse {
+        commitment: crate::to_0x_hex(commitment.to_bytes()),
+        encrypted_sub_shares,
+    })
+}
+
+/// The share a revoke request names, checked to be share `req.index` of the old verification
+/// vector, so a leader can only revoke shares of the key it re-shared. Returns it without the 0x
+/// prefix.
+pub fn revoked_share(req: &crate::enclave::types::RevokeShareRequest) -> Result<String> {
+    let pk_set = verification_vector(&req.verification_vector, &req.bls_pk_hex)?;
+    let pk_share_hex = crate::crypto::bls_keys::sanitize_bls_pk_hex(&req.pk_share_hex)?;
+    if hex::encode(pk_set.public_key_share(req.index).to_bytes()) != pk_share_hex {
+        bail!(
+            "0x{pk_share_hex} is not share {} of the verification vector",
+            req.index
+        )
+    }
+    Ok(pk_share_hex)
+}
+
+/// Decrypts the sub-shares a leader relayed and combines them into share `req.index` of the new
+/// sharing, checking each sub-share against the verification vector of the old share it was
+/// split from and the result against the new verification vector
+pub fn combine_sub_shares(
+    req: &crate::enclave::types::CombineSubSharesRequest,
+) -> Result<blsttc::SecretKeyShare> {
+    let old_pk_set = verification_vector(&req.old_verification_vector, &req.bls_pk_hex)?;
+    let pk_set = verification_vector(&req.verification_vector, &req.bls_pk_hex)?;
+    if req.sub_shares.len() != old_pk_set.threshold() + 1 {
+        bail!(
+            "Need sub-shares of {} old shares, got {}",
+            old_pk_set.threshold() + 1,
+            req.sub_shares.len()
+        )
+    }
+
+    let mut sub_shares = std::collections::BTreeMap::new();
+    for sub in &req.sub_shares {
+        let commitment = blsttc::PublicKeySet::from_bytes(crate::parse_hex(&sub.commitment)?)
+            .map_err(|e| {
+                anyhow!(
+                    "Bad verification vector of sub-share {}: {e:?}",
+                    sub.from_index
+                )
+            })?;
+        if commitment.public_key().to_bytes()
+            != old_pk_set.public_key_share(sub.from_index).to_bytes()
+        {
+            bail!(
+                "Sub-shares were not split from old share {}",
+                sub.from_index
+            )
+        }
+        let sub_share = decrypt_share(&req.worker_pk_hex, &sub.encrypted_sub_share_hex)?;
+        if sub_share.public_key_share() != commitment.public_key_share(req.index) {
+            bail!(
+                "Sub-share of old share {} is not share {} of its verification vector",
+                sub.from_index,
+                req.index
+            )
+        }
+        if sub_shares.insert(sub.from_index, sub_share).is_some() {
+            bail!("Got old share {} twice", sub.from_index)
+        }
+    }
+
+    let share = crate::crypto::reshare::combine_sub_shares(&sub_shares)?;
+    if share.public_key_share() != pk_set.public_key_share(req.index) {
+        bail!(
+            "Combined share is not share {} of the new verification vector",
+            req.index
+        )
+    }
+    Ok(share)
+}
@@ -0,0 +1,162 @@
+use anyhow::{bail, Context, Result};
+use axum::{
+    extract::State,
+    http::{header, Method, Request},
+    middleware::Next,
+    response::IntoResponse,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Routes every caller may use without the token, unless the scope is `All`. Reads, signing and
+/// attesting are open, everything that changes keys or their settings needs the token. Workers
+/// also handshake with and take key shares from their leader without it, as the leader proves
+/// itself by attestation and a share is sealed under their attested session.
+const OPEN_POST_ROUTES: [&str; 7] = [
+    "/api/v1/eth2/sign/",
+    "/leader/v1/eth2/sign/",
+    "/eth/v1/sign/",
+    "/eth/v1/aggregate",
+    "/eth/v1/verify",
+    "/eth/v1/remote-attestation/",
+    "/worker/v1/",
+];
+
+/// Which routes need the API token
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTokenScope {
+    /// Routes that add, delete or reconfigure keys
+    #[default]
+    Admin,
+    /// Every route, including signing and listing keys
+    All,
+}
+
+impl std::str::FromStr for ApiTokenScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "admin" => Ok(ApiTokenScope::Admin),
+            "all" => Ok(ApiTokenScope::All),
+            _ => bail!("Unknown API token scope {s}, expected admin or all"),
+        }
+    }
+}
+
+/// Whether `method` requests to `path` add, delete or reconfigure keys, rather than read, sign or
+/// attest
+pub fn is_admin_route(method: &Method, path: &str) -> bool {
+    let read = method == Method::GET || method == Method::HEAD;
+    let open_post =
+        method == Method::POST && OPEN_POST_ROUTES.iter().any(|route| path.starts_with(route));
+    !(read || open_post)
+}
+
+/// The bearer token that `require_api_token` checks requests for
+#[derive(Clone)]
+pub struct ApiToken {
+    token: std::sync::Arc<String>,
+    scope: ApiTokenScope,
+}
+
+impl std::fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiToken")
+            .field("token", &"<redacted>")
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+impl ApiToken {
+    pub fn new(token: &str, scope: ApiTokenScope) -> Result<Self> {
+        let token = token.trim();
+        if token.is_empty() {
+            bail!("The API token is empty")
+        }
+        Ok(ApiToken {
+            token: std::sync::Arc::new(token.to_string()),
+            scope,
+        })
+    }
+
+    /// Reads the token saved at `path`, or generates a random one and saves it there, readable by
+    /// the owner only, for the operator to hand to clients
+    pub fn load_or_generate(path: &str, scope: ApiTokenScope) -> Result<Self> {
+        let mut bytes = [0_u8; 32];
+        openssl::rand::rand_bytes(&mut bytes)?;
+        let token = hex::encode(bytes);
+        // Never replace a token clients may already use
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)
+        {
+            Ok(mut file) => {
+                file.write_all(token.as_bytes())
+                    .with_context(|| "Failed to write the API token")?;
+                file.sync_all()
+                    .with_context(|| "Failed to sync the API token")?;
+                ApiToken::new(&token, scope)
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let token = std::fs::read_to_string(path)
+                    .with_context(|| format!("Unable to read the API token at {path}"))?;
+                ApiToken::new(&token, scope)
+            }
+            Err(e) => Err(e).with_context(|| format!("Failed to create the API token at {path}")),
+        }
+    }
+
+    /// Whether `method` requests to `path` need the token
+    pub fn protects(&self, method: &Method, path: &str) -> bool {
+        self.scope == ApiTokenScope::All || is_admin_route(method, path)
+    }
+
+    /// Compares `candidate` to the token in constant time. Both are hashed first so neither
+    /// their contents nor their lengths leak through timing.
+    pub fn matches(&self, candidate: &str) -> bool {
+        let expected = openssl::sha::sha256(self.token.as_bytes());
+        let candidate = openssl::sha::sha256(candidate.as_bytes());
+        openssl::memcmp::eq(&expected, &candidate)
+    }
+}
+
+/// Refuses requests to the routes `token` protects with 401, unless they carry
+/// `Authorization: Bearer <token>`
+pub async fn require_api_token<B>(
+    State(token): State<ApiToken>,
+    req: Request<B>,
+    next: Next<B>,
+) -> axum::response::Response {
+    if !token.protects(req.method(), req.uri().path()) {
+        return next.run(req).await;
+    }
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|bearer| token.matches(bearer));
+    if authorized {
+        return next.run(req).await;
+    }
+    error!(
+        "Refusing {} {} without a valid API token",
+        req.method(),
+        req.uri().path()
+    );
+    (
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::UNAUTHORIZED,
+            "Missing or invalid API token",
+        ),
+    )
+        .into_response()
+}
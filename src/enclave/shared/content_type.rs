@@ -0,0 +1,69 @@
+use axum::{
+    http::{header, Method, Request},
+    middleware::Next,
+    response::IntoResponse,
+};
+use log::error;
+
+/// Whether the request carries a body, by its Content-Length or chunked Transfer-Encoding
+fn has_body<B>(req: &Request<B>) -> bool {
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    match content_length {
+        Some(len) => len > 0,
+        None => req.headers().contains_key(header::TRANSFER_ENCODING),
+    }
+}
+
+/// Whether `content_type` is `application/json`, parameters such as the charset aside
+fn is_json(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|essence| essence.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// Refuses request bodies that are not `application/json` with 415 before any route reads
+/// them. Every route that takes a body takes JSON, including those that parse it themselves.
+pub async fn require_json<B>(req: Request<B>, next: Next<B>) -> axum::response::Response {
+    let takes_body =
+        [Method::POST, Method::PUT, Method::PATCH, Method::DELETE].contains(req.method());
+    if !takes_body || !has_body(&req) {
+        return next.run(req).await;
+    }
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if is_json(content_type) {
+        return next.run(req).await;
+    }
+    error!(
+        "Refusing {} {} with Content-Type {content_type:?}",
+        req.method(),
+        req.uri().path()
+    );
+    crate::enclave::types::ErrorResponse::new(
+        axum::http::status::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        "Expected request with `Content-Type: application/json`",
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_json() {
+        assert!(is_json("application/json"));
+        assert!(is_json("Application/JSON; charset=utf-8"));
+        assert!(!is_json("text/html"));
+        assert!(!is_json("application/jsonp"));
+        assert!(!is_json(""));
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{bail, Result};
+use axum::{
+    extract::State,
+    http::{header, HeaderValue, Method, Request},
+    middleware::Next,
+    response::IntoResponse,
+};
+use log::error;
+
+/// Request headers browsers may send cross origin, for the API token and JSON bodies
+const ALLOWED_HEADERS: &str = "authorization, content-type";
+
+/// Which browser origins may call the API, with which methods. Only reads are allowed unless
+/// mutating methods are explicitly opted in to.
+#[derive(Clone, Debug)]
+pub struct CorsConfig {
+    /// Exact origins, such as `https://dashboard.example.com`, or `*` for any
+    allowed_origins: Vec<String>,
+    allowed_methods: Vec<Method>,
+    max_age_secs: u64,
+}
+
+impl CorsConfig {
+    /// Refuses methods other than GET and HEAD unless `allow_mutating`
+    pub fn new(
+        allowed_origins: Vec<String>,
+        allowed_methods: Vec<Method>,
+        max_age_secs: u64,
+        allow_mutating: bool,
+    ) -> Result<Self> {
+        if allowed_origins.is_empty() {
+            bail!("CORS needs at least one allowed origin")
+        }
+        if let Some(method) = allowed_methods
+            .iter()
+            .find(|method| !is_read(method) && !allow_mutating)
+        {
+            bail!("Allowing {method} cross origin needs the explicit opt-in to mutating routes")
+        }
+        Ok(CorsConfig {
+            allowed_origins: allowed_origins
+                .into_iter()
+                .map(|origin| origin.trim_end_matches('/').to_string())
+                .collect(),
+            allowed_methods,
+            max_age_secs,
+        })
+    }
+
+    /// Parses comma separated origins and methods, e.g. from the command line
+    pub fn parse(
+        allowed_origins: &str,
+        allowed_methods: Option<&str>,
+        max_age_secs: u64,
+        allow_mutating: bool,
+    ) -> Result<Self> {
+        let list = |s: &str| -> Vec<String> {
+            s.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        };
+        let allowed_methods = match allowed_methods {
+            Some(methods) => list(methods)
+                .iter()
+                .map(|method| {
+                    Method::from_bytes(method.to_uppercase().as_bytes())
+                        .map_err(|_| anyhow::anyhow!("Unknown method {method}"))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => vec![Method::GET, Method::HEAD],
+        };
+        CorsConfig::new(
+            list(allowed_origins),
+            allowed_methods,
+            max_age_secs,
+            allow_mutating,
+        )
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == origin)
+    }
+
+    fn allows_method(&self, method: &Method) -> bool {
+        self.allowed_methods.contains(method)
+    }
+
+    fn allowed_methods_header(&self) -> String {
+        self.allowed_methods
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn is_read(method: &Method) -> bool {
+    method == Method::GET || method == Method::HEAD
+}
+
+/// Answers CORS preflight requests, and adds `Access-Control-Allow-Origin` to the responses of
+/// cross origin requests `cors` allows. Requests without an `Origin` are passed through as is,
+/// and disallowed ones get no CORS headers, so browsers block them.
+pub async fn cors<B>(
+    State(cors): State<CorsConfig>,
+    req: Request<B>,
+    next: Next<B>,
+) -> axum::response::Response {
+    let Some(origin) = req
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+    else {
+        return next.run(req).await;
+    };
+    let allowed_origin = cors.allows_origin(&origin);
+
+    // A preflight asks whether the method it names may be sent
+    let preflight_method = req
+        .headers()
+        .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|value| Method::from_bytes(value.as_bytes()).ok());
+    if req.method() == Method::OPTIONS {
+        if let Some(method) = preflight_method {
+            if !(allowed_origin && cors.allows_method(&method)) {
+                error!("Refusing CORS preflight for {method} from {origin}");
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::FORBIDDEN,
+                    format!("{method} requests from {origin} are not allowed"),
+                )
+                .into_response();
+            }
+            return (
+                axum::http::status::StatusCode::NO_CONTENT,
+                [
+                    (header::ACCESS_CONTROL_ALLOW_ORIGIN, origin),
+                    (
+                        header::ACCESS_CONTROL_ALLOW_METHODS,
+                        cors.allowed_methods_header(),
+                    ),
+                    (
+                        header::ACCESS_CONTROL_ALLOW_HEADERS,
+                        ALLOWED_HEADERS.to_string(),
+                    ),
+                    (
+                        header::ACCESS_CONTROL_MAX_AGE,
+                        cors.max_age_secs.to_string(),
+                    ),
+                    (header::VARY, header::ORIGIN.to_string()),
+                ],
+            )
+                .into_response();
+        }
+    }
+
+    let allowed = allowed_origin && cors.allows_method(req.method());
+    let mut response = next.run(req).await;
+    if allowed {
+        if let Ok(origin) = HeaderValue::from_str(&origin) {
+            let headers = response.headers_mut();
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+            headers.append(header::VARY, HeaderValue::from_static("origin"));
+        }
+    }
+    response
+}
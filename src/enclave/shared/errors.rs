@@ -0,0 +1,46 @@
+use axum::{
+    http::{header, Request},
+    middleware::Next,
+    response::IntoResponse,
+};
+use log::error;
+
+/// Error bodies longer than this are not quoted in the rewritten message
+const MAX_QUOTED_ERROR_BYTES: usize = 4096;
+
+/// Rewrites error responses that are not JSON into an `ErrorResponse`, so clients get the same
+/// body from every route. Handlers already answer with `ErrorResponse`, this catches axum's own
+/// rejections, such as a malformed body, an unknown route or a body over the size limit, whose
+/// plain text becomes the message.
+pub async fn json_errors<B>(req: Request<B>, next: Next<B>) -> axum::response::Response {
+    let response = next.run(req).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let message = match hyper::body::to_bytes(body).await {
+        Ok(bytes) if !bytes.is_empty() && bytes.len() <= MAX_QUOTED_ERROR_BYTES => {
+            String::from_utf8_lossy(&bytes).trim().to_string()
+        }
+        Ok(_) => status.canonical_reason().unwrap_or_default().to_string(),
+        Err(e) => {
+            error!("Failed to read the {status} error body: {e}");
+            status.canonical_reason().unwrap_or_default().to_string()
+        }
+    };
+    let mut response = crate::enclave::types::ErrorResponse::new(status, message).into_response();
+    // Keep headers such as WWW-Authenticate or Allow, but not the stale body length and type
+    for (name, value) in parts.headers.iter() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+    response
+}
@@ -0,0 +1,64 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{
+    DeleteKeyStatus, DeleteKeysRequest, DeleteKeysResponse, DeleteKeysResponseInner,
+};
+use crate::io::key_management;
+
+/// Deletes each requested BLS key, overwriting the secret before unlinking. The slashing
+/// protection history is kept so the key can never be re-imported at an earlier slot.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<DeleteKeysRequest>,
+) -> axum::response::Response {
+    info!("delete_bls_keys()");
+    let mut data = vec![];
+    for pk in req.pubkeys.iter() {
+        let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(pk) {
+            Ok(pk) => pk,
+            Err(e) => {
+                data.push(DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::Error,
+                    message: Some(format!("Bad bls_pk_hex, {:?}", e)),
+                });
+                continue;
+            }
+        };
+
+        // Wait for in-flight signatures with the key to finish first
+        let _guard = state.sign_locks.lock(&bls_pk_hex).await;
+
+        if !key_management::bls_key_exists(&bls_pk_hex) {
+            data.push(DeleteKeysResponseInner {
+                status: DeleteKeyStatus::NotFound,
+                message: None,
+            });
+            continue;
+        }
+
+        let inner = match crate::crypto::bls_keys::delete_bls_key(&bls_pk_hex) {
+            Ok(()) => {
+                state.forget_key(&bls_pk_hex);
+                DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::Deleted,
+                    message: None,
+                }
+            }
+            Err(e) => {
+                error!("delete_bls_keys() failed for {bls_pk_hex} with: {:?}", e);
+                DeleteKeysResponseInner {
+                    status: DeleteKeyStatus::Error,
+                    message: Some(format!("Failed to delete key: {:?}", e)),
+                }
+            }
+        };
+        data.push(inner);
+    }
+
+    (
+        axum::http::status::StatusCode::OK,
+        Json(DeleteKeysResponse { data }),
+    )
+        .into_response()
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use log::{error, info};
+
+use crate::enclave::types::ErrorResponse;
+
+/// Removes the graffiti pinned for the BLS public key
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+) -> axum::response::Response {
+    info!("delete_graffiti()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    let result = state.validator_configs.update(&bls_pk_hex, |config| {
+        config.graffiti = None;
+    });
+
+    match result {
+        Ok(()) => axum::http::status::StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            error!("delete_graffiti() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to delete graffiti: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
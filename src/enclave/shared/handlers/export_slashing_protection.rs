@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportSlashingProtectionQuery {
+    /// Comma-separated list of BLS public keys to export, defaults to all keys
+    pub pubkeys: Option<String>,
+}
+
+/// Returns the slashing protection history of the saved BLS keys in the EIP-3076 interchange format
+pub async fn handler(
+    State(state): State<super::AppState>,
+    Query(query): Query<ExportSlashingProtectionQuery>,
+) -> axum::response::Response {
+    info!("export_slashing_protection()");
+    let pubkeys: Option<Vec<String>> = query.pubkeys.map(|pks| {
+        pks.split(',')
+            .filter(|pk| !pk.is_empty())
+            .map(|pk| pk.trim().to_string())
+            .collect()
+    });
+
+    match crate::eth2::slash_protection::SlashingProtectionDB::export(
+        state.slashing_backend.as_ref(),
+        state.genesis_validators_root,
+        pubkeys,
+    ) {
+        Ok(db) => (axum::http::status::StatusCode::OK, Json(db)).into_response(),
+        Err(e) => {
+            error!("export_slashing_protection() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Failed to export slashing protection: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, FeeRecipientResponse, FeeRecipientResponseInner};
+
+/// Returns the fee recipient pinned for the BLS public key
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+) -> axum::response::Response {
+    info!("get_fee_recipient()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    match state.validator_configs.get(&bls_pk_hex) {
+        Ok(config) => match config.fee_recipient {
+            Some(ethaddress) => (
+                axum::http::status::StatusCode::OK,
+                Json(FeeRecipientResponse {
+                    data: FeeRecipientResponseInner {
+                        pubkey: format!("0x{bls_pk_hex}"),
+                        ethaddress,
+                    },
+                }),
+            )
+                .into_response(),
+            None => ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("No fee recipient set for 0x{bls_pk_hex}"),
+            )
+            .into_response(),
+        },
+        Err(e) => {
+            error!("get_fee_recipient() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read fee recipient: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
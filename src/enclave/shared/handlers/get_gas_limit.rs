@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, GasLimitResponse, GasLimitResponseInner};
+
+/// Returns the gas limit pinned for the BLS public key
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+) -> axum::response::Response {
+    info!("get_gas_limit()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    match state.validator_configs.get(&bls_pk_hex) {
+        Ok(config) => match config.gas_limit {
+            Some(gas_limit) => (
+                axum::http::status::StatusCode::OK,
+                Json(GasLimitResponse {
+                    data: GasLimitResponseInner {
+                        pubkey: format!("0x{bls_pk_hex}"),
+                        gas_limit: gas_limit.to_string(),
+                    },
+                }),
+            )
+                .into_response(),
+            None => ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("No gas limit set for 0x{bls_pk_hex}"),
+            )
+            .into_response(),
+        },
+        Err(e) => {
+            error!("get_gas_limit() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read gas limit: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
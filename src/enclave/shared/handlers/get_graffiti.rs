@@ -0,0 +1,67 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, GraffitiResponse, GraffitiResponseInner};
+
+/// Returns the graffiti pinned for the BLS public key
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+) -> axum::response::Response {
+    info!("get_graffiti()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    let graffiti = state.validator_configs.get(&bls_pk_hex).and_then(|config| {
+        config
+            .graffiti
+            .map(|g| crate::io::validator_config::graffiti_to_text(&g))
+            .transpose()
+    });
+
+    match graffiti {
+        Ok(Some(graffiti)) => (
+            axum::http::status::StatusCode::OK,
+            Json(GraffitiResponse {
+                data: GraffitiResponseInner {
+                    pubkey: format!("0x{bls_pk_hex}"),
+                    graffiti,
+                },
+            }),
+        )
+            .into_response(),
+        Ok(None) => ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("No graffiti set for 0x{bls_pk_hex}"),
+        )
+        .into_response(),
+        Err(e) => {
+            error!("get_graffiti() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read graffiti: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
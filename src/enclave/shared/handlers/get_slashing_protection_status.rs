@@ -0,0 +1,86 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{
+    ErrorResponse, SlashingProtectionStatus, SlashingProtectionStatusResponse,
+};
+
+/// Returns the slashing protection watermarks, signature count and highest RANDAO reveal epoch
+/// of the BLS public key
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+) -> axum::response::Response {
+    info!("get_slashing_protection_status()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    match slashing_protection_status(&bls_pk_hex, &state) {
+        Ok(data) => (
+            axum::http::status::StatusCode::OK,
+            Json(SlashingProtectionStatusResponse { data }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("get_slashing_protection_status() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read slashing protection: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
+
+/// Reads the watermarks of a saved key, which are null for a key without a history
+pub fn slashing_protection_status(
+    bls_pk_hex: &str,
+    state: &super::AppState,
+) -> Result<SlashingProtectionStatus> {
+    let mut status = SlashingProtectionStatus {
+        pubkey: format!("0x{bls_pk_hex}"),
+        last_signed_block_slot: None,
+        last_signed_attestation_source_epoch: None,
+        last_signed_attestation_target_epoch: None,
+        signature_count: state.signature_counts.get(bls_pk_hex).to_string(),
+        highest_randao_reveal_epoch: state
+            .randao_reveal_epochs
+            .get(bls_pk_hex)
+            .map(|e| e.to_string()),
+    };
+    if !state.slashing_backend.exists(bls_pk_hex) {
+        return Ok(status);
+    }
+
+    let db = state.slashing_backend.read(bls_pk_hex)?;
+    if !db.signed_blocks.is_empty() {
+        status.last_signed_block_slot = Some(db.get_latest_signed_block_slot().to_string());
+    }
+    if !db.signed_attestations.is_empty() {
+        let (src, tgt) = db.get_latest_signed_attestation_epochs();
+        status.last_signed_attestation_source_epoch = Some(src.to_string());
+        status.last_signed_attestation_target_epoch = Some(tgt.to_string());
+    }
+    Ok(status)
+}
@@ -1,5 +1,7 @@
 use axum::response::IntoResponse;
 
+/// Liveness probe used by validator clients before enabling a remote signer. Does not touch
+/// the key or slashing protection directories.
 pub async fn handler() -> axum::response::Response {
-    (axum::http::status::StatusCode::OK).into_response()
+    (axum::http::status::StatusCode::OK, "OK").into_response()
 }
@@ -0,0 +1,77 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{HealthcheckResponse, KeyOriginCounts};
+
+/// Reports the chain Secure-Signer signs for, so operators can verify the network it was
+/// started with, how close the key directories are to the key quota, and whether the enclave
+/// can keep saving keys and histories. Unlike /upcheck its body is JSON.
+pub async fn handler(State(state): State<super::AppState>) -> axum::response::Response {
+    info!("healthcheck()");
+    let resp = report(&state);
+    (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+}
+
+/// The health of Secure-Signer saving to `state.data_dir`. The status is DEGRADED rather than UP
+/// when the directory is unwritable or a slashing protection history failed the startup check.
+pub fn report(state: &super::AppState) -> HealthcheckResponse {
+    let data_dir = &state.data_dir;
+    let data_dir_writable = match probe_writable(data_dir) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Data directory {} is not writable: {e}", data_dir.display());
+            false
+        }
+    };
+    let healthy = data_dir_writable && state.slashing_protection_intact;
+    HealthcheckResponse {
+        status: if healthy { "UP" } else { "DEGRADED" }.to_string(),
+        network: state.network.clone(),
+        genesis_fork_version: format!("0x{}", hex::encode(state.genesis_fork_version)),
+        genesis_validators_root: format!("0x{}", hex::encode(state.genesis_validators_root)),
+        genesis_time: state.slot_clock.map(|c| c.genesis_time.to_string()),
+        seconds_per_slot: state.slot_clock.map(|c| c.seconds_per_slot.to_string()),
+        bls_keys: crate::io::key_management::num_bls_keys(),
+        eth_keys: crate::io::key_management::num_eth_keys(),
+        max_keys: crate::io::key_management::max_keys(),
+        bls_key_origins: bls_key_origins(),
+        data_dir_writable,
+        slashing_protection_intact: state.slashing_protection_intact,
+        attestation: [
+            crate::io::remote_attestation::EvidenceKind::Epid,
+            crate::io::remote_attestation::EvidenceKind::Dcap,
+        ]
+        .into_iter()
+        .filter(|kind| crate::io::remote_attestation::check_attestation(*kind).is_ok())
+        .collect(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        commit: option_env!("SECURE_SIGNER_GIT_COMMIT").map(String::from),
+    }
+}
+
+/// Creates and removes a file in `dir`, which is created if missing. Each probe has its own
+/// file, so concurrent healthchecks do not remove each other's.
+fn probe_writable(dir: &std::path::Path) -> std::io::Result<()> {
+    static PROBES: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    std::fs::create_dir_all(dir)?;
+    let n = PROBES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let probe = dir.join(format!(".healthcheck-{n}"));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+fn bls_key_origins() -> KeyOriginCounts {
+    let mut counts = KeyOriginCounts::default();
+    for pk_hex in crate::io::key_management::list_bls_keys().unwrap_or_default() {
+        let origin = crate::io::key_metadata::read_bls_metadata(&pk_hex)
+            .map(|metadata| metadata.origin)
+            .unwrap_or(crate::io::key_metadata::KeyOrigin::Unknown);
+        match origin {
+            crate::io::key_metadata::KeyOrigin::Generated => counts.generated += 1,
+            crate::io::key_metadata::KeyOrigin::Derived => counts.derived += 1,
+            crate::io::key_metadata::KeyOrigin::Imported => counts.imported += 1,
+            crate::io::key_metadata::KeyOrigin::Unknown => counts.unknown += 1,
+        }
+    }
+    counts
+}
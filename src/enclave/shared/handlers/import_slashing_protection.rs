@@ -0,0 +1,49 @@
+use axum::{extract::State, response::IntoResponse};
+use log::{error, info};
+
+/// Seeds the slashing protection history from an EIP-3076 interchange file. The body is parsed
+/// as a raw string so a malformed file is reported with the offending record identified.
+pub async fn handler(
+    State(state): State<super::AppState>,
+    body: String,
+) -> axum::response::Response {
+    info!("import_slashing_protection()");
+    let db = match crate::eth2::slash_protection::SlashingProtectionDB::from_interchange_str(&body)
+    {
+        Ok(db) => db,
+        Err(e) => {
+            error!("import_slashing_protection() bad interchange file: {:?}", e);
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad interchange file: {:?}", e),
+            )
+            .into_response();
+        }
+    };
+
+    if let Err(e) = db.check_genesis_validators_root(state.genesis_validators_root) {
+        return crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("{:?}", e),
+        )
+        .into_response();
+    }
+
+    match db.import(
+        state.slashing_backend.as_ref(),
+        crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB,
+    ) {
+        Ok(n) => {
+            info!("Imported slashing protection for {n} keys");
+            axum::http::status::StatusCode::OK.into_response()
+        }
+        Err(e) => {
+            error!("import_slashing_protection() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to import slashing protection: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
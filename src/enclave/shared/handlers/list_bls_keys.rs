@@ -1,18 +1,35 @@
-use axum::{response::IntoResponse, Json};
+use axum::{extract::Query, response::IntoResponse, Json};
 use log::{error, info};
 
 use crate::io::key_management;
 
-pub async fn handler() -> axum::response::Response {
+/// Lists the saved BLS keys with their metadata, only those labeled `label` if it is set
+pub async fn handler(
+    Query(query): Query<crate::enclave::types::ListKeysQuery>,
+) -> axum::response::Response {
     info!("list_bls_keys()");
+    if let Err(resp) = super::check_label(query.label.as_deref()) {
+        return resp;
+    }
     match key_management::list_bls_keys() {
         Ok(list_res) => {
-            let resp = crate::enclave::types::ListKeysResponse::new(list_res);
+            let mut resp = crate::enclave::types::ListKeysResponse::new(list_res);
+            for key in resp.data.iter_mut() {
+                key.derivation_path = key_management::read_derivation_path(&key.pubkey);
+                key.metadata = crate::io::key_metadata::read_bls_metadata(&key.pubkey).ok();
+            }
+            if let Some(label) = &query.label {
+                resp.retain_label(label);
+            }
             (axum::http::status::StatusCode::OK, Json(resp)).into_response()
         }
         Err(e) => {
             error!("list_bls_keys() failed with: {:?}", e);
-            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("list_bls_keys failed: {:?}", e),
+            )
+            .into_response()
         }
     }
 }
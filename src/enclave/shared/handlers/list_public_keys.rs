@@ -0,0 +1,41 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::io::key_management;
+
+/// Web3Signer compatible listing of the BLS public keys this signer can serve, as a flat
+/// array of 0x-prefixed hex strings.
+pub async fn handler() -> axum::response::Response {
+    info!("list_public_keys()");
+    // The keys dir does not exist until the first key is saved
+    if !crate::io::data_dir::bls_keys_dir().exists() {
+        return (
+            axum::http::status::StatusCode::OK,
+            Json(Vec::<String>::new()),
+        )
+            .into_response();
+    }
+
+    match key_management::list_bls_keys() {
+        Ok(list_res) => {
+            let mut keys: Vec<String> = list_res
+                .iter()
+                .map(|pk| {
+                    let pk: String = crate::strip_0x_prefix!(pk.to_lowercase());
+                    format!("0x{pk}")
+                })
+                .collect();
+            keys.sort();
+            keys.dedup();
+            (axum::http::status::StatusCode::OK, Json(keys)).into_response()
+        }
+        Err(e) => {
+            error!("list_public_keys() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("list_public_keys failed: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
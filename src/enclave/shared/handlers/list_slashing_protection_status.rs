@@ -0,0 +1,33 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, ListSlashingProtectionStatusResponse};
+
+/// Returns the slashing protection watermarks and signature count of every saved BLS key
+pub async fn handler(State(state): State<super::AppState>) -> axum::response::Response {
+    info!("list_slashing_protection_status()");
+    let statuses: anyhow::Result<Vec<_>> =
+        crate::io::key_management::list_bls_keys().and_then(|pks| {
+            pks.iter()
+                .map(|pk| {
+                    super::get_slashing_protection_status::slashing_protection_status(pk, &state)
+                })
+                .collect()
+        });
+
+    match statuses {
+        Ok(data) => (
+            axum::http::status::StatusCode::OK,
+            Json(ListSlashingProtectionStatusResponse { data }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("list_slashing_protection_status() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read slashing protection: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
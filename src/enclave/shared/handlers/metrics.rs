@@ -0,0 +1,23 @@
+use axum::response::IntoResponse;
+use log::error;
+
+/// Reports the signer's counters and latency histograms in the Prometheus text format. Only
+/// aggregates are exposed, never key material or public keys.
+pub async fn handler() -> axum::response::Response {
+    match crate::enclave::shared::metrics::gather() {
+        Ok((content_type, body)) => (
+            axum::http::status::StatusCode::OK,
+            [(axum::http::header::CONTENT_TYPE, content_type)],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Failed to gather metrics: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to gather metrics: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
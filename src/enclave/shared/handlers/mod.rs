@@ -1,9 +1,337 @@
+pub mod delete_bls_keys;
+pub mod delete_fee_recipient;
+pub mod delete_gas_limit;
+pub mod delete_graffiti;
+pub mod export_slashing_protection;
+pub mod get_fee_recipient;
+pub mod get_gas_limit;
+pub mod get_graffiti;
+pub mod get_slashing_protection_status;
 pub mod health;
+pub mod healthcheck;
+pub mod import_slashing_protection;
 pub mod list_bls_keys;
 pub mod list_eth_keys;
+pub mod list_public_keys;
+pub mod list_slashing_protection_status;
+pub mod metrics;
+pub mod openapi;
+pub mod prune_slashing_protection;
 pub mod secure_sign_bls;
+pub mod set_fee_recipient;
+pub mod set_gas_limit;
+pub mod set_graffiti;
 
 #[derive(Clone)]
 pub struct AppState {
     pub genesis_fork_version: crate::eth2::eth_types::Version,
+    pub genesis_validators_root: crate::eth2::eth_types::Root,
+    /// Name of the network preset Secure-Signer was started with, if any
+    pub network: Option<String>,
+    pub gas_limit_policy: GasLimitPolicy,
+    pub slashing_floor: crate::eth2::slash_protection::SlashingFloor,
+    /// Number of recent blocks and attestations kept per key when pruning
+    pub slashing_prune_window: usize,
+    /// Refuses blocks and attestations too far ahead of the wall clock, unset to skip the check
+    pub slot_clock: Option<crate::eth2::slash_protection::SlotClock>,
+    /// Picks the fork version of each msg by its epoch rather than trusting its fork_info
+    pub fork_schedule: Option<crate::eth2::eth_types::ForkSchedule>,
+    /// Refuse, rather than correct, a fork_info that contradicts the fork_schedule
+    pub strict_fork_schedule: bool,
+    /// Refuse an AGGREGATE_AND_PROOF whose selection_proof was not signed by the aggregator
+    pub verify_selection_proofs: bool,
+    /// How many epochs a RANDAO reveal may be ahead of the key's last block, or of the wall
+    /// clock when slot_clock is set. Unset allows any epoch.
+    pub max_randao_lookahead: Option<crate::eth2::eth_types::Epoch>,
+    /// Serializes sign requests per key, shared by every clone of the state
+    pub sign_locks: KeyLocks,
+    /// Signatures produced per key since startup, shared by every clone of the state
+    pub signature_counts: SignatureCounts,
+    /// Highest RANDAO reveal epoch per key since startup, shared by every clone of the state
+    pub randao_reveal_epochs: RandaoRevealEpochs,
+    /// Fee recipient, gas limit and graffiti pinned per key, shared by every clone of the state
+    pub validator_configs: ValidatorConfigs,
+    /// Where slashing protection histories are saved, from `--slashing-db`
+    pub slashing_backend:
+        std::sync::Arc<dyn crate::eth2::slash_protection::SlashingProtectionBackend>,
+    /// Whether every slashing protection history was readable at startup
+    pub slashing_protection_intact: bool,
+    /// Where keys and slashing protection histories are saved, from `--datadir`
+    pub data_dir: std::path::PathBuf,
+    /// The settings Secure-Signer was started with, from `--config` and the command line
+    pub config: std::sync::Arc<crate::config::Config>,
+    /// Sessions with and health of the workers this enclave leads, shared by every clone of the
+    /// state
+    pub leader: crate::enclave::secure_signer::leader::LeaderState,
+    /// Sessions with the leaders this enclave works for, shared by every clone of the state
+    pub worker: crate::enclave::secure_signer::worker::WorkerState,
+}
+
+impl AppState {
+    /// The fork_info used for requests that omit theirs
+    pub fn default_fork_info(&self) -> crate::eth2::eth_types::ForkInfo {
+        crate::eth2::eth_types::ForkInfo {
+            fork: crate::eth2::eth_types::Fork {
+                previous_version: self.genesis_fork_version,
+                current_version: self.genesis_fork_version,
+                epoch: 0,
+            },
+            genesis_validators_root: self.genesis_validators_root,
+        }
+    }
+
+    /// Drops everything held in memory about a deleted BLS key
+    pub fn forget_key(&self, bls_pk_hex: &str) {
+        self.signature_counts.remove(bls_pk_hex);
+        self.randao_reveal_epochs.remove(bls_pk_hex);
+        self.validator_configs.remove(bls_pk_hex);
+    }
+}
+
+/// Above this many locks, the ones no request holds or waits for are dropped, like the idle
+/// buckets of `RateLimiter`
+const MAX_IDLE_KEY_LOCKS: usize = 1024;
+
+/// One lock per BLS key, so the slash protection check, signature and history update of a
+/// request cannot interleave with another request for the same key. Different keys still sign
+/// in parallel.
+#[derive(Clone, Default)]
+pub struct KeyLocks(
+    std::sync::Arc<
+        std::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<tokio::sync::Mutex<()>>>>,
+    >,
+);
+
+impl KeyLocks {
+    /// Waits for the lock of `bls_pk_hex`, which is held until the guard is dropped
+    pub async fn lock(&self, bls_pk_hex: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let key_lock = {
+            let mut locks = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            if locks.len() > MAX_IDLE_KEY_LOCKS {
+                // Only the map holds an idle lock, and nothing can clone it while the map is
+                // locked
+                locks.retain(|_, key_lock| std::sync::Arc::strong_count(key_lock) > 1);
+            }
+            locks.entry(bls_pk_hex).or_default().clone()
+        };
+        key_lock.lock_owned().await
+    }
+}
+
+/// In memory count of the signatures produced with each BLS key
+#[derive(Clone, Default)]
+pub struct SignatureCounts(
+    std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, u64>>>,
+);
+
+impl SignatureCounts {
+    pub fn increment(&self, bls_pk_hex: &str) {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut counts = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        *counts.entry(bls_pk_hex).or_default() += 1;
+    }
+
+    pub fn get(&self, bls_pk_hex: &str) -> u64 {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let counts = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        counts.get(&bls_pk_hex).copied().unwrap_or_default()
+    }
+
+    pub fn remove(&self, bls_pk_hex: &str) {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut counts = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        counts.remove(&bls_pk_hex);
+    }
+}
+
+/// In memory highest epoch each BLS key revealed its RANDAO for, so reveals far ahead of the
+/// chain stand out
+#[derive(Clone, Default)]
+pub struct RandaoRevealEpochs(
+    std::sync::Arc<
+        std::sync::Mutex<std::collections::HashMap<String, crate::eth2::eth_types::Epoch>>,
+    >,
+);
+
+impl RandaoRevealEpochs {
+    pub fn record(&self, bls_pk_hex: &str, epoch: crate::eth2::eth_types::Epoch) {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut epochs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let highest = epochs.entry(bls_pk_hex).or_insert(epoch);
+        *highest = std::cmp::max(*highest, epoch);
+    }
+
+    pub fn get(&self, bls_pk_hex: &str) -> Option<crate::eth2::eth_types::Epoch> {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let epochs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        epochs.get(&bls_pk_hex).copied()
+    }
+
+    pub fn remove(&self, bls_pk_hex: &str) {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut epochs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        epochs.remove(&bls_pk_hex);
+    }
+}
+
+/// The configs pinned through the keymanager API, read from disk once per BLS key and kept in
+/// sync by the routes that change them. An update holds the lock from read to write, so
+/// concurrent changes to different settings of one key are not lost.
+#[derive(Clone, Default)]
+pub struct ValidatorConfigs(
+    std::sync::Arc<
+        std::sync::Mutex<
+            std::collections::HashMap<String, crate::io::validator_config::ValidatorConfig>,
+        >,
+    >,
+);
+
+impl ValidatorConfigs {
+    pub fn get(
+        &self,
+        bls_pk_hex: &str,
+    ) -> anyhow::Result<crate::io::validator_config::ValidatorConfig> {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut configs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(config) = configs.get(&bls_pk_hex) {
+            return Ok(config.clone());
+        }
+        let config = crate::io::validator_config::ValidatorConfig::read(&bls_pk_hex)?;
+        configs.insert(bls_pk_hex, config.clone());
+        Ok(config)
+    }
+
+    /// Applies `change` to the key's config and saves it. The cached config only changes once
+    /// the new one is saved.
+    pub fn update(
+        &self,
+        bls_pk_hex: &str,
+        change: impl FnOnce(&mut crate::io::validator_config::ValidatorConfig),
+    ) -> anyhow::Result<()> {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut configs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut config = match configs.get(&bls_pk_hex) {
+            Some(config) => config.clone(),
+            None => crate::io::validator_config::ValidatorConfig::read(&bls_pk_hex)?,
+        };
+        change(&mut config);
+        config.write(&bls_pk_hex)?;
+        configs.insert(bls_pk_hex, config);
+        Ok(())
+    }
+
+    pub fn remove(&self, bls_pk_hex: &str) {
+        let bls_pk_hex = bls_pk_hex.to_lowercase();
+        let bls_pk_hex: String = crate::strip_0x_prefix!(bls_pk_hex);
+        let mut configs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        configs.remove(&bls_pk_hex);
+    }
+}
+
+/// What to do with a VALIDATOR_REGISTRATION whose gas_limit differs from the pinned one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GasLimitPolicy {
+    /// Refuse to sign the registration
+    #[default]
+    Reject,
+    /// Sign the registration with the pinned gas_limit instead
+    Override,
+}
+
+impl std::str::FromStr for GasLimitPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "reject" => Ok(GasLimitPolicy::Reject),
+            "override" => Ok(GasLimitPolicy::Override),
+            _ => anyhow::bail!("Unknown gas limit policy {s}, expected reject or override"),
+        }
+    }
+}
+
+/// Refuses a keygen or import that would exceed the key quota. The 507 tells the caller that
+/// keys must be deleted, or the quota raised, before more can be added.
+pub fn key_quota_response(e: &anyhow::Error) -> axum::response::Response {
+    log::error!("Refusing to save more keys: {}", e);
+    axum::response::IntoResponse::into_response(crate::enclave::types::ErrorResponse::new(
+        axum::http::status::StatusCode::INSUFFICIENT_STORAGE,
+        e.to_string(),
+    ))
+}
+
+/// Refuses an attestation that cannot be produced. Builds without SGX support return 501, as no
+/// configuration will help. Otherwise IAS credentials are missing, and the 503 tells the caller
+/// the enclave must be restarted with them. The body never echoes them.
+pub fn attestation_unavailable_response(e: &anyhow::Error) -> axum::response::Response {
+    log::error!("Refusing to attest: {}", e);
+    let status = if e.is::<crate::io::remote_attestation::AttestationUnsupported>() {
+        axum::http::status::StatusCode::NOT_IMPLEMENTED
+    } else {
+        axum::http::status::StatusCode::SERVICE_UNAVAILABLE
+    };
+    axum::response::IntoResponse::into_response(crate::enclave::types::ErrorResponse::new(
+        status,
+        e.to_string(),
+    ))
+}
+
+/// Refuses a peer's evidence that failed `check_measurement_policy`. Builds the measurement
+/// policy does not allow, or any build when no policy is loaded, get 403 naming the measurement,
+/// while evidence that is malformed or not signed by Intel gets 400.
+pub fn bad_evidence_response(e: &anyhow::Error) -> axum::response::Response {
+    let refused = e.is::<crate::io::remote_attestation::PolicyViolation>()
+        || e.is::<crate::io::remote_attestation::NoMeasurementPolicy>();
+    let status = if refused {
+        axum::http::status::StatusCode::FORBIDDEN
+    } else {
+        axum::http::status::StatusCode::BAD_REQUEST
+    };
+    axum::response::IntoResponse::into_response(crate::enclave::types::ErrorResponse::new(
+        status,
+        format!("Bad evidence: {:#}", e),
+    ))
+}
+
+/// Validates an optional key label, returning the 400 response if it is malformed
+pub fn check_label(label: Option<&str>) -> Result<(), axum::response::Response> {
+    match label.map(crate::io::key_metadata::validate_label) {
+        Some(Err(e)) => Err(axum::response::IntoResponse::into_response(
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad label: {}", e),
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idle_key_locks_are_pruned() {
+        let locks = KeyLocks::default();
+        let held = locks.lock("0x00").await;
+        for i in 1..=MAX_IDLE_KEY_LOCKS {
+            drop(locks.lock(&format!("{i:02x}")).await);
+        }
+
+        // Only the lock still held survives, along with the new one
+        drop(locks.lock("busy").await);
+        assert_eq!(locks.0.lock().unwrap().len(), 2);
+        assert!(locks.0.lock().unwrap().contains_key("00"));
+        drop(held);
+    }
 }
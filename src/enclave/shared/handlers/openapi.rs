@@ -0,0 +1,47 @@
+use axum::{http::header, response::IntoResponse};
+use log::info;
+
+/// The OpenAPI spec under resources/, bundled into one JSON document by build.rs
+pub const OPENAPI_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/openapi.json"));
+
+/// Serves the OpenAPI spec of every route Secure-Signer mounts
+pub async fn handler() -> axum::response::Response {
+    info!("openapi()");
+    (
+        axum::http::status::StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/json")],
+        OPENAPI_JSON,
+    )
+        .into_response()
+}
+
+/// A Swagger UI page browsing /api/schema. The page loads Swagger UI from a CDN, so it is only
+/// mounted when asked for with `--swagger-ui`.
+pub async fn swagger_ui() -> axum::response::Response {
+    info!("swagger_ui()");
+    (
+        axum::http::status::StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        SWAGGER_UI_HTML,
+    )
+        .into_response()
+}
+
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Secure-Signer API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/api/schema", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
@@ -0,0 +1,31 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::PruneSlashingProtectionResponse;
+
+/// Shrinks the saved slashing protection history of every key to what is needed for safety.
+/// Watermarks are never lowered.
+pub async fn handler(State(state): State<super::AppState>) -> axum::response::Response {
+    info!("prune_slashing_protection()");
+    match crate::eth2::slash_protection::SlashingProtectionDB::prune_all(
+        state.slashing_backend.as_ref(),
+        state.slashing_prune_window,
+    ) {
+        Ok(pruned) => {
+            info!("Pruned {pruned} slashing protection entries");
+            (
+                axum::http::status::StatusCode::OK,
+                Json(PruneSlashingProtectionResponse { pruned }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("prune_slashing_protection() failed with: {:?}", e);
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to prune slashing protection: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -1,16 +1,110 @@
 use axum::{
-    extract::{Path, State},
+    extract::{rejection::JsonRejection, Path, State},
+    http::HeaderMap,
+    response::IntoResponse,
     Json,
 };
-use log::info;
+use log::{error, info};
 
 /// Signs the specific type of request
 /// Maintains compatibility with https://consensys.github.io/web3signer/web3signer-eth2.html#tag/Signing
 pub async fn handler(
     Path(bls_pk_hex): Path<String>,
     State(state): State<crate::enclave::shared::handlers::AppState>,
-    Json(req): Json<crate::eth2::eth_signing::BLSSignMsg>,
+    headers: HeaderMap,
+    req: Result<Json<crate::eth2::eth_signing::BLSSignMsg>, JsonRejection>,
 ) -> axum::response::Response {
     info!("secure_sign_bls()");
-    crate::enclave::shared::sign_validator_message(Path(bls_pk_hex), State(state), Json(req))
+    let started = std::time::Instant::now();
+    let (msg_type, response) = sign(bls_pk_hex, state, headers, req).await;
+    crate::enclave::shared::metrics::observe_sign(msg_type, response.status(), started.elapsed());
+    response
+}
+
+/// Answers the sign request along with its msg type, "unknown" if the body is not a msg
+async fn sign(
+    bls_pk_hex: String,
+    state: crate::enclave::shared::handlers::AppState,
+    headers: HeaderMap,
+    req: Result<Json<crate::eth2::eth_signing::BLSSignMsg>, JsonRejection>,
+) -> (&'static str, axum::response::Response) {
+    // A well-formed body that does not fit the msg, e.g. a list past its SSZ limit, is a bad
+    // request like any other malformed msg rather than axum's default 422
+    let req = match req {
+        Ok(Json(req)) => req,
+        Err(JsonRejection::JsonDataError(e)) => {
+            error!("Bad sign request: {}", e.body_text());
+            return (
+                "unknown",
+                crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    e.body_text(),
+                )
+                .into_response(),
+            );
+        }
+        Err(rejection) => return ("unknown", rejection.into_response()),
+    };
+    let msg_type = req.type_name();
+
+    // Negotiate before signing so a 406 never advances the slashing protection DB
+    let accept = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok());
+    let format = match crate::enclave::types::SignatureFormat::from_accept(accept) {
+        Some(f) => f,
+        None => {
+            error!("Unsupported Accept header: {:?}", accept);
+            return (
+                msg_type,
+                crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::NOT_ACCEPTABLE,
+                    format!("Unsupported Accept header, expected application/json or text/plain"),
+                )
+                .into_response(),
+            );
+        }
+    };
+
+    // Sanitize the input bls_pk_hex
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                msg_type,
+                crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    format!("Bad bls_pk_hex, {:?}", e),
+                )
+                .into_response(),
+            );
+        }
+    };
+
+    // Web3Signer answers 404 for unknown keys so validator clients mark them as missing. Checked
+    // before taking the lock so made up pubkeys never get one
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        error!("Unknown BLS public key: {bls_pk_hex}");
+        return (
+            msg_type,
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::NOT_FOUND,
+                format!("Public key not found: 0x{bls_pk_hex}"),
+            )
+            .into_response(),
+        );
+    }
+
+    // Held until the msg is signed and recorded, so a concurrent request for the same key
+    // is checked against the updated watermark
+    let _guard = state.sign_locks.lock(&bls_pk_hex).await;
+
+    let response = crate::enclave::shared::sign_validator_message(
+        Path(bls_pk_hex),
+        State(state),
+        format,
+        Json(req),
+    );
+    (msg_type, response)
 }
@@ -0,0 +1,64 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SetFeeRecipientRequest};
+
+/// Pins the fee recipient for the BLS public key. VALIDATOR_REGISTRATION requests for the key
+/// with a different fee_recipient are refused from then on.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+    Json(req): Json<SetFeeRecipientRequest>,
+) -> axum::response::Response {
+    info!("set_fee_recipient()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let ethaddress = match crate::io::validator_config::sanitize_execution_address(&req.ethaddress)
+    {
+        Ok(addr) => addr,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad ethaddress, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    let result = state.validator_configs.update(&bls_pk_hex, |config| {
+        config.fee_recipient = Some(ethaddress);
+    });
+
+    match result {
+        Ok(()) => axum::http::status::StatusCode::ACCEPTED.into_response(),
+        Err(e) => {
+            error!("set_fee_recipient() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to set fee recipient: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SetGasLimitRequest};
+
+/// Pins the gas limit for the BLS public key. VALIDATOR_REGISTRATION requests for the key
+/// with a different gas_limit are then rejected or overridden depending on the GasLimitPolicy.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+    Json(req): Json<SetGasLimitRequest>,
+) -> axum::response::Response {
+    info!("set_gas_limit()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let gas_limit = match crate::io::validator_config::sanitize_gas_limit(&req.gas_limit) {
+        Ok(gas_limit) => gas_limit,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad gas_limit, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    let result = state.validator_configs.update(&bls_pk_hex, |config| {
+        config.gas_limit = Some(gas_limit);
+    });
+
+    match result {
+        Ok(()) => axum::http::status::StatusCode::ACCEPTED.into_response(),
+        Err(e) => {
+            error!("set_gas_limit() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to set gas limit: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{ErrorResponse, SetGraffitiRequest};
+
+/// Pins the graffiti for the BLS public key. Full BLOCK and BLOCK_V2 bodies for the key with a
+/// different graffiti are refused from then on.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<super::AppState>,
+    Json(req): Json<SetGraffitiRequest>,
+) -> axum::response::Response {
+    info!("set_graffiti()");
+    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let graffiti = match crate::io::validator_config::sanitize_graffiti(&req.graffiti) {
+        Ok(graffiti) => graffiti,
+        Err(e) => {
+            return ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad graffiti, {:?}", e),
+            )
+            .into_response()
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return ErrorResponse::new(
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Public key not found: 0x{bls_pk_hex}"),
+        )
+        .into_response();
+    }
+
+    let result = state.validator_configs.update(&bls_pk_hex, |config| {
+        config.graffiti = Some(graffiti);
+    });
+
+    match result {
+        Ok(()) => axum::http::status::StatusCode::ACCEPTED.into_response(),
+        Err(e) => {
+            error!("set_graffiti() failed with: {:?}", e);
+            ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to set graffiti: {:?}", e),
+            )
+            .into_response()
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::OnceLock;
+
+/// Upper bounds of the sign latency buckets in seconds. Signing itself takes about a
+/// millisecond, the slow tail is reading keys and writing slashing protection histories.
+const SIGN_LATENCY_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// The counters and histograms exposed at /metrics, created on first use
+struct Metrics {
+    registry: Registry,
+    sign_requests: IntCounterVec,
+    sign_latency: HistogramVec,
+    keys_created: IntCounterVec,
+    attestation_requests: IntCounterVec,
+    rate_limited: IntCounterVec,
+    rate_limit_buckets: IntGaugeVec,
+    worker_healthy: IntGaugeVec,
+    worker_missed_heartbeats: IntGaugeVec,
+    worker_heartbeat_latency: GaugeVec,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+impl Metrics {
+    fn new() -> Result<Self> {
+        let registry = Registry::new();
+        let sign_requests = IntCounterVec::new(
+            Opts::new(
+                "secure_signer_sign_requests_total",
+                "BLS sign requests by msg type and response status",
+            ),
+            &["type", "status"],
+        )?;
+        let sign_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "secure_signer_sign_duration_seconds",
+                "Time to answer BLS sign requests by msg type",
+            )
+            .buckets(SIGN_LATENCY_BUCKETS.to_vec()),
+            &["type"],
+        )?;
+        let keys_created = IntCounterVec::new(
+            Opts::new(
+                "secure_signer_keys_created_total",
+                "Keys saved by key kind and whether they were generated or imported",
+            ),
+            &["kind", "source"],
+        )?;
+        let attestation_requests = IntCounterVec::new(
+            Opts::new(
+                "secure_signer_attestation_requests_total",
+                "Remote attestation requests by evidence kind",
+            ),
+            &["kind"],
+        )?;
+        let rate_limited = IntCounterVec::new(
+            Opts::new(
+                "secure_signer_rate_limited_total",
+                "Requests refused with 429 by rate limiter",
+            ),
+            &["limiter"],
+        )?;
+        let rate_limit_buckets = IntGaugeVec::new(
+            Opts::new(
+                "secure_signer_rate_limit_buckets",
+                "Callers each rate limiter currently tracks a token bucket for",
+            ),
+            &["limiter"],
+        )?;
+        let worker_healthy = IntGaugeVec::new(
+            Opts::new(
+                "secure_signer_worker_healthy",
+                "1 while a registered worker answers its heartbeats, 0 once it missed too many",
+            ),
+            &["worker"],
+        )?;
+        let worker_missed_heartbeats = IntGaugeVec::new(
+            Opts::new(
+                "secure_signer_worker_missed_heartbeats",
+                "Heartbeats a registered worker missed in a row",
+            ),
+            &["worker"],
+        )?;
+        let worker_heartbeat_latency = GaugeVec::new(
+            Opts::new(
+                "secure_signer_worker_heartbeat_latency_seconds",
+                "Time a registered worker took to answer its last heartbeat",
+            ),
+            &["worker"],
+        )?;
+        registry.register(Box::new(sign_requests.clone()))?;
+        registry.register(Box::new(sign_latency.clone()))?;
+        registry.register(Box::new(keys_created.clone()))?;
+        registry.register(Box::new(attestation_requests.clone()))?;
+        registry.register(Box::new(rate_limited.clone()))?;
+        registry.register(Box::new(rate_limit_buckets.clone()))?;
+        registry.register(Box::new(worker_healthy.clone()))?;
+        registry.register(Box::new(worker_missed_heartbeats.clone()))?;
+        registry.register(Box::new(worker_heartbeat_latency.clone()))?;
+        Ok(Metrics {
+            registry,
+            sign_requests,
+            sign_latency,
+            keys_created,
+            attestation_requests,
+            rate_limited,
+            rate_limit_buckets,
+            worker_healthy,
+            worker_missed_heartbeats,
+            worker_heartbeat_latency,
+        })
+    }
+}
+
+fn metrics() -> &'static Metrics {
+    // The names and labels are fixed, so only a bug can fail to register them
+    METRICS.get_or_init(|| Metrics::new().expect("Bad metric definitions"))
+}
+
+/// Where a saved key came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeySource {
+    Keygen,
+    Import,
+}
+
+impl KeySource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            KeySource::Keygen => "keygen",
+            KeySource::Import => "import",
+        }
+    }
+}
+
+/// Counts a sign request of `msg_type` answered with `status` after `elapsed`
+pub fn observe_sign(msg_type: &str, status: axum::http::StatusCode, elapsed: std::time::Duration) {
+    let m = metrics();
+    m.sign_requests
+        .with_label_values(&[msg_type, status.as_str()])
+        .inc();
+    m.sign_latency
+        .with_label_values(&[msg_type])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Counts `n` newly saved BLS keys
+pub fn record_bls_keys_created(source: KeySource, n: usize) {
+    metrics()
+        .keys_created
+        .with_label_values(&["bls", source.as_str()])
+        .inc_by(n as u64);
+}
+
+/// Counts a newly generated ETH key
+pub fn record_eth_key_created() {
+    metrics()
+        .keys_created
+        .with_label_values(&["secp256k1", KeySource::Keygen.as_str()])
+        .inc();
+}
+
+/// Counts a remote attestation request for `kind` evidence
+pub fn record_attestation_request(kind: crate::io::remote_attestation::EvidenceKind) {
+    let kind = match kind {
+        crate::io::remote_attestation::EvidenceKind::Epid => "epid",
+        crate::io::remote_attestation::EvidenceKind::Dcap => "dcap",
+    };
+    metrics()
+        .attestation_requests
+        .with_label_values(&[kind])
+        .inc();
+}
+
+/// Counts a request the `limiter` rate limiter refused
+pub fn record_rate_limited(limiter: &str) {
+    metrics().rate_limited.with_label_values(&[limiter]).inc();
+}
+
+/// Reports how many token buckets the `limiter` rate limiter tracks
+pub fn set_rate_limit_buckets(limiter: &str, buckets: usize) {
+    metrics()
+        .rate_limit_buckets
+        .with_label_values(&[limiter])
+        .set(buckets as i64);
+}
+
+/// Reports the outcome of the last heartbeat of worker `id`, with its latency if it answered
+pub fn observe_worker_heartbeat(
+    id: &str,
+    healthy: bool,
+    missed: u32,
+    latency: Option<std::time::Duration>,
+) {
+    let m = metrics();
+    m.worker_healthy
+        .with_label_values(&[id])
+        .set(healthy as i64);
+    m.worker_missed_heartbeats
+        .with_label_values(&[id])
+        .set(missed as i64);
+    if let Some(latency) = latency {
+        m.worker_heartbeat_latency
+            .with_label_values(&[id])
+            .set(latency.as_secs_f64());
+    }
+}
+
+/// Every metric in the Prometheus text format, with its content type
+pub fn gather() -> Result<(String, Vec<u8>)> {
+    let encoder = TextEncoder::new();
+    let mut buf = vec![];
+    encoder.encode(&metrics().registry.gather(), &mut buf)?;
+    Ok((encoder.format_type().to_string(), buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather_exposes_recorded_metrics() {
+        observe_sign(
+            "ATTESTATION",
+            axum::http::StatusCode::PRECONDITION_FAILED,
+            std::time::Duration::from_millis(3),
+        );
+        record_bls_keys_created(KeySource::Import, 2);
+        record_attestation_request(crate::io::remote_attestation::EvidenceKind::Dcap);
+
+        let (content_type, body) = gather().unwrap();
+        assert!(content_type.starts_with("text/plain"));
+        let body = String::from_utf8(body).unwrap();
+        assert!(
+            body.contains(r#"secure_signer_sign_requests_total{status="412",type="ATTESTATION"}"#)
+        );
+        assert!(body.contains(
+            r#"secure_signer_sign_duration_seconds_bucket{type="ATTESTATION",le="0.005"}"#
+        ));
+        assert!(body.contains(r#"secure_signer_keys_created_total{kind="bls",source="import"}"#));
+        assert!(body.contains(r#"secure_signer_attestation_requests_total{kind="dcap"}"#));
+    }
+}
@@ -1,4 +1,14 @@
+pub mod api_token;
+pub mod content_type;
+pub mod cors;
+pub mod errors;
 pub mod handlers;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_log;
+pub mod shutdown;
+pub mod tls;
+pub mod unix_socket;
 use anyhow::{bail, Result};
 use axum::{
     extract::{Path, State},
@@ -10,154 +20,493 @@ use sha3::Digest;
 
 /// Signs the specific type of request
 /// Maintains compatibility with https://consensys.github.io/web3signer/web3signer-eth2.html#tag/Signing
+/// `bls_pk_hex` is the sanitized pubkey of a saved key, checked by the caller before it took the
+/// key's lock
 pub fn sign_validator_message(
     Path(bls_pk_hex): Path<String>,
     State(state): State<crate::enclave::shared::handlers::AppState>,
-    Json(req): Json<crate::eth2::eth_signing::BLSSignMsg>,
+    format: crate::enclave::types::SignatureFormat,
+    Json(mut req): Json<crate::eth2::eth_signing::BLSSignMsg>,
 ) -> axum::response::Response {
     info!("secure_sign_bls()");
-
-    // Sanitize the input bls_pk_hex
-    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
-        Ok(pk) => pk,
-        Err(e) => {
-            error!("Bad BLS public key format: {bls_pk_hex}");
-            return (
-                axum::http::status::StatusCode::BAD_REQUEST,
-                format!("Bad bls_pk_hex, {:?}", e),
-            )
-                .into_response();
-        }
-    };
+    // Each stage of the pipeline gets its own span, so the close events logged with their busy
+    // time show whether hashing, the slash protection DB or the signature was slow
+    let _span = tracing::info_span!("sign", msg_type = req.type_name()).entered();
 
     info!("Request for validator pubkey: {bls_pk_hex}");
     info!("Request:\n{:#?}", serde_json::to_string_pretty(&req));
 
-    // Verify not a slashable msg
-    match crate::enclave::shared::is_slashable(&bls_pk_hex, &req) {
-        Ok(b) => match b {
-            true => {
-                return (
-                    axum::http::status::StatusCode::PRECONDITION_FAILED,
-                    format!("Signing operation failed due to slashing protection rules"),
-                )
-                    .into_response()
-            }
-            false => {}
-        },
+    // Verify the msg respects the settings pinned through the keymanager API. This may rewrite
+    // the msg, so it must happen before the signing root is computed
+    match crate::enclave::shared::enforce_validator_config(&bls_pk_hex, &mut req, &state) {
+        Ok(None) => {}
+        Ok(Some(violation)) => {
+            error!("{violation}");
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::PRECONDITION_FAILED,
+                violation,
+            )
+            .into_response();
+        }
         Err(e) => {
-            return (
+            return crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Signing operation failed: {:?}", e),
             )
-                .into_response()
+            .into_response()
         }
     };
 
+    // The domain depends on the fork_info, so settle it before the signing root is computed
+    if let Some(violation) = crate::enclave::shared::resolve_fork_info(&mut req, &state) {
+        error!("{violation}");
+        return crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::PRECONDITION_FAILED,
+            violation,
+        )
+        .into_response();
+    }
+
+    // A selection proof the aggregator never signed means the validator client is corrupted,
+    // so refuse to sign an aggregate it would broadcast
+    if state.verify_selection_proofs {
+        if let Some(violation) =
+            crate::enclave::shared::selection_proof_violation(&bls_pk_hex, &req)
+        {
+            error!("{violation}");
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                violation,
+            )
+            .into_response();
+        }
+    }
+
     // Compute the msg to be signed
-    let signing_root: crate::eth2::eth_types::Root =
-        req.to_signing_root(Some(state.genesis_fork_version));
+    let signing_root: crate::eth2::eth_types::Root = tracing::info_span!("signing_root")
+        .in_scope(|| req.to_signing_root(Some(state.genesis_fork_version)));
     info!("signing_root: {}", hex::encode(signing_root));
 
-    // Update the slash protection DB if msg was a block or attestation
+    // Refuse to sign if the client's precomputed signingRoot disagrees with ours. Web3Signer
+    // only documents 400, 404, 412 and 500, so a bad signingRoot is a bad request.
+    if let Some(provided) = req.provided_signing_root() {
+        if provided != signing_root {
+            error!(
+                "signingRoot mismatch: provided {}, computed {}",
+                hex::encode(provided),
+                hex::encode(signing_root)
+            );
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::BAD_REQUEST,
+                "Provided signingRoot does not match the computed signing root",
+            )
+            .with_details(crate::enclave::types::SigningRootMismatch::new(
+                provided,
+                signing_root,
+            ))
+            .into_response();
+        }
+    }
+
+    // A far future slot would raise the watermark past every legitimate one, so refuse it
+    // before the slash protection DB is touched. Like any other refusal to protect the key it
+    // is a 412, which validator clients do not retry.
+    if let Some(clock) = state.slot_clock {
+        if let Some(violation) = crate::enclave::shared::far_future_violation(&req, clock) {
+            error!("{violation}");
+            return crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::PRECONDITION_FAILED,
+                violation,
+            )
+            .into_response();
+        }
+    }
+
+    // A reveal for a far future epoch leaks randomness the validator has not needed yet
+    if let Some(max_lookahead) = state.max_randao_lookahead {
+        match crate::enclave::shared::randao_lookahead_violation(
+            state.slashing_backend.as_ref(),
+            &bls_pk_hex,
+            &req,
+            max_lookahead,
+            state.slot_clock,
+        ) {
+            Ok(None) => {}
+            Ok(Some(violation)) => {
+                error!("{violation}");
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::PRECONDITION_FAILED,
+                    violation,
+                )
+                .into_response();
+            }
+            Err(e) => {
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                .into_response()
+            }
+        }
+    }
+
+    // Verify not a slashable msg and record it in the same slash protection transaction
     if req.can_be_slashed() {
-        if let Err(e) = crate::enclave::shared::update_slash_protection_db(&bls_pk_hex, &req) {
-            error!("Failed trying to update slash protection database");
-            return (
-                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Signing operation failed: {:?}", e),
+        match tracing::info_span!("slash_protection").in_scope(|| {
+            crate::enclave::shared::check_and_update_slash_protection_db(
+                state.slashing_backend.as_ref(),
+                &bls_pk_hex,
+                &req,
+                signing_root,
+                state.slashing_floor,
             )
+        }) {
+            Ok(true) => {}
+            Ok(false) => {
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::PRECONDITION_FAILED,
+                    format!("Signing operation failed due to slashing protection rules"),
+                )
+                .into_response()
+            }
+            Err(e) => {
+                error!("Failed trying to update slash protection database");
+                return crate::enclave::types::ErrorResponse::new(
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
                 .into_response();
+            }
         }
     }
 
     // Sign the message
-    match crate::crypto::bls_keys::bls_agg_sign_from_saved_sk(&bls_pk_hex, &signing_root) {
+    match tracing::info_span!("bls_sign").in_scope(|| {
+        crate::crypto::bls_keys::bls_agg_sign_from_saved_sk(&bls_pk_hex, &signing_root)
+    }) {
         Ok(sig) => {
-            info!("signature: {:?}", hex::encode(sig.to_bytes()));
-            let response = crate::enclave::types::SignatureResponse::new(&sig.to_bytes());
-            (axum::http::status::StatusCode::OK, Json(response)).into_response()
+            info!("signature: {:?}", hex::encode(&sig));
+            state.signature_counts.increment(&bls_pk_hex);
+            if let Err(e) = crate::io::key_metadata::record_bls_key_use(&bls_pk_hex) {
+                error!("Failed to record use of {bls_pk_hex}: {:?}", e);
+            }
+            if let crate::eth2::eth_signing::BLSSignMsg::RANDAO_REVEAL(m)
+            | crate::eth2::eth_signing::BLSSignMsg::randao_reveal(m) = &req
+            {
+                state
+                    .randao_reveal_epochs
+                    .record(&bls_pk_hex, m.randao_reveal.epoch);
+            }
+            let mut response = crate::enclave::types::SignatureResponse::new(&sig);
+            if let crate::eth2::eth_signing::BLSSignMsg::DEPOSIT(m)
+            | crate::eth2::eth_signing::BLSSignMsg::deposit(m) = &req
+            {
+                let root = crate::eth2::eth_signing::compute_deposit_data_root(
+                    &m.deposit,
+                    sig.clone().into(),
+                );
+                response = response.with_deposit_data_root(root);
+            }
+            if let crate::eth2::eth_signing::BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m)
+            | crate::eth2::eth_signing::BLSSignMsg::bls_to_execution_change(m) = &req
+            {
+                response = match response
+                    .with_signed_bls_to_execution_change(m.bls_to_execution_change.clone())
+                {
+                    Ok(r) => r,
+                    Err(e) => {
+                        return crate::enclave::types::ErrorResponse::new(
+                            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Signing operation failed: {:?}", e),
+                        )
+                        .into_response()
+                    }
+                };
+            }
+            match format {
+                crate::enclave::types::SignatureFormat::Json => {
+                    (axum::http::status::StatusCode::OK, Json(response)).into_response()
+                }
+                crate::enclave::types::SignatureFormat::Text => (
+                    axum::http::status::StatusCode::OK,
+                    [(
+                        axum::http::header::CONTENT_TYPE,
+                        "text/plain; charset=utf-8",
+                    )],
+                    response.signature,
+                )
+                    .into_response(),
+            }
         }
         Err(e) => {
             error!("Failed trying to sign");
-            return (
+            return crate::enclave::types::ErrorResponse::new(
                 axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Signing operation failed: {:?}", e),
             )
-                .into_response();
+            .into_response();
         }
     }
 }
 
-/// Returns true if signing_data is a block proposal or attestation and is slashable
-fn is_slashable(
+/// Returns a description of the violation if signing_data contradicts the validator's pinned
+/// config. A mismatched gas_limit is overwritten in place under GasLimitPolicy::Override.
+fn enforce_validator_config(
     bls_pk_hex: &String,
-    signing_data: &crate::eth2::eth_signing::BLSSignMsg,
-) -> Result<bool> {
-    // The slashing DB must exist
-    let db: crate::eth2::slash_protection::SlashingProtectionData =
-        crate::eth2::slash_protection::SlashingProtectionData::read(bls_pk_hex.as_str())?;
+    signing_data: &mut crate::eth2::eth_signing::BLSSignMsg,
+    state: &crate::enclave::shared::handlers::AppState,
+) -> Result<Option<String>> {
+    let config = state.validator_configs.get(bls_pk_hex)?;
 
     match signing_data {
+        crate::eth2::eth_signing::BLSSignMsg::VALIDATOR_REGISTRATION(m)
+        | crate::eth2::eth_signing::BLSSignMsg::validator_registration(m) => {
+            let registration = &mut m.validator_registration;
+            let requested = format!("0x{}", hex::encode(&registration.fee_recipient[..]));
+            if let Some(pinned) = config.fee_recipient {
+                if pinned != requested {
+                    return Ok(Some(format!(
+                        "fee_recipient {requested} does not match the configured {pinned}"
+                    )));
+                }
+            }
+
+            match config.gas_limit {
+                Some(pinned) if pinned != registration.gas_limit => match state.gas_limit_policy {
+                    crate::enclave::shared::handlers::GasLimitPolicy::Reject => Ok(Some(format!(
+                        "gas_limit {} does not match the configured {pinned}",
+                        registration.gas_limit
+                    ))),
+                    crate::enclave::shared::handlers::GasLimitPolicy::Override => {
+                        info!(
+                            "Overriding gas_limit {} with the configured {pinned}",
+                            registration.gas_limit
+                        );
+                        registration.gas_limit = pinned;
+                        // The client's signingRoot covers the original gas_limit
+                        m.signingRoot = None;
+                        Ok(None)
+                    }
+                },
+                _ => Ok(None),
+            }
+        }
+        // Only a full body exposes the graffiti, headers are signed as is
         crate::eth2::eth_signing::BLSSignMsg::BLOCK(m)
         | crate::eth2::eth_signing::BLSSignMsg::block(m) => {
-            Ok(db.is_slashable_block_slot(m.block.slot))
+            Ok(graffiti_violation(&config, &m.block.body.graffiti))
         }
         crate::eth2::eth_signing::BLSSignMsg::BLOCK_V2(m)
-        | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => {
-            Ok(db.is_slashable_block_slot(m.beacon_block.block_header.slot))
-        }
+        | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => Ok(m
+            .beacon_block
+            .graffiti
+            .and_then(|graffiti| graffiti_violation(&config, &graffiti))),
+        _ => Ok(None),
+    }
+}
 
-        crate::eth2::eth_signing::BLSSignMsg::ATTESTATION(m)
-        | crate::eth2::eth_signing::BLSSignMsg::attestation(m) => Ok(db
-            .is_slashable_attestation_epochs(
-                m.attestation.source.epoch,
-                m.attestation.target.epoch,
-            )),
-        _ => {
-            // Only block proposals and attestations are slashable
-            Ok(false)
+fn graffiti_violation(
+    config: &crate::io::validator_config::ValidatorConfig,
+    graffiti: &crate::eth2::eth_types::Bytes32,
+) -> Option<String> {
+    let requested = format!("0x{}", hex::encode(graffiti));
+    match &config.graffiti {
+        Some(pinned) if *pinned != requested => Some(format!(
+            "graffiti {requested} does not match the configured {pinned}"
+        )),
+        _ => None,
+    }
+}
+
+/// Fills in the configured fork_info for a request that omitted it. Otherwise describes why
+/// the supplied fork_info is for another chain than the configured genesis_validators_root,
+/// which is only checked when one was configured. With a fork schedule, the fork is then taken
+/// from the schedule at the msg's epoch, or in strict mode a contradicting fork is refused.
+pub(crate) fn resolve_fork_info(
+    signing_data: &mut crate::eth2::eth_signing::BLSSignMsg,
+    state: &crate::enclave::shared::handlers::AppState,
+) -> Option<String> {
+    let epoch = signing_data.domain_epoch();
+    let fork_info = signing_data.fork_info_mut()?;
+    let supplied = !fork_info.is_unset();
+    if !supplied {
+        *fork_info = state.default_fork_info();
+    }
+
+    let expected = state.genesis_validators_root;
+    let requested = fork_info.genesis_validators_root;
+    if expected != crate::eth2::eth_types::Root::default() && requested != expected {
+        return Some(format!(
+            "genesis_validators_root 0x{} does not match the configured 0x{}",
+            hex::encode(requested),
+            hex::encode(expected)
+        ));
+    }
+
+    if let (Some(schedule), Some(epoch)) = (&state.fork_schedule, epoch) {
+        let scheduled = schedule.fork_version_at_epoch(epoch);
+        let requested = fork_info.fork.version_at_epoch(epoch);
+        if requested != scheduled {
+            if supplied && state.strict_fork_schedule {
+                return Some(format!(
+                    "fork version 0x{} at epoch {epoch} does not match the scheduled 0x{}",
+                    hex::encode(requested),
+                    hex::encode(scheduled)
+                ));
+            }
+            info!(
+                "Signing at epoch {epoch} under the scheduled fork version 0x{}",
+                hex::encode(scheduled)
+            );
+            fork_info.fork = schedule.fork_at_epoch(epoch);
         }
     }
+    None
 }
 
-fn update_slash_protection_db(
+/// Describes why the selection_proof of an AGGREGATE_AND_PROOF does not verify against the
+/// aggregator's key, which signs the aggregate too. Other msgs carry no selection proof.
+fn selection_proof_violation(
     bls_pk_hex: &String,
     signing_data: &crate::eth2::eth_signing::BLSSignMsg,
-) -> Result<()> {
-    info!("update_slash_protection_db()");
-    let mut db: crate::eth2::slash_protection::SlashingProtectionData =
-        crate::eth2::slash_protection::SlashingProtectionData::read(bls_pk_hex.as_str())?;
-    let signing_root = signing_data.to_signing_root(None);
+) -> Option<String> {
+    let m = match signing_data {
+        crate::eth2::eth_signing::BLSSignMsg::AGGREGATE_AND_PROOF(m)
+        | crate::eth2::eth_signing::BLSSignMsg::aggregate_and_proof(m) => m,
+        _ => return None,
+    };
+    let slot = m.aggregate_and_proof.aggregate.data.slot;
+    let root =
+        crate::eth2::eth_signing::compute_selection_proof_signing_root(m.fork_info.clone(), slot);
+    match crate::crypto::bls_keys::bls_verify(
+        bls_pk_hex,
+        &root,
+        &m.aggregate_and_proof.selection_proof,
+    ) {
+        Ok(true) => None,
+        Ok(false) => Some(format!(
+            "selection_proof for slot {slot} was not signed by 0x{bls_pk_hex}"
+        )),
+        Err(e) => Some(format!("Bad selection_proof: {:?}", e)),
+    }
+}
+
+/// Describes why signing_data is too far ahead of the wall clock, if it is a block or
+/// attestation at all
+fn far_future_violation(
+    signing_data: &crate::eth2::eth_signing::BLSSignMsg,
+    clock: crate::eth2::slash_protection::SlotClock,
+) -> Option<String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let current_slot = clock.slot_at(now);
     match signing_data {
         crate::eth2::eth_signing::BLSSignMsg::BLOCK(m)
         | crate::eth2::eth_signing::BLSSignMsg::block(m) => {
-            let b = crate::eth2::slash_protection::SignedBlockSlot {
-                slot: m.block.slot,
-                signing_root: Some(signing_root),
-            };
-            db.new_block(b, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
-            db.write()
+            let slot = m.block.slot;
+            clock.is_far_future_slot(slot, now).then(|| {
+                format!("block slot {slot} is too far ahead of current slot {current_slot}")
+            })
         }
         crate::eth2::eth_signing::BLSSignMsg::BLOCK_V2(m)
         | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => {
-            let b = crate::eth2::slash_protection::SignedBlockSlot {
-                slot: m.beacon_block.block_header.slot,
-                signing_root: Some(signing_root),
-            };
-            db.new_block(b, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
-            db.write()
+            let slot = m.beacon_block.block_header.slot;
+            clock.is_far_future_slot(slot, now).then(|| {
+                format!("block slot {slot} is too far ahead of current slot {current_slot}")
+            })
         }
         crate::eth2::eth_signing::BLSSignMsg::ATTESTATION(m)
         | crate::eth2::eth_signing::BLSSignMsg::attestation(m) => {
-            let a = crate::eth2::slash_protection::SignedAttestationEpochs {
-                source_epoch: m.attestation.source.epoch,
-                target_epoch: m.attestation.target.epoch,
-                signing_root: Some(signing_root),
-            };
-            db.new_attestation(a, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
-            db.write()
+            let epoch = m.attestation.target.epoch;
+            clock.is_far_future_epoch(epoch, now).then(|| {
+                format!("attestation target epoch {epoch} is too far ahead of current slot {current_slot}")
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Describes why a RANDAO_REVEAL is more than `max_lookahead` epochs ahead of the current
+/// epoch, or without a slot clock, of the epoch of the key's last signed block. Reveals for keys
+/// that never signed a block cannot be checked without a slot clock.
+fn randao_lookahead_violation(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    bls_pk_hex: &String,
+    signing_data: &crate::eth2::eth_signing::BLSSignMsg,
+    max_lookahead: crate::eth2::eth_types::Epoch,
+    clock: Option<crate::eth2::slash_protection::SlotClock>,
+) -> Result<Option<String>> {
+    let epoch = match signing_data {
+        crate::eth2::eth_signing::BLSSignMsg::RANDAO_REVEAL(m)
+        | crate::eth2::eth_signing::BLSSignMsg::randao_reveal(m) => m.randao_reveal.epoch,
+        _ => return Ok(None),
+    };
+    let (reference, what) = match clock {
+        Some(clock) => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            (
+                crate::eth2::eth_signing::compute_epoch_at_slot(clock.slot_at(now)),
+                "the current epoch",
+            )
         }
+        None => {
+            if !backend.exists(bls_pk_hex) {
+                return Ok(None);
+            }
+            let db = backend.read(bls_pk_hex)?;
+            if db.signed_blocks.is_empty() {
+                return Ok(None);
+            }
+            (
+                crate::eth2::eth_signing::compute_epoch_at_slot(db.get_latest_signed_block_slot()),
+                "the epoch of the last signed block",
+            )
+        }
+    };
+    Ok((epoch > reference.saturating_add(max_lookahead)).then(|| {
+        format!("RANDAO reveal epoch {epoch} is more than {max_lookahead} epochs ahead of {what} {reference}")
+    }))
+}
+
+/// Records signing_data in the key's slash protection history unless it is slashable, either by
+/// that history or by the operator's floor. The check and the write share one backend
+/// transaction. Returns false, saving nothing, if the msg is slashable. A retry of the last
+/// signed msg with the same signing root is not slashable and leaves the history as is.
+fn check_and_update_slash_protection_db(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    bls_pk_hex: &String,
+    signing_data: &crate::eth2::eth_signing::BLSSignMsg,
+    signing_root: crate::eth2::eth_types::Root,
+    floor: crate::eth2::slash_protection::SlashingFloor,
+) -> Result<bool> {
+    info!("check_and_update_slash_protection_db()");
+    match signing_data {
+        crate::eth2::eth_signing::BLSSignMsg::BLOCK(m)
+        | crate::eth2::eth_signing::BLSSignMsg::block(m) => {
+            check_and_record_block(backend, bls_pk_hex, m.block.slot, signing_root, floor)
+        }
+        crate::eth2::eth_signing::BLSSignMsg::BLOCK_V2(m)
+        | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => check_and_record_block(
+            backend,
+            bls_pk_hex,
+            m.beacon_block.block_header.slot,
+            signing_root,
+            floor,
+        ),
+        crate::eth2::eth_signing::BLSSignMsg::ATTESTATION(m)
+        | crate::eth2::eth_signing::BLSSignMsg::attestation(m) => check_and_record_attestation(
+            backend,
+            bls_pk_hex,
+            m.attestation.source.epoch,
+            m.attestation.target.epoch,
+            signing_root,
+            floor,
+        ),
         _ => {
             // Only block proposals and attestations are slashable
             error!("Attempted to update slash protection db with non-slashable msg type");
@@ -166,6 +515,68 @@ fn update_slash_protection_db(
     }
 }
 
+fn check_and_record_block(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    bls_pk_hex: &String,
+    slot: crate::eth2::eth_types::Slot,
+    signing_root: crate::eth2::eth_types::Root,
+    floor: crate::eth2::slash_protection::SlashingFloor,
+) -> Result<bool> {
+    let mut repeat = false;
+    let saved = backend.update(bls_pk_hex, &mut |db| {
+        if floor.is_below_block_slot(slot) {
+            return Ok(false);
+        }
+        if db.is_repeat_block(slot, signing_root) {
+            info!("Re-signing block at slot {slot}");
+            repeat = true;
+            return Ok(false);
+        }
+        if db.is_slashable_block_slot(slot) {
+            return Ok(false);
+        }
+        let b = crate::eth2::slash_protection::SignedBlockSlot {
+            slot,
+            signing_root: Some(signing_root),
+        };
+        db.new_block(b, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
+        Ok(true)
+    })?;
+    Ok(saved || repeat)
+}
+
+fn check_and_record_attestation(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
+    bls_pk_hex: &String,
+    src: crate::eth2::eth_types::Epoch,
+    tgt: crate::eth2::eth_types::Epoch,
+    signing_root: crate::eth2::eth_types::Root,
+    floor: crate::eth2::slash_protection::SlashingFloor,
+) -> Result<bool> {
+    let mut repeat = false;
+    let saved = backend.update(bls_pk_hex, &mut |db| {
+        if floor.is_below_attestation_epochs(src, tgt) {
+            return Ok(false);
+        }
+        if db.is_repeat_attestation(src, tgt, signing_root) {
+            info!("Re-signing attestation with source {src} and target {tgt}");
+            repeat = true;
+            return Ok(false);
+        }
+        if db.is_slashable_attestation_epochs(src, tgt) {
+            return Ok(false);
+        }
+        let a = crate::eth2::slash_protection::SignedAttestationEpochs {
+            source_epoch: src,
+            target_epoch: tgt,
+            signing_root: Some(signing_root),
+        };
+        db.new_attestation(a, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
+        Ok(true)
+    })?;
+    Ok(saved || repeat)
+}
+
 pub fn build_validator_remote_attestation_payload(
     validator_pk_set: blsttc::PublicKeySet,
     signature: &crate::eth2::eth_types::BLSSignature,
@@ -189,7 +600,7 @@ pub fn build_validator_remote_attestation_payload(
 
     for (i, (sk_share, g_pk)) in enc_sk_shares.iter().zip(guardian_pks.iter()).enumerate() {
         // blsEncPrivKeyShares
-        hasher.update(hex::decode(&sk_share)?);
+        hasher.update(crate::parse_hex(sk_share)?);
 
         // blsPubKeyShares
         hasher.update(&validator_pk_set.public_key_share(i).to_bytes());
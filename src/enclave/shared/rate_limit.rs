@@ -0,0 +1,222 @@
+use anyhow::{bail, Result};
+use axum::{
+    extract::State,
+    http::{header, Request},
+    middleware::Next,
+    response::IntoResponse,
+};
+use log::error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Prefixes of the routes `RateLimits::sign` applies to, the BLS pubkey follows them
+const SIGN_ROUTES: [&str; 2] = ["/api/v1/eth2/sign/", "/leader/v1/eth2/sign/"];
+
+/// Above this many buckets, the full ones are dropped, as a full bucket behaves like none. This
+/// bounds the memory of callers cycling through made up pubkeys.
+const MAX_IDLE_BUCKETS: usize = 1024;
+
+/// How many requests a caller may make: `rate` per second on average, and up to `burst` at once
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub rate: f64,
+    pub burst: f64,
+}
+
+impl RateLimit {
+    /// `burst` defaults to one second's worth of requests
+    pub fn new(rate: u64, burst: Option<u64>) -> Result<Self> {
+        let burst = burst.unwrap_or(rate);
+        if rate == 0 || burst == 0 {
+            bail!("Rate limits must allow at least 1 request")
+        }
+        Ok(RateLimit {
+            rate: rate as f64,
+            burst: burst as f64,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.rate).min(limit.burst);
+        self.refilled_at = now;
+    }
+}
+
+/// A token bucket per caller key, sharing one `RateLimit`
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    name: &'static str,
+    limit: RateLimit,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+impl RateLimiter {
+    /// `name` labels the limiter in /metrics
+    pub fn new(name: &'static str, limit: RateLimit) -> Self {
+        RateLimiter {
+            name,
+            limit,
+            buckets: Default::default(),
+        }
+    }
+
+    /// Takes a token from `key`'s bucket at `now`, or returns how long until one is available
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            let limit = self.limit;
+            buckets.retain(|_, bucket| {
+                bucket.refill(limit, now);
+                bucket.tokens < limit.burst
+            });
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: self.limit.burst,
+            refilled_at: now,
+        });
+        bucket.refill(self.limit, now);
+        let result = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.limit.rate,
+            ))
+        };
+        crate::enclave::shared::metrics::set_rate_limit_buckets(self.name, buckets.len());
+        result
+    }
+
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+}
+
+/// The limiters `rate_limit` applies, each disabled when None
+#[derive(Clone, Debug, Default)]
+pub struct RateLimits {
+    /// Per BLS pubkey on the signing routes, the leader's included, so one runaway validator
+    /// client cannot starve the other keys
+    pub sign: Option<RateLimiter>,
+    /// Shared by every caller of the routes that add, delete or reconfigure keys
+    pub admin: Option<RateLimiter>,
+}
+
+impl RateLimits {
+    /// The limiter and bucket key `method` requests to `path` count against, if any
+    fn limiter_for(
+        &self,
+        method: &axum::http::Method,
+        path: &str,
+    ) -> Option<(&RateLimiter, String)> {
+        if let Some(bls_pk_hex) = SIGN_ROUTES
+            .iter()
+            .find_map(|route| path.strip_prefix(route))
+        {
+            let bls_pk_hex = bls_pk_hex.trim_start_matches("0x").to_lowercase();
+            return self.sign.as_ref().map(|limiter| (limiter, bls_pk_hex));
+        }
+        if crate::enclave::shared::api_token::is_admin_route(method, path) {
+            return self.admin.as_ref().map(|limiter| (limiter, String::new()));
+        }
+        None
+    }
+}
+
+/// Refuses requests over their route's rate limit with 429 and a Retry-After header in seconds
+pub async fn rate_limit<B>(
+    State(limits): State<RateLimits>,
+    req: Request<B>,
+    next: Next<B>,
+) -> axum::response::Response {
+    let Some((limiter, key)) = limits.limiter_for(req.method(), req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let retry_after = match limiter.check(&key) {
+        Ok(()) => return next.run(req).await,
+        Err(retry_after) => retry_after,
+    };
+    error!(
+        "Rate limiting {} {}, retry after {:?}",
+        req.method(),
+        req.uri().path(),
+        retry_after
+    );
+    crate::enclave::shared::metrics::record_rate_limited(limiter.name);
+    // Retry-After counts whole seconds, rounding down would invite an early retry
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        [(header::RETRY_AFTER, retry_after_secs.to_string())],
+        crate::enclave::types::ErrorResponse::new(
+            axum::http::status::StatusCode::TOO_MANY_REQUESTS,
+            format!("Rate limit exceeded, retry after {retry_after_secs}s"),
+        ),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refills_at_rate_up_to_burst() {
+        let limiter = RateLimiter::new("test", RateLimit::new(2, Some(3)).unwrap());
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_at("a", start).is_ok());
+        }
+        let retry_after = limiter.check_at("a", start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+        // Other keys have their own bucket
+        assert!(limiter.check_at("b", start).is_ok());
+
+        // One token back every 500ms
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check_at("a", later).is_ok());
+        assert!(limiter.check_at("a", later).is_err());
+
+        // Never more than the burst, however long the caller was idle
+        let much_later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(limiter.check_at("a", much_later).is_ok());
+        }
+        assert!(limiter.check_at("a", much_later).is_err());
+    }
+
+    #[test]
+    fn test_full_buckets_are_pruned() {
+        let limiter = RateLimiter::new("test", RateLimit::new(1, None).unwrap());
+        let start = Instant::now();
+        for i in 0..=MAX_IDLE_BUCKETS {
+            assert!(limiter.check_at(&i.to_string(), start).is_ok());
+        }
+        assert!(limiter.check_at("0", start).is_err());
+
+        // A second later every bucket refilled, so they are dropped
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.check_at("busy", later).is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+        assert!(limiter.check_at("busy", later).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_needs_a_request() {
+        assert!(RateLimit::new(0, None).is_err());
+        assert!(RateLimit::new(5, Some(0)).is_err());
+        assert_eq!(RateLimit::new(5, None).unwrap().burst, 5.0);
+    }
+}
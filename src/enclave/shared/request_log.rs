@@ -0,0 +1,123 @@
+use anyhow::{bail, Result};
+use axum::{extract::ConnectInfo, http::Request, middleware::Next};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::{filter::LevelFilter, fmt::format::FmtSpan, EnvFilter};
+
+/// Hex characters of a public key or address kept in logged paths
+const REDACTED_PATH_SEGMENT_CHARS: usize = 8;
+
+/// How log lines are written to stdout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One line per event, as before
+    #[default]
+    Text,
+    /// Multi-line events for reading in a terminal
+    Pretty,
+    /// One JSON object per event for log collectors
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("Unknown log format {s}, expected text, pretty or json"),
+        }
+    }
+}
+
+/// Installs the global subscriber for both `tracing` and `log` events. The level is read from
+/// RUST_LOG, e.g. `RUST_LOG=warn,puffersecuresigner=debug`, and defaults to info. Closing spans
+/// are logged with how long they took.
+pub fn init_logging(format: LogFormat) {
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+    match format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+/// Shortens hex path segments, such as the public key being signed with, to their first
+/// characters, so logs show which key was used without listing every key the signer holds
+pub fn redact_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let hex = segment.strip_prefix("0x").unwrap_or(segment);
+            if hex.len() > 2 * REDACTED_PATH_SEGMENT_CHARS
+                && hex.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                let prefix = &segment[..segment.len() - hex.len()];
+                format!("{prefix}{}…", &hex[..REDACTED_PATH_SEGMENT_CHARS])
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Emits one `request` event per request with its method, redacted path, status, latency and
+/// the client's address when the server records it. Bodies and headers are never logged.
+pub async fn log_requests<B>(req: Request<B>, next: Next<B>) -> axum::response::Response {
+    let started = std::time::Instant::now();
+    let method = req.method().clone();
+    let path = redact_path(req.uri().path());
+    let remote_addr = req
+        .extensions()
+        .get::<ConnectInfo<std::net::SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.to_string());
+
+    let response = next.run(req).await;
+
+    tracing::info!(
+        target: "request",
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_secs_f64() * 1000.0,
+        remote_addr = remote_addr.as_deref().unwrap_or("-"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_path() {
+        let pk = "0xa99a76ed7796f7be22d5b7e85deeb7c5677e88e511e0b337618f8c4eb61349b4bf2d153f649f7b53359fe8b94a38e44c";
+        assert_eq!(
+            redact_path(&format!("/api/v1/eth2/sign/{pk}")),
+            "/api/v1/eth2/sign/0xa99a76ed…"
+        );
+        assert_eq!(
+            redact_path(&format!("/eth/v1/validator/{}/graffiti", &pk[2..])),
+            "/eth/v1/validator/a99a76ed…/graffiti"
+        );
+        assert_eq!(redact_path("/eth/v1/keystores"), "/eth/v1/keystores");
+        assert_eq!(
+            redact_path("/eth/v1/remote-attestation/dcap"),
+            "/eth/v1/remote-attestation/dcap"
+        );
+    }
+
+    #[test]
+    fn test_log_format_from_str() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
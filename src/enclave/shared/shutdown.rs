@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use axum_server::tls_openssl::OpenSSLConfig;
+use log::info;
+
+/// Resolves on the first SIGINT or SIGTERM
+pub async fn shutdown_signal() {
+    let mut sigterm =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).ok();
+    let terminate = async {
+        match sigterm.as_mut() {
+            Some(sigterm) => {
+                sigterm.recv().await;
+            }
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+}
+
+/// Serves `app` on `listener`, over TLS when `tls` is set, until `shutdown` resolves. New
+/// connections are then refused, and in-flight requests get `grace` to finish, so a signature
+/// is not cut off between persisting its slashing protection and returning, before the
+/// remaining connections are closed. Cached keys are dropped once the server has stopped.
+/// Handlers can read the client's address from `ConnectInfo<SocketAddr>`.
+pub async fn serve_until(
+    listener: std::net::TcpListener,
+    app: axum::Router,
+    tls: Option<OpenSSLConfig>,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    grace: std::time::Duration,
+) -> Result<()> {
+    listener.set_nonblocking(true)?;
+    let handle = axum_server::Handle::new();
+    let stopping = handle.clone();
+    tokio::spawn(async move {
+        shutdown.await;
+        info!(
+            "Shutting down, waiting up to {}s for {} open connections",
+            grace.as_secs(),
+            stopping.connection_count()
+        );
+        stopping.graceful_shutdown(Some(grace));
+    });
+
+    let served = match tls {
+        Some(config) => {
+            axum_server::from_tcp_openssl(listener, config)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+                .await
+        }
+        None => {
+            axum_server::from_tcp(listener)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+                .await
+        }
+    };
+    served.with_context(|| "Server failed")?;
+    stopped();
+    Ok(())
+}
+
+/// Drops the cached keys once the server has stopped serving requests
+pub(crate) fn stopped() {
+    let dropped = crate::crypto::bls_keys::clear_cached_bls_keys();
+    info!("Secure-Signer stopped cleanly, dropped {dropped} cached keys");
+}
@@ -0,0 +1,109 @@
+use anyhow::{bail, Context, Result};
+use axum_server::tls_openssl::OpenSSLConfig;
+use log::info;
+use openssl::pkey::PKey;
+use openssl::ssl::{SslAcceptor, SslMethod, SslVerifyMode};
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::X509;
+
+/// Loads the PEM certificate chain, leaf first, and private key the HTTP server terminates TLS
+/// with. Malformed files and a key that does not match the certificate fail here, at startup,
+/// rather than on the first handshake. With `client_ca_path`, clients must present a certificate
+/// issued by one of the PEM CAs in it, see `require_client_certs`.
+pub fn load_tls_config(
+    cert_path: &str,
+    key_path: &str,
+    client_ca_path: Option<&str>,
+) -> Result<OpenSSLConfig> {
+    let cert_pem = std::fs::read(cert_path)
+        .with_context(|| format!("Failed to read TLS certificate {cert_path}"))?;
+    let chain = X509::stack_from_pem(&cert_pem)
+        .with_context(|| format!("Bad PEM in TLS certificate {cert_path}"))?;
+    let Some((leaf, intermediates)) = chain.split_first() else {
+        bail!("No certificate in TLS certificate {cert_path}")
+    };
+    let key_pem =
+        std::fs::read(key_path).with_context(|| format!("Failed to read TLS key {key_path}"))?;
+    let key = PKey::private_key_from_pem(&key_pem)
+        .with_context(|| format!("Bad PEM in TLS key {key_path}"))?;
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())?;
+    acceptor.set_certificate(leaf)?;
+    for intermediate in intermediates {
+        acceptor.add_extra_chain_cert(intermediate.clone())?;
+    }
+    acceptor.set_private_key(&key)?;
+    acceptor
+        .check_private_key()
+        .with_context(|| format!("TLS key {key_path} does not match {cert_path}"))?;
+    if let Some(client_ca_path) = client_ca_path {
+        require_client_certs(&mut acceptor, client_ca_path)?;
+    }
+    Ok(OpenSSLConfig::from_acceptor(std::sync::Arc::new(
+        acceptor.build(),
+    )))
+}
+
+/// Fails the handshake of clients without a certificate issued by a CA in the `client_ca_path`
+/// bundle, so unauthenticated clients never reach a handler. The subject and SHA-256 fingerprint
+/// of every accepted client certificate are logged.
+fn require_client_certs(
+    acceptor: &mut openssl::ssl::SslAcceptorBuilder,
+    client_ca_path: &str,
+) -> Result<()> {
+    let ca_pem = std::fs::read(client_ca_path)
+        .with_context(|| format!("Failed to read TLS client CA {client_ca_path}"))?;
+    let cas = X509::stack_from_pem(&ca_pem)
+        .with_context(|| format!("Bad PEM in TLS client CA {client_ca_path}"))?;
+    if cas.is_empty() {
+        bail!("No certificate in TLS client CA {client_ca_path}")
+    }
+
+    let mut store = X509StoreBuilder::new()?;
+    let mut ca_names = Stack::new()?;
+    for ca in cas {
+        ca_names.push(ca.subject_name().to_owned()?)?;
+        store.add_cert(ca)?;
+    }
+    acceptor.set_verify_cert_store(store.build())?;
+    // Tells clients which CAs their certificate must be issued by
+    acceptor.set_client_ca_list(ca_names);
+    acceptor.set_verify_callback(
+        SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+        |verified, ctx| {
+            if verified && ctx.error_depth() == 0 {
+                if let Some(cert) = ctx.current_cert() {
+                    let fingerprint = cert
+                        .digest(openssl::hash::MessageDigest::sha256())
+                        .map(hex::encode)
+                        .unwrap_or_default();
+                    info!(
+                        "Accepted TLS client {:?} with certificate fingerprint {}",
+                        cert.subject_name(),
+                        fingerprint
+                    );
+                }
+            }
+            verified
+        },
+    );
+    Ok(())
+}
+
+/// Serves `app` over TLS on an already bound listener until the server fails, see
+/// `shutdown::serve_until` to stop it
+pub async fn serve_tls(
+    listener: std::net::TcpListener,
+    config: OpenSSLConfig,
+    app: axum::Router,
+) -> Result<()> {
+    crate::enclave::shared::shutdown::serve_until(
+        listener,
+        app,
+        Some(config),
+        std::future::pending(),
+        std::time::Duration::ZERO,
+    )
+    .await
+}
@@ -0,0 +1,85 @@
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::pin::Pin;
+use std::task::Poll;
+
+/// Hands hyper the connections accepted on a Unix domain socket
+struct UnixAccept(tokio::net::UnixListener);
+
+impl hyper::server::accept::Accept for UnixAccept {
+    type Conn = tokio::net::UnixStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        match self.0.poll_accept(cx) {
+            Poll::Ready(accepted) => Poll::Ready(Some(accepted.map(|(stream, _)| stream))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Binds a Unix domain socket at `path` that only its owner may connect to. A socket left
+/// behind by a previous run is replaced, any other file at `path` is an error.
+pub fn bind_unix_socket(path: &std::path::Path) -> Result<tokio::net::UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            warn!("Replacing the stale socket {}", path.display());
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(_) => bail!("{} exists and is not a socket", path.display()),
+        Err(_) => {}
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to restrict {}", path.display()))?;
+    Ok(listener)
+}
+
+/// Serves `app` on the Unix domain socket at `path` until `shutdown` resolves, like
+/// `shutdown::serve_until` does over TCP. In-flight requests get `grace` to finish, then the
+/// socket file is removed and cached keys are dropped.
+pub async fn serve_unix_until(
+    path: &std::path::Path,
+    app: axum::Router,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    grace: std::time::Duration,
+) -> Result<()> {
+    let listener = bind_unix_socket(path)?;
+    let (stopping, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = axum::Server::builder(UnixAccept(listener))
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            info!(
+                "Shutting down, waiting up to {}s for open connections",
+                grace.as_secs()
+            );
+            let _ = stopping.send(());
+        });
+    let grace_over = async move {
+        match stopped.await {
+            Ok(()) => tokio::time::sleep(grace).await,
+            Err(_) => std::future::pending().await,
+        }
+    };
+
+    let served = tokio::select! {
+        served = server => served.with_context(|| "Server failed"),
+        _ = grace_over => {
+            info!("Grace period over, closing the remaining connections");
+            Ok(())
+        }
+    };
+    if let Err(e) = std::fs::remove_file(path) {
+        warn!("Failed to remove the socket {}: {e}", path.display());
+    }
+    served?;
+    crate::enclave::shared::shutdown::stopped();
+    Ok(())
+}
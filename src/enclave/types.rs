@@ -11,14 +11,22 @@ use tree_hash::TreeHash;
 pub struct KeyGenResponse {
     pub pk_hex: String,
     pub evidence: AttestationEvidence,
+    /// The address of ETH keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// The public key BLS signatures verify against, when built with `min-sig`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_pk: Option<String>,
 }
 
 impl KeyGenResponse {
     pub fn from_eth_key(pk: EthPublicKey, evidence: AttestationEvidence) -> Self {
-        let pk: String = strip_0x_prefix!(hex::encode(pk.serialize())); // uncompressed
+        let address = eth_keys::eth_pk_to_address(&pk);
         KeyGenResponse {
-            pk_hex: format!("0x{}", pk),
+            pk_hex: crate::to_0x_hex(pk.serialize()), // uncompressed
             evidence,
+            address: Some(address),
+            signing_pk: None,
         }
     }
 
@@ -26,6 +34,8 @@ impl KeyGenResponse {
         KeyGenResponse {
             pk_hex: format!("0x{}", &pk.to_hex()),
             evidence,
+            address: None,
+            signing_pk: None,
         }
     }
 
@@ -34,7 +44,7 @@ impl KeyGenResponse {
         self.evidence.verify_intel_signing_certificate()?;
 
         // Verify the MRENCLAVE measurement is valid
-        let mrenclave: String = strip_0x_prefix!(mrenclave);
+        let mrenclave = crate::normalize_hex(mrenclave)?;
         let got_mrenclave = self.evidence.get_mrenclave()?;
         if mrenclave != got_mrenclave {
             bail!("Received MRENCLAVE {got_mrenclave} does not match expected {mrenclave}")
@@ -59,7 +69,7 @@ impl KeyGenResponse {
         self.evidence.verify_intel_signing_certificate()?;
 
         // Verify the MRENCLAVE measurement is valid
-        let mrenclave: String = strip_0x_prefix!(mrenclave);
+        let mrenclave = crate::normalize_hex(mrenclave)?;
         let got_mrenclave = self.evidence.get_mrenclave()?;
         if mrenclave != got_mrenclave {
             bail!("Received MRENCLAVE {got_mrenclave} does not match expected {mrenclave}")
@@ -80,9 +90,129 @@ impl KeyGenResponse {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DeriveBlsKeyRequest {
+    /// The validator index i in the EIP-2334 signing key path m/12381/3600/i/0/0
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Optional body of the keygen routes
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct KeyGenRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Names the saved keys to attest to, at least one is required. The nonce is hex of up to
+/// 64 bytes chosen by the verifier, so old evidence cannot be replayed. It is also accepted as
+/// `challenge_hex`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RemoteAttestationRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eth_pubkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bls_pubkey: Option<String>,
+    #[serde(
+        default,
+        alias = "challenge_hex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub nonce: Option<String>,
+}
+
+/// Evidence whose report data is the hash of `binding`, see `KeyBinding`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RemoteAttestationResponse {
+    #[serde(flatten)]
+    pub binding: crate::io::remote_attestation::KeyBinding,
+    pub report_data: String,
+    pub evidence: AttestationEvidence,
+    /// Unix time in seconds the evidence was generated at, it may be served from the cache
+    pub generated_at: u64,
+}
+
+/// `fresh=true` regenerates evidence instead of serving it from the cache
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RemoteAttestationQuery {
+    #[serde(default)]
+    pub fresh: bool,
+}
+
+/// Query of the batch attestation route. `nonce` is hex encoded, up to 64 bytes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct KeySetAttestationQuery {
+    #[serde(default)]
+    pub kind: crate::io::remote_attestation::EvidenceKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+/// Evidence whose report data is the digest of every key the enclave holds, see `KeySetBinding`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct KeySetAttestationResponse {
+    #[serde(flatten)]
+    pub key_set: crate::io::remote_attestation::KeySetBinding,
+    pub report_data: String,
+    pub evidence: AttestationEvidence,
+}
+
+/// Replaces the label of a saved key, null removes it
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct UpdateKeyLabelRequest {
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DeriveBlsKeyResponse {
+    pub pk_hex: String,
+    pub derivation_path: String,
+    pub evidence: AttestationEvidence,
+}
+
+/// Returned once by mnemonic keygen. The mnemonic is not kept by the enclave, and is left out
+/// of its Debug output so it can never be logged.
+#[derive(Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MnemonicKeyGenResponse {
+    pub pk_hex: String,
+    pub derivation_path: String,
+    pub mnemonic: String,
+    pub evidence: AttestationEvidence,
+}
+
+impl std::fmt::Debug for MnemonicKeyGenResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MnemonicKeyGenResponse")
+            .field("pk_hex", &self.pk_hex)
+            .field("derivation_path", &self.derivation_path)
+            .field("mnemonic", &"<redacted>")
+            .field("evidence", &self.evidence)
+            .finish()
+    }
+}
+
+/// Restores the keys of validators 0..`num_keys` from a mnemonic. `encrypted_mnemonic` is
+/// hex-encoded and ECIES encrypted to the enclave ETH key `encrypting_pk_hex`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RecoverBlsKeysRequest {
+    pub encrypted_mnemonic: String,
+    pub encrypting_pk_hex: String,
+    pub num_keys: u32,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ListKeysResponseInner {
     pub pubkey: String,
+    /// The EIP-2334 path of keys derived from the enclave's seed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// The address of ETH keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Origin, creation and last signing time, and label of the key
+    #[serde(flatten)]
+    pub metadata: Option<crate::io::key_metadata::KeyMetadata>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -90,19 +220,31 @@ pub struct ListKeysResponse {
     pub data: Vec<ListKeysResponseInner>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListKeysQuery {
+    /// Only list keys with this label
+    pub label: Option<String>,
+}
+
 impl ListKeysResponse {
+    /// Drops the keys whose metadata does not carry `label`
+    pub fn retain_label(&mut self, label: &str) {
+        self.data.retain(|key| {
+            key.metadata
+                .as_ref()
+                .and_then(|m| m.label.as_deref())
+                .map_or(false, |l| l == label)
+        });
+    }
+
     pub fn new(keys: Vec<String>) -> ListKeysResponse {
         let inners = keys
             .iter()
-            .map(|pk| {
-                // Prepend leading 0x if needed
-                let pubkey = match pk[0..2].into() {
-                    "0x" => pk.to_string(),
-                    _ => "0x".to_owned() + &pk.to_string(),
-                };
-                ListKeysResponseInner {
-                    pubkey: pubkey.into(),
-                }
+            .map(|pk| ListKeysResponseInner {
+                pubkey: format!("0x{}", crate::io::key_management::key_fname(pk)),
+                derivation_path: None,
+                address: None,
+                metadata: None,
             })
             .collect();
 
@@ -110,25 +252,794 @@ impl ListKeysResponse {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DeleteKeysRequest {
+    pub pubkeys: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteKeyStatus {
+    Deleted,
+    NotFound,
+    Error,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DeleteKeysResponseInner {
+    pub status: DeleteKeyStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Status of each `DeleteKeysRequest.pubkeys` entry with the same length and order
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DeleteKeysResponse {
+    pub data: Vec<DeleteKeysResponseInner>,
+}
+
+/// Keymanager API import body. Each keystore is an EIP-2335 JSON string. If `encrypting_pk_hex`
+/// is set, each password is hex-encoded and ECIES encrypted to that enclave ETH key. Passwords
+/// are left out of its Debug output.
+#[derive(Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ImportKeystoresRequest {
+    pub keystores: Vec<String>,
+    pub passwords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypting_pk_hex: Option<String>,
+    /// EIP-3076 interchange file seeding the history of the imported keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slashing_protection: Option<String>,
+    /// Labels saved in each imported key's metadata, an empty label is the same as none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    /// Evidence of the enclave that exported the keystores, whose report data is
+    /// `transfer_report_data`. When set, the sender must be allowed by the measurement policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<AttestationEvidence>,
+}
+
+impl std::fmt::Debug for ImportKeystoresRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImportKeystoresRequest")
+            .field("keystores", &self.keystores)
+            .field("passwords", &"<redacted>")
+            .field("encrypting_pk_hex", &self.encrypting_pk_hex)
+            .field("slashing_protection", &self.slashing_protection)
+            .field("labels", &self.labels)
+            .field("evidence", &self.evidence)
+            .finish()
+    }
+}
+
+impl ImportKeystoresRequest {
+    /// SHA256 over the length-prefixed keystores, passwords, encrypting key and slashing
+    /// protection, so the sender's evidence vouches for exactly what is imported
+    pub fn transfer_report_data(&self) -> [u8; 32] {
+        let mut hasher = openssl::sha::Sha256::new();
+        let mut update = |field: &str| {
+            hasher.update(&(field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        };
+        update(&self.keystores.len().to_string());
+        for (keystore, password) in self.keystores.iter().zip(self.passwords.iter()) {
+            update(keystore);
+            update(password);
+        }
+        update(self.encrypting_pk_hex.as_deref().unwrap_or_default());
+        update(self.slashing_protection.as_deref().unwrap_or_default());
+        hasher.finish()
+    }
+}
+
+/// Moves saved BLS keys to another enclave holding the ETH key `encrypting_pk_hex`. `evidence`
+/// must attest to `encrypting_pk_hex` from an enclave build the measurement policy allows. If
+/// `attest` is set, the response carries evidence of this enclave over the exported keys.
+/// The response is an `ImportKeystoresRequest` for the other enclave.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExportKeystoresRequest {
+    pub pubkeys: Vec<String>,
+    pub encrypting_pk_hex: String,
+    /// The other enclave's evidence over `encrypting_pk_hex`, from its ETH keygen response
+    pub evidence: AttestationEvidence,
+    /// Attach this enclave's evidence to the response, for receivers enforcing a measurement
+    /// policy
+    #[serde(default)]
+    pub attest: bool,
+}
+
+/// Splits the saved BLS key `bls_pk_hex` into `n` Shamir shares, any `threshold` of which can sign
+/// for it, with 1 < `threshold` <= `n`. Workers are given by their ETH keygen responses, one share
+/// each, or if `workers` is empty the shares go to `n` of the verified, healthy workers registered
+/// with this leader. If `mrenclave` is set, each given worker's evidence must attest to its ETH
+/// key from an enclave measuring `mrenclave`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SplitBlsKeyRequest {
+    pub bls_pk_hex: String,
+    /// How many shares to split into, the number of `workers` if they are given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<usize>,
+    pub threshold: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workers: Vec<KeyGenResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrenclave: Option<String>,
+}
+
+/// The share of one worker, ECIES encrypted to its ETH key
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SplitBlsKeyShare {
+    pub worker_pk_hex: String,
+    /// Position of the share, partial signatures are recombined by it
+    pub index: usize,
+    pub pk_share_hex: String,
+    pub encrypted_sk_share_hex: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SplitBlsKeyResponse {
+    pub bls_pk_hex: String,
+    pub threshold: usize,
+    /// The hex-encoded `PublicKeySet`, committing to the coefficients of the sharing polynomial
+    pub verification_vector: String,
+    pub shares: Vec<SplitBlsKeyShare>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKeystoreStatus {
+    Imported,
+    Duplicate,
+    Error,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ImportKeystoresResponseInner {
+    pub status: ImportKeystoreStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Status of each `ImportKeystoresRequest.keystores` entry with the same length and order
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ImportKeystoresResponse {
+    pub data: Vec<ImportKeystoresResponseInner>,
+}
+
+/// A key whose secret lives in another signer, as listed by the keymanager remotekeys API
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RemoteKey {
+    pub pubkey: String,
+    pub url: String,
+    pub readonly: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListRemoteKeysResponse {
+    pub data: Vec<RemoteKey>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ImportRemoteKey {
+    pub pubkey: String,
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ImportRemoteKeysRequest {
+    pub remote_keys: Vec<ImportRemoteKey>,
+}
+
+/// Status of each `ImportRemoteKeysRequest.remote_keys` entry with the same length and order
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ImportRemoteKeysResponse {
+    pub data: Vec<ImportKeystoresResponseInner>,
+}
+
+/// A worker enclave asking the leader to register it. `evidence` must commit to
+/// `ecies_pubkey` and `nonce` as a `KeyBinding` of the ETH key alone.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RegisterWorkerRequest {
+    pub url: String,
+    pub ecies_pubkey: String,
+    pub evidence: AttestationEvidence,
+    #[serde(default)]
+    pub nonce: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RegisterWorkerResponse {
+    pub id: String,
+}
+
+/// Whether a registered worker's measurements are still allowed by the measurement policy
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerStatus {
+    Verified,
+    /// The policy was reloaded without the worker's build since it registered
+    Revoked,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Worker {
+    pub id: String,
+    pub url: String,
+    pub ecies_pubkey: String,
+    pub status: WorkerStatus,
+    /// False once the worker missed `--max-missed-heartbeats` heartbeats in a row
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// How long the last answered heartbeat took
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrenclave: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrsigner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isv_svn: Option<u16>,
+    pub registered_at: u64,
+    pub last_seen: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListWorkersResponse {
+    pub data: Vec<Worker>,
+}
+
+/// What a worker reports to its leader's heartbeats
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkerStatusResponse {
+    pub uptime_secs: u64,
+    /// 0x-prefixed BLS public keys the worker can sign with, including its key shares
+    pub key_shares: Vec<String>,
+}
+
+/// A worker's slashing protection watermarks for one of its key shares, which the leader checks
+/// agree before and after asking the workers for partial signatures. Each is null until the share
+/// signs, or is imported with, a block or attestation.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkerWatermarkResponse {
+    pub pk_share_hex: String,
+    pub block_slot: Option<u64>,
+    pub attestation_source_epoch: Option<u64>,
+    pub attestation_target_epoch: Option<u64>,
+}
+
+/// Starts a leader's handshake with a worker. The worker answers with evidence of `kind` that it
+/// holds `worker_pubkey`, bound to the leader's `nonce`, and a challenge for the leader's evidence.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HandshakeChallengeRequest {
+    pub leader_pubkey: String,
+    pub worker_pubkey: String,
+    pub nonce: String,
+    #[serde(default)]
+    pub kind: crate::io::remote_attestation::EvidenceKind,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HandshakeChallengeResponse {
+    pub challenge: String,
+    pub evidence: AttestationEvidence,
+}
+
+/// Finishes a handshake with the leader's evidence that it holds `leader_pubkey`, bound to the
+/// worker's `challenge`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HandshakeRequest {
+    pub leader_pubkey: String,
+    pub challenge: String,
+    pub evidence: AttestationEvidence,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HandshakeResponse {
+    /// How long the worker trusts the session, after which the leader must handshake again
+    pub expires_in_secs: u64,
+}
+
+/// A `WorkerKeyShareRequest` as JSON, sealed under the session of the leader `leader_pubkey`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SealedKeyShareRequest {
+    pub leader_pubkey: String,
+    pub sealed_share: String,
+}
+
+/// A key share a leader delivers to one of its workers. The worker decrypts it with the ETH key
+/// `worker_pk_hex` and keeps it only if it is share `index` of the `verification_vector`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkerKeyShareRequest {
+    pub worker_pk_hex: String,
+    pub bls_pk_hex: String,
+    pub index: usize,
+    pub verification_vector: String,
+    pub pk_share_hex: String,
+    pub encrypted_sk_share_hex: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkerKeyShareResponse {
+    /// The 0x-prefixed public key share the worker now signs with
+    pub pk_share_hex: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareDeliveryStatus {
+    Delivered,
+    /// The worker acknowledged the share on an earlier delivery, so it was not sent again
+    AlreadyDelivered,
+    Failed,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ShareDelivery {
+    pub index: usize,
+    pub worker_pk_hex: String,
+    pub status: ShareDeliveryStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Re-shares a split key `threshold`-of-`n` across the registered workers with the ETH keys
+/// `workers`, or the first `n` verified and healthy ones if none are given
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ReshareKeyRequest {
+    pub threshold: usize,
+    #[serde(default)]
+    pub n: Option<usize>,
+    #[serde(default)]
+    pub workers: Vec<String>,
+}
+
+/// The verification vector of the new sharing, and how delivering each new share went
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ReshareKeyResponse {
+    pub verification_vector: String,
+    pub data: Vec<ShareDelivery>,
+}
+
+/// A `SubShareRequest`, `CombineSubSharesRequest` or `RevokeShareRequest` as JSON, sealed under
+/// the session of the leader `leader_pubkey`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SealedReshareRequest {
+    pub leader_pubkey: String,
+    pub sealed_request: String,
+}
+
+/// Asks the worker with the ETH key `worker_pk_hex`, holding share `index` of `bls_pk_hex`, to
+/// split it into `threshold`-of-n sub-shares, one ECIES encrypted to each of the ETH keys
+/// `recipients`, in new share order
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SubShareRequest {
+    pub worker_pk_hex: String,
+    pub bls_pk_hex: String,
+    pub index: usize,
+    pub verification_vector: String,
+    pub pk_share_hex: String,
+    pub threshold: usize,
+    pub recipients: Vec<String>,
+}
+
+/// Asks the worker with the ETH key `worker_pk_hex` to delete its share `index` of `bls_pk_hex`,
+/// once the key was re-shared away from the old `verification_vector`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RevokeShareRequest {
+    pub worker_pk_hex: String,
+    pub bls_pk_hex: String,
+    pub index: usize,
+    pub verification_vector: String,
+    pub pk_share_hex: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SubShareResponse {
+    /// The hex-encoded `PublicKeySet` of the sub-shares, whose public key is the old share's
+    pub commitment: String,
+    /// Sub-share `j` ECIES encrypted to `recipients[j]`
+    pub encrypted_sub_shares: Vec<String>,
+}
+
+/// A sub-share of old share `from_index` for one new worker
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SubShare {
+    pub from_index: usize,
+    pub commitment: String,
+    pub encrypted_sub_share_hex: String,
+}
+
+/// Hands a new worker the sub-shares it combines into share `index` of the new sharing of
+/// `bls_pk_hex`, whose verification vector is `verification_vector`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CombineSubSharesRequest {
+    pub worker_pk_hex: String,
+    pub bls_pk_hex: String,
+    pub index: usize,
+    pub old_verification_vector: String,
+    pub verification_vector: String,
+    pub sub_shares: Vec<SubShare>,
+    /// The highest watermarks of the old shares, which the new share's slashing protection starts
+    /// from
+    pub watermark: WorkerWatermarkResponse,
+}
+
+/// A key split across this leader's workers, and whether enough of them can sign with it now
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ThresholdKeyInfo {
+    pub bls_pk_hex: String,
+    /// How many shares the key was split into
+    pub n: usize,
+    pub threshold: usize,
+    /// Indices of the shares delivered to workers that are registered and healthy
+    pub available_shares: Vec<usize>,
+    /// Whether at least `threshold` shares are available
+    pub signable: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListThresholdKeysResponse {
+    pub data: Vec<ThresholdKeyInfo>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionState {
+    /// The key was split, but no worker acknowledged its share yet
+    Undistributed,
+    /// Some workers acknowledged their share, and distributing again retries the others
+    Partial,
+    Distributed,
+}
+
+/// Which worker holds share `index` of a split key, and what the leader last heard from it.
+/// Timestamps are unix seconds.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ShareInventory {
+    pub index: usize,
+    pub pk_share_hex: String,
+    pub worker_pk_hex: String,
+    /// The registered worker the share was encrypted to, if it is still registered
+    pub worker_id: Option<String>,
+    pub acknowledged: bool,
+    pub delivered_at: Option<u64>,
+    /// When the worker last answered a heartbeat or registered
+    pub last_heartbeat: Option<u64>,
+    /// When the worker last sent a partial signature that verified, since the leader started
+    pub last_partial_signature: Option<u64>,
+    pub healthy: bool,
+}
+
+/// Everything the leader knows about a key split across its workers
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ThresholdKeyInventory {
+    pub bls_pk_hex: String,
+    pub n: usize,
+    pub threshold: usize,
+    pub verification_vector: String,
+    pub state: DistributionState,
+    pub signable: bool,
+    pub shares: Vec<ShareInventory>,
+}
+
+/// How delivering each share of a split key went, in share order
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DistributeKeySharesResponse {
+    pub data: Vec<ShareDelivery>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SetFeeRecipientRequest {
+    pub ethaddress: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FeeRecipientResponseInner {
+    pub pubkey: String,
+    pub ethaddress: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FeeRecipientResponse {
+    pub data: FeeRecipientResponseInner,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SetGasLimitRequest {
+    pub gas_limit: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct GasLimitResponseInner {
+    pub pubkey: String,
+    pub gas_limit: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct GasLimitResponse {
+    pub data: GasLimitResponseInner,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SetGraffitiRequest {
+    pub graffiti: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct GraffitiResponseInner {
+    pub pubkey: String,
+    pub graffiti: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct GraffitiResponse {
+    pub data: GraffitiResponseInner,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PruneSlashingProtectionResponse {
+    /// Number of signed blocks and attestations removed across all keys
+    pub pruned: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SlashingProtectionStatus {
+    pub pubkey: String,
+    /// Null until the key signs, or is imported with, a block
+    pub last_signed_block_slot: Option<String>,
+    /// Null until the key signs, or is imported with, an attestation
+    pub last_signed_attestation_source_epoch: Option<String>,
+    pub last_signed_attestation_target_epoch: Option<String>,
+    /// Signatures of any type produced with the key since Secure-Signer started
+    pub signature_count: String,
+    /// Null until the key reveals its RANDAO after Secure-Signer started
+    pub highest_randao_reveal_epoch: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SlashingProtectionStatusResponse {
+    pub data: SlashingProtectionStatus,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListSlashingProtectionStatusResponse {
+    pub data: Vec<SlashingProtectionStatus>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HealthcheckResponse {
+    pub status: String,
+    /// Null unless Secure-Signer was started with --network
+    pub network: Option<String>,
+    pub genesis_fork_version: String,
+    pub genesis_validators_root: String,
+    /// Null unless the genesis time is known from --network or --genesis-time
+    pub genesis_time: Option<String>,
+    pub seconds_per_slot: Option<String>,
+    /// Saved keys of each kind, each capped at `max_keys`
+    pub bls_keys: usize,
+    pub eth_keys: usize,
+    pub max_keys: usize,
+    pub bls_key_origins: KeyOriginCounts,
+    /// New keys and slashing protection updates fail while the data directory is unwritable
+    pub data_dir_writable: bool,
+    /// Whether every slashing protection history was readable at startup
+    pub slashing_protection_intact: bool,
+    /// The evidence remote attestation can produce, empty when it is unavailable
+    pub attestation: Vec<crate::io::remote_attestation::EvidenceKind>,
+    pub version: String,
+    /// Null unless the build was given SECURE_SIGNER_GIT_COMMIT
+    pub commit: Option<String>,
+}
+
+/// Saved BLS keys by how they came to be held
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct KeyOriginCounts {
+    pub generated: usize,
+    pub derived: usize,
+    pub imported: usize,
+    /// Keys saved before their origin was recorded
+    pub unknown: usize,
+}
+
+/// The body of every error response. `code` repeats the HTTP status, `details` carries
+/// structured context for the errors that have any, such as the index of a bad signature.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ErrorResponse {
+    pub code: u16,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorResponse {
+    pub fn new(status: axum::http::StatusCode, message: impl Into<String>) -> Self {
+        ErrorResponse {
+            code: status.as_u16(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+}
+
+impl axum::response::IntoResponse for ErrorResponse {
+    fn into_response(self) -> axum::response::Response {
+        let status = axum::http::StatusCode::from_u16(self.code)
+            .unwrap_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        (status, axum::Json(self)).into_response()
+    }
+}
+
+/// Response encoding for the signing endpoint, negotiated from the Accept header as Web3Signer does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureFormat {
+    /// `{"signature": "0x..."}`
+    Json,
+    /// The bare 0x-prefixed signature
+    Text,
+}
+
+impl SignatureFormat {
+    /// Picks the first acceptable media type. A missing or wildcard Accept gets text/plain,
+    /// returns None if nothing offered is supported (406).
+    pub fn from_accept(accept: Option<&str>) -> Option<Self> {
+        let accept = match accept {
+            Some(a) if !a.trim().is_empty() => a,
+            _ => return Some(SignatureFormat::Text),
+        };
+        accept
+            .split(',')
+            .map(|media| {
+                media
+                    .split(';')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .find_map(|media| match media.as_str() {
+                "application/json" | "application/*" => Some(SignatureFormat::Json),
+                "text/plain" | "text/*" | "*/*" => Some(SignatureFormat::Text),
+                _ => None,
+            })
+    }
+}
+
+/// The `details` of the 409 returned when the client's signingRoot differs from the one
+/// computed from the typed data, which usually points at a serialization bug on either side
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SigningRootMismatch {
+    pub provided_signing_root: String,
+    pub computed_signing_root: String,
+}
+
+impl SigningRootMismatch {
+    pub fn new(
+        provided: crate::eth2::eth_types::Root,
+        computed: crate::eth2::eth_types::Root,
+    ) -> Self {
+        SigningRootMismatch {
+            provided_signing_root: format!("0x{}", hex::encode(provided)),
+            computed_signing_root: format!("0x{}", hex::encode(computed)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SignatureResponse {
     pub signature: String,
+    /// Only set for DEPOSIT requests, ready to be passed to the deposit contract
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit_data_root: Option<String>,
+    /// Only set for BLS_TO_EXECUTION_CHANGE requests, ready to be broadcast to a beacon node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_bls_to_execution_change: Option<crate::eth2::eth_types::SignedBLSToExecutionChange>,
 }
 
 impl SignatureResponse {
     pub fn new(sig: &[u8]) -> Self {
         SignatureResponse {
             signature: format!("0x{}", hex::encode(sig)),
+            deposit_data_root: None,
+            signed_bls_to_execution_change: None,
         }
     }
 
+    pub fn with_deposit_data_root(mut self, root: crate::eth2::eth_types::Root) -> Self {
+        self.deposit_data_root = Some(format!("0x{}", hex::encode(root)));
+        self
+    }
+
+    pub fn with_signed_bls_to_execution_change(
+        mut self,
+        message: crate::eth2::eth_types::BLSToExecutionChange,
+    ) -> Result<Self> {
+        let signature = self.to_ssz_bytes()?;
+        self.signed_bls_to_execution_change =
+            Some(crate::eth2::eth_types::SignedBLSToExecutionChange { message, signature });
+        Ok(self)
+    }
+
     pub fn to_ssz_bytes(&self) -> Result<crate::eth2::eth_types::BLSSignature> {
-        let sig_stripped: String = strip_0x_prefix!(self.signature.clone());
-        let sig_bytes = hex::decode(sig_stripped)?;
+        let sig_bytes = crate::parse_hex(&self.signature)?;
         Ok(crate::eth2::eth_types::BLSSignature::from(sig_bytes))
     }
 }
 
+/// A 32B digest for an ETH key to sign, already hashed by the caller
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Secp256k1SignRequest {
+    pub msg_hex: String,
+}
+
+/// A recoverable ECDSA signature. `signature` is `r | s | v` with `v` in {27, 28}.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Secp256k1SignResponse {
+    pub signature: String,
+    pub r: String,
+    pub s: String,
+    pub v: u8,
+}
+
+impl Secp256k1SignResponse {
+    pub fn new(sig: &libsecp256k1::Signature, recovery_id: &libsecp256k1::RecoveryId) -> Self {
+        let sig = sig.serialize();
+        let v = recovery_id.serialize() + 27;
+        Secp256k1SignResponse {
+            signature: format!("0x{}{:02x}", hex::encode(sig), v),
+            r: format!("0x{}", hex::encode(&sig[..32])),
+            s: format!("0x{}", hex::encode(&sig[32..])),
+            v,
+        }
+    }
+}
+
+/// A BLS signature over `BlsAggregateRequest.msg_hex`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlsAggregateRequestInner {
+    pub bls_pk_hex: String,
+    pub signature_hex: String,
+}
+
+/// BLS signatures by different keys over the same hex-encoded message
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlsAggregateRequest {
+    pub msg_hex: String,
+    pub signatures: Vec<BlsAggregateRequestInner>,
+}
+
+/// The aggregate signature, which verifies against the aggregate `pubkey`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlsAggregateResponse {
+    pub signature: String,
+    pub pubkey: String,
+}
+
+/// A BLS signature to check, all fields hex-encoded
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlsVerifyRequest {
+    pub bls_pk_hex: String,
+    pub msg_hex: String,
+    pub signature_hex: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlsVerifyResponse {
+    pub valid: bool,
+}
+
+/// The `details` of the 400 identifying the entry of `BlsAggregateRequest.signatures` that
+/// could not be aggregated
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlsAggregateErrorDetails {
+    pub index: usize,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ValidateCustodyRequest {
@@ -168,7 +1079,7 @@ where
     D: serde::Deserializer<'de>,
 {
     let hex_string: String = Deserialize::deserialize(deserializer)?;
-    let bytes = hex::decode(&hex_string).map_err(serde::de::Error::custom)?;
+    let bytes = crate::parse_hex(&hex_string).map_err(serde::de::Error::custom)?;
 
     let signature =
         libsecp256k1::Signature::parse_standard_slice(&bytes).map_err(serde::de::Error::custom)?;
@@ -193,15 +1104,15 @@ pub struct BlsKeygenPayload {
 
 impl BlsKeygenPayload {
     pub fn public_key_set(&self) -> Result<PublicKeySet> {
-        let sanitized: String = crate::strip_0x_prefix!(&self.bls_pub_key_set);
-        Ok(PublicKeySet::from_bytes(hex::decode(sanitized)?)?)
+        Ok(PublicKeySet::from_bytes(crate::parse_hex(
+            &self.bls_pub_key_set,
+        )?)?)
     }
 
     pub fn withdrawal_credentials(&self) -> Result<[u8; 32]> {
-        let sanitized: String = crate::strip_0x_prefix!(&self.withdrawal_credentials);
         let mut wc: [u8; crate::constants::WITHDRAWAL_CREDENTIALS_BYTES] =
             [0; crate::constants::WITHDRAWAL_CREDENTIALS_BYTES];
-        let wc_bytes = hex::decode(&sanitized)?;
+        let wc_bytes = crate::parse_hex(&self.withdrawal_credentials)?;
         if wc_bytes.len() != crate::constants::WITHDRAWAL_CREDENTIALS_BYTES {
             bail!("Invalid  withdrawal_credentials")
         }
@@ -210,10 +1121,9 @@ impl BlsKeygenPayload {
     }
 
     pub fn signature(&self) -> Result<blsttc::Signature> {
-        let sanitized: String = crate::strip_0x_prefix!(&self.signature);
         let mut sig_bytes: [u8; crate::constants::BLS_SIG_BYTES] =
             [0; crate::constants::BLS_SIG_BYTES];
-        sig_bytes.copy_from_slice(&hex::decode(&sanitized)?);
+        sig_bytes.copy_from_slice(&crate::parse_hex(&self.signature)?);
         Ok(blsttc::Signature::from_bytes(sig_bytes)?)
     }
 
@@ -249,7 +1159,7 @@ impl BlsKeygenPayload {
 
     pub fn verify_public_keys_match(&self) -> Result<bool> {
         let pk_set = self.public_key_set()?;
-        let exp_pk_hex: String = crate::strip_0x_prefix!(&self.bls_pub_key);
+        let exp_pk_hex = crate::normalize_hex(&self.bls_pub_key)?;
         Ok(pk_set.public_key().to_hex() == exp_pk_hex)
     }
 
@@ -258,13 +1168,13 @@ impl BlsKeygenPayload {
         share_index: usize,
         guardian_enclave_sk: &EthSecretKey,
     ) -> Result<blsttc::SecretKeyShare> {
-        let sanitized_enc_sk_share: String = match self.bls_enc_priv_key_shares.get(share_index) {
-            Some(s) => crate::strip_0x_prefix!(s),
+        let enc_sk_bytes = match self.bls_enc_priv_key_shares.get(share_index) {
+            Some(s) => crate::parse_hex(s)?,
             None => bail!("bad share_index to read from bls_enc_priv_key_shares"),
         };
-        let enc_sk_bytes = hex::decode(&sanitized_enc_sk_share)?;
-        let sk_bytes =
-            crate::crypto::eth_keys::envelope_decrypt(&guardian_enclave_sk, &enc_sk_bytes)?;
+        let sk_bytes: crate::crypto::bls_keys::BlsSecretBytes = zeroize::Zeroizing::new(
+            crate::crypto::eth_keys::envelope_decrypt(&guardian_enclave_sk, &enc_sk_bytes)?,
+        );
         Ok(blsttc::SecretKeyShare::from_bytes(
             sk_bytes[..].try_into()?,
         )?)
@@ -282,8 +1192,9 @@ pub struct SignExitRequest {
 
 impl SignExitRequest {
     pub fn public_key_set(&self) -> Result<PublicKeySet> {
-        let sanitized: String = crate::strip_0x_prefix!(&self.bls_pub_key_set);
-        Ok(PublicKeySet::from_bytes(hex::decode(sanitized)?)?)
+        Ok(PublicKeySet::from_bytes(crate::parse_hex(
+            &self.bls_pub_key_set,
+        )?)?)
     }
 }
 
@@ -317,7 +1228,7 @@ where
 {
     let hex_strings: Vec<String> = pubkeys
         .iter()
-        .map(|pubkey| hex::encode(&pubkey.serialize()))
+        .map(|pubkey| crate::to_0x_hex(pubkey.serialize()))
         .collect();
 
     let mut seq = serializer.serialize_seq(Some(hex_strings.len()))?;
@@ -345,8 +1256,7 @@ where
         {
             let mut pubkeys = Vec::new();
             while let Some(hex_string) = seq.next_element::<String>()? {
-                let hex_string: String = strip_0x_prefix!(hex_string);
-                let bytes = hex::decode(&hex_string).map_err(serde::de::Error::custom)?;
+                let bytes = crate::parse_hex(&hex_string).map_err(serde::de::Error::custom)?;
                 let pubkey =
                     EthPublicKey::parse_slice(&bytes, None).map_err(serde::de::Error::custom)?;
                 pubkeys.push(pubkey);
@@ -362,7 +1272,7 @@ fn serialize_as_32_bytes_array<S>(data: &[u8; 32], serializer: S) -> Result<S::O
 where
     S: serde::Serializer,
 {
-    let hex_str = hex::encode(data);
+    let hex_str = crate::to_0x_hex(data);
     serializer.serialize_str(&hex_str)
 }
 
@@ -383,7 +1293,7 @@ where
         where
             E: serde::de::Error,
         {
-            let bytes = hex::decode(value).map_err(E::custom)?;
+            let bytes = crate::parse_hex(value).map_err(E::custom)?;
             if bytes.len() != 32 {
                 return Err(E::custom(format!("Expected 32 bytes, got {}", bytes.len())));
             }
@@ -395,3 +1305,33 @@ where
 
     deserializer.deserialize_str(HexVisitor)
 }
+
+/// A price canonicalized by the datafeed, with `price` an integer scaled by 10^`decimals`. Signed
+/// as the EIP-191 digest of keccak256(`abi.encode(pair, price, decimals, source, timestamp)`), so
+/// no other signed message can pass for a price.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PriceFeed {
+    pub pair: String,
+    /// Decimal string, as the price may not fit in a JSON number
+    pub price: String,
+    pub decimals: u8,
+    /// The host the price was fetched from
+    pub source: String,
+    /// When the price was fetched, in unix seconds
+    pub timestamp: u64,
+}
+
+/// A price and the datafeed key's signature over it
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SignedPriceResponse {
+    pub payload: PriceFeed,
+    /// The 0x-prefixed ABI encoding of `payload`
+    pub encoded: String,
+    /// keccak256("\x19Ethereum Signed Message:\n32" | keccak256(`encoded`)), the digest that was
+    /// signed
+    pub digest: String,
+    /// The compressed ETH public key that signed, and its address
+    pub eth_pk_hex: String,
+    pub address: String,
+    pub signature: Secp256k1SignResponse,
+}
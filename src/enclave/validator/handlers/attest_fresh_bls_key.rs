@@ -1,11 +1,16 @@
-use axum::{response::IntoResponse, Json};
+use axum::{extract::State, response::IntoResponse, Json};
 use log::{error, info};
 
 pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
     Json(keygen_payload): Json<crate::enclave::types::AttestFreshBlsKeyPayload>,
 ) -> axum::response::Response {
     info!("attest_fresh_bls_key()");
+    if let Err(e) = crate::io::key_management::check_bls_key_quota(1) {
+        return crate::enclave::shared::handlers::key_quota_response(&e);
+    }
     match crate::enclave::validator::attest_fresh_bls_key(
+        state.slashing_backend.as_ref(),
         keygen_payload.withdrawal_credentials,
         keygen_payload.guardian_pubkeys,
         keygen_payload.threshold,
@@ -17,7 +22,11 @@ pub async fn handler(
         }
         Err(e) => {
             error!("attest_fresh_bls_key() failed with: {:?}", e);
-            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            crate::enclave::types::ErrorResponse::new(
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("attest_fresh_bls_key failed: {:?}", e),
+            )
+            .into_response()
         }
     }
 }
@@ -49,6 +49,7 @@ impl RecipientKeys {
 }
 
 pub fn attest_fresh_bls_key(
+    backend: &dyn crate::eth2::slash_protection::SlashingProtectionBackend,
     withdrawal_credentials: [u8; 32],
     guardian_public_keys: Vec<EthPublicKey>,
     threshold: usize,
@@ -80,10 +81,20 @@ pub fn attest_fresh_bls_key(
 
     // save validator private key to enclave
     save_bls_key(&secret_key_set)?;
+    crate::io::key_metadata::write_bls_metadata(
+        &validator_pubkey.to_hex(),
+        &crate::io::key_metadata::KeyMetadata::new(
+            crate::io::key_metadata::KeyOrigin::Generated,
+            None,
+        ),
+    )?;
 
     // Create a new slashing protection database
-    crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&validator_pubkey.to_hex())?
-        .write()?;
+    backend.write(
+        &crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(
+            &validator_pubkey.to_hex(),
+        )?,
+    )?;
 
     // sign DepositMessage to deposit 32 ETH to beacon deposit contract
     let (signature, deposit_data_root) = crate::eth2::eth_signing::sign_full_deposit(
@@ -138,10 +149,12 @@ pub fn attest_fresh_bls_key(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::data_dir::ScopedDataDir;
     use ecies::{PublicKey as EthPublicKey, SecretKey as EthSecretKey};
 
     #[test]
     fn test() {
+        let _data_dir = ScopedDataDir::temp("validator_test");
         let n: usize = 4;
         let withdrawal_credentials: [u8; 32] = [0; 32];
 
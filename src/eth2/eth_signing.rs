@@ -1,5 +1,4 @@
 use super::eth_types::*;
-use crate::crypto::bls_keys;
 
 use anyhow::Result;
 use blsttc::SecretKeySet;
@@ -43,11 +42,7 @@ pub fn get_domain(fork_info: ForkInfo, domain_type: DomainType, epoch: Option<Ep
         None => fork_info.fork.epoch,
     };
 
-    let fork_version = if epoch < fork_info.fork.epoch {
-        fork_info.fork.previous_version
-    } else {
-        fork_info.fork.current_version
-    };
+    let fork_version = fork_info.fork.version_at_epoch(epoch);
     compute_domain(
         domain_type,
         Some(fork_version),
@@ -55,6 +50,15 @@ pub fn get_domain(fork_info: ForkInfo, domain_type: DomainType, epoch: Option<Ep
     )
 }
 
+/// Return the signing root of the selection proof for `slot`, which an aggregator signs as
+/// AGGREGATION_SLOT and embeds in its AggregateAndProof.
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#aggregation-selection
+pub fn compute_selection_proof_signing_root(fork_info: ForkInfo, slot: Slot) -> Root {
+    let epoch = compute_epoch_at_slot(slot);
+    let domain = get_domain(fork_info, DOMAIN_SELECTION_PROOF, Some(epoch));
+    compute_signing_root(slot, domain)
+}
+
 /// Return the domain for the ``domain_type`` and ``fork_version``.
 pub fn compute_domain(
     domain_type: DomainType,
@@ -74,7 +78,8 @@ pub fn compute_domain(
     d
 }
 
-/// Reusable signing function that signs SSZ objects by fetching bls sk from memory
+/// Reusable signing function that signs SSZ objects by fetching bls sk from memory. Always signs
+/// under min_pk, as the beacon chain verifies these signatures whichever BLS variant is selected.
 pub fn secure_sign<T: Encode + TreeHash>(
     pk_hex: String,
     msg: T,
@@ -82,9 +87,26 @@ pub fn secure_sign<T: Encode + TreeHash>(
 ) -> Result<BLSSignature> {
     let root: Root = compute_signing_root(msg, domain);
     info!("Computed signingRoot: {:?}", hex::encode(root));
-    let sig = bls_keys::bls_agg_sign_from_saved_sk(&pk_hex, &root)?;
-    info!("Computed signature: {:?}", hex::encode(sig.to_bytes()));
-    Ok(<_>::from(sig.to_bytes().to_vec()))
+    let sig = crate::crypto::bls_variant::sign_from_saved_sk::<crate::crypto::bls_variant::MinPk>(
+        &pk_hex, &root,
+    )?;
+    info!("Computed signature: {:?}", hex::encode(&sig));
+    Ok(<_>::from(sig))
+}
+
+/// Return the root of the DepositData built from the signed ``deposit_message``, as expected
+/// by the deposit contract.
+pub fn compute_deposit_data_root(
+    deposit_message: &DepositMessage,
+    signature: BLSSignature,
+) -> Root {
+    let dd = DepositData {
+        pubkey: deposit_message.pubkey.clone(),
+        withdrawal_credentials: deposit_message.withdrawal_credentials,
+        amount: deposit_message.amount,
+        signature,
+    };
+    dd.tree_hash_root().to_fixed_bytes()
 }
 
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#submit-deposit
@@ -98,15 +120,7 @@ pub fn get_deposit_signature(
     let sig = secure_sign(pk_hex, deposit_message.clone(), domain)?;
 
     let dm_root = deposit_message.tree_hash_root().to_fixed_bytes();
-
-    let dd = DepositData {
-        pubkey: deposit_message.pubkey.clone(),
-        withdrawal_credentials: deposit_message.withdrawal_credentials,
-        amount: deposit_message.amount,
-        signature: sig.clone(),
-    };
-
-    let dd_root = dd.tree_hash_root().to_fixed_bytes();
+    let dd_root = compute_deposit_data_root(&deposit_message, sig.clone());
 
     let dr = DepositResponse {
         pubkey: hex::encode(&deposit_message.pubkey[..]),
@@ -163,6 +177,7 @@ pub enum BLSSignMsg {
     SYNC_COMMITTEE_SELECTION_PROOF(SyncCommitteeSelectionProofRequest),
     SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(SyncCommitteeContributionAndProofRequest),
     VALIDATOR_REGISTRATION(ValidatorRegistrationRequest),
+    BLS_TO_EXECUTION_CHANGE(BLSToExecutionChangeRequest),
 
     // lower case
     block(BlockRequest),
@@ -177,6 +192,7 @@ pub enum BLSSignMsg {
     sync_committee_selection_proof(SyncCommitteeSelectionProofRequest),
     sync_committee_contribution_and_proof(SyncCommitteeContributionAndProofRequest),
     validator_registration(ValidatorRegistrationRequest),
+    bls_to_execution_change(BLSToExecutionChangeRequest),
 }
 
 impl BLSSignMsg {
@@ -194,6 +210,144 @@ impl BLSSignMsg {
         }
     }
 
+    /// The msg's `type`, upper case whichever case the request used
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            BLSSignMsg::BLOCK(_) | BLSSignMsg::block(_) => "BLOCK",
+            BLSSignMsg::BLOCK_V2(_) | BLSSignMsg::block_v2(_) => "BLOCK_V2",
+            BLSSignMsg::ATTESTATION(_) | BLSSignMsg::attestation(_) => "ATTESTATION",
+            BLSSignMsg::RANDAO_REVEAL(_) | BLSSignMsg::randao_reveal(_) => "RANDAO_REVEAL",
+            BLSSignMsg::AGGREGATE_AND_PROOF(_) | BLSSignMsg::aggregate_and_proof(_) => {
+                "AGGREGATE_AND_PROOF"
+            }
+            BLSSignMsg::AGGREGATION_SLOT(_) | BLSSignMsg::aggregation_slot(_) => "AGGREGATION_SLOT",
+            BLSSignMsg::DEPOSIT(_) | BLSSignMsg::deposit(_) => "DEPOSIT",
+            BLSSignMsg::VOLUNTARY_EXIT(_) | BLSSignMsg::voluntary_exit(_) => "VOLUNTARY_EXIT",
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(_) | BLSSignMsg::sync_committee_message(_) => {
+                "SYNC_COMMITTEE_MESSAGE"
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(_)
+            | BLSSignMsg::sync_committee_selection_proof(_) => "SYNC_COMMITTEE_SELECTION_PROOF",
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(_)
+            | BLSSignMsg::sync_committee_contribution_and_proof(_) => {
+                "SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF"
+            }
+            BLSSignMsg::VALIDATOR_REGISTRATION(_) | BLSSignMsg::validator_registration(_) => {
+                "VALIDATOR_REGISTRATION"
+            }
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(_) | BLSSignMsg::bls_to_execution_change(_) => {
+                "BLS_TO_EXECUTION_CHANGE"
+            }
+        }
+    }
+
+    /// The epoch that selects the fork version of the msg's domain, for the types signed under
+    /// the fork_info
+    pub fn domain_epoch(&self) -> Option<Epoch> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => {
+                Some(compute_epoch_at_slot(m.block.slot))
+            }
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => {
+                Some(compute_epoch_at_slot(m.beacon_block.block_header.slot))
+            }
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => {
+                Some(m.attestation.target.epoch)
+            }
+            BLSSignMsg::RANDAO_REVEAL(m) | BLSSignMsg::randao_reveal(m) => {
+                Some(m.randao_reveal.epoch)
+            }
+            BLSSignMsg::AGGREGATE_AND_PROOF(m) | BLSSignMsg::aggregate_and_proof(m) => Some(
+                compute_epoch_at_slot(m.aggregate_and_proof.aggregate.data.slot),
+            ),
+            BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => {
+                Some(compute_epoch_at_slot(m.aggregation_slot.slot))
+            }
+            BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => {
+                Some(m.voluntary_exit.epoch)
+            }
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(m) | BLSSignMsg::sync_committee_message(m) => {
+                Some(compute_epoch_at_slot(m.sync_committee_message.slot))
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => {
+                Some(compute_epoch_at_slot(m.sync_aggregator_selection_data.slot))
+            }
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => Some(compute_epoch_at_slot(
+                m.contribution_and_proof.contribution.slot,
+            )),
+            // Signed over the genesis fork version whatever the fork_info says
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(_)
+            | BLSSignMsg::bls_to_execution_change(_)
+            | BLSSignMsg::DEPOSIT(_)
+            | BLSSignMsg::deposit(_)
+            | BLSSignMsg::VALIDATOR_REGISTRATION(_)
+            | BLSSignMsg::validator_registration(_) => None,
+        }
+    }
+
+    /// The fork_info the msg is signed under, for the types that carry one
+    pub fn fork_info_mut(&mut self) -> Option<&mut ForkInfo> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => Some(&mut m.fork_info),
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => Some(&mut m.fork_info),
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => Some(&mut m.fork_info),
+            BLSSignMsg::RANDAO_REVEAL(m) | BLSSignMsg::randao_reveal(m) => Some(&mut m.fork_info),
+            BLSSignMsg::AGGREGATE_AND_PROOF(m) | BLSSignMsg::aggregate_and_proof(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => Some(&mut m.fork_info),
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(m) | BLSSignMsg::sync_committee_message(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => Some(&mut m.fork_info),
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => Some(&mut m.fork_info),
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                Some(&mut m.fork_info)
+            }
+            // Signed over the genesis fork version alone
+            BLSSignMsg::DEPOSIT(_)
+            | BLSSignMsg::deposit(_)
+            | BLSSignMsg::VALIDATOR_REGISTRATION(_)
+            | BLSSignMsg::validator_registration(_) => None,
+        }
+    }
+
+    /// The optional precomputed signingRoot supplied by the client
+    pub fn provided_signing_root(&self) -> Option<Root> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => m.signingRoot,
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => m.signingRoot,
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => m.signingRoot,
+            BLSSignMsg::RANDAO_REVEAL(m) | BLSSignMsg::randao_reveal(m) => m.signingRoot,
+            BLSSignMsg::AGGREGATE_AND_PROOF(m) | BLSSignMsg::aggregate_and_proof(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => m.signingRoot,
+            BLSSignMsg::DEPOSIT(m) | BLSSignMsg::deposit(m) => m.signingRoot,
+            BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => m.signingRoot,
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(m) | BLSSignMsg::sync_committee_message(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => m.signingRoot,
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => m.signingRoot,
+            BLSSignMsg::VALIDATOR_REGISTRATION(m) | BLSSignMsg::validator_registration(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                m.signingRoot
+            }
+        }
+    }
+
     pub fn to_signing_root(&self, _genesis_fork_version: Option<Version>) -> Root {
         match self {
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#signature
@@ -245,9 +399,7 @@ impl BLSSignMsg {
             }
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#aggregation-selection
             BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => {
-                let epoch = compute_epoch_at_slot(m.aggregation_slot.slot.clone());
-                let domain = get_domain(m.fork_info.clone(), DOMAIN_SELECTION_PROOF, Some(epoch));
-                compute_signing_root(m.aggregation_slot.slot.clone(), domain)
+                compute_selection_proof_signing_root(m.fork_info.clone(), m.aggregation_slot.slot)
             }
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#submit-deposit
             BLSSignMsg::DEPOSIT(m) | BLSSignMsg::deposit(m) => {
@@ -299,6 +451,16 @@ impl BLSSignMsg {
                     compute_domain(DOMAIN_APPLICATION_BUILDER, _genesis_fork_version, None);
                 compute_signing_root(m.validator_registration.clone(), domain)
             }
+            // https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#new-process_bls_to_execution_change
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                // Signed over the genesis fork version so the message stays valid across forks
+                let domain = compute_domain(
+                    DOMAIN_BLS_TO_EXECUTION_CHANGE,
+                    _genesis_fork_version,
+                    Some(m.fork_info.genesis_validators_root),
+                );
+                compute_signing_root(m.bls_to_execution_change.clone(), domain)
+            }
         }
     }
 }
@@ -3,14 +3,11 @@ use serde::de::{self, Deserializer};
 use serde::ser::{self, Serializer};
 use serde::{Deserialize, Serialize};
 use serde_hex::{SerHex, StrictPfx};
-use serde_utils::quoted_u64;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use ssz_types::{typenum, BitList, BitVector, FixedVector, VariableList};
 use tree_hash_derive::TreeHash;
 
-use crate::strip_0x_prefix;
-
 /// Types
 pub type Bytes4 = [u8; 4];
 pub type Bytes32 = [u8; 32];
@@ -60,12 +57,16 @@ pub const DOMAIN_AGGREGATE_AND_PROOF: DomainType = [6_u8, 0_u8, 0_u8, 0_u8]; //
 pub const DOMAIN_SYNC_COMMITTEE: DomainType = [7_u8, 0_u8, 0_u8, 0_u8]; // '0x07000000'
 pub const DOMAIN_SYNC_COMMITTEE_SELECTION_PROOF: DomainType = [8_u8, 0_u8, 0_u8, 0_u8]; // '0x08000000'
 pub const DOMAIN_CONTRIBUTION_AND_PROOF: DomainType = [9_u8, 0_u8, 0_u8, 0_u8]; // '0x09000000'
+pub const DOMAIN_BLS_TO_EXECUTION_CHANGE: DomainType = [10_u8, 0_u8, 0_u8, 0_u8]; // '0x0A000000'
 pub const DOMAIN_APPLICATION_MASK: DomainType = [0_u8, 0_u8, 0_u8, 1_u8]; // '0x00000001'
 pub const DOMAIN_APPLICATION_BUILDER: DomainType = [0_u8, 0_u8, 0_u8, 1_u8]; // '0x00000001'
 
 pub const GENESIS_FORK_VERSION: Version = [0_u8, 0_u8, 0_u8, 0_u8]; // '0x00000000'
 pub const SLOTS_PER_EPOCH: u64 = 32;
 
+/// Fork names accepted in the `version` field of a BLOCK_V2 request
+pub const KNOWN_FORK_NAMES: [&str; 5] = ["PHASE0", "ALTAIR", "BELLATRIX", "CAPELLA", "DENEB"];
+
 // altair
 #[allow(non_camel_case_types)]
 pub type SYNC_COMMITTEE_SIZE = typenum::U512;
@@ -93,20 +94,170 @@ pub type MAX_BLS_TO_EXECUTION_CHANGES = typenum::U16;
 pub type MAX_WITHDRAWALS_PER_PAYLOAD = typenum::U16;
 
 // Custom deserializers
+
+/// Decodes a single hex field, refusing it before decoding if it is longer than any field of
+/// the spec, so one huge string cannot make the enclave allocate its decoded bytes
+fn parse_hex_field<E: de::Error>(hex_str: &str) -> Result<Vec<u8>, E> {
+    let max_len = 2 + 2 * crate::constants::MAX_HEX_FIELD_BYTES;
+    if hex_str.len() > max_len {
+        return Err(E::custom(format!(
+            "Hex string of {} characters exceeds the limit of {max_len}",
+            hex_str.len()
+        )));
+    }
+    crate::parse_hex(hex_str).map_err(|e| E::custom(format!("Not valid hex: {:?}", e)))
+}
+
 pub fn from_hex_to_ssz_type<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
     T: From<Vec<u8>>,
 {
     let hex_str: &str = Deserialize::deserialize(deserializer)?;
-    let hex_str: &str = strip_0x_prefix!(hex_str);
-    let bytes = match hex::decode(hex_str) {
-        Ok(bs) => bs,
-        Err(e) => return Err(de::Error::custom(format!("Not valid hex: {:?}", e))),
-    };
+    let bytes = parse_hex_field(hex_str)?;
     Ok(T::from(bytes))
 }
 
+/// Like `from_hex_to_ssz_type` but decodes via SSZ, so fixed-size types (e.g. a 20-byte
+/// `ExecutionAddress`) reject inputs of the wrong length and lists reject inputs over their
+/// limit, instead of silently padding or truncating them.
+pub fn from_hex_to_ssz_type_checked<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Decode,
+{
+    let hex_str: &str = Deserialize::deserialize(deserializer)?;
+    let bytes = parse_hex_field(hex_str)?;
+    T::from_ssz_bytes(&bytes)
+        .map_err(|e| de::Error::custom(format!("Invalid length for SSZ type: {:?}", e)))
+}
+
+/// Deserializes a list of hex strings (e.g. execution payload `transactions`), enforcing both the
+/// per-item and the list length limits.
+pub fn from_hex_list_to_ssz_list<'de, D, T, N>(
+    deserializer: D,
+) -> Result<VariableList<T, N>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Decode,
+    N: typenum::Unsigned,
+{
+    let hex_strs: Vec<String> = Deserialize::deserialize(deserializer)?;
+    let mut items = Vec::with_capacity(hex_strs.len());
+    for (i, hex_str) in hex_strs.iter().enumerate() {
+        let bytes = crate::parse_hex(hex_str)
+            .map_err(|e| de::Error::custom(format!("Not valid hex at index {}: {:?}", i, e)))?;
+        let item = T::from_ssz_bytes(&bytes).map_err(|e| {
+            de::Error::custom(format!(
+                "Invalid length for SSZ type at index {}: {:?}",
+                i, e
+            ))
+        })?;
+        items.push(item);
+    }
+    VariableList::new(items).map_err(|_| {
+        de::Error::custom(format!(
+            "{} items exceed the SSZ list limit of {}",
+            hex_strs.len(),
+            N::to_usize()
+        ))
+    })
+}
+
+pub fn to_hex_list_from_ssz_list<S, T, N>(
+    data: &VariableList<T, N>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Encode,
+    N: typenum::Unsigned,
+{
+    let hex_strs: Vec<String> = data
+        .iter()
+        .map(|item| "0x".to_string() + &hex::encode(item.as_ssz_bytes()))
+        .collect();
+    hex_strs.serialize(serializer)
+}
+
+/// Deserializes a JSON array into a `VariableList`, refusing it as soon as it grows past the
+/// SSZ limit `N` rather than after buffering the whole array. The error names `field` and the
+/// limit so an oversized request can be told apart from a malformed one.
+fn de_bounded_list<'de, D, T, N>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<VariableList<T, N>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+    N: typenum::Unsigned,
+{
+    struct BoundedListVisitor<T, N> {
+        field: &'static str,
+        _phantom: std::marker::PhantomData<(T, N)>,
+    }
+
+    impl<'de, T, N> de::Visitor<'de> for BoundedListVisitor<T, N>
+    where
+        T: Deserialize<'de>,
+        N: typenum::Unsigned,
+    {
+        type Value = VariableList<T, N>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(
+                f,
+                "a list of at most {} items for {}",
+                N::to_usize(),
+                self.field
+            )
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let limit = N::to_usize();
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(limit));
+            while let Some(item) = seq.next_element()? {
+                if items.len() == limit {
+                    return Err(de::Error::custom(format!(
+                        "{} exceeds its SSZ list limit of {}",
+                        self.field, limit
+                    )));
+                }
+                items.push(item);
+            }
+            VariableList::new(items).map_err(|e| de::Error::custom(format!("{:?}", e)))
+        }
+    }
+
+    deserializer.deserialize_seq(BoundedListVisitor {
+        field,
+        _phantom: std::marker::PhantomData,
+    })
+}
+
+/// Names the field for `de_bounded_list`, as `deserialize_with` only takes a path
+macro_rules! bounded_list_deserializer {
+    ($name:ident, $field:literal) => {
+        pub fn $name<'de, D, T, N>(deserializer: D) -> Result<VariableList<T, N>, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+            N: typenum::Unsigned,
+        {
+            de_bounded_list(deserializer, $field)
+        }
+    };
+}
+
+bounded_list_deserializer!(de_attesting_indices, "attesting_indices");
+bounded_list_deserializer!(de_proposer_slashings, "proposer_slashings");
+bounded_list_deserializer!(de_attester_slashings, "attester_slashings");
+bounded_list_deserializer!(de_attestations, "attestations");
+bounded_list_deserializer!(de_deposits, "deposits");
+bounded_list_deserializer!(de_voluntary_exits, "voluntary_exits");
+bounded_list_deserializer!(de_withdrawals, "withdrawals");
+bounded_list_deserializer!(de_bls_to_execution_changes, "bls_to_execution_changes");
+
 pub fn to_hex_from_ssz_type<S, T>(data: &T, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -122,11 +273,7 @@ where
     T: Decode,
 {
     let hex_str: &str = Deserialize::deserialize(deserializer)?;
-    let hex_str: &str = strip_0x_prefix!(hex_str);
-    let bytes = match hex::decode(hex_str) {
-        Ok(bs) => bs,
-        Err(e) => return Err(de::Error::custom(format!("Not valid hex: {:?}", e))),
-    };
+    let bytes = parse_hex_field(hex_str)?;
     match T::from_ssz_bytes(&bytes.as_ssz_bytes()) {
         Ok(out) => Ok(out),
         Err(e) => {
@@ -150,13 +297,16 @@ where
         None => return Err(de::Error::custom("Invalid decimal string for U256")),
     };
 
-    if bytes.len() != 4 {
+    // `to_u64_digits` drops leading zero limbs, so anything below 2^192 has fewer than 4
+    if bytes.len() > 4 {
         return Err(de::Error::custom(
             "Decimal string doesn't match the required length for U256",
         ));
     }
 
-    let out: U256 = FixedVector::from(bytes);
+    let mut limbs = bytes;
+    limbs.resize(4, 0);
+    let out: U256 = FixedVector::from(limbs);
     Ok(out)
 }
 
@@ -195,10 +345,70 @@ where
         Some(v) => v,
         None => return Err(ser::Error::custom("Can't serialize None")),
     };
-    let hex_string = hex::encode(v);
+    // EIP-3076 and `de_signing_root` both expect the 0x prefix
+    let hex_string = "0x".to_string() + &hex::encode(v);
     serializer.serialize_str(&hex_string)
 }
 
+/// Parses a uint64 quantity given as a decimal or 0x-prefixed hex string
+pub fn parse_quoted_or_hex_u64(s: &str) -> Result<u64, String> {
+    let parsed = match s.strip_prefix("0x") {
+        Some(hex) if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            u64::from_str_radix(hex, 16)
+        }
+        None if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) => s.parse::<u64>(),
+        _ => return Err(format!("Invalid uint64 quantity: {s:?}")),
+    };
+    parsed.map_err(|e| format!("{s:?} does not fit in a u64: {:?}", e))
+}
+
+/// Like `serde_utils::quoted_u64` but also accepts 0x-prefixed hex strings and bare JSON numbers,
+/// so every uint64 field becomes a u64 at deserialization whatever encoding the client used.
+/// Serializes as a quoted decimal, as Web3Signer does.
+pub mod quoted_or_hex_u64 {
+    use serde::de::{self, Deserializer};
+    use serde::Deserialize;
+
+    pub use serde_utils::quoted_u64::serialize;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum QuotedOrNumber {
+        Quoted(String),
+        Number(u64),
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match QuotedOrNumber::deserialize(deserializer)? {
+            QuotedOrNumber::Number(n) => Ok(n),
+            QuotedOrNumber::Quoted(s) => {
+                super::parse_quoted_or_hex_u64(&s).map_err(de::Error::custom)
+            }
+        }
+    }
+}
+
+/// Rejects BLOCK_V2 `version` values that are not a known fork name (case-insensitive)
+pub fn de_fork_name<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let version: String = Deserialize::deserialize(deserializer)?;
+    if !KNOWN_FORK_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(&version))
+    {
+        return Err(de::Error::custom(format!(
+            "Unknown fork version: {}",
+            version
+        )));
+    }
+    Ok(version)
+}
+
 // Datatypes from ETH2 specs
 
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
@@ -215,7 +425,7 @@ pub struct Fork {
     pub previous_version: Version,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub current_version: Version,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub epoch: Epoch,
 }
 
@@ -234,9 +444,284 @@ pub struct ForkInfo {
     pub genesis_validators_root: Root,
 }
 
+impl Fork {
+    /// The fork version a msg at `epoch` is signed under
+    pub fn version_at_epoch(&self, epoch: Epoch) -> Version {
+        if epoch < self.epoch {
+            self.previous_version
+        } else {
+            self.current_version
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ScheduledFork {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub version: Version,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub epoch: Epoch,
+}
+
+/// The fork versions of a network and the epochs they activate at, starting with the genesis
+/// fork at epoch 0
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ForkSchedule {
+    pub forks: Vec<ScheduledFork>,
+}
+
+impl ForkSchedule {
+    /// Phase0, Altair, Bellatrix, Capella and Deneb of a known network
+    pub fn preset(network: &str) -> Option<Self> {
+        let forks: [(Version, Epoch); 5] = match network {
+            "mainnet" => [
+                ([0, 0, 0, 0], 0),
+                ([1, 0, 0, 0], 74240),
+                ([2, 0, 0, 0], 144896),
+                ([3, 0, 0, 0], 194048),
+                ([4, 0, 0, 0], 269568),
+            ],
+            "goerli" => [
+                ([0, 0, 0x10, 0x20], 0),
+                ([1, 0, 0x10, 0x20], 36660),
+                ([2, 0, 0x10, 0x20], 112260),
+                ([3, 0, 0x10, 0x20], 162304),
+                ([4, 0, 0x10, 0x20], 231680),
+            ],
+            "sepolia" => [
+                ([0x90, 0, 0, 0x69], 0),
+                ([0x90, 0, 0, 0x70], 50),
+                ([0x90, 0, 0, 0x71], 100),
+                ([0x90, 0, 0, 0x72], 56832),
+                ([0x90, 0, 0, 0x73], 132608),
+            ],
+            "holesky" => [
+                ([1, 1, 0x70, 0], 0),
+                ([2, 1, 0x70, 0], 0),
+                ([3, 1, 0x70, 0], 0),
+                ([4, 1, 0x70, 0], 256),
+                ([5, 1, 0x70, 0], 29696),
+            ],
+            _ => return None,
+        };
+        Some(ForkSchedule {
+            forks: forks
+                .iter()
+                .map(|(version, epoch)| ScheduledFork {
+                    version: *version,
+                    epoch: *epoch,
+                })
+                .collect(),
+        })
+    }
+
+    /// Loads a preset by network name, or else a JSON schedule from the file at `spec`
+    pub fn load(spec: &str) -> anyhow::Result<Self> {
+        if let Some(schedule) = Self::preset(spec) {
+            return Ok(schedule);
+        }
+        let json = std::fs::read(spec).map_err(|e| {
+            anyhow::anyhow!("Unknown network or unreadable fork schedule {spec}: {e}")
+        })?;
+        let schedule: ForkSchedule = serde_json::from_slice(&json)?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        match self.forks.first() {
+            Some(genesis) if genesis.epoch == 0 => {}
+            _ => anyhow::bail!("Fork schedule must start with the genesis fork at epoch 0"),
+        }
+        if self.forks.windows(2).any(|w| w[1].epoch < w[0].epoch) {
+            anyhow::bail!("Fork schedule epochs must be non-decreasing");
+        }
+        Ok(())
+    }
+
+    pub fn genesis_fork_version(&self) -> Version {
+        self.forks[0].version
+    }
+
+    /// The fork active at `epoch`, as it would be reported in a fork_info
+    pub fn fork_at_epoch(&self, epoch: Epoch) -> Fork {
+        let active = self
+            .forks
+            .iter()
+            .rposition(|f| f.epoch <= epoch)
+            .unwrap_or_default();
+        Fork {
+            previous_version: self.forks[active.saturating_sub(1)].version,
+            current_version: self.forks[active].version,
+            epoch: self.forks[active].epoch,
+        }
+    }
+
+    pub fn fork_version_at_epoch(&self, epoch: Epoch) -> Version {
+        self.fork_at_epoch(epoch).current_version
+    }
+}
+
+/// The chain parameters Secure-Signer needs to know about a network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkPreset {
+    pub name: String,
+    pub genesis_fork_version: Version,
+    pub genesis_validators_root: Root,
+    pub fork_schedule: ForkSchedule,
+    /// Unix time of slot 0
+    pub genesis_time: u64,
+    pub seconds_per_slot: u64,
+}
+
+/// The subset of a consensus-layer config.yaml describing a network. GENESIS_TIME and
+/// GENESIS_VALIDATORS_ROOT are not spec config keys, but are read when present since the
+/// config alone does not determine them.
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct ConsensusConfig {
+    CONFIG_NAME: Option<String>,
+    MIN_GENESIS_TIME: u64,
+    GENESIS_DELAY: u64,
+    GENESIS_FORK_VERSION: String,
+    ALTAIR_FORK_VERSION: Option<String>,
+    ALTAIR_FORK_EPOCH: Option<Epoch>,
+    BELLATRIX_FORK_VERSION: Option<String>,
+    BELLATRIX_FORK_EPOCH: Option<Epoch>,
+    CAPELLA_FORK_VERSION: Option<String>,
+    CAPELLA_FORK_EPOCH: Option<Epoch>,
+    DENEB_FORK_VERSION: Option<String>,
+    DENEB_FORK_EPOCH: Option<Epoch>,
+    SECONDS_PER_SLOT: u64,
+    GENESIS_TIME: Option<u64>,
+    GENESIS_VALIDATORS_ROOT: Option<String>,
+}
+
+fn parse_hex_bytes<const N: usize>(name: &str, hex_str: &str) -> anyhow::Result<[u8; N]> {
+    crate::parse_hex(hex_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("{name} must be {N} hex encoded bytes, got {hex_str}"))
+}
+
+impl NetworkPreset {
+    pub fn preset(network: &str) -> Option<Self> {
+        let (genesis_validators_root, genesis_time) = match network {
+            "mainnet" => (
+                "4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95",
+                1606824023,
+            ),
+            "goerli" => (
+                "043db0d9a83813551ee2f33450d23797757d430911a9320530ad8a0eabc43efb",
+                1616508000,
+            ),
+            "sepolia" => (
+                "d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078",
+                1655733600,
+            ),
+            "holesky" => (
+                "9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1",
+                1695902400,
+            ),
+            _ => return None,
+        };
+        let fork_schedule = ForkSchedule::preset(network)?;
+        Some(NetworkPreset {
+            name: network.to_string(),
+            genesis_fork_version: fork_schedule.genesis_fork_version(),
+            genesis_validators_root: parse_hex_bytes(
+                "genesis_validators_root",
+                genesis_validators_root,
+            )
+            .ok()?,
+            fork_schedule,
+            genesis_time,
+            seconds_per_slot: 12,
+        })
+    }
+
+    /// Loads a preset by network name, or for `custom` the consensus-layer config.yaml at
+    /// `config_path`
+    pub fn load(network: &str, config_path: Option<&str>) -> anyhow::Result<Self> {
+        match (network, config_path) {
+            ("custom", Some(path)) => Self::from_config_yaml(path),
+            ("custom", None) => anyhow::bail!("A custom network needs a config.yaml"),
+            (name, _) => Self::preset(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown network {name}, expected mainnet, goerli, sepolia, holesky or custom"
+                )
+            }),
+        }
+    }
+
+    fn from_config_yaml(path: &str) -> anyhow::Result<Self> {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Unreadable network config {path}: {e}"))?;
+        let config: ConsensusConfig = serde_yaml::from_str(&yaml)?;
+
+        let mut forks = vec![ScheduledFork {
+            version: parse_hex_bytes("GENESIS_FORK_VERSION", &config.GENESIS_FORK_VERSION)?,
+            epoch: 0,
+        }];
+        for (name, version, epoch) in [
+            (
+                "ALTAIR",
+                &config.ALTAIR_FORK_VERSION,
+                config.ALTAIR_FORK_EPOCH,
+            ),
+            (
+                "BELLATRIX",
+                &config.BELLATRIX_FORK_VERSION,
+                config.BELLATRIX_FORK_EPOCH,
+            ),
+            (
+                "CAPELLA",
+                &config.CAPELLA_FORK_VERSION,
+                config.CAPELLA_FORK_EPOCH,
+            ),
+            ("DENEB", &config.DENEB_FORK_VERSION, config.DENEB_FORK_EPOCH),
+        ] {
+            if let (Some(version), Some(epoch)) = (version, epoch) {
+                forks.push(ScheduledFork {
+                    version: parse_hex_bytes(&format!("{name}_FORK_VERSION"), version)?,
+                    epoch,
+                });
+            }
+        }
+        let fork_schedule = ForkSchedule { forks };
+        fork_schedule.validate()?;
+
+        let genesis_validators_root = match &config.GENESIS_VALIDATORS_ROOT {
+            Some(root) => parse_hex_bytes("GENESIS_VALIDATORS_ROOT", root)?,
+            None => Root::default(),
+        };
+        Ok(NetworkPreset {
+            name: config.CONFIG_NAME.unwrap_or("custom".to_string()),
+            genesis_fork_version: fork_schedule.genesis_fork_version(),
+            genesis_validators_root,
+            fork_schedule,
+            genesis_time: config
+                .GENESIS_TIME
+                .unwrap_or(config.MIN_GENESIS_TIME.saturating_add(config.GENESIS_DELAY)),
+            seconds_per_slot: config.SECONDS_PER_SLOT,
+        })
+    }
+}
+
+impl ForkInfo {
+    /// True for the all-zero fork_info of a request that omitted it. No live chain has a zero
+    /// genesis_validators_root.
+    pub fn is_unset(&self) -> bool {
+        self.genesis_validators_root == Root::default()
+            && self.fork.previous_version == Version::default()
+            && self.fork.current_version == Version::default()
+            && self.fork.epoch == 0
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 pub struct Checkpoint {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub epoch: Epoch,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub root: Root,
@@ -245,7 +730,7 @@ pub struct Checkpoint {
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 /// used by Web3Signer type = "RANDAO_REVEAL"
 pub struct RandaoReveal {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub epoch: Epoch,
 }
 
@@ -253,9 +738,9 @@ pub struct RandaoReveal {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#attestationdata
 /// used by Web3Signer type = "ATTESTATION"
 pub struct AttestationData {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub index: CommitteeIndex,
     // LMD GHOST vote
     #[serde(with = "SerHex::<StrictPfx>")]
@@ -269,9 +754,9 @@ pub struct AttestationData {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconblockheader
 /// used by Web3Signer type = "BLOCK_V2"
 pub struct BeaconBlockHeader {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub proposer_index: ValidatorIndex,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub parent_root: Root,
@@ -299,6 +784,7 @@ pub struct ProposerSlashing {
 
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 pub struct IndexedAttestation {
+    #[serde(deserialize_with = "de_attesting_indices")]
     pub attesting_indices: VariableList<ValidatorIndex, MAX_VALIDATORS_PER_COMMITTEE>,
     pub data: AttestationData,
     #[serde(
@@ -318,7 +804,7 @@ pub struct AttesterSlashing {
 pub struct Eth1Data {
     #[serde(with = "SerHex::<StrictPfx>")]
     pub deposit_root: Root,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub deposit_count: u64,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub block_hash: Hash32,
@@ -335,7 +821,7 @@ pub struct DepositMessage {
     pub pubkey: BLSPubkey,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub withdrawal_credentials: Bytes32,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub amount: Gwei,
 }
 
@@ -350,7 +836,7 @@ pub struct DepositData {
     pub pubkey: BLSPubkey,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub withdrawal_credentials: Bytes32,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub amount: Gwei,
     #[serde(
         deserialize_with = "from_hex_to_ssz_type",
@@ -369,9 +855,9 @@ pub struct Deposit {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#voluntaryexit
 /// used by Web3Signer type = "VOLUNTARY_EXIT"
 pub struct VoluntaryExit {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub epoch: Epoch, // Earliest epoch when voluntary exit can be processed
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub validator_index: ValidatorIndex,
 }
 
@@ -389,15 +875,15 @@ pub struct SignedVoluntaryExit {
 /// https://github.com/ethereum/builder-specs/blob/main/specs/bellatrix/builder.md#validatorregistrationv1
 /// used by Web3Signer type = "VALIDATOR_REGISTRATION"
 pub struct ValidatorRegistration {
-    #[serde(deserialize_with = "from_hex_to_ssz_type")]
+    #[serde(deserialize_with = "from_hex_to_ssz_type_checked")]
     #[serde(serialize_with = "to_hex_from_ssz_type")]
     pub fee_recipient: ExecutionAddress,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub gas_limit: u64,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub timestamp: u64,
     #[serde(
-        deserialize_with = "from_hex_to_ssz_type",
+        deserialize_with = "from_hex_to_ssz_type_checked",
         serialize_with = "to_hex_from_ssz_type"
     )]
     pub pubkey: BLSPubkey,
@@ -443,10 +929,15 @@ pub struct BeaconBlockBody {
     #[serde(with = "SerHex::<StrictPfx>")]
     pub graffiti: Bytes32, // Arbitrary data
     // Operations,
+    #[serde(deserialize_with = "de_proposer_slashings")]
     pub proposer_slashings: VariableList<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attester_slashings")]
     pub attester_slashings: VariableList<AttesterSlashing, MAX_ATTESTER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attestations")]
     pub attestations: VariableList<Attestation, MAX_ATTESTATIONS>,
+    #[serde(deserialize_with = "de_deposits")]
     pub deposits: VariableList<Deposit, MAX_DEPOSITS>,
+    #[serde(deserialize_with = "de_voluntary_exits")]
     pub voluntary_exits: VariableList<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
     pub sync_aggregate: SyncAggregate, // # [New in Altair]
 
@@ -454,6 +945,7 @@ pub struct BeaconBlockBody {
     pub execution_payload: ExecutionPayload, //   # [New in Bellatrix]
 
     // https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#beaconblockbody
+    #[serde(deserialize_with = "de_bls_to_execution_changes")]
     pub bls_to_execution_changes:
         VariableList<SignedBLSToExecutionChange, MAX_BLS_TO_EXECUTION_CHANGES>, // [New in Capella]
 }
@@ -479,16 +971,16 @@ pub struct ExecutionPayload {
     pub logs_bloom: FixedVector<u8, BYTES_PER_LOGS_BLOOM>,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub prev_randao: Root, // 'difficulty' in the yellow paper
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub block_number: u64, // 'number' in the yellow paper
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub gas_limit: u64,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub gas_used: u64,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub timestamp: u64,
     #[serde(
-        deserialize_with = "from_hex_to_ssz_type",
+        deserialize_with = "from_hex_to_ssz_type_checked",
         serialize_with = "to_hex_from_ssz_type"
     )]
     pub extra_data: VariableList<u8, MAX_EXTRA_DATA_BYTES>,
@@ -500,21 +992,219 @@ pub struct ExecutionPayload {
     // Extra payload fields,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub block_hash: Root, // Hash of execution block
+    #[serde(
+        deserialize_with = "from_hex_list_to_ssz_list",
+        serialize_with = "to_hex_list_from_ssz_list"
+    )]
     pub transactions: VariableList<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD>,
+    #[serde(deserialize_with = "de_withdrawals")]
     pub withdrawals: VariableList<Withdrawal, MAX_WITHDRAWALS_PER_PAYLOAD>, // [New in Capella]
 }
 
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/bellatrix/beacon-chain.md#executionpayload
+pub struct ExecutionPayloadBellatrix {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_hash: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub fee_recipient: ExecutionAddress,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub receipts_root: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub logs_bloom: FixedVector<u8, BYTES_PER_LOGS_BLOOM>,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub prev_randao: Root,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub block_number: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub gas_used: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub timestamp: u64,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub extra_data: VariableList<u8, MAX_EXTRA_DATA_BYTES>,
+    #[serde(
+        deserialize_with = "from_u256_string",
+        serialize_with = "to_u256_string"
+    )]
+    pub base_fee_per_gas: U256,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub block_hash: Root,
+    #[serde(
+        deserialize_with = "from_hex_list_to_ssz_list",
+        serialize_with = "to_hex_list_from_ssz_list"
+    )]
+    pub transactions: VariableList<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/bellatrix/beacon-chain.md#executionpayloadheader
+pub struct ExecutionPayloadHeaderBellatrix {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_hash: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub fee_recipient: ExecutionAddress,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub receipts_root: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub logs_bloom: FixedVector<u8, BYTES_PER_LOGS_BLOOM>,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub prev_randao: Root,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub block_number: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub gas_used: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub timestamp: u64,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub extra_data: VariableList<u8, MAX_EXTRA_DATA_BYTES>,
+    #[serde(
+        deserialize_with = "from_u256_string",
+        serialize_with = "to_u256_string"
+    )]
+    pub base_fee_per_gas: U256,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub block_hash: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub transactions_root: Root,
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#executionpayloadheader
+pub struct ExecutionPayloadHeader {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_hash: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub fee_recipient: ExecutionAddress,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub receipts_root: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub logs_bloom: FixedVector<u8, BYTES_PER_LOGS_BLOOM>,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub prev_randao: Root,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub block_number: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub gas_used: u64,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub timestamp: u64,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type_checked",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub extra_data: VariableList<u8, MAX_EXTRA_DATA_BYTES>,
+    #[serde(
+        deserialize_with = "from_u256_string",
+        serialize_with = "to_u256_string"
+    )]
+    pub base_fee_per_gas: U256,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub block_hash: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub transactions_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub withdrawals_root: Root, // [New in Capella]
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/bellatrix/beacon-chain.md#beaconblockbody
+pub struct BeaconBlockBodyBellatrix {
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub randao_reveal: BLSSignature,
+    pub eth1_data: Eth1Data,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub graffiti: Bytes32,
+    #[serde(deserialize_with = "de_proposer_slashings")]
+    pub proposer_slashings: VariableList<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attester_slashings")]
+    pub attester_slashings: VariableList<AttesterSlashing, MAX_ATTESTER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attestations")]
+    pub attestations: VariableList<Attestation, MAX_ATTESTATIONS>,
+    #[serde(deserialize_with = "de_deposits")]
+    pub deposits: VariableList<Deposit, MAX_DEPOSITS>,
+    #[serde(deserialize_with = "de_voluntary_exits")]
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
+    pub sync_aggregate: SyncAggregate,
+    pub execution_payload: ExecutionPayloadBellatrix, // [New in Bellatrix]
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/builder-specs/blob/main/specs/bellatrix/builder.md#blindedbeaconblockbody
+/// Merkleizes to the same root as the `BeaconBlockBodyBellatrix` it was blinded from.
+pub struct BlindedBeaconBlockBodyBellatrix {
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub randao_reveal: BLSSignature,
+    pub eth1_data: Eth1Data,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub graffiti: Bytes32,
+    #[serde(deserialize_with = "de_proposer_slashings")]
+    pub proposer_slashings: VariableList<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attester_slashings")]
+    pub attester_slashings: VariableList<AttesterSlashing, MAX_ATTESTER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attestations")]
+    pub attestations: VariableList<Attestation, MAX_ATTESTATIONS>,
+    #[serde(deserialize_with = "de_deposits")]
+    pub deposits: VariableList<Deposit, MAX_DEPOSITS>,
+    #[serde(deserialize_with = "de_voluntary_exits")]
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
+    pub sync_aggregate: SyncAggregate,
+    pub execution_payload_header: ExecutionPayloadHeaderBellatrix,
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#blstoexecutionchange
+/// used by type = "BLS_TO_EXECUTION_CHANGE"
 pub struct BLSToExecutionChange {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub validator_index: ValidatorIndex,
     #[serde(
-        deserialize_with = "from_hex_to_ssz_type",
+        deserialize_with = "from_hex_to_ssz_type_checked",
         serialize_with = "to_hex_from_ssz_type"
     )]
     pub from_bls_pubkey: BLSPubkey,
     #[serde(
-        deserialize_with = "from_hex_to_ssz_type",
+        deserialize_with = "from_hex_to_ssz_type_checked",
         serialize_with = "to_hex_from_ssz_type"
     )]
     pub to_execution_address: ExecutionAddress,
@@ -534,9 +1224,9 @@ pub struct SignedBLSToExecutionChange {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconblock
 /// used by Web3Signer type = "BLOCK" for phase 0 backward compatibility.
 pub struct BeaconBlock {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub proposer_index: ValidatorIndex,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub parent_root: Root,
@@ -545,19 +1235,202 @@ pub struct BeaconBlock {
     pub body: BeaconBlockBody,
 }
 
+/// Builds the header of a block from its fields. The header shares the block's
+/// hash_tree_root, so signing either is equivalent.
+fn block_header<B: tree_hash::TreeHash>(
+    slot: Slot,
+    proposer_index: ValidatorIndex,
+    parent_root: Root,
+    state_root: Root,
+    body: &B,
+) -> BeaconBlockHeader {
+    BeaconBlockHeader {
+        slot,
+        proposer_index,
+        parent_root,
+        state_root,
+        body_root: body.tree_hash_root().to_fixed_bytes(),
+    }
+}
+
+impl BeaconBlock {
+    pub fn to_header(&self) -> BeaconBlockHeader {
+        block_header(
+            self.slot,
+            self.proposer_index,
+            self.parent_root,
+            self.state_root,
+            &self.body,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconblockbody
+pub struct BeaconBlockBodyPhase0 {
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub randao_reveal: BLSSignature,
+    pub eth1_data: Eth1Data,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub graffiti: Bytes32,
+    #[serde(deserialize_with = "de_proposer_slashings")]
+    pub proposer_slashings: VariableList<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attester_slashings")]
+    pub attester_slashings: VariableList<AttesterSlashing, MAX_ATTESTER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attestations")]
+    pub attestations: VariableList<Attestation, MAX_ATTESTATIONS>,
+    #[serde(deserialize_with = "de_deposits")]
+    pub deposits: VariableList<Deposit, MAX_DEPOSITS>,
+    #[serde(deserialize_with = "de_voluntary_exits")]
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconblock
+/// used by Web3Signer type = "BLOCK_V2" with version = "PHASE0".
+pub struct BeaconBlockPhase0 {
+    #[serde(with = "quoted_or_hex_u64")]
+    pub slot: Slot,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub proposer_index: ValidatorIndex,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    pub body: BeaconBlockBodyPhase0,
+}
+
+impl BeaconBlockPhase0 {
+    pub fn to_header(&self) -> BeaconBlockHeader {
+        block_header(
+            self.slot,
+            self.proposer_index,
+            self.parent_root,
+            self.state_root,
+            &self.body,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/beacon-chain.md#beaconblockbody
+pub struct BeaconBlockBodyAltair {
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub randao_reveal: BLSSignature,
+    pub eth1_data: Eth1Data,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub graffiti: Bytes32,
+    #[serde(deserialize_with = "de_proposer_slashings")]
+    pub proposer_slashings: VariableList<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attester_slashings")]
+    pub attester_slashings: VariableList<AttesterSlashing, MAX_ATTESTER_SLASHINGS>,
+    #[serde(deserialize_with = "de_attestations")]
+    pub attestations: VariableList<Attestation, MAX_ATTESTATIONS>,
+    #[serde(deserialize_with = "de_deposits")]
+    pub deposits: VariableList<Deposit, MAX_DEPOSITS>,
+    #[serde(deserialize_with = "de_voluntary_exits")]
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
+    pub sync_aggregate: SyncAggregate, // # [New in Altair]
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconblock
+/// with the Altair body, used by Web3Signer type = "BLOCK_V2" with version = "ALTAIR".
+pub struct BeaconBlockAltair {
+    #[serde(with = "quoted_or_hex_u64")]
+    pub slot: Slot,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub proposer_index: ValidatorIndex,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    pub body: BeaconBlockBodyAltair,
+}
+
+impl BeaconBlockAltair {
+    pub fn to_header(&self) -> BeaconBlockHeader {
+        block_header(
+            self.slot,
+            self.proposer_index,
+            self.parent_root,
+            self.state_root,
+            &self.body,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// Used by Web3Signer type = "BLOCK_V2" with version = "BELLATRIX".
+pub struct BeaconBlockBellatrix {
+    #[serde(with = "quoted_or_hex_u64")]
+    pub slot: Slot,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub proposer_index: ValidatorIndex,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    pub body: BeaconBlockBodyBellatrix,
+}
+
+impl BeaconBlockBellatrix {
+    pub fn to_header(&self) -> BeaconBlockHeader {
+        block_header(
+            self.slot,
+            self.proposer_index,
+            self.parent_root,
+            self.state_root,
+            &self.body,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// A Bellatrix block carrying only the execution payload header (builder API).
+pub struct BlindedBeaconBlockBellatrix {
+    #[serde(with = "quoted_or_hex_u64")]
+    pub slot: Slot,
+    #[serde(with = "quoted_or_hex_u64")]
+    pub proposer_index: ValidatorIndex,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    pub body: BlindedBeaconBlockBodyBellatrix,
+}
+
+impl BlindedBeaconBlockBellatrix {
+    pub fn to_header(&self) -> BeaconBlockHeader {
+        block_header(
+            self.slot,
+            self.proposer_index,
+            self.parent_root,
+            self.state_root,
+            &self.body,
+        )
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#withdrawal
 pub struct Withdrawal {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub index: WithdrawalIndex,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub validator_index: ValidatorIndex,
     #[serde(
         deserialize_with = "from_hex_to_ssz_type",
         serialize_with = "to_hex_from_ssz_type"
     )]
     pub address: ExecutionAddress,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub amount: Gwei,
 }
 
@@ -565,7 +1438,7 @@ pub struct Withdrawal {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#aggregateandproof
 /// used by Web3Signer type = "AGGREGATE_AND_PROOF"
 pub struct AggregateAndProof {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub aggregator_index: ValidatorIndex,
     pub aggregate: Attestation,
     #[serde(
@@ -580,13 +1453,13 @@ pub struct AggregateAndProof {
 /// used by Web3Signer type = "SYNC_COMMITTEE_MESSAGE"
 pub struct SyncCommitteeMessage {
     // Slot to which this contribution pertains
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
     // Block root for this signature
     #[serde(with = "SerHex::<StrictPfx>")]
     pub beacon_block_root: Root,
     // Index of the validator that produced this signature
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub validator_index: ValidatorIndex,
     // Signature by the validator over the block root of `slot`
     #[serde(
@@ -601,13 +1474,13 @@ pub struct SyncCommitteeMessage {
 /// used by Web3Signer type = "SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF"
 pub struct SyncCommitteeContribution {
     // Slot to which this contribution pertains
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
     // Block root for this contribution
     #[serde(with = "SerHex::<StrictPfx>")]
     pub beacon_block_root: Root,
     // The subcommittee this contribution pertains to out of the broader sync committee
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub subcommittee_index: u64,
     // A bit is set if a signature from the validator at the corresponding
     // index in the subcommittee is present in the aggregate `signature`.
@@ -628,7 +1501,7 @@ pub struct SyncCommitteeContribution {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/validator.md#contributionandproof
 /// used by Web3Signer type = "SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF"
 pub struct ContributionAndProof {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub aggregator_index: ValidatorIndex,
     pub contribution: SyncCommitteeContribution,
     #[serde(
@@ -642,22 +1515,23 @@ pub struct ContributionAndProof {
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/validator.md#syncaggregatorselectiondata
 /// used by Web3Signer type = "SYNC_COMMITTEE_SELECTION_PROOF"
 pub struct SyncAggregatorSelectionData {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub subcommittee_index: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone, Default)]
 /// used by Web3Signer type = "AGGREGATION_SLOT"
 pub struct AggregationSlot {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct BlockRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -670,6 +1544,7 @@ pub struct BlockRequest {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct BlockV2Request {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -679,15 +1554,81 @@ pub struct BlockV2Request {
     pub beacon_block: BlockV2RequestWrapper,
 }
 
+/// Clients send either the `block_header` (e.g. blinded blocks) or the full `block`. A full
+/// block is reduced to its header at deserialization, which has the same signing root.
 #[derive(Deserialize, Serialize, Debug)]
+#[serde(try_from = "BlockV2RequestWrapperRaw")]
 pub struct BlockV2RequestWrapper {
     pub version: String,
     pub block_header: BeaconBlockHeader,
+    /// The body's graffiti, only known when the full `block` was sent
+    #[serde(skip)]
+    pub graffiti: Option<Bytes32>,
+}
+
+#[derive(Deserialize)]
+struct BlockV2RequestWrapperRaw {
+    #[serde(deserialize_with = "de_fork_name")]
+    version: String,
+    #[serde(default)]
+    block_header: Option<BeaconBlockHeader>,
+    #[serde(default)]
+    block: Option<serde_json::Value>,
+}
+
+impl TryFrom<BlockV2RequestWrapperRaw> for BlockV2RequestWrapper {
+    type Error = String;
+
+    fn try_from(raw: BlockV2RequestWrapperRaw) -> Result<Self, Self::Error> {
+        let (block_header, graffiti) = match (raw.block_header, raw.block) {
+            (Some(header), None) => (header, None),
+            (None, Some(block)) => match raw.version.to_ascii_uppercase().as_str() {
+                "PHASE0" => {
+                    let block = serde_json::from_value::<BeaconBlockPhase0>(block)
+                        .map_err(|e| format!("Invalid PHASE0 block: {:?}", e))?;
+                    (block.to_header(), Some(block.body.graffiti))
+                }
+                "ALTAIR" => {
+                    let block = serde_json::from_value::<BeaconBlockAltair>(block)
+                        .map_err(|e| format!("Invalid ALTAIR block: {:?}", e))?;
+                    (block.to_header(), Some(block.body.graffiti))
+                }
+                "BELLATRIX" if block["body"].get("execution_payload_header").is_some() => {
+                    let block = serde_json::from_value::<BlindedBeaconBlockBellatrix>(block)
+                        .map_err(|e| format!("Invalid blinded BELLATRIX block: {:?}", e))?;
+                    (block.to_header(), Some(block.body.graffiti))
+                }
+                "BELLATRIX" => {
+                    let block = serde_json::from_value::<BeaconBlockBellatrix>(block)
+                        .map_err(|e| format!("Invalid BELLATRIX block: {:?}", e))?;
+                    (block.to_header(), Some(block.body.graffiti))
+                }
+                "CAPELLA" => {
+                    let block = serde_json::from_value::<BeaconBlock>(block)
+                        .map_err(|e| format!("Invalid CAPELLA block: {:?}", e))?;
+                    (block.to_header(), Some(block.body.graffiti))
+                }
+                _ => {
+                    return Err(format!(
+                        "Full {} blocks are not supported, send block_header instead",
+                        raw.version
+                    ))
+                }
+            },
+            _ => return Err("Expected exactly one of block or block_header".into()),
+        };
+        Ok(BlockV2RequestWrapper {
+            version: raw.version,
+            block_header,
+            graffiti,
+        })
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct AttestationRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -700,6 +1641,7 @@ pub struct AttestationRequest {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct RandaoRevealRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -712,6 +1654,7 @@ pub struct RandaoRevealRequest {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct AggregateAndProofRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -724,6 +1667,7 @@ pub struct AggregateAndProofRequest {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct AggregationSlotRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -760,6 +1704,7 @@ pub struct DepositResponse {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct VoluntaryExitRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -772,6 +1717,7 @@ pub struct VoluntaryExitRequest {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct SyncCommitteeMessageRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -789,7 +1735,7 @@ pub struct SyncCommitteeMessageRequest {
 /// We are following this convention for compatibility.
 pub struct SyncCommitteeMessageRequestWrapper {
     // Slot to which this contribution pertains
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
     // Block root for this signature
     #[serde(with = "SerHex::<StrictPfx>")]
@@ -799,6 +1745,7 @@ pub struct SyncCommitteeMessageRequestWrapper {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct SyncCommitteeSelectionProofRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -811,6 +1758,7 @@ pub struct SyncCommitteeSelectionProofRequest {
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct SyncCommitteeContributionAndProofRequest {
+    #[serde(default)]
     pub fork_info: ForkInfo,
     #[serde(default)]
     #[serde(deserialize_with = "de_signing_root")]
@@ -831,11 +1779,99 @@ pub struct ValidatorRegistrationRequest {
     pub validator_registration: ValidatorRegistration,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[allow(non_snake_case)]
+pub struct BLSToExecutionChangeRequest {
+    /// Only the genesis_validators_root is used, the domain is always computed with the
+    /// genesis fork version
+    #[serde(default)]
+    pub fork_info: ForkInfo,
+    #[serde(default)]
+    #[serde(deserialize_with = "de_signing_root")]
+    #[serde(serialize_with = "se_signing_root")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signingRoot: Option<Root>,
+    pub bls_to_execution_change: BLSToExecutionChange,
+}
+
 #[cfg(test)]
 mod serialization_tests {
     use super::*;
     use anyhow::Result;
 
+    #[test]
+    fn test_quoted_or_hex_u64() {
+        #[derive(Deserialize, Serialize)]
+        struct S {
+            #[serde(with = "quoted_or_hex_u64")]
+            slot: Slot,
+        }
+        let parse =
+            |v: &str| serde_json::from_str::<S>(&format!(r#"{{"slot": {v}}}"#)).map(|s| s.slot);
+        assert_eq!(parse(r#""256""#).unwrap(), 256);
+        assert_eq!(parse(r#""0x100""#).unwrap(), 256);
+        assert_eq!(parse(r#""0xff""#).unwrap(), 255);
+        assert_eq!(parse("256").unwrap(), 256);
+        assert_eq!(parse(r#""0xffffffffffffffff""#).unwrap(), u64::MAX);
+        assert!(parse(r#""0x10000000000000000""#).is_err());
+        assert!(parse(r#""0x""#).is_err());
+        assert!(parse(r#""-1""#).is_err());
+        assert!(parse(r#""""#).is_err());
+        assert_eq!(
+            serde_json::to_string(&S { slot: 0x100 }).unwrap(),
+            r#"{"slot":"256"}"#
+        );
+    }
+
+    #[test]
+    fn test_hex_fields_are_capped() {
+        #[derive(Deserialize)]
+        struct S {
+            #[serde(deserialize_with = "from_hex_to_ssz_type_checked")]
+            extra_data: VariableList<u8, MAX_EXTRA_DATA_BYTES>,
+        }
+        let parse =
+            |hex: String| serde_json::from_str::<S>(&format!(r#"{{"extra_data": "0x{hex}"}}"#));
+        assert_eq!(parse("ab".repeat(32)).unwrap().extra_data.len(), 32);
+        let err = parse("ab".repeat(crate::constants::MAX_HEX_FIELD_BYTES + 1))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("exceeds the limit"));
+    }
+
+    #[test]
+    fn test_uint64_fields_accept_decimal_and_hex() -> Result<()> {
+        let decimal: VoluntaryExit =
+            serde_json::from_str(r#"{"epoch": "254", "validator_index": "421337"}"#)?;
+        let hex: VoluntaryExit =
+            serde_json::from_str(r#"{"epoch": "0xfe", "validator_index": "0x66dd9"}"#)?;
+        assert_eq!(decimal.epoch, 254);
+        assert_eq!(decimal.validator_index, 421337);
+        assert_eq!(
+            tree_hash::TreeHash::tree_hash_root(&hex),
+            tree_hash::TreeHash::tree_hash_root(&decimal)
+        );
+        // Responses echo quantities as decimal strings whatever the request used
+        assert_eq!(
+            serde_json::to_string(&hex)?,
+            r#"{"epoch":"254","validator_index":"421337"}"#
+        );
+
+        let w: Withdrawal = serde_json::from_str(
+            r#"{
+                "index": "0x10",
+                "validator_index": "16",
+                "address": "0x0000000000000000000000000000000000000001",
+                "amount": "0x773594000"
+            }"#,
+        )?;
+        assert_eq!(
+            (w.index, w.validator_index, w.amount),
+            (16, 16, 32000000000)
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_fork() -> Result<()> {
         let req = r#"
@@ -852,6 +1888,103 @@ mod serialization_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fork_schedule() -> Result<()> {
+        let mainnet = ForkSchedule::preset("mainnet").unwrap();
+        assert_eq!(mainnet.genesis_fork_version(), [0, 0, 0, 0]);
+        assert_eq!(mainnet.fork_version_at_epoch(74239), [0, 0, 0, 0]);
+        assert_eq!(mainnet.fork_version_at_epoch(74240), [1, 0, 0, 0]);
+        assert_eq!(mainnet.fork_version_at_epoch(200000), [3, 0, 0, 0]);
+        assert_eq!(mainnet.fork_version_at_epoch(u64::MAX), [4, 0, 0, 0]);
+        let fork = mainnet.fork_at_epoch(200000);
+        assert_eq!(fork.previous_version, [2, 0, 0, 0]);
+        assert_eq!(fork.current_version, [3, 0, 0, 0]);
+        assert_eq!(fork.epoch, 194048);
+        assert_eq!(fork.version_at_epoch(194047), [2, 0, 0, 0]);
+
+        // Forks sharing an activation epoch resolve to the last of them
+        let holesky = ForkSchedule::preset("holesky").unwrap();
+        assert_eq!(holesky.genesis_fork_version(), [1, 1, 0x70, 0]);
+        assert_eq!(holesky.fork_version_at_epoch(0), [3, 1, 0x70, 0]);
+        assert!(ForkSchedule::preset("nope").is_none());
+
+        let data_dir = crate::io::data_dir::ScopedDataDir::temp("test_fork_schedule");
+        std::fs::create_dir_all(data_dir.path())?;
+        let path = data_dir.path().join("test_fork_schedule.json");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            r#"{"forks": [{"version": "0x00000001", "epoch": "0"}, {"version": "0x01000001", "epoch": "10"}]}"#,
+        )?;
+        let devnet = ForkSchedule::load(path)?;
+        assert_eq!(devnet.fork_version_at_epoch(9), [0, 0, 0, 1]);
+        assert_eq!(devnet.fork_version_at_epoch(10), [1, 0, 0, 1]);
+
+        std::fs::write(
+            path,
+            r#"{"forks": [{"version": "0x01000001", "epoch": "10"}]}"#,
+        )?;
+        assert!(ForkSchedule::load(path).is_err());
+        std::fs::remove_file(path).ok();
+        assert!(ForkSchedule::load(path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_presets() -> Result<()> {
+        let mainnet = NetworkPreset::load("mainnet", None)?;
+        assert_eq!(mainnet.genesis_fork_version, [0, 0, 0, 0]);
+        assert_eq!(mainnet.genesis_validators_root[..2], [0x4b, 0x36]);
+        assert_eq!(mainnet.genesis_time, 1606824023);
+        assert_eq!(
+            mainnet.fork_schedule,
+            ForkSchedule::preset("mainnet").unwrap()
+        );
+        for network in ["goerli", "sepolia", "holesky"] {
+            assert_eq!(NetworkPreset::load(network, None)?.name, network);
+        }
+        assert!(NetworkPreset::load("nope", None).is_err());
+        assert!(NetworkPreset::load("custom", None).is_err());
+
+        let data_dir = crate::io::data_dir::ScopedDataDir::temp("test_network_config");
+        std::fs::create_dir_all(data_dir.path())?;
+        let path = data_dir.path().join("test_network_config.yaml");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            r#"
+PRESET_BASE: 'mainnet'
+CONFIG_NAME: 'devnet'
+MIN_GENESIS_TIME: 1700000000
+GENESIS_DELAY: 60
+GENESIS_FORK_VERSION: 0x10000038
+ALTAIR_FORK_VERSION: 0x20000038
+ALTAIR_FORK_EPOCH: 0
+BELLATRIX_FORK_VERSION: 0x30000038
+BELLATRIX_FORK_EPOCH: 5
+CAPELLA_FORK_VERSION: 0x40000038
+CAPELLA_FORK_EPOCH: 18446744073709551615
+SECONDS_PER_SLOT: 6
+"#,
+        )?;
+        let devnet = NetworkPreset::load("custom", Some(path))?;
+        std::fs::remove_file(path).ok();
+        assert_eq!(devnet.name, "devnet");
+        assert_eq!(devnet.genesis_fork_version, [0x10, 0, 0, 0x38]);
+        assert_eq!(devnet.genesis_validators_root, Root::default());
+        assert_eq!(devnet.genesis_time, 1700000060);
+        assert_eq!(devnet.seconds_per_slot, 6);
+        assert_eq!(
+            devnet.fork_schedule.fork_version_at_epoch(0),
+            [0x20, 0, 0, 0x38]
+        );
+        assert_eq!(
+            devnet.fork_schedule.fork_version_at_epoch(1000),
+            [0x30, 0, 0, 0x38]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_fork_info() -> Result<()> {
         let req = r#"
@@ -942,4 +2075,229 @@ mod serialization_tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_deserialize_validator_registration_rejects_short_fee_recipient() {
+        // 19-byte fee_recipient: an execution address must be exactly 20 bytes
+        let req = r#"
+            {
+                "fee_recipient": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a",
+                "gas_limit": "30000000",
+                "timestamp": "100",
+                "pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18"
+            }"#;
+        assert!(serde_json::from_str::<ValidatorRegistration>(req).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_block_v2_wrapper_rejects_unknown_version() {
+        let header = r#""block_header": {
+                    "slot": "0",
+                    "proposer_index": "0",
+                    "parent_root":"0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "state_root":"0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "body_root":"0x0000000000000000000000000000000000000000000000000000000000000000"
+                }"#;
+        let ok = format!(r#"{{ "version": "Bellatrix", {header} }}"#);
+        assert!(serde_json::from_str::<BlockV2RequestWrapper>(&ok).is_ok());
+        let bad = format!(r#"{{ "version": "MERGE", {header} }}"#);
+        assert!(serde_json::from_str::<BlockV2RequestWrapper>(&bad).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_block_v2_wrapper_full_altair_block() {
+        let body = r#"{
+                "randao_reveal": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "eth1_data": {
+                    "deposit_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "deposit_count": "0",
+                    "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                },
+                "graffiti": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "proposer_slashings": [],
+                "attester_slashings": [],
+                "attestations": [],
+                "deposits": [],
+                "voluntary_exits": [],
+                "sync_aggregate": {
+                    "sync_committee_bits": "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                    "sync_committee_signature": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                }
+            }"#;
+        let req = format!(
+            r#"{{
+                "version": "ALTAIR",
+                "block": {{
+                    "slot": "7",
+                    "proposer_index": "3",
+                    "parent_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "state_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "body": {body}
+                }}
+            }}"#
+        );
+        let w: BlockV2RequestWrapper = serde_json::from_str(&req).unwrap();
+        let body: BeaconBlockBodyAltair = serde_json::from_str(body).unwrap();
+        assert_eq!(w.graffiti, Some([0; 32]));
+        assert_eq!(w.block_header.slot, 7);
+        assert_eq!(w.block_header.proposer_index, 3);
+        assert_eq!(
+            w.block_header.body_root,
+            tree_hash::TreeHash::tree_hash_root(&body).to_fixed_bytes()
+        );
+
+        // Full blocks for forks without a modelled body are rejected
+        let req = req.replace("ALTAIR", "DENEB");
+        assert!(serde_json::from_str::<BlockV2RequestWrapper>(&req).is_err());
+    }
+
+    #[test]
+    fn test_ssz_list_limits() {
+        let exit = r#"{
+                "message": {"epoch": "1", "validator_index": "2"},
+                "signature": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            }"#;
+        let body = |n: usize| {
+            format!(
+                r#"{{
+                    "randao_reveal": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                    "eth1_data": {{
+                        "deposit_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "deposit_count": "0",
+                        "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                    }},
+                    "graffiti": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "proposer_slashings": [],
+                    "attester_slashings": [],
+                    "attestations": [],
+                    "deposits": [],
+                    "voluntary_exits": [{}]
+                }}"#,
+                vec![exit; n].join(",")
+            )
+        };
+
+        let full: BeaconBlockBodyPhase0 = serde_json::from_str(&body(16)).unwrap();
+        assert_eq!(full.voluntary_exits.len(), 16);
+
+        let err = serde_json::from_str::<BeaconBlockBodyPhase0>(&body(17)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("voluntary_exits exceeds its SSZ list limit of 16"));
+
+        let indices = |n: u64| {
+            let indices: Vec<String> = (0..n).map(|i| i.to_string()).collect();
+            format!("[{}]", indices.join(","))
+        };
+        let de = |json: &str| {
+            let mut de = serde_json::Deserializer::from_str(json);
+            de_attesting_indices::<_, ValidatorIndex, MAX_VALIDATORS_PER_COMMITTEE>(&mut de)
+        };
+        assert_eq!(de(&indices(2048)).unwrap().len(), 2048);
+        assert!(de(&indices(2049))
+            .unwrap_err()
+            .to_string()
+            .contains("attesting_indices exceeds its SSZ list limit of 2048"));
+    }
+
+    #[test]
+    fn test_deserialize_block_v2_wrapper_full_phase0_block() {
+        let body = r#"{
+                "randao_reveal": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "eth1_data": {
+                    "deposit_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "deposit_count": "0",
+                    "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                },
+                "graffiti": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a",
+                "proposer_slashings": [],
+                "attester_slashings": [],
+                "attestations": [],
+                "deposits": [],
+                "voluntary_exits": [{
+                    "message": {"epoch": "1", "validator_index": "2"},
+                    "signature": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                }]
+            }"#;
+        let req = format!(
+            r#"{{
+                "version": "PHASE0",
+                "block": {{
+                    "slot": "7",
+                    "proposer_index": "3",
+                    "parent_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "state_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "body": {body}
+                }}
+            }}"#
+        );
+        let w: BlockV2RequestWrapper = serde_json::from_str(&req).unwrap();
+        let body: BeaconBlockBodyPhase0 = serde_json::from_str(body).unwrap();
+        assert_eq!(w.graffiti, Some([0x2a; 32]));
+        assert_eq!(w.block_header.slot, 7);
+        assert_eq!(
+            w.block_header.body_root,
+            tree_hash::TreeHash::tree_hash_root(&body).to_fixed_bytes()
+        );
+
+        // The voluntary_exits length is mixed into the body root
+        let mut emptied = body.clone();
+        emptied.voluntary_exits = VariableList::empty();
+        assert_ne!(
+            tree_hash::TreeHash::tree_hash_root(&emptied),
+            tree_hash::TreeHash::tree_hash_root(&body)
+        );
+    }
+    fn mock_bellatrix_payload(extra_data: &str, transactions: &str) -> String {
+        format!(
+            r#"{{
+                "parent_hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "fee_recipient": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a",
+                "state_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "receipts_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "logs_bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                "prev_randao": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "block_number": "1",
+                "gas_limit": "30000000",
+                "gas_used": "21000",
+                "timestamp": "1663224179",
+                "extra_data": "{extra_data}",
+                "base_fee_per_gas": "7",
+                "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "transactions": {transactions}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn test_bellatrix_transactions_root_known_answer() {
+        // Expected roots computed independently with the consensus-specs `hash_tree_root`
+        // reference algorithm for List[Transaction, MAX_TRANSACTIONS_PER_PAYLOAD]
+        let req = mock_bellatrix_payload("0x", "[]");
+        let p: ExecutionPayloadBellatrix = serde_json::from_str(&req).unwrap();
+        assert_eq!(
+            hex::encode(tree_hash::TreeHash::tree_hash_root(&p.transactions)),
+            "7ffe241ea60187fdb0187bfa22de35d1f9bed7ab061d9401fd47e34a54fbede1"
+        );
+
+        let txs = format!(
+            r#"["0x02f87201", "0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222324252627"]"#
+        );
+        let req = mock_bellatrix_payload("0x", &txs);
+        let p: ExecutionPayloadBellatrix = serde_json::from_str(&req).unwrap();
+        assert_eq!(p.transactions.len(), 2);
+        assert_eq!(
+            hex::encode(tree_hash::TreeHash::tree_hash_root(&p.transactions)),
+            "aa9438fa6b4f4fcf503884a3cff80637b0149ec643cfc69a4db703de60c7e1ac"
+        );
+        assert_eq!(p.base_fee_per_gas[..], [7, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_bellatrix_payload_rejects_oversized_extra_data() {
+        let ok = mock_bellatrix_payload(&format!("0x{}", "ab".repeat(32)), "[]");
+        assert!(serde_json::from_str::<ExecutionPayloadBellatrix>(&ok).is_ok());
+        let too_long = mock_bellatrix_payload(&format!("0x{}", "ab".repeat(33)), "[]");
+        assert!(serde_json::from_str::<ExecutionPayloadBellatrix>(&too_long).is_err());
+    }
 }
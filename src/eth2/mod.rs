@@ -1,3 +1,5 @@
 pub mod eth_signing;
 pub mod eth_types;
 pub mod slash_protection;
+#[cfg(feature = "sqlite")]
+pub mod slash_protection_sqlite;
@@ -1,21 +1,81 @@
-use crate::strip_0x_prefix;
-
 use super::eth_types::{
-    de_signing_root, from_hex_to_ssz_type, se_signing_root, to_hex_from_ssz_type, BLSPubkey, Epoch,
-    Root, Slot,
+    de_signing_root, from_hex_to_ssz_type, quoted_or_hex_u64, se_signing_root,
+    to_hex_from_ssz_type, BLSPubkey, Epoch, Root, Slot, SLOTS_PER_EPOCH,
 };
-use crate::constants::SLASHING_PROTECTION_DIR;
+use crate::io::data_dir::slashing_protection_dir;
 
 use anyhow::{bail, Context, Result};
 use hex;
-use log::{debug, error};
+use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
 use serde_hex::{SerHex, StrictPfx};
-use serde_utils::quoted_u64;
 use ssz::Encode;
 use ssz_types::FixedVector;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Suffix of the temporary file a history is staged in before being renamed into place
+const TMP_SUFFIX: &str = ".tmp";
+
+/// Version of the per-key file written by `FileBackend::write`. Files without a
+/// `format_version` predate it, may hold hex slots and epochs, and are rewritten on first read.
+pub const SLASHING_PROTECTION_FORMAT_VERSION: u64 = 1;
+
+/// Operator supplied lower bounds applied to every key on top of its own history, for
+/// validators migrated without an EIP-3076 interchange file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlashingFloor {
+    /// Blocks below this slot are refused
+    pub min_slot: Slot,
+    /// Attestations whose target is below this epoch are refused
+    pub min_epoch: Epoch,
+}
+
+impl SlashingFloor {
+    pub fn is_below_block_slot(&self, slot: Slot) -> bool {
+        slot < self.min_slot
+    }
+
+    pub fn is_below_attestation_epochs(&self, _src: Epoch, tgt: Epoch) -> bool {
+        // The source trails the target, so only the target is held to the floor
+        tgt < self.min_epoch
+    }
+}
+
+/// Wall clock bound on the slots and epochs that may be signed, so a request far in the future
+/// cannot raise a key's watermark past every legitimate slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotClock {
+    /// Unix time of slot 0
+    pub genesis_time: u64,
+    pub seconds_per_slot: u64,
+    /// How far past the current slot a block or attestation target may be
+    pub max_future_slots: Slot,
+}
+
+impl SlotClock {
+    /// The slot at unix time `now`, or 0 before genesis
+    pub fn slot_at(&self, now: u64) -> Slot {
+        now.saturating_sub(self.genesis_time) / std::cmp::max(self.seconds_per_slot, 1)
+    }
+
+    pub fn is_far_future_slot(&self, slot: Slot, now: u64) -> bool {
+        slot > self.slot_at(now).saturating_add(self.max_future_slots)
+    }
+
+    pub fn is_far_future_epoch(&self, epoch: Epoch, now: u64) -> bool {
+        self.is_far_future_slot(epoch.saturating_mul(SLOTS_PER_EPOCH), now)
+    }
+}
+
+/// The on-disk form of SlashingProtectionData, which stays a plain EIP-3076 record elsewhere
+#[derive(Serialize)]
+struct VersionedSlashingProtectionData<'a> {
+    format_version: u64,
+    #[serde(flatten)]
+    data: &'a SlashingProtectionData,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SlashingProtectionMetaData {
@@ -24,7 +84,7 @@ pub struct SlashingProtectionMetaData {
     pub genesis_validators_root: Root,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SlashingProtectionData {
     #[serde(
         deserialize_with = "from_hex_to_ssz_type",
@@ -45,8 +105,7 @@ impl SlashingProtectionData {
     }
 
     pub fn from_pk_hex(pk_hex: &String) -> Result<Self> {
-        let pk_hex: String = strip_0x_prefix!(pk_hex);
-        let pk_bytes = hex::decode(pk_hex)?;
+        let pk_bytes = crate::parse_hex(pk_hex)?;
         let pk_bytes: BLSPubkey = FixedVector::from(pk_bytes.to_vec());
         Ok(SlashingProtectionData::new(pk_bytes))
     }
@@ -63,6 +122,16 @@ impl SlashingProtectionData {
         slot <= last_slot
     }
 
+    /// Returns true if the block at the watermark slot was signed with this signing root, so
+    /// signing it again is a retry that yields the same signature
+    pub fn is_repeat_block(&self, slot: Slot, signing_root: Root) -> bool {
+        slot == self.get_latest_signed_block_slot()
+            && self
+                .signed_blocks
+                .iter()
+                .any(|b| b.slot == slot && b.signing_root == Some(signing_root))
+    }
+
     /// If the SlashingProtectionDB is growable, append the new block, otherwise
     /// overwrite the 0th element.
     pub fn new_block(&mut self, block: SignedBlockSlot, growable: bool) -> Result<()> {
@@ -97,9 +166,35 @@ impl SlashingProtectionData {
         (latest_src, latest_tgt)
     }
 
+    /// Refusing a source below the highest signed source or a target at or below the highest
+    /// signed target is the EIP-3076 watermark condition, which rules out double votes and both
+    /// surrounding and surrounded votes. The history is also searched for surround votes directly
+    /// so that an imported history whose watermarks were lowered by hand is still honoured.
     pub fn is_slashable_attestation_epochs(&self, src: Epoch, tgt: Epoch) -> bool {
         let (last_src, last_tgt) = self.get_latest_signed_attestation_epochs();
-        src < last_src || tgt <= last_tgt
+        src > tgt || src < last_src || tgt <= last_tgt || self.is_surround_vote(src, tgt)
+    }
+
+    /// Returns true if an attestation with these epochs would surround, or be surrounded by, an
+    /// attestation in the history
+    pub fn is_surround_vote(&self, src: Epoch, tgt: Epoch) -> bool {
+        self.signed_attestations.iter().any(|a| {
+            let surrounds = src < a.source_epoch && tgt > a.target_epoch;
+            let surrounded = src > a.source_epoch && tgt < a.target_epoch;
+            surrounds || surrounded
+        })
+    }
+
+    /// Returns true if the attestation at the watermark target epoch was signed with this source
+    /// epoch and signing root, so signing it again is a retry that yields the same signature
+    pub fn is_repeat_attestation(&self, src: Epoch, tgt: Epoch, signing_root: Root) -> bool {
+        let (_, last_tgt) = self.get_latest_signed_attestation_epochs();
+        tgt == last_tgt
+            && self.signed_attestations.iter().any(|a| {
+                a.source_epoch == src
+                    && a.target_epoch == tgt
+                    && a.signing_root == Some(signing_root)
+            })
     }
 
     /// If the SlashingProtectionDB is growable, append the new attestation epochs, otherwise
@@ -110,6 +205,10 @@ impl SlashingProtectionData {
         growable: bool,
     ) -> Result<()> {
         let (prev_src, prev_tgt) = self.get_latest_signed_attestation_epochs();
+        if attest.source_epoch > attest.target_epoch {
+            error!("Attestation source epoch is past its target epoch");
+            bail!("Will not save this slashable Attestation!");
+        }
         if attest.source_epoch < prev_src {
             error!("Attestation source epoch is decreasing");
             bail!("Will not save this slashable Attestation!");
@@ -118,6 +217,10 @@ impl SlashingProtectionData {
             error!("Attestation target epoch is non-increasing");
             bail!("Will not save this slashable Attestation!");
         }
+        if self.is_surround_vote(attest.source_epoch, attest.target_epoch) {
+            error!("Attestation is a surround vote");
+            bail!("Will not save this slashable Attestation!");
+        }
 
         if growable || self.signed_attestations.is_empty() {
             self.signed_attestations.push(attest);
@@ -127,33 +230,224 @@ impl SlashingProtectionData {
         Ok(())
     }
 
-    pub fn write(&self) -> Result<()> {
-        let fname = hex::encode(self.pubkey.as_ssz_bytes());
-        let file_path: PathBuf = [SLASHING_PROTECTION_DIR, &fname].iter().collect();
+    /// Raises this history's block slot and attestation epoch watermarks to those of `other`.
+    /// Watermarks are never lowered.
+    pub fn merge_watermarks(&mut self, other: &SlashingProtectionData, growable: bool) {
+        if let Some(b) = other.signed_blocks.iter().max_by_key(|b| b.slot) {
+            if self.signed_blocks.is_empty() || b.slot > self.get_latest_signed_block_slot() {
+                if growable || self.signed_blocks.is_empty() {
+                    self.signed_blocks.push(b.clone());
+                } else {
+                    self.signed_blocks[0] = b.clone();
+                }
+            }
+        }
+
+        if !other.signed_attestations.is_empty() {
+            let (src, tgt) = self.get_latest_signed_attestation_epochs();
+            let (other_src, other_tgt) = other.get_latest_signed_attestation_epochs();
+            if self.signed_attestations.is_empty() || other_src > src || other_tgt > tgt {
+                let attest = SignedAttestationEpochs {
+                    source_epoch: std::cmp::max(src, other_src),
+                    target_epoch: std::cmp::max(tgt, other_tgt),
+                    signing_root: None,
+                };
+                if growable || self.signed_attestations.is_empty() {
+                    self.signed_attestations.push(attest);
+                } else {
+                    self.signed_attestations[0] = attest;
+                }
+            }
+        }
+    }
+
+    /// Drops the entries that are not needed for safety. The highest signed slot and source and
+    /// target epochs are always kept, as are the `window` most recent blocks and attestations
+    /// with their signing roots. Returns the number of entries removed.
+    pub fn prune(&mut self, window: usize) -> usize {
+        let before = self.signed_blocks.len() + self.signed_attestations.len();
+        let keep = std::cmp::max(window, 1);
+
+        self.signed_blocks.sort_by_key(|b| b.slot);
+        let cut = self.signed_blocks.len().saturating_sub(keep);
+        self.signed_blocks.drain(..cut);
+
+        let (max_src, _) = self.get_latest_signed_attestation_epochs();
+        self.signed_attestations
+            .sort_by_key(|a| (a.target_epoch, a.source_epoch));
+        let cut = self.signed_attestations.len().saturating_sub(keep);
+        let dropped: Vec<SignedAttestationEpochs> = self.signed_attestations.drain(..cut).collect();
+        // The highest source may belong to an older target
+        if !self
+            .signed_attestations
+            .iter()
+            .any(|a| a.source_epoch == max_src)
+        {
+            if let Some(a) = dropped.into_iter().find(|a| a.source_epoch == max_src) {
+                self.signed_attestations.insert(0, a);
+            }
+        }
+
+        before - self.signed_blocks.len() - self.signed_attestations.len()
+    }
+}
+
+/// Where the per-key SlashingProtectionData is persisted. The backend is chosen once at startup
+/// and handed to the handlers in `AppState`, `FileBackend` keeps one JSON file per key in the
+/// slashing protection dir.
+pub trait SlashingProtectionBackend: Send + Sync {
+    /// Returns true if a history was saved for the key, whether or not it is readable
+    fn exists(&self, pk_hex: &str) -> bool;
+
+    fn read(&self, pk_hex: &str) -> Result<SlashingProtectionData>;
+
+    fn write(&self, data: &SlashingProtectionData) -> Result<()>;
+
+    /// Lists the keys with a saved slashing protection history
+    fn pk_hexes(&self) -> Result<Vec<String>>;
+
+    /// Reads the key's history, hands it to `f` and saves it only if `f` returns true, all within
+    /// a single transaction. Returns the value of `f`.
+    fn update(
+        &self,
+        pk_hex: &str,
+        f: &mut dyn FnMut(&mut SlashingProtectionData) -> Result<bool>,
+    ) -> Result<bool>;
+}
+
+/// Reads every saved history, so an unreadable or corrupt one is found at startup rather than
+/// when its key next signs. Returns the number of histories checked.
+pub fn check_integrity(backend: &dyn SlashingProtectionBackend) -> Result<usize> {
+    let pk_hexes = backend.pk_hexes()?;
+    for pk_hex in pk_hexes.iter() {
+        backend
+            .read(pk_hex)
+            .with_context(|| format!("Bad slashing protection history for {pk_hex}"))?;
+    }
+    Ok(pk_hexes.len())
+}
+
+/// Saves each key's history as a versioned JSON file named after the key
+pub struct FileBackend;
+
+impl SlashingProtectionBackend for FileBackend {
+    fn exists(&self, pk_hex: &str) -> bool {
+        let pk_hex = crate::io::key_management::key_fname(pk_hex);
+        let file_path: PathBuf = slashing_protection_dir().join(&pk_hex);
+        file_path.exists()
+    }
+
+    fn read(&self, pk_hex: &str) -> Result<SlashingProtectionData> {
+        let pk_hex = crate::io::key_management::key_fname(pk_hex);
+        let file_path: PathBuf = slashing_protection_dir().join(&pk_hex);
+        let json_vec = fs::read(file_path)?;
+        let mut value: serde_json::Value =
+            serde_json::from_slice(&json_vec).with_context(|| "failed to read protection data")?;
+        let format_version = match value
+            .as_object_mut()
+            .and_then(|o| o.remove("format_version"))
+        {
+            Some(v) => v
+                .as_u64()
+                .with_context(|| format!("Bad slash protection format_version: {v}"))?,
+            None => 0,
+        };
+        if format_version > SLASHING_PROTECTION_FORMAT_VERSION {
+            bail!("Unsupported slash protection format_version {format_version} for 0x{pk_hex}");
+        }
+        let data: SlashingProtectionData =
+            serde_json::from_value(value).with_context(|| "failed to read protection data")?;
+        debug!("Reading Slash Protection DB:\n{:#?}", data);
+
+        if format_version < SLASHING_PROTECTION_FORMAT_VERSION {
+            info!("Migrating slash protection data for 0x{pk_hex} to format_version {SLASHING_PROTECTION_FORMAT_VERSION}");
+            self.write(&data)?;
+        }
+        Ok(data)
+    }
+
+    fn write(&self, data: &SlashingProtectionData) -> Result<()> {
+        let fname = hex::encode(data.pubkey.as_ssz_bytes());
+        let file_path: PathBuf = slashing_protection_dir().join(&fname);
         if let Some(p) = file_path.parent() {
             fs::create_dir_all(p).with_context(|| "Failed to create slashing dir")?
         };
-        let json = serde_json::to_string(&self)?;
+        let json = serde_json::to_string(&VersionedSlashingProtectionData {
+            format_version: SLASHING_PROTECTION_FORMAT_VERSION,
+            data,
+        })?;
         debug!("Writing Slash Protection DB:\n{json}");
-        fs::write(&file_path, json).with_context(|| "failed to write protection data")
+        write_atomic(&file_path, json.as_bytes()).with_context(|| "failed to write protection data")
     }
 
-    pub fn read(pk_hex: &str) -> Result<Self> {
-        let pk_hex: String = strip_0x_prefix!(pk_hex);
-        let file_path: PathBuf = [SLASHING_PROTECTION_DIR, &pk_hex].iter().collect();
-        let json_vec = fs::read(file_path)?;
-        let json =
-            serde_json::from_slice(&json_vec).with_context(|| "failed to read protection data")?;
-        debug!("Reading Slash Protection DB:\n{:#?}", json);
-        Ok(json)
+    fn pk_hexes(&self) -> Result<Vec<String>> {
+        match fs::read_dir(slashing_protection_dir()) {
+            Ok(entries) => {
+                let mut pks = vec![];
+                for entry in entries {
+                    let entry = entry.with_context(|| "failed to read slashing dir")?;
+                    match entry.file_name().into_string() {
+                        // Left behind by a write that was interrupted before the rename
+                        Ok(pk) if pk.ends_with(TMP_SUFFIX) => continue,
+                        Ok(pk) => pks.push(pk),
+                        Err(e) => bail!("Error, bad file name in slashing dir: {:?}", e),
+                    }
+                }
+                Ok(pks)
+            }
+            // Nothing has been signed or generated yet
+            Err(_) => Ok(vec![]),
+        }
+    }
+
+    fn update(
+        &self,
+        pk_hex: &str,
+        f: &mut dyn FnMut(&mut SlashingProtectionData) -> Result<bool>,
+    ) -> Result<bool> {
+        // The atomic write is the commit, nothing reaches disk if `f` declines or fails
+        let mut data = self.read(pk_hex)?;
+        if !f(&mut data)? {
+            return Ok(false);
+        }
+        self.write(&data)?;
+        Ok(true)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Replaces `file_path` with `contents` such that a crash leaves either the old or the new file
+/// on disk, never a partial one. The contents are synced to a temporary file in the same
+/// directory, renamed over `file_path`, then the directory is synced to persist the rename.
+pub(crate) fn write_atomic(file_path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = file_path
+        .parent()
+        .with_context(|| format!("No parent dir for {:?}", file_path))?;
+    let mut tmp_name = file_path
+        .file_name()
+        .with_context(|| format!("No file name in {:?}", file_path))?
+        .to_os_string();
+    tmp_name.push(TMP_SUFFIX);
+    let tmp_path = dir.join(tmp_name);
+
+    let mut tmp =
+        fs::File::create(&tmp_path).with_context(|| format!("Failed to create {:?}", tmp_path))?;
+    tmp.write_all(contents)?;
+    tmp.sync_all()
+        .with_context(|| format!("Failed to sync {:?}", tmp_path))?;
+    drop(tmp);
+
+    fs::rename(&tmp_path, file_path)
+        .with_context(|| format!("Failed to rename {:?} to {:?}", tmp_path, file_path))?;
+    fs::File::open(dir)
+        .and_then(|d| d.sync_all())
+        .with_context(|| format!("Failed to sync {:?}", dir))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SignedAttestationEpochs {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub source_epoch: Epoch,
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub target_epoch: Epoch,
     #[serde(default)]
     #[serde(
@@ -166,7 +460,7 @@ pub struct SignedAttestationEpochs {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SignedBlockSlot {
-    #[serde(with = "quoted_u64")]
+    #[serde(with = "quoted_or_hex_u64")]
     pub slot: Slot,
     #[serde(default)]
     #[serde(
@@ -181,7 +475,9 @@ pub struct SignedBlockSlot {
 /// Specs: https://eips.ethlibrary.io/eip-3076.html
 pub struct SlashingProtectionDB {
     pub metadata: SlashingProtectionMetaData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub data: Vec<SlashingProtectionData>,
 }
@@ -206,16 +502,138 @@ impl SlashingProtectionDB {
         Ok(db)
     }
 
-    pub fn read(&self) -> Result<()> {
-        // TODO combine all saved SlashingProtectionData into
-        // a SlashingProtectionDB to return via GET endpoint.
+    /// Parses an interchange file record by record so that a malformed file can be reported
+    /// with the offending record identified.
+    pub fn from_interchange_str(json: &str) -> Result<Self> {
+        let raw: serde_json::Value =
+            serde_json::from_str(json).with_context(|| "Interchange file is not valid JSON")?;
+
+        let metadata: SlashingProtectionMetaData =
+            serde_json::from_value(raw.get("metadata").cloned().unwrap_or_default())
+                .with_context(|| "Malformed metadata")?;
+        if metadata.interchange_format_version != "5" {
+            bail!(
+                "Unsupported interchange_format_version: {}",
+                metadata.interchange_format_version
+            )
+        }
+
+        let records = match raw.get("data").and_then(|d| d.as_array()) {
+            Some(records) => records,
+            None => bail!("Missing data array"),
+        };
+
+        let mut data = vec![];
+        for (i, record) in records.iter().enumerate() {
+            let pubkey = record
+                .get("pubkey")
+                .and_then(|pk| pk.as_str())
+                .unwrap_or("unknown");
+            match crate::parse_hex(pubkey) {
+                Ok(pk) if pk.len() == crate::constants::BLS_PUB_KEY_BYTES => {}
+                _ => bail!("Malformed record data[{i}]: invalid pubkey {pubkey}"),
+            }
+            let record: SlashingProtectionData = serde_json::from_value(record.clone())
+                .with_context(|| format!("Malformed record data[{i}] (pubkey {pubkey})"))?;
+            if let Some(j) = record
+                .signed_attestations
+                .iter()
+                .position(|a| a.source_epoch > a.target_epoch)
+            {
+                bail!("Malformed record data[{i}] (pubkey {pubkey}): signed_attestations[{j}] source_epoch exceeds target_epoch")
+            }
+            data.push(record);
+        }
+
+        Ok(SlashingProtectionDB {
+            metadata,
+            title: None,
+            description: None,
+            data,
+        })
+    }
+
+    /// Errors if the interchange file was produced for a different chain. An all-zero `expected`
+    /// root means the chain is not configured and any file is accepted.
+    pub fn check_genesis_validators_root(&self, expected: Root) -> Result<()> {
+        if expected != Root::default() && self.metadata.genesis_validators_root != expected {
+            bail!(
+                "Interchange genesis_validators_root 0x{} does not match 0x{}",
+                hex::encode(self.metadata.genesis_validators_root),
+                hex::encode(expected)
+            );
+        }
         Ok(())
     }
+
+    /// Seeds the saved slashing protection history of each key in the interchange file,
+    /// never lowering an existing watermark. Returns the number of keys updated.
+    pub fn import(&self, backend: &dyn SlashingProtectionBackend, growable: bool) -> Result<usize> {
+        for record in self.data.iter() {
+            let pk_hex = hex::encode(record.pubkey.as_ssz_bytes());
+            // An unreadable history must not be mistaken for an empty one
+            let mut db = if backend.exists(&pk_hex) {
+                backend.read(&pk_hex)?
+            } else {
+                SlashingProtectionData::from_pk_hex(&pk_hex)?
+            };
+            db.merge_watermarks(record, growable);
+            backend
+                .write(&db)
+                .with_context(|| format!("Failed to save slashing protection for 0x{pk_hex}"))?;
+        }
+        Ok(self.data.len())
+    }
+
+    /// Combines the saved SlashingProtectionData of each key into an interchange file. If `pubkeys`
+    /// is supplied, only the histories of those keys are included.
+    pub fn export(
+        backend: &dyn SlashingProtectionBackend,
+        genesis_validators_root: Root,
+        pubkeys: Option<Vec<String>>,
+    ) -> Result<Self> {
+        let mut db = SlashingProtectionDB::new();
+        db.metadata.genesis_validators_root = genesis_validators_root;
+
+        let pk_hexes: Vec<String> = match pubkeys {
+            Some(pks) => pks
+                .iter()
+                .map(|pk| crate::io::key_management::key_fname(pk))
+                .collect(),
+            None => backend.pk_hexes()?,
+        };
+
+        for pk_hex in pk_hexes {
+            let data = backend
+                .read(&pk_hex)
+                .with_context(|| format!("No slashing protection data for 0x{pk_hex}"))?;
+            db.data.push(data);
+        }
+        db.data.sort_by_key(|d| d.pubkey.as_ssz_bytes());
+        Ok(db)
+    }
+
+    /// Prunes the saved history of every key, returning the number of entries removed
+    pub fn prune_all(backend: &dyn SlashingProtectionBackend, window: usize) -> Result<usize> {
+        let mut pruned = 0;
+        for pk_hex in backend.pk_hexes()? {
+            let mut data = backend
+                .read(&pk_hex)
+                .with_context(|| format!("No slashing protection data for 0x{pk_hex}"))?;
+            let n = data.prune(window);
+            if n > 0 {
+                backend.write(&data)?;
+                pruned += n;
+            }
+        }
+        Ok(pruned)
+    }
 }
 
 #[cfg(test)]
 pub mod test_slash_protection {
     use super::*;
+    use crate::io::data_dir::ScopedDataDir;
     use hex;
     use ssz::Encode;
 
@@ -307,8 +725,320 @@ pub mod test_slash_protection {
         Ok(())
     }
 
+    #[test]
+    fn test_export_omits_empty_optional_fields() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_export_omits_empty_optional_fields");
+        let raw = dummy_slash_protection_data();
+        let db = SlashingProtectionDB::from_str(&raw)?;
+        for data in db.data.iter() {
+            FileBackend.write(&data)?;
+        }
+
+        let exported =
+            SlashingProtectionDB::export(&FileBackend, db.metadata.genesis_validators_root, None)?;
+        let json: serde_json::Value = serde_json::to_value(&exported)?;
+        assert!(json.get("title").is_none());
+        assert!(json.get("description").is_none());
+        assert_eq!(
+            json["metadata"]["genesis_validators_root"],
+            "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+        );
+        assert_eq!(json["data"][0]["pubkey"], "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18");
+        assert_eq!(json["data"][0]["signed_blocks"][0]["slot"], "81952");
+        assert_eq!(
+            json["data"][0]["signed_blocks"][0]["signing_root"],
+            "0x4ff6f743a43f3b4f95350831aeaf0a122a1a392922c45d804280284a69eb850b"
+        );
+        assert!(json["data"][0]["signed_blocks"][1]
+            .get("signing_root")
+            .is_none());
+        assert_eq!(
+            json["data"][0]["signed_attestations"][1]["target_epoch"],
+            "3008"
+        );
+
+        // Round trip
+        let db = SlashingProtectionDB::from_str(&serde_json::to_string(&exported)?)?;
+        assert_eq!(db.data.len(), 1);
+        assert_eq!(db.data[0].signed_blocks.len(), 2);
+
+        // Filtering on an unknown key fails rather than silently exporting nothing
+        assert!(SlashingProtectionDB::export(
+            &FileBackend,
+            Root::default(),
+            Some(vec![format!("0x{}", "ab".repeat(48))])
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_never_lowers_watermarks() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_import_never_lowers_watermarks");
+        let pk_hex = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+
+        // Existing history is ahead of the interchange file for blocks but behind for attestations
+        let mut existing = SlashingProtectionData::from_pk_hex(&pk_hex.to_string())?;
+        existing.new_block(
+            SignedBlockSlot {
+                slot: 90000,
+                signing_root: None,
+            },
+            false,
+        )?;
+        existing.new_attestation(
+            SignedAttestationEpochs {
+                source_epoch: 100,
+                target_epoch: 200,
+                signing_root: None,
+            },
+            false,
+        )?;
+        FileBackend.write(&existing)?;
+
+        let db = SlashingProtectionDB::from_interchange_str(&dummy_slash_protection_data())?;
+        assert_eq!(db.import(&FileBackend, false)?, 1);
+
+        let d = FileBackend.read(pk_hex)?;
+        assert_eq!(d.get_latest_signed_block_slot(), 90000);
+        assert_eq!(d.get_latest_signed_attestation_epochs(), (2290, 3008));
+        assert!(d.is_slashable_attestation_epochs(2290, 3008));
+        assert!(!d.is_slashable_attestation_epochs(2290, 3009));
+
+        // Importing into an empty history seeds it with the file's maxima
+        fs::remove_dir_all(slashing_protection_dir())?;
+        db.import(&FileBackend, false)?;
+        let d = FileBackend.read(pk_hex)?;
+        assert_eq!(d.get_latest_signed_block_slot(), 81952);
+        assert!(d.is_slashable_block_slot(81952));
+        assert_eq!(d.get_latest_signed_attestation_epochs(), (2290, 3008));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_replaces_file_atomically() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_write_replaces_file_atomically");
+        let pk_hex = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+        let mut db = SlashingProtectionData::from_pk_hex(&pk_hex.to_string())?;
+        FileBackend.write(&db)?;
+        db.new_block(
+            SignedBlockSlot {
+                slot: 10,
+                signing_root: None,
+            },
+            false,
+        )?;
+        FileBackend.write(&db)?;
+
+        assert_eq!(FileBackend.read(pk_hex)?.get_latest_signed_block_slot(), 10);
+        let files: Vec<_> = fs::read_dir(slashing_protection_dir())?
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(files, vec![pk_hex.to_string()]);
+
+        // A temporary file left by an interrupted write is not exported
+        let tmp_path: PathBuf = slashing_protection_dir().join(&format!("{pk_hex}{TMP_SUFFIX}"));
+        fs::write(&tmp_path, "{")?;
+        let exported = SlashingProtectionDB::export(&FileBackend, Root::default(), None)?;
+        assert_eq!(exported.data.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_hex_history_is_migrated() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_legacy_hex_history_is_migrated");
+        fs::create_dir_all(slashing_protection_dir())?;
+        let pk_hex = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+        let legacy = format!(
+            r#"{{
+                "pubkey": "0x{pk_hex}",
+                "signed_blocks": [{{ "slot": "0xff" }}],
+                "signed_attestations": [{{ "source_epoch": "0xff", "target_epoch": "0x100" }}]
+            }}"#
+        );
+        let file_path: PathBuf = slashing_protection_dir().join(pk_hex);
+        fs::write(&file_path, legacy)?;
+
+        // "0x100" sorts below "0xff" as a string but 256 > 255
+        let d = FileBackend.read(pk_hex)?;
+        assert_eq!(d.get_latest_signed_block_slot(), 255);
+        assert!(d.is_slashable_block_slot(0xfe));
+        assert!(d.is_slashable_block_slot(0xff));
+        assert!(!d.is_slashable_block_slot(0x100));
+        assert_eq!(d.get_latest_signed_attestation_epochs(), (255, 256));
+        assert!(d.is_slashable_attestation_epochs(0xff, 0xff));
+        assert!(!d.is_slashable_attestation_epochs(0x100, 0x101));
+
+        // The file was rewritten with decimal values and a format version
+        let migrated: serde_json::Value = serde_json::from_slice(&fs::read(&file_path)?)?;
+        assert_eq!(
+            migrated["format_version"],
+            SLASHING_PROTECTION_FORMAT_VERSION
+        );
+        assert_eq!(migrated["signed_blocks"][0]["slot"], "255");
+        assert_eq!(migrated["signed_attestations"][0]["target_epoch"], "256");
+        assert_eq!(
+            FileBackend.read(pk_hex)?.get_latest_signed_block_slot(),
+            255
+        );
+
+        // Files from a newer release are refused rather than misread
+        let mut future = migrated.clone();
+        future["format_version"] = (SLASHING_PROTECTION_FORMAT_VERSION + 1).into();
+        fs::write(&file_path, future.to_string())?;
+        assert!(FileBackend.read(pk_hex).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncated_history_is_not_treated_as_empty() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_truncated_history_is_not_treated_as_empty");
+        let pk_hex = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+        let mut db = SlashingProtectionData::from_pk_hex(&pk_hex.to_string())?;
+        db.new_block(
+            SignedBlockSlot {
+                slot: 90000,
+                signing_root: None,
+            },
+            false,
+        )?;
+        FileBackend.write(&db)?;
+
+        let file_path: PathBuf = slashing_protection_dir().join(pk_hex);
+        let json = fs::read(&file_path)?;
+        fs::write(&file_path, &json[..json.len() / 2])?;
+        assert!(FileBackend.exists(pk_hex));
+        assert!(FileBackend.read(pk_hex).is_err());
+
+        // Importing must not replace the unreadable history with the file's lower watermark
+        let db = SlashingProtectionDB::from_interchange_str(&dummy_slash_protection_data())?;
+        assert!(db.import(&FileBackend, false).is_err());
+        assert_eq!(fs::read(&file_path)?, &json[..json.len() / 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_keeps_signing_behavior() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_prune_keeps_signing_behavior");
+        let mut data = SlashingProtectionData::new(BLSPubkey::default());
+        for slot in 1..=50 {
+            data.new_block(
+                SignedBlockSlot {
+                    slot,
+                    signing_root: Some([slot as u8; 32]),
+                },
+                true,
+            )?;
+        }
+        for epoch in 0..30 {
+            data.new_attestation(
+                SignedAttestationEpochs {
+                    source_epoch: epoch,
+                    target_epoch: epoch + 1,
+                    signing_root: Some([epoch as u8; 32]),
+                },
+                true,
+            )?;
+        }
+        // An imported record whose source is higher than that of the latest target
+        data.signed_attestations.push(SignedAttestationEpochs {
+            source_epoch: 35,
+            target_epoch: 20,
+            signing_root: None,
+        });
+
+        let before = data.clone();
+        assert_eq!(data.prune(4), 46 + 26);
+        assert_eq!(data.signed_blocks.len(), 4);
+        assert_eq!(data.signed_attestations.len(), 5);
+        assert_eq!(data.signed_blocks[3].signing_root, Some([50; 32]));
+
+        assert_eq!(
+            data.get_latest_signed_block_slot(),
+            before.get_latest_signed_block_slot()
+        );
+        assert_eq!(
+            data.get_latest_signed_attestation_epochs(),
+            before.get_latest_signed_attestation_epochs()
+        );
+        for slot in 0..60 {
+            assert_eq!(
+                data.is_slashable_block_slot(slot),
+                before.is_slashable_block_slot(slot)
+            );
+        }
+        for src in 0..40 {
+            for tgt in 0..40 {
+                assert_eq!(
+                    data.is_slashable_attestation_epochs(src, tgt),
+                    before.is_slashable_attestation_epochs(src, tgt)
+                );
+            }
+        }
+
+        // Pruning again has nothing left to remove
+        assert_eq!(data.prune(4), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_slashing_floor() {
+        let floor = SlashingFloor {
+            min_slot: 100,
+            min_epoch: 10,
+        };
+        assert!(floor.is_below_block_slot(99));
+        assert!(!floor.is_below_block_slot(100));
+        assert!(floor.is_below_attestation_epochs(8, 9));
+        assert!(!floor.is_below_attestation_epochs(9, 10));
+        assert!(!SlashingFloor::default().is_below_block_slot(0));
+    }
+
+    #[test]
+    fn test_slot_clock() {
+        let clock = SlotClock {
+            genesis_time: 1000,
+            seconds_per_slot: 12,
+            max_future_slots: 2,
+        };
+        // Before genesis everything is measured from slot 0
+        assert_eq!(clock.slot_at(0), 0);
+        assert!(!clock.is_far_future_slot(2, 0));
+        assert!(clock.is_far_future_slot(3, 0));
+
+        let now = 1000 + 12 * 100 + 5;
+        assert_eq!(clock.slot_at(now), 100);
+        assert!(!clock.is_far_future_slot(102, now));
+        assert!(clock.is_far_future_slot(103, now));
+        assert!(clock.is_far_future_slot(1 << 62, now));
+        // Epoch 3 starts at slot 96 and epoch 4 at slot 128
+        assert!(!clock.is_far_future_epoch(3, now));
+        assert!(clock.is_far_future_epoch(4, now));
+        assert!(clock.is_far_future_epoch(u64::MAX, now));
+    }
+
+    #[test]
+    fn test_import_identifies_malformed_record() {
+        let raw = dummy_slash_protection_data().replace(r#""slot": "81951""#, r#""slot": "abc""#);
+        let err = SlashingProtectionDB::from_interchange_str(&raw).unwrap_err();
+        assert!(format!("{:?}", err).contains("data[0]"));
+
+        let raw = dummy_slash_protection_data().replace(
+            r#""source_epoch": "2290",
+                    "target_epoch": "3008""#,
+            r#""source_epoch": "4000",
+                    "target_epoch": "3008""#,
+        );
+        let err = SlashingProtectionDB::from_interchange_str(&raw).unwrap_err();
+        assert!(format!("{:?}", err).contains("signed_attestations[1]"));
+
+        let raw = dummy_slash_protection_data().replace(r#""5""#, r#""4""#);
+        assert!(SlashingProtectionDB::from_interchange_str(&raw).is_err());
+    }
+
     #[test]
     fn test_blocks() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_blocks");
         let pk = BLSPubkey::default();
         let mut data = SlashingProtectionData::new(pk.clone());
         assert_eq!(data.signed_blocks.len(), 0);
@@ -395,10 +1125,10 @@ pub mod test_slash_protection {
         assert_eq!(data.get_latest_signed_block_slot(), 5000);
 
         // Write the protection (serialization)
-        data.write()?;
+        FileBackend.write(&data)?;
 
         // Read the protection (deserialization)
-        let d = SlashingProtectionData::read(&hex::encode(pk.as_ssz_bytes()))?;
+        let d = FileBackend.read(&hex::encode(pk.as_ssz_bytes()))?;
         assert_eq!(d.signed_blocks.len(), 3);
         assert_eq!(d.signed_blocks[0].slot, 11);
         assert_eq!(d.signed_blocks[1].slot, 12);
@@ -417,6 +1147,7 @@ pub mod test_slash_protection {
 
     #[test]
     fn test_attestations() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_attestations");
         let pk = BLSPubkey::default();
         let mut data = SlashingProtectionData::new(pk.clone());
         assert_eq!(data.signed_blocks.len(), 0);
@@ -531,10 +1262,10 @@ pub mod test_slash_protection {
         assert_eq!(data.get_latest_signed_attestation_epochs(), (20, 31));
 
         // Write the protection (serialization)
-        data.write()?;
+        FileBackend.write(&data)?;
 
         // Read the protection (deserialization)
-        let d = SlashingProtectionData::read(&hex::encode(pk.as_ssz_bytes()))?;
+        let d = FileBackend.read(&hex::encode(pk.as_ssz_bytes()))?;
         assert_eq!(d.signed_attestations.len(), 2);
         assert_eq!(d.signed_attestations[0].source_epoch, 20);
         assert_eq!(d.signed_attestations[0].target_epoch, 30);
@@ -551,4 +1282,72 @@ pub mod test_slash_protection {
 
         Ok(())
     }
+
+    fn history(attestations: &[(Epoch, Epoch)]) -> SlashingProtectionData {
+        let mut data = SlashingProtectionData::new(BLSPubkey::default());
+        for (src, tgt) in attestations {
+            data.signed_attestations.push(SignedAttestationEpochs {
+                source_epoch: *src,
+                target_epoch: *tgt,
+                signing_root: None,
+            });
+        }
+        data
+    }
+
+    #[test]
+    fn test_eip3076_surround_votes() {
+        // Modelled on the EIP-3076 slashing-protection-interchange-tests named above each case, as
+        // (history, attestation, slashable)
+        let cases: Vec<(Vec<(Epoch, Epoch)>, (Epoch, Epoch), bool)> = vec![
+            // single_validator_single_attestation_signing
+            (vec![(0, 3)], (1, 4), false),
+            // single_validator_slashable_attestations_double_vote
+            (vec![(2, 3)], (2, 3), true),
+            (vec![(2, 3)], (1, 3), true),
+            // single_validator_slashable_attestations_surrounds_existing
+            (vec![(2, 3)], (0, 4), true),
+            // single_validator_slashable_attestations_surrounded_by_existing
+            (vec![(2, 6)], (3, 5), true),
+            (vec![(2, 6)], (2, 5), true),
+            // single_validator_source_greater_than_target
+            (vec![], (8, 7), true),
+            (vec![(0, 3)], (8, 7), true),
+            // multiple_interchanges_single_validator_multiple_blocks_out_of_order, attestations
+            (vec![(0, 1), (1, 2), (5, 10)], (4, 11), true),
+            (vec![(0, 1), (1, 2), (5, 10)], (5, 11), false),
+            // single_validator_source_greater_than_target_surrounding
+            (vec![(5, 2)], (3, 4), true),
+            // single_validator_source_greater_than_target_surrounded
+            (vec![(5, 2)], (6, 1), true),
+            (vec![(5, 2)], (6, 6), false),
+        ];
+
+        for (attestations, (src, tgt), slashable) in cases {
+            let mut data = history(&attestations);
+            assert_eq!(
+                data.is_slashable_attestation_epochs(src, tgt),
+                slashable,
+                "history {:?}, attestation ({src}, {tgt})",
+                attestations
+            );
+            let a = SignedAttestationEpochs {
+                source_epoch: src,
+                target_epoch: tgt,
+                signing_root: None,
+            };
+            assert_eq!(data.new_attestation(a, true).is_err(), slashable);
+        }
+    }
+
+    #[test]
+    fn test_is_surround_vote() {
+        let data = history(&[(10, 20), (30, 40)]);
+        assert!(!data.is_surround_vote(30, 41));
+        assert!(data.is_surround_vote(5, 25));
+        assert!(data.is_surround_vote(12, 18));
+        assert!(!data.is_surround_vote(10, 20));
+        assert!(data.is_slashable_attestation_epochs(30, 40));
+        assert!(!data.is_slashable_attestation_epochs(30, 41));
+    }
 }
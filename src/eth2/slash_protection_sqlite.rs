@@ -0,0 +1,324 @@
+use super::eth_types::Root;
+use super::slash_protection::{
+    SignedAttestationEpochs, SignedBlockSlot, SlashingProtectionBackend, SlashingProtectionData,
+};
+
+use anyhow::{bail, Context, Result};
+use log::debug;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use ssz::Encode;
+use ssz_types::FixedVector;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Mirrors the Lighthouse slashing protection database so that an EIP-3076 interchange file maps
+/// one to one onto its rows. Unlike Lighthouse, a missing signing root is stored as NULL.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS validators (
+        id INTEGER PRIMARY KEY,
+        public_key BLOB NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS signed_blocks (
+        validator_id INTEGER NOT NULL,
+        slot INTEGER NOT NULL,
+        signing_root BLOB,
+        FOREIGN KEY(validator_id) REFERENCES validators(id),
+        UNIQUE (validator_id, slot)
+    );
+    CREATE TABLE IF NOT EXISTS signed_attestations (
+        validator_id INTEGER NOT NULL,
+        source_epoch INTEGER NOT NULL,
+        target_epoch INTEGER NOT NULL,
+        signing_root BLOB,
+        FOREIGN KEY(validator_id) REFERENCES validators(id),
+        UNIQUE (validator_id, target_epoch)
+    );
+";
+
+/// Saves every key's history in one SQLite database
+pub struct SqliteBackend {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteBackend {
+    pub fn open(db_path: &Path) -> Result<Self> {
+        if let Some(p) = db_path.parent() {
+            fs::create_dir_all(p).with_context(|| "Failed to create slashing dir")?
+        };
+        let conn = Connection::open(db_path)
+            .with_context(|| format!("Failed to open slashing protection db {:?}", db_path))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| "Failed to create slashing protection tables")?;
+        Ok(SqliteBackend {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        match self.conn.lock() {
+            Ok(conn) => Ok(conn),
+            Err(_) => bail!("Slashing protection db lock poisoned"),
+        }
+    }
+}
+
+fn pk_bytes(pk_hex: &str) -> Result<Vec<u8>> {
+    crate::parse_hex(pk_hex)
+}
+
+/// SQLite integers are signed, so values past i64::MAX are refused rather than wrapped
+fn to_sql_u64(v: u64) -> Result<i64> {
+    i64::try_from(v).with_context(|| format!("{v} is too large for the slashing protection db"))
+}
+
+fn to_root(root: Option<Vec<u8>>) -> Result<Option<Root>> {
+    match root {
+        Some(r) => match r.try_into() {
+            Ok(r) => Ok(Some(r)),
+            Err(r) => bail!("Bad signing_root in slashing protection db: {:?}", r),
+        },
+        None => Ok(None),
+    }
+}
+
+fn validator_id(conn: &Connection, pk: &[u8]) -> Result<Option<i64>> {
+    Ok(conn
+        .query_row(
+            "SELECT id FROM validators WHERE public_key = ?1",
+            params![pk],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn load(conn: &Connection, pk: &[u8]) -> Result<Option<SlashingProtectionData>> {
+    let id = match validator_id(conn, pk)? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let mut data = SlashingProtectionData::new(FixedVector::from(pk.to_vec()));
+
+    let mut stmt = conn.prepare(
+        "SELECT slot, signing_root FROM signed_blocks WHERE validator_id = ?1 ORDER BY slot",
+    )?;
+    let rows = stmt.query_map(params![id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, Option<Vec<u8>>>(1)?))
+    })?;
+    for row in rows {
+        let (slot, signing_root) = row?;
+        data.signed_blocks.push(SignedBlockSlot {
+            slot: slot as u64,
+            signing_root: to_root(signing_root)?,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT source_epoch, target_epoch, signing_root FROM signed_attestations
+         WHERE validator_id = ?1 ORDER BY target_epoch",
+    )?;
+    let rows = stmt.query_map(params![id], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, Option<Vec<u8>>>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (source_epoch, target_epoch, signing_root) = row?;
+        data.signed_attestations.push(SignedAttestationEpochs {
+            source_epoch: source_epoch as u64,
+            target_epoch: target_epoch as u64,
+            signing_root: to_root(signing_root)?,
+        });
+    }
+    debug!("Reading Slash Protection DB:\n{:#?}", data);
+    Ok(Some(data))
+}
+
+/// Replaces the key's rows with `data`. Callers provide the transaction.
+fn store(conn: &Connection, data: &SlashingProtectionData) -> Result<()> {
+    let pk = data.pubkey.as_ssz_bytes();
+    conn.execute(
+        "INSERT OR IGNORE INTO validators (public_key) VALUES (?1)",
+        params![pk],
+    )?;
+    let id = validator_id(conn, &pk)?.with_context(|| "Failed to register validator")?;
+
+    conn.execute(
+        "DELETE FROM signed_blocks WHERE validator_id = ?1",
+        params![id],
+    )?;
+    for b in data.signed_blocks.iter() {
+        conn.execute(
+            "INSERT INTO signed_blocks (validator_id, slot, signing_root) VALUES (?1, ?2, ?3)",
+            params![id, to_sql_u64(b.slot)?, b.signing_root.map(|r| r.to_vec())],
+        )?;
+    }
+
+    conn.execute(
+        "DELETE FROM signed_attestations WHERE validator_id = ?1",
+        params![id],
+    )?;
+    for a in data.signed_attestations.iter() {
+        conn.execute(
+            "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                to_sql_u64(a.source_epoch)?,
+                to_sql_u64(a.target_epoch)?,
+                a.signing_root.map(|r| r.to_vec())
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+impl SlashingProtectionBackend for SqliteBackend {
+    fn exists(&self, pk_hex: &str) -> bool {
+        let (Ok(pk), Ok(conn)) = (pk_bytes(pk_hex), self.lock()) else {
+            return false;
+        };
+        matches!(validator_id(&conn, &pk), Ok(Some(_)))
+    }
+
+    fn read(&self, pk_hex: &str) -> Result<SlashingProtectionData> {
+        let pk = pk_bytes(pk_hex)?;
+        let conn = self.lock()?;
+        load(&conn, &pk)?.with_context(|| format!("No slashing protection data for {pk_hex}"))
+    }
+
+    fn write(&self, data: &SlashingProtectionData) -> Result<()> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        store(&tx, data)?;
+        tx.commit()
+            .with_context(|| "failed to write protection data")
+    }
+
+    fn pk_hexes(&self) -> Result<Vec<String>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT public_key FROM validators ORDER BY id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?;
+        let mut pks = vec![];
+        for row in rows {
+            pks.push(hex::encode(row?));
+        }
+        Ok(pks)
+    }
+
+    fn update(
+        &self,
+        pk_hex: &str,
+        f: &mut dyn FnMut(&mut SlashingProtectionData) -> Result<bool>,
+    ) -> Result<bool> {
+        let pk = pk_bytes(pk_hex)?;
+        let mut conn = self.lock()?;
+        // Take the write lock up front so another process cannot sign between the read and write
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let mut data =
+            load(&tx, &pk)?.with_context(|| format!("No slashing protection data for {pk_hex}"))?;
+        // Dropping the transaction without committing rolls it back
+        if !f(&mut data)? {
+            return Ok(false);
+        }
+        store(&tx, &data)?;
+        tx.commit()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth2::slash_protection::SlashingProtectionDB;
+    use crate::io::data_dir::{slashing_protection_sqlite_path, ScopedDataDir};
+
+    const PK_HEX: &str = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+
+    fn fresh_backend() -> Result<SqliteBackend> {
+        SqliteBackend::open(&slashing_protection_sqlite_path())
+    }
+
+    #[test]
+    fn test_sqlite_round_trips_interchange() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_sqlite_round_trips_interchange");
+        let backend = fresh_backend()?;
+        assert!(!backend.exists(PK_HEX));
+
+        let raw =
+            crate::eth2::slash_protection::test_slash_protection::dummy_slash_protection_data();
+        let db = SlashingProtectionDB::from_str(&raw)?;
+        backend.write(&db.data[0])?;
+        assert!(backend.exists(&format!("0x{PK_HEX}")));
+        assert_eq!(backend.pk_hexes()?, vec![PK_HEX.to_string()]);
+
+        let data = backend.read(PK_HEX)?;
+        assert_eq!(data.pubkey.as_ssz_bytes(), db.data[0].pubkey.as_ssz_bytes());
+        // Rows come back ordered by slot and target epoch
+        assert_eq!(data.signed_blocks[0].slot, 81951);
+        assert!(data.signed_blocks[0].signing_root.is_none());
+        assert_eq!(data.signed_blocks[1].slot, 81952);
+        assert_eq!(
+            data.signed_blocks[1].signing_root,
+            db.data[0].signed_blocks[0].signing_root
+        );
+        assert_eq!(data.get_latest_signed_attestation_epochs(), (2290, 3008));
+        assert_eq!(
+            data.signed_attestations[0].signing_root,
+            db.data[0].signed_attestations[0].signing_root
+        );
+
+        // Unknown keys are errors, not empty histories
+        assert!(backend.read(&"ab".repeat(48)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sqlite_update_only_commits_accepted_msgs() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_sqlite_update_only_commits_accepted_msgs");
+        let backend = fresh_backend()?;
+        backend.write(&SlashingProtectionData::from_pk_hex(&PK_HEX.to_string())?)?;
+
+        let record = |slot| {
+            backend.update(PK_HEX, &mut |db: &mut SlashingProtectionData| {
+                if db.is_slashable_block_slot(slot) {
+                    return Ok(false);
+                }
+                db.new_block(
+                    SignedBlockSlot {
+                        slot,
+                        signing_root: Some([7; 32]),
+                    },
+                    false,
+                )?;
+                Ok(true)
+            })
+        };
+        assert!(record(10)?);
+        assert!(!record(10)?);
+        assert!(!record(9)?);
+        assert!(record(11)?);
+
+        let data = backend.read(PK_HEX)?;
+        assert_eq!(data.signed_blocks.len(), 1);
+        assert_eq!(data.get_latest_signed_block_slot(), 11);
+
+        // A failing update leaves the history untouched
+        assert!(backend
+            .update(PK_HEX, &mut |db: &mut SlashingProtectionData| {
+                db.signed_blocks.clear();
+                bail!("boom")
+            })
+            .is_err());
+        assert_eq!(backend.read(PK_HEX)?.get_latest_signed_block_slot(), 11);
+
+        // Slots that do not fit in an SQLite integer are refused
+        let mut data = backend.read(PK_HEX)?;
+        data.signed_blocks[0].slot = u64::MAX;
+        assert!(backend.write(&data).is_err());
+        assert_eq!(backend.read(PK_HEX)?.get_latest_signed_block_slot(), 11);
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use crate::constants::DEFAULT_DATA_DIR;
+use anyhow::{bail, Result};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+thread_local! {
+    /// Takes precedence over DATA_DIR on its thread, see `ScopedDataDir`
+    static SCOPED_DATA_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Sets the directory everything Secure-Signer saves lives under. Must be called before the
+/// first key or slashing protection history is read or saved.
+pub fn set_data_dir(dir: impl Into<PathBuf>) -> Result<()> {
+    if DATA_DIR.set(dir.into()).is_err() {
+        bail!("Data directory already set");
+    }
+    Ok(())
+}
+
+/// The directory everything Secure-Signer saves lives under, `./etc/` unless set otherwise
+pub fn data_dir() -> PathBuf {
+    SCOPED_DATA_DIR
+        .with(|scoped| scoped.borrow().clone())
+        .unwrap_or_else(|| {
+            DATA_DIR
+                .get_or_init(|| PathBuf::from(DEFAULT_DATA_DIR))
+                .clone()
+        })
+}
+
+pub fn keys_dir() -> PathBuf {
+    data_dir().join("keys")
+}
+
+pub fn bls_keys_dir() -> PathBuf {
+    keys_dir().join("bls_keys")
+}
+
+pub fn eth_keys_dir() -> PathBuf {
+    keys_dir().join("eth_keys")
+}
+
+pub fn remote_keys_dir() -> PathBuf {
+    keys_dir().join("remote_keys")
+}
+
+pub fn threshold_keys_dir() -> PathBuf {
+    keys_dir().join("threshold_keys")
+}
+
+pub fn derivation_paths_dir() -> PathBuf {
+    keys_dir().join("derivation_paths")
+}
+
+pub fn bls_metadata_dir() -> PathBuf {
+    keys_dir().join("bls_metadata")
+}
+
+pub fn eth_metadata_dir() -> PathBuf {
+    keys_dir().join("eth_metadata")
+}
+
+pub fn bls_seed_path() -> PathBuf {
+    keys_dir().join("bls_seed")
+}
+
+pub fn bls_seed_index_path() -> PathBuf {
+    keys_dir().join("bls_seed_index")
+}
+
+pub fn vault_key_path() -> PathBuf {
+    keys_dir().join("vault_key")
+}
+
+pub fn vault_password_path() -> PathBuf {
+    keys_dir().join("vault_password")
+}
+
+pub fn slashing_protection_dir() -> PathBuf {
+    data_dir().join("slashing")
+}
+
+pub fn slashing_protection_sqlite_path() -> PathBuf {
+    data_dir().join("slashing.sqlite")
+}
+
+pub fn validator_config_dir() -> PathBuf {
+    data_dir().join("validator_config")
+}
+
+pub fn workers_dir() -> PathBuf {
+    data_dir().join("workers")
+}
+
+/// Points `data_dir` at a directory of its own on the current thread until dropped, then
+/// removes it. Lets tests run in parallel without clobbering each other's keys. Anything run on
+/// another thread, e.g. by `spawn_blocking`, still sees the process wide directory unless it
+/// enters the same one with `ScopedDataDir::enter`.
+pub struct ScopedDataDir {
+    dir: PathBuf,
+    previous: Option<PathBuf>,
+    /// Only the scope that created `dir` removes it
+    owned: bool,
+}
+
+impl ScopedDataDir {
+    /// A fresh directory named after `name` under the system temp dir
+    pub fn temp(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("secure-signer-{name}-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let previous = SCOPED_DATA_DIR.with(|scoped| scoped.replace(Some(dir.clone())));
+        ScopedDataDir {
+            dir,
+            previous,
+            owned: true,
+        }
+    }
+
+    /// Points `data_dir` at an existing `dir` on the current thread until dropped, for threads a
+    /// test hands its work to. Leaves `dir` in place.
+    pub fn enter(dir: &Path) -> Self {
+        let previous = SCOPED_DATA_DIR.with(|scoped| scoped.replace(Some(dir.to_path_buf())));
+        ScopedDataDir {
+            dir: dir.to_path_buf(),
+            previous,
+            owned: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for ScopedDataDir {
+    fn drop(&mut self) {
+        SCOPED_DATA_DIR.with(|scoped| scoped.replace(self.previous.take()));
+        if self.owned {
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_data_dir_is_per_thread() {
+        let outer = data_dir();
+        {
+            let scoped = ScopedDataDir::temp("test_scoped_data_dir");
+            assert_eq!(data_dir(), scoped.path());
+            assert_eq!(bls_keys_dir(), scoped.path().join("keys/bls_keys"));
+            std::fs::create_dir_all(bls_keys_dir()).unwrap();
+
+            let other_thread = std::thread::spawn(data_dir).join().unwrap();
+            assert_eq!(other_thread, outer);
+
+            // Another thread can enter it, and leaving does not remove it
+            let dir = scoped.path().to_path_buf();
+            let entered = std::thread::spawn(move || {
+                let _entered = ScopedDataDir::enter(&dir);
+                data_dir()
+            })
+            .join()
+            .unwrap();
+            assert_eq!(entered, scoped.path());
+            assert!(bls_keys_dir().exists());
+        }
+        assert_eq!(data_dir(), outer);
+        assert!(!std::env::temp_dir()
+            .join(format!(
+                "secure-signer-test_scoped_data_dir-{}",
+                std::process::id()
+            ))
+            .exists());
+    }
+}
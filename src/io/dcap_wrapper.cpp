@@ -0,0 +1,38 @@
+#include <string.h>
+#include <stdio.h>
+
+#include "occlum_dcap.h"
+
+
+extern "C"
+int do_dcap_quote(uint8_t data[64], uint8_t * quote, uint32_t * quote_len) {
+  // 64 Byte report data to embed in the ECDSA quote
+  sgx_report_data_t report_data = {0};
+  for (int i = 0; i < 64; ++i) {
+      report_data.d[i] = data[i];
+  }
+
+  void * handle = dcap_quote_open();
+  if (handle == NULL) {
+    printf("Fail to open the DCAP quote device!\n");
+    return -1;
+  }
+
+  uint32_t quote_size = dcap_get_quote_size(handle);
+  if (quote_size == 0 || quote_size > *quote_len) {
+    printf("Bad DCAP quote size %u for a buffer of %u!\n", quote_size, *quote_len);
+    dcap_quote_close(handle);
+    return -1;
+  }
+
+  int ret = dcap_generate_quote(handle, quote, &report_data);
+  dcap_quote_close(handle);
+  if (ret) {
+    printf("Fail to generate the DCAP quote, error code is %x!\n", ret);
+    return ret;
+  }
+
+  // Report the written length back to the caller's buffer
+  *quote_len = quote_size;
+  return 0;
+}
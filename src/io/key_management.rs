@@ -1,97 +1,311 @@
-use crate::constants::{BLS_KEYS_DIR, ETH_KEYS_DIR};
+use crate::constants::DEFAULT_MAX_KEYS;
+use crate::io::data_dir::{
+    bls_keys_dir, bls_seed_index_path, bls_seed_path, derivation_paths_dir, eth_keys_dir,
+};
+use crate::io::key_vault::{is_sealed, vault, KeyVault};
 use crate::strip_0x_prefix;
 use anyhow::{bail, Context, Result};
 
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use zeroize::Zeroizing;
+
+static MAX_KEYS: OnceLock<usize> = OnceLock::new();
+/// Held from counting the saved keys until a new one is written, so concurrent keygens cannot
+/// overshoot the quota
+static QUOTA_LOCK: Mutex<()> = Mutex::new(());
+
+/// Caps how many keys of each kind, BLS or ETH, may be saved. Must be called before the first
+/// key is saved.
+pub fn set_max_keys(max_keys: usize) -> Result<()> {
+    if MAX_KEYS.set(max_keys).is_err() {
+        bail!("Key quota already set");
+    }
+    Ok(())
+}
+
+/// Returns how many keys of each kind may be saved
+pub fn max_keys() -> usize {
+    *MAX_KEYS.get_or_init(|| DEFAULT_MAX_KEYS)
+}
+
+fn lock_quota() -> Result<MutexGuard<'static, ()>> {
+    QUOTA_LOCK
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Key quota lock poisoned"))
+}
+
+/// Errors if saving `new_keys` more keys in `path_to_dir` would exceed `limit`
+fn check_quota(path_to_dir: &Path, kind: &str, new_keys: usize, limit: usize) -> Result<()> {
+    let count = count_keys(path_to_dir);
+    if count + new_keys > limit {
+        bail!("Key quota exceeded: {count} of at most {limit} {kind} keys are saved, cannot save {new_keys} more")
+    }
+    Ok(())
+}
+
+/// Errors if saving `new_keys` more BLS keys would exceed the key quota
+pub fn check_bls_key_quota(new_keys: usize) -> Result<()> {
+    check_quota(&bls_keys_dir(), "BLS", new_keys, max_keys())
+}
+
+/// Errors if saving `new_keys` more ETH keys would exceed the key quota
+pub fn check_eth_key_quota(new_keys: usize) -> Result<()> {
+    check_quota(&eth_keys_dir(), "ETH", new_keys, max_keys())
+}
 
-// Writes the sk_hex string to the specified path
+// Seals the sk_hex string and writes it to the specified path
 fn write_key(file_path: PathBuf, sk_hex: &str) -> Result<()> {
     if let Some(p) = file_path.parent() {
         fs::create_dir_all(p).with_context(|| "Failed to create keys dir")?
     };
-    fs::write(&file_path, sk_hex).with_context(|| "failed to write sk")
+    let sealed = vault().seal(sk_hex.as_bytes())?;
+
+    // Write to a hidden file first so an existing key is never left half overwritten
+    let fname = file_path
+        .file_name()
+        .with_context(|| "Bad key path")?
+        .to_string_lossy();
+    let tmp_path = file_path.with_file_name(format!(".{fname}.tmp"));
+    fs::write(&tmp_path, sealed).with_context(|| "failed to write sk")?;
+    fs::rename(&tmp_path, &file_path).with_context(|| "failed to write sk")
+}
+
+/// Key files are named from the lowercase hex public key without the `0x` prefix, whichever form
+/// the caller supplied
+pub fn key_fname(pk_hex: &str) -> String {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    pk_hex.to_ascii_lowercase()
 }
 
 /// Writes the hex-encoded ETH secret key to a file named from `fname`
 pub fn write_eth_key(pk_hex: &String, sk_hex: &String) -> Result<()> {
     // Sanitize inputs
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let pk_hex: &str = &key_fname(pk_hex);
     let sk_hex: &str = strip_0x_prefix!(sk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
-    write_key(file_path, sk_hex)
+    let file_path: PathBuf = eth_keys_dir().join(pk_hex);
+    let _guard = lock_quota()?;
+    if !key_exists(&file_path) {
+        check_eth_key_quota(1)?;
+    }
+    write_key(file_path, sk_hex)?;
+    crate::io::remote_attestation::evict_cached_evidence(pk_hex);
+    Ok(())
 }
 
 /// Writes the hex-encoded BLS secret key to a file named from `fname`
 pub fn write_bls_key(pk_hex: &String, sk_hex: &String) -> Result<()> {
     // Sanitize inputs
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let pk_hex: &str = &key_fname(pk_hex);
     let sk_hex: &str = strip_0x_prefix!(sk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
-    write_key(file_path, sk_hex)
+    let file_path: PathBuf = bls_keys_dir().join(pk_hex);
+    let _guard = lock_quota()?;
+    if !key_exists(&file_path) {
+        check_bls_key_quota(1)?;
+    }
+    write_key(file_path, sk_hex)?;
+    crate::crypto::bls_keys::evict_cached_bls_key(pk_hex);
+    crate::io::remote_attestation::evict_cached_evidence(pk_hex);
+    Ok(())
 }
 
 /// Writes the BLS secret key to a keystore file
 pub fn write_bls_keystore(pk_hex: &String, sk: &[u8], password: &String) -> Result<String> {
     // Create the keys dir if it does not exist
-    fs::create_dir_all(BLS_KEYS_DIR).with_context(|| "Failed to create keys dir")?;
+    fs::create_dir_all(bls_keys_dir()).with_context(|| "Failed to create keys dir")?;
 
     // Sanitize inputs
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let pk_hex: &str = &key_fname(pk_hex);
     let mut rng = rand::thread_rng();
 
+    let _guard = lock_quota()?;
+    if !bls_key_exists(pk_hex) {
+        check_bls_key_quota(1)?;
+    }
+
     // Create encrypted keystore
-    let uuid = eth_keystore::encrypt_key(BLS_KEYS_DIR, &mut rng, sk, password, Some(pk_hex))?;
+    let uuid = eth_keystore::encrypt_key(bls_keys_dir(), &mut rng, sk, password, Some(pk_hex))?;
+    crate::crypto::bls_keys::evict_cached_bls_key(pk_hex);
+    crate::io::remote_attestation::evict_cached_evidence(pk_hex);
     Ok(uuid)
 }
 
-/// Reads hex-encoded secret key from the specified path and returns the hex-decoded bytes
+/// Writes the hex-encoded seed that BLS keys are derived from
+pub fn write_bls_seed(seed: &[u8]) -> Result<()> {
+    write_key(bls_seed_path(), &hex::encode(seed))
+}
+
+/// Persists the index of the next key to derive deterministically from the seed. A crash leaves
+/// either the old or the new index on disk.
+pub fn write_bls_seed_index(index: u32) -> Result<()> {
+    let file_path = bls_seed_index_path();
+    if let Some(p) = file_path.parent() {
+        fs::create_dir_all(p).with_context(|| "Failed to create keys dir")?
+    };
+    crate::eth2::slash_protection::write_atomic(&file_path, index.to_string().as_bytes())
+}
+
+/// Records the EIP-2334 path a BLS key was derived at
+pub fn write_derivation_path(pk_hex: &str, path: &str) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = derivation_paths_dir().join(pk_hex);
+    if let Some(p) = file_path.parent() {
+        fs::create_dir_all(p).with_context(|| "Failed to create derivation paths dir")?
+    };
+    fs::write(&file_path, path).with_context(|| "failed to write derivation path")
+}
+
+/// Unseals the hex-encoded secret key at the specified path and returns the hex-decoded bytes.
+/// Keys saved in plaintext by older versions are sealed in place on first read.
 fn read_key(file_path: PathBuf) -> Result<Vec<u8>> {
-    let sk_rec_bytes = fs::read(&file_path).with_context(|| "Unable to read secret key")?;
-    hex::decode(sk_rec_bytes).with_context(|| "Unable to hex-decode secret key")
+    let saved = Zeroizing::new(fs::read(&file_path).with_context(|| "Unable to read secret key")?);
+    if is_sealed(&saved) {
+        let sk_hex = Zeroizing::new(vault().unseal(&saved)?);
+        return hex::decode(&*sk_hex).with_context(|| "Unable to hex-decode secret key");
+    }
+    let sk = hex::decode(&*saved).with_context(|| "Unable to hex-decode secret key")?;
+    let sk_hex = std::str::from_utf8(&saved).with_context(|| "Bad plaintext key")?;
+    write_key(file_path, sk_hex).with_context(|| "Failed to seal plaintext key")?;
+    Ok(sk)
+}
+
+/// Reseals every saved secret with the current vault, whether it was saved in plaintext or
+/// sealed by `previous`. Keystores are already encrypted and left as they are. Returns the
+/// number of resealed secrets.
+pub fn reseal_keys(previous: &dyn KeyVault) -> Result<usize> {
+    let mut file_paths: Vec<PathBuf> = vec![bls_seed_path()];
+    for dir in [bls_keys_dir(), eth_keys_dir()] {
+        if let Ok(fnames) = list_fnames(&dir) {
+            file_paths.extend(fnames.iter().map(|f| dir.join(f)));
+        }
+    }
+
+    let mut resealed = 0;
+    for file_path in file_paths {
+        if !key_exists(&file_path) {
+            continue;
+        }
+        let saved =
+            Zeroizing::new(fs::read(&file_path).with_context(|| "Unable to read secret key")?);
+        let sk_hex = if !is_sealed(&saved) {
+            if hex::decode(&*saved).is_err() {
+                continue;
+            }
+            saved
+        } else if vault().unseal(&saved).is_ok() {
+            continue;
+        } else {
+            Zeroizing::new(previous.unseal(&saved).with_context(|| {
+                format!(
+                    "Unable to unseal {:?} with either vault",
+                    file_path.as_os_str()
+                )
+            })?)
+        };
+        let sk_hex = std::str::from_utf8(&sk_hex).with_context(|| "Bad secret key")?;
+        write_key(file_path, sk_hex)?;
+        resealed += 1;
+    }
+    Ok(resealed)
 }
 
 /// Reads hex-encoded ETH secret key from a file named from `pk_hex` and returns the bytes
 pub fn read_eth_key(pk_hex: &str) -> Result<Vec<u8>> {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = eth_keys_dir().join(pk_hex);
     read_key(file_path)
 }
 
 /// Reads hex-encoded BLS secret key from a file named from `pk_hex` and returns the bytes
 pub fn read_bls_key(pk_hex: &str) -> Result<Vec<u8>> {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = bls_keys_dir().join(pk_hex);
     read_key(file_path)
 }
 
+/// Reads the seed that BLS keys are derived from, if one was created
+pub fn read_bls_seed() -> Result<Option<Vec<u8>>> {
+    let file_path = bls_seed_path();
+    if !key_exists(&file_path) {
+        return Ok(None);
+    }
+    Ok(Some(read_key(file_path)?))
+}
+
+/// Reads the index of the next key to derive deterministically from the seed, 0 if none was
+/// derived yet
+pub fn read_bls_seed_index() -> Result<u32> {
+    match fs::read_to_string(bls_seed_index_path()) {
+        Ok(index) => index
+            .trim()
+            .parse()
+            .with_context(|| "Corrupt bls seed index"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e).with_context(|| "Unable to read bls seed index"),
+    }
+}
+
+/// Reads the EIP-2334 path a BLS key was derived at, or None if it was not derived
+pub fn read_derivation_path(pk_hex: &str) -> Option<String> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = derivation_paths_dir().join(pk_hex);
+    fs::read_to_string(file_path).ok()
+}
+
 /// Reads BLS secret key from encrypted keystore
 pub fn read_bls_keystore(pk_hex: &String, password: &String) -> Result<Vec<u8>> {
     // Sanitize inputs
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = bls_keys_dir().join(pk_hex);
     let sk_bytes = eth_keystore::decrypt_key(file_path, password)?;
     Ok(sk_bytes)
 }
 
-/// Deletes the secret key saved at the specified path
+/// Overwrites the secret key saved at the specified path with zeros before deleting it
 fn delete_key(file_path: PathBuf) -> Result<()> {
+    let len = fs::metadata(&file_path)
+        .with_context(|| format!("failed to find key at: {:?}", file_path.as_os_str()))?
+        .len();
+
+    // Zero-fill the key material so it is not left behind in the freed blocks
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(&file_path)
+        .with_context(|| "failed to open key for overwriting")?;
+    file.write_all(&vec![0_u8; len as usize])
+        .with_context(|| "failed to overwrite key")?;
+    file.sync_all()
+        .with_context(|| "failed to sync overwritten key")?;
+    drop(file);
+
     fs::remove_file(&file_path)
         .with_context(|| format!("failed to delete key at: {:?}", file_path.as_os_str()))
 }
 
-/// Deletes the ETH secret key saved at the specified path
+/// Deletes the ETH secret key saved at the specified path, along with its metadata
 pub fn delete_eth_key(pk_hex: &str) -> Result<()> {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
-    delete_key(file_path)
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = eth_keys_dir().join(pk_hex);
+    delete_key(file_path)?;
+    crate::io::remote_attestation::evict_cached_evidence(pk_hex);
+    crate::io::key_metadata::delete_eth_metadata(pk_hex);
+    Ok(())
 }
 
-/// Deletes the BLS secret key saved at the specified path
+/// Deletes the BLS secret key saved at the specified path, along with its derivation path and
+/// metadata
 pub fn delete_bls_key(pk_hex: &str) -> Result<()> {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
-    delete_key(file_path)
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = bls_keys_dir().join(pk_hex);
+    delete_key(file_path)?;
+    crate::crypto::bls_keys::evict_cached_bls_key(pk_hex);
+    crate::io::remote_attestation::evict_cached_evidence(pk_hex);
+    let path_file: PathBuf = derivation_paths_dir().join(pk_hex);
+    fs::remove_file(path_file).ok();
+    crate::io::key_metadata::delete_bls_metadata(pk_hex);
+    Ok(())
 }
 
 /// Return true if the key at the specified path exists
@@ -101,20 +315,20 @@ fn key_exists(file_path: &PathBuf) -> bool {
 
 /// Return true if the ETH key at the specified path exists
 pub fn eth_key_exists(pk_hex: &str) -> bool {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = eth_keys_dir().join(pk_hex);
     key_exists(&file_path)
 }
 
 /// Return true if the BLS key at the specified path exists
 pub fn bls_key_exists(pk_hex: &str) -> bool {
-    let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = bls_keys_dir().join(pk_hex);
     key_exists(&file_path)
 }
 
 /// Return the file names in the specified directory
-fn list_fnames(path_to_dir: &str) -> Result<Vec<String>> {
+fn list_fnames(path_to_dir: &Path) -> Result<Vec<String>> {
     let paths = fs::read_dir(path_to_dir).with_context(|| "No keys saved in dir")?;
 
     let mut keys: Vec<String> = Vec::new();
@@ -129,6 +343,8 @@ fn list_fnames(path_to_dir: &str) -> Result<Vec<String>> {
         let fname = p.file_name();
 
         match fname.to_os_string().into_string() {
+            // Skip keys that are still being written
+            Ok(s) if s.starts_with('.') => continue,
             Ok(s) => keys.push(s),
             Err(e) => bail!("Error, bad file name in list_keys(): {:?}", e),
         }
@@ -139,13 +355,28 @@ fn list_fnames(path_to_dir: &str) -> Result<Vec<String>> {
 /// Returns the file names of each of the saved bls secret keys, where each fname
 /// is assumed to be the compressed public key in hex without the `0x` prefix.
 pub fn list_bls_keys() -> Result<Vec<String>> {
-    list_fnames(BLS_KEYS_DIR)
+    list_fnames(&bls_keys_dir())
+}
+
+/// Returns the number of keys saved in the specified directory, zero if it does not exist
+fn count_keys(path_to_dir: &Path) -> usize {
+    list_fnames(path_to_dir).map(|f| f.len()).unwrap_or(0)
+}
+
+/// Returns the number of saved BLS keys
+pub fn num_bls_keys() -> usize {
+    count_keys(&bls_keys_dir())
+}
+
+/// Returns the number of saved ETH keys
+pub fn num_eth_keys() -> usize {
+    count_keys(&eth_keys_dir())
 }
 
 /// Returns the file names of each of the saved eth secret keys, where each fname
 /// is assumed to be the eth wallet address derived from the eth public key in hex without the `0x` prefix.
 pub fn list_eth_keys() -> Result<Vec<String>> {
-    list_fnames(ETH_KEYS_DIR)
+    list_fnames(&eth_keys_dir())
 }
 
 #[cfg(test)]
@@ -153,58 +384,54 @@ mod test_key_management {
     use hex::FromHex;
 
     use super::*;
-    use crate::constants::KEYS_DIR;
-    use std::path::Path;
-
-    // Helper function to read the content of a file
-    fn read_file(file_path: &Path) -> Result<String> {
-        fs::read_to_string(file_path).with_context(|| "failed to read")
-    }
+    use crate::io::data_dir::{keys_dir, ScopedDataDir};
 
     #[test]
     fn test_write_key() {
-        let file_path: PathBuf = [KEYS_DIR, "test"].iter().collect();
+        let _data_dir = ScopedDataDir::temp("test_write_key");
+        let file_path: PathBuf = keys_dir().join("test");
 
         let sk_hex = "abcdef123456";
 
         write_key(file_path.clone(), sk_hex).unwrap();
 
-        let written_content = read_file(&file_path).unwrap();
-        assert_eq!(written_content, sk_hex);
-        fs::remove_dir_all("./etc").ok();
+        // Only the sealed key is written to disk
+        let written_content = fs::read(&file_path).unwrap();
+        assert!(is_sealed(&written_content));
+        assert_eq!(vault().unseal(&written_content).unwrap(), sk_hex.as_bytes());
     }
 
     #[test]
     fn test_write_eth_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_write_eth_key");
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
         write_eth_key(&pk_hex.to_string(), &sk_hex.to_string()).unwrap();
 
-        let file_path: PathBuf = [ETH_KEYS_DIR, "1234abcd"].iter().collect();
-        let written_content = read_file(&file_path).unwrap();
-        assert_eq!(written_content, "abcdef123456");
-        fs::remove_dir_all("./etc").ok();
+        let file_path: PathBuf = eth_keys_dir().join("1234abcd");
+        let written_content = fs::read(&file_path).unwrap();
+        assert!(is_sealed(&written_content));
+        assert_eq!(vault().unseal(&written_content).unwrap(), b"abcdef123456");
     }
 
     #[test]
     fn test_write_bls_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_write_bls_key");
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
         write_bls_key(&pk_hex.to_string(), &sk_hex.to_string()).unwrap();
 
-        let file_path: PathBuf = [BLS_KEYS_DIR, "1234abcd"].iter().collect();
-        let written_content = read_file(&file_path).unwrap();
-        assert_eq!(written_content, "abcdef123456");
-        fs::remove_dir_all("./etc").ok();
+        let file_path: PathBuf = bls_keys_dir().join("1234abcd");
+        let written_content = fs::read(&file_path).unwrap();
+        assert!(is_sealed(&written_content));
+        assert_eq!(vault().unseal(&written_content).unwrap(), b"abcdef123456");
     }
 
     #[test]
     fn test_write_read_delete_eth_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_write_read_delete_eth_key");
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
@@ -224,7 +451,7 @@ mod test_key_management {
 
     #[test]
     fn test_write_read_delete_bls_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_write_read_delete_bls_key");
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
@@ -244,7 +471,7 @@ mod test_key_management {
 
     #[test]
     fn test_write_read_delete_bls_keystore() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_write_read_delete_bls_keystore");
         let pk_hex = "a8a1580a80406ccb0a89e1115c92ec1a09994e2ac6341cfddcad5daf75f587244aa6d722b3449a17b0b0b482c1d13215";
         let sk_hex = "4c627588f8040116b75f14fdb55b552612a46a2cd91e65b516defe39d81fc08f";
         let sk_bytes_in = hex::decode(sk_hex).unwrap();
@@ -265,9 +492,103 @@ mod test_key_management {
         assert!(!bls_key_exists(pk_hex));
     }
 
+    #[test]
+    fn test_delete_overwrites_before_unlinking() {
+        let _data_dir = ScopedDataDir::temp("test_delete_overwrites_before_unlinking");
+        let pk_hex = "0x1234abcd";
+        let sk_hex = "0xabcdef123456";
+        write_bls_key(&pk_hex.to_string(), &sk_hex.to_string()).unwrap();
+
+        // Keep a hard link to the file so its contents survive the unlink
+        let file_path: PathBuf = bls_keys_dir().join("1234abcd");
+        let link_path: PathBuf = keys_dir().join("link");
+        fs::hard_link(&file_path, &link_path).unwrap();
+        let sealed_len = fs::read(&file_path).unwrap().len();
+
+        delete_bls_key(pk_hex).unwrap();
+        assert!(!bls_key_exists(pk_hex));
+
+        let remaining = fs::read(&link_path).unwrap();
+        assert_eq!(remaining.len(), sealed_len);
+        assert!(remaining.iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_plaintext_keys_are_sealed_on_first_read() {
+        let _data_dir = ScopedDataDir::temp("test_plaintext_keys_are_sealed_on_first_read");
+        let file_path: PathBuf = bls_keys_dir().join("1234abcd");
+        fs::create_dir_all(bls_keys_dir()).unwrap();
+        fs::write(&file_path, "abcdef123456").unwrap();
+
+        let sk_bytes = read_bls_key("0x1234abcd").unwrap();
+        assert_eq!(sk_bytes, vec![0xab, 0xcd, 0xef, 0x12, 0x34, 0x56]);
+        assert!(is_sealed(&fs::read(&file_path).unwrap()));
+
+        // The migrated key still reads back the same
+        assert_eq!(read_bls_key("0x1234abcd").unwrap(), sk_bytes);
+        assert_eq!(list_bls_keys().unwrap(), vec!["1234abcd".to_string()]);
+    }
+
+    #[test]
+    fn test_plaintext_eth_keys_are_sealed_on_first_read() {
+        let _data_dir = ScopedDataDir::temp("test_plaintext_eth_keys_are_sealed_on_first_read");
+        let (sk, pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
+        let pk_hex = crate::crypto::eth_keys::eth_pk_to_hex(&pk);
+        let file_path: PathBuf = eth_keys_dir().join(key_fname(&pk_hex));
+        fs::create_dir_all(eth_keys_dir()).unwrap();
+        fs::write(&file_path, hex::encode(sk.serialize())).unwrap();
+
+        assert_eq!(read_eth_key(&pk_hex).unwrap(), sk.serialize());
+        let migrated = fs::read(&file_path).unwrap();
+        assert!(is_sealed(&migrated));
+        assert!(!migrated
+            .windows(64)
+            .any(|w| w == hex::encode(sk.serialize()).as_bytes()));
+
+        // Later reads unseal the migrated key rather than migrating it again
+        assert_eq!(read_eth_key(&pk_hex).unwrap(), sk.serialize());
+        assert_eq!(fs::read(&file_path).unwrap(), migrated);
+    }
+
+    #[test]
+    fn test_reseal_keys_from_previous_vault() {
+        let _data_dir = ScopedDataDir::temp("test_reseal_keys_from_previous_vault");
+        let previous = crate::io::key_vault::FileVault::new(keys_dir().join("old_vault_key"));
+
+        // One key sealed by the previous vault, one in plaintext and one keystore
+        fs::create_dir_all(bls_keys_dir()).unwrap();
+        let sealed_path: PathBuf = bls_keys_dir().join("1234abcd");
+        fs::write(&sealed_path, previous.seal(b"abcdef123456").unwrap()).unwrap();
+        let plaintext_path: PathBuf = bls_keys_dir().join("5678ef01");
+        fs::write(&plaintext_path, "deadbeef2468").unwrap();
+        let keystore_path: PathBuf = bls_keys_dir().join("9abc");
+        fs::write(&keystore_path, r#"{"crypto":{}}"#).unwrap();
+        assert!(read_bls_key("1234abcd").is_err());
+
+        assert_eq!(reseal_keys(&previous).unwrap(), 2);
+        assert_eq!(
+            read_bls_key("1234abcd").unwrap(),
+            vec![0xab, 0xcd, 0xef, 0x12, 0x34, 0x56]
+        );
+        assert!(is_sealed(&fs::read(&plaintext_path).unwrap()));
+        assert_eq!(
+            fs::read_to_string(&keystore_path).unwrap(),
+            r#"{"crypto":{}}"#
+        );
+
+        // Nothing is left to reseal
+        assert_eq!(reseal_keys(&previous).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delete_missing_key_fails() {
+        let _data_dir = ScopedDataDir::temp("test_delete_missing_key_fails");
+        assert!(delete_bls_key("0xdeadbeef").is_err());
+    }
+
     #[test]
     fn test_list_eth_keys() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_list_eth_keys");
         let pk_hex1 = "0x1234abcd";
         let sk_hex1 = "0xabcdef123456";
         let pk_hex2 = "0x5678ef01";
@@ -297,7 +618,7 @@ mod test_key_management {
 
     #[test]
     fn test_list_bls_keys() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = ScopedDataDir::temp("test_list_bls_keys");
         let pk_hex1 = "0x1234abcd";
         let sk_hex1 = "0xabcdef123456";
         let pk_hex2 = "0x5678ef01";
@@ -324,4 +645,34 @@ mod test_key_management {
         let bls_keys = list_bls_keys().unwrap();
         assert_eq!(bls_keys.len(), 0);
     }
+
+    #[test]
+    fn test_key_quota() {
+        let _data_dir = ScopedDataDir::temp("test_key_quota");
+        let dir = &keys_dir().join("test_quota");
+
+        // No keys saved yet
+        check_quota(dir, "BLS", 2, 2).unwrap();
+        assert!(check_quota(dir, "BLS", 3, 2).is_err());
+
+        write_key(dir.join("1234abcd"), "abcdef123456").unwrap();
+        check_quota(dir, "BLS", 1, 2).unwrap();
+        let e = check_quota(dir, "BLS", 2, 2).unwrap_err();
+        assert!(e.to_string().contains("1 of at most 2 BLS keys"));
+
+        // Deleting a key frees its quota
+        delete_key(dir.join("1234abcd")).unwrap();
+        check_quota(dir, "BLS", 2, 2).unwrap();
+    }
+
+    #[test]
+    fn test_bls_seed_index_round_trips() {
+        let _data_dir = ScopedDataDir::temp("test_bls_seed_index_round_trips");
+        assert_eq!(read_bls_seed_index().unwrap(), 0);
+        write_bls_seed_index(7).unwrap();
+        assert_eq!(read_bls_seed_index().unwrap(), 7);
+        assert!(!bls_seed_index_path()
+            .with_file_name("bls_seed_index.tmp")
+            .exists());
+    }
 }
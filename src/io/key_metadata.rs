@@ -0,0 +1,263 @@
+use crate::constants::MAX_KEY_LABEL_LEN;
+use crate::io::data_dir::{
+    bls_keys_dir, bls_metadata_dir, derivation_paths_dir, eth_keys_dir, eth_metadata_dir,
+};
+use crate::io::key_management::key_fname;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How a key came to be held by the enclave
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyOrigin {
+    Generated,
+    Derived,
+    Imported,
+    /// The key was saved before metadata was recorded
+    Unknown,
+}
+
+/// Bookkeeping saved per key, next to the key itself. Timestamps are unix seconds.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct KeyMetadata {
+    pub origin: KeyOrigin,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// When the key last produced a signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<u64>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl KeyMetadata {
+    pub fn new(origin: KeyOrigin, label: Option<String>) -> Self {
+        KeyMetadata {
+            origin,
+            created_at: now(),
+            label,
+            last_used: None,
+        }
+    }
+}
+
+/// Labels are 1 to `MAX_KEY_LABEL_LEN` ASCII letters, digits, '-', '_' or '.'
+pub fn validate_label(label: &str) -> Result<()> {
+    if label.is_empty() || label.len() > MAX_KEY_LABEL_LEN {
+        bail!("Label must be 1 to {MAX_KEY_LABEL_LEN} characters long")
+    }
+    if !label
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        bail!("Label may only contain ASCII letters, digits, '-', '_' and '.'")
+    }
+    Ok(())
+}
+
+fn write_metadata(metadata_dir: &Path, pk_hex: &str, metadata: &KeyMetadata) -> Result<()> {
+    fs::create_dir_all(metadata_dir).with_context(|| "Failed to create key metadata dir")?;
+    let file_path: PathBuf = metadata_dir.join(pk_hex);
+    let json = serde_json::to_string(metadata)?;
+    fs::write(&file_path, json).with_context(|| "failed to write key metadata")
+}
+
+/// Reads the key's metadata. Keys saved before metadata was recorded get a record synthesized
+/// from the key file, which is saved so `created_at` stays stable.
+fn read_metadata(metadata_dir: &Path, keys_dir: &Path, pk_hex: &str) -> Result<KeyMetadata> {
+    let file_path: PathBuf = metadata_dir.join(pk_hex);
+    if let Ok(json) = fs::read_to_string(&file_path) {
+        if let Ok(metadata) = serde_json::from_str(&json) {
+            return Ok(metadata);
+        }
+    }
+
+    let key_path: PathBuf = keys_dir.join(pk_hex);
+    let created_at = fs::metadata(&key_path)
+        .with_context(|| format!("No key saved for {pk_hex}"))?
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_else(now);
+    let path_file: PathBuf = derivation_paths_dir().join(pk_hex);
+    let origin = if keys_dir == bls_keys_dir() && path_file.exists() {
+        KeyOrigin::Derived
+    } else {
+        KeyOrigin::Unknown
+    };
+    let metadata = KeyMetadata {
+        origin,
+        created_at,
+        label: None,
+        last_used: None,
+    };
+    write_metadata(metadata_dir, pk_hex, &metadata)?;
+    Ok(metadata)
+}
+
+fn record_use(metadata_dir: &Path, keys_dir: &Path, pk_hex: &str) -> Result<()> {
+    let mut metadata = read_metadata(metadata_dir, keys_dir, pk_hex)?;
+    metadata.last_used = Some(now());
+    write_metadata(metadata_dir, pk_hex, &metadata)
+}
+
+fn set_label(
+    metadata_dir: &Path,
+    keys_dir: &Path,
+    pk_hex: &str,
+    label: Option<String>,
+) -> Result<KeyMetadata> {
+    if let Some(label) = &label {
+        validate_label(label)?;
+    }
+    let mut metadata = read_metadata(metadata_dir, keys_dir, pk_hex)?;
+    metadata.label = label;
+    write_metadata(metadata_dir, pk_hex, &metadata)?;
+    Ok(metadata)
+}
+
+fn delete_metadata(metadata_dir: &Path, pk_hex: &str) {
+    let file_path: PathBuf = metadata_dir.join(pk_hex);
+    fs::remove_file(file_path).ok();
+}
+
+/// Saves the metadata of the BLS key
+pub fn write_bls_metadata(pk_hex: &str, metadata: &KeyMetadata) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    write_metadata(&bls_metadata_dir(), pk_hex, metadata)
+}
+
+/// Saves the metadata of the ETH key, named from the compressed public key like the key file
+pub fn write_eth_metadata(pk_hex: &str, metadata: &KeyMetadata) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    write_metadata(&eth_metadata_dir(), pk_hex, metadata)
+}
+
+/// Reads the metadata of the saved BLS key, synthesizing it for keys saved without one
+pub fn read_bls_metadata(pk_hex: &str) -> Result<KeyMetadata> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    read_metadata(&bls_metadata_dir(), &bls_keys_dir(), pk_hex)
+}
+
+/// Reads the metadata of the saved ETH key, synthesizing it for keys saved without one
+pub fn read_eth_metadata(pk_hex: &str) -> Result<KeyMetadata> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    read_metadata(&eth_metadata_dir(), &eth_keys_dir(), pk_hex)
+}
+
+/// Sets `last_used` of the BLS key to now
+pub fn record_bls_key_use(pk_hex: &str) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    record_use(&bls_metadata_dir(), &bls_keys_dir(), pk_hex)
+}
+
+/// Sets `last_used` of the ETH key to now
+pub fn record_eth_key_use(pk_hex: &str) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    record_use(&eth_metadata_dir(), &eth_keys_dir(), pk_hex)
+}
+
+/// Replaces the label of the saved BLS key, None removes it. Returns the updated metadata.
+pub fn set_bls_label(pk_hex: &str, label: Option<String>) -> Result<KeyMetadata> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    set_label(&bls_metadata_dir(), &bls_keys_dir(), pk_hex, label)
+}
+
+/// Replaces the label of the saved ETH key, None removes it. Returns the updated metadata.
+pub fn set_eth_label(pk_hex: &str, label: Option<String>) -> Result<KeyMetadata> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    set_label(&eth_metadata_dir(), &eth_keys_dir(), pk_hex, label)
+}
+
+/// Removes the metadata of a deleted BLS key, if any
+pub fn delete_bls_metadata(pk_hex: &str) {
+    let pk_hex: &str = &key_fname(pk_hex);
+    delete_metadata(&bls_metadata_dir(), pk_hex)
+}
+
+/// Removes the metadata of a deleted ETH key, if any
+pub fn delete_eth_metadata(pk_hex: &str) {
+    let pk_hex: &str = &key_fname(pk_hex);
+    delete_metadata(&eth_metadata_dir(), pk_hex)
+}
+
+#[cfg(test)]
+mod test_key_metadata {
+    use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+    use crate::io::key_management::{delete_bls_key, write_bls_key, write_derivation_path};
+
+    #[test]
+    fn test_metadata_is_synthesized_for_old_keys() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_metadata_is_synthesized_for_old_keys");
+        let unknown_pk = "0xaaaa0051";
+        let derived_pk = "0xbbbb0051";
+        write_bls_key(&unknown_pk.to_string(), &"abcdef".to_string())?;
+        write_bls_key(&derived_pk.to_string(), &"abcdef".to_string())?;
+        write_derivation_path(derived_pk, "m/12381/3600/0/0/0")?;
+
+        let metadata = read_bls_metadata(unknown_pk)?;
+        assert_eq!(metadata.origin, KeyOrigin::Unknown);
+        assert!(metadata.created_at > 0);
+        assert_eq!(metadata.last_used, None);
+        assert_eq!(read_bls_metadata(derived_pk)?.origin, KeyOrigin::Derived);
+
+        // The synthesized record is kept and updated on use
+        record_bls_key_use(unknown_pk)?;
+        let used = read_bls_metadata(unknown_pk)?;
+        assert_eq!(used.created_at, metadata.created_at);
+        assert!(used.last_used.is_some());
+
+        // Keys that were never saved have no metadata
+        assert!(read_bls_metadata("0xcccc0051").is_err());
+
+        delete_bls_key(unknown_pk)?;
+        delete_bls_key(derived_pk)?;
+        assert!(!bls_metadata_dir().join("aaaa0051").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_round_trips() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_metadata_round_trips");
+        let pk_hex = "0xdddd0051";
+        write_bls_key(&pk_hex.to_string(), &"abcdef".to_string())?;
+        let metadata = KeyMetadata::new(KeyOrigin::Imported, Some("node-1".to_string()));
+        write_bls_metadata(pk_hex, &metadata)?;
+        assert_eq!(read_bls_metadata(pk_hex)?, metadata);
+        delete_bls_key(pk_hex)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_labels_are_validated() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_labels_are_validated");
+        let pk_hex = "0xeeee0056";
+        write_bls_key(&pk_hex.to_string(), &"abcdef".to_string())?;
+
+        let metadata = set_bls_label(pk_hex, Some("customer-a.testnet_1".to_string()))?;
+        assert_eq!(metadata.label.as_deref(), Some("customer-a.testnet_1"));
+        assert_eq!(read_bls_metadata(pk_hex)?, metadata);
+
+        for bad in ["", "has space", "émoji", &"a".repeat(MAX_KEY_LABEL_LEN + 1)] {
+            assert!(set_bls_label(pk_hex, Some(bad.to_string())).is_err());
+        }
+        assert_eq!(read_bls_metadata(pk_hex)?, metadata);
+
+        assert_eq!(set_bls_label(pk_hex, None)?.label, None);
+        assert!(set_bls_label("0xffff0056", Some("unknown".to_string())).is_err());
+        delete_bls_key(pk_hex)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use crate::io::data_dir::{vault_key_path, vault_password_path};
+use anyhow::{bail, Context, Result};
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+/// Prefixes every sealed blob. Legacy key files are plaintext hex, so they can never start with it.
+const SEALED_MAGIC: &[u8] = b"SSV1";
+const IV_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+
+/// Encrypts secrets before they are written to disk so that only this enclave can read them back
+pub trait KeyVault: Send + Sync {
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
+    fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Returns true if `bytes` were written by a `KeyVault` rather than saved as plaintext
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(SEALED_MAGIC)
+}
+
+fn gcm_cipher(key: &[u8]) -> Result<Cipher> {
+    match key.len() {
+        16 => Ok(Cipher::aes_128_gcm()),
+        32 => Ok(Cipher::aes_256_gcm()),
+        n => bail!("Bad vault key length {n}"),
+    }
+}
+
+/// AES-GCM encrypts `plaintext` into `magic | iv | tag | ciphertext`
+fn gcm_seal(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut iv = [0_u8; IV_BYTES];
+    openssl::rand::rand_bytes(&mut iv)?;
+    let mut tag = [0_u8; TAG_BYTES];
+    let ct = encrypt_aead(
+        gcm_cipher(key)?,
+        key,
+        Some(&iv),
+        SEALED_MAGIC,
+        plaintext,
+        &mut tag,
+    )
+    .with_context(|| "Failed to seal key")?;
+    Ok([SEALED_MAGIC, &iv, &tag, &ct].concat())
+}
+
+fn gcm_unseal(key: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if !is_sealed(sealed) || sealed.len() < SEALED_MAGIC.len() + IV_BYTES + TAG_BYTES {
+        bail!("Not a sealed key")
+    }
+    let (iv, rest) = sealed[SEALED_MAGIC.len()..].split_at(IV_BYTES);
+    let (tag, ct) = rest.split_at(TAG_BYTES);
+    decrypt_aead(gcm_cipher(key)?, key, Some(iv), SEALED_MAGIC, ct, tag)
+        .with_context(|| "Failed to unseal key, it was sealed by another vault")
+}
+
+#[cfg(feature = "sgx")]
+#[link(name = "epid")]
+extern "C" {
+    /// Fetches the MRSIGNER sealing key, defined in src/io/seal_wrapper.cpp
+    fn get_mrsigner_seal_key(key: *mut u8) -> i32;
+}
+
+/// Seals with the enclave's MRSIGNER sealing key, so keys survive enclave upgrades from the same
+/// signer but cannot be read outside of it
+#[cfg(feature = "sgx")]
+pub struct SgxVault;
+
+#[cfg(feature = "sgx")]
+impl SgxVault {
+    fn seal_key(&self) -> Result<[u8; 16]> {
+        let mut key = [0_u8; 16];
+        if unsafe { get_mrsigner_seal_key(key.as_mut_ptr()) } != 0 {
+            bail!("Failed to get the SGX sealing key")
+        }
+        Ok(key)
+    }
+}
+
+#[cfg(feature = "sgx")]
+impl KeyVault for SgxVault {
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        gcm_seal(&self.seal_key()?, plaintext)
+    }
+
+    fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        gcm_unseal(&self.seal_key()?, sealed)
+    }
+}
+
+/// Software fallback for non-SGX builds. The AES-256 key is kept in a file next to the keys, so
+/// this only keeps secrets out of the key files themselves.
+pub struct FileVault {
+    key_path: PathBuf,
+    /// Kept once read, so the vault keeps working for the process even if the data directory
+    /// it was created in goes away
+    key: OnceLock<Vec<u8>>,
+}
+
+static FILE_VAULT_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+impl FileVault {
+    pub fn new(key_path: PathBuf) -> Self {
+        FileVault {
+            key_path,
+            key: OnceLock::new(),
+        }
+    }
+
+    /// Reads the vault key, creating it on first use
+    fn vault_key(&self) -> Result<Vec<u8>> {
+        if let Some(key) = self.key.get() {
+            return Ok(key.clone());
+        }
+        let _guard = FILE_VAULT_LOCK
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Vault lock poisoned"))?;
+        if let Some(p) = self.key_path.parent() {
+            fs::create_dir_all(p).with_context(|| "Failed to create keys dir")?
+        };
+        let mut key = vec![0_u8; 32];
+        openssl::rand::rand_bytes(&mut key)?;
+        // Never replace a key another process already created
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&self.key_path)
+        {
+            Ok(mut file) => {
+                file.write_all(&key)
+                    .with_context(|| "Failed to write vault key")?;
+                file.sync_all()
+                    .with_context(|| "Failed to sync vault key")?;
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                key = fs::read(&self.key_path).with_context(|| "Unable to read vault key")?;
+            }
+            Err(e) => return Err(e).with_context(|| "Failed to create vault key"),
+        }
+        Ok(self.key.get_or_init(|| key).clone())
+    }
+}
+
+impl KeyVault for FileVault {
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        gcm_seal(&self.vault_key()?, plaintext)
+    }
+
+    fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        gcm_unseal(&self.vault_key()?, sealed)
+    }
+}
+
+/// scrypt parameters for deriving a `PasswordVault` key, the same as EIP-2335 keystores
+const SCRYPT_N: u64 = 1 << 18;
+const SCRYPT_R: u64 = 8;
+const SCRYPT_P: u64 = 1;
+const SCRYPT_SALT_BYTES: usize = 32;
+/// Sealed next to the salt so a wrong password is caught at startup
+const PASSWORD_CHECK: &[u8] = b"secure-signer vault password";
+
+/// Seals with an AES-256-GCM key derived from a master password via scrypt, for deployments
+/// without SGX sealing
+pub struct PasswordVault {
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl PasswordVault {
+    fn derive_key(password: &str, salt: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let mut key = Zeroizing::new(vec![0_u8; 32]);
+        openssl::pkcs5::scrypt(
+            password.as_bytes(),
+            salt,
+            SCRYPT_N,
+            SCRYPT_R,
+            SCRYPT_P,
+            // scrypt needs 128 * N * r bytes
+            2 * 128 * SCRYPT_N * SCRYPT_R,
+            &mut key,
+        )
+        .with_context(|| "Failed to derive vault key")?;
+        Ok(key)
+    }
+
+    /// Derives the vault key from `password`. The first unlock saves the salt and a check value
+    /// to `check_path`, later unlocks fail unless given the same password.
+    pub fn unlock(password: &str, check_path: &Path) -> Result<Self> {
+        if check_path.exists() {
+            let saved = fs::read(check_path).with_context(|| "Unable to read vault password")?;
+            if saved.len() < SCRYPT_SALT_BYTES {
+                bail!("Corrupt vault password file {:?}", check_path)
+            }
+            let (salt, check) = saved.split_at(SCRYPT_SALT_BYTES);
+            let key = Self::derive_key(password, salt)?;
+            match gcm_unseal(&key, check) {
+                Ok(pt) if pt == PASSWORD_CHECK => Ok(PasswordVault { key }),
+                _ => bail!("Wrong vault password"),
+            }
+        } else {
+            let mut salt = [0_u8; SCRYPT_SALT_BYTES];
+            openssl::rand::rand_bytes(&mut salt)?;
+            let key = Self::derive_key(password, &salt)?;
+            let check = gcm_seal(&key, PASSWORD_CHECK)?;
+            if let Some(p) = check_path.parent() {
+                fs::create_dir_all(p).with_context(|| "Failed to create keys dir")?
+            };
+            fs::write(check_path, [&salt[..], &check].concat())
+                .with_context(|| "Failed to write vault password")?;
+            Ok(PasswordVault { key })
+        }
+    }
+}
+
+impl KeyVault for PasswordVault {
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        gcm_seal(&self.key, plaintext)
+    }
+
+    fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        gcm_unseal(&self.key, sealed)
+    }
+}
+
+static VAULT: OnceLock<Box<dyn KeyVault>> = OnceLock::new();
+
+/// Selects the vault keys are sealed with. Must be called before the first key is read or written.
+pub fn set_vault(v: Box<dyn KeyVault>) -> Result<()> {
+    if VAULT.set(v).is_err() {
+        bail!("Key vault already set");
+    }
+    Ok(())
+}
+
+/// The vault used when none was set, SGX sealing in enclave builds
+#[cfg(feature = "sgx")]
+pub fn default_vault() -> Box<dyn KeyVault> {
+    Box::new(SgxVault)
+}
+
+/// The vault used when none was set, SGX sealing in enclave builds
+#[cfg(not(feature = "sgx"))]
+pub fn default_vault() -> Box<dyn KeyVault> {
+    Box::new(FileVault::new(vault_key_path()))
+}
+
+/// Returns the vault that every key read and write goes through
+pub fn vault() -> &'static dyn KeyVault {
+    VAULT.get_or_init(default_vault).as_ref()
+}
+
+/// Seals keys with a `PasswordVault` from now on, failing if `password` does not match the one
+/// the keys were first protected with. Keys saved in plaintext or sealed by the default vault are
+/// resealed, and their number returned.
+pub fn use_password(password: &str) -> Result<usize> {
+    let v = PasswordVault::unlock(password, &vault_password_path())?;
+    set_vault(Box::new(v))?;
+    crate::io::key_management::reseal_keys(default_vault().as_ref())
+}
+
+/// Reads the master password from stdin
+pub fn prompt_password() -> Result<String> {
+    print!("Enter the key vault password: ");
+    std::io::stdout().flush()?;
+    let mut password = String::new();
+    std::io::stdin()
+        .read_line(&mut password)
+        .with_context(|| "Failed to read password")?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+
+    #[test]
+    fn test_file_vault_round_trips() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_file_vault_round_trips");
+        let key_path = vault_key_path();
+        let vault = FileVault::new(key_path.clone());
+
+        let sealed = vault.seal(b"abcdef123456")?;
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(12).any(|w| w == b"abcdef123456"));
+        assert_eq!(vault.unseal(&sealed)?, b"abcdef123456");
+
+        // Tampering is detected
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(vault.unseal(&tampered).is_err());
+
+        // Plaintext is not mistaken for a sealed key
+        assert!(vault.unseal(b"abcdef123456").is_err());
+
+        // Only the owner can read the vault key
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&key_path)?.permissions().mode() & 0o777, 0o600);
+
+        // A vault with another key cannot unseal
+        fs::remove_file(&key_path)?;
+        assert!(FileVault::new(key_path.clone()).unseal(&sealed).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_password_vault_refuses_wrong_password() -> Result<()> {
+        let _data_dir = ScopedDataDir::temp("test_password_vault_refuses_wrong_password");
+        let check_path = vault_password_path();
+
+        let vault = PasswordVault::unlock("hunter2", &check_path)?;
+        let sealed = vault.seal(b"abcdef123456")?;
+
+        // The same password unlocks the same key
+        let vault = PasswordVault::unlock("hunter2", &check_path)?;
+        assert_eq!(vault.unseal(&sealed)?, b"abcdef123456");
+
+        assert!(PasswordVault::unlock("hunter3", &check_path).is_err());
+        Ok(())
+    }
+}
@@ -1,2 +1,9 @@
+pub mod data_dir;
 pub mod key_management;
+pub mod key_metadata;
+pub mod key_vault;
 pub mod remote_attestation;
+pub mod remote_keys;
+pub mod threshold_keys;
+pub mod validator_config;
+pub mod workers;
@@ -1,12 +1,11 @@
 #include <string.h>
 #include <iostream>
 
-#include "ra_config.h"
 #include "tee/ra_quote.h"
 
 
 extern "C"
-void do_epid_ra(uint8_t data[64], char * report, char * signature, char * signing_cert) {
+void do_epid_ra(uint8_t data[64], const char * ias_url, const char * ias_access_key, const char * spid_hex, char * report, char * signature, char * signing_cert) {
   // 64 Byte report data to embed in the intel-signed report
   sgx_report_data_t report_data = {0};
   for (int i = 0; i < 64; ++i) {
@@ -14,11 +13,12 @@ void do_epid_ra(uint8_t data[64], char * report, char * signature, char * signin
       // std::cout << "data[" << i << "]: " << (int) report_data.d[i] << std::endl; 
    }
 
-  // Don't need to set IAS key/cert when we used accesskey authentication
+  // Don't need to set IAS key/cert when we used accesskey authentication.
+  // The credentials are configured at startup, never log them.
   RaIasServerCfg ias_server;
-  ias_server.endpoint = RA_CONF_STR(kConfIasServer);
-  ias_server.accesskey = RA_CONF_STR(kConfIasAccessKey);
-  std::string spid = RA_CONF_STR(kConfSPID);
+  ias_server.endpoint = ias_url;
+  ias_server.accesskey = ias_access_key;
+  std::string spid = spid_hex;
 
   ra::occlum::RaEnclaveQuote ra;
   ra::occlum::RaIasReport ias_report;
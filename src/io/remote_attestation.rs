@@ -2,14 +2,23 @@ use anyhow::{bail, Context, Result};
 use blsttc::PublicKey;
 use ecies::PublicKey as EthPublicKey;
 use log::{debug, info};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey, EcKeyRef, EcPoint};
+use openssl::ecdsa::EcdsaSig;
+use openssl::nid::Nid;
+use openssl::pkey::Public;
 use openssl::stack::Stack;
 use openssl::x509::store::X509StoreBuilder;
-use openssl::x509::{X509StoreContext, X509};
+use openssl::x509::{CrlStatus, X509Crl, X509NameRef, X509StoreContext, X509};
 use serde::Deserialize;
 use serde_derive::Serialize;
+use serde_json::value::RawValue;
 
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::os::raw::c_char;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "sgx")]
 #[link(name = "epid")]
@@ -17,44 +26,677 @@ extern "C" {
     /// The cpp function for epid remote attestation with IAS defined in src/ra_wrapper.cpp
     fn do_epid_ra(
         data: *const u8,
+        ias_url: *const c_char,
+        ias_access_key: *const c_char,
+        spid: *const c_char,
         report: *mut c_char,
         signature: *mut c_char,
         signing_cert: *mut c_char,
     );
+
+    /// The cpp function for dcap quote generation defined in src/dcap_wrapper.cpp. Writes at most
+    /// `quote_len` bytes, then sets it to the quote's length. Returns 0 on success.
+    fn do_dcap_quote(data: *const u8, quote: *mut u8, quote_len: *mut u32) -> i32;
 }
 
 #[cfg(not(feature = "sgx"))]
 // Use this func sig for local development
 pub fn do_epid_ra(
     _data: *const u8,
+    _ias_url: *const c_char,
+    _ias_access_key: *const c_char,
+    _spid: *const c_char,
     _report: *mut c_char,
     _signature: *mut c_char,
     _signing_cert: *mut c_char,
 ) {
 }
 
+#[cfg(not(feature = "sgx"))]
+// Use this func sig for local development, it produces an empty quote
+pub unsafe fn do_dcap_quote(_data: *const u8, _quote: *mut u8, quote_len: *mut u32) -> i32 {
+    *quote_len = 0;
+    0
+}
+
+/// The Intel Attestation Service account EPID attestation is performed with. It is read at
+/// startup rather than built into the enclave, so using another account keeps MRENCLAVE.
+/// `Debug` redacts the credentials.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct IasConfig {
+    #[serde(default = "default_ias_url")]
+    pub ias_url: String,
+    pub ias_access_key: String,
+    pub enclave_spid: String,
+}
+
+fn default_ias_url() -> String {
+    crate::constants::DEFAULT_IAS_URL.to_string()
+}
+
+impl std::fmt::Debug for IasConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IasConfig")
+            .field("ias_url", &self.ias_url)
+            .field("ias_access_key", &"<redacted>")
+            .field("enclave_spid", &"<redacted>")
+            .finish()
+    }
+}
+
+impl IasConfig {
+    /// Reads a JSON file with the keys of conf/ra_config.json. Errors never quote the file.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let json = std::fs::read(path).with_context(|| format!("Couldn't read {path}"))?;
+        let config: IasConfig = serde_json::from_slice(&json).map_err(|e| {
+            anyhow::anyhow!(
+                "{} is not an IAS config, error at line {} column {}",
+                path,
+                e.line(),
+                e.column()
+            )
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads IAS_ACCESS_KEY, IAS_SPID and optionally IAS_URL. None when neither is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let (ias_access_key, enclave_spid) = match (var("IAS_ACCESS_KEY"), var("IAS_SPID")) {
+            (None, None) => return Ok(None),
+            (Some(key), Some(spid)) => (key, spid),
+            _ => bail!("IAS_ACCESS_KEY and IAS_SPID must be set together"),
+        };
+        let config = IasConfig {
+            ias_url: var("IAS_URL").unwrap_or_else(default_ias_url),
+            ias_access_key,
+            enclave_spid,
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.ias_access_key.is_empty() {
+            bail!("The IAS access key is empty")
+        }
+        if self.enclave_spid.len() != 32
+            || !self.enclave_spid.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            bail!("The SPID must be 32 hex characters")
+        }
+        if !self.ias_url.starts_with("https://") {
+            bail!("The IAS url must be https")
+        }
+        Ok(())
+    }
+}
+
+/// Returned when EPID attestation is requested before IAS credentials are configured
+#[derive(Debug)]
+pub struct AttestationNotConfigured;
+
+impl std::fmt::Display for AttestationNotConfigured {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "EPID attestation is not configured, set IAS_ACCESS_KEY and IAS_SPID or pass --ias-config"
+        )
+    }
+}
+
+impl std::error::Error for AttestationNotConfigured {}
+
+static IAS_CONFIG: OnceLock<IasConfig> = OnceLock::new();
+
+/// Sets the IAS account for EPID attestation. Must be called before the first attestation.
+pub fn set_ias_config(config: IasConfig) -> Result<()> {
+    if IAS_CONFIG.set(config).is_err() {
+        bail!("IAS config already set");
+    }
+    Ok(())
+}
+
+/// The IAS account for EPID attestation, or `AttestationNotConfigured`
+pub fn require_ias_config() -> Result<&'static IasConfig> {
+    match IAS_CONFIG.get() {
+        Some(config) => Ok(config),
+        None => Err(AttestationNotConfigured.into()),
+    }
+}
+
+/// Returned by builds with neither SGX nor the `mock-attestation` feature, which have no
+/// evidence to offer
+#[derive(Debug)]
+pub struct AttestationUnsupported;
+
+impl std::fmt::Display for AttestationUnsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "This build has no SGX support, remote attestation is unavailable"
+        )
+    }
+}
+
+impl std::error::Error for AttestationUnsupported {}
+
+/// Fails unless `kind` evidence can be produced. SGX builds need an IAS account for EPID, builds
+/// without SGX only return unsigned placeholders with the `mock-attestation` feature.
+pub fn check_attestation(kind: EvidenceKind) -> Result<()> {
+    if cfg!(feature = "sgx") {
+        if kind == EvidenceKind::Epid {
+            require_ias_config()?;
+        }
+        Ok(())
+    } else if cfg!(any(test, feature = "mock-attestation")) {
+        Ok(())
+    } else {
+        Err(AttestationUnsupported.into())
+    }
+}
+
+static PCCS_URL: OnceLock<String> = OnceLock::new();
+
+/// Points DCAP collateral requests at a PCCS other than the local default. Must be called before
+/// the first DCAP attestation.
+pub fn set_pccs_url(pccs_url: &str) -> Result<()> {
+    if PCCS_URL
+        .set(pccs_url.trim_end_matches('/').to_string())
+        .is_err()
+    {
+        bail!("PCCS url already set");
+    }
+    Ok(())
+}
+
+/// The PCCS that DCAP collateral is fetched from
+pub fn pccs_url() -> &'static str {
+    PCCS_URL.get_or_init(|| crate::constants::DEFAULT_PCCS_URL.to_string())
+}
+
+/// The attestation scheme that produced an `AttestationEvidence`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EvidenceKind {
+    /// An EPID quote, verified by IAS and returned as its signed report
+    #[default]
+    Epid,
+    /// An ECDSA quote, returned with the collateral to verify it
+    Dcap,
+}
+
+/// The PCCS collateral needed to verify an ECDSA quote, named after Intel's sgx_ql_qve_collateral_t
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DcapCollateral {
+    pub pck_crl_issuer_chain: String,
+    pub root_ca_crl: String,
+    pub pck_crl: String,
+    pub tcb_info_issuer_chain: String,
+    pub tcb_info: String,
+    pub qe_identity_issuer_chain: String,
+    pub qe_identity: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct AttestationEvidence {
+    /// Evidence predating DCAP has no kind and is EPID
+    #[serde(default)]
+    pub kind: EvidenceKind,
+    /// The IAS attestation verification report, JSON whose isvEnclaveQuoteBody holds the quote
+    #[serde(alias = "ias_report")]
     pub raw_report: String,
+    /// The base64 RSA-SHA256 signature of `raw_report` from the X-IASReport-Signature header
+    #[serde(alias = "ias_signature")]
     pub signed_report: String,
+    /// The PEM report signing certificate followed by Intel's root CA, from the
+    /// X-IASReport-Signing-Certificate header
+    #[serde(alias = "ias_cert_chain")]
     pub signing_cert: String,
+    /// The base64 ECDSA quote of DCAP evidence
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub quote: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collateral: Option<DcapCollateral>,
+}
+
+/// Attestation evidence and the Unix time in seconds it was generated at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedEvidence {
+    pub evidence: AttestationEvidence,
+    pub generated_at: u64,
+}
+
+/// Recent evidence by what it commits to, so clients verifying us at once share one quote
+static EVIDENCE_CACHE: OnceLock<Mutex<HashMap<(KeyBinding, EvidenceKind), CachedEvidence>>> =
+    OnceLock::new();
+static EVIDENCE_CACHE_TTL: OnceLock<u64> = OnceLock::new();
+
+/// Sets how many seconds evidence is reused for, 0 generates it for every request. Must be called
+/// before the first evidence is cached.
+pub fn set_evidence_cache_ttl(ttl_secs: u64) -> Result<()> {
+    if EVIDENCE_CACHE_TTL.set(ttl_secs).is_err() {
+        bail!("Evidence cache TTL already set");
+    }
+    Ok(())
+}
+
+/// How many seconds evidence is reused for
+pub fn evidence_cache_ttl() -> u64 {
+    *EVIDENCE_CACHE_TTL
+        .get()
+        .unwrap_or(&crate::constants::DEFAULT_EVIDENCE_CACHE_TTL_SECS)
+}
+
+fn evidence_cache() -> &'static Mutex<HashMap<(KeyBinding, EvidenceKind), CachedEvidence>> {
+    EVIDENCE_CACHE.get_or_init(Default::default)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Evidence of `kind` committing to `binding` that is younger than the TTL
+pub fn cached_evidence(binding: &KeyBinding, kind: EvidenceKind) -> Option<CachedEvidence> {
+    let ttl = evidence_cache_ttl();
+    let cache = evidence_cache().lock().ok()?;
+    cache
+        .get(&(binding.clone(), kind))
+        .filter(|cached| now().saturating_sub(cached.generated_at) < ttl)
+        .cloned()
+}
+
+/// Caches evidence committing to `binding` that was generated now, and returns when that was.
+/// Expired evidence is dropped first, then the oldest if the cache is still full.
+pub fn cache_evidence(binding: KeyBinding, evidence: AttestationEvidence) -> u64 {
+    let generated_at = now();
+    let ttl = evidence_cache_ttl();
+    if ttl == 0 {
+        return generated_at;
+    }
+    if let Ok(mut cache) = evidence_cache().lock() {
+        cache.retain(|_, cached| generated_at.saturating_sub(cached.generated_at) < ttl);
+        if cache.len() >= crate::constants::MAX_CACHED_EVIDENCE {
+            if let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.generated_at)
+                .map(|(key, _)| key.clone())
+            {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            (binding, evidence.kind),
+            CachedEvidence {
+                evidence,
+                generated_at,
+            },
+        );
+    }
+    generated_at
+}
+
+/// Drops cached evidence committing to the key, whenever its key file is written or deleted
+pub fn evict_cached_evidence(pk_hex: &str) {
+    let pk_hex = crate::io::key_management::key_fname(pk_hex);
+    let binds = |pk: &Option<String>| {
+        pk.as_deref().map_or(false, |pk| {
+            crate::io::key_management::key_fname(pk) == pk_hex
+        })
+    };
+    if let Ok(mut cache) = evidence_cache().lock() {
+        cache.retain(|(binding, _), _| !binds(&binding.eth_pubkey) && !binds(&binding.bls_pubkey));
+    }
+}
+
+/// IAS quote statuses of platforms that are up to date, or that need the software or
+/// configuration mitigations listed in the report's advisories
+pub const ACCEPTED_QUOTE_STATUSES: [&str; 4] = [
+    "OK",
+    "SW_HARDENING_NEEDED",
+    "CONFIGURATION_NEEDED",
+    "CONFIGURATION_AND_SW_HARDENING_NEEDED",
+];
+
+/// The DCAP counterparts of `ACCEPTED_QUOTE_STATUSES`, TCB statuses from Intel's TCB info
+pub const ACCEPTED_TCB_STATUSES: [&str; 4] = [
+    "UpToDate",
+    "SWHardeningNeeded",
+    "ConfigurationNeeded",
+    "ConfigurationAndSWHardeningNeeded",
+];
+
+/// The outcome of verifying `AttestationEvidence`, with what the evidence attests to
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttestationVerdict {
+    pub valid: bool,
+    pub kind: EvidenceKind,
+    /// Why the evidence is not valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The IAS quote status of EPID evidence, or the TCB status of DCAP evidence's platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advisory_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrenclave: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrsigner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isv_prod_id: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isv_svn: Option<u16>,
+    /// The 64 bytes of report data, holding the attested public key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report_data: Option<String>,
+}
+
+/// The enclave builds trusted to hand this enclave secrets. Evidence is allowed if its MRENCLAVE
+/// or its MRSIGNER is listed, and its ISV SVN is at least `min_isv_svn`. Measurements are hex,
+/// with or without 0x.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MeasurementPolicy {
+    #[serde(default)]
+    pub mrenclaves: Vec<String>,
+    #[serde(default)]
+    pub mrsigners: Vec<String>,
+    #[serde(default)]
+    pub min_isv_svn: u16,
+}
+
+impl MeasurementPolicy {
+    /// Reads a JSON policy file, normalizing its measurements to lowercase hex without 0x
+    pub fn from_file(path: &str) -> Result<Self> {
+        let json = std::fs::read(path).with_context(|| format!("Couldn't read {path}"))?;
+        let mut policy: MeasurementPolicy = serde_json::from_slice(&json)
+            .with_context(|| format!("{path} is not a measurement policy"))?;
+        for measurement in policy
+            .mrenclaves
+            .iter_mut()
+            .chain(policy.mrsigners.iter_mut())
+        {
+            let bytes = crate::parse_hex(measurement)?;
+            if bytes.len() != 32 {
+                bail!("Measurement {} is not 32 bytes", measurement)
+            }
+            *measurement = hex::encode(bytes);
+        }
+        if policy.mrenclaves.is_empty() && policy.mrsigners.is_empty() {
+            bail!("{path} allows no MRENCLAVE or MRSIGNER")
+        }
+        Ok(policy)
+    }
+
+    /// Checks the measurements `verdict` claims, which must separately be verified
+    pub fn check(&self, verdict: &AttestationVerdict) -> Result<(), PolicyViolation> {
+        let violation = || PolicyViolation {
+            mrenclave: verdict.mrenclave.clone().unwrap_or_default(),
+            mrsigner: verdict.mrsigner.clone().unwrap_or_default(),
+            isv_svn: verdict.isv_svn.unwrap_or_default(),
+        };
+        let listed = |allowed: &Vec<String>, got: &Option<String>| {
+            got.as_ref().is_some_and(|got| allowed.contains(got))
+        };
+        if !listed(&self.mrenclaves, &verdict.mrenclave)
+            && !listed(&self.mrsigners, &verdict.mrsigner)
+        {
+            return Err(violation());
+        }
+        if verdict.isv_svn.unwrap_or_default() < self.min_isv_svn {
+            return Err(violation());
+        }
+        Ok(())
+    }
+}
+
+/// Evidence from an enclave build the `MeasurementPolicy` does not allow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyViolation {
+    pub mrenclave: String,
+    pub mrsigner: String,
+    pub isv_svn: u16,
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MRENCLAVE {} MRSIGNER {} ISVSVN {} is not allowed by the measurement policy",
+            self.mrenclave, self.mrsigner, self.isv_svn
+        )
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// Returned when evidence is checked before a measurement policy was loaded
+#[derive(Debug)]
+pub struct NoMeasurementPolicy;
+
+impl std::fmt::Display for NoMeasurementPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No measurement policy is loaded, pass --measurement-policy to allow senders"
+        )
+    }
+}
+
+impl std::error::Error for NoMeasurementPolicy {}
+
+static MEASUREMENT_POLICY_PATH: OnceLock<String> = OnceLock::new();
+static MEASUREMENT_POLICY: RwLock<Option<MeasurementPolicy>> = RwLock::new(None);
+
+/// Loads the measurement policy at `path`, which `reload_measurement_policy` rereads
+pub fn set_measurement_policy_path(path: &str) -> Result<()> {
+    if MEASUREMENT_POLICY_PATH.set(path.to_string()).is_err() {
+        bail!("Measurement policy path already set");
+    }
+    reload_measurement_policy()?;
+    Ok(())
+}
+
+/// Rereads the measurement policy file. A file that fails to load leaves the current policy in
+/// place.
+pub fn reload_measurement_policy() -> Result<MeasurementPolicy> {
+    let Some(path) = MEASUREMENT_POLICY_PATH.get() else {
+        bail!("Secure-Signer was started without a measurement policy")
+    };
+    let policy = MeasurementPolicy::from_file(path)?;
+    *MEASUREMENT_POLICY
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = Some(policy.clone());
+    info!(
+        "Loaded a measurement policy of {} MRENCLAVEs and {} MRSIGNERs",
+        policy.mrenclaves.len(),
+        policy.mrsigners.len()
+    );
+    Ok(policy)
+}
+
+/// Checks `evidence` comes from an enclave build trusted to hand this enclave secrets. Fails
+/// with a `PolicyViolation` if its measurements are not allowed, checked before its signatures
+/// so the offending measurement is reported even for forged evidence. Without a loaded policy
+/// no evidence is allowed, failing with `NoMeasurementPolicy`.
+pub fn check_measurement_policy(evidence: &AttestationEvidence) -> Result<AttestationVerdict> {
+    let verdict = evidence.verify();
+    if verdict.mrenclave.is_none() {
+        bail!(
+            "Couldn't read the evidence's measurements: {}",
+            verdict.error.unwrap_or_default()
+        )
+    }
+    check_loaded_measurement_policy(&verdict)?;
+    if !verdict.valid {
+        bail!(
+            "Invalid attestation evidence: {}",
+            verdict.error.unwrap_or_default()
+        )
+    }
+    Ok(verdict)
+}
+
+/// Checks the measurements of an already verified `verdict` against the loaded policy, failing
+/// with `NoMeasurementPolicy` if none was loaded
+pub fn check_loaded_measurement_policy(verdict: &AttestationVerdict) -> Result<()> {
+    let policy = MEASUREMENT_POLICY.read().unwrap_or_else(|e| e.into_inner());
+    match policy.as_ref() {
+        Some(policy) => Ok(policy.check(verdict)?),
+        None => Err(NoMeasurementPolicy.into()),
+    }
+}
+
+/// The saved keys and verifier nonce that DCAP evidence of saved keys commits to. The report data
+/// is SHA256(eth_pk || bls_pk || nonce), where an absent key is zero-filled to its compressed
+/// length, so verifiers recompute it from exactly these fields.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    /// The compressed 33 byte ETH public key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eth_pubkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bls_pubkey: Option<String>,
+    #[serde(default)]
+    pub nonce: String,
+}
+
+impl KeyBinding {
+    /// The hash the quote's report data starts with
+    pub fn report_data(&self) -> Result<[u8; 32]> {
+        let mut preimage = Vec::with_capacity(
+            crate::constants::ETH_COMPRESSED_PK_BYTES + crate::constants::BLS_PUB_KEY_BYTES,
+        );
+        for (pk_hex, len) in [
+            (&self.eth_pubkey, crate::constants::ETH_COMPRESSED_PK_BYTES),
+            (&self.bls_pubkey, crate::constants::BLS_PUB_KEY_BYTES),
+        ] {
+            match pk_hex {
+                Some(pk_hex) => {
+                    let pk = crate::parse_hex(pk_hex)?;
+                    if pk.len() != len {
+                        bail!("Bound public key {} is not {} bytes", pk_hex, len)
+                    }
+                    preimage.extend_from_slice(&pk);
+                }
+                None => preimage.extend_from_slice(&vec![0; len]),
+            }
+        }
+        if self.eth_pubkey.is_none() && self.bls_pubkey.is_none() {
+            bail!("A key binding needs an eth_pubkey or bls_pubkey")
+        }
+        preimage.extend_from_slice(&crate::parse_hex(&self.nonce)?);
+        Ok(openssl::sha::sha256(&preimage))
+    }
+
+    /// Verifies the quote of `evidence` commits to this binding
+    pub fn verify(&self, evidence: &AttestationEvidence) -> Result<()> {
+        evidence
+            .verify_report_data(&self.report_data()?)
+            .with_context(|| "The attestation evidence does not commit to the bound keys")
+    }
+}
+
+/// Every public key the enclave holds, which batch evidence commits to at once. The report data
+/// is SHA256("SECURE_SIGNER_KEY_SET" || eth_count || eth_pks || bls_count || bls_pks || nonce),
+/// with u32 big-endian counts and each list sorted by its compressed bytes, so the digest does
+/// not depend on the order keys are listed in.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeySetBinding {
+    /// 0x-prefixed compressed 33 byte ETH public keys
+    pub eth_pubkeys: Vec<String>,
+    /// 0x-prefixed 48 byte BLS public keys
+    pub bls_pubkeys: Vec<String>,
+    /// The number of keys in both lists, so an omitted key is detected
+    pub count: usize,
+    #[serde(default)]
+    pub nonce: String,
+}
+
+impl KeySetBinding {
+    /// Binds every ETH and BLS key currently saved
+    pub fn held_keys(nonce: &[u8]) -> Result<Self> {
+        let mut eth_pubkeys = crate::io::key_management::list_eth_keys().unwrap_or_default();
+        let mut bls_pubkeys = crate::io::key_management::list_bls_keys().unwrap_or_default();
+        for pk_hex in eth_pubkeys.iter_mut().chain(bls_pubkeys.iter_mut()) {
+            *pk_hex = crate::to_0x_hex(crate::parse_hex(pk_hex)?);
+        }
+        eth_pubkeys.sort();
+        bls_pubkeys.sort();
+        Ok(KeySetBinding {
+            count: eth_pubkeys.len() + bls_pubkeys.len(),
+            eth_pubkeys,
+            bls_pubkeys,
+            nonce: crate::to_0x_hex(nonce),
+        })
+    }
+
+    /// The hash the quote's report data starts with
+    pub fn report_data(&self) -> Result<[u8; 32]> {
+        if self.count != self.eth_pubkeys.len() + self.bls_pubkeys.len() {
+            bail!(
+                "The key set lists {} keys but claims {}",
+                self.eth_pubkeys.len() + self.bls_pubkeys.len(),
+                self.count
+            )
+        }
+        let mut preimage = b"SECURE_SIGNER_KEY_SET".to_vec();
+        for (pubkeys, len) in [
+            (&self.eth_pubkeys, crate::constants::ETH_COMPRESSED_PK_BYTES),
+            (&self.bls_pubkeys, crate::constants::BLS_PUB_KEY_BYTES),
+        ] {
+            let mut pks = pubkeys
+                .iter()
+                .map(|pk_hex| {
+                    let pk = crate::parse_hex(pk_hex)?;
+                    if pk.len() != len {
+                        bail!("Public key {} is not {} bytes", pk_hex, len)
+                    }
+                    Ok(pk)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            pks.sort();
+            preimage.extend_from_slice(&(pks.len() as u32).to_be_bytes());
+            pks.iter().for_each(|pk| preimage.extend_from_slice(pk));
+        }
+        preimage.extend_from_slice(&crate::parse_hex(&self.nonce)?);
+        Ok(openssl::sha::sha256(&preimage))
+    }
+
+    /// Verifies the quote of `evidence` commits to this key set
+    pub fn verify(&self, evidence: &AttestationEvidence) -> Result<()> {
+        evidence
+            .verify_report_data(&self.report_data()?)
+            .with_context(|| "The attestation evidence does not commit to the key set")
+    }
+}
+
+/// Pads `data` with zeros to the 64 bytes of report data a quote commits to
+fn to_report_data(data: &[u8]) -> Result<[u8; 64]> {
+    if data.len() > 64 {
+        bail!("remote attestation report data exceed 64B limit!")
+    }
+    let mut report_data = [0_u8; 64];
+    report_data[..data.len()].copy_from_slice(data);
+    Ok(report_data)
 }
 
 impl AttestationEvidence {
     pub fn new(data: &[u8]) -> Result<Self> {
         info!("Attempting Remote Attestation");
-        if data.len() > 64 {
-            bail!("remote attestation report data exceed 64B limit!")
+        let report_data = to_report_data(data)?;
+        if !cfg!(feature = "sgx") {
+            return AttestationEvidence::placeholder(&report_data);
         }
-
-        let mut sized_data: Vec<u8> = Vec::with_capacity(64);
-        // add the data
-        sized_data.extend_from_slice(data);
-        // pad any remaining bytes with 0
-        sized_data.extend_from_slice(&vec![0; 64 - data.len()]);
-
-        let mut report_data = [0_u8; 64];
-        report_data.clone_from_slice(&sized_data[..]);
+        let ias = require_ias_config()?.clone();
+        let ias_url = CString::new(ias.ias_url).with_context(|| "Bad IAS url")?;
+        let ias_access_key =
+            CString::new(ias.ias_access_key).with_context(|| "Bad IAS access key")?;
+        let spid = CString::new(ias.enclave_spid).with_context(|| "Bad SPID")?;
 
         // sufficient sized buffers
         //TODO: vec with capacity; Currently using vec with capacity leads to failure in attestation
@@ -77,7 +719,15 @@ impl AttestationEvidence {
 
         unsafe {
             // call cpp EPID remote attestation lib
-            do_epid_ra(&report_data as *const u8, raw_rpt, raw_sig, raw_cert);
+            do_epid_ra(
+                &report_data as *const u8,
+                ias_url.as_ptr(),
+                ias_access_key.as_ptr(),
+                spid.as_ptr(),
+                raw_rpt,
+                raw_sig,
+                raw_cert,
+            );
             _rpt = CString::from_raw(raw_rpt);
             _sig = CString::from_raw(raw_sig);
             _cert = CString::from_raw(raw_cert);
@@ -94,13 +744,87 @@ impl AttestationEvidence {
             raw_report,
             signed_report,
             signing_cert,
+            ..Default::default()
+        })
+    }
+
+    /// Unsigned EPID evidence of `report_data` with zeroed measurements, returned outside of SGX
+    /// with the `mock-attestation` feature so development builds produce evidence of the same
+    /// shape. It never verifies.
+    #[cfg(any(test, feature = "mock-attestation"))]
+    fn placeholder(report_data: &[u8; 64]) -> Result<Self> {
+        let mut body = [0_u8; 432];
+        body[368..432].copy_from_slice(report_data);
+        let report = AttestationReport {
+            version: 4,
+            isvEnclaveQuoteBody: openssl::base64::encode_block(&body),
+            ..Default::default()
+        };
+        Ok(AttestationEvidence {
+            raw_report: serde_json::to_string(&report)?,
+            ..Default::default()
+        })
+    }
+
+    #[cfg(not(any(test, feature = "mock-attestation")))]
+    fn placeholder(_report_data: &[u8; 64]) -> Result<Self> {
+        Err(AttestationUnsupported.into())
+    }
+
+    /// Generates `kind` evidence over `data`, DCAP collateral is fetched from `pccs_url()`
+    pub async fn generate(kind: EvidenceKind, data: &[u8]) -> Result<Self> {
+        match kind {
+            EvidenceKind::Epid => AttestationEvidence::new(data),
+            EvidenceKind::Dcap => AttestationEvidence::new_dcap(data, pccs_url()).await,
+        }
+    }
+
+    /// Generates an ECDSA quote over `data` and fetches its collateral from `pccs_url`. Non-SGX
+    /// builds produce an empty quote with no collateral under `mock-attestation`.
+    pub async fn new_dcap(data: &[u8], pccs_url: &str) -> Result<Self> {
+        info!("Attempting DCAP Remote Attestation");
+        check_attestation(EvidenceKind::Dcap)?;
+        let report_data = to_report_data(data)?;
+
+        let mut quote = vec![0_u8; crate::constants::DCAP_QUOTE_MAX_BYTES];
+        let mut quote_len = quote.len() as u32;
+        // call cpp DCAP quote generation lib
+        let ret =
+            unsafe { do_dcap_quote(report_data.as_ptr(), quote.as_mut_ptr(), &mut quote_len) };
+        if ret != 0 {
+            bail!("DCAP quote generation failed with error code {:x}", ret)
+        }
+        quote.truncate(quote_len as usize);
+
+        let collateral = if quote.is_empty() {
+            None
+        } else {
+            Some(DcapCollateral::fetch(pccs_url, &quote).await?)
+        };
+
+        Ok(AttestationEvidence {
+            kind: EvidenceKind::Dcap,
+            quote: openssl::base64::encode_block(&quote),
+            collateral,
+            ..Default::default()
         })
     }
 
+    /// Verifies the evidence is rooted in Intel's attestation PKI, see
+    /// `verify_ias_signing_certificate` and `verify_dcap_quote`.
+    pub fn verify_intel_signing_certificate(&self) -> Result<()> {
+        match self.kind {
+            EvidenceKind::Epid => self.verify_ias_signing_certificate(),
+            EvidenceKind::Dcap => self.verify_dcap_quote(),
+        }
+    }
+
     /// Verifies attestation evidence IAS signatures. During remote attestation
     /// IAS returns their signing certificate and root CA as concatenated PEMs.
-    /// This function verifies that the signing certificate is rooted in Intel's root CA.
-    pub fn verify_intel_signing_certificate(&self) -> Result<()> {
+    /// This function verifies that the root CA is Intel's, see `is_trusted_ias_root_ca`,
+    /// that it issued the signing certificate, and that the signing certificate signed the
+    /// raw report.
+    fn verify_ias_signing_certificate(&self) -> Result<()> {
         debug!("Verifying certificate {}", self.signing_cert);
         let x509s = X509::stack_from_pem(&self.signing_cert.as_bytes())?;
 
@@ -156,6 +880,11 @@ impl AttestationEvidence {
             None => bail!("Couldn't extract COMMONNAME from intel x509 cert"),
         }
 
+        // The names alone can be copied by any self-signed certificate
+        if !is_trusted_ias_root_ca(&root_x509)? {
+            bail!("The IAS root CA is not Intel's")
+        }
+
         let mut builder = X509StoreBuilder::new()?;
         let _ = builder.add_cert(root_x509.clone());
         let trust = builder.build();
@@ -173,17 +902,169 @@ impl AttestationEvidence {
             cert_chain.as_ref(),
             |c| c.verify_cert(),
         ) {
-            Ok(true) => Ok(()),
+            Ok(true) => {}
             _ => bail!("Failed to verify the intel signing certificate"),
         }
+
+        // Verify the report was signed by the signing_x509
+        let signature = openssl::base64::decode_block(&self.signed_report)
+            .with_context(|| "Couldn't base64 decode the signed report")?;
+        let public_key = signing_x509.public_key()?;
+        let mut verifier =
+            openssl::sign::Verifier::new(openssl::hash::MessageDigest::sha256(), &public_key)?;
+        verifier.update(self.raw_report.as_bytes())?;
+        match verifier.verify(&signature) {
+            Ok(true) => Ok(()),
+            _ => bail!("Failed to verify the IAS signature over the report"),
+        }
     }
 
-    pub fn get_report_data(&self) -> Result<[u8; 64]> {
+    /// Verifies an ECDSA quote against the PCK certificate chain it embeds. The chain must be
+    /// rooted in Intel's SGX root CA, see `verify_sgx_cert_chain`, the PCK key must have signed
+    /// the quoting enclave's report, that report must commit to the attestation key, and the
+    /// attestation key must have signed the quote. The platform's TCB level is checked against
+    /// the collateral by `verify`, see `DcapCollateral::verify`.
+    fn verify_dcap_quote(&self) -> Result<()> {
+        let quote_bytes = openssl::base64::decode_block(&self.quote)
+            .with_context(|| "Couldn't base64 decode the DCAP quote")?;
+        let quote = DcapQuote::parse(&quote_bytes)?;
+
+        let x509s = quote.pck_cert_chain()?;
+        let [pck_x509, _, root_x509] = x509s.as_slice() else {
+            bail!("Expected a PCK certificate chain of 3 certificates")
+        };
+        let n = common_name(pck_x509.subject_name())?;
+        if n != "Intel SGX PCK Certificate" {
+            bail!("The x509 certificate has an invalid common name: {}", n)
+        }
+        let n = common_name(root_x509.subject_name())?;
+        if n != "Intel SGX Root CA" {
+            bail!("The x509 certificate has an invalid common name: {}", n)
+        }
+        verify_sgx_cert_chain(&x509s).with_context(|| "Failed to verify the PCK certificate")?;
+
+        // The PCK key vouches for the quoting enclave
+        let pck_key = pck_x509.public_key()?.ec_key()?;
+        if !verify_p256_signature(&pck_key, quote.qe_report, quote.qe_report_signature)? {
+            bail!("Failed to verify the quoting enclave report signature")
+        }
+
+        // The quoting enclave vouches for the attestation key
+        let mut key_and_auth_data = quote.attestation_key.to_vec();
+        key_and_auth_data.extend_from_slice(quote.qe_auth_data);
+        if openssl::sha::sha256(&key_and_auth_data) != quote.qe_report[320..352] {
+            bail!("The quoting enclave report does not commit to the attestation key")
+        }
+
+        // The attestation key vouches for the enclave's report
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let mut ctx = BigNumContext::new()?;
+        let point = EcPoint::from_bytes(
+            &group,
+            &[&[0x04_u8][..], quote.attestation_key].concat(),
+            &mut ctx,
+        )?;
+        let attestation_key = EcKey::from_public_key(&group, &point)?;
+        if !verify_p256_signature(&attestation_key, quote.signed, quote.signature)? {
+            bail!("Failed to verify the DCAP quote signature")
+        }
+        Ok(())
+    }
+
+    /// The body of the enclave's report, which EPID and DCAP quotes lay out the same way
+    fn quote_body(&self) -> Result<QuoteBody> {
+        match self.kind {
+            EvidenceKind::Epid => {
+                let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
+                    .with_context(|| {
+                        "Couldn't get AttestationReport from AttestationEvidence.raw_report"
+                    })?;
+                report.deserialize_quote_body()
+            }
+            EvidenceKind::Dcap => {
+                let quote = openssl::base64::decode_block(&self.quote)
+                    .with_context(|| "Couldn't base64 decode the DCAP quote")?;
+                QuoteBody::from_bytes(DcapQuote::parse(&quote)?.signed)
+            }
+        }
+    }
+
+    /// Verifies the evidence's signatures and quote status, see `verify_intel_signing_certificate`.
+    /// What the quote attests to is extracted even from invalid evidence, so operators can see
+    /// what was claimed, but it must only be trusted when `valid` is set. The status of DCAP
+    /// evidence is its platform's TCB status, checked against its collateral.
+    pub fn verify(&self) -> AttestationVerdict {
+        let mut verdict = AttestationVerdict {
+            kind: self.kind,
+            ..Default::default()
+        };
+        if let Ok(body) = self.quote_body() {
+            verdict.mrenclave = Some(body.MRENCLAVE);
+            verdict.mrsigner = Some(body.MRSIGNER);
+            verdict.isv_prod_id = Some(body.ISVPRODID);
+            verdict.isv_svn = Some(body.ISVSVN);
+            verdict.report_data = Some(crate::to_0x_hex(&body.REPORTDATA));
+        }
+        match self
+            .verify_intel_signing_certificate()
+            .and_then(|_| self.verify_quote_status(&mut verdict))
+        {
+            Ok(()) => verdict.valid = true,
+            Err(e) => verdict.error = Some(format!("{:#}", e)),
+        }
+        verdict
+    }
+
+    /// Records the IAS quote status of EPID evidence in `verdict`, failing unless it is one of
+    /// `ACCEPTED_QUOTE_STATUSES`, see `verify_tcb_status` for DCAP evidence
+    fn verify_quote_status(&self, verdict: &mut AttestationVerdict) -> Result<()> {
+        if self.kind == EvidenceKind::Dcap {
+            return self.verify_tcb_status(verdict);
+        }
         let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
             .with_context(|| {
                 "Couldn't get AttestationReport from AttestationEvidence.raw_report"
             })?;
-        let body = report.deserialize_quote_body()?;
+        verdict.status = Some(report.isvEnclaveQuoteStatus.clone());
+        verdict.advisory_ids = report.advisoryIDs;
+        if !ACCEPTED_QUOTE_STATUSES.contains(&report.isvEnclaveQuoteStatus.as_str()) {
+            bail!(
+                "The quote status {} is not trusted",
+                report.isvEnclaveQuoteStatus
+            )
+        }
+        Ok(())
+    }
+
+    /// Records the TCB status of DCAP evidence's platform in `verdict`, failing unless the
+    /// collateral vouches for the quote, see `DcapCollateral::verify`, and the status is one of
+    /// `ACCEPTED_TCB_STATUSES`
+    fn verify_tcb_status(&self, verdict: &mut AttestationVerdict) -> Result<()> {
+        let Some(collateral) = &self.collateral else {
+            bail!("DCAP evidence has no collateral to check its TCB level against")
+        };
+        let quote = openssl::base64::decode_block(&self.quote)
+            .with_context(|| "Couldn't base64 decode the DCAP quote")?;
+        let quote = DcapQuote::parse(&quote)?;
+        let level = collateral.verify(&quote)?;
+        verdict.status = Some(level.tcbStatus.clone());
+        verdict.advisory_ids = level.advisoryIDs;
+        if !ACCEPTED_TCB_STATUSES.contains(&level.tcbStatus.as_str()) {
+            bail!("The TCB status {} is not trusted", level.tcbStatus)
+        }
+        Ok(())
+    }
+
+    /// Checks the quote's report data is `data` padded with zeros
+    pub fn verify_report_data(&self, data: &[u8]) -> Result<()> {
+        if self.get_report_data()? != to_report_data(data)? {
+            bail!("Unexpected report data")
+        }
+        Ok(())
+    }
+
+    pub fn get_report_data(&self) -> Result<[u8; 64]> {
+        let body = self.quote_body()?;
         let mut report_bytes: [u8; 64] = [0_u8; 64];
         report_bytes.clone_from_slice(&body.REPORTDATA[0..64]);
         Ok(report_bytes)
@@ -191,11 +1072,7 @@ impl AttestationEvidence {
 
     /// Expets the BLS public key to be in the first 48 bytes of report body
     pub fn get_bls_pk(&self) -> Result<PublicKey> {
-        let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
-            .with_context(|| {
-                "Couldn't get AttestationReport from AttestationEvidence.raw_report"
-            })?;
-        let body = report.deserialize_quote_body()?;
+        let body = self.quote_body()?;
         let mut pk_bytes: [u8; 48] = [0_u8; 48];
         pk_bytes.clone_from_slice(&body.REPORTDATA[0..48]);
         match  PublicKey::from_bytes(pk_bytes) {
@@ -205,36 +1082,733 @@ impl AttestationEvidence {
     }
 
     pub fn get_eth_pk(&self) -> Result<EthPublicKey> {
-        let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
-            .with_context(|| {
-                "Couldn't get AttestationReport from AttestationEvidence.raw_report"
-            })?;
-        let body = report.deserialize_quote_body()?;
+        let body = self.quote_body()?;
         let pk_bytes = &body.REPORTDATA[0..33];
         let pk = EthPublicKey::parse_slice(pk_bytes, None)?;
         Ok(pk)
     }
 
     pub fn get_mrenclave(&self) -> Result<String> {
-        let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
-            .with_context(|| {
-                "Couldn't get AttestationReport from AttestationEvidence.raw_report"
-            })?;
-        let body = report.deserialize_quote_body()?;
+        let body = self.quote_body()?;
         Ok(body.MRENCLAVE)
     }
 
     pub fn get_mrsigner(&self) -> Result<String> {
-        let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
-            .with_context(|| {
-                "Couldn't get AttestationReport from AttestationEvidence.raw_report"
-            })?;
-        let body = report.deserialize_quote_body()?;
+        let body = self.quote_body()?;
         Ok(body.MRSIGNER)
     }
 }
 
-#[allow(non_snake_case)]
+impl DcapCollateral {
+    /// Fetches the collateral to verify `quote` with Intel's PCCS v4 API. The TCB info is looked
+    /// up by the FMSPC of the quote's PCK certificate.
+    pub async fn fetch(pccs_url: &str, quote: &[u8]) -> Result<Self> {
+        let x509s = DcapQuote::parse(quote)?.pck_cert_chain()?;
+        let pck_x509 = match x509s.first() {
+            Some(x) => x,
+            None => bail!("Couldn't extract PCK certificate pem!"),
+        };
+        let fmspc = hex::encode(pck_fmspc(&pck_x509.to_der()?)?);
+        let ca = match common_name(pck_x509.issuer_name())?.as_str() {
+            "Intel SGX PCK Platform CA" => "platform",
+            _ => "processor",
+        };
+
+        // The collateral is signed by Intel and checked by the verifier, so the self-signed
+        // certificate a local PCCS is installed with can be accepted
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()?;
+        let base = format!("{}/sgx/certification/v4", pccs_url.trim_end_matches('/'));
+        let (pck_crl, pck_crl_issuer_chain) = pccs_get(
+            &client,
+            &format!("{base}/pckcrl?ca={ca}&encoding=pem"),
+            Some("SGX-PCK-CRL-Issuer-Chain"),
+        )
+        .await?;
+        let (tcb_info, tcb_info_issuer_chain) = pccs_get(
+            &client,
+            &format!("{base}/tcb?fmspc={fmspc}"),
+            Some("TCB-Info-Issuer-Chain"),
+        )
+        .await?;
+        let (qe_identity, qe_identity_issuer_chain) = pccs_get(
+            &client,
+            &format!("{base}/qe/identity"),
+            Some("SGX-Enclave-Identity-Issuer-Chain"),
+        )
+        .await?;
+        let (root_ca_crl, _) = pccs_get(&client, &format!("{base}/rootcacrl"), None).await?;
+
+        Ok(DcapCollateral {
+            pck_crl_issuer_chain,
+            root_ca_crl,
+            pck_crl,
+            tcb_info_issuer_chain,
+            tcb_info,
+            qe_identity_issuer_chain,
+            qe_identity,
+        })
+    }
+}
+
+impl DcapCollateral {
+    /// Checks the platform and quoting enclave of `quote`, whose PCK certificate chain must
+    /// already be verified, returning the platform's TCB level. The TCB info and QE identity must
+    /// be signed under Intel's SGX root CA and not past their next update, the PCK certificate
+    /// and its CA must not be revoked, and the quoting enclave must be Intel's at an up to date
+    /// TCB level.
+    fn verify(&self, quote: &DcapQuote) -> Result<TcbLevel> {
+        let x509s = quote.pck_cert_chain()?;
+        let [pck_x509, ca_x509, root_x509] = x509s.as_slice() else {
+            bail!("Expected a PCK certificate chain of 3 certificates")
+        };
+        check_not_revoked(&self.root_ca_crl, root_x509, ca_x509)
+            .with_context(|| "Failed to check the root CA CRL")?;
+        check_not_revoked(&self.pck_crl, ca_x509, pck_x509)
+            .with_context(|| "Failed to check the PCK CRL")?;
+
+        let qe_identity: QeIdentity =
+            verify_signed_collateral(&self.qe_identity, &self.qe_identity_issuer_chain)
+                .with_context(|| "Failed to verify the QE identity")?;
+        let qe_status = qe_identity.tcb_status(quote.qe_report)?;
+        if qe_status != "UpToDate" {
+            bail!(
+                "The quoting enclave's TCB status {} is not trusted",
+                qe_status
+            )
+        }
+
+        let tcb_info: TcbInfo =
+            verify_signed_collateral(&self.tcb_info, &self.tcb_info_issuer_chain)
+                .with_context(|| "Failed to verify the TCB info")?;
+        check_next_update(&tcb_info.nextUpdate)?;
+        let pck_der = pck_x509.to_der()?;
+        if !tcb_info
+            .fmspc
+            .eq_ignore_ascii_case(&hex::encode(pck_fmspc(&pck_der)?))
+        {
+            bail!("The TCB info is for another FMSPC, {}", tcb_info.fmspc)
+        }
+        let (cpu_svns, pce_svn) = pck_tcb(&pck_der)?;
+        tcb_info.tcb_level(&cpu_svns, pce_svn)
+    }
+}
+
+/// TCB info or QE identity, whose signature is over the exact JSON text of its body
+#[derive(Deserialize)]
+struct SignedCollateral<'a> {
+    #[serde(borrow, alias = "tcbInfo", alias = "enclaveIdentity")]
+    body: &'a RawValue,
+    signature: String,
+}
+
+/// The TCB levels of the platforms of one FMSPC, version 3 of Intel's TCB info
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+struct TcbInfo {
+    nextUpdate: String,
+    fmspc: String,
+    /// Ordered from the latest TCB level down
+    tcbLevels: Vec<TcbLevel>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug, Clone)]
+struct TcbLevel {
+    tcb: Tcb,
+    tcbStatus: String,
+    #[serde(default)]
+    advisoryIDs: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Tcb {
+    sgxtcbcomponents: Vec<TcbComponent>,
+    pcesvn: u16,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TcbComponent {
+    svn: u8,
+}
+
+impl TcbInfo {
+    /// The latest TCB level whose SVNs the platform's all reach, as Intel's quote verification
+    /// library picks it
+    fn tcb_level(&self, cpu_svns: &[u8; 16], pce_svn: u16) -> Result<TcbLevel> {
+        for level in &self.tcbLevels {
+            if level.tcb.sgxtcbcomponents.len() != cpu_svns.len() {
+                bail!("The TCB info has a malformed TCB level")
+            }
+            let reached = level
+                .tcb
+                .sgxtcbcomponents
+                .iter()
+                .zip(cpu_svns)
+                .all(|(component, svn)| *svn >= component.svn);
+            if reached && pce_svn >= level.tcb.pcesvn {
+                return Ok(level.clone());
+            }
+        }
+        bail!("The platform's TCB level is not supported")
+    }
+}
+
+/// The quoting enclave Intel vouches for and its TCB levels, version 2 of Intel's QE identity.
+/// Masks and values are hex, MISCSELECT as a big endian integer, ATTRIBUTES in report order.
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+struct QeIdentity {
+    nextUpdate: String,
+    miscselect: String,
+    miscselectMask: String,
+    attributes: String,
+    attributesMask: String,
+    mrsigner: String,
+    isvprodid: u16,
+    /// Ordered from the latest TCB level down
+    tcbLevels: Vec<QeTcbLevel>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+struct QeTcbLevel {
+    tcb: QeTcb,
+    tcbStatus: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct QeTcb {
+    isvsvn: u16,
+}
+
+impl QeIdentity {
+    /// The TCB status of the quoting enclave that produced `qe_report`, failing unless it is the
+    /// enclave this identity describes
+    fn tcb_status(&self, qe_report: &[u8]) -> Result<String> {
+        check_next_update(&self.nextUpdate)?;
+        // The QE report is a report body, laid out as in a quote after its 48 byte header
+        let body = QuoteBody::from_bytes(&[&[0_u8; 48][..], qe_report].concat())?;
+        let masked = |value: &[u8], expected: &str, mask: &str| -> Result<bool> {
+            let expected = hex::decode(expected)?;
+            let mask = hex::decode(mask)?;
+            if expected.len() != value.len() || mask.len() != value.len() {
+                bail!("The QE identity has a malformed mask")
+            }
+            Ok(value
+                .iter()
+                .zip(&mask)
+                .zip(&expected)
+                .all(|((value, mask), expected)| value & mask == expected & mask))
+        };
+        if !masked(
+            &body.MISCSELECT.to_be_bytes(),
+            &self.miscselect,
+            &self.miscselectMask,
+        )? || !masked(&body.ATTRIBUTES, &self.attributes, &self.attributesMask)?
+            || !body.MRSIGNER.eq_ignore_ascii_case(&self.mrsigner)
+            || body.ISVPRODID != self.isvprodid
+        {
+            bail!("The quoting enclave is not the one Intel's QE identity describes")
+        }
+        match self.tcbLevels.iter().find(|l| body.ISVSVN >= l.tcb.isvsvn) {
+            Some(level) => Ok(level.tcbStatus.clone()),
+            None => bail!("The quoting enclave's TCB level is not supported"),
+        }
+    }
+}
+
+/// Verifies TCB info or QE identity JSON was signed by the first certificate of its PEM issuer
+/// chain, see `verify_sgx_cert_chain`, and parses its body
+fn verify_signed_collateral<T: serde::de::DeserializeOwned>(
+    json: &str,
+    issuer_chain: &str,
+) -> Result<T> {
+    let signed: SignedCollateral = serde_json::from_str(json)?;
+    let x509s = X509::stack_from_pem(issuer_chain.as_bytes())?;
+    verify_sgx_cert_chain(&x509s)?;
+    let signature = hex::decode(&signed.signature)?;
+    let signer = x509s[0].public_key()?.ec_key()?;
+    if !verify_p256_signature(&signer, signed.body.get().as_bytes(), &signature)? {
+        bail!("Failed to verify the collateral signature")
+    }
+    Ok(serde_json::from_str(signed.body.get())?)
+}
+
+/// Fails once `next_update`, a UTC time like 2024-06-19T10:23:31Z, has passed
+fn check_next_update(next_update: &str) -> Result<()> {
+    let next_update_time =
+        openssl::asn1::Asn1Time::from_str(&next_update.replace(['-', ':', 'T'], ""))
+            .with_context(|| format!("Malformed next update {next_update}"))?;
+    if next_update_time < openssl::asn1::Asn1Time::days_from_now(0)? {
+        bail!("The collateral expired at {next_update}")
+    }
+    Ok(())
+}
+
+/// Fails unless `crl`, PEM or hex encoded DER as PCCS serves them, is signed by `issuer` and
+/// does not revoke `x509`
+fn check_not_revoked(crl: &str, issuer: &X509, x509: &X509) -> Result<()> {
+    let crl = if crl.trim_start().starts_with("-----BEGIN") {
+        X509Crl::from_pem(crl.as_bytes())?
+    } else {
+        X509Crl::from_der(&hex::decode(crl.trim())?)?
+    };
+    if !crl.verify(&issuer.public_key()?)? {
+        bail!(
+            "The CRL is not signed by {}",
+            common_name(issuer.subject_name())?
+        )
+    }
+    match crl.get_by_cert(x509) {
+        CrlStatus::NotRevoked => Ok(()),
+        _ => bail!("{} is revoked", common_name(x509.subject_name())?),
+    }
+}
+
+/// GETs a PCCS resource, returning its body and the url-decoded issuer chain `header`
+async fn pccs_get(
+    client: &reqwest::Client,
+    url: &str,
+    header: Option<&str>,
+) -> Result<(String, String)> {
+    debug!("Fetching DCAP collateral {}", url);
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("PCCS request {url} failed"))?;
+    if !resp.status().is_success() {
+        bail!("PCCS request {} failed with {}", url, resp.status())
+    }
+    let issuer_chain = match header {
+        Some(header) => match resp.headers().get(header) {
+            Some(value) => percent_decode(value.to_str()?)?,
+            None => bail!("PCCS response to {} is missing {}", url, header),
+        },
+        None => String::new(),
+    };
+    Ok((resp.text().await?, issuer_chain))
+}
+
+/// Intel's Attestation Report Signing CA, which issues the certificates IAS signs reports with
+const INTEL_IAS_ROOT_CA_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIFSzCCA7OgAwIBAgIJANEHdl0yo7CUMA0GCSqGSIb3DQEBCwUAMH4xCzAJBgNV
+BAYTAlVTMQswCQYDVQQIDAJDQTEUMBIGA1UEBwwLU2FudGEgQ2xhcmExGjAYBgNV
+BAoMEUludGVsIENvcnBvcmF0aW9uMTAwLgYDVQQDDCdJbnRlbCBTR1ggQXR0ZXN0
+YXRpb24gUmVwb3J0IFNpZ25pbmcgQ0EwIBcNMTYxMTE0MTUzNzMxWhgPMjA0OTEy
+MzEyMzU5NTlaMH4xCzAJBgNVBAYTAlVTMQswCQYDVQQIDAJDQTEUMBIGA1UEBwwL
+U2FudGEgQ2xhcmExGjAYBgNVBAoMEUludGVsIENvcnBvcmF0aW9uMTAwLgYDVQQD
+DCdJbnRlbCBTR1ggQXR0ZXN0YXRpb24gUmVwb3J0IFNpZ25pbmcgQ0EwggGiMA0G
+CSqGSIb3DQEBAQUAA4IBjwAwggGKAoIBgQCfPGR+tXc8u1EtJzLA10Feu1Wg+p7e
+LmSRmeaCHbkQ1TF3Nwl3RmpqXkeGzNLd69QUnWovYyVSndEMyYc3sHecGgfinEeh
+rgBJSEdsSJ9FpaFdesjsxqzGRa20PYdnnfWcCTvFoulpbFR4VBuXnnVLVzkUvlXT
+L/TAnd8nIZk0zZkFJ7P5LtePvykkar7LcSQO85wtcQe0R1Raf/sQ6wYKaKmFgCGe
+NpEJUmg4ktal4qgIAxk+QHUxQE42sxViN5mqglB0QJdUot/o9a/V/mMeH8KvOAiQ
+byinkNndn+Bgk5sSV5DFgF0DffVqmVMblt5p3jPtImzBIH0QQrXJq39AT8cRwP5H
+afuVeLHcDsRp6hol4P+ZFIhu8mmbI1u0hH3W/0C2BuYXB5PC+5izFFh/nP0lc2Lf
+6rELO9LZdnOhpL1ExFOq9H/B8tPQ84T3Sgb4nAifDabNt/zu6MmCGo5U8lwEFtGM
+RoOaX4AS+909x00lYnmtwsDVWv9vBiJCXRsCAwEAAaOByTCBxjBgBgNVHR8EWTBX
+MFWgU6BRhk9odHRwOi8vdHJ1c3RlZHNlcnZpY2VzLmludGVsLmNvbS9jb250ZW50
+L0NSTC9TR1gvQXR0ZXN0YXRpb25SZXBvcnRTaWduaW5nQ0EuY3JsMB0GA1UdDgQW
+BBR4Q3t2pn680K9+QjfrNXw7hwFRPDAfBgNVHSMEGDAWgBR4Q3t2pn680K9+Qjfr
+NXw7hwFRPDAOBgNVHQ8BAf8EBAMCAQYwEgYDVR0TAQH/BAgwBgEB/wIBADANBgkq
+hkiG9w0BAQsFAAOCAYEAeF8tYMXICvQqeXYQITkV2oLJsp6J4JAqJabHWxYJHGir
+IEqucRiJSSx+HjIJEUVaj8E0QjEud6Y5lNmXlcjqRXaCPOqK0eGRz6hi+ripMtPZ
+sFNaBwLQVV905SDjAzDzNIDnrcnXyB4gcDFCvwDFKKgLRjOB/WAqgscDUoGq5ZVi
+zLUzTqiQPmULAQaB9c6Oti6snEFJiCQ67JLyW/E83/frzCmO5Ru6WjU4tmsmy8Ra
+Ud4APK0wZTGtfPXU7w+IBdG5Ez0kE1qzxGQaL4gINJ1zMyleDnbuS8UicjJijvqA
+152Sq049ESDz+1rRGc2NVEqh1KaGXmtXvqxXcTB+Ljy5Bw2ke0v8iGngFBPqCTVB
+3op5KBG3RjbF6RRSzwzuWfL7QErNC8WEy5yDVARzTA5+xmBc388v9Dm21HGfcC8O
+DD+gT9sSpssq0ascmvH49MOgjt1yoysLtdCtJW/9FZpoOypaHx0R+mJTLwPXVMrv
+DaVzWh5aiEx+idkSGMnX
+-----END CERTIFICATE-----
+";
+
+/// Intel's SGX Root CA, which issues the PCK certificate chains DCAP quotes embed
+const INTEL_SGX_ROOT_CA_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIICjzCCAjSgAwIBAgIUImUM1lqdNInzg7SVUr9QGzknBqwwCgYIKoZIzj0EAwIw
+aDEaMBgGA1UEAwwRSW50ZWwgU0dYIFJvb3QgQ0ExGjAYBgNVBAoMEUludGVsIENv
+cnBvcmF0aW9uMRQwEgYDVQQHDAtTYW50YSBDbGFyYTELMAkGA1UECAwCQ0ExCzAJ
+BgNVBAYTAlVTMB4XDTE4MDUyMTEwNDUxMFoXDTQ5MTIzMTIzNTk1OVowaDEaMBgG
+A1UEAwwRSW50ZWwgU0dYIFJvb3QgQ0ExGjAYBgNVBAoMEUludGVsIENvcnBvcmF0
+aW9uMRQwEgYDVQQHDAtTYW50YSBDbGFyYTELMAkGA1UECAwCQ0ExCzAJBgNVBAYT
+AlVTMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEC6nEwMDIYZOj/iPWsCzaEKi7
+1OiOSLRFhWGjbnBVJfVnkY4u3IjkDYYL0MxO4mqsyYjlBalTVYxFP2sJBK5zlKOB
+uzCBuDAfBgNVHSMEGDAWgBQiZQzWWp00ifODtJVSv1AbOScGrDBSBgNVHR8ESzBJ
+MEegRaBDhkFodHRwczovL2NlcnRpZmljYXRlcy50cnVzdGVkc2VydmljZXMuaW50
+ZWwuY29tL0ludGVsU0dYUm9vdENBLmRlcjAdBgNVHQ4EFgQUImUM1lqdNInzg7SV
+Ur9QGzknBqwwDgYDVR0PAQH/BAQDAgEGMBIGA1UdEwEB/wQIMAYBAf8CAQEwCgYI
+KoZIzj0EAwIDSQAwRgIhAOW/5QkR+S9CiSDcNoowLuPRLsWGf/Yi7GSX94BgwTwg
+AiEA4J0lrHoMs+Xo5o/sX6O9QWxHRAvZUGOdRQ7cvqRXaqI=
+-----END CERTIFICATE-----
+";
+
+#[cfg(any(test, feature = "mock-attestation"))]
+static MOCK_IAS_ROOT_CAS: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+#[cfg(any(test, feature = "mock-attestation"))]
+static MOCK_SGX_ROOT_CAS: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+/// Trusts `root_ca_pem` alongside Intel's root CA, so tests can verify reports signed by a mock
+/// IAS
+#[cfg(any(test, feature = "mock-attestation"))]
+pub fn trust_mock_ias_root_ca(root_ca_pem: &[u8]) -> Result<()> {
+    let der = X509::from_pem(root_ca_pem)?.to_der()?;
+    MOCK_IAS_ROOT_CAS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(der);
+    Ok(())
+}
+
+/// Trusts `root_ca_pem` alongside Intel's SGX root CA, so tests can verify quotes whose PCK
+/// certificate chain is mocked
+#[cfg(any(test, feature = "mock-attestation"))]
+pub fn trust_mock_sgx_root_ca(root_ca_pem: &[u8]) -> Result<()> {
+    let der = X509::from_pem(root_ca_pem)?.to_der()?;
+    MOCK_SGX_ROOT_CAS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(der);
+    Ok(())
+}
+
+/// A certificate for `common_name` issued by `issuer`, or self-signed, with `extensions`. CAs
+/// may sign certificates.
+#[cfg(any(test, feature = "mock-attestation"))]
+fn mock_cert(
+    common_name: &str,
+    serial: u32,
+    key: &openssl::pkey::PKeyRef<openssl::pkey::Private>,
+    issuer: Option<(&X509, &openssl::pkey::PKeyRef<openssl::pkey::Private>)>,
+    ca: bool,
+    extensions: Vec<openssl::x509::X509Extension>,
+) -> X509 {
+    use openssl::x509::extension::{BasicConstraints, KeyUsage};
+
+    let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, common_name)
+        .unwrap();
+    let name = name.build();
+    let mut builder = openssl::x509::X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(serial).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(key).unwrap();
+    let not_before = openssl::asn1::Asn1Time::days_from_now(0).unwrap();
+    let not_after = openssl::asn1::Asn1Time::days_from_now(1).unwrap();
+    builder.set_not_before(&not_before).unwrap();
+    builder.set_not_after(&not_after).unwrap();
+    if ca {
+        let constraints = BasicConstraints::new().critical().ca().build().unwrap();
+        builder.append_extension(constraints).unwrap();
+        let usage = KeyUsage::new()
+            .critical()
+            .key_cert_sign()
+            .crl_sign()
+            .build()
+            .unwrap();
+        builder.append_extension(usage).unwrap();
+    }
+    for extension in extensions {
+        builder.append_extension(extension).unwrap();
+    }
+    let (issuer_name, issuer_key) = match issuer {
+        Some((issuer_x509, issuer_key)) => (issuer_x509.subject_name(), issuer_key),
+        None => (&*name, key),
+    };
+    builder.set_issuer_name(issuer_name).unwrap();
+    builder
+        .sign(issuer_key, openssl::hash::MessageDigest::sha256())
+        .unwrap();
+    builder.build()
+}
+
+/// A root CA and report signing certificate with the names IAS uses, neither issued by Intel
+#[cfg(any(test, feature = "mock-attestation"))]
+fn mock_ias_pki() -> (X509, X509, openssl::pkey::PKey<openssl::pkey::Private>) {
+    let rsa = || openssl::pkey::PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap());
+    let root_key = rsa().unwrap();
+    let signing_key = rsa().unwrap();
+    let root_x509 = mock_cert(
+        "Intel SGX Attestation Report Signing CA",
+        1,
+        &root_key,
+        None,
+        true,
+        vec![],
+    );
+    let signing_x509 = mock_cert(
+        "Intel SGX Attestation Report Signing",
+        2,
+        &signing_key,
+        Some((&root_x509, &root_key)),
+        false,
+        vec![],
+    );
+    (root_x509, signing_x509, signing_key)
+}
+
+/// EPID evidence of `data` from an enclave measuring `mrenclave`, signed by a mock IAS whose root
+/// CA is trusted from then on, so tests can reach the paths that only take valid evidence
+#[cfg(any(test, feature = "mock-attestation"))]
+pub fn mock_ias_evidence(mrenclave: &[u8; 32], data: &[u8]) -> Result<AttestationEvidence> {
+    let (root_x509, signing_x509, signing_key) = mock_ias_pki();
+    let root_pem = String::from_utf8(root_x509.to_pem()?)?;
+    trust_mock_ias_root_ca(root_pem.as_bytes())?;
+
+    let mut body = [0_u8; 432];
+    body[112..144].copy_from_slice(mrenclave);
+    body[368..432].copy_from_slice(&to_report_data(data)?);
+    let raw_report = serde_json::to_string(&AttestationReport {
+        id: "1".into(),
+        timestamp: "2023-01-20T19:47:28.465440".into(),
+        version: 4,
+        isvEnclaveQuoteStatus: "OK".into(),
+        isvEnclaveQuoteBody: openssl::base64::encode_block(&body),
+        ..Default::default()
+    })?;
+    let mut signer =
+        openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), &signing_key)?;
+    signer.update(raw_report.as_bytes())?;
+    Ok(AttestationEvidence {
+        raw_report,
+        signed_report: openssl::base64::encode_block(&signer.sign_to_vec()?),
+        signing_cert: String::from_utf8(signing_x509.to_pem()?)? + &root_pem,
+        ..Default::default()
+    })
+}
+
+/// Whether `root_x509` is Intel's IAS root CA, or a mock root CA trusted for tests
+fn is_trusted_ias_root_ca(root_x509: &X509) -> Result<bool> {
+    let der = root_x509.to_der()?;
+    if der == X509::from_pem(INTEL_IAS_ROOT_CA_PEM.as_bytes())?.to_der()? {
+        return Ok(true);
+    }
+    #[cfg(any(test, feature = "mock-attestation"))]
+    if MOCK_IAS_ROOT_CAS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&der)
+    {
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Whether `root_x509` is Intel's SGX root CA, or a mock root CA trusted for tests
+fn is_trusted_sgx_root_ca(root_x509: &X509) -> Result<bool> {
+    let der = root_x509.to_der()?;
+    if der == X509::from_pem(INTEL_SGX_ROOT_CA_PEM.as_bytes())?.to_der()? {
+        return Ok(true);
+    }
+    #[cfg(any(test, feature = "mock-attestation"))]
+    if MOCK_SGX_ROOT_CAS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&der)
+    {
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Verifies the first certificate of `x509s` is issued through the rest up to the last, which
+/// must be Intel's SGX root CA, see `is_trusted_sgx_root_ca`
+fn verify_sgx_cert_chain(x509s: &[X509]) -> Result<()> {
+    let (leaf_x509, root_x509) = match (x509s.first(), x509s.last()) {
+        (Some(leaf), Some(root)) if x509s.len() > 1 => (leaf, root),
+        _ => bail!("Expected a certificate chain up to the SGX root CA"),
+    };
+    // The names alone can be copied by any self-signed certificate
+    if !is_trusted_sgx_root_ca(root_x509)? {
+        bail!("The SGX root CA is not Intel's")
+    }
+
+    let mut builder = X509StoreBuilder::new()?;
+    builder.add_cert(root_x509.clone())?;
+    let trust = builder.build();
+
+    let mut cert_chain: Stack<X509> = Stack::new()?;
+    for x509 in &x509s[1..] {
+        cert_chain
+            .push(x509.clone())
+            .with_context(|| "could not push to cert chain")?;
+    }
+
+    let mut store = X509StoreContext::new()?;
+    match store.init(trust.as_ref(), leaf_x509, cert_chain.as_ref(), |c| {
+        c.verify_cert()
+    }) {
+        Ok(true) => Ok(()),
+        _ => bail!("Failed to verify the certificate chain"),
+    }
+}
+
+/// Decodes the %XX escapes PCCS and IAS use for PEM chains in headers
+fn percent_decode(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let escape = value
+                .get(i + 1..i + 3)
+                .with_context(|| "Truncated percent escape")?;
+            decoded.push(u8::from_str_radix(escape, 16)?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(String::from_utf8(decoded)?)
+}
+
+fn common_name(name: &X509NameRef) -> Result<String> {
+    match name.entries_by_nid(Nid::COMMONNAME).last() {
+        Some(name) => Ok(name
+            .data()
+            .as_utf8()
+            .with_context(|| "Couldn't convert x509 name data to string")?
+            .to_string()),
+        None => bail!("Couldn't extract COMMONNAME from intel x509 cert"),
+    }
+}
+
+/// Reads the FMSPC, the platform's family and model, from the SGX extension of a DER PCK
+/// certificate. It is the 6 byte OCTET STRING after the OID 1.2.840.113741.1.13.1.4.
+fn pck_fmspc(der: &[u8]) -> Result<[u8; 6]> {
+    const FMSPC_OID: [u8; 12] = [
+        0x06, 0x0a, 0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01, 0x04,
+    ];
+    let at = match der.windows(FMSPC_OID.len()).position(|w| w == FMSPC_OID) {
+        Some(at) => at + FMSPC_OID.len(),
+        None => bail!("PCK certificate has no FMSPC"),
+    };
+    match der.get(at..at + 8) {
+        Some([0x04, 0x06, fmspc @ ..]) => Ok(fmspc.try_into()?),
+        _ => bail!("PCK certificate has a malformed FMSPC"),
+    }
+}
+
+/// Reads the TCB the PCK certificate was issued for from its SGX extension, the 16 CPU SVN
+/// components and the PCE SVN. They are the INTEGERs after the OIDs 1.2.840.113741.1.13.1.2.1
+/// to .17.
+fn pck_tcb(der: &[u8]) -> Result<([u8; 16], u16)> {
+    let integer = |n: u8| -> Result<u16> {
+        let oid = [
+            0x06, 0x0b, 0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01, 0x02, n,
+        ];
+        let at = match der.windows(oid.len()).position(|w| w == oid) {
+            Some(at) => at + oid.len(),
+            None => bail!("PCK certificate has no TCB component {}", n),
+        };
+        match der.get(at..) {
+            Some([0x02, len @ 1..=3, rest @ ..])
+                if rest.len() >= *len as usize && rest[0] & 0x80 == 0 =>
+            {
+                let value = rest[..*len as usize]
+                    .iter()
+                    .fold(0_u32, |value, byte| (value << 8) | *byte as u32);
+                Ok(u16::try_from(value)?)
+            }
+            _ => bail!("PCK certificate has a malformed TCB component {}", n),
+        }
+    };
+    let mut cpu_svns = [0_u8; 16];
+    for (i, svn) in cpu_svns.iter_mut().enumerate() {
+        *svn = u8::try_from(integer(i as u8 + 1)?)?;
+    }
+    Ok((cpu_svns, integer(17)?))
+}
+
+/// Verifies a raw r || s ECDSA P-256 signature over the SHA-256 of `msg`
+fn verify_p256_signature(key: &EcKeyRef<Public>, msg: &[u8], sig: &[u8]) -> Result<bool> {
+    if sig.len() != 64 {
+        bail!("ECDSA signature was not the right length of 64B!")
+    }
+    let sig = EcdsaSig::from_private_components(
+        BigNum::from_slice(&sig[..32])?,
+        BigNum::from_slice(&sig[32..])?,
+    )?;
+    Ok(sig.verify(&openssl::sha::sha256(msg), key)?)
+}
+
+/// The sections of a version 3 ECDSA quote, following
+/// https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_SGX_ECDSA_QuoteLibReference_DCAP_API.pdf
+struct DcapQuote<'a> {
+    /// The quote header and the enclave's report body, which the attestation key signs
+    signed: &'a [u8],
+    signature: &'a [u8],
+    attestation_key: &'a [u8],
+    qe_report: &'a [u8],
+    qe_report_signature: &'a [u8],
+    qe_auth_data: &'a [u8],
+    certification_data_type: u16,
+    certification_data: &'a [u8],
+}
+
+impl<'a> DcapQuote<'a> {
+    fn parse(quote: &'a [u8]) -> Result<Self> {
+        let mut at = 0;
+        let mut take = |len: usize| -> Result<&'a [u8]> {
+            let bytes = match quote.get(at..at + len) {
+                Some(bytes) => bytes,
+                None => bail!("DCAP quote was truncated at byte {}", at),
+            };
+            at += len;
+            Ok(bytes)
+        };
+
+        let signed = take(432)?;
+        if u16::from_le_bytes(signed[0..2].try_into()?) != 3 {
+            bail!("Only version 3 DCAP quotes are supported")
+        }
+        let _signature_data_len = take(4)?;
+        let signature = take(64)?;
+        let attestation_key = take(64)?;
+        let qe_report = take(384)?;
+        let qe_report_signature = take(64)?;
+        let qe_auth_data_len = u16::from_le_bytes(take(2)?.try_into()?);
+        let qe_auth_data = take(qe_auth_data_len as usize)?;
+        let certification_data_type = u16::from_le_bytes(take(2)?.try_into()?);
+        let certification_data_len = u32::from_le_bytes(take(4)?.try_into()?);
+        let certification_data = take(certification_data_len as usize)?;
+
+        Ok(DcapQuote {
+            signed,
+            signature,
+            attestation_key,
+            qe_report,
+            qe_report_signature,
+            qe_auth_data,
+            certification_data_type,
+            certification_data,
+        })
+    }
+
+    /// The PCK certificate, its intermediate CA, and the root CA
+    fn pck_cert_chain(&self) -> Result<Vec<X509>> {
+        // Certification data type 5 is the PEM chain, other types need a PCCS lookup
+        if self.certification_data_type != 5 {
+            bail!(
+                "Unsupported DCAP certification data type {}",
+                self.certification_data_type
+            )
+        }
+        let pem = match self.certification_data.iter().position(|b| *b == 0) {
+            Some(end) => &self.certification_data[..end],
+            None => self.certification_data,
+        };
+        Ok(X509::stack_from_pem(pem)?)
+    }
+}
+
+#[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct AttestationReport {
     pub id: String,
@@ -271,7 +1845,14 @@ impl AttestationReport {
     pub fn deserialize_quote_body(&self) -> Result<QuoteBody> {
         let body = &self.isvEnclaveQuoteBody;
         let body_decoded = openssl::base64::decode_block(body)?;
+        QuoteBody::from_bytes(&body_decoded)
+    }
+}
 
+impl QuoteBody {
+    /// Decodes the 432 byte quote header and report body. The header fields are named for EPID,
+    /// the report body is shared with DCAP quotes.
+    pub fn from_bytes(body_decoded: &[u8]) -> Result<Self> {
         if body_decoded.len() != 432 {
             bail!("base64 decoded quote body was not the right length of 432B!")
         }
@@ -361,4 +1942,940 @@ mod tests {
         assert_eq!(exp_eth_pk, got_pk);
         Ok(())
     }
+
+    #[test]
+    fn test_evidence_predating_dcap_is_epid() {
+        let evidence = fetch_dummy_bls_evidence();
+        assert_eq!(evidence.kind, EvidenceKind::Epid);
+        assert!(evidence.collateral.is_none());
+
+        let json = serde_json::to_value(&evidence).unwrap();
+        assert_eq!(json["kind"], "epid");
+        assert!(json.get("quote").is_none());
+        assert!(json.get("collateral").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dcap_evidence_outside_sgx_is_empty() {
+        // Nothing is fetched from the PCCS without a quote
+        let evidence = AttestationEvidence::new_dcap(&[7_u8; 48], "https://localhost:1")
+            .await
+            .unwrap();
+        assert_eq!(evidence.kind, EvidenceKind::Dcap);
+        assert!(evidence.quote.is_empty());
+        assert!(evidence.collateral.is_none());
+        assert!(evidence.verify_intel_signing_certificate().is_err());
+        assert!(AttestationEvidence::new_dcap(&[0_u8; 65], "")
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_dcap_quote_parsing() {
+        let mut quote = vec![0_u8; 432];
+        quote[0] = 3;
+        quote.extend_from_slice(&[0_u8; 4 + 64 + 64 + 384 + 64]);
+        // QE auth data
+        quote.extend_from_slice(&2_u16.to_le_bytes());
+        quote.extend_from_slice(&[0xaa, 0xbb]);
+        // PEM certification data with a trailing NUL
+        quote.extend_from_slice(&5_u16.to_le_bytes());
+        quote.extend_from_slice(&4_u32.to_le_bytes());
+        quote.extend_from_slice(b"pem\0");
+
+        let parsed = DcapQuote::parse(&quote).unwrap();
+        assert_eq!(parsed.signed, &quote[..432]);
+        assert_eq!(parsed.qe_auth_data, &[0xaa, 0xbb]);
+        assert_eq!(parsed.certification_data_type, 5);
+        assert_eq!(parsed.certification_data, b"pem\0");
+        assert!(QuoteBody::from_bytes(parsed.signed).is_ok());
+
+        assert!(DcapQuote::parse(&quote[..quote.len() - 1]).is_err());
+        quote[0] = 4;
+        assert!(DcapQuote::parse(&quote).is_err());
+    }
+
+    #[test]
+    fn test_pck_fmspc() {
+        let mut der = vec![0x30, 0x82];
+        der.extend_from_slice(&[
+            0x06, 0x0a, 0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01, 0x04,
+        ]);
+        der.extend_from_slice(&[0x04, 0x06, 0x00, 0x90, 0x6e, 0xa1, 0x00, 0x00]);
+        assert_eq!(
+            pck_fmspc(&der).unwrap(),
+            [0x00, 0x90, 0x6e, 0xa1, 0x00, 0x00]
+        );
+        assert!(pck_fmspc(&der[..der.len() - 1]).is_err());
+        assert!(pck_fmspc(&[0x30, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(
+            percent_decode("-----BEGIN%20CERTIFICATE-----%0AMII%2B").unwrap(),
+            "-----BEGIN CERTIFICATE-----\nMII+"
+        );
+        assert!(percent_decode("%2").is_err());
+        assert!(percent_decode("%zz").is_err());
+    }
+
+    #[test]
+    fn test_verdict_of_valid_evidence() {
+        let verdict = fetch_dummy_bls_evidence().verify();
+        assert!(verdict.valid, "{:?}", verdict.error);
+        assert_eq!(verdict.kind, EvidenceKind::Epid);
+        assert_eq!(verdict.status.as_deref(), Some("SW_HARDENING_NEEDED"));
+        assert_eq!(
+            verdict.advisory_ids,
+            vec!["INTEL-SA-00334".to_string(), "INTEL-SA-00615".to_string()]
+        );
+        assert_eq!(
+            verdict.mrenclave.as_deref(),
+            Some("4db2b7e0ca5fecaaf37973fa19e55e8c973ad11ed0f663ee51027e499185ad72")
+        );
+        assert_eq!(
+            verdict.mrsigner.as_deref(),
+            Some("83d719e77deaca1470f6baf62a4d774303c899db69020f9c70ee1dfc08c7ce9e")
+        );
+        assert_eq!(verdict.isv_prod_id, Some(0));
+        assert_eq!(verdict.isv_svn, Some(0));
+        assert!(verdict
+            .report_data
+            .unwrap()
+            .starts_with("0x8e2a741e80fee324a0915b40aec28701"));
+        assert!(verdict.error.is_none());
+    }
+
+    #[test]
+    fn test_verdict_of_tampered_evidence() {
+        // Swapping the attested key for another one breaks the IAS signature
+        let mut evidence = fetch_dummy_bls_evidence();
+        let other = fetch_dummy_eth_evidence();
+        let other_report: AttestationReport =
+            serde_json::from_slice(other.raw_report.as_bytes()).unwrap();
+        let mut report: AttestationReport =
+            serde_json::from_slice(evidence.raw_report.as_bytes()).unwrap();
+        report.isvEnclaveQuoteBody = other_report.isvEnclaveQuoteBody;
+        evidence.raw_report = serde_json::to_string(&report).unwrap();
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("IAS signature"));
+        // The claimed key is still reported
+        assert!(verdict
+            .report_data
+            .unwrap()
+            .starts_with("0x027ca56a6b4fe0cd6a635508378b7db6"));
+
+        // So does upgrading the quote status
+        let mut evidence = fetch_dummy_bls_evidence();
+        evidence.raw_report = evidence.raw_report.replace("SW_HARDENING_NEEDED", "OK");
+        assert!(!evidence.verify().valid);
+
+        // Or presenting another report's signature
+        let mut evidence = fetch_dummy_bls_evidence();
+        evidence.signed_report = other.signed_report;
+        assert!(!evidence.verify().valid);
+
+        // Or a certificate that is not rooted in Intel's CA
+        let mut evidence = fetch_dummy_bls_evidence();
+        evidence.signing_cert = evidence.signing_cert.replacen("MIIEoTCC", "MIIEoTCD", 1);
+        assert!(!evidence.verify().valid);
+
+        let verdict = AttestationEvidence::default().verify();
+        assert!(!verdict.valid);
+        assert!(verdict.mrenclave.is_none());
+    }
+
+    #[test]
+    fn test_untrusted_quote_status_is_rejected() {
+        let mut evidence = fetch_dummy_bls_evidence();
+        evidence.raw_report = evidence
+            .raw_report
+            .replace("SW_HARDENING_NEEDED", "GROUP_REVOKED");
+        let mut verdict = AttestationVerdict::default();
+        assert!(evidence.verify_quote_status(&mut verdict).is_err());
+        assert_eq!(verdict.status.as_deref(), Some("GROUP_REVOKED"));
+
+        let evidence = fetch_dummy_bls_evidence();
+        let mut verdict = AttestationVerdict::default();
+        evidence.verify_quote_status(&mut verdict).unwrap();
+        assert_eq!(verdict.status.as_deref(), Some("SW_HARDENING_NEEDED"));
+    }
+
+    #[test]
+    fn test_key_binding_report_data() {
+        let eth_pk = format!("0x02{}", "11".repeat(32));
+        let bls_pk = format!("0x{}", "22".repeat(48));
+        let binding = KeyBinding {
+            eth_pubkey: Some(eth_pk.clone()),
+            bls_pubkey: Some(bls_pk.clone()),
+            nonce: "0x33".into(),
+        };
+        let preimage = [
+            crate::parse_hex(&eth_pk).unwrap(),
+            crate::parse_hex(&bls_pk).unwrap(),
+            vec![0x33],
+        ]
+        .concat();
+        assert_eq!(
+            binding.report_data().unwrap(),
+            openssl::sha::sha256(&preimage)
+        );
+
+        // Absent keys are zero-filled, so a key cannot move between slots
+        let bls_only = KeyBinding {
+            bls_pubkey: Some(bls_pk.clone()),
+            ..Default::default()
+        };
+        let preimage = [vec![0; 33], crate::parse_hex(&bls_pk).unwrap()].concat();
+        assert_eq!(
+            bls_only.report_data().unwrap(),
+            openssl::sha::sha256(&preimage)
+        );
+
+        assert!(KeyBinding::default().report_data().is_err());
+        let mut bad = binding.clone();
+        bad.eth_pubkey = Some(bls_pk);
+        assert!(bad.report_data().is_err());
+
+        // Evidence of the raw public key does not commit to a binding
+        let evidence = fetch_dummy_bls_evidence();
+        let binding = KeyBinding {
+            bls_pubkey: Some(format!("0x{}", evidence.get_bls_pk().unwrap().to_hex())),
+            ..Default::default()
+        };
+        assert!(binding.verify(&evidence).is_err());
+    }
+
+    #[test]
+    fn test_placeholder_evidence_outside_sgx() {
+        let evidence = AttestationEvidence::new(&[7_u8; 48]).unwrap();
+        let mut report_data = [0_u8; 64];
+        report_data[..48].copy_from_slice(&[7_u8; 48]);
+        assert_eq!(evidence.get_report_data().unwrap(), report_data);
+        assert_eq!(evidence.get_mrenclave().unwrap(), hex::encode([0_u8; 32]));
+        assert!(evidence.verify_intel_signing_certificate().is_err());
+        assert!(!evidence.verify().valid);
+
+        // The IAS header names are accepted for the EPID fields
+        let json = serde_json::json!({
+            "ias_report": evidence.raw_report,
+            "ias_signature": "",
+            "ias_cert_chain": "",
+        });
+        let renamed: AttestationEvidence = serde_json::from_value(json).unwrap();
+        assert_eq!(renamed, evidence);
+    }
+
+    #[test]
+    fn test_key_set_report_data() {
+        let eth_pks = vec![
+            format!("0x03{}", "11".repeat(32)),
+            format!("0x02{}", "22".repeat(32)),
+        ];
+        let bls_pks = vec![format!("0x{}", "33".repeat(48))];
+        let key_set = KeySetBinding {
+            eth_pubkeys: eth_pks.clone(),
+            bls_pubkeys: bls_pks.clone(),
+            count: 3,
+            nonce: "0x44".into(),
+        };
+        let preimage = [
+            b"SECURE_SIGNER_KEY_SET".to_vec(),
+            2_u32.to_be_bytes().to_vec(),
+            crate::parse_hex(&eth_pks[1]).unwrap(),
+            crate::parse_hex(&eth_pks[0]).unwrap(),
+            1_u32.to_be_bytes().to_vec(),
+            crate::parse_hex(&bls_pks[0]).unwrap(),
+            vec![0x44],
+        ]
+        .concat();
+        assert_eq!(
+            key_set.report_data().unwrap(),
+            openssl::sha::sha256(&preimage)
+        );
+
+        // The digest does not depend on the listed order
+        let mut reordered = key_set.clone();
+        reordered.eth_pubkeys.reverse();
+        assert_eq!(
+            reordered.report_data().unwrap(),
+            key_set.report_data().unwrap()
+        );
+
+        // Omitted keys are detected by the count, or change the digest
+        let mut omitted = key_set.clone();
+        omitted.eth_pubkeys.pop();
+        assert!(omitted.report_data().is_err());
+        omitted.count = 2;
+        assert_ne!(
+            omitted.report_data().unwrap(),
+            key_set.report_data().unwrap()
+        );
+
+        // A key cannot move between the lists
+        let mut moved = key_set.clone();
+        moved.bls_pubkeys.push(moved.eth_pubkeys.pop().unwrap());
+        assert!(moved.report_data().is_err());
+
+        assert_ne!(
+            KeySetBinding::default().report_data().unwrap(),
+            openssl::sha::sha256(&[])
+        );
+    }
+
+    #[test]
+    fn test_evidence_cache() {
+        let bls_pk = crate::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_hex();
+        let binding = KeyBinding {
+            bls_pubkey: Some(format!("0x{bls_pk}")),
+            nonce: "0x01".into(),
+            ..Default::default()
+        };
+        assert!(cached_evidence(&binding, EvidenceKind::Dcap).is_none());
+
+        let evidence = AttestationEvidence {
+            kind: EvidenceKind::Dcap,
+            quote: "AwA=".into(),
+            ..Default::default()
+        };
+        let generated_at = cache_evidence(binding.clone(), evidence.clone());
+        let cached = cached_evidence(&binding, EvidenceKind::Dcap).unwrap();
+        assert_eq!(cached.evidence, evidence);
+        assert_eq!(cached.generated_at, generated_at);
+
+        // Evidence is only shared for the same kind and nonce
+        assert!(cached_evidence(&binding, EvidenceKind::Epid).is_none());
+        let mut other_nonce = binding.clone();
+        other_nonce.nonce = "0x02".into();
+        assert!(cached_evidence(&other_nonce, EvidenceKind::Dcap).is_none());
+
+        // Rewriting or deleting the key drops its evidence, whatever the hex form
+        evict_cached_evidence(&bls_pk.to_uppercase());
+        assert!(cached_evidence(&binding, EvidenceKind::Dcap).is_none());
+    }
+
+    #[test]
+    fn test_ias_config_never_reveals_credentials() {
+        let access_key = "5c216729e0524cc887d344a1ea5d2b8b";
+        let spid = "42616C98D53C9712639447C9B0E7003F";
+        let path = std::env::temp_dir().join("test_ias_config.json");
+        let path = path.to_str().unwrap();
+
+        std::fs::write(
+            path,
+            format!(r#"{{"enclave_spid":"{spid}","ias_access_key":"{access_key}"}}"#),
+        )
+        .unwrap();
+        let config = IasConfig::from_file(path).unwrap();
+        assert_eq!(config.ias_url, crate::constants::DEFAULT_IAS_URL);
+        assert_eq!(config.ias_access_key, access_key);
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains(access_key));
+        assert!(!debug.contains(spid));
+
+        // Neither malformed nor invalid files are echoed back in the error
+        for json in [
+            format!(r#"{{"enclave_spid":"{spid}","ias_access_key":"{access_key}""#),
+            format!(r#"{{"enclave_spid":"{spid}zz","ias_access_key":"{access_key}"}}"#),
+            format!(r#"{{"enclave_spid":"{spid}","ias_access_key":{access_key}}}"#),
+        ] {
+            std::fs::write(path, json).unwrap();
+            let e = format!("{:?}", IasConfig::from_file(path).unwrap_err());
+            assert!(!e.contains(access_key));
+            assert!(!e.contains(spid));
+        }
+        std::fs::remove_file(path).unwrap();
+
+        let e = require_ias_config().unwrap_err();
+        assert!(e.downcast_ref::<AttestationNotConfigured>().is_some());
+    }
+
+    #[test]
+    fn test_measurement_policy() {
+        let verdict = fetch_dummy_bls_evidence().verify();
+        let mrenclave = verdict.mrenclave.clone().unwrap();
+        let mrsigner = verdict.mrsigner.clone().unwrap();
+
+        // Measurements are normalized when loaded
+        let path = std::env::temp_dir().join("test_measurement_policy_unit.json");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            format!(
+                r#"{{"mrenclaves":["0x{}"],"min_isv_svn":1}}"#,
+                mrenclave.to_uppercase()
+            ),
+        )
+        .unwrap();
+        let mut policy = MeasurementPolicy::from_file(path).unwrap();
+        assert_eq!(policy.mrenclaves, vec![mrenclave.clone()]);
+        for json in [r#"{}"#, r#"{"mrsigners":["0x1234"]}"#] {
+            std::fs::write(path, json).unwrap();
+            assert!(MeasurementPolicy::from_file(path).is_err());
+        }
+        std::fs::remove_file(path).unwrap();
+
+        // The dummy evidence has ISV SVN 0
+        let violation = policy.check(&verdict).unwrap_err();
+        assert_eq!(violation.mrenclave, mrenclave);
+        assert!(violation.to_string().contains(&mrenclave));
+        policy.min_isv_svn = 0;
+        assert!(policy.check(&verdict).is_ok());
+
+        // Either measurement is enough
+        policy.mrenclaves = vec![];
+        assert!(policy.check(&verdict).is_err());
+        policy.mrsigners = vec![mrsigner];
+        assert!(policy.check(&verdict).is_ok());
+        assert!(policy.check(&AttestationVerdict::default()).is_err());
+
+        // No evidence is allowed before a policy is loaded
+        let e = check_measurement_policy(&fetch_dummy_bls_evidence()).unwrap_err();
+        assert!(e.is::<NoMeasurementPolicy>());
+    }
+
+    /// Serves IAS's `POST /report` on a local port, signing reports over the posted quote's body
+    /// with `signing_key` the way IAS does. Returns the base url.
+    async fn spawn_mock_ias(
+        cert_chain_pem: String,
+        signing_key: openssl::pkey::PKey<openssl::pkey::Private>,
+    ) -> String {
+        let report = move |axum::Json(req): axum::Json<serde_json::Value>| {
+            let signing_key = signing_key.clone();
+            let cert_chain_pem = cert_chain_pem.clone();
+            async move {
+                let quote = openssl::base64::decode_block(
+                    req["isvEnclaveQuote"].as_str().unwrap_or_default(),
+                )
+                .unwrap_or_default();
+                let report = AttestationReport {
+                    id: "1".into(),
+                    timestamp: "2023-01-20T19:47:28.465440".into(),
+                    version: 4,
+                    isvEnclaveQuoteStatus: "OK".into(),
+                    isvEnclaveQuoteBody: openssl::base64::encode_block(&quote[..432]),
+                    ..Default::default()
+                };
+                let report = serde_json::to_string(&report).unwrap();
+                let mut signer = openssl::sign::Signer::new(
+                    openssl::hash::MessageDigest::sha256(),
+                    &signing_key,
+                )
+                .unwrap();
+                signer.update(report.as_bytes()).unwrap();
+                let signature = openssl::base64::encode_block(&signer.sign_to_vec().unwrap());
+                let cert_chain: String = cert_chain_pem
+                    .bytes()
+                    .map(|b| match b {
+                        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' => (b as char).to_string(),
+                        _ => format!("%{:02X}", b),
+                    })
+                    .collect();
+                (
+                    [
+                        ("X-IASReport-Signature", signature),
+                        ("X-IASReport-Signing-Certificate", cert_chain),
+                    ],
+                    report,
+                )
+            }
+        };
+        let app = axum::Router::new().route("/attestation/v4/report", axum::routing::post(report));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/attestation/v4", listener.local_addr().unwrap());
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+        url
+    }
+
+    /// Posts `quote` to the IAS at `ias_url` and assembles the evidence from its response
+    async fn fetch_ias_evidence(ias_url: &str, quote: &[u8]) -> Result<AttestationEvidence> {
+        let resp = reqwest::Client::new()
+            .post(format!("{ias_url}/report"))
+            .json(&serde_json::json!({ "isvEnclaveQuote": openssl::base64::encode_block(quote) }))
+            .send()
+            .await?
+            .error_for_status()?;
+        let header = |name: &str| -> Result<String> {
+            Ok(resp
+                .headers()
+                .get(name)
+                .with_context(|| format!("IAS response has no {name} header"))?
+                .to_str()?
+                .to_string())
+        };
+        let signed_report = header("X-IASReport-Signature")?;
+        let signing_cert = percent_decode(&header("X-IASReport-Signing-Certificate")?)?;
+        Ok(AttestationEvidence {
+            raw_report: resp.text().await?,
+            signed_report,
+            signing_cert,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_verify_mock_ias_report() {
+        let (root_x509, signing_x509, signing_key) = mock_ias_pki();
+        let root_pem = String::from_utf8(root_x509.to_pem().unwrap()).unwrap();
+        let cert_chain_pem = String::from_utf8(signing_x509.to_pem().unwrap()).unwrap() + &root_pem;
+        let ias_url = spawn_mock_ias(cert_chain_pem, signing_key).await;
+
+        // An EPID quote is the 432 byte body followed by the EPID signature
+        let mut quote = vec![0_u8; 432];
+        quote[112..144].copy_from_slice(&[0xaa; 32]);
+        quote[368..400].copy_from_slice(&[7_u8; 32]);
+        quote.extend_from_slice(&[0xee; 680]);
+        let evidence = fetch_ias_evidence(&ias_url, &quote).await.unwrap();
+
+        // The mock root CA is refused until it is trusted
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("not Intel's"));
+
+        trust_mock_ias_root_ca(root_pem.as_bytes()).unwrap();
+        let verdict = evidence.verify();
+        assert!(verdict.valid, "{:?}", verdict.error);
+        assert_eq!(verdict.status.as_deref(), Some("OK"));
+        assert_eq!(verdict.mrenclave, Some(hex::encode([0xaa; 32])));
+        evidence.verify_report_data(&[7_u8; 32]).unwrap();
+
+        // The signature covers the raw report
+        let mut tampered = evidence.clone();
+        tampered.raw_report = tampered
+            .raw_report
+            .replace("\"OK\"", "\"GROUP_OUT_OF_DATE\"");
+        assert_ne!(tampered.raw_report, evidence.raw_report);
+        assert!(!tampered.verify().valid);
+
+        // A self-signed certificate with IAS's name is not enough
+        let (_, other_signing_x509, _) = mock_ias_pki();
+        let mut forged = evidence.clone();
+        forged.signing_cert =
+            String::from_utf8(other_signing_x509.to_pem().unwrap()).unwrap() + &root_pem;
+        assert!(!forged.verify().valid);
+    }
+
+    #[test]
+    fn test_intel_ias_root_ca_is_pinned() {
+        let x509s =
+            X509::stack_from_pem(fetch_dummy_bls_evidence().signing_cert.as_bytes()).unwrap();
+        assert!(is_trusted_ias_root_ca(&x509s[1]).unwrap());
+        assert!(!is_trusted_ias_root_ca(&x509s[0]).unwrap());
+    }
+
+    /// The FMSPC and TCB the PCK certificates of `mock_dcap_pki` are issued for
+    const MOCK_FMSPC: [u8; 6] = [0x00, 0x90, 0x6e, 0xa1, 0x00, 0x00];
+    const MOCK_CPU_SVN: u8 = 5;
+    const MOCK_PCE_SVN: u16 = 11;
+    /// The quoting enclave of `mock_dcap_evidence`
+    const MOCK_QE_MRSIGNER: [u8; 32] = [0x8c; 32];
+    const MOCK_QE_ISVSVN: u16 = 8;
+
+    /// A DER TLV of `tag` around `content`
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut tlv = vec![tag];
+        match content.len() {
+            len @ 0..=0x7f => tlv.push(len as u8),
+            len @ 0x80..=0xff => tlv.extend([0x81, len as u8]),
+            len => {
+                tlv.push(0x82);
+                tlv.extend((len as u16).to_be_bytes());
+            }
+        }
+        tlv.extend_from_slice(content);
+        tlv
+    }
+
+    fn der_integer(value: u32) -> Vec<u8> {
+        let mut content: Vec<u8> = value
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        if content.is_empty() || content[0] & 0x80 != 0 {
+            content.insert(0, 0);
+        }
+        der(0x02, &content)
+    }
+
+    /// The SGX extension of a PCK certificate for a platform of `fmspc` at the given TCB
+    fn sgx_extension_der(fmspc: &[u8; 6], cpu_svns: &[u8; 16], pce_svn: u16) -> Vec<u8> {
+        let oid = |arcs: &[u8]| {
+            let sgx = [0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+            der(0x06, &[&sgx[..], arcs].concat())
+        };
+        let mut tcb = Vec::new();
+        for (i, svn) in cpu_svns.iter().enumerate() {
+            tcb.extend(der(
+                0x30,
+                &[oid(&[2, i as u8 + 1]), der_integer(*svn as u32)].concat(),
+            ));
+        }
+        tcb.extend(der(
+            0x30,
+            &[oid(&[2, 17]), der_integer(pce_svn as u32)].concat(),
+        ));
+        tcb.extend(der(0x30, &[oid(&[2, 18]), der(0x04, cpu_svns)].concat()));
+        der(
+            0x30,
+            &[
+                der(0x30, &[oid(&[1]), der(0x04, &[0; 16])].concat()),
+                der(0x30, &[oid(&[2]), der(0x30, &tcb)].concat()),
+                der(0x30, &[oid(&[4]), der(0x04, fmspc)].concat()),
+            ]
+            .concat(),
+        )
+    }
+
+    /// A PCK certificate chain with the names Intel uses, rooted in a self-signed "Intel SGX Root
+    /// CA", and the key of each certificate
+    fn mock_dcap_pki() -> (Vec<X509>, Vec<openssl::pkey::PKey<openssl::pkey::Private>>) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let p256 = || openssl::pkey::PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let root_key = p256();
+        let ca_key = p256();
+        let pck_key = p256();
+        let root_x509 = mock_cert("Intel SGX Root CA", 1, &root_key, None, true, vec![]);
+        let ca_x509 = mock_cert(
+            "Intel SGX PCK Platform CA",
+            2,
+            &ca_key,
+            Some((&root_x509, &root_key)),
+            true,
+            vec![],
+        );
+        let sgx_extension = openssl::x509::X509Extension::new_from_der(
+            &openssl::asn1::Asn1Object::from_str("1.2.840.113741.1.13.1").unwrap(),
+            false,
+            &openssl::asn1::Asn1OctetString::new_from_bytes(&sgx_extension_der(
+                &MOCK_FMSPC,
+                &[MOCK_CPU_SVN; 16],
+                MOCK_PCE_SVN,
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        let pck_x509 = mock_cert(
+            "Intel SGX PCK Certificate",
+            3,
+            &pck_key,
+            Some((&ca_x509, &ca_key)),
+            false,
+            vec![sgx_extension],
+        );
+        (
+            vec![pck_x509, ca_x509, root_x509],
+            vec![pck_key, ca_key, root_key],
+        )
+    }
+
+    /// A DER CRL of `issuer` revoking the certificates numbered `revoked_serials`
+    fn mock_crl(
+        issuer: &X509,
+        issuer_key: &openssl::pkey::PKeyRef<openssl::pkey::Private>,
+        revoked_serials: &[u32],
+    ) -> Vec<u8> {
+        let ecdsa_with_sha256 = der(
+            0x30,
+            &der(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02]),
+        );
+        let utc_time = |time: &str| der(0x17, time.as_bytes());
+        let mut tbs = [
+            der_integer(1),
+            ecdsa_with_sha256.clone(),
+            issuer.subject_name().to_der().unwrap(),
+            utc_time("240101000000Z"),
+            utc_time("491231235959Z"),
+        ]
+        .concat();
+        if !revoked_serials.is_empty() {
+            let revoked: Vec<u8> = revoked_serials
+                .iter()
+                .flat_map(|serial| {
+                    der(
+                        0x30,
+                        &[der_integer(*serial), utc_time("240101000000Z")].concat(),
+                    )
+                })
+                .collect();
+            tbs.extend(der(0x30, &revoked));
+        }
+        let tbs = der(0x30, &tbs);
+        let signature = EcdsaSig::sign(&openssl::sha::sha256(&tbs), &issuer_key.ec_key().unwrap())
+            .unwrap()
+            .to_der()
+            .unwrap();
+        der(
+            0x30,
+            &[
+                tbs,
+                ecdsa_with_sha256,
+                der(0x03, &[&[0_u8][..], &signature].concat()),
+            ]
+            .concat(),
+        )
+    }
+
+    /// Collateral for quotes under `mock_dcap_pki`, signed under its root CA. The platform's TCB
+    /// level has `tcb_status`, the quoting enclave is up to date from `qe_isvsvn`, and the PCK
+    /// CRL revokes `revoked_serials`.
+    fn mock_dcap_collateral(
+        pck_chain: &[X509],
+        keys: &[openssl::pkey::PKey<openssl::pkey::Private>],
+        tcb_status: &str,
+        qe_isvsvn: u16,
+        revoked_serials: &[u32],
+    ) -> DcapCollateral {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let signing_key =
+            openssl::pkey::PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let signing_x509 = mock_cert(
+            "Intel SGX TCB Signing",
+            4,
+            &signing_key,
+            Some((&pck_chain[2], &keys[2])),
+            false,
+            vec![],
+        );
+        let pem_chain = |x509s: &[&X509]| {
+            let pems: Vec<Vec<u8>> = x509s.iter().map(|x509| x509.to_pem().unwrap()).collect();
+            String::from_utf8(pems.concat()).unwrap()
+        };
+        // Intel signs the exact JSON text of the body
+        let sign = |field: &str, body: serde_json::Value| {
+            let body = body.to_string();
+            let signature = p256_sign(&signing_key.ec_key().unwrap(), body.as_bytes());
+            format!(
+                r#"{{"{field}":{body},"signature":"{}"}}"#,
+                hex::encode(signature)
+            )
+        };
+
+        let tcb_level = |svn: u8, status: &str| {
+            serde_json::json!({
+                "tcb": {
+                    "sgxtcbcomponents": vec![serde_json::json!({ "svn": svn }); 16],
+                    "pcesvn": MOCK_PCE_SVN,
+                },
+                "tcbDate": "2024-01-01T00:00:00Z",
+                "tcbStatus": status,
+                "advisoryIDs": ["INTEL-SA-00615"],
+            })
+        };
+        let tcb_info = serde_json::json!({
+            "id": "SGX",
+            "version": 3,
+            "issueDate": "2024-01-01T00:00:00Z",
+            "nextUpdate": "2999-01-01T00:00:00Z",
+            "fmspc": hex::encode(MOCK_FMSPC),
+            "pceId": "0000",
+            "tcbType": 0,
+            "tcbEvaluationDataNumber": 16,
+            "tcbLevels": [
+                tcb_level(MOCK_CPU_SVN + 1, "UpToDate"),
+                tcb_level(MOCK_CPU_SVN, tcb_status),
+                tcb_level(0, "OutOfDate"),
+            ],
+        });
+        let qe_identity = serde_json::json!({
+            "id": "QE",
+            "version": 2,
+            "issueDate": "2024-01-01T00:00:00Z",
+            "nextUpdate": "2999-01-01T00:00:00Z",
+            "miscselect": "00000000",
+            "miscselectMask": "FFFFFFFF",
+            "attributes": "11000000000000000000000000000000",
+            "attributesMask": "FBFFFFFFFFFFFFFF0000000000000000",
+            "mrsigner": hex::encode_upper(MOCK_QE_MRSIGNER),
+            "isvprodid": 1,
+            "tcbLevels": [
+                { "tcb": { "isvsvn": qe_isvsvn }, "tcbStatus": "UpToDate" },
+                { "tcb": { "isvsvn": 0 }, "tcbStatus": "OutOfDate" },
+            ],
+        });
+
+        let pck_crl = mock_crl(&pck_chain[1], &keys[1], revoked_serials);
+        let root_ca_crl = mock_crl(&pck_chain[2], &keys[2], &[]);
+        DcapCollateral {
+            pck_crl_issuer_chain: pem_chain(&[&pck_chain[1], &pck_chain[2]]),
+            // PCCS serves the PCK CRL as PEM and the root CA CRL as hex encoded DER
+            root_ca_crl: hex::encode(root_ca_crl),
+            pck_crl: String::from_utf8(X509Crl::from_der(&pck_crl).unwrap().to_pem().unwrap())
+                .unwrap(),
+            tcb_info_issuer_chain: pem_chain(&[&signing_x509, &pck_chain[2]]),
+            tcb_info: sign("tcbInfo", tcb_info),
+            qe_identity_issuer_chain: pem_chain(&[&signing_x509, &pck_chain[2]]),
+            qe_identity: sign("enclaveIdentity", qe_identity),
+        }
+    }
+
+    /// A raw r || s ECDSA P-256 signature over the SHA-256 of `msg`
+    fn p256_sign(key: &EcKeyRef<openssl::pkey::Private>, msg: &[u8]) -> Vec<u8> {
+        let sig = EcdsaSig::sign(&openssl::sha::sha256(msg), key).unwrap();
+        [
+            sig.r().to_vec_padded(32).unwrap(),
+            sig.s().to_vec_padded(32).unwrap(),
+        ]
+        .concat()
+    }
+
+    /// DCAP evidence of `data` from an enclave measuring `mrenclave`, quoted under `pck_chain`
+    fn mock_dcap_evidence(
+        pck_chain: &[X509],
+        pck_key: &openssl::pkey::PKeyRef<openssl::pkey::Private>,
+        mrenclave: &[u8; 32],
+        data: &[u8],
+    ) -> AttestationEvidence {
+        let mut body = [0_u8; 432];
+        body[0..2].copy_from_slice(&3_u16.to_le_bytes());
+        body[112..144].copy_from_slice(mrenclave);
+        body[368..432].copy_from_slice(&to_report_data(data).unwrap());
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let attestation_key = EcKey::generate(&group).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let attestation_pk = attestation_key
+            .public_key()
+            .to_bytes(
+                &group,
+                openssl::ec::PointConversionForm::UNCOMPRESSED,
+                &mut ctx,
+            )
+            .unwrap()[1..]
+            .to_vec();
+        let qe_auth_data = [0x5a_u8; 32];
+        let mut qe_report = [0_u8; 384];
+        // The quoting enclave Intel's QE identity describes, see `mock_dcap_collateral`
+        qe_report[48] = 0x11;
+        qe_report[128..160].copy_from_slice(&MOCK_QE_MRSIGNER);
+        qe_report[256..258].copy_from_slice(&1_u16.to_le_bytes());
+        qe_report[258..260].copy_from_slice(&MOCK_QE_ISVSVN.to_le_bytes());
+        qe_report[320..352].copy_from_slice(&openssl::sha::sha256(
+            &[&attestation_pk[..], &qe_auth_data].concat(),
+        ));
+        let mut pem = Vec::new();
+        for x509 in pck_chain {
+            pem.extend_from_slice(&x509.to_pem().unwrap());
+        }
+
+        let mut quote = body.to_vec();
+        quote.extend_from_slice(&0_u32.to_le_bytes());
+        quote.extend_from_slice(&p256_sign(&attestation_key, &body));
+        quote.extend_from_slice(&attestation_pk);
+        quote.extend_from_slice(&qe_report);
+        quote.extend_from_slice(&p256_sign(&pck_key.ec_key().unwrap(), &qe_report));
+        quote.extend_from_slice(&(qe_auth_data.len() as u16).to_le_bytes());
+        quote.extend_from_slice(&qe_auth_data);
+        quote.extend_from_slice(&5_u16.to_le_bytes());
+        quote.extend_from_slice(&(pem.len() as u32).to_le_bytes());
+        quote.extend_from_slice(&pem);
+        AttestationEvidence {
+            kind: EvidenceKind::Dcap,
+            quote: openssl::base64::encode_block(&quote),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_intel_sgx_root_ca_is_pinned() {
+        let intel = X509::from_pem(INTEL_SGX_ROOT_CA_PEM.as_bytes()).unwrap();
+        assert_eq!(
+            common_name(intel.subject_name()).unwrap(),
+            "Intel SGX Root CA"
+        );
+        assert!(is_trusted_sgx_root_ca(&intel).unwrap());
+
+        // A quote whose PCK chain is rooted in a self-signed "Intel SGX Root CA" is forged
+        let (pck_chain, keys) = mock_dcap_pki();
+        let evidence = mock_dcap_evidence(&pck_chain, &keys[0], &[0xaa; 32], &[7_u8; 32]);
+        assert!(!is_trusted_sgx_root_ca(&pck_chain[2]).unwrap());
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("not Intel's"));
+        // The forged quote still names what it claims
+        assert_eq!(verdict.mrenclave, Some(hex::encode([0xaa; 32])));
+
+        // Everything but the root CA verifies, so the pin is all that refuses it
+        trust_mock_sgx_root_ca(&pck_chain[2].to_pem().unwrap()).unwrap();
+        evidence.verify_dcap_quote().unwrap();
+        evidence.verify_report_data(&[7_u8; 32]).unwrap();
+    }
+
+    #[test]
+    fn test_dcap_tcb_level_is_checked() {
+        let (pck_chain, keys) = mock_dcap_pki();
+        trust_mock_sgx_root_ca(&pck_chain[2].to_pem().unwrap()).unwrap();
+        let mut evidence = mock_dcap_evidence(&pck_chain, &keys[0], &[0xaa; 32], &[7_u8; 32]);
+        let collateral = |tcb_status: &str, qe_isvsvn: u16, revoked_serials: &[u32]| {
+            mock_dcap_collateral(&pck_chain, &keys, tcb_status, qe_isvsvn, revoked_serials)
+        };
+
+        // Without collateral the TCB level is unknown
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("no collateral"));
+
+        evidence.collateral = Some(collateral("SWHardeningNeeded", MOCK_QE_ISVSVN, &[]));
+        let verdict = evidence.verify();
+        assert!(verdict.valid, "{:?}", verdict.error);
+        assert_eq!(verdict.status.as_deref(), Some("SWHardeningNeeded"));
+        assert_eq!(verdict.advisory_ids, vec!["INTEL-SA-00615".to_string()]);
+
+        // Outdated and revoked TCB levels are refused
+        for tcb_status in ["OutOfDate", "Revoked"] {
+            evidence.collateral = Some(collateral(tcb_status, MOCK_QE_ISVSVN, &[]));
+            let verdict = evidence.verify();
+            assert!(!verdict.valid);
+            assert_eq!(verdict.status.as_deref(), Some(tcb_status));
+        }
+
+        // So are outdated quoting enclaves
+        evidence.collateral = Some(collateral("UpToDate", MOCK_QE_ISVSVN + 1, &[]));
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("quoting enclave"));
+
+        // And revoked PCK certificates
+        evidence.collateral = Some(collateral("UpToDate", MOCK_QE_ISVSVN, &[3]));
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("revoked"));
+
+        // And collateral changed after it was signed
+        let mut tampered = collateral("OutOfDate", MOCK_QE_ISVSVN, &[]);
+        tampered.tcb_info = tampered.tcb_info.replace("OutOfDate", "UpToDate");
+        evidence.collateral = Some(tampered);
+        let verdict = evidence.verify();
+        assert!(!verdict.valid);
+        assert!(verdict.error.unwrap().contains("signature"));
+
+        // And collateral signed by anyone but Intel
+        let (other_chain, other_keys) = mock_dcap_pki();
+        evidence.collateral = Some(mock_dcap_collateral(
+            &other_chain,
+            &other_keys,
+            "UpToDate",
+            MOCK_QE_ISVSVN,
+            &[],
+        ));
+        assert!(!evidence.verify().valid);
+    }
+
+    #[test]
+    fn test_pck_tcb() {
+        let mut cpu_svns = [3_u8; 16];
+        cpu_svns[15] = 0xff;
+        let der = sgx_extension_der(&MOCK_FMSPC, &cpu_svns, 0x1234);
+        assert_eq!(pck_tcb(&der).unwrap(), (cpu_svns, 0x1234));
+        assert_eq!(pck_fmspc(&der).unwrap(), MOCK_FMSPC);
+        assert!(pck_tcb(&der[..der.len() / 2]).is_err());
+    }
 }
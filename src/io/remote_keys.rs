@@ -0,0 +1,92 @@
+use crate::io::data_dir::remote_keys_dir;
+use crate::io::key_management::key_fname;
+use anyhow::{bail, Context, Result};
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Registers the BLS public key as being served by the signer at `url`. The registry is a
+/// file per pubkey (hex without the `0x` prefix) containing the url.
+pub fn write_remote_key(pk_hex: &str, url: &str) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    fs::create_dir_all(remote_keys_dir()).with_context(|| "Failed to create remote keys dir")?;
+    let file_path: PathBuf = remote_keys_dir().join(pk_hex);
+    fs::write(&file_path, url).with_context(|| "failed to write remote key")
+}
+
+/// Returns the url of the signer registered for the BLS public key
+pub fn read_remote_key_url(pk_hex: &str) -> Result<String> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = remote_keys_dir().join(pk_hex);
+    fs::read_to_string(&file_path).with_context(|| "Unable to read remote key")
+}
+
+/// Return true if the BLS public key is registered as a remote key
+pub fn remote_key_exists(pk_hex: &str) -> bool {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = remote_keys_dir().join(pk_hex);
+    file_path.exists()
+}
+
+/// Removes the BLS public key from the remote key registry
+pub fn delete_remote_key(pk_hex: &str) -> Result<()> {
+    let pk_hex: &str = &key_fname(pk_hex);
+    let file_path: PathBuf = remote_keys_dir().join(pk_hex);
+    fs::remove_file(&file_path).with_context(|| {
+        format!(
+            "failed to delete remote key at: {:?}",
+            file_path.as_os_str()
+        )
+    })
+}
+
+/// Returns the (pubkey, url) pairs of each registered remote key sorted by pubkey, where
+/// each pubkey is hex without the `0x` prefix. Returns an empty list if none were registered.
+pub fn list_remote_keys() -> Result<Vec<(String, String)>> {
+    let paths = match fs::read_dir(remote_keys_dir()) {
+        Ok(paths) => paths,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => bail!("failed to read remote keys dir: {}", e),
+    };
+
+    let mut keys: Vec<(String, String)> = Vec::new();
+    for path in paths {
+        let p = path.with_context(|| "failed to find path")?;
+        let pk_hex = match p.file_name().into_string() {
+            Ok(s) => s,
+            Err(e) => bail!("Error, bad file name in list_remote_keys(): {:?}", e),
+        };
+        let url = read_remote_key_url(&pk_hex)?;
+        keys.push((pk_hex, url));
+    }
+    keys.sort();
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod test_remote_keys {
+    use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+
+    #[test]
+    fn test_write_list_delete_remote_keys() {
+        let _data_dir = ScopedDataDir::temp("test_write_list_delete_remote_keys");
+        assert!(list_remote_keys().unwrap().is_empty());
+
+        write_remote_key("0x5678ef01", "http://worker-2:9001").unwrap();
+        write_remote_key("1234abcd", "http://worker-1:9001").unwrap();
+        assert!(remote_key_exists("0x1234abcd"));
+        assert_eq!(
+            list_remote_keys().unwrap(),
+            vec![
+                ("1234abcd".to_string(), "http://worker-1:9001".to_string()),
+                ("5678ef01".to_string(), "http://worker-2:9001".to_string()),
+            ]
+        );
+
+        delete_remote_key("0x1234abcd").unwrap();
+        assert!(!remote_key_exists("1234abcd"));
+        assert!(delete_remote_key("0x1234abcd").is_err());
+        assert_eq!(list_remote_keys().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,44 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <unistd.h>
+
+#include "sgx_key.h"
+
+typedef struct {
+  const sgx_key_request_t* key_request;  // Input
+  sgx_key_128bit_t* key;                 // Output
+} sgxioc_get_key_arg_t;
+
+#define SGXIOC_GET_KEY _IOWR('s', 11, sgxioc_get_key_arg_t)
+
+extern "C"
+int get_mrsigner_seal_key(uint8_t key[16]) {
+  // Bind the key to the enclave signer so that upgraded enclaves can still unseal
+  sgx_key_request_t key_request;
+  memset(&key_request, 0, sizeof(key_request));
+  key_request.key_name = SGX_KEYSELECT_SEAL;
+  key_request.key_policy = SGX_KEYPOLICY_MRSIGNER;
+  key_request.attribute_mask.flags = 0xFF0000000000000B;
+  key_request.attribute_mask.xfrm = 0;
+
+  int sgx_fd = open("/dev/sgx", O_RDONLY);
+  if (sgx_fd < 0) {
+    printf("Fail to open /dev/sgx\n");
+    return -1;
+  }
+
+  sgx_key_128bit_t seal_key;
+  sgxioc_get_key_arg_t args = {&key_request, &seal_key};
+  int ret = ioctl(sgx_fd, SGXIOC_GET_KEY, &args);
+  close(sgx_fd);
+  if (ret < 0) {
+    printf("Fail to get the sealing key\n");
+    return -1;
+  }
+
+  memcpy(key, seal_key, sizeof(seal_key));
+  memset(seal_key, 0, sizeof(seal_key));
+  return 0;
+}
@@ -0,0 +1,114 @@
+use crate::io::data_dir::threshold_keys_dir;
+use crate::io::key_management::key_fname;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+
+/// A BLS key split across worker enclaves, which a leader signs with by recombining the workers'
+/// partial signatures. Saved as a JSON file per pubkey (hex without the `0x` prefix).
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ThresholdKey {
+    /// 0x-prefixed 48 byte BLS public key that recombined signatures verify under
+    pub bls_pk_hex: String,
+    /// How many partial signatures recombine into a signature
+    pub threshold: usize,
+    /// The hex-encoded `PublicKeySet`, committing to the coefficients of the sharing polynomial
+    pub verification_vector: String,
+    pub shares: Vec<ThresholdShare>,
+}
+
+/// Where share `index` of a `ThresholdKey` is held
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ThresholdShare {
+    pub index: usize,
+    /// The ECIES public key of the worker the share was encrypted to
+    pub worker_pk_hex: String,
+    /// The public key share the worker signs with
+    pub pk_share_hex: String,
+    /// The secret key share ECIES encrypted to the worker, kept so a failed delivery can be
+    /// retried
+    #[serde(default)]
+    pub encrypted_sk_share_hex: String,
+    /// Whether the worker acknowledged saving the share. Only those workers are asked to sign.
+    #[serde(default)]
+    pub acknowledged: bool,
+    /// When the worker acknowledged the share, in unix seconds
+    #[serde(default)]
+    pub delivered_at: Option<u64>,
+}
+
+fn threshold_key_path(pk_hex: &str) -> PathBuf {
+    threshold_keys_dir().join(key_fname(pk_hex))
+}
+
+pub fn write_threshold_key(key: &ThresholdKey) -> Result<()> {
+    fs::create_dir_all(threshold_keys_dir())
+        .with_context(|| "Failed to create threshold keys dir")?;
+    let json = serde_json::to_string(key)?;
+    fs::write(threshold_key_path(&key.bls_pk_hex), json)
+        .with_context(|| "failed to write threshold key")
+}
+
+pub fn read_threshold_key(pk_hex: &str) -> Result<ThresholdKey> {
+    let json = fs::read_to_string(threshold_key_path(pk_hex))
+        .with_context(|| "Unable to read threshold key")?;
+    serde_json::from_str(&json).with_context(|| format!("Threshold key {pk_hex} is corrupt"))
+}
+
+/// Return true if the BLS public key was split across workers
+pub fn threshold_key_exists(pk_hex: &str) -> bool {
+    threshold_key_path(pk_hex).exists()
+}
+
+/// Returns every key split across workers sorted by public key, or an empty list if none were
+pub fn list_threshold_keys() -> Result<Vec<ThresholdKey>> {
+    let paths = match fs::read_dir(threshold_keys_dir()) {
+        Ok(paths) => paths,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => bail!("failed to read threshold keys dir: {}", e),
+    };
+
+    let mut keys: Vec<ThresholdKey> = Vec::new();
+    for path in paths {
+        let p = path.with_context(|| "failed to find path")?;
+        let fname = match p.file_name().into_string() {
+            Ok(s) => s,
+            Err(e) => bail!("Error, bad file name in list_threshold_keys(): {:?}", e),
+        };
+        keys.push(read_threshold_key(&fname)?);
+    }
+    keys.sort_by(|a, b| a.bls_pk_hex.cmp(&b.bls_pk_hex));
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod test_threshold_keys {
+    use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+
+    #[test]
+    fn test_write_read_threshold_key() {
+        let _data_dir = ScopedDataDir::temp("test_write_read_threshold_key");
+        let key = ThresholdKey {
+            bls_pk_hex: format!("0x{}", "ab".repeat(48)),
+            threshold: 2,
+            verification_vector: "0x1234".to_string(),
+            shares: vec![ThresholdShare {
+                index: 0,
+                worker_pk_hex: format!("0x02{}", "11".repeat(32)),
+                pk_share_hex: format!("0x{}", "cd".repeat(48)),
+                encrypted_sk_share_hex: "0x5678".to_string(),
+                acknowledged: false,
+                delivered_at: None,
+            }],
+        };
+        assert!(!threshold_key_exists(&key.bls_pk_hex));
+        assert!(list_threshold_keys().unwrap().is_empty());
+        write_threshold_key(&key).unwrap();
+        assert!(threshold_key_exists(&"AB".repeat(48)));
+        assert_eq!(read_threshold_key(&"ab".repeat(48)).unwrap(), key);
+        assert_eq!(list_threshold_keys().unwrap(), vec![key]);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::io::data_dir::validator_config_dir;
+use crate::io::key_management::key_fname;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Per-validator settings managed through the keymanager API and enforced when signing
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ValidatorConfig {
+    /// 0x-prefixed lowercase 20-byte execution address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    /// 0x-prefixed 32-byte graffiti
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graffiti: Option<String>,
+}
+
+impl ValidatorConfig {
+    /// Reads the config for the BLS public key, defaulting to an empty config if none was saved
+    pub fn read(pk_hex: &str) -> Result<Self> {
+        let pk_hex: &str = &key_fname(pk_hex);
+        let file_path: PathBuf = validator_config_dir().join(pk_hex);
+        if !file_path.exists() {
+            return Ok(ValidatorConfig::default());
+        }
+        let json = fs::read_to_string(&file_path)
+            .with_context(|| format!("Unable to read validator config for {pk_hex}"))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Malformed validator config for {pk_hex}"))
+    }
+
+    /// Saves the config for the BLS public key
+    pub fn write(&self, pk_hex: &str) -> Result<()> {
+        let pk_hex: &str = &key_fname(pk_hex);
+        fs::create_dir_all(validator_config_dir())
+            .with_context(|| "Failed to create validator config dir")?;
+        let file_path: PathBuf = validator_config_dir().join(pk_hex);
+        let json = serde_json::to_string(self)?;
+        fs::write(&file_path, json).with_context(|| "failed to write validator config")
+    }
+}
+
+/// Validates that `addr` is a hex-encoded 20-byte execution address and returns it
+/// 0x-prefixed and lowercase
+pub fn sanitize_execution_address(addr: &str) -> Result<String> {
+    let bytes = crate::parse_hex(addr).with_context(|| "Not valid hex")?;
+    if bytes.len() != 20 {
+        bail!("Expected a 20-byte address, got {} bytes", bytes.len());
+    }
+    Ok(crate::to_0x_hex(bytes))
+}
+
+/// Parses a gas limit given as a decimal string, rejecting zero
+pub fn sanitize_gas_limit(gas_limit: &str) -> Result<u64> {
+    if gas_limit.is_empty() || !gas_limit.chars().all(|c| c.is_ascii_digit()) {
+        bail!("Expected a decimal string, got {gas_limit}");
+    }
+    let gas_limit: u64 = gas_limit
+        .parse()
+        .with_context(|| "Gas limit does not fit in a uint64")?;
+    if gas_limit == 0 {
+        bail!("Gas limit must be positive");
+    }
+    Ok(gas_limit)
+}
+
+/// Encodes UTF-8 graffiti text of at most 32 bytes as the 0x-prefixed, zero-padded 32-byte
+/// value that appears in block bodies
+pub fn sanitize_graffiti(graffiti: &str) -> Result<String> {
+    let bytes = graffiti.as_bytes();
+    if bytes.len() > 32 {
+        bail!("Graffiti must be at most 32 bytes, got {}", bytes.len());
+    }
+    let mut padded = [0_u8; 32];
+    padded[..bytes.len()].copy_from_slice(bytes);
+    Ok(crate::to_0x_hex(padded))
+}
+
+/// Decodes a 0x-prefixed 32-byte graffiti back to text, dropping the zero padding
+pub fn graffiti_to_text(graffiti: &str) -> Result<String> {
+    let bytes = crate::parse_hex(graffiti).with_context(|| "Not valid hex")?;
+    let end = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+#[cfg(test)]
+mod test_validator_config {
+    use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+
+    #[test]
+    fn test_read_write_validator_config() {
+        let _data_dir = ScopedDataDir::temp("test_read_write_validator_config");
+        let pk_hex = "0x1234abcd";
+        assert_eq!(
+            ValidatorConfig::read(pk_hex).unwrap(),
+            ValidatorConfig::default()
+        );
+
+        let mut config = ValidatorConfig::default();
+        config.fee_recipient = Some(format!("0x{}", "2a".repeat(20)));
+        config.gas_limit = Some(30_000_000);
+        config.graffiti = Some(sanitize_graffiti("puffer").unwrap());
+        config.write(pk_hex).unwrap();
+        assert_eq!(ValidatorConfig::read("1234abcd").unwrap(), config);
+    }
+
+    #[test]
+    fn test_sanitize_execution_address() {
+        let addr = format!("0x{}", "AB".repeat(20));
+        assert_eq!(
+            sanitize_execution_address(&addr).unwrap(),
+            format!("0x{}", "ab".repeat(20))
+        );
+        assert!(sanitize_execution_address(&"ab".repeat(19)).is_err());
+        assert!(sanitize_execution_address("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_sanitize_gas_limit() {
+        assert_eq!(sanitize_gas_limit("30000000").unwrap(), 30_000_000);
+        assert_eq!(sanitize_gas_limit(&u64::MAX.to_string()).unwrap(), u64::MAX);
+        assert!(sanitize_gas_limit("0").is_err());
+        assert!(sanitize_gas_limit("-1").is_err());
+        assert!(sanitize_gas_limit("+1").is_err());
+        assert!(sanitize_gas_limit("0x10").is_err());
+        assert!(sanitize_gas_limit("").is_err());
+        assert!(sanitize_gas_limit("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn test_sanitize_graffiti() {
+        let graffiti = sanitize_graffiti("teku/v0.12.10-dev-c81ca625").unwrap();
+        assert_eq!(
+            graffiti,
+            "0x74656b752f76302e31322e31302d6465762d6338316361363235000000000000"
+        );
+        assert_eq!(
+            graffiti_to_text(&graffiti).unwrap(),
+            "teku/v0.12.10-dev-c81ca625"
+        );
+        assert_eq!(
+            sanitize_graffiti("").unwrap(),
+            format!("0x{}", "00".repeat(32))
+        );
+        assert!(sanitize_graffiti(&"a".repeat(32)).is_ok());
+        assert!(sanitize_graffiti(&"a".repeat(33)).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::io::data_dir::workers_dir;
+use crate::io::remote_attestation::AttestationVerdict;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A worker enclave registered with this leader, saved as `<id>.json`. Timestamps are unix
+/// seconds.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkerRecord {
+    pub id: String,
+    pub url: String,
+    /// The worker's 0x-prefixed compressed ECIES public key, which its evidence commits to
+    pub ecies_pubkey: String,
+    /// The verdict on the evidence the worker registered with
+    pub verdict: AttestationVerdict,
+    pub registered_at: u64,
+    pub last_seen: u64,
+}
+
+/// The current unix time in seconds
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// A worker's id, the first 8 bytes of SHA256 over its compressed ECIES public key, so the same
+/// worker registering again keeps its id
+pub fn worker_id(ecies_pk_hex: &str) -> Result<String> {
+    let pk = crate::parse_hex(ecies_pk_hex)?;
+    Ok(hex::encode(&openssl::sha::sha256(&pk)[..8]))
+}
+
+fn worker_path(id: &str) -> PathBuf {
+    workers_dir().join(format!("{id}.json"))
+}
+
+/// Saves the worker, keeping when it first registered if it was already known
+pub fn register_worker(
+    url: &str,
+    ecies_pk_hex: &str,
+    verdict: AttestationVerdict,
+) -> Result<WorkerRecord> {
+    let id = worker_id(ecies_pk_hex)?;
+    let now = now();
+    let registered_at = read_worker(&id).map(|w| w.registered_at).unwrap_or(now);
+    let worker = WorkerRecord {
+        id,
+        url: url.to_string(),
+        ecies_pubkey: ecies_pk_hex.to_string(),
+        verdict,
+        registered_at,
+        last_seen: now,
+    };
+    write_worker(&worker)?;
+    Ok(worker)
+}
+
+pub fn write_worker(worker: &WorkerRecord) -> Result<()> {
+    fs::create_dir_all(workers_dir()).with_context(|| "Failed to create workers dir")?;
+    let json = serde_json::to_string(worker)?;
+    fs::write(worker_path(&worker.id), json).with_context(|| "failed to write worker")
+}
+
+pub fn read_worker(id: &str) -> Result<WorkerRecord> {
+    let json = fs::read_to_string(worker_path(id)).with_context(|| "Unable to read worker")?;
+    serde_json::from_str(&json).with_context(|| format!("Worker {id} is corrupt"))
+}
+
+/// Records that the worker answered just now
+pub fn touch_worker(id: &str) -> Result<()> {
+    let mut worker = read_worker(id)?;
+    worker.last_seen = now();
+    write_worker(&worker)
+}
+
+/// Returns every registered worker sorted by id, or an empty list if none registered
+pub fn list_workers() -> Result<Vec<WorkerRecord>> {
+    let paths = match fs::read_dir(workers_dir()) {
+        Ok(paths) => paths,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => bail!("failed to read workers dir: {}", e),
+    };
+
+    let mut workers: Vec<WorkerRecord> = Vec::new();
+    for path in paths {
+        let p = path.with_context(|| "failed to find path")?;
+        let fname = match p.file_name().into_string() {
+            Ok(s) => s,
+            Err(e) => bail!("Error, bad file name in list_workers(): {:?}", e),
+        };
+        if let Some(id) = fname.strip_suffix(".json") {
+            workers.push(read_worker(id)?);
+        }
+    }
+    workers.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(workers)
+}
+
+#[cfg(test)]
+mod test_workers {
+    use super::*;
+    use crate::io::data_dir::ScopedDataDir;
+
+    #[test]
+    fn test_register_list_workers() {
+        let _data_dir = ScopedDataDir::temp("test_register_list_workers");
+        assert!(list_workers().unwrap().is_empty());
+
+        let pk = format!("0x02{}", "11".repeat(32));
+        let worker = register_worker("http://worker-1:9001", &pk, Default::default()).unwrap();
+        assert_eq!(worker.id, worker_id(&pk).unwrap());
+        assert_eq!(worker.id.len(), 16);
+        assert_eq!(list_workers().unwrap(), vec![worker.clone()]);
+
+        // Registering again moves the worker, keeping its id and first registration
+        let mut first = worker.clone();
+        first.registered_at = 1;
+        write_worker(&first).unwrap();
+        let again = register_worker("http://worker-1:9002", &pk, Default::default()).unwrap();
+        assert_eq!(again.id, worker.id);
+        assert_eq!(again.registered_at, 1);
+        assert_eq!(again.url, "http://worker-1:9002");
+        assert_eq!(list_workers().unwrap().len(), 1);
+
+        register_worker(
+            "http://worker-2:9001",
+            &format!("0x03{}", "22".repeat(32)),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(list_workers().unwrap().len(), 2);
+    }
+}
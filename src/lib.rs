@@ -3,6 +3,7 @@ extern crate anyhow;
 extern crate env_logger;
 extern crate libc;
 
+pub mod config;
 pub mod constants;
 pub mod crypto;
 pub mod enclave;
@@ -14,6 +15,60 @@ pub mod io;
 #[macro_export]
 macro_rules! strip_0x_prefix {
     ($hex:expr) => {
-        $hex.strip_prefix("0x").unwrap_or(&$hex).into()
+        $hex.strip_prefix("0x")
+            .or($hex.strip_prefix("0X"))
+            .unwrap_or(&$hex)
+            .into()
     };
 }
+
+/// Hex-decodes `hex_str`, accepting an optional 0x/0X prefix and either case. Errors out on an
+/// odd length or a non-hex character.
+pub fn parse_hex(hex_str: &str) -> anyhow::Result<Vec<u8>> {
+    let hex_str: &str = strip_0x_prefix!(hex_str);
+    if hex_str.len() % 2 != 0 {
+        anyhow::bail!("Hex string has odd length {}", hex_str.len());
+    }
+    if let Some(c) = hex_str.chars().find(|c| !c.is_ascii_hexdigit()) {
+        anyhow::bail!("Invalid hex character {c:?}");
+    }
+    Ok(hex::decode(hex_str)?)
+}
+
+/// The canonical form of a hex string, lowercase without the 0x prefix, as used in file names
+pub fn normalize_hex(hex_str: &str) -> anyhow::Result<String> {
+    Ok(hex::encode(parse_hex(hex_str)?))
+}
+
+/// Encodes `bytes` as lowercase 0x-prefixed hex, the form every response emits
+pub fn to_0x_hex<T: AsRef<[u8]>>(bytes: T) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        let expected = vec![0xab, 0xcd, 0x01];
+        for hex_str in ["abcd01", "0xabcd01", "0XABCD01", "0xAbCd01", "ABCD01"] {
+            assert_eq!(parse_hex(hex_str).unwrap(), expected);
+        }
+        assert!(parse_hex("").unwrap().is_empty());
+        assert!(parse_hex("0x").unwrap().is_empty());
+
+        // Odd lengths, non-hex characters and doubled prefixes are rejected
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("0xzz").is_err());
+        assert!(parse_hex("0x0xab").is_err());
+        assert!(parse_hex("+1ab").is_err());
+    }
+
+    #[test]
+    fn test_normalize_hex() {
+        assert_eq!(normalize_hex("0XABcd").unwrap(), "abcd");
+        assert_eq!(to_0x_hex([0xab, 0xcd]), "0xabcd");
+        assert!(normalize_hex("0xabc").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use axum::http::{header, HeaderValue};
+use puffersecuresigner::enclave::shared::api_token::{require_api_token, ApiToken, ApiTokenScope};
+
+/// Serves a key management, a signing and a listing route behind `token`
+pub fn mock_api_token_app(token: ApiToken) -> Result<axum_test::TestServer> {
+    let ok = || async { "OK" };
+    let test_app = axum::Router::new()
+        .route("/eth/v1/keygen/bls", axum::routing::post(ok))
+        .route("/eth/v1/keystores", axum::routing::get(ok).delete(ok))
+        .route("/api/v1/eth2/sign/:bls_pk_hex", axum::routing::post(ok))
+        .route("/leader/v1/eth2/sign/:bls_pk_hex", axum::routing::post(ok))
+        .layer(axum::middleware::from_fn_with_state(
+            token,
+            require_api_token,
+        ))
+        .into_make_service();
+    axum_test::TestServer::new(test_app)
+}
+
+fn bearer(token: &str) -> HeaderValue {
+    HeaderValue::from_str(&format!("Bearer {token}")).unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_api_token() {
+    let token = "0123456789abcdef";
+    let server = mock_api_token_app(ApiToken::new(token, ApiTokenScope::Admin).unwrap()).unwrap();
+
+    // Key management needs the token
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 401);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(!err.message.contains(token));
+    for wrong in [
+        "",
+        "0123456789abcdeX",
+        "0123456789abcdef0",
+        token.get(..8).unwrap(),
+    ] {
+        let resp = server
+            .delete("/eth/v1/keystores")
+            .add_header(header::AUTHORIZATION, bearer(wrong))
+            .await;
+        assert_eq!(resp.status_code(), 401);
+    }
+    let resp = server
+        .delete("/eth/v1/keystores")
+        .add_header(header::AUTHORIZATION, HeaderValue::from_static(token))
+        .await;
+    assert_eq!(resp.status_code(), 401);
+    let resp = server
+        .post("/eth/v1/keygen/bls")
+        .add_header(header::AUTHORIZATION, bearer(token))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // Signing and listing keys stay open
+    assert_eq!(server.get("/eth/v1/keystores").await.status_code(), 200);
+    assert_eq!(
+        server.post("/api/v1/eth2/sign/0x1234").await.status_code(),
+        200
+    );
+    assert_eq!(
+        server
+            .post("/leader/v1/eth2/sign/0x1234")
+            .await
+            .status_code(),
+        200
+    );
+}
+
+#[tokio::test]
+async fn test_api_token_scope_all() {
+    let token = "0123456789abcdef";
+    let server = mock_api_token_app(ApiToken::new(token, "all".parse().unwrap()).unwrap()).unwrap();
+    assert_eq!(server.get("/eth/v1/keystores").await.status_code(), 401);
+    assert_eq!(
+        server.post("/api/v1/eth2/sign/0x1234").await.status_code(),
+        401
+    );
+    let resp = server
+        .post("/api/v1/eth2/sign/0x1234")
+        .add_header(header::AUTHORIZATION, bearer(token))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert!("some".parse::<ApiTokenScope>().is_err());
+}
+
+#[tokio::test]
+async fn test_api_token_is_generated_once() {
+    let path = std::env::temp_dir().join("test_api_token_is_generated_once");
+    let path = path.to_str().unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let generated = ApiToken::load_or_generate(path, ApiTokenScope::Admin).unwrap();
+    let saved = std::fs::read_to_string(path).unwrap();
+    assert_eq!(saved.len(), 64);
+    assert!(generated.matches(&saved));
+    assert!(!format!("{:?}", generated).contains(&saved));
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Restarts keep the token clients were given
+    let reloaded = ApiToken::load_or_generate(path, ApiTokenScope::Admin).unwrap();
+    assert!(reloaded.matches(&saved));
+    std::fs::remove_file(path).unwrap();
+
+    assert!(ApiToken::new(" \n", ApiTokenScope::Admin).is_err());
+}
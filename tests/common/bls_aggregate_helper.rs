@@ -0,0 +1,73 @@
+use anyhow::Result;
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::crypto::bls_variant::{BlsVariant, SelectedVariant};
+use puffersecuresigner::enclave::types::{
+    BlsAggregateErrorDetails, BlsAggregateRequest, BlsAggregateRequestInner, BlsAggregateResponse,
+    ErrorResponse,
+};
+use puffersecuresigner::to_0x_hex;
+
+pub async fn mock_aggregate_route(req: &BlsAggregateRequest) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/aggregate",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::aggregate_bls_signatures::handler,
+            ),
+        )
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/aggregate").json(req).await)
+}
+
+/// Signs `msg` with `n` fresh BLS keys
+fn sign_with_new_keys(n: usize, msg: &[u8]) -> Vec<BlsAggregateRequestInner> {
+    (0..n)
+        .map(|_| {
+            let sk = bls_keys::new_bls_key(0).secret_key().to_bytes();
+            BlsAggregateRequestInner {
+                bls_pk_hex: to_0x_hex(SelectedVariant::sk_to_pk(&sk).unwrap()),
+                signature_hex: to_0x_hex(SelectedVariant::sign(&sk, msg).unwrap()),
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_aggregate_bls_signatures() {
+    let msg = [0x42_u8; 32];
+    let mut req = BlsAggregateRequest {
+        msg_hex: format!("0x{}", hex::encode(msg)),
+        signatures: sign_with_new_keys(3, &msg),
+    };
+
+    let resp = mock_aggregate_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let agg: BlsAggregateResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let sig = hex::decode(agg.signature.strip_prefix("0x").unwrap()).unwrap();
+    assert!(bls_keys::bls_verify(&agg.pubkey, &msg, &sig).unwrap());
+    assert!(!bls_keys::bls_verify(&agg.pubkey, &[0x43_u8; 32], &sig).unwrap());
+
+    // A signature over another message is reported by its index
+    req.signatures[1] = sign_with_new_keys(1, &[0x43_u8; 32]).remove(0);
+    let resp = mock_aggregate_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+    let err: ErrorResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let details: BlsAggregateErrorDetails = serde_json::from_value(err.details.unwrap()).unwrap();
+    assert_eq!(details.index, 1);
+
+    // So are malformed signatures
+    req.signatures[1] = sign_with_new_keys(1, &msg).remove(0);
+    req.signatures[2].signature_hex = "0xdeadbeef".to_string();
+    let resp = mock_aggregate_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+    let err: ErrorResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let details: BlsAggregateErrorDetails = serde_json::from_value(err.details.unwrap()).unwrap();
+    assert_eq!(details.index, 2);
+
+    req.signatures.clear();
+    let resp = mock_aggregate_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
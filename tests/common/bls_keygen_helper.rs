@@ -15,6 +15,7 @@ pub async fn mock_bls_keygen_route() -> Result<axum_test::TestResponse> {
                 puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
             ),
         )
+        .with_state(super::signing_helper::mock_app_state())
         .into_make_service();
 
     let server = axum_test::TestServer::new(test_app)?;
@@ -73,12 +74,17 @@ pub async fn register_new_bls_key(
 
 #[tokio::test]
 async fn test_register_new_bls_key() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_register_new_bls_key");
     let port = read_secure_signer_port();
     let _ = register_new_bls_key(port).await;
 }
 
 #[tokio::test]
 async fn test_bls_key_in_remote_attestation_evidence() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_bls_key_in_remote_attestation_evidence",
+    );
     if env::var("SECURE_SIGNER_PORT").is_ok() {
         // Local dev is not set so use SGX.
 
@@ -98,3 +104,189 @@ async fn test_bls_key_in_remote_attestation_evidence() {
         assert_eq!(hex::encode(&got_payload[0..BLS_PUB_KEY_BYTES]), pk.to_hex());
     }
 }
+
+pub async fn mock_derive_bls_key_route(index: u32) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls/derive",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::derive_bls_key::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server
+        .post("/eth/v1/keygen/bls/derive")
+        .json(&puffersecuresigner::enclave::types::DeriveBlsKeyRequest { index, label: None })
+        .await)
+}
+
+#[tokio::test]
+async fn test_derive_bls_key_from_enclave_seed() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_derive_bls_key_from_enclave_seed",
+    );
+    let index = 7;
+    let resp = mock_derive_bls_key_route(index).await.unwrap();
+    assert_eq!(resp.status_code(), 201);
+    let derived: puffersecuresigner::enclave::types::DeriveBlsKeyResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(derived.derivation_path, format!("m/12381/3600/{index}/0/0"));
+
+    let seed = puffersecuresigner::io::key_management::read_bls_seed()
+        .unwrap()
+        .unwrap();
+    let sk =
+        puffersecuresigner::crypto::bls_keys::derive_bls_child_key(&seed, &derived.derivation_path)
+            .unwrap();
+    assert_eq!(
+        derived.pk_hex,
+        format!("0x{}", sk.public_keys().public_key().to_hex())
+    );
+
+    // The same index maps to the same key, which is only saved once
+    let resp = mock_derive_bls_key_route(index).await.unwrap();
+    assert_eq!(resp.status_code(), 409);
+
+    // Listings report the path the key was derived at
+    let resp = super::getter_routes_helper::mock_list_bls_keys_route()
+        .await
+        .unwrap();
+    let keys: puffersecuresigner::enclave::types::ListKeysResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    let listed = keys
+        .data
+        .iter()
+        .find(|k| k.pubkey == derived.pk_hex)
+        .unwrap();
+    assert_eq!(listed.derivation_path, Some(derived.derivation_path));
+}
+
+pub async fn mock_deterministic_bls_keygen_route() -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls/deterministic",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::deterministic_bls_keygen::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/keygen/bls/deterministic").await)
+}
+
+#[tokio::test]
+async fn test_deterministic_keys_are_restored_from_the_seed() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_deterministic_keys_are_restored_from_the_seed",
+    );
+    let mut keys = vec![];
+    for _ in 0..3 {
+        let resp = mock_deterministic_bls_keygen_route().await.unwrap();
+        assert_eq!(resp.status_code(), 201);
+        let derived: puffersecuresigner::enclave::types::DeriveBlsKeyResponse =
+            serde_json::from_slice(resp.as_bytes()).unwrap();
+        let index: u32 = derived
+            .derivation_path
+            .strip_prefix("m/12381/3600/")
+            .and_then(|rest| rest.strip_suffix("/0/0"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        keys.push((index, derived.pk_hex));
+    }
+
+    // Every call hands out a new index
+    assert!(keys.windows(2).all(|w| w[0].0 < w[1].0));
+    for (index, pk_hex) in keys.iter() {
+        let sk = puffersecuresigner::crypto::bls_keys::derive_indexed_bls_key(*index).unwrap();
+        assert_eq!(
+            pk_hex,
+            &format!("0x{}", sk.public_keys().public_key().to_hex())
+        );
+    }
+
+    // Wipe the keys, keeping only a backup of the seed
+    let backup = puffersecuresigner::io::key_management::read_bls_seed()
+        .unwrap()
+        .unwrap();
+    for (_, pk_hex) in keys.iter() {
+        puffersecuresigner::io::key_management::delete_bls_key(pk_hex).unwrap();
+        assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+            pk_hex
+        ));
+    }
+
+    // Restoring the seed restores the same keys at the same indices
+    assert!(puffersecuresigner::crypto::bls_keys::set_bls_seed(&backup).unwrap());
+    for (index, pk_hex) in keys.iter() {
+        let sk = puffersecuresigner::crypto::bls_keys::derive_bls_child_key(
+            &backup,
+            &puffersecuresigner::crypto::bls_keys::signing_key_path(*index),
+        )
+        .unwrap();
+        assert_eq!(
+            pk_hex,
+            &format!("0x{}", sk.public_keys().public_key().to_hex())
+        );
+    }
+}
+
+pub async fn mock_recover_bls_keys_route(
+    req: &puffersecuresigner::enclave::types::RecoverBlsKeysRequest,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls/recover",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::recover_bls_keys::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/keygen/bls/recover").json(req).await)
+}
+
+#[tokio::test]
+async fn test_recover_bls_keys_refuses_bad_mnemonics() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_recover_bls_keys_refuses_bad_mnemonics",
+    );
+    let (sk, pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    puffersecuresigner::crypto::eth_keys::save_eth_key(sk, pk).unwrap();
+    let encrypt = |msg: &str| {
+        hex::encode(
+            puffersecuresigner::crypto::eth_keys::envelope_encrypt(&pk, msg.as_bytes()).unwrap(),
+        )
+    };
+    let req =
+        |encrypted_mnemonic: String| puffersecuresigner::enclave::types::RecoverBlsKeysRequest {
+            encrypted_mnemonic,
+            encrypting_pk_hex: puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(&pk),
+            num_keys: 1,
+        };
+
+    // The checksum word of the all-zero entropy mnemonic is "art"
+    let bad_checksum = ["abandon"; 24].join(" ");
+    let resp = mock_recover_bls_keys_route(&req(encrypt(&bad_checksum)))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("Invalid BIP-39 mnemonic"));
+
+    // Plaintext mnemonics are refused
+    let plaintext = ["abandon"; 23].join(" ") + " art";
+    let resp = mock_recover_bls_keys_route(&req(hex::encode(plaintext)))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
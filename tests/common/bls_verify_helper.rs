@@ -0,0 +1,84 @@
+use anyhow::Result;
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::types::{BlsVerifyRequest, BlsVerifyResponse};
+
+pub async fn mock_verify_route(req: &BlsVerifyRequest) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/verify/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::verify_bls_signature::handler,
+            ),
+        )
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/verify/bls").json(req).await)
+}
+
+/// Signs `msg` with a fresh BLS key
+fn signed_request(msg: &[u8]) -> BlsVerifyRequest {
+    let sk = bls_keys::new_bls_key(0);
+    BlsVerifyRequest {
+        bls_pk_hex: format!("0x{}", sk.public_keys().public_key().to_hex()),
+        msg_hex: format!("0x{}", hex::encode(msg)),
+        signature_hex: format!(
+            "0x{}",
+            hex::encode(bls_keys::bls_agg_sign(&sk, msg).to_bytes())
+        ),
+    }
+}
+
+async fn verify(req: &BlsVerifyRequest) -> bool {
+    let resp = mock_verify_route(req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp: BlsVerifyResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    resp.valid
+}
+
+#[tokio::test]
+async fn test_verify_valid_bls_signature() {
+    assert!(verify(&signed_request(&[0x42_u8; 32])).await);
+}
+
+#[tokio::test]
+async fn test_verify_tampered_message() {
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.msg_hex = format!("0x{}", hex::encode([0x43_u8; 32]));
+    assert!(!verify(&req).await);
+}
+
+#[tokio::test]
+async fn test_verify_wrong_key() {
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.bls_pk_hex = signed_request(&[0x42_u8; 32]).bls_pk_hex;
+    assert!(!verify(&req).await);
+}
+
+#[tokio::test]
+async fn test_verify_invalid_points_are_not_valid() {
+    // The identity key and a signature that is not a curve point are well-formed but invalid
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.bls_pk_hex = format!("0xc0{}", "00".repeat(47));
+    assert!(!verify(&req).await);
+
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.signature_hex = format!("0x{}", "ff".repeat(96));
+    assert!(!verify(&req).await);
+}
+
+#[tokio::test]
+async fn test_verify_rejects_malformed_hex() {
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.msg_hex = "0xzz".to_string();
+    assert_eq!(mock_verify_route(&req).await.unwrap().status_code(), 400);
+
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.signature_hex = "0xdeadbeef".to_string();
+    assert_eq!(mock_verify_route(&req).await.unwrap().status_code(), 400);
+
+    let mut req = signed_request(&[0x42_u8; 32]);
+    req.bls_pk_hex = "0x1234".to_string();
+    assert_eq!(mock_verify_route(&req).await.unwrap().status_code(), 400);
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use axum::http::{header, HeaderValue};
+
+/// Serves an echo route with a 1 KiB body limit and one with the default, behind `require_json`
+pub fn mock_body_limits_app() -> Result<axum_test::TestServer> {
+    let echo = |body: String| async move { body };
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(echo).layer(axum::extract::DefaultBodyLimit::max(1024)),
+        )
+        .route("/eth/v1/keystores", axum::routing::post(echo).delete(echo))
+        .layer(axum::extract::DefaultBodyLimit::max(4096))
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::content_type::require_json,
+        ))
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::errors::json_errors,
+        ))
+        .into_make_service();
+    axum_test::TestServer::new(test_app)
+}
+
+#[tokio::test]
+async fn test_bodies_must_be_json() {
+    let server = mock_body_limits_app().unwrap();
+    let body = r#"{"keystores":[]}"#;
+
+    let resp = server
+        .post("/eth/v1/keystores")
+        .bytes(body.into())
+        .content_type("text/html")
+        .await;
+    assert_eq!(resp.status_code(), 415);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 415);
+    let resp = server.delete("/eth/v1/keystores").bytes(body.into()).await;
+    assert_eq!(resp.status_code(), 415);
+
+    let resp = server
+        .post("/eth/v1/keystores")
+        .bytes(body.into())
+        .content_type("application/json; charset=utf-8")
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(resp.text(), body);
+
+    // Requests without a body need no Content-Type
+    let resp = server
+        .post("/eth/v1/keystores")
+        .add_header(header::CONTENT_LENGTH, HeaderValue::from_static("0"))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_routes_have_their_own_body_limits() {
+    let server = mock_body_limits_app().unwrap();
+    let body = format!(r#"{{"padding":"{}"}}"#, " ".repeat(2048));
+
+    let resp = server
+        .post("/api/v1/eth2/sign/0x1234")
+        .bytes(body.clone().into())
+        .content_type("application/json")
+        .await;
+    assert_eq!(resp.status_code(), 413);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 413);
+
+    let resp = server
+        .post("/eth/v1/keystores")
+        .bytes(body.into())
+        .content_type("application/json")
+        .await;
+    assert_eq!(resp.status_code(), 200);
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use axum::http::{header, HeaderValue, Method};
+use puffersecuresigner::enclave::shared::cors::{cors, CorsConfig};
+
+const DASHBOARD: &str = "https://dashboard.example.com";
+
+/// Serves a listing, a key management and a signing route behind `config`
+pub fn mock_cors_app(config: CorsConfig) -> Result<axum_test::TestServer> {
+    let ok = || async { "OK" };
+    let test_app = axum::Router::new()
+        .route("/eth/v1/keystores", axum::routing::get(ok).delete(ok))
+        .route("/api/v1/eth2/sign/:bls_pk_hex", axum::routing::post(ok))
+        .layer(axum::middleware::from_fn_with_state(config, cors))
+        .into_make_service();
+    axum_test::TestServer::new(test_app)
+}
+
+fn preflight(
+    server: &axum_test::TestServer,
+    path: &str,
+    origin: &'static str,
+    method: &'static str,
+) -> axum_test::TestRequest {
+    server
+        .method(Method::OPTIONS, path)
+        .add_header(header::ORIGIN, HeaderValue::from_static(origin))
+        .add_header(
+            header::ACCESS_CONTROL_REQUEST_METHOD,
+            HeaderValue::from_static(method),
+        )
+}
+
+#[tokio::test]
+async fn test_cors_allows_reads_by_default() {
+    let server = mock_cors_app(CorsConfig::parse(DASHBOARD, None, 600, false).unwrap()).unwrap();
+
+    // Preflight for a read
+    let resp = preflight(&server, "/eth/v1/keystores", DASHBOARD, "GET").await;
+    assert_eq!(resp.status_code(), 204);
+    let headers = resp.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], DASHBOARD);
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, HEAD");
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+        "authorization, content-type"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+    // The read itself
+    let resp = server
+        .get("/eth/v1/keystores")
+        .add_header(header::ORIGIN, HeaderValue::from_static(DASHBOARD))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        DASHBOARD
+    );
+
+    // Mutating methods are not allowed cross origin
+    let resp = preflight(&server, "/eth/v1/keystores", DASHBOARD, "DELETE").await;
+    assert_eq!(resp.status_code(), 403);
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+    let resp = server
+        .post("/api/v1/eth2/sign/0x1234")
+        .add_header(header::ORIGIN, HeaderValue::from_static(DASHBOARD))
+        .await;
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    // Neither are other origins
+    let resp = preflight(
+        &server,
+        "/eth/v1/keystores",
+        "https://evil.example.com",
+        "GET",
+    )
+    .await;
+    assert_eq!(resp.status_code(), 403);
+    let resp = server
+        .get("/eth/v1/keystores")
+        .add_header(
+            header::ORIGIN,
+            HeaderValue::from_static("https://evil.example.com"),
+        )
+        .await;
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    // Same origin requests are untouched
+    let resp = server.get("/eth/v1/keystores").await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_cors_mutating_routes_need_opt_in() {
+    assert!(CorsConfig::parse(DASHBOARD, Some("GET,DELETE"), 600, false).is_err());
+    assert!(CorsConfig::parse("", None, 600, false).is_err());
+    assert!(CorsConfig::parse(DASHBOARD, Some("GET,NOT A METHOD"), 600, false).is_err());
+
+    let server = mock_cors_app(
+        CorsConfig::parse(
+            &format!("{DASHBOARD}/, https://other.example.com"),
+            Some("get, delete"),
+            60,
+            true,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let resp = preflight(&server, "/eth/v1/keystores", DASHBOARD, "DELETE").await;
+    assert_eq!(resp.status_code(), 204);
+    assert_eq!(
+        resp.headers()[header::ACCESS_CONTROL_ALLOW_METHODS],
+        "GET, DELETE"
+    );
+    assert_eq!(resp.headers()[header::ACCESS_CONTROL_MAX_AGE], "60");
+    let resp = server
+        .delete("/eth/v1/keystores")
+        .add_header(header::ORIGIN, HeaderValue::from_static(DASHBOARD))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        DASHBOARD
+    );
+
+    // POST was not opted in
+    let resp = preflight(&server, "/api/v1/eth2/sign/0x1234", DASHBOARD, "POST").await;
+    assert_eq!(resp.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_cors_any_origin() {
+    let server = mock_cors_app(CorsConfig::parse("*", None, 600, false).unwrap()).unwrap();
+    let resp = preflight(&server, "/eth/v1/keystores", "http://localhost:8080", "GET").await;
+    assert_eq!(resp.status_code(), 204);
+    assert_eq!(
+        resp.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "http://localhost:8080"
+    );
+}
@@ -0,0 +1,137 @@
+use puffersecuresigner::crypto::eth_keys;
+use puffersecuresigner::enclave::secure_signer::datafeed::{
+    abi_encode, signed_price, DatafeedSource, UpstreamError,
+};
+use sha3::Digest;
+
+/// A price source answering every pair with `body` and `status`
+fn spawn_source(status: u16, body: &'static str) -> String {
+    let url = super::leader_sign_helper::spawn_server(axum::Router::new().route(
+        "/v1/:pair",
+        axum::routing::get(move || async move {
+            (
+                axum::http::StatusCode::from_u16(status).unwrap(),
+                [(axum::http::header::CONTENT_TYPE, "application/json")],
+                body,
+            )
+        }),
+    ));
+    format!("{url}/v1/{{pair}}")
+}
+
+async fn datafeed_price(pair: &str) -> axum_test::TestResponse {
+    let app = axum::Router::new().route(
+        "/datafeed/v1/price/:pair",
+        axum::routing::get(
+            puffersecuresigner::enclave::secure_signer::handlers::datafeed_price::handler,
+        ),
+    );
+    let server = axum_test::TestServer::new(app.into_make_service()).unwrap();
+    server.get(&format!("/datafeed/v1/price/{pair}")).await
+}
+
+#[tokio::test]
+async fn test_datafeed_signs_canonical_prices() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_datafeed_signs_canonical_prices",
+    );
+    let pk = eth_keys::eth_key_gen().unwrap();
+    let eth_pk_hex = eth_keys::eth_pk_to_hex(&pk);
+
+    let url = spawn_source(200, r#"{"data": {"amount": "3012.5"}}"#);
+    let source = DatafeedSource::new(&url, "/data/amount", &eth_pk_hex).unwrap();
+    let resp = signed_price(&source, "ethusd").await.unwrap();
+    assert_eq!(resp.payload.pair, "ETHUSD");
+    assert_eq!(resp.payload.price, "301250000000");
+    assert_eq!(resp.payload.decimals, 8);
+    assert_eq!(resp.payload.source, "127.0.0.1");
+    assert_eq!(resp.eth_pk_hex, format!("0x{eth_pk_hex}"));
+    assert_eq!(resp.address, eth_keys::eth_pk_to_address(&pk));
+
+    // The signature is over the EIP-191 digest of the keccak256 of the ABI encoding of the
+    // payload, and recovers the key
+    let encoded = abi_encode(&resp.payload).unwrap();
+    assert_eq!(resp.encoded, puffersecuresigner::to_0x_hex(&encoded));
+    let mut prefixed = b"\x19Ethereum Signed Message:\n32".to_vec();
+    prefixed.extend_from_slice(&sha3::Keccak256::digest(&encoded));
+    let digest = sha3::Keccak256::digest(&prefixed);
+    assert_eq!(resp.digest, puffersecuresigner::to_0x_hex(digest));
+    let sig_bytes = hex::decode(resp.signature.signature.strip_prefix("0x").unwrap()).unwrap();
+    let sig = libsecp256k1::Signature::parse_standard_slice(&sig_bytes[..64]).unwrap();
+    let msg = libsecp256k1::Message::parse_slice(&digest).unwrap();
+    let recovery_id = libsecp256k1::RecoveryId::parse_rpc(resp.signature.v).unwrap();
+    assert_eq!(libsecp256k1::recover(&msg, &sig, &recovery_id).unwrap(), pk);
+
+    // Numbers are taken as well as strings
+    let url = spawn_source(200, r#"{"price": 0.5}"#);
+    let source = DatafeedSource::new(&url, "/price", &eth_pk_hex).unwrap();
+    let resp = signed_price(&source, "ETHUSD").await.unwrap();
+    assert_eq!(resp.payload.price, "50000000");
+}
+
+#[tokio::test]
+async fn test_datafeed_never_signs_a_failed_source() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_datafeed_never_signs_a_failed_source",
+    );
+    let eth_pk_hex = eth_keys::eth_pk_to_hex(&eth_keys::eth_key_gen().unwrap());
+
+    let failing = [
+        spawn_source(500, r#"{"error": "down"}"#),
+        spawn_source(200, r#"{"price": "-1"}"#),
+        spawn_source(200, r#"{"amount": "3012.5"}"#),
+        spawn_source(200, "not json"),
+        format!("{}/v1/{{pair}}", super::leader_sign_helper::dead_url()),
+    ];
+    for url in failing {
+        let source = DatafeedSource::new(&url, "/price", &eth_pk_hex).unwrap();
+        let e = signed_price(&source, "ETHUSD").await.unwrap_err();
+        assert!(e.is::<UpstreamError>(), "{url}: {e:?}");
+    }
+
+    // A source is only built for a saved key and a url with a pair placeholder
+    let unsaved = eth_keys::eth_pk_to_hex(&eth_keys::new_eth_key().unwrap().1);
+    let url = spawn_source(200, r#"{"price": "1"}"#);
+    assert!(DatafeedSource::new(&url, "/price", &unsaved).is_err());
+    assert!(DatafeedSource::new("http://127.0.0.1/v1/ETHUSD", "/price", &eth_pk_hex).is_err());
+    assert!(DatafeedSource::new("file:///v1/{pair}", "/price", &eth_pk_hex).is_err());
+
+    // Bad pairs are refused before the source is asked, and without a datafeed nothing is served
+    assert_eq!(datafeed_price("ETH-USD").await.status_code(), 400);
+    assert_eq!(datafeed_price("ETHUSD").await.status_code(), 503);
+}
+
+#[tokio::test]
+async fn test_generic_route_refuses_the_datafeed_key() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_generic_route_refuses_the_datafeed_key",
+    );
+    let datafeed_pk_hex = eth_keys::eth_pk_to_hex(&eth_keys::eth_key_gen().unwrap());
+    let other_pk_hex = eth_keys::eth_pk_to_hex(&eth_keys::eth_key_gen().unwrap());
+
+    let mut config = puffersecuresigner::config::Config::default();
+    config.datafeed.eth_pk = Some(format!("0x{datafeed_pk_hex}"));
+    let mut state = super::signing_helper::mock_app_state();
+    state.config = std::sync::Arc::new(config);
+
+    // A digest signed with the datafeed key could pass for a signed price, so only the datafeed
+    // signs with it
+    let req = puffersecuresigner::enclave::types::Secp256k1SignRequest {
+        msg_hex: format!("0x{}", hex::encode([0x42_u8; 32])),
+    };
+    for pk_hex in [datafeed_pk_hex.clone(), format!("0x{datafeed_pk_hex}")] {
+        let resp = super::eth_keygen_helper::mock_sign_secp256k1_route_with_state(
+            &pk_hex,
+            &req,
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status_code(), 403);
+    }
+    let resp =
+        super::eth_keygen_helper::mock_sign_secp256k1_route_with_state(&other_pk_hex, &req, state)
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
@@ -0,0 +1,180 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::eth_keygen_helper::register_new_eth_key;
+use super::getter_routes_helper::{bls_key_exists, eth_key_exists};
+use super::read_secure_signer_port;
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::types::{DeleteKeyStatus, DeleteKeysRequest, DeleteKeysResponse};
+use reqwest::{Client, Response, StatusCode};
+
+pub async fn mock_delete_bls_keys_route(
+    req: &DeleteKeysRequest,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::delete(
+                puffersecuresigner::enclave::shared::handlers::delete_bls_keys::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.delete("/eth/v1/keystores").json(req).await)
+}
+
+pub async fn mock_delete_eth_keys_route(
+    req: &DeleteKeysRequest,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/secp256k1",
+            axum::routing::delete(
+                puffersecuresigner::enclave::secure_signer::handlers::delete_eth_keys::handler,
+            ),
+        )
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.delete("/eth/v1/keygen/secp256k1").json(req).await)
+}
+
+pub async fn request_delete_bls_keys_route(
+    req: &DeleteKeysRequest,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!("http://localhost:{}/eth/v1/keystores", port);
+    let response = client.delete(&url).json(req).send().await;
+
+    response
+}
+
+pub async fn make_delete_bls_keys_request(
+    pubkeys: Vec<String>,
+    port: Option<u16>,
+) -> Result<(DeleteKeysResponse, StatusCode)> {
+    let req = DeleteKeysRequest { pubkeys };
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_delete_bls_keys_route(&req, p).await?;
+            let status = resp.status();
+            let resp: DeleteKeysResponse = resp
+                .json()
+                .await
+                .with_context(|| format!("Failed to parse to DeleteKeysResponse"))?;
+            Ok((resp, status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_delete_bls_keys_route(&req).await?;
+            let status = resp.status_code();
+            let resp: DeleteKeysResponse = serde_json::from_slice(resp.as_bytes())
+                .with_context(|| "Failed to parse to DeleteKeysResponse")?;
+            Ok((resp, status))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_delete_bls_key_removes_it_from_list() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_delete_bls_key_removes_it_from_list",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    assert!(bls_key_exists(&bls_pk_hex, port).await);
+
+    let (resp, status) = make_delete_bls_keys_request(vec![bls_pk_hex.clone()], port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data.len(), 1);
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::Deleted);
+    assert!(!bls_key_exists(&bls_pk_hex, port).await);
+
+    // Deleting again reports the key as missing
+    let (resp, status) = make_delete_bls_keys_request(vec![bls_pk_hex], port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::NotFound);
+}
+
+#[tokio::test]
+async fn test_delete_bls_keys_reports_status_per_key() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_delete_bls_keys_reports_status_per_key",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let missing_pk_hex = format!("0x{}", "ab".repeat(48));
+
+    let (resp, status) =
+        make_delete_bls_keys_request(vec![bls_pk_hex, missing_pk_hex, "0xbad".to_string()], port)
+            .await
+            .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data.len(), 3);
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::Deleted);
+    assert_eq!(resp.data[1].status, DeleteKeyStatus::NotFound);
+    assert_eq!(resp.data[2].status, DeleteKeyStatus::Error);
+}
+
+#[tokio::test]
+async fn test_deleted_bls_key_cannot_sign() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_deleted_bls_key_cannot_sign");
+    let randao_reveal = || {
+        let req = crate::signing_tests::randao_reveal::mock_randao_reveal_request();
+        puffersecuresigner::eth2::eth_signing::BLSSignMsg::RANDAO_REVEAL(
+            serde_json::from_str(&req).unwrap(),
+        )
+    };
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = super::signing_helper::mock_secure_sign_route(&bls_pk_hex, randao_reveal())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    let (resp, _) = make_delete_bls_keys_request(vec![bls_pk_hex.clone()], None)
+        .await
+        .unwrap();
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::Deleted);
+
+    // The key file is gone and nothing cached signs in its place
+    let pk_hex: String = puffersecuresigner::strip_0x_prefix!(bls_pk_hex);
+    let file_path: std::path::PathBuf =
+        puffersecuresigner::io::data_dir::bls_keys_dir().join(&pk_hex);
+    assert!(!file_path.exists());
+    let resp = super::signing_helper::mock_secure_sign_route(&bls_pk_hex, randao_reveal())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_delete_eth_key() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_delete_eth_key");
+    let eth_pk_hex = register_new_eth_key(None).await.pk_hex;
+    assert!(eth_key_exists(&eth_pk_hex, None).await);
+
+    let req = DeleteKeysRequest {
+        pubkeys: vec![eth_pk_hex.clone(), "0xbad".to_string()],
+    };
+    let resp = mock_delete_eth_keys_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp: DeleteKeysResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::Deleted);
+    assert_eq!(resp.data[1].status, DeleteKeyStatus::Error);
+    assert!(!eth_key_exists(&eth_pk_hex, None).await);
+
+    // Deleting again reports the key as missing
+    let resp = mock_delete_eth_keys_route(&req).await.unwrap();
+    let resp: DeleteKeysResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::NotFound);
+}
@@ -0,0 +1,271 @@
+use puffersecuresigner::crypto::{bls_keys, eth_keys};
+use puffersecuresigner::enclave::secure_signer::worker::{open_key_share, save_key_share};
+use puffersecuresigner::enclave::types::{
+    DistributeKeySharesResponse, DistributionState, HandshakeChallengeRequest,
+    HandshakeChallengeResponse, HandshakeRequest, KeyGenResponse, SealedKeyShareRequest,
+    ShareDeliveryStatus, SplitBlsKeyRequest, ThresholdKeyInventory, WorkerKeyShareRequest,
+};
+use puffersecuresigner::eth2::slash_protection::{FileBackend, SlashingProtectionBackend};
+use puffersecuresigner::io::threshold_keys::read_threshold_key;
+
+fn mock_worker_keyshare_app() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/worker/v1/keyshare",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_keyshare::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+async fn distribute(bls_pk_hex: &str) -> axum_test::TestResponse {
+    let test_app = axum::Router::new()
+        .route(
+            "/leader/v1/keys/:bls_pk_hex/distribute",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::distribute_key_shares::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+    server
+        .post(&format!("/leader/v1/keys/{bls_pk_hex}/distribute"))
+        .await
+}
+
+#[tokio::test]
+async fn test_worker_keyshare_checks_the_verification_vector() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_worker_keyshare_checks_the_verification_vector",
+    );
+    let worker_pk = eth_keys::eth_key_gen().unwrap();
+    let sk = bls_keys::new_bls_key(0).secret_key();
+    let (pk_set, sk_shares) = bls_keys::split_bls_key(&sk, 3, 2).unwrap();
+
+    let share = |index: usize| {
+        let ct = eth_keys::envelope_encrypt(&worker_pk, &sk_shares[index].to_bytes()).unwrap();
+        WorkerKeyShareRequest {
+            worker_pk_hex: puffersecuresigner::to_0x_hex(worker_pk.serialize_compressed()),
+            bls_pk_hex: format!("0x{}", pk_set.public_key().to_hex()),
+            index,
+            verification_vector: format!("0x{}", hex::encode(pk_set.to_bytes())),
+            pk_share_hex: format!(
+                "0x{}",
+                hex::encode(pk_set.public_key_share(index).to_bytes())
+            ),
+            encrypted_sk_share_hex: puffersecuresigner::to_0x_hex(ct),
+        }
+    };
+    let leader_pk_hex =
+        puffersecuresigner::to_0x_hex(eth_keys::new_eth_key().unwrap().1.serialize_compressed());
+
+    // A share claiming another index of the verification vector is refused
+    let mut req = share(1);
+    req.index = 2;
+    assert!(open_key_share(&req).is_err());
+
+    // So is a share encrypted to a key this worker does not hold
+    let mut req = share(1);
+    let (_, other_pk) = eth_keys::new_eth_key().unwrap();
+    req.worker_pk_hex = puffersecuresigner::to_0x_hex(other_pk.serialize_compressed());
+    assert!(open_key_share(&req).is_err());
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &share(1).pk_share_hex
+    ));
+
+    // Shares are only accepted from a leader that attested to this worker
+    let resp = mock_worker_keyshare_app()
+        .post("/worker/v1/keyshare")
+        .json(&SealedKeyShareRequest {
+            leader_pubkey: leader_pk_hex,
+            sealed_share: puffersecuresigner::to_0x_hex(serde_json::to_vec(&share(1)).unwrap()),
+        })
+        .await;
+    assert_eq!(resp.status_code(), 403);
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &share(1).pk_share_hex
+    ));
+
+    // The share is saved as a signing key, and saving it again is harmless
+    for _ in 0..2 {
+        let sk_share = open_key_share(&share(1)).unwrap();
+        let saved = save_key_share(&FileBackend, &sk_share).unwrap();
+        assert_eq!(saved, share(1).pk_share_hex);
+    }
+    assert!(puffersecuresigner::io::key_management::bls_key_exists(
+        &share(1).pk_share_hex
+    ));
+    assert!(FileBackend.exists(&share(1).pk_share_hex[2..]));
+}
+
+#[tokio::test]
+async fn test_worker_handshake_refuses_unattested_leaders() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_worker_handshake_refuses_unattested_leaders",
+    );
+    let test_app = axum::Router::new()
+        .route(
+            "/worker/v1/handshake/challenge",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_handshake_challenge::handler,
+            ),
+        )
+        .route(
+            "/worker/v1/handshake",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_handshake::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+    let leader_pk_hex =
+        puffersecuresigner::to_0x_hex(eth_keys::new_eth_key().unwrap().1.serialize_compressed());
+    let challenge_req = |worker_pk_hex: String| HandshakeChallengeRequest {
+        leader_pubkey: leader_pk_hex.clone(),
+        worker_pubkey: worker_pk_hex,
+        nonce: puffersecuresigner::enclave::secure_signer::handshake::new_nonce().unwrap(),
+        kind: Default::default(),
+    };
+
+    // The worker only attests to keys it holds
+    let (_, other_pk) = eth_keys::new_eth_key().unwrap();
+    let resp = server
+        .post("/worker/v1/handshake/challenge")
+        .json(&challenge_req(puffersecuresigner::to_0x_hex(
+            other_pk.serialize_compressed(),
+        )))
+        .await;
+    assert_eq!(resp.status_code(), 404);
+
+    let worker_pk = eth_keys::eth_key_gen().unwrap();
+    let resp = server
+        .post("/worker/v1/handshake/challenge")
+        .json(&challenge_req(puffersecuresigner::to_0x_hex(
+            worker_pk.serialize_compressed(),
+        )))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let challenge: HandshakeChallengeResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // Outside of SGX the leader's evidence is unsigned, so it is refused, and the challenge
+    // cannot be answered again
+    let finish = HandshakeRequest {
+        leader_pubkey: leader_pk_hex.clone(),
+        challenge: challenge.challenge,
+        evidence: Default::default(),
+    };
+    for _ in 0..2 {
+        let resp = server.post("/worker/v1/handshake").json(&finish).await;
+        assert_eq!(resp.status_code(), 403);
+    }
+}
+
+#[tokio::test]
+async fn test_distribute_refuses_unattested_workers() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_distribute_refuses_unattested_workers",
+    );
+    let sk = bls_keys::new_bls_key(0).secret_key();
+    let bls_pk_hex = sk.public_key().to_hex();
+    puffersecuresigner::io::key_management::write_bls_key(&bls_pk_hex, &hex::encode(sk.to_bytes()))
+        .unwrap();
+
+    // The workers registered, but their evidence does not pass a measurement policy
+    let worker_pks: Vec<_> = (0..2).map(|_| eth_keys::eth_key_gen().unwrap()).collect();
+    for pk in &worker_pks {
+        puffersecuresigner::io::workers::register_worker(
+            &super::leader_sign_helper::spawn_worker(),
+            &puffersecuresigner::to_0x_hex(pk.serialize_compressed()),
+            Default::default(),
+        )
+        .unwrap();
+    }
+    // Nor can the leader pick them to split across
+    let mut req = SplitBlsKeyRequest {
+        bls_pk_hex: bls_pk_hex.clone(),
+        n: Some(2),
+        threshold: 2,
+        workers: vec![],
+        mrenclave: None,
+    };
+    let resp = super::split_bls_key_helper::mock_split_bls_key_route(&req)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+    req.n = None;
+    let resp = super::split_bls_key_helper::mock_split_bls_key_route(&req)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    let req = SplitBlsKeyRequest {
+        bls_pk_hex: bls_pk_hex.clone(),
+        n: None,
+        threshold: 2,
+        workers: worker_pks
+            .iter()
+            .map(|pk| KeyGenResponse {
+                pk_hex: eth_keys::eth_pk_to_hex(pk),
+                evidence: Default::default(),
+                address: None,
+                signing_pk: None,
+            })
+            .collect(),
+        mrenclave: None,
+    };
+    let resp = super::split_bls_key_helper::mock_split_bls_key_route(&req)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let leader = super::signing_helper::mock_app_state();
+    let inventory = |resp: axum_test::TestResponse| -> ThresholdKeyInventory {
+        assert_eq!(resp.status_code(), 200);
+        serde_json::from_slice(resp.as_bytes()).unwrap()
+    };
+    let split = inventory(super::leader_sign_helper::key_inventory(&leader, &bls_pk_hex).await);
+    assert_eq!(split.state, DistributionState::Undistributed);
+    assert!(!split.signable);
+    assert!(split
+        .shares
+        .iter()
+        .all(|share| share.worker_id.is_some() && share.delivered_at.is_none()));
+
+    let resp = distribute(&bls_pk_hex).await;
+    assert_eq!(resp.status_code(), 200);
+    let deliveries: DistributeKeySharesResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(deliveries.data.len(), 2);
+    for delivery in &deliveries.data {
+        assert_eq!(delivery.status, ShareDeliveryStatus::Failed);
+        assert!(delivery.message.as_ref().unwrap().contains("revoked"));
+    }
+    let key = read_threshold_key(&bls_pk_hex).unwrap();
+    assert!(key.shares.iter().all(|share| !share.acknowledged));
+    assert!(key
+        .shares
+        .iter()
+        .all(|share| !share.encrypted_sk_share_hex.is_empty()));
+
+    // Acknowledged shares are not sent again
+    let mut key = key;
+    key.shares[0].acknowledged = true;
+    puffersecuresigner::io::threshold_keys::write_threshold_key(&key).unwrap();
+    let resp = distribute(&bls_pk_hex).await;
+    let deliveries: DistributeKeySharesResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(
+        deliveries.data[0].status,
+        ShareDeliveryStatus::AlreadyDelivered
+    );
+    assert_eq!(deliveries.data[1].status, ShareDeliveryStatus::Failed);
+    assert!(read_threshold_key(&bls_pk_hex).unwrap().shares[0].acknowledged);
+    let partial = inventory(super::leader_sign_helper::key_inventory(&leader, &bls_pk_hex).await);
+    assert_eq!(partial.state, DistributionState::Partial);
+    assert!(partial.shares[0].acknowledged && !partial.shares[1].acknowledged);
+
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    assert_eq!(distribute(&unknown_pk_hex).await.status_code(), 404);
+}
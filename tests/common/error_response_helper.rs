@@ -0,0 +1,122 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::signing_helper::{mock_secure_sign_route, mock_secure_sign_route_raw};
+use puffersecuresigner::enclave::types::ErrorResponse;
+use puffersecuresigner::eth2::eth_signing::{BLSSignMsg, BlockRequest};
+
+/// Asserts the body is an `ErrorResponse` with `status` as its code
+fn assert_error_body(resp: &axum_test::TestResponse, status: u16) -> ErrorResponse {
+    assert_eq!(resp.status_code(), status);
+    assert!(resp
+        .header(axum::http::header::CONTENT_TYPE)
+        .to_str()
+        .unwrap()
+        .starts_with("application/json"));
+    let body: serde_json::Value = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let fields: Vec<&String> = body.as_object().unwrap().keys().collect();
+    assert!(fields
+        .iter()
+        .all(|field| ["code", "message", "details"].contains(&field.as_str())));
+    let err: ErrorResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(err.code, status);
+    assert!(!err.message.is_empty());
+    err
+}
+
+fn block_request(slot: u64) -> BLSSignMsg {
+    let req: BlockRequest = serde_json::from_str(
+        &crate::signing_tests::block::mock_propose_block_request(slot),
+    )
+    .unwrap();
+    BLSSignMsg::BLOCK(req)
+}
+
+/// Serves the sign route and a route failing with a plain text 500 behind `json_errors`
+fn error_test_server() -> axum_test::TestServer {
+    let app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .route(
+            "/broken",
+            axum::routing::get(|| async {
+                (
+                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    "Something broke",
+                )
+            }),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::errors::json_errors,
+        ));
+    axum_test::TestServer::new(app.into_make_service()).unwrap()
+}
+
+#[tokio::test]
+async fn test_bad_request_error_body() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_bad_request_error_body");
+    let resp = mock_secure_sign_route(&"0xdeadbeef".to_string(), block_request(1))
+        .await
+        .unwrap();
+    let err = assert_error_body(&resp, 400);
+    assert!(err.message.contains("Bad bls_pk_hex"));
+
+    // Malformed JSON is rejected by axum before the handler runs
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = error_test_server()
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .content_type("application/json")
+        .bytes("{not json".into())
+        .await;
+    assert_error_body(&resp, 400);
+
+    let resp = mock_secure_sign_route_raw(&bls_pk_hex, r#"{"type": "BLOCK"}"#.to_string())
+        .await
+        .unwrap();
+    assert_error_body(&resp, 400);
+}
+
+#[tokio::test]
+async fn test_not_found_error_body() {
+    let bls_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_request(1))
+        .await
+        .unwrap();
+    let err = assert_error_body(&resp, 404);
+    assert!(err.message.contains(&bls_pk_hex));
+
+    let resp = error_test_server().get("/no/such/route").await;
+    let err = assert_error_body(&resp, 404);
+    assert_eq!(err.message, "Not Found");
+}
+
+#[tokio::test]
+async fn test_precondition_failed_error_body() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_precondition_failed_error_body",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_request(100))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    // A block below the last signed slot is slashable
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_request(99))
+        .await
+        .unwrap();
+    let err = assert_error_body(&resp, 412);
+    assert!(err.message.contains("slashing protection"));
+}
+
+#[tokio::test]
+async fn test_internal_error_body() {
+    let resp = error_test_server().get("/broken").await;
+    let err = assert_error_body(&resp, 500);
+    assert_eq!(err.message, "Something broke");
+    assert!(err.details.is_none());
+}
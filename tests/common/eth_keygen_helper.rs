@@ -72,6 +72,8 @@ pub async fn register_new_eth_key(
 
 #[tokio::test]
 async fn test_register_new_eth_key() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_register_new_eth_key");
     let port = read_secure_signer_port();
     let resp = register_new_eth_key(port).await;
     let _pk = eth_keys::eth_pk_from_hex(&resp.pk_hex).unwrap();
@@ -80,6 +82,9 @@ async fn test_register_new_eth_key() {
 
 #[tokio::test]
 async fn test_eth_key_in_remote_attestation_evidence() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_eth_key_in_remote_attestation_evidence",
+    );
     if env::var("SECURE_SIGNER_PORT").is_ok() {
         // Local dev is not set so use SGX.
         let port = read_secure_signer_port();
@@ -99,3 +104,141 @@ async fn test_eth_key_in_remote_attestation_evidence() {
         );
     }
 }
+
+pub async fn mock_sign_secp256k1_route(
+    eth_pk_hex: &str,
+    req: &puffersecuresigner::enclave::types::Secp256k1SignRequest,
+) -> Result<axum_test::TestResponse> {
+    mock_sign_secp256k1_route_with_state(eth_pk_hex, req, super::signing_helper::mock_app_state())
+        .await
+}
+
+pub async fn mock_sign_secp256k1_route_with_state(
+    eth_pk_hex: &str,
+    req: &puffersecuresigner::enclave::types::Secp256k1SignRequest,
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/sign/secp256k1/:eth_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::sign_secp256k1::handler,
+            ),
+        )
+        .with_state(state)
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server
+        .post(&format!("/eth/v1/sign/secp256k1/{eth_pk_hex}"))
+        .json(req)
+        .await)
+}
+
+#[tokio::test]
+async fn test_sign_secp256k1_digest() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_sign_secp256k1_digest");
+    let eth_pk_hex = register_new_eth_key(None).await.pk_hex;
+    let digest = [0x42_u8; 32];
+    let req = puffersecuresigner::enclave::types::Secp256k1SignRequest {
+        msg_hex: format!("0x{}", hex::encode(digest)),
+    };
+
+    let resp = mock_sign_secp256k1_route(&eth_pk_hex, &req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp: puffersecuresigner::enclave::types::Secp256k1SignResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // r | s verifies against the key, and v recovers it
+    let sig_bytes = hex::decode(resp.signature.strip_prefix("0x").unwrap()).unwrap();
+    assert_eq!(sig_bytes.len(), 65);
+    assert_eq!(sig_bytes[64], resp.v);
+    let sig = libsecp256k1::Signature::parse_standard_slice(&sig_bytes[..64]).unwrap();
+    let msg = libsecp256k1::Message::parse(&digest);
+    let pk = eth_keys::eth_pk_from_hex(&eth_pk_hex).unwrap();
+    assert!(libsecp256k1::verify(&msg, &sig, &pk));
+    let recovery_id = libsecp256k1::RecoveryId::parse_rpc(resp.v).unwrap();
+    assert_eq!(libsecp256k1::recover(&msg, &sig, &recovery_id).unwrap(), pk);
+    assert_eq!(
+        format!("{}{}", resp.r, resp.s.strip_prefix("0x").unwrap()),
+        format!("0x{}", hex::encode(&sig_bytes[..64]))
+    );
+
+    // Digests must be 32 bytes
+    let short = puffersecuresigner::enclave::types::Secp256k1SignRequest {
+        msg_hex: "0xdeadbeef".to_string(),
+    };
+    let resp = mock_sign_secp256k1_route(&eth_pk_hex, &short)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    // Unknown keys are not found
+    let (_, unknown_pk) = eth_keys::new_eth_key().unwrap();
+    let resp = mock_sign_secp256k1_route(&eth_keys::eth_pk_to_hex(&unknown_pk), &req)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 404);
+
+    // Malformed keys are bad requests
+    let resp = mock_sign_secp256k1_route("0xdeadbeef", &req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
+
+pub async fn mock_get_eth_key_by_address_route(address: &str) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/eth/:address",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::get_eth_key_by_address::handler,
+            ),
+        )
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.get(&format!("/eth/v1/keygen/eth/{address}")).await)
+}
+
+#[tokio::test]
+async fn test_eth_keys_report_their_address() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_eth_keys_report_their_address");
+    let resp = register_new_eth_key(None).await;
+    let pk = eth_keys::parse_eth_pk_hex(&resp.pk_hex).unwrap();
+    let address = resp.address.unwrap();
+    assert_eq!(address, eth_keys::eth_pk_to_address(&pk));
+
+    // The listed key carries the same address
+    let (keys, _) = super::getter_routes_helper::make_list_request(
+        super::getter_routes_helper::ListRequestKind::ETH,
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(keys
+        .data
+        .iter()
+        .any(|k| k.address.as_ref() == Some(&address)));
+
+    // Addresses resolve back to the key regardless of case
+    for a in [address.clone(), address.to_lowercase()] {
+        let resp = mock_get_eth_key_by_address_route(&a).await.unwrap();
+        assert_eq!(resp.status_code(), 200);
+        let key: puffersecuresigner::enclave::types::ListKeysResponseInner =
+            serde_json::from_slice(resp.as_bytes()).unwrap();
+        assert_eq!(key.pubkey, format!("0x{}", eth_keys::eth_pk_to_hex(&pk)));
+        assert_eq!(key.address, Some(address.clone()));
+    }
+
+    let resp = mock_get_eth_key_by_address_route(&format!("0x{}", "00".repeat(20)))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 404);
+    let resp = mock_get_eth_key_by_address_route("0xdeadbeef")
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
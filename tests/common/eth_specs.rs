@@ -11,6 +11,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 pub const BASE_DIR: &str = "./tests/consensus-spec-tests/tests/mainnet/capella/ssz_static/";
+pub const BELLATRIX_BASE_DIR: &str =
+    "./tests/consensus-spec-tests/tests/mainnet/bellatrix/ssz_static/";
+pub const ALTAIR_BASE_DIR: &str = "./tests/consensus-spec-tests/tests/mainnet/altair/ssz_static/";
+pub const PHASE0_BASE_DIR: &str = "./tests/consensus-spec-tests/tests/mainnet/phase0/ssz_static/";
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 struct ExpectedRoot {
@@ -123,6 +127,7 @@ fn get_test_vec_block_v2(ssz_file: &Path, root_file: &Path) -> Result<BLSSignMsg
     let req_wrapper = BlockV2RequestWrapper {
         version: "Capella".to_string(),
         block_header,
+        graffiti: None,
     };
 
     let req = BlockV2Request {
@@ -318,6 +323,26 @@ fn test_sync_aggregate() {
     }
 }
 
+#[test]
+fn test_sync_committee_contribution() {
+    let path: PathBuf = [BASE_DIR, "SyncCommitteeContribution"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<SyncCommitteeContribution>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_contribution_and_proof() {
+    let path: PathBuf = [BASE_DIR, "ContributionAndProof"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<ContributionAndProof>(ssz_file, root_file).unwrap();
+    }
+}
+
 #[test]
 fn test_bls_to_execution_changes() {
     let path: PathBuf = [BASE_DIR, "BLSToExecutionChange"].iter().collect();
@@ -419,3 +444,98 @@ fn test_sync_committee_contribution_and_proof_ssz_static() {
     dbg!(&path);
     get_all_test_vecs("ContributionAndProof").unwrap();
 }
+
+#[test]
+fn test_phase0_beacon_block_body() {
+    let path: PathBuf = [PHASE0_BASE_DIR, "BeaconBlockBody"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<BeaconBlockBodyPhase0>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_phase0_beacon_block_header_matches_block_root() {
+    let path: PathBuf = [PHASE0_BASE_DIR, "BeaconBlock"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        let block = get_test_vec_container::<BeaconBlockPhase0>(ssz_file, root_file).unwrap();
+        assert_eq!(block.to_header().tree_hash_root(), block.tree_hash_root());
+    }
+}
+
+#[test]
+fn test_altair_beacon_block_body() {
+    let path: PathBuf = [ALTAIR_BASE_DIR, "BeaconBlockBody"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<BeaconBlockBodyAltair>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_altair_beacon_block_header_matches_block_root() {
+    let path: PathBuf = [ALTAIR_BASE_DIR, "BeaconBlock"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        let block = get_test_vec_container::<BeaconBlockAltair>(ssz_file, root_file).unwrap();
+        assert_eq!(block.to_header().tree_hash_root(), block.tree_hash_root());
+    }
+}
+
+#[test]
+fn test_bellatrix_execution_payload() {
+    let path: PathBuf = [BELLATRIX_BASE_DIR, "ExecutionPayload"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<ExecutionPayloadBellatrix>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_bellatrix_execution_payload_header() {
+    let path: PathBuf = [BELLATRIX_BASE_DIR, "ExecutionPayloadHeader"]
+        .iter()
+        .collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<ExecutionPayloadHeaderBellatrix>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_capella_execution_payload_header() {
+    let path: PathBuf = [BASE_DIR, "ExecutionPayloadHeader"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<ExecutionPayloadHeader>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_bellatrix_beacon_block_body() {
+    let path: PathBuf = [BELLATRIX_BASE_DIR, "BeaconBlockBody"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        get_test_vec_container::<BeaconBlockBodyBellatrix>(ssz_file, root_file).unwrap();
+    }
+}
+
+#[test]
+fn test_bellatrix_beacon_block_header_matches_block_root() {
+    let path: PathBuf = [BELLATRIX_BASE_DIR, "BeaconBlock"].iter().collect();
+    let file_paths = get_testvec_file_names(&path).unwrap();
+    for (ssz_file, root_file) in file_paths.iter() {
+        dbg!(ssz_file);
+        let block = get_test_vec_container::<BeaconBlockBellatrix>(ssz_file, root_file).unwrap();
+        assert_eq!(block.to_header().tree_hash_root(), block.tree_hash_root());
+    }
+}
@@ -0,0 +1,213 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::read_secure_signer_port;
+use super::signing_helper::make_signing_route_request;
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::types::{FeeRecipientResponse, SetFeeRecipientRequest};
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use reqwest::{Client, Method, Response, StatusCode};
+
+fn mock_fee_recipient_app() -> axum::routing::IntoMakeService<axum::Router> {
+    axum::Router::new()
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/feerecipient",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_fee_recipient::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_fee_recipient::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_fee_recipient::handler),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service()
+}
+
+pub async fn mock_fee_recipient_route(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+) -> Result<axum_test::TestResponse> {
+    let server = axum_test::TestServer::new(mock_fee_recipient_app())?;
+    let path = format!("/eth/v1/validator/{}/feerecipient", bls_pk_hex);
+    let req = match method {
+        Method::POST => server.post(&path),
+        Method::DELETE => server.delete(&path),
+        _ => server.get(&path),
+    };
+    Ok(match body {
+        Some(body) => req.json(&body).await,
+        None => req.await,
+    })
+}
+
+pub async fn request_fee_recipient_route(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!(
+        "http://localhost:{}/eth/v1/validator/{}/feerecipient",
+        port, bls_pk_hex
+    );
+    let req = client.request(method, &url);
+    match body {
+        Some(body) => req.json(&body).send().await,
+        None => req.send().await,
+    }
+}
+
+/// Returns the raw response body alongside the status, as POST and DELETE answer without a body
+async fn make_fee_recipient_request(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+    port: Option<u16>,
+) -> Result<(Vec<u8>, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_fee_recipient_route(method, bls_pk_hex, body, p).await?;
+            let status = resp.status();
+            let bytes = resp
+                .bytes()
+                .await
+                .with_context(|| "Failed to read feerecipient response")?;
+            Ok((bytes.to_vec(), status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_fee_recipient_route(method, bls_pk_hex, body).await?;
+            Ok((resp.as_bytes().to_vec(), resp.status_code()))
+        }
+    }
+}
+
+pub async fn make_get_fee_recipient_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<(Option<FeeRecipientResponse>, StatusCode)> {
+    let (bytes, status) = make_fee_recipient_request(Method::GET, bls_pk_hex, None, port).await?;
+    Ok((serde_json::from_slice(&bytes).ok(), status))
+}
+
+pub async fn make_set_fee_recipient_request(
+    bls_pk_hex: &str,
+    ethaddress: &str,
+    port: Option<u16>,
+) -> Result<StatusCode> {
+    let req = SetFeeRecipientRequest {
+        ethaddress: ethaddress.to_string(),
+    };
+    let (_, status) = make_fee_recipient_request(
+        Method::POST,
+        bls_pk_hex,
+        Some(serde_json::to_value(req)?),
+        port,
+    )
+    .await?;
+    Ok(status)
+}
+
+pub async fn make_delete_fee_recipient_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<StatusCode> {
+    let (_, status) = make_fee_recipient_request(Method::DELETE, bls_pk_hex, None, port).await?;
+    Ok(status)
+}
+
+fn validator_registration_request() -> BLSSignMsg {
+    let req = crate::signing_tests::validator_registration::mock_validator_registration_request();
+    serde_json::from_str(&req).unwrap()
+}
+
+#[tokio::test]
+async fn test_set_get_delete_fee_recipient() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_set_get_delete_fee_recipient");
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let ethaddress = format!("0x{}", "ab".repeat(20));
+
+    let (resp, status) = make_get_fee_recipient_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+    assert!(resp.is_none());
+
+    let status = make_set_fee_recipient_request(&bls_pk_hex, &ethaddress.to_uppercase(), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+
+    let (resp, status) = make_get_fee_recipient_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let resp = resp.unwrap();
+    assert_eq!(resp.data.pubkey, format!("0x{}", bls_pk_hex));
+    assert_eq!(resp.data.ethaddress, ethaddress);
+
+    let status = make_delete_fee_recipient_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 204);
+
+    let (_, status) = make_get_fee_recipient_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_set_fee_recipient_rejects_bad_input() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_set_fee_recipient_rejects_bad_input",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // 19 bytes
+    let status = make_set_fee_recipient_request(&bls_pk_hex, &"ab".repeat(19), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+
+    // Unknown key
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let status = make_set_fee_recipient_request(&unknown_pk_hex, &"ab".repeat(20), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_pinned_fee_recipient_enforced_on_registration() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_pinned_fee_recipient_enforced_on_registration",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // The mock registration uses 0x2a2a...2a
+    let status = make_set_fee_recipient_request(&bls_pk_hex, &"ab".repeat(20), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) =
+        make_signing_route_request(validator_registration_request(), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 412);
+
+    let status = make_set_fee_recipient_request(&bls_pk_hex, &"2a".repeat(20), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) =
+        make_signing_route_request(validator_registration_request(), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,290 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::read_secure_signer_port;
+use super::signing_helper::{
+    make_signing_route_request, mock_app_state, mock_secure_sign_route_with_state,
+};
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::shared::handlers::GasLimitPolicy;
+use puffersecuresigner::enclave::types::{GasLimitResponse, SetGasLimitRequest, SignatureResponse};
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use reqwest::{Client, Method, Response, StatusCode};
+
+fn mock_gas_limit_app() -> axum::routing::IntoMakeService<axum::Router> {
+    axum::Router::new()
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/gas_limit",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_gas_limit::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_gas_limit::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_gas_limit::handler),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service()
+}
+
+pub async fn mock_gas_limit_route(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+) -> Result<axum_test::TestResponse> {
+    let server = axum_test::TestServer::new(mock_gas_limit_app())?;
+    let path = format!("/eth/v1/validator/{}/gas_limit", bls_pk_hex);
+    let req = match method {
+        Method::POST => server.post(&path),
+        Method::DELETE => server.delete(&path),
+        _ => server.get(&path),
+    };
+    Ok(match body {
+        Some(body) => req.json(&body).await,
+        None => req.await,
+    })
+}
+
+pub async fn request_gas_limit_route(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!(
+        "http://localhost:{}/eth/v1/validator/{}/gas_limit",
+        port, bls_pk_hex
+    );
+    let req = client.request(method, &url);
+    match body {
+        Some(body) => req.json(&body).send().await,
+        None => req.send().await,
+    }
+}
+
+/// Returns the raw response body alongside the status, as POST and DELETE answer without a body
+async fn make_gas_limit_request(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+    port: Option<u16>,
+) -> Result<(Vec<u8>, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_gas_limit_route(method, bls_pk_hex, body, p).await?;
+            let status = resp.status();
+            let bytes = resp
+                .bytes()
+                .await
+                .with_context(|| "Failed to read gas_limit response")?;
+            Ok((bytes.to_vec(), status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_gas_limit_route(method, bls_pk_hex, body).await?;
+            Ok((resp.as_bytes().to_vec(), resp.status_code()))
+        }
+    }
+}
+
+pub async fn make_get_gas_limit_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<(Option<GasLimitResponse>, StatusCode)> {
+    let (bytes, status) = make_gas_limit_request(Method::GET, bls_pk_hex, None, port).await?;
+    Ok((serde_json::from_slice(&bytes).ok(), status))
+}
+
+pub async fn make_set_gas_limit_request(
+    bls_pk_hex: &str,
+    gas_limit: &str,
+    port: Option<u16>,
+) -> Result<StatusCode> {
+    let req = SetGasLimitRequest {
+        gas_limit: gas_limit.to_string(),
+    };
+    let (_, status) = make_gas_limit_request(
+        Method::POST,
+        bls_pk_hex,
+        Some(serde_json::to_value(req)?),
+        port,
+    )
+    .await?;
+    Ok(status)
+}
+
+pub async fn make_delete_gas_limit_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<StatusCode> {
+    let (_, status) = make_gas_limit_request(Method::DELETE, bls_pk_hex, None, port).await?;
+    Ok(status)
+}
+
+/// The mock registration requests a gas_limit of 30000000
+fn validator_registration_request() -> BLSSignMsg {
+    let req = crate::signing_tests::validator_registration::mock_validator_registration_request();
+    serde_json::from_str(&req).unwrap()
+}
+
+#[tokio::test]
+async fn test_set_get_delete_gas_limit() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_set_get_delete_gas_limit");
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let (_, status) = make_get_gas_limit_request(&bls_pk_hex, port).await.unwrap();
+    assert_eq!(status, 404);
+
+    let status = make_set_gas_limit_request(&bls_pk_hex, "25000000", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+
+    let (resp, status) = make_get_gas_limit_request(&bls_pk_hex, port).await.unwrap();
+    assert_eq!(status, 200);
+    let resp = resp.unwrap();
+    assert_eq!(resp.data.pubkey, format!("0x{}", bls_pk_hex));
+    assert_eq!(resp.data.gas_limit, "25000000");
+
+    let status = make_delete_gas_limit_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 204);
+
+    let (_, status) = make_get_gas_limit_request(&bls_pk_hex, port).await.unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_set_gas_limit_rejects_bad_input() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_set_gas_limit_rejects_bad_input",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    for gas_limit in ["0", "-1", "0x1c9c380", "18446744073709551616"] {
+        let status = make_set_gas_limit_request(&bls_pk_hex, gas_limit, port)
+            .await
+            .unwrap();
+        assert_eq!(status, 400, "{gas_limit}");
+    }
+
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let status = make_set_gas_limit_request(&unknown_pk_hex, "30000000", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_pinned_gas_limit_rejects_registration() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_pinned_gas_limit_rejects_registration",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let status = make_set_gas_limit_request(&bls_pk_hex, "25000000", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) =
+        make_signing_route_request(validator_registration_request(), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 412);
+
+    let status = make_set_gas_limit_request(&bls_pk_hex, "30000000", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) =
+        make_signing_route_request(validator_registration_request(), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_pinned_gas_limit_overrides_registration() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_pinned_gas_limit_overrides_registration",
+    );
+    // The policy is fixed when Secure-Signer starts, so only the mocked route can exercise it
+    let port = None;
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let mut state = mock_app_state();
+    state.gas_limit_policy = GasLimitPolicy::Override;
+
+    let status = make_set_gas_limit_request(&bls_pk_hex, "25000000", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        validator_registration_request(),
+        Some("application/json"),
+        state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let overridden: SignatureResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // Matches a signature over the registration with the pinned gas_limit
+    let status = make_delete_gas_limit_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 204);
+    let mut req = validator_registration_request();
+    if let BLSSignMsg::VALIDATOR_REGISTRATION(m) = &mut req {
+        m.signingRoot = None;
+        m.validator_registration.gas_limit = 25_000_000;
+    }
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.unwrap().signature, overridden.signature);
+}
+
+#[tokio::test]
+async fn test_validator_configs_keep_concurrent_updates() {
+    let data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_validator_configs_keep_concurrent_updates",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let state = mock_app_state();
+    let fee_recipient = format!("0x{}", "2a".repeat(20));
+
+    let updates: Vec<_> = (0..8)
+        .map(|i| {
+            let state = state.clone();
+            let bls_pk_hex = bls_pk_hex.clone();
+            let fee_recipient = fee_recipient.clone();
+            let dir = data_dir.path().to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::enter(&dir);
+                state.validator_configs.update(&bls_pk_hex, |config| {
+                    if i % 2 == 0 {
+                        config.fee_recipient = Some(fee_recipient);
+                    } else {
+                        config.gas_limit = Some(30_000_000);
+                    }
+                })
+            })
+        })
+        .collect();
+    for update in updates {
+        update.await.unwrap().unwrap();
+    }
+
+    // Both settings were saved, and the cached config matches the saved one
+    let saved =
+        puffersecuresigner::io::validator_config::ValidatorConfig::read(&bls_pk_hex).unwrap();
+    assert_eq!(saved.fee_recipient, Some(fee_recipient));
+    assert_eq!(saved.gas_limit, Some(30_000_000));
+    assert_eq!(state.validator_configs.get(&bls_pk_hex).unwrap(), saved);
+}
@@ -55,6 +55,44 @@ pub async fn request_list_bls_keys_route(port: u16) -> Result<Response, reqwest:
 
     response
 }
+pub async fn mock_list_public_keys_route() -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/publicKeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_public_keys::handler,
+            ),
+        )
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.get("/api/v1/eth2/publicKeys").await)
+}
+
+pub async fn make_list_public_keys_request(port: Option<u16>) -> Result<(Vec<String>, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let url = format!("http://localhost:{}/api/v1/eth2/publicKeys", p);
+            let resp = Client::new().get(&url).send().await?;
+            let status = resp.status();
+            let keys: Vec<String> = resp
+                .json()
+                .await
+                .with_context(|| format!("Failed to parse to Vec<String>"))?;
+            Ok((keys, status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_list_public_keys_route().await?;
+            let keys: Vec<String> = serde_json::from_slice(resp.as_bytes())
+                .with_context(|| "Failed to parse to Vec<String>")?;
+            Ok((keys, resp.status_code()))
+        }
+    }
+}
+
 pub enum ListRequestKind {
     BLS,
     ETH,
@@ -125,7 +163,10 @@ pub async fn bls_key_exists(bls_pk_hex: &str, port: Option<u16>) -> bool {
 
 /// Verifies the supplied eth_pk_hex is one of the returned keys when querying the Secure-Signer's known eth keys
 pub async fn eth_key_exists(eth_pk_hex: &str, port: Option<u16>) -> bool {
-    let eth_pk_hex: String = strip_0x_prefix!(eth_pk_hex);
+    // Keys are listed in compressed form, keygen returns them uncompressed
+    let pk =
+        puffersecuresigner::crypto::eth_keys::parse_eth_pk_hex(&eth_pk_hex.to_string()).unwrap();
+    let eth_pk_hex = puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(&pk);
     let (keys, status) = make_list_request(ListRequestKind::ETH, port).await.unwrap();
     assert_eq!(status, 200);
     let keys = keys.data;
@@ -137,6 +178,8 @@ pub async fn eth_key_exists(eth_pk_hex: &str, port: Option<u16>) -> bool {
 
 #[tokio::test]
 async fn verify_list_bls_keys_works() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("verify_list_bls_keys_works");
     let port = read_secure_signer_port();
     let (keys, status) = make_list_request(ListRequestKind::BLS, port).await.unwrap();
     assert_eq!(status, 200);
@@ -154,8 +197,27 @@ async fn verify_list_bls_keys_works() {
     assert_eq!(keys.data.len(), num_exist + 2);
 }
 
+#[tokio::test]
+async fn verify_list_bls_keys_emits_prefixed_lowercase_hex() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "verify_list_bls_keys_emits_prefixed_lowercase_hex",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let (keys, status) = make_list_request(ListRequestKind::BLS, port).await.unwrap();
+    assert_eq!(status, 200);
+    assert!(keys.data.iter().any(|k| k.pubkey == bls_pk_hex));
+    assert!(keys
+        .data
+        .iter()
+        .all(|k| k.pubkey.starts_with("0x") && k.pubkey == k.pubkey.to_lowercase()));
+}
+
 #[tokio::test]
 async fn verify_list_eth_keys_works() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("verify_list_eth_keys_works");
     let port = read_secure_signer_port();
     let (keys, status) = make_list_request(ListRequestKind::ETH, port).await.unwrap();
     assert_eq!(status, 200);
@@ -172,3 +234,23 @@ async fn verify_list_eth_keys_works() {
 
     assert_eq!(keys.data.len(), num_exist + 2);
 }
+
+#[tokio::test]
+async fn verify_list_public_keys_works() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("verify_list_public_keys_works");
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let (keys, status) = make_list_public_keys_request(port).await.unwrap();
+    assert_eq!(status, 200);
+    let bls_pk_hex: String = strip_0x_prefix!(bls_pk_hex);
+    assert!(keys.contains(&format!("0x{bls_pk_hex}")));
+    assert!(keys.iter().all(|k| k.starts_with("0x")));
+
+    // No duplicates
+    let mut deduped = keys.clone();
+    deduped.sort();
+    deduped.dedup();
+    assert_eq!(deduped.len(), keys.len());
+}
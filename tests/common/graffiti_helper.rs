@@ -0,0 +1,282 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::read_secure_signer_port;
+use super::signing_helper::make_signing_route_request;
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::types::{GraffitiResponse, SetGraffitiRequest};
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use reqwest::{Client, Method, Response, StatusCode};
+
+fn mock_graffiti_app() -> axum::routing::IntoMakeService<axum::Router> {
+    axum::Router::new()
+        .route(
+            "/eth/v1/validator/:bls_pk_hex/graffiti",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_graffiti::handler,
+            )
+            .post(puffersecuresigner::enclave::shared::handlers::set_graffiti::handler)
+            .delete(puffersecuresigner::enclave::shared::handlers::delete_graffiti::handler),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service()
+}
+
+pub async fn mock_graffiti_route(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+) -> Result<axum_test::TestResponse> {
+    let server = axum_test::TestServer::new(mock_graffiti_app())?;
+    let path = format!("/eth/v1/validator/{}/graffiti", bls_pk_hex);
+    let req = match method {
+        Method::POST => server.post(&path),
+        Method::DELETE => server.delete(&path),
+        _ => server.get(&path),
+    };
+    Ok(match body {
+        Some(body) => req.json(&body).await,
+        None => req.await,
+    })
+}
+
+pub async fn request_graffiti_route(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!(
+        "http://localhost:{}/eth/v1/validator/{}/graffiti",
+        port, bls_pk_hex
+    );
+    let req = client.request(method, &url);
+    match body {
+        Some(body) => req.json(&body).send().await,
+        None => req.send().await,
+    }
+}
+
+/// Returns the raw response body alongside the status, as POST and DELETE answer without a body
+async fn make_graffiti_request(
+    method: Method,
+    bls_pk_hex: &str,
+    body: Option<serde_json::Value>,
+    port: Option<u16>,
+) -> Result<(Vec<u8>, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_graffiti_route(method, bls_pk_hex, body, p).await?;
+            let status = resp.status();
+            let bytes = resp
+                .bytes()
+                .await
+                .with_context(|| "Failed to read graffiti response")?;
+            Ok((bytes.to_vec(), status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_graffiti_route(method, bls_pk_hex, body).await?;
+            Ok((resp.as_bytes().to_vec(), resp.status_code()))
+        }
+    }
+}
+
+pub async fn make_get_graffiti_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<(Option<GraffitiResponse>, StatusCode)> {
+    let (bytes, status) = make_graffiti_request(Method::GET, bls_pk_hex, None, port).await?;
+    Ok((serde_json::from_slice(&bytes).ok(), status))
+}
+
+pub async fn make_set_graffiti_request(
+    bls_pk_hex: &str,
+    graffiti: &str,
+    port: Option<u16>,
+) -> Result<StatusCode> {
+    let req = SetGraffitiRequest {
+        graffiti: graffiti.to_string(),
+    };
+    let (_, status) = make_graffiti_request(
+        Method::POST,
+        bls_pk_hex,
+        Some(serde_json::to_value(req)?),
+        port,
+    )
+    .await?;
+    Ok(status)
+}
+
+pub async fn make_delete_graffiti_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<StatusCode> {
+    let (_, status) = make_graffiti_request(Method::DELETE, bls_pk_hex, None, port).await?;
+    Ok(status)
+}
+
+fn block_request(slot: u64) -> BLSSignMsg {
+    let req = crate::signing_tests::block::mock_propose_block_request(slot);
+    serde_json::from_str(&req).unwrap()
+}
+
+/// A full ALTAIR BLOCK_V2 request whose body carries the all-zero graffiti
+fn full_block_v2_request(slot: u64) -> BLSSignMsg {
+    let req = format!(
+        r#"{{
+            "type": "BLOCK_V2",
+            "fork_info": {{
+                "fork": {{
+                    "previous_version": "0x00000001",
+                    "current_version": "0x00000001",
+                    "epoch": "0"
+                }},
+                "genesis_validators_root": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
+            }},
+            "beacon_block": {{
+                "version": "ALTAIR",
+                "block": {{
+                    "slot": "{slot}",
+                    "proposer_index": "3",
+                    "parent_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "state_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "body": {{
+                        "randao_reveal": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                        "eth1_data": {{
+                            "deposit_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                            "deposit_count": "0",
+                            "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                        }},
+                        "graffiti": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "proposer_slashings": [],
+                        "attester_slashings": [],
+                        "attestations": [],
+                        "deposits": [],
+                        "voluntary_exits": [],
+                        "sync_aggregate": {{
+                            "sync_committee_bits": "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                            "sync_committee_signature": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                        }}
+                    }}
+                }}
+            }}
+        }}"#
+    );
+    serde_json::from_str(&req).unwrap()
+}
+
+#[tokio::test]
+async fn test_set_get_delete_graffiti() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_set_get_delete_graffiti");
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let (_, status) = make_get_graffiti_request(&bls_pk_hex, port).await.unwrap();
+    assert_eq!(status, 404);
+
+    let status = make_set_graffiti_request(&bls_pk_hex, "puffer", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+
+    let (resp, status) = make_get_graffiti_request(&bls_pk_hex, port).await.unwrap();
+    assert_eq!(status, 200);
+    let resp = resp.unwrap();
+    assert_eq!(resp.data.pubkey, format!("0x{}", bls_pk_hex));
+    assert_eq!(resp.data.graffiti, "puffer");
+
+    let status = make_delete_graffiti_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 204);
+
+    let (_, status) = make_get_graffiti_request(&bls_pk_hex, port).await.unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_set_graffiti_rejects_bad_input() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_set_graffiti_rejects_bad_input",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let status = make_set_graffiti_request(&bls_pk_hex, &"a".repeat(33), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let status = make_set_graffiti_request(&unknown_pk_hex, "puffer", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_pinned_graffiti_enforced_on_block() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_pinned_graffiti_enforced_on_block",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // The mock BLOCK carries "teku/v0.12.10-dev-c81ca625"
+    let status = make_set_graffiti_request(&bls_pk_hex, "puffer", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) = make_signing_route_request(block_request(1234), &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 412);
+
+    let status = make_set_graffiti_request(&bls_pk_hex, "teku/v0.12.10-dev-c81ca625", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) = make_signing_route_request(block_request(1234), &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_pinned_graffiti_enforced_on_full_block_v2() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_pinned_graffiti_enforced_on_full_block_v2",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let status = make_set_graffiti_request(&bls_pk_hex, "puffer", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) = make_signing_route_request(full_block_v2_request(7), &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 412);
+
+    // Headers do not expose the graffiti so they are signed as is
+    let req = crate::signing_tests::block_v2::mock_propose_block_v2_request(8);
+    let req: BLSSignMsg = serde_json::from_str(&req).unwrap();
+    let (_, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    // The empty graffiti matches the all-zero body field
+    let status = make_set_graffiti_request(&bls_pk_hex, "", port)
+        .await
+        .unwrap();
+    assert_eq!(status, 202);
+    let (_, status) = make_signing_route_request(full_block_v2_request(9), &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,369 @@
+use super::read_secure_signer_port;
+
+use anyhow::Result;
+use reqwest::{Client, StatusCode};
+
+pub async fn mock_upcheck_route() -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/upcheck",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
+        )
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.get("/upcheck").await)
+}
+
+/// Returns the status, content type, and body of an /upcheck request
+pub async fn make_upcheck_request(port: Option<u16>) -> Result<(StatusCode, String, String)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let url = format!("http://localhost:{}/upcheck", p);
+            let resp = Client::new().get(&url).send().await?;
+            let status = resp.status();
+            let content_type = resp
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap_or_default().to_string())
+                .unwrap_or_default();
+            Ok((status, content_type, resp.text().await?))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_upcheck_route().await?;
+            let content_type = resp
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap_or_default().to_string())
+                .unwrap_or_default();
+            Ok((resp.status_code(), content_type, resp.text()))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_upcheck_returns_ok() {
+    let port = read_secure_signer_port();
+    let (status, content_type, body) = make_upcheck_request(port).await.unwrap();
+    assert_eq!(status, 200);
+    assert!(content_type.starts_with("text/plain"));
+    assert_eq!(body, "OK");
+}
+
+pub async fn mock_healthcheck_route(
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/healthcheck",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::healthcheck::handler),
+        )
+        .with_state(state)
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.get("/healthcheck").await)
+}
+
+#[tokio::test]
+async fn test_healthcheck_reports_network() {
+    let preset = puffersecuresigner::eth2::eth_types::NetworkPreset::load("holesky", None).unwrap();
+    let mut state = super::signing_helper::mock_app_state();
+    state.network = Some(preset.name.clone());
+    state.genesis_fork_version = preset.genesis_fork_version;
+    state.genesis_validators_root = preset.genesis_validators_root;
+    state.slot_clock = Some(puffersecuresigner::eth2::slash_protection::SlotClock {
+        genesis_time: preset.genesis_time,
+        seconds_per_slot: preset.seconds_per_slot,
+        max_future_slots: puffersecuresigner::constants::DEFAULT_MAX_FUTURE_SLOTS,
+    });
+
+    let resp = mock_healthcheck_route(state).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let health: puffersecuresigner::enclave::types::HealthcheckResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(health.status, "UP");
+    assert_eq!(health.network, Some("holesky".to_string()));
+    assert_eq!(health.genesis_fork_version, "0x01017000");
+    assert_eq!(
+        health.genesis_validators_root,
+        "0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"
+    );
+    assert_eq!(health.genesis_time, Some("1695902400".to_string()));
+    assert_eq!(health.seconds_per_slot, Some("12".to_string()));
+}
+
+#[tokio::test]
+async fn test_healthcheck_reports_key_quota() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_healthcheck_reports_key_quota");
+    super::bls_keygen_helper::register_new_bls_key(None).await;
+    super::eth_keygen_helper::register_new_eth_key(None).await;
+
+    let resp = mock_healthcheck_route(super::signing_helper::mock_app_state())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let health: puffersecuresigner::enclave::types::HealthcheckResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(health.bls_keys >= 1);
+    assert!(health.eth_keys >= 1);
+    assert_eq!(
+        health.max_keys,
+        puffersecuresigner::constants::DEFAULT_MAX_KEYS
+    );
+    let origins = health.bls_key_origins;
+    assert_eq!(
+        origins.generated + origins.derived + origins.imported + origins.unknown,
+        health.bls_keys
+    );
+    assert!(origins.generated >= 1);
+    assert_eq!(health.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn test_healthcheck_degrades_when_data_dir_is_unwritable() {
+    use puffersecuresigner::enclave::shared::handlers::healthcheck::report;
+
+    let data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_healthcheck_degrades_when_data_dir_is_unwritable",
+    );
+    let mut state = super::signing_helper::mock_app_state();
+    assert_eq!(state.data_dir, data_dir.path());
+    let health = report(&state);
+    assert_eq!(health.status, "UP");
+    assert!(health.data_dir_writable);
+    assert!(health.slashing_protection_intact);
+
+    // Nothing can be created below a regular file, even as root
+    let file = data_dir.path().join("not-a-dir");
+    std::fs::write(&file, b"").unwrap();
+    state.data_dir = file.join("data");
+    let health = report(&state);
+    assert_eq!(health.status, "DEGRADED");
+    assert!(!health.data_dir_writable);
+
+    state.data_dir = data_dir.path().to_path_buf();
+    state.slashing_protection_intact = false;
+    let health = report(&state);
+    assert_eq!(health.status, "DEGRADED");
+    assert!(health.data_dir_writable);
+    assert!(!health.slashing_protection_intact);
+}
+
+/// A certificate for 127.0.0.1 issued by `issuer`, or self-signed, and its key
+fn issue_cert(
+    common_name: &str,
+    issuer: Option<&(
+        openssl::x509::X509,
+        openssl::pkey::PKey<openssl::pkey::Private>,
+    )>,
+) -> (
+    openssl::x509::X509,
+    openssl::pkey::PKey<openssl::pkey::Private>,
+) {
+    let key = openssl::pkey::PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+    name.append_entry_by_nid(openssl::nid::Nid::COMMONNAME, common_name)
+        .unwrap();
+    let name = name.build();
+    let mut builder = openssl::x509::X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = openssl::bn::BigNum::from_u32(rand::random::<u32>())
+        .unwrap()
+        .to_asn1_integer()
+        .unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    let not_before = openssl::asn1::Asn1Time::days_from_now(0).unwrap();
+    let not_after = openssl::asn1::Asn1Time::days_from_now(1).unwrap();
+    builder.set_not_before(&not_before).unwrap();
+    builder.set_not_after(&not_after).unwrap();
+    let san = openssl::x509::extension::SubjectAlternativeName::new()
+        .ip("127.0.0.1")
+        .build(&builder.x509v3_context(issuer.map(|(x509, _)| x509.as_ref()), None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    match issuer {
+        Some((issuer_x509, issuer_key)) => {
+            builder.set_issuer_name(issuer_x509.subject_name()).unwrap();
+            builder
+                .sign(issuer_key, openssl::hash::MessageDigest::sha256())
+                .unwrap();
+        }
+        None => {
+            builder.set_issuer_name(&name).unwrap();
+            let ca = openssl::x509::extension::BasicConstraints::new()
+                .critical()
+                .ca()
+                .build()
+                .unwrap();
+            builder.append_extension(ca).unwrap();
+            builder
+                .sign(&key, openssl::hash::MessageDigest::sha256())
+                .unwrap();
+        }
+    }
+    (builder.build(), key)
+}
+
+/// A self-signed certificate for 127.0.0.1 and its key, as PEMs
+fn self_signed_cert() -> (Vec<u8>, Vec<u8>) {
+    let (cert, key) = issue_cert("secure-signer", None);
+    (
+        cert.to_pem().unwrap(),
+        key.private_key_to_pem_pkcs8().unwrap(),
+    )
+}
+
+/// Serves /upcheck over TLS on a local port, returning the port
+fn spawn_tls_upcheck(config: axum_server::tls_openssl::OpenSSLConfig) -> u16 {
+    let app = axum::Router::new().route(
+        "/upcheck",
+        axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
+    );
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(puffersecuresigner::enclave::shared::tls::serve_tls(
+        listener, config, app,
+    ));
+    port
+}
+
+#[tokio::test]
+async fn test_upcheck_over_tls() {
+    let (cert_pem, key_pem) = self_signed_cert();
+    let dir = std::env::temp_dir();
+    let cert_path = dir.join("test_upcheck_over_tls.crt");
+    let key_path = dir.join("test_upcheck_over_tls.key");
+    std::fs::write(&cert_path, &cert_pem).unwrap();
+    std::fs::write(&key_path, &key_pem).unwrap();
+    let cert_path = cert_path.to_str().unwrap();
+    let key_path = key_path.to_str().unwrap();
+
+    // Malformed or mismatched PEMs fail at startup
+    let e = puffersecuresigner::enclave::shared::tls::load_tls_config(key_path, key_path, None)
+        .unwrap_err();
+    assert!(format!("{e:#}").contains("TLS certificate"));
+    let e = puffersecuresigner::enclave::shared::tls::load_tls_config(cert_path, cert_path, None)
+        .unwrap_err();
+    assert!(format!("{e:#}").contains("TLS key"));
+    let (_, other_key_pem) = self_signed_cert();
+    let other_key_path = dir.join("test_upcheck_over_tls_other.key");
+    std::fs::write(&other_key_path, &other_key_pem).unwrap();
+    let other_key_path = other_key_path.to_str().unwrap();
+    assert!(puffersecuresigner::enclave::shared::tls::load_tls_config(
+        cert_path,
+        other_key_path,
+        None
+    )
+    .is_err());
+
+    let config =
+        puffersecuresigner::enclave::shared::tls::load_tls_config(cert_path, key_path, None)
+            .unwrap();
+    let port = spawn_tls_upcheck(config);
+
+    let client = Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(&cert_pem).unwrap())
+        .build()
+        .unwrap();
+    let resp = client
+        .get(format!("https://127.0.0.1:{port}/upcheck"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().await.unwrap(), "OK");
+
+    // Cleartext requests are not served
+    assert!(Client::new()
+        .get(format!("http://127.0.0.1:{port}/upcheck"))
+        .send()
+        .await
+        .map(|resp| resp.status() != 200)
+        .unwrap_or(true));
+
+    for path in [cert_path, key_path, other_key_path] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_upcheck_requires_client_cert() {
+    let (server_cert_pem, server_key_pem) = self_signed_cert();
+    let client_ca = issue_cert("validator client CA", None);
+    let (client_cert, client_key) = issue_cert("validator client", Some(&client_ca));
+    let rogue_ca = issue_cert("validator client CA", None);
+    let (rogue_cert, rogue_key) = issue_cert("validator client", Some(&rogue_ca));
+
+    let dir = std::env::temp_dir();
+    let cert_path = dir.join("test_upcheck_requires_client_cert.crt");
+    let key_path = dir.join("test_upcheck_requires_client_cert.key");
+    let client_ca_path = dir.join("test_upcheck_requires_client_cert_ca.crt");
+    std::fs::write(&cert_path, &server_cert_pem).unwrap();
+    std::fs::write(&key_path, &server_key_pem).unwrap();
+    std::fs::write(&client_ca_path, client_ca.0.to_pem().unwrap()).unwrap();
+    let (cert_path, key_path, client_ca_path) = (
+        cert_path.to_str().unwrap(),
+        key_path.to_str().unwrap(),
+        client_ca_path.to_str().unwrap(),
+    );
+
+    // A client CA file without certificates is refused at startup
+    assert!(puffersecuresigner::enclave::shared::tls::load_tls_config(
+        cert_path,
+        key_path,
+        Some(key_path)
+    )
+    .is_err());
+    let config = puffersecuresigner::enclave::shared::tls::load_tls_config(
+        cert_path,
+        key_path,
+        Some(client_ca_path),
+    )
+    .unwrap();
+    let port = spawn_tls_upcheck(config);
+
+    let upcheck = |identity: Option<(
+        &openssl::x509::X509,
+        &openssl::pkey::PKey<openssl::pkey::Private>,
+    )>| {
+        let mut client = Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(&server_cert_pem).unwrap());
+        if let Some((cert, key)) = identity {
+            client = client.identity(
+                reqwest::Identity::from_pkcs8_pem(
+                    &cert.to_pem().unwrap(),
+                    &key.private_key_to_pem_pkcs8().unwrap(),
+                )
+                .unwrap(),
+            );
+        }
+        let client = client.build().unwrap();
+        async move {
+            client
+                .get(format!("https://127.0.0.1:{port}/upcheck"))
+                .send()
+                .await
+                .map(|resp| resp.status())
+        }
+    };
+
+    assert_eq!(
+        upcheck(Some((&client_cert, &client_key))).await.unwrap(),
+        200
+    );
+    // Clients without a certificate, or with one from another CA, fail the handshake
+    assert!(upcheck(None).await.is_err());
+    assert!(upcheck(Some((&rogue_cert, &rogue_key))).await.is_err());
+
+    for path in [cert_path, key_path, client_ca_path] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,473 @@
+use super::read_secure_signer_port;
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::types::{
+    ImportKeystoreStatus, ImportKeystoresRequest, ImportKeystoresResponse,
+};
+use reqwest::{Client, Response, StatusCode};
+
+/// EIP-2335 pbkdf2 test vector: https://eips.ethereum.org/EIPS/eip-2335
+pub const PBKDF2_KEYSTORE: &str = r#"{"crypto":{"kdf":{"function":"pbkdf2","params":{"dklen":32,"c":262144,"prf":"hmac-sha256","salt":"d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"},"message":""},"checksum":{"function":"sha256","params":{},"message":"8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"},"cipher":{"function":"aes-128-ctr","params":{"iv":"264daa3f303d7259501c93d997d84fe6"},"message":"cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"}},"description":"This is a test keystore that uses PBKDF2 to secure the secret.","pubkey":"9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07","path":"m/12381/60/0/0","uuid":"64625def-3331-4eea-ab6f-782f3ed16a83","version":4}"#;
+pub const PBKDF2_KEYSTORE_PASSWORD: &str = "testpassword\u{1f511}";
+pub const PBKDF2_KEYSTORE_PUBKEY: &str = "0x9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+
+pub async fn mock_import_bls_keystores_route(
+    req: &ImportKeystoresRequest,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::import_bls_keystores::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/keystores").json(req).await)
+}
+
+pub async fn request_import_bls_keystores_route(
+    req: &ImportKeystoresRequest,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!("http://localhost:{}/eth/v1/keystores", port);
+    let response = client.post(&url).json(req).send().await;
+
+    response
+}
+
+pub async fn make_import_bls_keystores_request(
+    req: ImportKeystoresRequest,
+    port: Option<u16>,
+) -> Result<(ImportKeystoresResponse, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_import_bls_keystores_route(&req, p).await?;
+            let status = resp.status();
+            let resp: ImportKeystoresResponse = resp
+                .json()
+                .await
+                .with_context(|| format!("Failed to parse to ImportKeystoresResponse"))?;
+            Ok((resp, status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_import_bls_keystores_route(&req).await?;
+            let status = resp.status_code();
+            let resp: ImportKeystoresResponse = serde_json::from_slice(resp.as_bytes())
+                .with_context(|| "Failed to parse to ImportKeystoresResponse")?;
+            Ok((resp, status))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_import_bls_keystores_reports_status_per_keystore() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_import_bls_keystores_reports_status_per_keystore",
+    );
+    let port = read_secure_signer_port();
+    // Start from a clean slate in case a previous run imported the test vector
+    super::delete_keys_helper::make_delete_bls_keys_request(
+        vec![PBKDF2_KEYSTORE_PUBKEY.to_string()],
+        port,
+    )
+    .await
+    .unwrap();
+
+    // The history was produced by the signer this key is migrating from
+    let interchange = format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format_version": "5",
+                "genesis_validators_root": "0x0000000000000000000000000000000000000000000000000000000000000000"
+            }},
+            "data": [{{
+                "pubkey": "{PBKDF2_KEYSTORE_PUBKEY}",
+                "signed_blocks": [{{ "slot": "81952" }}],
+                "signed_attestations": []
+            }}]
+        }}"#
+    );
+    let req = ImportKeystoresRequest {
+        keystores: vec![PBKDF2_KEYSTORE.to_string(), PBKDF2_KEYSTORE.to_string()],
+        passwords: vec![
+            "wrongpassword".to_string(),
+            PBKDF2_KEYSTORE_PASSWORD.to_string(),
+        ],
+        encrypting_pk_hex: None,
+        slashing_protection: Some(interchange),
+        labels: None,
+        evidence: None,
+    };
+    let (resp, status) = make_import_bls_keystores_request(req.clone(), port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data.len(), 2);
+    assert_eq!(resp.data[0].status, ImportKeystoreStatus::Error);
+    assert_eq!(resp.data[1].status, ImportKeystoreStatus::Imported);
+    assert!(super::getter_routes_helper::bls_key_exists(PBKDF2_KEYSTORE_PUBKEY, port).await);
+
+    // Importing the same keystore again is reported as a duplicate
+    let (resp, status) = make_import_bls_keystores_request(req, port).await.unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data[1].status, ImportKeystoreStatus::Duplicate);
+
+    // The first sign request must respect the imported watermark despite no local signing
+    for slot in [81951, 81952] {
+        let req = crate::signing_tests::block::mock_propose_block_request(slot);
+        let (_, status) = super::signing_helper::make_signing_route_request(
+            serde_json::from_str(&req).unwrap(),
+            &PBKDF2_KEYSTORE_PUBKEY.to_string(),
+            port,
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, 412);
+    }
+
+    super::delete_keys_helper::make_delete_bls_keys_request(
+        vec![PBKDF2_KEYSTORE_PUBKEY.to_string()],
+        port,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_import_bls_keystores_rejects_mismatched_passwords() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_import_bls_keystores_rejects_mismatched_passwords",
+    );
+    let port = read_secure_signer_port();
+    let req = ImportKeystoresRequest {
+        keystores: vec![PBKDF2_KEYSTORE.to_string()],
+        passwords: vec![],
+        encrypting_pk_hex: None,
+        slashing_protection: None,
+        labels: None,
+        evidence: None,
+    };
+    match port {
+        Some(p) => {
+            let resp = request_import_bls_keystores_route(&req, p).await.unwrap();
+            assert_eq!(resp.status(), 400);
+        }
+        None => {
+            let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+            assert_eq!(resp.status_code(), 400);
+        }
+    }
+}
+
+pub async fn mock_export_bls_keystores_route(
+    req: &puffersecuresigner::enclave::types::ExportKeystoresRequest,
+) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores/export",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::export_bls_keystores::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/keystores/export").json(req).await)
+}
+
+#[tokio::test]
+async fn test_export_bls_keystores_needs_attested_encrypting_keys() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_export_bls_keystores_needs_attested_encrypting_keys",
+    );
+    let bls_pk_hex = super::bls_keygen_helper::register_new_bls_key(None)
+        .await
+        .pk_hex;
+    let (_, eth_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let mut export = puffersecuresigner::enclave::types::ExportKeystoresRequest {
+        pubkeys: vec![bls_pk_hex.clone()],
+        encrypting_pk_hex: puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(&eth_pk),
+        evidence: unsigned_evidence(&[0x33; 32]),
+        attest: false,
+    };
+
+    // Evidence from a build no policy allows is refused, naming its measurement
+    let resp = mock_export_bls_keystores_route(&export).await.unwrap();
+    assert_eq!(resp.status_code(), 403);
+    assert!(String::from_utf8_lossy(resp.as_bytes()).contains(&hex::encode([0x33; 32])));
+
+    // Keys are only exported to valid ETH keys
+    export.encrypting_pk_hex = "0xdeadbeef".to_string();
+    let resp = mock_export_bls_keystores_route(&export).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    puffersecuresigner::io::key_management::delete_bls_key(&bls_pk_hex).unwrap();
+}
+
+/// Moves a key to another enclave of the build `mrenclave`, which the loaded measurement policy
+/// must allow, and checks it signs there as it did here
+#[cfg(feature = "mock-attestation")]
+async fn export_bls_keystores_to_another_enclave(mrenclave: &[u8; 32]) {
+    let randao_reveal = || {
+        let req = crate::signing_tests::randao_reveal::mock_randao_reveal_request();
+        puffersecuresigner::eth2::eth_signing::BLSSignMsg::RANDAO_REVEAL(
+            serde_json::from_str(&req).unwrap(),
+        )
+    };
+
+    // Enclave A generates a key and signs with it
+    let bls_pk_hex = super::bls_keygen_helper::register_new_bls_key(None)
+        .await
+        .pk_hex;
+    let resp = super::signing_helper::mock_secure_sign_route(&bls_pk_hex, randao_reveal())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let sig_a: puffersecuresigner::enclave::types::SignatureResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // Enclave B's attested ETH key
+    let (eth_sk, eth_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    puffersecuresigner::crypto::eth_keys::save_eth_key(eth_sk, eth_pk).unwrap();
+    let mut export = puffersecuresigner::enclave::types::ExportKeystoresRequest {
+        pubkeys: vec![bls_pk_hex.clone()],
+        encrypting_pk_hex: puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(&eth_pk),
+        evidence: puffersecuresigner::io::remote_attestation::mock_ias_evidence(
+            mrenclave,
+            &eth_pk.serialize_compressed(),
+        )
+        .unwrap(),
+        attest: false,
+    };
+
+    // Enclave builds outside of the policy are refused
+    let mut bad_export = export.clone();
+    bad_export.evidence = puffersecuresigner::io::remote_attestation::mock_ias_evidence(
+        &[0x33; 32],
+        &eth_pk.serialize_compressed(),
+    )
+    .unwrap();
+    let resp = mock_export_bls_keystores_route(&bad_export).await.unwrap();
+    assert_eq!(resp.status_code(), 403);
+
+    // The evidence must be over the key the passwords are encrypted to
+    let (_, other_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let mut bad_export = export.clone();
+    bad_export.encrypting_pk_hex = puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(&other_pk);
+    let resp = mock_export_bls_keystores_route(&bad_export).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    // Unknown keys are not exported
+    export.pubkeys.push(format!("0x{}", "ab".repeat(48)));
+    let resp = mock_export_bls_keystores_route(&export).await.unwrap();
+    assert_eq!(resp.status_code(), 404);
+    export.pubkeys.pop();
+
+    let resp = mock_export_bls_keystores_route(&export).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let import: ImportKeystoresRequest = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(import.keystores.len(), 1);
+    assert!(import.slashing_protection.is_some());
+
+    // Both enclaves share this process's data directory, so remove the key from A before B
+    // imports it
+    puffersecuresigner::io::key_management::delete_bls_key(&bls_pk_hex).unwrap();
+    let (resp, status) = make_import_bls_keystores_request(import, None)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data[0].status, ImportKeystoreStatus::Imported);
+
+    // Enclave B signs with the same key
+    let resp = super::signing_helper::mock_secure_sign_route(&bls_pk_hex, randao_reveal())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let sig_b: puffersecuresigner::enclave::types::SignatureResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(sig_a.signature, sig_b.signature);
+}
+
+#[tokio::test]
+async fn test_imported_keys_list_their_metadata() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_imported_keys_list_their_metadata",
+    );
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    let bls_pk_hex = format!("0x{}", sk.public_keys().public_key().to_hex());
+    let keystore =
+        puffersecuresigner::crypto::keystore::encrypt_bls_keystore(&sk, "password", "").unwrap();
+
+    // Labels must line up with the keystores
+    let mut req = ImportKeystoresRequest {
+        keystores: vec![keystore],
+        passwords: vec!["password".to_string()],
+        encrypting_pk_hex: None,
+        slashing_protection: None,
+        labels: Some(vec![]),
+        evidence: None,
+    };
+    let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    req.labels = Some(vec!["node-1".to_string()]);
+    let (resp, status) = make_import_bls_keystores_request(req, None).await.unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data[0].status, ImportKeystoreStatus::Imported);
+
+    let listed_metadata = || async {
+        let (keys, status) = super::getter_routes_helper::make_list_request(
+            super::getter_routes_helper::ListRequestKind::BLS,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, 200);
+        keys.data
+            .into_iter()
+            .find(|k| k.pubkey == bls_pk_hex)
+            .unwrap()
+            .metadata
+            .unwrap()
+    };
+    let metadata = listed_metadata().await;
+    assert_eq!(
+        metadata.origin,
+        puffersecuresigner::io::key_metadata::KeyOrigin::Imported
+    );
+    assert_eq!(metadata.label, Some("node-1".to_string()));
+    assert!(metadata.created_at > 0);
+    assert_eq!(metadata.last_used, None);
+
+    // Signing records when the key was last used
+    let req = crate::signing_tests::randao_reveal::mock_randao_reveal_request();
+    let resp = super::signing_helper::mock_secure_sign_route(
+        &bls_pk_hex,
+        puffersecuresigner::eth2::eth_signing::BLSSignMsg::RANDAO_REVEAL(
+            serde_json::from_str(&req).unwrap(),
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let metadata = listed_metadata().await;
+    assert!(metadata.last_used.unwrap() >= metadata.created_at);
+
+    puffersecuresigner::crypto::bls_keys::delete_bls_key(&bls_pk_hex).unwrap();
+}
+
+/// EPID evidence claiming `mrenclave`, with a quote body IAS never signed
+fn unsigned_evidence(
+    mrenclave: &[u8; 32],
+) -> puffersecuresigner::io::remote_attestation::AttestationEvidence {
+    let mut body = [0_u8; 432];
+    body[112..144].copy_from_slice(mrenclave);
+    let report = serde_json::json!({
+        "id": "0",
+        "timestamp": "2023-01-20T19:47:28.465440",
+        "version": 4,
+        "epidPseudonym": "",
+        "advisoryURL": "",
+        "advisoryIDs": [],
+        "isvEnclaveQuoteStatus": "OK",
+        "isvEnclaveQuoteBody": openssl::base64::encode_block(&body),
+    });
+    puffersecuresigner::io::remote_attestation::AttestationEvidence {
+        raw_report: report.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_import_bls_keystores_enforces_measurement_policy() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_import_bls_keystores_enforces_measurement_policy",
+    );
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/measurement-policy/reload",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::reload_measurement_policy::handler,
+            ),
+        )
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+
+    let blessed = [0x11_u8; 32];
+    let other = [0x22_u8; 32];
+    let mut req = ImportKeystoresRequest {
+        keystores: vec![PBKDF2_KEYSTORE.to_string()],
+        passwords: vec![PBKDF2_KEYSTORE_PASSWORD.to_string()],
+        encrypting_pk_hex: None,
+        slashing_protection: None,
+        labels: None,
+        evidence: Some(unsigned_evidence(&other)),
+    };
+
+    // Without a policy no sender is allowed
+    let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 403);
+    assert_eq!(
+        server
+            .post("/eth/v1/measurement-policy/reload")
+            .await
+            .status_code(),
+        500
+    );
+
+    let path = std::env::temp_dir().join("test_measurement_policy.json");
+    let write_policy = |mrenclave: &[u8; 32]| {
+        let policy = serde_json::json!({ "mrenclaves": [format!("0x{}", hex::encode(mrenclave))] });
+        std::fs::write(&path, policy.to_string()).unwrap();
+    };
+    write_policy(&blessed);
+    puffersecuresigner::io::remote_attestation::set_measurement_policy_path(path.to_str().unwrap())
+        .unwrap();
+
+    // Senders outside of the policy are named in the refusal
+    let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 403);
+    assert!(String::from_utf8_lossy(resp.as_bytes()).contains(&hex::encode(other)));
+
+    // A reloaded policy applies to the next import
+    write_policy(&other);
+    let resp = server.post("/eth/v1/measurement-policy/reload").await;
+    assert_eq!(resp.status_code(), 200);
+    let policy: puffersecuresigner::io::remote_attestation::MeasurementPolicy =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(policy.mrenclaves, vec![hex::encode(other)]);
+
+    // An allowed measurement still needs evidence signed by IAS
+    let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+    req.evidence = Some(unsigned_evidence(&blessed));
+    let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 403);
+
+    // A broken policy file keeps the loaded policy
+    std::fs::write(&path, "{").unwrap();
+    assert_eq!(
+        server
+            .post("/eth/v1/measurement-policy/reload")
+            .await
+            .status_code(),
+        500
+    );
+    req.evidence = Some(unsigned_evidence(&other));
+    let resp = mock_import_bls_keystores_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+    std::fs::remove_file(&path).unwrap();
+
+    // Keys are only exported to enclaves the policy allows, so this is where they can move
+    #[cfg(feature = "mock-attestation")]
+    export_bls_keystores_to_another_enclave(&other).await;
+}
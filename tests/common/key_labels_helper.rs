@@ -0,0 +1,137 @@
+use anyhow::Result;
+use puffersecuresigner::enclave::types::{KeyGenRequest, KeyGenResponse, ListKeysResponse};
+use puffersecuresigner::io::key_metadata::KeyMetadata;
+
+pub fn mock_key_labels_app() -> Result<axum_test::TestServer> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/keygen/secp256k1",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::eth_keygen::handler,
+            )
+            .get(puffersecuresigner::enclave::shared::handlers::list_eth_keys::handler),
+        )
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/keystores/:pubkey",
+            axum::routing::patch(
+                puffersecuresigner::enclave::secure_signer::handlers::update_key_label::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    axum_test::TestServer::new(test_app)
+}
+
+fn listed(resp: &axum_test::TestResponse) -> Vec<String> {
+    let keys: ListKeysResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    keys.data.into_iter().map(|k| k.pubkey).collect()
+}
+
+#[tokio::test]
+async fn test_keys_are_listed_by_label() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_keys_are_listed_by_label");
+    let server = mock_key_labels_app().unwrap();
+    let label = format!("customer-{}", rand::random::<u32>());
+    let other = format!("testnet-{}", rand::random::<u32>());
+
+    let resp = server
+        .post("/eth/v1/keygen/bls")
+        .json(&KeyGenRequest {
+            label: Some(label.clone()),
+        })
+        .await;
+    assert_eq!(resp.status_code(), 201);
+    let labeled: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // Keys generated without a body stay unlabeled
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+    let unlabeled: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    let resp = server
+        .get(&format!("/eth/v1/keystores?label={label}"))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(listed(&resp), vec![labeled.pk_hex.clone()]);
+    let all = listed(&server.get("/eth/v1/keystores").await);
+    assert!(all.contains(&labeled.pk_hex) && all.contains(&unlabeled.pk_hex));
+
+    // Relabeling moves the key between filters
+    let resp = server
+        .patch(&format!("/eth/v1/keystores/{}", unlabeled.pk_hex))
+        .json(&serde_json::json!({ "label": label }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let metadata: KeyMetadata = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(metadata.label.as_ref(), Some(&label));
+    let resp = server
+        .patch(&format!("/eth/v1/keystores/{}", labeled.pk_hex))
+        .json(&serde_json::json!({ "label": other }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp = server
+        .get(&format!("/eth/v1/keystores?label={label}"))
+        .await;
+    assert_eq!(listed(&resp), vec![unlabeled.pk_hex.clone()]);
+
+    // ETH keys are labeled and filtered the same way
+    let resp = server
+        .post("/eth/v1/keygen/secp256k1")
+        .json(&KeyGenRequest {
+            label: Some(other.clone()),
+        })
+        .await;
+    assert_eq!(resp.status_code(), 201);
+    let eth_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let pk = puffersecuresigner::crypto::eth_keys::parse_eth_pk_hex(&eth_key.pk_hex).unwrap();
+    let resp = server
+        .get(&format!("/eth/v1/keygen/secp256k1?label={other}"))
+        .await;
+    assert_eq!(
+        listed(&resp),
+        vec![format!(
+            "0x{}",
+            puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(&pk)
+        )]
+    );
+
+    // Malformed labels are refused everywhere
+    let resp = server
+        .post("/eth/v1/keygen/bls")
+        .json(&KeyGenRequest {
+            label: Some("not a label!".to_string()),
+        })
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    let resp = server.get("/eth/v1/keystores?label=not%20a%20label").await;
+    assert_eq!(resp.status_code(), 400);
+    let resp = server
+        .patch(&format!("/eth/v1/keystores/{}", labeled.pk_hex))
+        .json(&serde_json::json!({ "label": "a".repeat(65) }))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+
+    let unknown = puffersecuresigner::crypto::bls_keys::new_bls_key(0)
+        .public_keys()
+        .public_key()
+        .to_hex();
+    let resp = server
+        .patch(&format!("/eth/v1/keystores/0x{unknown}"))
+        .json(&serde_json::json!({ "label": null }))
+        .await;
+    assert_eq!(resp.status_code(), 404);
+}
@@ -0,0 +1,371 @@
+use puffersecuresigner::eth2::eth_signing::{BLSSignMsg, BlockRequest};
+use puffersecuresigner::eth2::slash_protection::{FileBackend, SlashingProtectionBackend};
+use puffersecuresigner::io::threshold_keys::{ThresholdKey, ThresholdShare};
+use std::time::Duration;
+
+fn block_request(slot: u64) -> BLSSignMsg {
+    let req: BlockRequest = serde_json::from_str(
+        &crate::signing_tests::block::mock_propose_block_request(slot),
+    )
+    .unwrap();
+    BLSSignMsg::BLOCK(req)
+}
+
+/// Serves `app` on a local port until the test ends
+pub fn spawn_server(app: axum::Router) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(puffersecuresigner::enclave::shared::shutdown::serve_until(
+        listener,
+        app,
+        None,
+        std::future::pending(),
+        Duration::from_secs(1),
+    ));
+    format!("http://127.0.0.1:{port}")
+}
+
+/// A worker enclave, serving the sign route for the key share it holds, its watermarks and its
+/// status
+pub fn spawn_worker() -> String {
+    spawn_server(
+        axum::Router::new()
+            .route(
+                "/worker/v1/status",
+                axum::routing::get(
+                    puffersecuresigner::enclave::secure_signer::handlers::worker_status::handler,
+                ),
+            )
+            .route(
+                "/worker/v1/watermark/:pk_share_hex",
+                axum::routing::get(
+                    puffersecuresigner::enclave::secure_signer::handlers::worker_watermark::handler,
+                ),
+            )
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .with_state(super::signing_helper::mock_app_state()),
+    )
+}
+
+/// The url of a port nothing listens on
+pub fn dead_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port())
+}
+
+/// A worker that never answers
+fn spawn_hung_worker() -> String {
+    spawn_server(axum::Router::new().route(
+        "/api/v1/eth2/sign/:bls_pk_hex",
+        axum::routing::post(|| async { std::future::pending::<()>().await }),
+    ))
+}
+
+/// Splits a fresh key 2-of-3, saving each share as the key of a worker registered at `urls`
+pub fn split_across_workers(urls: &[String]) -> (blsttc::PublicKeySet, Vec<String>) {
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0).secret_key();
+    let (pk_set, sk_shares) =
+        puffersecuresigner::crypto::bls_keys::split_bls_key(&sk, urls.len(), 2).unwrap();
+
+    let mut shares = vec![];
+    let mut worker_pks = vec![];
+    for (index, (url, sk_share)) in urls.iter().zip(sk_shares).enumerate() {
+        let pk_share_hex = hex::encode(sk_share.public_key_share().to_bytes());
+        puffersecuresigner::io::key_management::write_bls_key(
+            &pk_share_hex,
+            &hex::encode(sk_share.to_bytes()),
+        )
+        .unwrap();
+        puffersecuresigner::eth2::slash_protection::SlashingProtectionData::from_pk_hex(
+            &pk_share_hex,
+        )
+        .unwrap()
+        .write()
+        .unwrap();
+
+        let worker_pk = puffersecuresigner::crypto::eth_keys::new_eth_key()
+            .unwrap()
+            .1;
+        let worker_pk_hex = puffersecuresigner::to_0x_hex(worker_pk.serialize_compressed());
+        puffersecuresigner::io::workers::register_worker(url, &worker_pk_hex, Default::default())
+            .unwrap();
+        shares.push(ThresholdShare {
+            index,
+            worker_pk_hex: worker_pk_hex.clone(),
+            pk_share_hex: format!("0x{pk_share_hex}"),
+            encrypted_sk_share_hex: String::new(),
+            acknowledged: true,
+            delivered_at: Some(0),
+        });
+        worker_pks.push(worker_pk_hex);
+    }
+    puffersecuresigner::io::threshold_keys::write_threshold_key(&ThresholdKey {
+        bls_pk_hex: format!("0x{}", pk_set.public_key().to_hex()),
+        threshold: 2,
+        verification_vector: format!("0x{}", hex::encode(pk_set.to_bytes())),
+        shares,
+    })
+    .unwrap();
+    (pk_set, worker_pks)
+}
+
+async fn leader_sign(
+    state: &puffersecuresigner::enclave::shared::handlers::AppState,
+    bls_pk_hex: &str,
+    req: &BLSSignMsg,
+) -> axum_test::TestResponse {
+    let leader = axum::Router::new()
+        .route(
+            "/leader/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::leader_sign::handler,
+            ),
+        )
+        .with_state(state.clone());
+    let server = axum_test::TestServer::new(leader.into_make_service()).unwrap();
+    server
+        .post(&format!("/leader/v1/eth2/sign/{bls_pk_hex}"))
+        .add_header(
+            axum::http::header::ACCEPT,
+            axum::http::HeaderValue::from_static("application/json"),
+        )
+        .json(req)
+        .await
+}
+
+/// The listing of the split key `bls_pk_hex` by the leader with `state`
+async fn listed_key(
+    state: &puffersecuresigner::enclave::shared::handlers::AppState,
+    bls_pk_hex: &str,
+) -> puffersecuresigner::enclave::types::ThresholdKeyInfo {
+    let leader = axum::Router::new()
+        .route(
+            "/leader/v1/keys",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::list_threshold_keys::handler,
+            ),
+        )
+        .with_state(state.clone());
+    let server = axum_test::TestServer::new(leader.into_make_service()).unwrap();
+    let resp = server.get("/leader/v1/keys").await;
+    assert_eq!(resp.status_code(), 200);
+    let keys: puffersecuresigner::enclave::types::ListThresholdKeysResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    keys.data
+        .into_iter()
+        .find(|key| key.bls_pk_hex == format!("0x{bls_pk_hex}"))
+        .unwrap()
+}
+
+/// The inventory of the split key `bls_pk_hex` by the leader with `state`
+pub async fn key_inventory(
+    state: &puffersecuresigner::enclave::shared::handlers::AppState,
+    bls_pk_hex: &str,
+) -> axum_test::TestResponse {
+    let leader = axum::Router::new()
+        .route(
+            "/leader/v1/keys/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::get_threshold_key::handler,
+            ),
+        )
+        .with_state(state.clone());
+    let server = axum_test::TestServer::new(leader.into_make_service()).unwrap();
+    server.get(&format!("/leader/v1/keys/{bls_pk_hex}")).await
+}
+
+fn signature(resp: &axum_test::TestResponse) -> blsttc::Signature {
+    let resp: puffersecuresigner::enclave::types::SignatureResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    let bytes = puffersecuresigner::parse_hex(&resp.signature).unwrap();
+    blsttc::Signature::from_bytes(bytes.try_into().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_leader_recombines_partial_signatures() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_leader_recombines_partial_signatures",
+    );
+    let urls = vec![spawn_worker(), spawn_worker(), spawn_worker()];
+    let (pk_set, worker_pks) = split_across_workers(&urls);
+    let bls_pk_hex = pk_set.public_key().to_hex();
+    let leader = super::signing_helper::mock_app_state();
+    let listed = listed_key(&leader, &bls_pk_hex).await;
+    assert_eq!((listed.n, listed.threshold), (3, 2));
+    assert_eq!(listed.available_shares, vec![0, 1, 2]);
+    assert!(listed.signable);
+
+    let req = block_request(100);
+    let resp = leader_sign(&leader, &bls_pk_hex, &req).await;
+    assert_eq!(resp.status_code(), 200);
+    let root = req.to_signing_root(None);
+    assert!(pk_set.public_key().verify(&signature(&resp), root));
+
+    // The inventory shows which worker sent a partial signature
+    let resp = key_inventory(&leader, &bls_pk_hex).await;
+    assert_eq!(resp.status_code(), 200);
+    let inventory: puffersecuresigner::enclave::types::ThresholdKeyInventory =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(
+        inventory.state,
+        puffersecuresigner::enclave::types::DistributionState::Distributed
+    );
+    assert_eq!(
+        inventory.verification_vector,
+        format!("0x{}", hex::encode(pk_set.to_bytes()))
+    );
+    assert!(inventory.signable);
+    for (share, worker_pk) in inventory.shares.iter().zip(&worker_pks) {
+        assert_eq!(&share.worker_pk_hex, worker_pk);
+        assert!(share.worker_id.is_some());
+        assert!(share.last_heartbeat.is_some());
+        assert!(share.healthy);
+    }
+    assert!(
+        inventory
+            .shares
+            .iter()
+            .filter(|share| share.last_partial_signature.is_some())
+            .count()
+            >= 2
+    );
+
+    // Any two workers are enough, the third hanging does not hold the signature up
+    puffersecuresigner::io::workers::register_worker(
+        &spawn_hung_worker(),
+        &worker_pks[2],
+        Default::default(),
+    )
+    .unwrap();
+    let req = block_request(101);
+    let resp = tokio::time::timeout(
+        Duration::from_secs(1),
+        leader_sign(&leader, &bls_pk_hex, &req),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    assert!(pk_set
+        .public_key()
+        .verify(&signature(&resp), req.to_signing_root(None)));
+
+    // The workers' watermarks are past a slashable block, so the leader refuses it without
+    // asking for partial signatures, and the worker that missed its heartbeats is not waited on
+    puffersecuresigner::io::workers::register_worker(
+        &dead_url(),
+        &worker_pks[2],
+        Default::default(),
+    )
+    .unwrap();
+    let client = reqwest::Client::new();
+    for _ in 0..leader.leader.max_missed_heartbeats() {
+        leader.leader.poll_workers(&client).await.unwrap();
+    }
+    let listed = listed_key(&leader, &bls_pk_hex).await;
+    assert_eq!(listed.available_shares, vec![0, 1]);
+    assert!(listed.signable);
+    let resp = tokio::time::timeout(
+        Duration::from_secs(1),
+        leader_sign(&leader, &bls_pk_hex, &block_request(99)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 412);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 412);
+    assert!(err.message.contains("last signed block slot 101"));
+}
+
+#[tokio::test]
+async fn test_leader_refuses_diverged_watermarks() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_leader_refuses_diverged_watermarks",
+    );
+    let urls = vec![spawn_worker(), spawn_worker(), spawn_worker()];
+    let (pk_set, worker_pks) = split_across_workers(&urls);
+    let bls_pk_hex = pk_set.public_key().to_hex();
+    let pk_share_hex = |index: usize| hex::encode(pk_set.public_key_share(index).to_bytes());
+    let leader = super::signing_helper::mock_app_state();
+    let resp = leader_sign(&leader, &bls_pk_hex, &block_request(10)).await;
+    assert_eq!(resp.status_code(), 200);
+    // Every worker asked recorded the block, not just the two whose partials were recombined
+    for index in 0..3 {
+        let watermark = puffersecuresigner::enclave::secure_signer::worker::watermark(
+            &FileBackend,
+            &pk_share_hex(index),
+        )
+        .unwrap();
+        assert_eq!(watermark.block_slot, Some(10));
+    }
+
+    // The worker holding share 0 signed a later block on its own
+    let mut db = FileBackend.read(&pk_share_hex(0)).unwrap();
+    db.new_block(
+        puffersecuresigner::eth2::slash_protection::SignedBlockSlot {
+            slot: 20,
+            signing_root: None,
+        },
+        true,
+    )
+    .unwrap();
+    FileBackend.write(&db).unwrap();
+    let watermark = puffersecuresigner::enclave::secure_signer::worker::watermark(
+        &FileBackend,
+        &pk_share_hex(0),
+    )
+    .unwrap();
+    assert_eq!(watermark.block_slot, Some(20));
+    assert_eq!(watermark.attestation_target_epoch, None);
+
+    // So the others must not sign a block it already passed, though they could recombine it
+    let resp = leader_sign(&leader, &bls_pk_hex, &block_request(15)).await;
+    assert_eq!(resp.status_code(), 412);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    let worker_id = puffersecuresigner::io::workers::worker_id(&worker_pks[0]).unwrap();
+    assert!(err.message.contains(&worker_id));
+    assert!(err.message.contains("last signed block slot 20"));
+    for index in 1..3 {
+        let watermark = puffersecuresigner::enclave::secure_signer::worker::watermark(
+            &FileBackend,
+            &pk_share_hex(index),
+        )
+        .unwrap();
+        assert_eq!(watermark.block_slot, Some(10));
+    }
+
+    let resp = leader_sign(&leader, &bls_pk_hex, &block_request(21)).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(pk_set
+        .public_key()
+        .verify(&signature(&resp), block_request(21).to_signing_root(None)));
+
+    let resp = reqwest::get(format!(
+        "{}/worker/v1/watermark/{}",
+        urls[0],
+        blsttc::SecretKey::random().public_key().to_hex()
+    ))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_leader_sign_unknown_key() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_leader_sign_unknown_key");
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let leader = super::signing_helper::mock_app_state();
+    let resp = leader_sign(&leader, &unknown_pk_hex, &block_request(1)).await;
+    assert_eq!(resp.status_code(), 404);
+    assert_eq!(
+        key_inventory(&leader, &unknown_pk_hex).await.status_code(),
+        404
+    );
+}
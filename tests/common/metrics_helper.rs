@@ -0,0 +1,49 @@
+use super::signing_helper::mock_secure_sign_route;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::eth_types::VoluntaryExitRequest;
+
+async fn scrape_metrics() -> String {
+    let app = axum::Router::new().route(
+        "/metrics",
+        axum::routing::get(puffersecuresigner::enclave::shared::handlers::metrics::handler),
+    );
+    let server = axum_test::TestServer::new(app.into_make_service()).unwrap();
+    let resp = server.get("/metrics").await;
+    resp.assert_status_ok();
+    assert!(resp
+        .header(axum::http::header::CONTENT_TYPE)
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    resp.text()
+}
+
+/// The count of the sample line that starts with `series`, 0 if it is missing
+fn sample(metrics: &str, series: &str) -> u64 {
+    metrics
+        .lines()
+        .find_map(|line| line.strip_prefix(series))
+        .map(|value| value.trim().parse().unwrap())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_metrics_count_sign_requests_by_type_and_status() {
+    // No other test signs a voluntary exit for an unknown key, so tests running in parallel do not
+    // move this count
+    let series = r#"secure_signer_sign_requests_total{status="404",type="VOLUNTARY_EXIT"}"#;
+    let before = sample(&scrape_metrics().await, series);
+
+    let req: VoluntaryExitRequest =
+        serde_json::from_str(&crate::signing_tests::voluntary_exit::mock_voluntary_exit_request())
+            .unwrap();
+    let bls_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let resp = mock_secure_sign_route(&bls_pk_hex, BLSSignMsg::VOLUNTARY_EXIT(req))
+        .await
+        .unwrap();
+    resp.assert_status(axum::http::StatusCode::NOT_FOUND);
+
+    let metrics = scrape_metrics().await;
+    assert_eq!(sample(&metrics, series), before + 1);
+    assert!(metrics.contains(r#"secure_signer_sign_duration_seconds_count{type="VOLUNTARY_EXIT"}"#));
+}
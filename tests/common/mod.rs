@@ -1,13 +1,44 @@
 use blsttc::SecretKeySet;
 use puffersecuresigner::crypto::bls_keys;
-use puffersecuresigner::eth2::slash_protection::SlashingProtectionData;
+use puffersecuresigner::eth2::slash_protection::{
+    FileBackend, SlashingProtectionBackend, SlashingProtectionData,
+};
 use puffersecuresigner::strip_0x_prefix;
 
+pub mod api_token_helper;
+pub mod bls_aggregate_helper;
 pub mod bls_keygen_helper;
+pub mod bls_verify_helper;
+pub mod body_limits_helper;
+pub mod cors_helper;
+pub mod datafeed_helper;
+pub mod delete_keys_helper;
+pub mod distribute_key_shares_helper;
+pub mod error_response_helper;
 pub mod eth_keygen_helper;
 pub mod eth_specs;
+pub mod fee_recipient_helper;
+pub mod gas_limit_helper;
 pub mod getter_routes_helper;
+pub mod graffiti_helper;
+pub mod health_helper;
+pub mod import_keystores_helper;
+pub mod key_labels_helper;
+pub mod leader_sign_helper;
+pub mod metrics_helper;
+pub mod openapi_helper;
+pub mod rate_limit_helper;
+pub mod remote_attestation_helper;
+pub mod remote_keys_helper;
+pub mod reshare_key_shares_helper;
+pub mod shutdown_helper;
 pub mod signing_helper;
+pub mod slashing_protection_status_helper;
+pub mod split_bls_key_helper;
+pub mod unix_socket_helper;
+pub mod verify_attestation_helper;
+pub mod web3signer_compat_helper;
+pub mod workers_helper;
 
 /// Reads the `SECURE_SIGNER_PORT` environment variable.
 /// If the return value is Some(port), it is expected that Secure-Signer is running on localhost:port
@@ -41,7 +72,7 @@ pub fn setup_dummy_keypair() -> String {
 
     // init slashing protection db
     let db = SlashingProtectionData::from_pk_hex(&pk_hex).unwrap();
-    db.write().unwrap();
+    FileBackend.write(&db).unwrap();
 
     pk_hex
 }
@@ -0,0 +1,80 @@
+use puffersecuresigner::enclave::shared::handlers::openapi;
+
+async fn mock_openapi_route() -> axum_test::TestResponse {
+    let app = axum::Router::new().route("/api/schema", axum::routing::get(openapi::handler));
+    axum_test::TestServer::new(app.into_make_service())
+        .unwrap()
+        .get("/api/schema")
+        .await
+}
+
+/// Route paths the secure-signer bin mounts, with `:param` segments as `{}`
+fn mounted_routes() -> Vec<String> {
+    let bin = include_str!("../../src/bin/secure-signer.rs");
+    let mut routes: Vec<String> = bin
+        .split(".route(")
+        .skip(1)
+        .filter_map(|call| call.split('"').nth(1))
+        .map(|path| normalize(path, ':'))
+        .collect();
+    routes.sort();
+    routes.dedup();
+    routes
+}
+
+/// `path` with every parameter segment, starting with `marker`, replaced by `{}`
+fn normalize(path: &str, marker: char) -> String {
+    path.split('/')
+        .map(|segment| {
+            if segment.starts_with(marker) {
+                "{}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[tokio::test]
+async fn test_openapi_spec_parses() {
+    let resp = mock_openapi_route().await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.header(axum::http::header::CONTENT_TYPE),
+        "application/json"
+    );
+    let spec: serde_json::Value = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+
+    // Every $ref is inlined
+    assert!(!openapi::OPENAPI_JSON.contains("\"$ref\""));
+
+    let sign = &spec["paths"]["/api/v1/eth2/sign/{identifier}"]["post"];
+    assert!(sign["responses"]["412"]["description"]
+        .as_str()
+        .unwrap()
+        .contains("slashing protection"));
+    assert!(
+        sign["responses"]["412"]["content"]["application/json"]["schema"]["properties"]
+            .get("message")
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn test_openapi_spec_covers_every_route() {
+    let spec: serde_json::Value = serde_json::from_str(openapi::OPENAPI_JSON).unwrap();
+    let documented: Vec<String> = spec["paths"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(|path| normalize(path, '{'))
+        .collect();
+    let routes = mounted_routes();
+    assert!(routes.contains(&"/api/v1/eth2/sign/{}".to_string()));
+    for route in routes {
+        assert!(documented.contains(&route), "{route} is not documented");
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use axum::http::header;
+use puffersecuresigner::enclave::shared::rate_limit::{
+    rate_limit, RateLimit, RateLimiter, RateLimits,
+};
+
+/// Serves a signing, a key management and a listing route behind `limits`
+pub fn mock_rate_limit_app(limits: RateLimits) -> Result<axum_test::TestServer> {
+    let ok = || async { "OK" };
+    let test_app = axum::Router::new()
+        .route("/eth/v1/keygen/bls", axum::routing::post(ok))
+        .route("/eth/v1/keystores", axum::routing::get(ok).delete(ok))
+        .route("/api/v1/eth2/sign/:bls_pk_hex", axum::routing::post(ok))
+        .route("/leader/v1/eth2/sign/:bls_pk_hex", axum::routing::post(ok))
+        .route(
+            "/metrics",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::metrics::handler),
+        )
+        .layer(axum::middleware::from_fn_with_state(limits, rate_limit))
+        .into_make_service();
+    axum_test::TestServer::new(test_app)
+}
+
+#[tokio::test]
+async fn test_sign_rate_limit_is_per_pubkey() {
+    let server = mock_rate_limit_app(RateLimits {
+        sign: Some(RateLimiter::new(
+            "sign",
+            RateLimit::new(1, Some(2)).unwrap(),
+        )),
+        admin: None,
+    })
+    .unwrap();
+
+    for _ in 0..2 {
+        let resp = server.post("/api/v1/eth2/sign/0xaBcD").await;
+        assert_eq!(resp.status_code(), 200);
+    }
+    // The 0x prefix and case do not make it another key
+    let resp = server.post("/api/v1/eth2/sign/abcd").await;
+    assert_eq!(resp.status_code(), 429);
+    assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "1");
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 429);
+
+    // Other keys and routes are not limited
+    let resp = server.post("/api/v1/eth2/sign/0x1234").await;
+    assert_eq!(resp.status_code(), 200);
+
+    // The leader's sign route is limited per key too
+    for _ in 0..2 {
+        let resp = server.post("/leader/v1/eth2/sign/0x5678").await;
+        assert_eq!(resp.status_code(), 200);
+    }
+    let resp = server.post("/leader/v1/eth2/sign/0x5678").await;
+    assert_eq!(resp.status_code(), 429);
+    let resp = server.post("/leader/v1/eth2/sign/0x9abc").await;
+    assert_eq!(resp.status_code(), 200);
+    for _ in 0..5 {
+        assert_eq!(server.post("/eth/v1/keygen/bls").await.status_code(), 200);
+    }
+
+    let metrics = server.get("/metrics").await.text();
+    assert!(metrics.contains(r#"secure_signer_rate_limited_total{limiter="sign"}"#));
+    assert!(metrics.contains(r#"secure_signer_rate_limit_buckets{limiter="sign"} 4"#));
+}
+
+#[tokio::test]
+async fn test_admin_rate_limit_is_global() {
+    let server = mock_rate_limit_app(RateLimits {
+        sign: None,
+        admin: Some(RateLimiter::new("admin", RateLimit::new(1, None).unwrap())),
+    })
+    .unwrap();
+
+    assert_eq!(server.post("/eth/v1/keygen/bls").await.status_code(), 200);
+    // A different admin route shares the same bucket
+    assert_eq!(server.delete("/eth/v1/keystores").await.status_code(), 429);
+
+    // Reads and signing stay unlimited
+    for _ in 0..5 {
+        assert_eq!(server.get("/eth/v1/keystores").await.status_code(), 200);
+        assert_eq!(
+            server.post("/api/v1/eth2/sign/0x1234").await.status_code(),
+            200
+        );
+        assert_eq!(
+            server
+                .post("/leader/v1/eth2/sign/0x1234")
+                .await
+                .status_code(),
+            200
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limits_are_disabled_by_default() {
+    let server = mock_rate_limit_app(RateLimits::default()).unwrap();
+    for _ in 0..20 {
+        assert_eq!(
+            server.post("/api/v1/eth2/sign/0x1234").await.status_code(),
+            200
+        );
+        assert_eq!(server.post("/eth/v1/keygen/bls").await.status_code(), 200);
+    }
+}
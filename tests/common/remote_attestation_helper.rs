@@ -0,0 +1,296 @@
+use anyhow::Result;
+use puffersecuresigner::enclave::types::{
+    KeyGenResponse, KeySetAttestationResponse, RemoteAttestationRequest, RemoteAttestationResponse,
+};
+use puffersecuresigner::io::remote_attestation::EvidenceKind;
+
+pub fn mock_remote_attestation_app() -> Result<axum_test::TestServer> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/keygen/secp256k1",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::eth_keygen::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/remote-attestation/all",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::attest_key_set::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/remote-attestation/:kind",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::remote_attestation::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    axum_test::TestServer::new(test_app)
+}
+
+async fn attest(
+    server: &axum_test::TestServer,
+    req: &RemoteAttestationRequest,
+) -> axum_test::TestResponse {
+    server
+        .post("/eth/v1/remote-attestation/dcap")
+        .json(req)
+        .await
+}
+
+#[tokio::test]
+async fn test_dcap_binds_saved_keys_into_report_data() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_dcap_binds_saved_keys_into_report_data",
+    );
+    let server = mock_remote_attestation_app().unwrap();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+    let bls_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let resp = server.post("/eth/v1/keygen/secp256k1").await;
+    assert_eq!(resp.status_code(), 201);
+    let eth_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let eth_pk = puffersecuresigner::crypto::eth_keys::parse_eth_pk_hex(&eth_key.pk_hex).unwrap();
+
+    let resp = attest(
+        &server,
+        &RemoteAttestationRequest {
+            eth_pubkey: Some(eth_key.pk_hex.clone()),
+            bls_pubkey: Some(bls_key.pk_hex.clone()),
+            nonce: Some("0xABCD".to_string()),
+        },
+    )
+    .await;
+    assert_eq!(resp.status_code(), 200);
+    let attested: RemoteAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    // The preimage is echoed in its canonical form
+    assert_eq!(
+        attested.binding.eth_pubkey,
+        Some(puffersecuresigner::to_0x_hex(eth_pk.serialize_compressed()))
+    );
+    assert_eq!(attested.binding.bls_pubkey, Some(bls_key.pk_hex.clone()));
+    assert_eq!(attested.binding.nonce, "0xabcd");
+    assert_eq!(
+        attested.report_data,
+        puffersecuresigner::to_0x_hex(attested.binding.report_data().unwrap())
+    );
+    // Outside of SGX the quote is empty and there is no collateral to fetch
+    assert_eq!(attested.evidence.kind, EvidenceKind::Dcap);
+    assert!(attested.evidence.collateral.is_none());
+
+    // The same binding is served from the cache until fresh evidence is asked for
+    let req = RemoteAttestationRequest {
+        eth_pubkey: Some(eth_key.pk_hex.clone()),
+        bls_pubkey: Some(bls_key.pk_hex.clone()),
+        nonce: Some("0xabcd".to_string()),
+    };
+    let resp = attest(&server, &req).await;
+    assert_eq!(resp.status_code(), 200);
+    let cached: RemoteAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(cached.generated_at, attested.generated_at);
+    let resp = server
+        .post("/eth/v1/remote-attestation/dcap?fresh=true")
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let fresh: RemoteAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(fresh.generated_at >= attested.generated_at);
+    assert_eq!(fresh.report_data, attested.report_data);
+
+    // A challenge is the nonce, and a new one is never served from the cache
+    let resp = server
+        .post("/eth/v1/remote-attestation/dcap")
+        .json(&serde_json::json!({
+            "bls_pubkey": bls_key.pk_hex,
+            "eth_pubkey": eth_key.pk_hex,
+            "challenge_hex": "0xabcd",
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let challenged: RemoteAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(challenged.binding.nonce, "0xabcd");
+    assert_eq!(challenged.report_data, attested.report_data);
+    let resp = server
+        .post("/eth/v1/remote-attestation/dcap")
+        .json(&serde_json::json!({
+            "bls_pubkey": bls_key.pk_hex,
+            "eth_pubkey": eth_key.pk_hex,
+            "challenge_hex": "0xabce",
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let challenged: RemoteAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(challenged.binding.nonce, "0xabce");
+    assert_ne!(challenged.report_data, attested.report_data);
+    for challenge in ["0xabcg", "0xabc"] {
+        let resp = server
+            .post("/eth/v1/remote-attestation/dcap")
+            .json(&serde_json::json!({
+                "bls_pubkey": bls_key.pk_hex,
+                "challenge_hex": challenge,
+            }))
+            .await;
+        assert_eq!(resp.status_code(), 400);
+    }
+
+    // Either key can be attested alone
+    let resp = attest(
+        &server,
+        &RemoteAttestationRequest {
+            bls_pubkey: Some(bls_key.pk_hex),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(resp.status_code(), 200);
+    let attested: RemoteAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(attested.binding.eth_pubkey.is_none());
+    assert_ne!(
+        attested.report_data,
+        puffersecuresigner::to_0x_hex([0_u8; 32])
+    );
+}
+
+#[tokio::test]
+async fn test_dcap_refuses_keys_not_on_file() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_dcap_refuses_keys_not_on_file");
+    let server = mock_remote_attestation_app().unwrap();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    let bls_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    // A substituted key must not be attested, even next to a key the enclave holds
+    let (_, other_eth_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let resp = attest(
+        &server,
+        &RemoteAttestationRequest {
+            eth_pubkey: Some(puffersecuresigner::crypto::eth_keys::eth_pk_to_hex(
+                &other_eth_pk,
+            )),
+            bls_pubkey: Some(bls_key.pk_hex.clone()),
+            nonce: None,
+        },
+    )
+    .await;
+    assert_eq!(resp.status_code(), 400);
+
+    let unknown_bls_pk = puffersecuresigner::crypto::bls_keys::new_bls_key(0)
+        .public_keys()
+        .public_key()
+        .to_hex();
+    let resp = attest(
+        &server,
+        &RemoteAttestationRequest {
+            bls_pubkey: Some(format!("0x{unknown_bls_pk}")),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(resp.status_code(), 400);
+
+    for req in [
+        RemoteAttestationRequest::default(),
+        RemoteAttestationRequest {
+            bls_pubkey: Some("0x1234".to_string()),
+            ..Default::default()
+        },
+        RemoteAttestationRequest {
+            bls_pubkey: Some(bls_key.pk_hex.clone()),
+            nonce: Some(format!("0x{}", "00".repeat(65))),
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(attest(&server, &req).await.status_code(), 400);
+    }
+}
+
+#[tokio::test]
+async fn test_epid_without_ias_config_is_unavailable() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_epid_without_ias_config_is_unavailable",
+    );
+    let server = mock_remote_attestation_app().unwrap();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+    let bls_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // The tests never configure IAS, so EPID is refused rather than panicking inside the FFI
+    let resp = server
+        .post("/eth/v1/remote-attestation/epid")
+        .json(&RemoteAttestationRequest {
+            bls_pubkey: Some(bls_key.pk_hex),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(resp.status_code(), 503);
+    let body = String::from_utf8(resp.as_bytes().to_vec()).unwrap();
+    assert!(body.contains("EPID attestation is not configured"));
+    assert!(!body.contains("ias_access_key"));
+
+    let resp = server
+        .post("/eth/v1/remote-attestation/tpm")
+        .json(&RemoteAttestationRequest::default())
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_attest_every_held_key() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_attest_every_held_key");
+    let server = mock_remote_attestation_app().unwrap();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    let bls_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let resp = server.post("/eth/v1/keygen/secp256k1").await;
+    let eth_key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let eth_pk = puffersecuresigner::crypto::eth_keys::parse_eth_pk_hex(&eth_key.pk_hex).unwrap();
+
+    let resp = server
+        .get("/eth/v1/remote-attestation/all?kind=dcap&nonce=0xABCD")
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let attested: KeySetAttestationResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(attested.key_set.bls_pubkeys.contains(&bls_key.pk_hex));
+    assert!(attested
+        .key_set
+        .eth_pubkeys
+        .contains(&puffersecuresigner::to_0x_hex(
+            eth_pk.serialize_compressed()
+        )));
+    assert_eq!(
+        attested.key_set.count,
+        attested.key_set.eth_pubkeys.len() + attested.key_set.bls_pubkeys.len()
+    );
+    assert_eq!(attested.key_set.nonce, "0xabcd");
+    assert_eq!(attested.evidence.kind, EvidenceKind::Dcap);
+
+    // Verifiers recompute the digest from the lists in any order
+    let mut key_set = attested.key_set.clone();
+    key_set.bls_pubkeys.reverse();
+    assert_eq!(
+        attested.report_data,
+        puffersecuresigner::to_0x_hex(key_set.report_data().unwrap())
+    );
+
+    // EPID needs IAS credentials, which the tests never configure
+    let resp = server.get("/eth/v1/remote-attestation/all").await;
+    assert_eq!(resp.status_code(), 503);
+    let resp = server
+        .get(&format!(
+            "/eth/v1/remote-attestation/all?kind=dcap&nonce=0x{}",
+            "00".repeat(65)
+        ))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
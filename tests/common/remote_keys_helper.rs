@@ -0,0 +1,176 @@
+use super::read_secure_signer_port;
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::types::{
+    DeleteKeyStatus, DeleteKeysRequest, DeleteKeysResponse, ImportKeystoreStatus, ImportRemoteKey,
+    ImportRemoteKeysRequest, ImportRemoteKeysResponse, ListRemoteKeysResponse,
+};
+use reqwest::{Client, Method, Response, StatusCode};
+
+fn mock_remote_keys_app() -> axum::routing::IntoMakeService<axum::Router> {
+    axum::Router::new()
+        .route(
+            "/eth/v1/remotekeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::list_remote_keys::handler,
+            )
+            .post(puffersecuresigner::enclave::secure_signer::handlers::import_remote_keys::handler)
+            .delete(
+                puffersecuresigner::enclave::secure_signer::handlers::delete_remote_keys::handler,
+            ),
+        )
+        .into_make_service()
+}
+
+pub async fn mock_remote_keys_route(
+    method: Method,
+    body: Option<serde_json::Value>,
+) -> Result<axum_test::TestResponse> {
+    let server = axum_test::TestServer::new(mock_remote_keys_app())?;
+    let req = match method {
+        Method::POST => server.post("/eth/v1/remotekeys"),
+        Method::DELETE => server.delete("/eth/v1/remotekeys"),
+        _ => server.get("/eth/v1/remotekeys"),
+    };
+    Ok(match body {
+        Some(body) => req.json(&body).await,
+        None => req.await,
+    })
+}
+
+pub async fn request_remote_keys_route(
+    method: Method,
+    body: Option<serde_json::Value>,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!("http://localhost:{}/eth/v1/remotekeys", port);
+    let req = client.request(method, &url);
+    match body {
+        Some(body) => req.json(&body).send().await,
+        None => req.send().await,
+    }
+}
+
+async fn make_remote_keys_request<T: serde::de::DeserializeOwned>(
+    method: Method,
+    body: Option<serde_json::Value>,
+    port: Option<u16>,
+) -> Result<(T, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_remote_keys_route(method, body, p).await?;
+            let status = resp.status();
+            let resp: T = resp
+                .json()
+                .await
+                .with_context(|| "Failed to parse remotekeys response")?;
+            Ok((resp, status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_remote_keys_route(method, body).await?;
+            let status = resp.status_code();
+            let resp: T = serde_json::from_slice(resp.as_bytes())
+                .with_context(|| "Failed to parse remotekeys response")?;
+            Ok((resp, status))
+        }
+    }
+}
+
+pub async fn make_list_remote_keys_request(
+    port: Option<u16>,
+) -> Result<(ListRemoteKeysResponse, StatusCode)> {
+    make_remote_keys_request(Method::GET, None, port).await
+}
+
+pub async fn make_import_remote_keys_request(
+    remote_keys: Vec<ImportRemoteKey>,
+    port: Option<u16>,
+) -> Result<(ImportRemoteKeysResponse, StatusCode)> {
+    let req = ImportRemoteKeysRequest { remote_keys };
+    make_remote_keys_request(Method::POST, Some(serde_json::to_value(req)?), port).await
+}
+
+pub async fn make_delete_remote_keys_request(
+    pubkeys: Vec<String>,
+    port: Option<u16>,
+) -> Result<(DeleteKeysResponse, StatusCode)> {
+    let req = DeleteKeysRequest { pubkeys };
+    make_remote_keys_request(Method::DELETE, Some(serde_json::to_value(req)?), port).await
+}
+
+#[tokio::test]
+async fn test_import_list_delete_remote_keys() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_import_list_delete_remote_keys",
+    );
+    let port = read_secure_signer_port();
+    let pk_hex = format!("0x{}", blsttc::SecretKey::random().public_key().to_hex());
+    let url = "http://worker-1:9001".to_string();
+
+    let (resp, status) = make_import_remote_keys_request(
+        vec![
+            ImportRemoteKey {
+                pubkey: pk_hex.clone(),
+                url: url.clone(),
+            },
+            ImportRemoteKey {
+                pubkey: pk_hex.clone(),
+                url: url.clone(),
+            },
+            ImportRemoteKey {
+                pubkey: "0xbad".to_string(),
+                url: url.clone(),
+            },
+        ],
+        port,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data.len(), 3);
+    assert_eq!(resp.data[0].status, ImportKeystoreStatus::Imported);
+    assert_eq!(resp.data[1].status, ImportKeystoreStatus::Duplicate);
+    assert_eq!(resp.data[2].status, ImportKeystoreStatus::Error);
+
+    let (resp, status) = make_list_remote_keys_request(port).await.unwrap();
+    assert_eq!(status, 200);
+    let listed = resp.data.iter().find(|k| k.pubkey == pk_hex).unwrap();
+    assert_eq!(listed.url, url);
+    assert!(!listed.readonly);
+
+    // A missing key is reported per key instead of failing the batch
+    let missing_pk_hex = format!("0x{}", blsttc::SecretKey::random().public_key().to_hex());
+    let (resp, status) =
+        make_delete_remote_keys_request(vec![pk_hex.clone(), missing_pk_hex], port)
+            .await
+            .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data[0].status, DeleteKeyStatus::Deleted);
+    assert_eq!(resp.data[1].status, DeleteKeyStatus::NotFound);
+
+    let (resp, _) = make_list_remote_keys_request(port).await.unwrap();
+    assert!(resp.data.iter().all(|k| k.pubkey != pk_hex));
+}
+
+#[tokio::test]
+async fn test_import_remote_key_rejects_non_http_url() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_import_remote_key_rejects_non_http_url",
+    );
+    let port = read_secure_signer_port();
+    let pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let (resp, status) = make_import_remote_keys_request(
+        vec![ImportRemoteKey {
+            pubkey: pk_hex,
+            url: "worker-1:9001".to_string(),
+        }],
+        port,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.data[0].status, ImportKeystoreStatus::Error);
+}
@@ -0,0 +1,311 @@
+use puffersecuresigner::crypto::{bls_keys, eth_keys};
+use puffersecuresigner::enclave::secure_signer::worker::{
+    combine_sub_shares, revoked_share, save_key_share, seed_watermark, sub_share, watermark,
+};
+use puffersecuresigner::enclave::types::{
+    CombineSubSharesRequest, ReshareKeyRequest, RevokeShareRequest, SealedReshareRequest, SubShare,
+    SubShareRequest, SubShareResponse, WorkerWatermarkResponse,
+};
+use puffersecuresigner::eth2::slash_protection::{FileBackend, SlashingProtectionBackend};
+use std::collections::BTreeMap;
+
+async fn reshare(bls_pk_hex: &str, req: &ReshareKeyRequest) -> axum_test::TestResponse {
+    let leader = axum::Router::new()
+        .route(
+            "/leader/v1/keys/:bls_pk_hex/reshare",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::reshare_key_shares::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state());
+    let server = axum_test::TestServer::new(leader.into_make_service()).unwrap();
+    server
+        .post(&format!("/leader/v1/keys/{bls_pk_hex}/reshare"))
+        .json(req)
+        .await
+}
+
+#[tokio::test]
+async fn test_reshare_keeps_the_group_public_key() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_reshare_keeps_the_group_public_key",
+    );
+    let sk = bls_keys::new_bls_key(0).secret_key();
+    let bls_pk_hex = format!("0x{}", sk.public_key().to_hex());
+    let (pk_set, sk_shares) = bls_keys::split_bls_key(&sk, 3, 2).unwrap();
+    let vvec_hex = format!("0x{}", hex::encode(pk_set.to_bytes()));
+    let pk_share_hexes: Vec<String> = sk_shares
+        .iter()
+        .map(|s| save_key_share(&FileBackend, s).unwrap())
+        .collect();
+
+    // Old share 0 already signed a block at slot 100
+    let mut db = puffersecuresigner::eth2::slash_protection::SlashingProtectionData::from_pk_hex(
+        &pk_share_hexes[0],
+    )
+    .unwrap();
+    db.new_block(
+        puffersecuresigner::eth2::slash_protection::SignedBlockSlot {
+            slot: 100,
+            signing_root: None,
+        },
+        false,
+    )
+    .unwrap();
+    FileBackend.write(&db).unwrap();
+
+    // Old shares 0 and 2 re-share the key 3-of-4 across new workers
+    let new_workers: Vec<String> = (0..4)
+        .map(|_| {
+            puffersecuresigner::to_0x_hex(eth_keys::eth_key_gen().unwrap().serialize_compressed())
+        })
+        .collect();
+    let old_worker_pk_hex =
+        puffersecuresigner::to_0x_hex(eth_keys::eth_key_gen().unwrap().serialize_compressed());
+    let mut split = BTreeMap::new();
+    for index in [0, 2] {
+        let resp: SubShareResponse = sub_share(&SubShareRequest {
+            worker_pk_hex: old_worker_pk_hex.clone(),
+            bls_pk_hex: bls_pk_hex.clone(),
+            index,
+            verification_vector: vvec_hex.clone(),
+            pk_share_hex: pk_share_hexes[index].clone(),
+            threshold: 3,
+            recipients: new_workers.clone(),
+        })
+        .unwrap();
+        split.insert(index, resp);
+    }
+    // A worker only splits the share it was asked for
+    assert!(sub_share(&SubShareRequest {
+        worker_pk_hex: old_worker_pk_hex.clone(),
+        bls_pk_hex: bls_pk_hex.clone(),
+        index: 1,
+        verification_vector: vvec_hex.clone(),
+        pk_share_hex: pk_share_hexes[0].clone(),
+        threshold: 3,
+        recipients: new_workers.clone(),
+    })
+    .is_err());
+
+    let commitments: BTreeMap<_, _> = split
+        .iter()
+        .map(|(&index, resp)| {
+            let bytes = puffersecuresigner::parse_hex(&resp.commitment).unwrap();
+            (index, blsttc::PublicKeySet::from_bytes(bytes).unwrap())
+        })
+        .collect();
+    let new_pk_set =
+        puffersecuresigner::crypto::reshare::combine_commitments(&commitments).unwrap();
+    assert_eq!(new_pk_set.public_key(), pk_set.public_key());
+    let old_watermark = watermark(&FileBackend, &pk_share_hexes[0][2..]).unwrap();
+    assert_eq!(old_watermark.block_slot, Some(100));
+
+    let combine_req = |j: usize| CombineSubSharesRequest {
+        worker_pk_hex: new_workers[j].clone(),
+        bls_pk_hex: bls_pk_hex.clone(),
+        index: j,
+        old_verification_vector: vvec_hex.clone(),
+        verification_vector: format!("0x{}", hex::encode(new_pk_set.to_bytes())),
+        sub_shares: split
+            .iter()
+            .map(|(&from_index, resp)| SubShare {
+                from_index,
+                commitment: resp.commitment.clone(),
+                encrypted_sub_share_hex: resp.encrypted_sub_shares[j].clone(),
+            })
+            .collect(),
+        watermark: WorkerWatermarkResponse {
+            pk_share_hex: format!(
+                "0x{}",
+                hex::encode(new_pk_set.public_key_share(j).to_bytes())
+            ),
+            ..old_watermark.clone()
+        },
+    };
+
+    // Sub-shares split from another share than claimed, or too few of them, are refused
+    let mut req = combine_req(0);
+    req.sub_shares[1].commitment = req.sub_shares[0].commitment.clone();
+    assert!(combine_sub_shares(&req).is_err());
+    let mut req = combine_req(0);
+    req.sub_shares.pop();
+    assert!(combine_sub_shares(&req).is_err());
+    // As are sub-shares for another worker
+    let mut req = combine_req(0);
+    req.worker_pk_hex = new_workers[1].clone();
+    assert!(combine_sub_shares(&req).is_err());
+
+    let msg = b"reshared";
+    let mut partials = BTreeMap::new();
+    for j in 0..4 {
+        let req = combine_req(j);
+        let share = combine_sub_shares(&req).unwrap();
+        seed_watermark(&FileBackend, &req.watermark.pk_share_hex, &req.watermark).unwrap();
+        let saved = save_key_share(&FileBackend, &share).unwrap();
+        assert_eq!(saved, req.watermark.pk_share_hex);
+        // The new share cannot sign below what the old shares signed
+        assert_eq!(
+            watermark(&FileBackend, &saved[2..]).unwrap().block_slot,
+            Some(100)
+        );
+        partials.insert(j, share.sign(msg));
+    }
+    partials.remove(&2);
+    let sig = bls_keys::combine_partial_signatures(&new_pk_set, &partials, msg).unwrap();
+    assert!(sk.public_key().verify(&sig, msg));
+
+    // The old holders are then asked to delete their shares, but only shares of the old sharing
+    let revoke_req = |index: usize| RevokeShareRequest {
+        worker_pk_hex: old_worker_pk_hex.clone(),
+        bls_pk_hex: bls_pk_hex.clone(),
+        index,
+        verification_vector: vvec_hex.clone(),
+        pk_share_hex: pk_share_hexes[0].clone(),
+    };
+    assert_eq!(
+        revoked_share(&revoke_req(0)).unwrap(),
+        pk_share_hexes[0][2..]
+    );
+    assert!(revoked_share(&revoke_req(1)).is_err());
+    let mut req = revoke_req(0);
+    req.verification_vector = format!("0x{}", hex::encode(new_pk_set.to_bytes()));
+    assert!(revoked_share(&req).is_err());
+}
+
+#[tokio::test]
+async fn test_reshare_refuses_bad_requests() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_reshare_refuses_bad_requests");
+    let urls: Vec<String> = (0..3)
+        .map(|_| super::leader_sign_helper::dead_url())
+        .collect();
+    let (pk_set, _) = super::leader_sign_helper::split_across_workers(&urls);
+    let bls_pk_hex = pk_set.public_key().to_hex();
+
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let req = ReshareKeyRequest {
+        threshold: 2,
+        n: Some(3),
+        workers: vec![],
+    };
+    assert_eq!(reshare(&unknown_pk_hex, &req).await.status_code(), 404);
+
+    for (threshold, n) in [(1, Some(3)), (4, Some(3)), (2, None)] {
+        let req = ReshareKeyRequest {
+            threshold,
+            n,
+            workers: vec![],
+        };
+        assert_eq!(reshare(&bls_pk_hex, &req).await.status_code(), 400);
+    }
+
+    // The new workers must be registered and pass the measurement policy
+    let unregistered =
+        puffersecuresigner::to_0x_hex(eth_keys::new_eth_key().unwrap().1.serialize_compressed());
+    let req = ReshareKeyRequest {
+        threshold: 2,
+        n: None,
+        workers: vec![unregistered.clone(), unregistered],
+    };
+    let resp = reshare(&bls_pk_hex, &req).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("not registered"));
+    let req = ReshareKeyRequest {
+        threshold: 2,
+        n: Some(3),
+        workers: vec![],
+    };
+    assert_eq!(reshare(&bls_pk_hex, &req).await.status_code(), 400);
+
+    // The old sharing is untouched
+    let key = puffersecuresigner::io::threshold_keys::read_threshold_key(&bls_pk_hex).unwrap();
+    assert_eq!(
+        key.verification_vector,
+        format!("0x{}", hex::encode(pk_set.to_bytes()))
+    );
+
+    // Workers only take re-sharing requests from a leader that attested to them
+    let worker = axum::Router::new()
+        .route(
+            "/worker/v1/reshare/subshares",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_reshare_subshares::handler,
+            ),
+        )
+        .route(
+            "/worker/v1/reshare/combine",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_reshare_combine::handler,
+            ),
+        )
+        .route(
+            "/worker/v1/reshare/revoke",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_reshare_revoke::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state());
+    let server = axum_test::TestServer::new(worker.into_make_service()).unwrap();
+    let sealed = SealedReshareRequest {
+        leader_pubkey: puffersecuresigner::to_0x_hex(
+            eth_keys::new_eth_key().unwrap().1.serialize_compressed(),
+        ),
+        sealed_request: "0x00".to_string(),
+    };
+    for path in [
+        "/worker/v1/reshare/subshares",
+        "/worker/v1/reshare/combine",
+        "/worker/v1/reshare/revoke",
+    ] {
+        assert_eq!(server.post(path).json(&sealed).await.status_code(), 403);
+    }
+}
+
+#[tokio::test]
+async fn test_reshare_needs_every_old_holders_watermarks() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_reshare_needs_every_old_holders_watermarks",
+    );
+    // Old shares 0 and 1 could be split, but the holder of share 2 does not answer
+    let urls = vec![
+        super::leader_sign_helper::spawn_worker(),
+        super::leader_sign_helper::spawn_worker(),
+        super::leader_sign_helper::dead_url(),
+    ];
+    let (pk_set, _) = super::leader_sign_helper::split_across_workers(&urls);
+    let bls_pk_hex = pk_set.public_key().to_hex();
+    let key = puffersecuresigner::io::threshold_keys::read_threshold_key(&bls_pk_hex).unwrap();
+    let new_workers: Vec<_> = (0..3)
+        .map(|_| {
+            let pk_hex = puffersecuresigner::to_0x_hex(
+                eth_keys::new_eth_key().unwrap().1.serialize_compressed(),
+            );
+            puffersecuresigner::io::workers::register_worker(
+                &super::leader_sign_helper::dead_url(),
+                &pk_hex,
+                Default::default(),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    // So its watermarks are unknown and no share is split
+    let state = super::signing_helper::mock_app_state();
+    let e = state
+        .leader
+        .reshare(&key, 2, new_workers)
+        .await
+        .unwrap_err();
+    let failed = e
+        .downcast_ref::<puffersecuresigner::enclave::secure_signer::leader::ReshareFailed>()
+        .unwrap();
+    assert_eq!(failed.failures.len(), 1);
+    assert!(failed.failures[0].starts_with("share 2"));
+    assert_eq!(
+        puffersecuresigner::io::threshold_keys::read_threshold_key(&bls_pk_hex)
+            .unwrap()
+            .verification_vector,
+        key.verification_vector
+    );
+}
@@ -0,0 +1,72 @@
+use puffersecuresigner::enclave::shared::shutdown::serve_until;
+use std::time::Duration;
+
+/// Serves a route that takes `delay` to answer until the returned sender fires
+fn spawn_slow_server(
+    delay: Duration,
+    grace: Duration,
+) -> (
+    u16,
+    tokio::sync::oneshot::Sender<()>,
+    tokio::task::JoinHandle<anyhow::Result<()>>,
+) {
+    let app = axum::Router::new().route(
+        "/slow",
+        axum::routing::get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    );
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_until(
+        listener,
+        app,
+        None,
+        async move {
+            let _ = stopped.await;
+        },
+        grace,
+    ));
+    (port, stop, server)
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_in_flight_requests() {
+    let (port, stop, server) =
+        spawn_slow_server(Duration::from_millis(500), Duration::from_secs(5));
+    let in_flight = tokio::spawn(reqwest::get(format!("http://127.0.0.1:{port}/slow")));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    stop.send(()).unwrap();
+    let resp = in_flight.await.unwrap().unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().await.unwrap(), "done");
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    // No new connections are accepted once stopped
+    assert!(reqwest::get(format!("http://127.0.0.1:{port}/slow"))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_is_bounded_by_the_grace_period() {
+    let (port, stop, server) =
+        spawn_slow_server(Duration::from_secs(60), Duration::from_millis(200));
+    let in_flight = tokio::spawn(reqwest::get(format!("http://127.0.0.1:{port}/slow")));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    stop.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(in_flight.await.unwrap().is_err());
+}
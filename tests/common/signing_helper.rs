@@ -3,13 +3,61 @@ use super::bls_keygen_helper::register_new_bls_key;
 use super::read_secure_signer_port;
 
 use anyhow::{Context, Result};
-use puffersecuresigner::eth2::{eth_signing::BLSSignMsg, eth_types::GENESIS_FORK_VERSION};
+use puffersecuresigner::eth2::{
+    eth_signing::BLSSignMsg,
+    eth_types::{Root, GENESIS_FORK_VERSION},
+};
 use reqwest::{Client, Response, StatusCode};
 use serde_json;
 
 pub async fn mock_secure_sign_route(
     bls_pk: &String,
     signing_data: BLSSignMsg,
+) -> Result<axum_test::TestResponse> {
+    mock_secure_sign_route_with_accept(bls_pk, signing_data, Some("application/json")).await
+}
+
+pub async fn mock_secure_sign_route_with_accept(
+    bls_pk: &String,
+    signing_data: BLSSignMsg,
+    accept: Option<&str>,
+) -> Result<axum_test::TestResponse> {
+    mock_secure_sign_route_with_state(bls_pk, signing_data, accept, mock_app_state()).await
+}
+
+pub fn mock_app_state() -> puffersecuresigner::enclave::shared::handlers::AppState {
+    puffersecuresigner::enclave::shared::handlers::AppState {
+        genesis_fork_version: GENESIS_FORK_VERSION,
+        genesis_validators_root: Root::default(),
+        network: None,
+        gas_limit_policy: Default::default(),
+        slashing_floor: Default::default(),
+        slashing_prune_window: puffersecuresigner::constants::DEFAULT_SLASHING_PRUNE_WINDOW,
+        slot_clock: None,
+        fork_schedule: None,
+        strict_fork_schedule: false,
+        verify_selection_proofs: false,
+        max_randao_lookahead: None,
+        sign_locks: Default::default(),
+        signature_counts: Default::default(),
+        randao_reveal_epochs: Default::default(),
+        validator_configs: Default::default(),
+        slashing_backend: std::sync::Arc::new(
+            puffersecuresigner::eth2::slash_protection::FileBackend,
+        ),
+        slashing_protection_intact: true,
+        data_dir: puffersecuresigner::io::data_dir::data_dir(),
+        config: Default::default(),
+        leader: Default::default(),
+        worker: Default::default(),
+    }
+}
+
+pub async fn mock_secure_sign_route_with_state(
+    bls_pk: &String,
+    signing_data: BLSSignMsg,
+    accept: Option<&str>,
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
 ) -> Result<axum_test::TestResponse> {
     let uri = format!("/api/v1/eth2/sign/{}", bls_pk);
     let test_app = axum::Router::new()
@@ -19,14 +67,46 @@ pub async fn mock_secure_sign_route(
                 puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
             ),
         )
-        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
-            genesis_fork_version: GENESIS_FORK_VERSION,
-        })
+        .with_state(state)
         .into_make_service();
 
     let server = axum_test::TestServer::new(test_app)?;
 
-    Ok(server.post(&uri).json(&signing_data).await)
+    let mut req = server.post(&uri).json(&signing_data);
+    if let Some(accept) = accept {
+        req = req.add_header(
+            axum::http::header::ACCEPT,
+            axum::http::HeaderValue::from_str(accept)?,
+        );
+    }
+    Ok(req.await)
+}
+
+/// Posts `body` as is, so requests that do not deserialize into a BLSSignMsg can be tested
+pub async fn mock_secure_sign_route_raw(
+    bls_pk: &String,
+    body: String,
+) -> Result<axum_test::TestResponse> {
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk);
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .layer(axum::extract::DefaultBodyLimit::max(
+            puffersecuresigner::constants::DEFAULT_MAX_SIGN_BODY_BYTES,
+        ))
+        .with_state(mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+    Ok(server
+        .post(&uri)
+        .bytes(body.into())
+        .content_type("application/json")
+        .await)
 }
 
 /// Makes a request to Secure-Aggregator aggregate_route on the specified port
@@ -46,6 +126,7 @@ pub async fn request_secure_sign_route(
     let response = client
         .post(&url)
         .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
         .body(json_req.clone())
         .send()
         .await;
@@ -87,6 +168,7 @@ pub async fn make_signing_route_request(
 
 #[tokio::test]
 async fn test_sign_route() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_sign_route");
     let port = read_secure_signer_port();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
 
@@ -102,7 +184,7 @@ async fn test_sign_route() {
                 }},
                 "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
             }},
-            "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
+            "signingRoot": "0xa66cf8c7a50c5b8b175aff4822e8b1aac5798f5add50c192931dc6e79baed060",
             "attestation": {{
                 "slot": "255",
                 "index": "65535",
@@ -126,3 +208,203 @@ async fn test_sign_route() {
     _ = resp.unwrap();
     assert_eq!(status, 200);
 }
+
+#[tokio::test]
+async fn test_sign_route_unknown_pk_returns_json_error() {
+    let bls_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let req: BLSSignMsg = serde_json::from_str(
+        r#"
+        {
+            "type": "RANDAO_REVEAL",
+            "fork_info":{
+                "fork":{
+                   "previous_version":"0x00000000",
+                   "current_version":"0x00000000",
+                   "epoch":"0"
+                },
+                "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+            },
+            "randao_reveal":{
+                "epoch": "10"
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let resp = mock_secure_sign_route(&bls_pk_hex, req).await.unwrap();
+    assert_eq!(resp.status_code(), 404);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 404);
+    assert!(err.message.contains(&bls_pk_hex));
+}
+
+#[cfg(feature = "min-sig")]
+#[tokio::test]
+async fn test_sign_route_signs_under_min_sig() {
+    use puffersecuresigner::crypto::bls_variant::{BlsVariant, MinSig};
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sign_route_signs_under_min_sig",
+    );
+    let keygen = register_new_bls_key(None).await;
+    let signing_pk = hex::decode(keygen.signing_pk.unwrap().trim_start_matches("0x")).unwrap();
+    assert_eq!(signing_pk.len(), MinSig::PUB_KEY_BYTES);
+
+    let req = mock_randao_reveal();
+    let root = req.to_signing_root(None);
+    let resp = mock_secure_sign_route(&keygen.pk_hex, req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let sig: puffersecuresigner::enclave::types::SignatureResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    let sig = hex::decode(sig.signature.trim_start_matches("0x")).unwrap();
+    assert_eq!(sig.len(), MinSig::SIG_BYTES);
+    assert!(MinSig::verify(&signing_pk, &root, &sig));
+}
+
+fn mock_attestation_with_signing_root(signing_root: &str) -> BLSSignMsg {
+    let req = format!(
+        r#"
+        {{
+            "type": "ATTESTATION",
+            "fork_info":{{
+                "fork":{{
+                   "previous_version":"0x00000001",
+                   "current_version":"0x00000001",
+                   "epoch":"0"
+                }},
+                "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+            }},
+            "signingRoot": "{signing_root}",
+            "attestation": {{
+                "slot": "255",
+                "index": "65535",
+                "beacon_block_root": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
+                "source": {{
+                    "epoch": "10",
+                    "root": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+                }},
+                "target": {{
+                    "epoch": "11",
+                    "root": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+                }}
+            }}
+        }}"#
+    );
+    serde_json::from_str(&req).unwrap()
+}
+
+#[tokio::test]
+async fn test_sign_route_rejects_mismatched_signing_root() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sign_route_rejects_mismatched_signing_root",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let computed = "0xa66cf8c7a50c5b8b175aff4822e8b1aac5798f5add50c192931dc6e79baed060";
+    let provided = "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69";
+
+    let req = mock_attestation_with_signing_root(provided);
+    let resp = mock_secure_sign_route(&bls_pk_hex, req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    let err: puffersecuresigner::enclave::types::SigningRootMismatch =
+        serde_json::from_value(err.details.unwrap()).unwrap();
+    assert_eq!(err.provided_signing_root, provided);
+    assert_eq!(err.computed_signing_root, computed);
+
+    // The rejected request must not have advanced the slashing protection watermarks
+    let req = mock_attestation_with_signing_root(computed);
+    let resp = mock_secure_sign_route(&bls_pk_hex, req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
+
+fn mock_randao_reveal() -> BLSSignMsg {
+    serde_json::from_str(
+        r#"
+        {
+            "type": "RANDAO_REVEAL",
+            "fork_info":{
+                "fork":{
+                   "previous_version":"0x00000000",
+                   "current_version":"0x00000000",
+                   "epoch":"0"
+                },
+                "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+            },
+            "randao_reveal":{
+                "epoch": "10"
+            }
+        }"#,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_sign_route_accepts_any_pubkey_hex_form() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sign_route_accepts_any_pubkey_hex_form",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let bare = bls_pk_hex.trim_start_matches("0x").to_string();
+
+    let mut sigs = vec![];
+    for pk_hex in [
+        bls_pk_hex.clone(),
+        bare.clone(),
+        format!("0X{}", bare.to_uppercase()),
+        format!("0x{}", bare.to_uppercase()),
+    ] {
+        let resp = mock_secure_sign_route(&pk_hex, mock_randao_reveal())
+            .await
+            .unwrap();
+        assert_eq!(resp.status_code(), 200, "signing with {pk_hex}");
+        let sig: puffersecuresigner::enclave::types::SignatureResponse =
+            serde_json::from_slice(resp.as_bytes()).unwrap();
+        assert!(sig.signature.starts_with("0x"));
+        assert_eq!(sig.signature, sig.signature.to_lowercase());
+        sigs.push(sig.signature);
+    }
+    sigs.dedup();
+    assert_eq!(sigs.len(), 1);
+
+    // Malformed hex is refused rather than looked up
+    let resp = mock_secure_sign_route(&format!("0x{}zz", &bare[2..]), mock_randao_reveal())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_sign_route_accept_negotiation() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_sign_route_accept_negotiation");
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+
+    // application/json gets the JSON object
+    let resp = mock_secure_sign_route_with_accept(
+        &bls_pk_hex,
+        mock_randao_reveal(),
+        Some("application/json"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let json_sig: puffersecuresigner::enclave::types::SignatureResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+
+    // text/plain and a missing Accept both get the bare signature
+    for accept in [Some("text/plain"), None] {
+        let resp = mock_secure_sign_route_with_accept(&bls_pk_hex, mock_randao_reveal(), accept)
+            .await
+            .unwrap();
+        assert_eq!(resp.status_code(), 200);
+        assert_eq!(resp.text(), json_sig.signature);
+    }
+
+    // anything else is refused
+    let resp =
+        mock_secure_sign_route_with_accept(&bls_pk_hex, mock_randao_reveal(), Some("text/html"))
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 406);
+}
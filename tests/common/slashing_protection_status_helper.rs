@@ -0,0 +1,158 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::read_secure_signer_port;
+use super::signing_helper::{mock_app_state, mock_secure_sign_route_with_state};
+
+use anyhow::{Context, Result};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::types::{
+    ListSlashingProtectionStatusResponse, SlashingProtectionStatusResponse,
+};
+use reqwest::{Client, Response, StatusCode};
+
+fn mock_slashing_protection_status_app(
+    state: AppState,
+) -> axum::routing::IntoMakeService<axum::Router> {
+    axum::Router::new()
+        .route(
+            "/eth/v1/slashing-protection-status",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_slashing_protection_status::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/slashing-protection/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::get_slashing_protection_status::handler,
+            ),
+        )
+        .with_state(state)
+        .into_make_service()
+}
+
+pub async fn mock_slashing_protection_status_route(
+    path: &str,
+    state: AppState,
+) -> Result<axum_test::TestResponse> {
+    let server = axum_test::TestServer::new(mock_slashing_protection_status_app(state))?;
+    Ok(server.get(path).await)
+}
+
+pub async fn request_slashing_protection_status_route(
+    path: &str,
+    port: u16,
+) -> Result<Response, reqwest::Error> {
+    let client = Client::new();
+    let url = format!("http://localhost:{}{}", port, path);
+    client.get(&url).send().await
+}
+
+async fn make_slashing_protection_status_request(
+    path: &str,
+    port: Option<u16>,
+) -> Result<(Vec<u8>, StatusCode)> {
+    match port {
+        // Make the actual http req to a running Secure-Signer instance
+        Some(p) => {
+            let resp = request_slashing_protection_status_route(path, p).await?;
+            let status = resp.status();
+            let bytes = resp
+                .bytes()
+                .await
+                .with_context(|| "Failed to read slashing protection status response")?;
+            Ok((bytes.to_vec(), status))
+        }
+        // Mock an http request
+        None => {
+            let resp = mock_slashing_protection_status_route(path, mock_app_state()).await?;
+            Ok((resp.as_bytes().to_vec(), resp.status_code()))
+        }
+    }
+}
+
+pub async fn make_get_slashing_protection_status_request(
+    bls_pk_hex: &str,
+    port: Option<u16>,
+) -> Result<(Option<SlashingProtectionStatusResponse>, StatusCode)> {
+    let path = format!("/eth/v1/slashing-protection/{}", bls_pk_hex);
+    let (bytes, status) = make_slashing_protection_status_request(&path, port).await?;
+    Ok((serde_json::from_slice(&bytes).ok(), status))
+}
+
+pub async fn make_list_slashing_protection_status_request(
+    port: Option<u16>,
+) -> Result<(Option<ListSlashingProtectionStatusResponse>, StatusCode)> {
+    let (bytes, status) =
+        make_slashing_protection_status_request("/eth/v1/slashing-protection-status", port).await?;
+    Ok((serde_json::from_slice(&bytes).ok(), status))
+}
+
+#[tokio::test]
+async fn test_slashing_protection_status_of_new_key() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slashing_protection_status_of_new_key",
+    );
+    let port = read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    let (resp, status) = make_get_slashing_protection_status_request(&bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let data = resp.unwrap().data;
+    assert_eq!(data.pubkey, format!("0x{}", bls_pk_hex));
+    assert!(data.last_signed_block_slot.is_none());
+    assert!(data.last_signed_attestation_source_epoch.is_none());
+    assert!(data.last_signed_attestation_target_epoch.is_none());
+    assert_eq!(data.signature_count, "0");
+
+    let (resp, status) = make_list_slashing_protection_status_request(port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert!(resp
+        .unwrap()
+        .data
+        .iter()
+        .any(|d| d.pubkey == format!("0x{}", bls_pk_hex)));
+
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let (_, status) = make_get_slashing_protection_status_request(&unknown_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_slashing_protection_status_tracks_signing() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slashing_protection_status_tracks_signing",
+    );
+    // The signature count lives in the AppState, so only the mocked routes can share it
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let state = mock_app_state();
+
+    let block = crate::signing_tests::block::mock_propose_block_request(1234);
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        serde_json::from_str(&block).unwrap(),
+        Some("application/json"),
+        state.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    let resp = mock_slashing_protection_status_route(
+        &format!("/eth/v1/slashing-protection/0x{}", bls_pk_hex),
+        state.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let data = serde_json::from_slice::<SlashingProtectionStatusResponse>(resp.as_bytes())
+        .unwrap()
+        .data;
+    assert_eq!(data.last_signed_block_slot, Some("1234".to_string()));
+    assert!(data.last_signed_attestation_target_epoch.is_none());
+    assert_eq!(data.signature_count, "1");
+}
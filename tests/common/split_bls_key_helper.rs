@@ -0,0 +1,107 @@
+use super::bls_keygen_helper::register_new_bls_key;
+
+use anyhow::Result;
+use blsttc::{PublicKeySet, SecretKeyShare, SignatureShare};
+use puffersecuresigner::crypto::{bls_keys, eth_keys};
+use puffersecuresigner::enclave::types::{KeyGenResponse, SplitBlsKeyRequest, SplitBlsKeyResponse};
+use puffersecuresigner::io::remote_attestation::AttestationEvidence;
+use std::collections::BTreeMap;
+
+pub async fn mock_split_bls_key_route(req: &SplitBlsKeyRequest) -> Result<axum_test::TestResponse> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores/split",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::split_bls_key::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    let server = axum_test::TestServer::new(test_app)?;
+
+    Ok(server.post("/eth/v1/keystores/split").json(req).await)
+}
+
+#[tokio::test]
+async fn test_split_bls_key_for_workers() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_split_bls_key_for_workers");
+    let (n, t) = (4, 3);
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+
+    // Workers would send their attested ETH keygen responses
+    let worker_keys: Vec<_> = (0..n).map(|_| eth_keys::new_eth_key().unwrap()).collect();
+    let workers: Vec<KeyGenResponse> = worker_keys
+        .iter()
+        .map(|(_, pk)| KeyGenResponse {
+            pk_hex: eth_keys::eth_pk_to_hex_uncompressed(pk),
+            evidence: AttestationEvidence::default(),
+            address: None,
+            signing_pk: None,
+        })
+        .collect();
+    let mut req = SplitBlsKeyRequest {
+        bls_pk_hex: bls_pk_hex.clone(),
+        n: None,
+        threshold: t,
+        workers,
+        mrenclave: None,
+    };
+
+    let resp = mock_split_bls_key_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let split: SplitBlsKeyResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(split.bls_pk_hex, bls_pk_hex);
+    assert_eq!(split.shares.len(), n);
+    let vv = split.verification_vector.strip_prefix("0x").unwrap();
+    let pk_set = PublicKeySet::from_bytes(hex::decode(vv).unwrap()).unwrap();
+
+    // Each worker decrypts its share and checks it against the verification vector
+    let msg = b"hello puffer";
+    let mut partials = BTreeMap::new();
+    for ((eth_sk, _), share) in worker_keys.iter().zip(split.shares.iter()) {
+        let ct = hex::decode(share.encrypted_sk_share_hex.strip_prefix("0x").unwrap()).unwrap();
+        let sk_bytes = eth_keys::envelope_decrypt(eth_sk, &ct).unwrap();
+        let sk_share = SecretKeyShare::from_bytes(sk_bytes[..].try_into().unwrap()).unwrap();
+        assert_eq!(
+            sk_share.public_key_share(),
+            pk_set.public_key_share(share.index)
+        );
+        let partial: SignatureShare = sk_share.sign(msg);
+        partials.insert(share.index, partial);
+    }
+
+    // t partials recombine into a signature under the original key, t - 1 do not
+    let enough: BTreeMap<_, _> = partials.clone().into_iter().skip(1).collect();
+    let sig = bls_keys::combine_partial_signatures(&pk_set, &enough, msg).unwrap();
+    let sig_bytes = sig.to_bytes();
+    assert!(bls_keys::bls_verify(&bls_pk_hex, msg, &sig_bytes).unwrap());
+    let too_few: BTreeMap<_, _> = enough.into_iter().take(t - 1).collect();
+    assert!(bls_keys::combine_partial_signatures(&pk_set, &too_few, msg).is_err());
+
+    // A threshold above the number of workers is refused
+    req.threshold = n + 1;
+    let resp = mock_split_bls_key_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    // A single share could sign alone, so a threshold of 1 is refused
+    req.threshold = 1;
+    let resp = mock_split_bls_key_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    // So is an n other than the number of workers given
+    req.threshold = t;
+    req.n = Some(n + 1);
+    let resp = mock_split_bls_key_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    // So are unknown keys
+    req.n = Some(n);
+    req.bls_pk_hex = format!(
+        "0x{}",
+        bls_keys::new_bls_key(0).public_keys().public_key().to_hex()
+    );
+    let resp = mock_split_bls_key_route(&req).await.unwrap();
+    assert_eq!(resp.status_code(), 404);
+}
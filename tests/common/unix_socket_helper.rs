@@ -0,0 +1,109 @@
+use puffersecuresigner::enclave::shared::unix_socket::serve_unix_until;
+use std::os::unix::fs::PermissionsExt;
+use std::time::Duration;
+
+/// Serves the health route and an echo route that stand in for the API
+fn mock_app() -> axum::Router {
+    axum::Router::new()
+        .route(
+            "/upcheck",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
+        )
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                |axum::extract::Path(bls_pk_hex): axum::extract::Path<String>, body: String| async move {
+                    format!("{bls_pk_hex}:{body}")
+                },
+            ),
+        )
+}
+
+/// Sends one request over a new connection to the socket at `path`
+async fn request_over_socket(
+    path: &std::path::Path,
+    req: hyper::Request<hyper::Body>,
+) -> (u16, String) {
+    let stream = tokio::net::UnixStream::connect(path).await.unwrap();
+    let (mut sender, conn) = hyper::client::conn::handshake(stream).await.unwrap();
+    tokio::spawn(conn);
+    let resp = sender.send_request(req).await.unwrap();
+    let status = resp.status().as_u16();
+    let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_unix_socket_serves_the_same_routes() {
+    let path = std::env::temp_dir().join(format!("secure-signer-{}.sock", std::process::id()));
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = {
+        let path = path.clone();
+        tokio::spawn(async move {
+            serve_unix_until(
+                &path,
+                mock_app(),
+                async move {
+                    let _ = stopped.await;
+                },
+                Duration::from_secs(1),
+            )
+            .await
+        })
+    };
+    while !path.exists() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    // The same answers as over TCP
+    let tcp = axum_test::TestServer::new(mock_app().into_make_service()).unwrap();
+    let (status, body) = request_over_socket(
+        &path,
+        hyper::Request::get("/upcheck")
+            .body(hyper::Body::empty())
+            .unwrap(),
+    )
+    .await;
+    let resp = tcp.get("/upcheck").await;
+    assert_eq!(status, resp.status_code().as_u16());
+    assert_eq!(body, resp.text());
+
+    let (status, body) = request_over_socket(
+        &path,
+        hyper::Request::post("/api/v1/eth2/sign/0x1234")
+            .body(hyper::Body::from("msg"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "0x1234:msg");
+    let (status, _) = request_over_socket(
+        &path,
+        hyper::Request::get("/unknown")
+            .body(hyper::Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, tcp.get("/unknown").await.status_code().as_u16());
+
+    // The socket is removed on shutdown
+    stop.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_unix_socket_never_replaces_other_files() {
+    let path = std::env::temp_dir().join(format!("secure-signer-{}.txt", std::process::id()));
+    std::fs::write(&path, "keep me").unwrap();
+    let served = serve_unix_until(&path, mock_app(), async {}, Duration::ZERO).await;
+    assert!(served.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use puffersecuresigner::enclave::types::KeyGenResponse;
+use puffersecuresigner::io::remote_attestation::{AttestationVerdict, EvidenceKind};
+
+pub fn mock_verify_attestation_app() -> Result<axum_test::TestServer> {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/verify-attestation",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::verify_attestation::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+
+    axum_test::TestServer::new(test_app)
+}
+
+#[tokio::test]
+async fn test_verify_attestation_rejects_unsigned_evidence() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_verify_attestation_rejects_unsigned_evidence",
+    );
+    let server = mock_verify_attestation_app().unwrap();
+
+    // Outside of SGX keygen evidence is empty, so it cannot verify
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+    let key: KeyGenResponse = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let resp = server
+        .post("/eth/v1/verify-attestation")
+        .json(&key.evidence)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let verdict: AttestationVerdict = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(!verdict.valid);
+    assert_eq!(verdict.kind, EvidenceKind::Epid);
+    assert!(verdict.error.is_some());
+
+    let resp = server
+        .post("/eth/v1/verify-attestation")
+        .json(&serde_json::json!({
+            "kind": "dcap",
+            "raw_report": "",
+            "signed_report": "",
+            "signing_cert": "",
+            "quote": "AwACAAAAAAA=",
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let verdict: AttestationVerdict = serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert!(!verdict.valid);
+    assert_eq!(verdict.kind, EvidenceKind::Dcap);
+    assert!(verdict.mrenclave.is_none());
+
+    let resp = server
+        .post("/eth/v1/verify-attestation")
+        .json(&serde_json::json!({ "kind": "tdx" }))
+        .await;
+    assert!(resp.status_code().is_client_error());
+}
@@ -0,0 +1,114 @@
+use super::bls_keygen_helper::register_new_bls_key;
+use super::signing_helper::{mock_secure_sign_route, mock_secure_sign_route_with_accept};
+use puffersecuresigner::eth2::eth_signing::{BLSSignMsg, BlockRequest};
+
+/// The sign route's bodies as Web3Signer's OpenAPI documents them, which validator clients such
+/// as Lighthouse parse. `{signature}` and `{pubkey}` are filled in per test.
+pub const SIGNATURE_JSON: &str = r#"{"signature":"{signature}"}"#;
+pub const NOT_FOUND_JSON: &str = r#"{"code":404,"message":"Public key not found: {pubkey}"}"#;
+pub const SLASHABLE_JSON: &str =
+    r#"{"code":412,"message":"Signing operation failed due to slashing protection rules"}"#;
+
+fn block_request(slot: u64) -> BLSSignMsg {
+    let req: BlockRequest = serde_json::from_str(
+        &crate::signing_tests::block::mock_propose_block_request(slot),
+    )
+    .unwrap();
+    BLSSignMsg::BLOCK(req)
+}
+
+fn content_type(resp: &axum_test::TestResponse) -> String {
+    resp.header(axum::http::header::CONTENT_TYPE)
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_web3signer_signature_bodies() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_web3signer_signature_bodies");
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_request(100))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(content_type(&resp), "application/json");
+    let body: serde_json::Value = serde_json::from_slice(resp.as_bytes()).unwrap();
+    let signature = body["signature"].as_str().unwrap();
+    assert_eq!(
+        signature.len(),
+        2 + 2 * puffersecuresigner::constants::BLS_SIG_BYTES
+    );
+    assert_eq!(
+        resp.text(),
+        SIGNATURE_JSON.replace("{signature}", signature)
+    );
+
+    // text/plain is the bare signature, with nothing around it
+    let resp =
+        mock_secure_sign_route_with_accept(&bls_pk_hex, block_request(101), Some("text/plain"))
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(content_type(&resp), "text/plain; charset=utf-8");
+    assert!(resp.text().starts_with("0x"));
+    assert_eq!(resp.text().len(), signature.len());
+}
+
+#[tokio::test]
+async fn test_web3signer_error_bodies() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_web3signer_error_bodies");
+    let unknown_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let resp = mock_secure_sign_route(&unknown_pk_hex, block_request(1))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 404);
+    assert_eq!(content_type(&resp), "application/json");
+    assert_eq!(
+        resp.text(),
+        NOT_FOUND_JSON.replace("{pubkey}", &format!("0x{unknown_pk_hex}"))
+    );
+
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_request(100))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_request(99))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 412);
+    assert_eq!(content_type(&resp), "application/json");
+    assert_eq!(resp.text(), SLASHABLE_JSON);
+}
+
+#[tokio::test]
+async fn test_sign_route_only_answers_documented_statuses() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sign_route_only_answers_documented_statuses",
+    );
+    // Refusals Web3Signer has no code for map onto the ones it documents: a far future slot is
+    // refused to protect the key like a slashable one
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = super::signing_helper::mock_app_state();
+    state.slot_clock = Some(puffersecuresigner::eth2::slash_protection::SlotClock {
+        genesis_time: 0,
+        seconds_per_slot: 12,
+        max_future_slots: 10,
+    });
+    let resp = super::signing_helper::mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        block_request(1 << 62),
+        Some("application/json"),
+        state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 412);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 412);
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use puffersecuresigner::enclave::types::ListWorkersResponse;
+
+pub fn mock_workers_app(
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> Result<axum_test::TestServer> {
+    let test_app = axum::Router::new()
+        .route(
+            "/leader/v1/workers",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::list_workers::handler,
+            )
+            .post(puffersecuresigner::enclave::secure_signer::handlers::register_worker::handler),
+        )
+        .with_state(state)
+        .into_make_service();
+
+    axum_test::TestServer::new(test_app)
+}
+
+async fn list_workers(server: &axum_test::TestServer) -> ListWorkersResponse {
+    let resp = server.get("/leader/v1/workers").await;
+    assert_eq!(resp.status_code(), 200);
+    serde_json::from_slice(resp.as_bytes()).unwrap()
+}
+
+#[tokio::test]
+async fn test_register_worker_refuses_bad_evidence() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_register_worker_refuses_bad_evidence",
+    );
+    let server = mock_workers_app(super::signing_helper::mock_app_state()).unwrap();
+    assert!(list_workers(&server).await.data.is_empty());
+
+    let ecies_pk = puffersecuresigner::crypto::eth_keys::new_eth_key()
+        .unwrap()
+        .1;
+    let ecies_pk_hex = puffersecuresigner::to_0x_hex(ecies_pk.serialize_compressed());
+
+    // Outside of SGX evidence is unsigned, so whatever the measurement policy it is refused
+    let resp = server
+        .post("/leader/v1/workers")
+        .json(&serde_json::json!({
+            "url": "http://worker-1:9001",
+            "ecies_pubkey": ecies_pk_hex,
+            "evidence": { "raw_report": "", "signed_report": "", "signing_cert": "" },
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 403);
+    let err: puffersecuresigner::enclave::types::ErrorResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(err.code, 403);
+    assert!(list_workers(&server).await.data.is_empty());
+
+    // Bad urls and keys are refused before the evidence is looked at
+    let resp = server
+        .post("/leader/v1/workers")
+        .json(&serde_json::json!({
+            "url": "worker-1:9001",
+            "ecies_pubkey": ecies_pk_hex,
+            "evidence": { "raw_report": "", "signed_report": "", "signing_cert": "" },
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    let resp = server
+        .post("/leader/v1/workers")
+        .json(&serde_json::json!({
+            "url": "http://worker-1:9001",
+            "ecies_pubkey": "0x1234",
+            "evidence": { "raw_report": "", "signed_report": "", "signing_cert": "" },
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(list_workers(&server).await.data.is_empty());
+}
+
+#[tokio::test]
+async fn test_worker_status_lists_key_shares() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_worker_status_lists_key_shares",
+    );
+    let sk = blsttc::SecretKey::random();
+    let pk_hex = sk.public_key().to_hex();
+    puffersecuresigner::io::key_management::write_bls_key(&pk_hex, &hex::encode(sk.to_bytes()))
+        .unwrap();
+
+    let test_app = axum::Router::new()
+        .route(
+            "/worker/v1/status",
+            axum::routing::get(
+                puffersecuresigner::enclave::secure_signer::handlers::worker_status::handler,
+            ),
+        )
+        .with_state(super::signing_helper::mock_app_state())
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+    let resp = server.get("/worker/v1/status").await;
+    assert_eq!(resp.status_code(), 200);
+    let status: puffersecuresigner::enclave::types::WorkerStatusResponse =
+        serde_json::from_slice(resp.as_bytes()).unwrap();
+    assert_eq!(status.key_shares, vec![format!("0x{pk_hex}")]);
+}
+
+#[tokio::test]
+async fn test_heartbeats_mark_silent_workers_unhealthy() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_heartbeats_mark_silent_workers_unhealthy",
+    );
+    let register = |url: String| {
+        let pk = puffersecuresigner::crypto::eth_keys::new_eth_key()
+            .unwrap()
+            .1;
+        let pk_hex = puffersecuresigner::to_0x_hex(pk.serialize_compressed());
+        puffersecuresigner::io::workers::register_worker(&url, &pk_hex, Default::default()).unwrap()
+    };
+    let alive = register(super::leader_sign_helper::spawn_worker());
+    let dead = register(super::leader_sign_helper::dead_url());
+
+    let state = super::signing_helper::mock_app_state();
+    let client = reqwest::Client::new();
+    let max_missed = state.leader.max_missed_heartbeats();
+    for missed in 1..=max_missed {
+        state.leader.poll_workers(&client).await.unwrap();
+        let health = state.leader.worker_health(&dead.id);
+        assert_eq!(health.consecutive_failures, missed);
+        assert_eq!(health.is_healthy(max_missed), missed < max_missed);
+    }
+
+    let server = mock_workers_app(state).unwrap();
+    let workers = list_workers(&server).await.data;
+    let listed = |id: &str| workers.iter().find(|w| w.id == id).unwrap().clone();
+    assert!(listed(&alive.id).healthy);
+    assert!(listed(&alive.id).latency_ms.is_some());
+    assert!(!listed(&dead.id).healthy);
+    assert_eq!(listed(&dead.id).consecutive_failures, max_missed);
+
+    let (_, body) = puffersecuresigner::enclave::shared::metrics::gather().unwrap();
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains(&format!(
+        "secure_signer_worker_healthy{{worker=\"{}\"}} 0",
+        dead.id
+    )));
+    assert!(body.contains(&format!(
+        "secure_signer_worker_healthy{{worker=\"{}\"}} 1",
+        alive.id
+    )));
+}
@@ -26,7 +26,7 @@ pub fn mock_aggregate_and_proof_request() -> String {
                   }},
                   "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
                }},
-               "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
+               "signingRoot": "0x1d50b7293ee72d9e1c008f9b6b433eee5093a993c8d3a9d45f361fcd3f084e92",
                "aggregate_and_proof":{{
                     "aggregator_index": "5",
                     "aggregate": {{
@@ -66,6 +66,9 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 pub async fn test_aggregate_aggregate_and_proof_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_aggregate_and_proof_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = aggregate_and_proof_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -77,6 +80,9 @@ pub async fn test_aggregate_aggregate_and_proof_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_aggregate_and_proof_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_aggregate_and_proof_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("81e56af6c3b9f0ce1c7fd3545a3d689fc2edd2c9dd5451ea5f345cc57d74de76ed940e373fdccc76150e643edc57bdb0145ad3770d9207164484f86f746fb26f889833106e3e17cd49572eb7938a9e4502bba99c3234f32695f73ef3ed18bb51".to_string());
     let req = aggregate_and_proof_request();
@@ -92,6 +98,9 @@ pub async fn test_aggregate_aggregate_and_proof_happy_path_test_vec() {
 
 #[tokio::test]
 async fn test_aggregate_and_proof_eth2_specs() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_and_proof_eth2_specs",
+    );
     let path: PathBuf = [eth_specs::BASE_DIR, "AggregateAndProof"].iter().collect();
     dbg!(&path);
     let port = common::read_secure_signer_port();
@@ -110,3 +119,67 @@ async fn test_aggregate_and_proof_eth2_specs() {
         assert_eq!(status, 200);
     }
 }
+
+#[tokio::test]
+async fn test_aggregate_and_proof_verifies_selection_proof() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_and_proof_verifies_selection_proof",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = mock_app_state();
+    state.verify_selection_proofs = true;
+
+    let aggregate = |selection_proof: &[u8]| {
+        let mut req = aggregate_and_proof_request();
+        if let BLSSignMsg::AGGREGATE_AND_PROOF(m) = &mut req {
+            m.signingRoot = None;
+            m.aggregate_and_proof.selection_proof = selection_proof.to_vec().into();
+        }
+        req
+    };
+
+    // Have the aggregator sign its own selection proof for the aggregate's slot
+    let mut selection = aggregate(&[0; 96]);
+    let BLSSignMsg::AGGREGATE_AND_PROOF(m) = &mut selection else {
+        unreachable!()
+    };
+    let root = compute_selection_proof_signing_root(
+        m.fork_info.clone(),
+        m.aggregate_and_proof.aggregate.data.slot,
+    );
+    let sk = puffersecuresigner::crypto::bls_keys::fetch_bls_sk(&bls_pk_hex).unwrap();
+    let proof = sk.secret_key().sign(root).to_bytes();
+
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        aggregate(&proof),
+        Some("application/json"),
+        state.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    // A proof over anything but the slot is refused, but only when verification is enabled
+    let wrong_proof = sk.secret_key().sign([0; 32]).to_bytes();
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        aggregate(&wrong_proof),
+        Some("application/json"),
+        state.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 400);
+
+    state.verify_selection_proofs = false;
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        aggregate(&wrong_proof),
+        Some("application/json"),
+        state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
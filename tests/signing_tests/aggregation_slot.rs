@@ -3,6 +3,7 @@ use crate::common::bls_keygen_helper::register_new_bls_key;
 use crate::common::signing_helper::*;
 use puffersecuresigner::eth2::eth_signing::*;
 use puffersecuresigner::eth2::eth_types::*;
+use puffersecuresigner::eth2::slash_protection::{FileBackend, SlashingProtectionBackend};
 use puffersecuresigner::strip_0x_prefix;
 
 fn aggregation_slot_request() -> BLSSignMsg {
@@ -26,7 +27,7 @@ pub fn mock_aggregation_slot_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0xfae6059beffb4b31d35df953a9e129f7da1108525f466f93f334c7d552a75cfb",
             "aggregation_slot": {{
                 "slot": "123123"
             }}
@@ -48,6 +49,9 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 pub async fn test_aggregate_aggregation_slot_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_aggregation_slot_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = aggregation_slot_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -59,6 +63,9 @@ pub async fn test_aggregate_aggregation_slot_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_aggregation_slot_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_aggregation_slot_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("84eaf231b6b98cafebf914888d98a5239ee69b338b2aa6f87d9c7ecf7f602644ffb75f78bc91fe48b85ae6df660a48e916aef96677b809436b0504fe3e85c22b79d686eb46787ffc0a4d37cbdb1ba45f5c8e22d1e43e6429eb151d3099ff1cdb".to_string());
     let req = aggregation_slot_request();
@@ -71,3 +78,38 @@ pub async fn test_aggregate_aggregation_slot_happy_path_test_vec() {
     let got_sig: String = strip_0x_prefix!(sig);
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
+
+#[test]
+fn test_aggregation_slot_signing_root_known_answer() {
+    // Expected root computed independently with the consensus-specs `compute_signing_root`
+    // reference algorithm over DOMAIN_SELECTION_PROOF.
+    let req = aggregation_slot_request();
+    assert_eq!(
+        hex::encode(req.to_signing_root(None)),
+        "fae6059beffb4b31d35df953a9e129f7da1108525f466f93f334c7d552a75cfb"
+    );
+}
+
+#[tokio::test]
+pub async fn test_aggregation_slot_does_not_touch_watermarks() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregation_slot_does_not_touch_watermarks",
+    );
+    let port = None;
+    let req = aggregation_slot_request();
+    assert!(!req.can_be_slashed());
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // Signing the same slot twice succeeds
+    for _ in 0..2 {
+        let (_resp, status) =
+            make_signing_route_request(aggregation_slot_request(), &bls_pk_hex, port)
+                .await
+                .unwrap();
+        assert_eq!(status, 200);
+    }
+
+    let db = FileBackend.read(&bls_pk_hex).unwrap();
+    assert!(db.signed_blocks.is_empty());
+    assert!(db.signed_attestations.is_empty());
+}
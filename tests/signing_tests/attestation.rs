@@ -29,7 +29,6 @@ fn mock_attestation_request(src_epoch: u64, tgt_epoch: u64) -> String {
                 }},
                 "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
             }},
-            "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
             "attestation": {{
                 "slot": "255",
                 "index": "65535",
@@ -60,8 +59,23 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
     assert_eq!(status, 400);
 }
 
+#[tokio::test]
+pub async fn test_aggregate_route_fails_from_unknown_pk_hex() {
+    let port = common::read_secure_signer_port();
+
+    let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    let bls_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+}
+
 #[tokio::test]
 pub async fn test_aggregate_attestation_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_attestation_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -73,6 +87,9 @@ pub async fn test_aggregate_attestation_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_attestation_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_attestation_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("80f9bc73528e2025e8514c89ba468dbe48e8154795c5822fc59c7c3f8982a29a9c5456c87ccdb86765b2759802749fa411c0c52ed542b717a590f77cddafd774d17e94de720f0c21b12d10c969b5141ebad17cffd4af5addec4f8882a200ebf1".to_string());
     let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
@@ -88,6 +105,9 @@ pub async fn test_aggregate_attestation_happy_path_test_vec() {
 
 #[tokio::test]
 pub async fn test_slash_protection_allows_non_slashable_attestation() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_allows_non_slashable_attestation",
+    );
     let port = common::read_secure_signer_port();
     let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -106,6 +126,9 @@ pub async fn test_slash_protection_allows_non_slashable_attestation() {
 
 #[tokio::test]
 pub async fn test_slash_protection_prevents_decreasing_source() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_prevents_decreasing_source",
+    );
     let port = common::read_secure_signer_port();
     let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -122,8 +145,33 @@ pub async fn test_slash_protection_prevents_decreasing_source() {
     assert_eq!(status, 412);
 }
 
+#[tokio::test]
+pub async fn test_slash_protection_resigns_identical_attestation() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_resigns_identical_attestation",
+    );
+    let port = common::read_secure_signer_port();
+    let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    // A validator client retrying the same attestation gets the same signature back
+    let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    let (retry_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.unwrap().signature, retry_resp.unwrap().signature);
+}
+
 #[tokio::test]
 pub async fn test_slash_protection_prevents_same_target() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_prevents_same_target",
+    );
     let port = common::read_secure_signer_port();
     let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -132,8 +180,11 @@ pub async fn test_slash_protection_prevents_same_target() {
         .unwrap();
     assert_eq!(status, 200);
 
-    // mock data for ATTESTATION request (attempt a slashable offense - non-increasing target)
-    let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    // mock data for ATTESTATION request (attempt a slashable offense - double vote on the target)
+    let mut req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    if let BLSSignMsg::ATTESTATION(m) = &mut req {
+        m.attestation.beacon_block_root = [0x42; 32];
+    }
     let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
         .await
         .unwrap();
@@ -142,6 +193,9 @@ pub async fn test_slash_protection_prevents_same_target() {
 
 #[tokio::test]
 pub async fn test_slash_protection_prevents_decreasing_target() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_prevents_decreasing_target",
+    );
     let port = common::read_secure_signer_port();
     let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -158,6 +212,115 @@ pub async fn test_slash_protection_prevents_decreasing_target() {
     assert_eq!(status, 412);
 }
 
+#[tokio::test]
+pub async fn test_fork_info_defaults_to_configured_chain() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_fork_info_defaults_to_configured_chain",
+    );
+    // The fallback fork_info is fixed when Secure-Signer starts, so only the mocked route can
+    // exercise it
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = mock_app_state();
+    state.genesis_fork_version = [0, 0, 0, 1];
+    let explicit = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    if let BLSSignMsg::ATTESTATION(m) = &explicit {
+        state.genesis_validators_root = m.fork_info.genesis_validators_root;
+    }
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(&mock_attestation_request(START_SRC_EPOCH, START_TGT_EPOCH)).unwrap();
+    json.as_object_mut().unwrap().remove("fork_info");
+    let implicit: BLSSignMsg = serde_json::from_value(json).unwrap();
+
+    let mut sigs = vec![];
+    for req in [implicit, explicit] {
+        let resp = mock_secure_sign_route_with_state(
+            &bls_pk_hex,
+            req,
+            Some("application/json"),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status_code(), 200);
+        sigs.push(resp.as_bytes().to_vec());
+    }
+    // Same signing root, so the explicit request is an identical retry
+    assert_eq!(sigs[0], sigs[1]);
+
+    // A fork_info from another chain is refused before slash protection is consulted
+    let mut req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH + 1);
+    if let BLSSignMsg::ATTESTATION(m) = &mut req {
+        m.fork_info.genesis_validators_root = [0x42; 32];
+    }
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req, Some("application/json"), state)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 412);
+}
+
+#[tokio::test]
+pub async fn test_fork_schedule_overrides_or_refuses_wrong_fork_version() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_fork_schedule_overrides_or_refuses_wrong_fork_version",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = mock_app_state();
+    state.fork_schedule = Some(ForkSchedule {
+        forks: vec![ScheduledFork {
+            version: [0, 0, 0, 1],
+            epoch: 0,
+        }],
+    });
+    let wrong_fork = |src, tgt| {
+        let mut req = attestation_req(src, tgt);
+        if let BLSSignMsg::ATTESTATION(m) = &mut req {
+            m.fork_info.fork.current_version = [0, 0, 0, 2];
+        }
+        req
+    };
+
+    // Without strict mode the scheduled fork version is signed under instead
+    let mut sigs = vec![];
+    for req in [
+        attestation_req(START_SRC_EPOCH, START_TGT_EPOCH),
+        wrong_fork(START_SRC_EPOCH, START_TGT_EPOCH),
+    ] {
+        let resp = mock_secure_sign_route_with_state(
+            &bls_pk_hex,
+            req,
+            Some("application/json"),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status_code(), 200);
+        sigs.push(resp.as_bytes().to_vec());
+    }
+    assert_eq!(sigs[0], sigs[1]);
+
+    state.strict_fork_schedule = true;
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        wrong_fork(START_SRC_EPOCH, START_TGT_EPOCH + 1),
+        Some("application/json"),
+        state.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 412);
+
+    let resp = mock_secure_sign_route_with_state(
+        &bls_pk_hex,
+        attestation_req(START_SRC_EPOCH, START_TGT_EPOCH + 1),
+        Some("application/json"),
+        state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
+
 async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
     for i in 1..n {
         let req = attestation_req(i, i);
@@ -170,6 +333,8 @@ async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
 
 #[tokio::test]
 pub async fn perf_tester() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("attestation_perf_tester");
     let n = 1000;
     let port = common::read_secure_signer_port();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -178,6 +343,8 @@ pub async fn perf_tester() {
 
 #[tokio::test]
 async fn test_attestation_eth2_specs() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_attestation_eth2_specs");
     let path: PathBuf = [eth_specs::BASE_DIR, "Attestation"].iter().collect();
     dbg!(&path);
     let msgs = eth_specs::get_all_test_vecs("Attestation").unwrap();
@@ -194,7 +361,7 @@ async fn test_attestation_eth2_specs() {
         if let BLSSignMsg::ATTESTATION(msg) = &msg {
             src = msg.attestation.source.epoch;
             tgt = msg.attestation.target.epoch;
-            if src < last_src || tgt <= last_tgt {
+            if src > tgt || src < last_src || tgt <= last_tgt {
                 slashable = true;
             }
         }
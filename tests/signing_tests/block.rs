@@ -15,7 +15,16 @@ fn block_proposal_request(slot: u64) -> BLSSignMsg {
     BLSSignMsg::BLOCK(signing_data)
 }
 
-fn mock_propose_block_request(slot: u64) -> String {
+/// A block at `slot` that conflicts with block_proposal_request(slot)
+fn conflicting_block_proposal_request(slot: u64) -> BLSSignMsg {
+    let mut req = block_proposal_request(slot);
+    if let BLSSignMsg::BLOCK(m) = &mut req {
+        m.block.state_root = [0x42; 32];
+    }
+    req
+}
+
+pub fn mock_propose_block_request(slot: u64) -> String {
     let req = format!(
         r#"
             {{
@@ -69,8 +78,7 @@ fn mock_propose_block_request(slot: u64) -> String {
                      }},
                      "bls_to_execution_changes": []
                   }}
-               }},
-               "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+               }}
             }}"#
     );
     req
@@ -89,6 +97,8 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_happy_path() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_aggregate_block_happy_path");
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -100,6 +110,9 @@ pub async fn test_aggregate_block_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_block_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("a156ad93565d3a5d9e79f36a55f335a9c589d6428613ef067620d50185121f7b6ab8e54acc86d67a66c0addb25107c5509f6f35cc1f98651c24c673227197d98dcfd9a93e9672d19b37c25c8b1ccefad70ca42052dd76e3b59713c074ddf4d22".to_string());
     let req = block_proposal_request(START_SLOT);
@@ -115,6 +128,9 @@ pub async fn test_aggregate_block_happy_path_test_vec() {
 
 #[tokio::test]
 pub async fn test_slash_protection_allows_increasing_slot() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_allows_increasing_slot",
+    );
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -131,8 +147,33 @@ pub async fn test_slash_protection_allows_increasing_slot() {
     assert_eq!(status, 200);
 }
 
+#[tokio::test]
+pub async fn test_slash_protection_resigns_identical_block() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_resigns_identical_block",
+    );
+    let port = common::read_secure_signer_port();
+    let req = block_proposal_request(START_SLOT);
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    // A validator client retrying the same block gets the same signature back
+    let req = block_proposal_request(START_SLOT);
+    let (retry_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(resp.unwrap().signature, retry_resp.unwrap().signature);
+}
+
 #[tokio::test]
 pub async fn test_slash_protection_prevents_duplicate_slot() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_prevents_duplicate_slot",
+    );
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -141,16 +182,26 @@ pub async fn test_slash_protection_prevents_duplicate_slot() {
         .unwrap();
     assert_eq!(status, 200);
 
-    // mock data for BLOCK request (attempt a slashable offense - non-increasing slot)
-    let req = block_proposal_request(START_SLOT);
+    // mock data for BLOCK request (attempt a slashable offense - different block at the same slot)
+    let req = conflicting_block_proposal_request(START_SLOT);
     let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
         .await
         .unwrap();
     assert_eq!(status, 412);
+
+    // The conflicting block does not move the watermark, the original can still be retried
+    let req = block_proposal_request(START_SLOT);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
 }
 
 #[tokio::test]
 pub async fn test_slash_protection_prevents_decreasing_slot() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_prevents_decreasing_slot",
+    );
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -167,6 +218,143 @@ pub async fn test_slash_protection_prevents_decreasing_slot() {
     assert_eq!(status, 412);
 }
 
+#[tokio::test]
+pub async fn test_slash_protection_refuses_truncated_history() {
+    // Corrupts the history on local disk, so only the mocked route can exercise it
+    let port = None;
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slash_protection_refuses_truncated_history",
+    );
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (_resp, status) =
+        make_signing_route_request(block_proposal_request(START_SLOT), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 200);
+
+    // Simulate a crash that left half of the history on disk
+    let pk_hex: String = strip_0x_prefix!(bls_pk_hex);
+    let file_path: PathBuf =
+        puffersecuresigner::io::data_dir::slashing_protection_dir().join(&pk_hex);
+    let json = std::fs::read(&file_path).unwrap();
+    std::fs::write(&file_path, &json[..json.len() / 2]).unwrap();
+
+    // Neither an old nor a new slot may be signed against an unknown watermark
+    for slot in [START_SLOT, START_SLOT + 1] {
+        let (_resp, status) =
+            make_signing_route_request(block_proposal_request(slot), &bls_pk_hex, port)
+                .await
+                .unwrap();
+        assert_eq!(status, 500);
+    }
+}
+
+#[tokio::test]
+pub async fn test_slashing_floor_refuses_lower_slots() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slashing_floor_refuses_lower_slots",
+    );
+    // The floor is fixed when Secure-Signer starts, so only the mocked route can exercise it
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = mock_app_state();
+    state.slashing_floor.min_slot = START_SLOT + 10;
+
+    for (slot, expected) in [(START_SLOT, 412), (START_SLOT + 10, 200)] {
+        let resp = mock_secure_sign_route_with_state(
+            &bls_pk_hex,
+            block_proposal_request(slot),
+            Some("application/json"),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status_code(), expected);
+    }
+}
+
+#[test]
+pub fn test_concurrent_conflicting_blocks_sign_once() {
+    let data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_concurrent_conflicting_blocks_sign_once",
+    );
+    // The requests are served on the worker threads, which must all see the test's data dir
+    let dir = data_dir.path().to_path_buf();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .on_thread_start(move || {
+            std::mem::forget(puffersecuresigner::io::data_dir::ScopedDataDir::enter(&dir))
+        })
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        // Both requests must share one AppState, so only the mocked route can exercise it
+        let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+        let state = mock_app_state();
+
+        let requests: Vec<_> = [
+            block_proposal_request(START_SLOT),
+            conflicting_block_proposal_request(START_SLOT),
+        ]
+        .into_iter()
+        .map(|req| {
+            let (bls_pk_hex, state) = (bls_pk_hex.clone(), state.clone());
+            tokio::spawn(async move {
+                mock_secure_sign_route_with_state(&bls_pk_hex, req, Some("application/json"), state)
+                    .await
+                    .unwrap()
+                    .status_code()
+            })
+        })
+        .collect();
+
+        let mut statuses = vec![];
+        for r in requests {
+            statuses.push(r.await.unwrap().as_u16());
+        }
+        statuses.sort();
+        assert_eq!(statuses, vec![200, 412]);
+    });
+}
+
+#[tokio::test]
+pub async fn test_slot_clock_refuses_far_future_slots() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_slot_clock_refuses_far_future_slots",
+    );
+    // The slot clock is fixed when Secure-Signer starts, so only the mocked route can exercise it
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = mock_app_state();
+    // START_SLOT is the current slot
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    state.slot_clock = Some(puffersecuresigner::eth2::slash_protection::SlotClock {
+        genesis_time: now - 12 * START_SLOT,
+        seconds_per_slot: 12,
+        max_future_slots: 10,
+    });
+
+    // The refused slot must not raise the watermark
+    for (slot, expected) in [
+        (1 << 62, 412),
+        (START_SLOT + 100, 412),
+        (START_SLOT + 5, 200),
+    ] {
+        let resp = mock_secure_sign_route_with_state(
+            &bls_pk_hex,
+            block_proposal_request(slot),
+            Some("application/json"),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status_code(), expected);
+    }
+}
+
 async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
     for i in 1..n {
         let req = block_proposal_request(i);
@@ -179,6 +367,7 @@ async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
 
 #[tokio::test]
 pub async fn perf_tester() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp("block_perf_tester");
     let n = 1000;
     let port = common::read_secure_signer_port();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -187,6 +376,7 @@ pub async fn perf_tester() {
 
 #[tokio::test]
 async fn test_block_eth2_specs() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_block_eth2_specs");
     let path: PathBuf = [eth_specs::BASE_DIR, "BeaconBlock"].iter().collect();
     dbg!(&path);
     let msgs = eth_specs::get_all_test_vecs("BeaconBlock").unwrap();
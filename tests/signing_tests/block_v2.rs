@@ -28,7 +28,6 @@ pub fn mock_propose_block_v2_request(slot: u64) -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
              }},
-             "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
             "beacon_block": {{
                 "version": "BELLATRIX",
                 "block_header": {{
@@ -57,6 +56,8 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_v2_happy_path() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_aggregate_block_v2_happy_path");
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -68,6 +69,9 @@ pub async fn test_aggregate_block_v2_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_v2_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_block_v2_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("b0eb25ae2c2df6f3089953596341912ec3137457088c5ba57be9f326a647b9e60a931a0971e68f27c1bbe6d5a100c58e0518691357c047851fc2db686d681c65acc3000d218c64f036fbf68d028d840e775d805ccadba4f6fcf1b099bcd63117".to_string());
     let req = block_proposal_request(START_SLOT);
@@ -83,6 +87,9 @@ pub async fn test_aggregate_block_v2_happy_path_test_vec() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_v2_slash_protection_allows_increasing_slot() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_block_v2_slash_protection_allows_increasing_slot",
+    );
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -101,6 +108,9 @@ pub async fn test_aggregate_block_v2_slash_protection_allows_increasing_slot() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_slash_protection_prevents_duplicate_slot() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_block_slash_protection_prevents_duplicate_slot",
+    );
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -119,6 +129,9 @@ pub async fn test_aggregate_block_slash_protection_prevents_duplicate_slot() {
 
 #[tokio::test]
 pub async fn test_aggregate_block_slash_protection_prevents_decreasing_slot() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_block_slash_protection_prevents_decreasing_slot",
+    );
     let port = common::read_secure_signer_port();
     let req = block_proposal_request(START_SLOT);
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -135,6 +148,110 @@ pub async fn test_aggregate_block_slash_protection_prevents_decreasing_slot() {
     assert_eq!(status, 412);
 }
 
+fn full_phase0_block_request(voluntary_exits: usize) -> String {
+    let exit = r#"{
+        "message": {"epoch": "1", "validator_index": "2"},
+        "signature": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+    }"#;
+    format!(
+        r#"
+        {{
+            "type": "BLOCK_V2",
+            "beacon_block": {{
+                "version": "PHASE0",
+                "block": {{
+                    "slot": "{START_SLOT}",
+                    "proposer_index": "0",
+                    "parent_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "state_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "body": {{
+                        "randao_reveal": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                        "eth1_data": {{
+                            "deposit_root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                            "deposit_count": "0",
+                            "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                        }},
+                        "graffiti": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "proposer_slashings": [],
+                        "attester_slashings": [],
+                        "attestations": [],
+                        "deposits": [],
+                        "voluntary_exits": [{}]
+                    }}
+                }}
+            }}
+        }}"#,
+        vec![exit; voluntary_exits].join(",")
+    )
+}
+
+#[tokio::test]
+async fn test_block_v2_refuses_lists_past_their_ssz_limit() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_block_v2_refuses_lists_past_their_ssz_limit",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = mock_secure_sign_route_raw(&bls_pk_hex, full_phase0_block_request(16))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    let resp = mock_secure_sign_route_raw(&bls_pk_hex, full_phase0_block_request(17))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp
+        .text()
+        .contains("voluntary_exits exceeds its SSZ list limit of 16"));
+
+    // Oversized bodies are refused before they are deserialized
+    let padding = " ".repeat(puffersecuresigner::constants::DEFAULT_MAX_SIGN_BODY_BYTES);
+    let resp = mock_secure_sign_route_raw(&bls_pk_hex, full_phase0_block_request(1) + &padding)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 413);
+}
+
+/// A BLOCK_V2 request as Lighthouse's Web3Signer client serializes it, quantities as decimal strings
+fn lighthouse_block_v2_request(slot: &str, proposer_index: &str) -> String {
+    format!(
+        r#"{{"type":"BLOCK_V2","fork_info":{{"fork":{{"previous_version":"0x02000000","current_version":"0x03000000","epoch":"194048"}},"genesis_validators_root":"0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"}},"signingRoot":"0xc332c9e0b5b42259dccfa37cea73df1d0713bb2fcc8b1054e6730a3bcb26162d","beacon_block":{{"version":"CAPELLA","block_header":{{"slot":"{slot}","proposer_index":"{proposer_index}","parent_root":"0xb8a3a3b2e33d2b1a4ee7c1b2f5c3b1c9d0f7c3d1b2a9e8f7c6d5b4a3928170ff","state_root":"0x2c8f8b7f1b3e6e1d0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b9c8d7e","body_root":"0x6f0e1c2d3b4a59687766554433221100ffeeddccbbaa99887766554433221100"}}}}}}"#
+    )
+}
+
+#[tokio::test]
+async fn test_block_v2_accepts_lighthouse_decimal_and_hex_quantities() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_block_v2_accepts_lighthouse_decimal_and_hex_quantities",
+    );
+    // The signingRoot only matches if slot 7000001 and proposer 421337 are parsed as such
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = mock_secure_sign_route_raw(
+        &bls_pk_hex,
+        lighthouse_block_v2_request("7000001", "421337"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let resp = mock_secure_sign_route_raw(
+        &bls_pk_hex,
+        lighthouse_block_v2_request("0x6acfc1", "0x66dd9"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    let resp = mock_secure_sign_route_raw(
+        &bls_pk_hex,
+        lighthouse_block_v2_request("7000002", "421337"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
+
 async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
     for i in 1..n {
         let req = block_proposal_request(i);
@@ -147,6 +264,7 @@ async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
 
 #[tokio::test]
 pub async fn perf_tester() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp("block_v2_perf_tester");
     let n = 1000;
     let port = common::read_secure_signer_port();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -155,6 +273,8 @@ pub async fn perf_tester() {
 
 #[tokio::test]
 async fn test_block_v_eth2_specs() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_block_v_eth2_specs");
     let path: PathBuf = [eth_specs::BASE_DIR, "BeaconBlockHeader"].iter().collect();
     dbg!(&path);
     let msgs = eth_specs::get_all_test_vecs("BeaconBlockHeader").unwrap();
@@ -190,3 +310,18 @@ async fn test_block_v_eth2_specs() {
         }
     }
 }
+
+#[test]
+fn test_block_v2_signing_root_known_answer() {
+    // Expected roots computed independently with the consensus-specs `compute_signing_root`
+    // reference algorithm over DOMAIN_BEACON_PROPOSER. Slot 1234 (epoch 38) precedes the fork
+    // epoch 750 so it must use previous_version; slot 24000 (epoch 750) uses current_version.
+    assert_eq!(
+        hex::encode(block_proposal_request(START_SLOT).to_signing_root(None)),
+        "2cb9062ddea21df33f2b65689688c374d8e1d640ef6491d71074a8df830050b7"
+    );
+    assert_eq!(
+        hex::encode(block_proposal_request(750 * SLOTS_PER_EPOCH).to_signing_root(None)),
+        "2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a"
+    );
+}
@@ -0,0 +1,104 @@
+use crate::common;
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::common::signing_helper::*;
+use puffersecuresigner::eth2::eth_signing::*;
+use puffersecuresigner::eth2::eth_types::*;
+
+fn bls_to_execution_change_request() -> BLSSignMsg {
+    // Create a BLSToExecutionChangeRequest
+    let req = mock_bls_to_execution_change_request();
+    let signing_data: BLSToExecutionChangeRequest =
+        serde_json::from_str(&req).expect("Failed to serialize mock BLSToExecutionChangeRequest");
+    BLSSignMsg::BLS_TO_EXECUTION_CHANGE(signing_data)
+}
+
+pub fn mock_bls_to_execution_change_request() -> String {
+    let req = format!(
+        r#"
+        {{
+            "type": "BLS_TO_EXECUTION_CHANGE",
+            "fork_info":{{
+                "fork":{{
+                   "previous_version":"0x02000000",
+                   "current_version":"0x03000000",
+                   "epoch":"194048"
+                }},
+                "genesis_validators_root":"0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            }},
+            "bls_to_execution_change": {{
+                "validator_index": "1",
+                "from_bls_pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18",
+                "to_execution_address": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
+            }}
+        }}"#
+    );
+    req
+}
+
+#[test]
+fn test_bls_to_execution_change_signing_root_known_answer() {
+    // Expected root computed independently with the consensus-specs `compute_signing_root`
+    // reference algorithm over the mainnet DOMAIN_BLS_TO_EXECUTION_CHANGE
+    // (0x0a000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66).
+    let req = bls_to_execution_change_request();
+    assert_eq!(
+        hex::encode(req.to_signing_root(Some(GENESIS_FORK_VERSION))),
+        "57b7848bf90ce5f165e400bb6a62cb7d92b11430daeffba91d093cb098fce18b"
+    );
+}
+
+#[test]
+fn test_bls_to_execution_change_ignores_current_fork() {
+    // The fork_info's current_version must not leak into the domain
+    let req = bls_to_execution_change_request();
+    assert_ne!(
+        hex::encode(req.to_signing_root(Some(GENESIS_FORK_VERSION))),
+        // the root had the Capella fork version (0x03000000) been used
+        "cd5dcacf42c0fc65543ed8ee898f9cb964275a4169c505b86be8a39de6622bcd"
+    );
+    assert!(!req.can_be_slashed());
+}
+
+#[tokio::test]
+async fn test_bls_to_execution_change_fails_from_invalid_pk_hex() {
+    let port = common::read_secure_signer_port();
+    let req = bls_to_execution_change_request();
+    let bls_pk_hex = "0xdeadbeef".to_string();
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_bls_to_execution_change_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_bls_to_execution_change_happy_path",
+    );
+    let port = common::read_secure_signer_port();
+    let req = bls_to_execution_change_request();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_bls_to_execution_change_returns_signed_message() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_bls_to_execution_change_returns_signed_message",
+    );
+    let port = None;
+    let req = bls_to_execution_change_request();
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let resp = resp.unwrap();
+    let signed = resp.signed_bls_to_execution_change.clone().unwrap();
+    assert_eq!(signed.message.validator_index, 1);
+    assert_eq!(signed.message.to_execution_address[..], [42_u8; 20]);
+    assert_eq!(signed.signature, resp.to_ssz_bytes().unwrap());
+}
@@ -5,6 +5,7 @@ use puffersecuresigner::eth2::eth_signing::*;
 use puffersecuresigner::eth2::eth_types::*;
 use puffersecuresigner::strip_0x_prefix;
 use std::path::PathBuf;
+use tree_hash::TreeHash;
 
 fn sync_committee_contribution_and_proof_request() -> BLSSignMsg {
     // Create a SyncCommitteeContributionAndProofRequest
@@ -30,7 +31,7 @@ fn mock_sync_committee_contribution_and_proof_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0xf6f70c45db83110f2b967b4498cee380bcf3f4fb27e9d65251f2c08f81f2e9fa",
             "contribution_and_proof": {{
                 "aggregator_index": "123123",
                 "selection_proof": "0x8209b5391cd69f392b1f02dbc03bab61f574bb6bb54bf87b59e2a85bdc0756f7db6a71ce1b41b727a1f46ccc77b213bf0df1426177b5b29926b39956114421eaa36ec4602969f6f6370a44de44a6bce6dae2136e5fb594cce2a476354264d1ea",
@@ -60,6 +61,9 @@ async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 async fn test_aggregate_sync_committee_contribution_and_proof_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_sync_committee_contribution_and_proof_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = sync_committee_contribution_and_proof_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -71,6 +75,9 @@ async fn test_aggregate_sync_committee_contribution_and_proof_happy_path() {
 
 #[tokio::test]
 async fn test_aggregate_sync_committee_contribution_and_proof_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_sync_committee_contribution_and_proof_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("ae7248f762bf491101f3621bb0b1c85dd2264cdec4ebfcc4774c41d41229123728046722e16cf676742a1ac32b1d3d7611042c5d0e5b813d8c71477ccd2e1a4264a66eb3eb3d58b68641c592f210650c0e182357acf1dde03be8fda1011377b3".to_string());
     let req = sync_committee_contribution_and_proof_request();
@@ -86,6 +93,9 @@ async fn test_aggregate_sync_committee_contribution_and_proof_happy_path_test_ve
 
 #[tokio::test]
 async fn test_sync_committee_contribution_eth2_specs() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sync_committee_contribution_eth2_specs",
+    );
     let path: PathBuf = [eth_specs::BASE_DIR, "ContributionAndProof"]
         .iter()
         .collect();
@@ -107,3 +117,51 @@ async fn test_sync_committee_contribution_eth2_specs() {
         assert_eq!(status, 200);
     }
 }
+
+#[test]
+fn test_contribution_and_proof_hash_tree_root_fixture() {
+    // Expected roots computed independently with the consensus-specs SSZ reference merkleization.
+    // Bits 0 and 127 are set to exercise the Bitvector[128] packing.
+    let req = mock_sync_committee_contribution_and_proof_request().replace(
+        r#""aggregation_bits": "0x00000000000000000000000000000000""#,
+        r#""aggregation_bits": "0x01000000000000000000000000000080""#,
+    );
+    let req: SyncCommitteeContributionAndProofRequest = serde_json::from_str(&req).unwrap();
+    let contribution_and_proof = req.contribution_and_proof.clone();
+    assert!(contribution_and_proof
+        .contribution
+        .aggregation_bits
+        .get(0)
+        .unwrap());
+    assert!(contribution_and_proof
+        .contribution
+        .aggregation_bits
+        .get(127)
+        .unwrap());
+    assert_eq!(
+        contribution_and_proof
+            .contribution
+            .aggregation_bits
+            .num_set_bits(),
+        2
+    );
+    assert_eq!(
+        hex::encode(contribution_and_proof.contribution.tree_hash_root()),
+        "4de8ff8d4bf669a6010ddfb760b94f1b7b5988bdfcb7f5bb4adac495394f1de3"
+    );
+    assert_eq!(
+        hex::encode(contribution_and_proof.tree_hash_root()),
+        "43d98fcb37df23cd056275cf127e900206b708ece496255ddb3458bd91afb4e2"
+    );
+    assert_eq!(
+        hex::encode(BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(req).to_signing_root(None)),
+        "b853a8ce724e26fa2ebf43fa0ce3450523b08916490b9bfcb6b6466b1825181f"
+    );
+
+    // The mock request with no participation bits
+    let req = sync_committee_contribution_and_proof_request();
+    assert_eq!(
+        hex::encode(req.to_signing_root(None)),
+        "f6f70c45db83110f2b967b4498cee380bcf3f4fb27e9d65251f2c08f81f2e9fa"
+    );
+}
@@ -45,6 +45,8 @@ async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 async fn test_aggregate_deposit_happy_path() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_aggregate_deposit_happy_path");
     let port = common::read_secure_signer_port();
     let req = deposit_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -56,6 +58,9 @@ async fn test_aggregate_deposit_happy_path() {
 
 #[tokio::test]
 async fn test_aggregate_deposit_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_deposit_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("82cc787865c0fb7147fe7350dd5a71f5d92c6a1771eb951826f6b339a319e1904a2310d5d3cbc5e2d0e5f35f2bfe6da5164c33114663222d4238a43d495876dae873dc6af338c4af4f6dbe1ae181331581bdcd353509a2356977b6625c9ab0e5".to_string());
     let req = deposit_request();
@@ -69,8 +74,43 @@ async fn test_aggregate_deposit_happy_path_test_vec() {
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
 
+#[tokio::test]
+async fn test_deposit_returns_deposit_data_root() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_deposit_returns_deposit_data_root",
+    );
+    let port = None;
+    let req = deposit_request();
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let resp = resp.unwrap();
+    // Expected root computed independently over the DepositData with the signature above
+    assert_eq!(
+        resp.deposit_data_root.unwrap(),
+        "0x7617557bab5904a9247e2e48eaf414b8c6dbf0dcd13f5983121979deb096e998"
+    );
+}
+
+#[test]
+fn test_deposit_domain_ignores_genesis_validators_root() {
+    // The deposit domain only depends on the request's genesis_fork_version
+    let req = deposit_request();
+    let exp_root = "403d5c444d19c6a375b640640283378afc25ffcfb958585f3a33408b388b74b3";
+    assert_eq!(hex::encode(req.to_signing_root(None)), exp_root);
+    assert_eq!(
+        hex::encode(req.to_signing_root(Some([1, 2, 3, 4]))),
+        exp_root
+    );
+}
+
 #[tokio::test]
 async fn test_sync_committee_message_eth2_specs() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sync_committee_message_eth2_specs",
+    );
     let path: PathBuf = [eth_specs::BASE_DIR, "DepositMessage"].iter().collect();
     dbg!(&path);
     let port = common::read_secure_signer_port();
@@ -3,9 +3,11 @@ pub mod aggregation_slot;
 pub mod attestation;
 pub mod block;
 pub mod block_v2;
+pub mod bls_to_execution_change;
 pub mod contribution_and_proof;
 pub mod deposit;
 pub mod randao_reveal;
 pub mod sync_committee_message;
 pub mod sync_committee_selection_proof;
 pub mod validator_registration;
+pub mod voluntary_exit;
@@ -25,7 +25,7 @@ pub fn mock_randao_reveal_request() -> String {
               }},
               "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
            }},
-           "signingRoot": "0xbf70dbbbc83299fb877334eaeaefb32df44242c1bf078cdc1836dcc3282d4fbd",
+           "signingRoot": "0x66ab8ee49a2b08e5603e2f94cfd7ff3db0f68877e2b57b99720d41b22c94be2e",
            "randao_reveal":{{
                 "epoch": "10"
            }}
@@ -44,8 +44,23 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
     assert_eq!(status, 400);
 }
 
+#[tokio::test]
+pub async fn test_aggregate_route_fails_from_unknown_pk_hex() {
+    let port = common::read_secure_signer_port();
+    let req = randao_reveal_request();
+    let bls_pk_hex = blsttc::SecretKey::random().public_key().to_hex();
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 404);
+    assert!(resp.is_none());
+}
+
 #[tokio::test]
 pub async fn test_aggregate_randao_reveal_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_randao_reveal_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = randao_reveal_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -57,6 +72,9 @@ pub async fn test_aggregate_randao_reveal_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_randao_reveal_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_randao_reveal_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("a8c5eb481ef1c3ea345bc9cb9ce9918e18ef052d8287bacd3b1e1bbd34bc4e1e016602b778535d5b582bc35ea6d2ded106ea2cfec06f8b6c5bd049dbf0a544207ac3b21c634b8e78c2c0135a0000e961adae192203ef168de1edb83618d1a76d".to_string());
     let req = randao_reveal_request();
@@ -69,3 +87,49 @@ pub async fn test_aggregate_randao_reveal_happy_path_test_vec() {
     let got_sig: String = strip_0x_prefix!(sig);
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
+
+#[tokio::test]
+async fn test_randao_reveal_lookahead_is_bounded_by_last_block() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_randao_reveal_lookahead_is_bounded_by_last_block",
+    );
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let mut state = mock_app_state();
+    state.max_randao_lookahead = Some(2);
+
+    let reveal = |epoch| {
+        let mut req = randao_reveal_request();
+        if let BLSSignMsg::RANDAO_REVEAL(m) = &mut req {
+            m.signingRoot = None;
+            m.randao_reveal.epoch = epoch;
+        }
+        req
+    };
+    let sign = |req| {
+        mock_secure_sign_route_with_state(&bls_pk_hex, req, Some("application/json"), state.clone())
+    };
+
+    // Nothing to compare against before the key signs a block
+    assert_eq!(sign(reveal(1000)).await.unwrap().status_code(), 200);
+
+    // Slot 1234 is in epoch 38
+    let block = crate::signing_tests::block::mock_propose_block_request(1234);
+    let resp = sign(serde_json::from_str(&block).unwrap()).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(sign(reveal(40)).await.unwrap().status_code(), 200);
+    assert_eq!(sign(reveal(41)).await.unwrap().status_code(), 412);
+
+    let resp =
+        crate::common::slashing_protection_status_helper::mock_slashing_protection_status_route(
+            &format!("/eth/v1/slashing-protection/0x{}", bls_pk_hex),
+            state.clone(),
+        )
+        .await
+        .unwrap();
+    let data = serde_json::from_slice::<
+        puffersecuresigner::enclave::types::SlashingProtectionStatusResponse,
+    >(resp.as_bytes())
+    .unwrap()
+    .data;
+    assert_eq!(data.highest_randao_reveal_epoch, Some("1000".to_string()));
+}
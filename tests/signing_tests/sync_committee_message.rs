@@ -29,7 +29,7 @@ pub fn mock_sync_committee_message_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0x98bd7ac851ee91b3562a0ae3ab899d8bf9833900fea6d34c7dbed645dea80f0e",
             "sync_committee_message": {{
                 "slot": "123123",
                 "beacon_block_root": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a"
@@ -52,6 +52,9 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 pub async fn test_aggregate_sync_committee_message_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_sync_committee_message_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = sync_committee_message_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -61,8 +64,47 @@ pub async fn test_aggregate_sync_committee_message_happy_path() {
     assert_eq!(status, 200);
 }
 
+#[tokio::test]
+pub async fn test_sync_committee_messages_for_same_slot_both_succeed() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sync_committee_messages_for_same_slot_both_succeed",
+    );
+    let port = common::read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // Sync committee messages are not slashable so they bypass the watermark checks
+    let req = sync_committee_message_request();
+    assert!(!req.can_be_slashed());
+    let (first, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    let req = sync_committee_message_request();
+    let (second, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(first.unwrap().signature, second.unwrap().signature);
+
+    // A different block root for the same slot is also signed
+    let req = mock_sync_committee_message_request().replace(
+        r#""beacon_block_root": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a""#,
+        r#""beacon_block_root": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a""#,
+    );
+    let req: SyncCommitteeMessageRequest = serde_json::from_str(&req).unwrap();
+    let (_resp, status) =
+        make_signing_route_request(BLSSignMsg::SYNC_COMMITTEE_MESSAGE(req), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 200);
+}
+
 #[tokio::test]
 pub async fn test_aggregate_sync_committee_message_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_sync_committee_message_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("8b3c0f3cb3427a6009ee7d2f6691480fcf93d21fc7231d333b0bf997e7fe147f0700e61f4790246ce5650a8510374f3d0d14286e41943a80f30dd9cfc197155f0e8cd4f4ced1f1f2b37214fa146640f59f0b7d59cf61980166287083936eea30".to_string());
     let req = sync_committee_message_request();
@@ -78,6 +120,8 @@ pub async fn test_aggregate_sync_committee_message_happy_path_test_vec() {
 
 #[tokio::test]
 async fn test_sync_committee_eth2_specs() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_sync_committee_eth2_specs");
     let path: PathBuf = [eth_specs::BASE_DIR, "SyncCommitteeMessage"]
         .iter()
         .collect();
@@ -29,7 +29,7 @@ pub fn mock_sync_committee_selection_proof_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0x52d4a281e039ef8ee7f649960356d2783632c3039f411681ba768518465b615f",
             "sync_aggregator_selection_data": {{
                 "slot": "123123",
                 "subcommittee_index": "12345"
@@ -52,6 +52,9 @@ pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 pub async fn test_aggregate_sync_committee_selection_proof_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_sync_committee_selection_proof_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = sync_committee_selection_proof_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -63,6 +66,9 @@ pub async fn test_aggregate_sync_committee_selection_proof_happy_path() {
 
 #[tokio::test]
 pub async fn test_aggregate_sync_committee_selection_proof_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_sync_committee_selection_proof_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("84b81f509f9ffb74439a0c862aaafbcb7c6a406bddcb7d5c30b668153a8d86b7a10425bf9e04254ae22e1c9f3dbd5fbe172014c74ee17984e0a90dad03ed31597aabc8d00a78af41f9696aa017f65306154f2dd51f669f12155b7de0269881c0".to_string());
     let req = sync_committee_selection_proof_request();
@@ -76,8 +82,42 @@ pub async fn test_aggregate_sync_committee_selection_proof_happy_path_test_vec()
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
 
+#[test]
+fn test_sync_committee_selection_proof_signing_root_known_answer() {
+    // Expected root computed independently with the consensus-specs `compute_signing_root`
+    // reference algorithm over DOMAIN_SYNC_COMMITTEE_SELECTION_PROOF.
+    let req = sync_committee_selection_proof_request();
+    assert!(!req.can_be_slashed());
+    assert_eq!(
+        hex::encode(req.to_signing_root(None)),
+        "52d4a281e039ef8ee7f649960356d2783632c3039f411681ba768518465b615f"
+    );
+}
+
+#[test]
+fn test_sync_committee_selection_proof_accepts_web3signer_json() {
+    // The request type is dispatched on the `type` field in either case
+    for t in [
+        "SYNC_COMMITTEE_SELECTION_PROOF",
+        "sync_committee_selection_proof",
+    ] {
+        let req = mock_sync_committee_selection_proof_request().replace(
+            r#""type": "SYNC_COMMITTEE_SELECTION_PROOF""#,
+            &format!(r#""type": "{t}""#),
+        );
+        let msg: BLSSignMsg = serde_json::from_str(&req).unwrap();
+        assert_eq!(
+            hex::encode(msg.to_signing_root(None)),
+            "52d4a281e039ef8ee7f649960356d2783632c3039f411681ba768518465b615f"
+        );
+    }
+}
+
 #[tokio::test]
 async fn test_sync_committee_committee_selection_proof_eth2_specs() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_sync_committee_committee_selection_proof_eth2_specs",
+    );
     let path: PathBuf = [eth_specs::BASE_DIR, "SyncAggregatorSelectionData"]
         .iter()
         .collect();
@@ -43,6 +43,9 @@ async fn test_aggregate_route_fails_from_invalid_pk_hex() {
 
 #[tokio::test]
 async fn test_aggregate_validator_registration_happy_path() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_validator_registration_happy_path",
+    );
     let port = common::read_secure_signer_port();
     let req = validator_registration_request();
     let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
@@ -54,6 +57,9 @@ async fn test_aggregate_validator_registration_happy_path() {
 
 #[tokio::test]
 async fn test_aggregate_validator_registration_happy_path_test_vec() {
+    let _data_dir = puffersecuresigner::io::data_dir::ScopedDataDir::temp(
+        "test_aggregate_validator_registration_happy_path_test_vec",
+    );
     let port = None;
     let exp_sig = Some("8dc27307e86e464e1eb09247a127cf728df3bdf38bc6871a909a955da178ace5ad3b9087013b0bd24d8af57fb4e5f90f103d200a3e06b4cd56fa780bceac878425de9415f3f947cb279ef9f83141a4c7757100cba5314ac1c0f3dc9b1d92efd5".to_string());
     let req = validator_registration_request();
@@ -66,3 +72,14 @@ async fn test_aggregate_validator_registration_happy_path_test_vec() {
     let got_sig: String = strip_0x_prefix!(sig);
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
+
+#[test]
+fn test_validator_registration_signing_root_known_answer() {
+    // Root from Web3Signer's VALIDATOR_REGISTRATION test vector: DOMAIN_APPLICATION_BUILDER
+    // over the genesis fork version with a zero genesis_validators_root.
+    let req = validator_registration_request();
+    assert_eq!(
+        hex::encode(req.to_signing_root(Some(GENESIS_FORK_VERSION))),
+        "139d59dbb1770fdc582ff75193720352ccc76131e37ac69d0c10e7416f3f3050"
+    );
+}
@@ -0,0 +1,87 @@
+use crate::common;
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::common::signing_helper::*;
+use puffersecuresigner::eth2::eth_signing::*;
+use puffersecuresigner::eth2::eth_types::*;
+
+fn voluntary_exit_request() -> BLSSignMsg {
+    // Create a VoluntaryExitRequest
+    let req = mock_voluntary_exit_request();
+    let signing_data: VoluntaryExitRequest =
+        serde_json::from_str(&req).expect("Failed to serialize mock VoluntaryExitRequest");
+    BLSSignMsg::VOLUNTARY_EXIT(signing_data)
+}
+
+pub fn mock_voluntary_exit_request() -> String {
+    let req = format!(
+        r#"
+        {{
+            "type": "VOLUNTARY_EXIT",
+            "fork_info":{{
+                "fork":{{
+                   "previous_version":"0x00000001",
+                   "current_version":"0x00000001",
+                   "epoch":"0"
+                }},
+                "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+            }},
+            "signingRoot": "0x4bbce1765da1cb24e1c03a8f847b3654cd12d05d61be9442e23e3cd33f94c474",
+            "voluntary_exit": {{
+                "epoch": "119",
+                "validator_index": "0"
+            }}
+        }}"#
+    );
+    req
+}
+
+#[tokio::test]
+async fn test_voluntary_exit_route_fails_from_invalid_pk_hex() {
+    let port = common::read_secure_signer_port();
+    let req = voluntary_exit_request();
+    let bls_pk_hex = "0xdeadbeef".to_string();
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_voluntary_exit_happy_path() {
+    let _data_dir =
+        puffersecuresigner::io::data_dir::ScopedDataDir::temp("test_voluntary_exit_happy_path");
+    let port = common::read_secure_signer_port();
+    let req = voluntary_exit_request();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn test_voluntary_exit_signing_root_known_answer() {
+    // Expected root computed independently with the consensus-specs `compute_signing_root`
+    // reference algorithm over DOMAIN_VOLUNTARY_EXIT.
+    let req = voluntary_exit_request();
+    let got = req.to_signing_root(None);
+    assert_eq!(
+        hex::encode(got),
+        "4bbce1765da1cb24e1c03a8f847b3654cd12d05d61be9442e23e3cd33f94c474"
+    );
+
+    // The domain must not depend on the genesis_fork_version supplied to the signer
+    let req = voluntary_exit_request();
+    assert_eq!(req.to_signing_root(Some([1, 2, 3, 4])), got);
+}
+
+#[test]
+fn test_voluntary_exit_epoch_is_hashed() {
+    let req = mock_voluntary_exit_request().replace(r#""epoch": "119""#, r#""epoch": "120""#);
+    let req: VoluntaryExitRequest = serde_json::from_str(&req).unwrap();
+    let root = BLSSignMsg::VOLUNTARY_EXIT(req).to_signing_root(None);
+    assert_ne!(
+        hex::encode(root),
+        "4bbce1765da1cb24e1c03a8f847b3654cd12d05d61be9442e23e3cd33f94c474"
+    );
+}